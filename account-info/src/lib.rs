@@ -68,6 +68,7 @@ impl fmt::Debug for AccountInfo<'_> {
 }
 
 impl<'a> AccountInfo<'a> {
+    #[inline(always)]
     pub fn signer_key(&self) -> Option<&Address> {
         if self.is_signer {
             Some(self.key)
@@ -76,14 +77,17 @@ impl<'a> AccountInfo<'a> {
         }
     }
 
+    #[inline(always)]
     pub fn unsigned_key(&self) -> &Address {
         self.key
     }
 
+    #[inline(always)]
     pub fn lamports(&self) -> u64 {
         **self.lamports.borrow()
     }
 
+    #[inline(always)]
     pub fn try_lamports(&self) -> Result<u64, ProgramError> {
         Ok(**self.try_borrow_lamports()?)
     }
@@ -101,46 +105,106 @@ impl<'a> AccountInfo<'a> {
         *original_data_len_ptr as usize
     }
 
+    #[inline(always)]
     pub fn data_len(&self) -> usize {
         self.data.borrow().len()
     }
 
+    #[inline(always)]
     pub fn try_data_len(&self) -> Result<usize, ProgramError> {
         Ok(self.try_borrow_data()?.len())
     }
 
+    #[inline(always)]
     pub fn data_is_empty(&self) -> bool {
         self.data.borrow().is_empty()
     }
 
+    #[inline(always)]
     pub fn try_data_is_empty(&self) -> Result<bool, ProgramError> {
         Ok(self.try_borrow_data()?.is_empty())
     }
 
+    #[inline(always)]
     pub fn try_borrow_lamports(&self) -> Result<Ref<'_, &mut u64>, ProgramError> {
         self.lamports
             .try_borrow()
             .map_err(|_| ProgramError::AccountBorrowFailed)
     }
 
+    #[inline(always)]
     pub fn try_borrow_mut_lamports(&self) -> Result<RefMut<'_, &'a mut u64>, ProgramError> {
         self.lamports
             .try_borrow_mut()
             .map_err(|_| ProgramError::AccountBorrowFailed)
     }
 
+    #[inline(always)]
     pub fn try_borrow_data(&self) -> Result<Ref<'_, &mut [u8]>, ProgramError> {
         self.data
             .try_borrow()
             .map_err(|_| ProgramError::AccountBorrowFailed)
     }
 
+    #[inline(always)]
     pub fn try_borrow_mut_data(&self) -> Result<RefMut<'_, &'a mut [u8]>, ProgramError> {
         self.data
             .try_borrow_mut()
             .map_err(|_| ProgramError::AccountBorrowFailed)
     }
 
+    /// Returns the memory address of the account data.
+    ///
+    /// The address is read from the `&mut [u8]` stored in the `data`
+    /// `RefCell`, without going through its borrow flag. `&mut [u8]` and
+    /// `*mut [u8]` share the same layout, so the slice pointer is copied out
+    /// of the cell without creating a reference to it.
+    ///
+    /// # Safety
+    ///
+    /// The data must not be mutably borrowed through [`Self::try_borrow_mut_data`]
+    /// while this method is called, since the returned `RefMut` may replace
+    /// the slice, as [`Self::resize`] does.
+    ///
+    /// De-referencing the pointer requires the caller to uphold Rust's
+    /// aliasing rules. It is undefined behavior to de-reference the pointer or
+    /// write through it while any safe reference (e.g., from any of the
+    /// `try_borrow_data` or `try_borrow_mut_data` methods) to the same data is
+    /// still alive.
+    #[inline(always)]
+    pub unsafe fn data_ptr(&self) -> *mut u8 {
+        self.data_slice_ptr() as *mut u8
+    }
+
+    /// Returns an immutable reference to the data in the account.
+    ///
+    /// # Safety
+    ///
+    /// This method is unsafe because it does not return a `Ref`, thus leaving the borrow
+    /// flag untouched. Useful when an instruction has verified non-duplicate accounts.
+    #[inline(always)]
+    pub unsafe fn borrow_data_unchecked(&self) -> &[u8] {
+        &*self.data_slice_ptr()
+    }
+
+    /// Returns a mutable reference to the data in the account.
+    ///
+    /// # Safety
+    ///
+    /// This method is unsafe because it does not return a `RefMut`, thus leaving the borrow
+    /// flag untouched. Useful when an instruction has verified non-duplicate accounts.
+    #[allow(clippy::mut_from_ref)]
+    #[inline(always)]
+    pub unsafe fn borrow_data_unchecked_mut(&self) -> &mut [u8] {
+        &mut *self.data_slice_ptr()
+    }
+
+    /// The slice pointer stored in the `data` `RefCell`.
+    #[inline(always)]
+    unsafe fn data_slice_ptr(&self) -> *mut [u8] {
+        self.data.as_ptr().cast::<*mut [u8]>().read()
+    }
+
     /// Resize the account's data: Either truncating or zero extending.
     ///
     /// Note:  Account data can be increased within a single call by up to
@@ -472,8 +536,8 @@ pub fn check_type_assumptions() {
 mod tests {
     use {
         super::*,
-        crate::debug_account_data::{Hex, MAX_DEBUG_ACCOUNT_DATA},
-        alloc::{format, vec},
+        crate::debug_account_data::MAX_DEBUG_ACCOUNT_DATA,
+        alloc::{format, string::String, vec},
     };
 
     #[test]
@@ -522,12 +586,53 @@ mod tests {
         assert_eq!(info.key, info.as_ref().key);
     }
 
+    #[test]
+    fn test_account_info_unchecked_data() {
+        let key = Address::new_unique();
+        let mut lamports = 42;
+        let mut data: vec::Vec<u8> = (0..80).collect();
+        let info = AccountInfo::new(&key, false, true, &mut lamports, &mut data, &key, false);
+
+        unsafe {
+            assert_eq!(
+                info.data_ptr(),
+                info.try_borrow_mut_data().unwrap().as_mut_ptr()
+            );
+            assert_eq!(
+                info.borrow_data_unchecked(),
+                &**info.try_borrow_data().unwrap()
+            );
+
+            // the borrow flag is left untouched
+            let data = info.borrow_data_unchecked_mut();
+            assert!(info.try_borrow_mut_data().is_ok());
+            data[0] = 0xff;
+            *info.data_ptr().add(1) = 0xfe;
+        }
+        let data = info.try_borrow_data().unwrap();
+        assert_eq!(data[..3], [0xff, 0xfe, 2]);
+        unsafe {
+            assert_eq!(info.borrow_data_unchecked(), &**data);
+        }
+        drop(data);
+
+        let mut empty: [u8; 0] = [];
+        let info = AccountInfo::new(&key, false, true, &mut lamports, &mut empty, &key, false);
+        unsafe {
+            assert!(info.borrow_data_unchecked().is_empty());
+            assert!(info.borrow_data_unchecked_mut().is_empty());
+        }
+    }
+
     #[test]
     fn test_account_info_debug_data() {
         let key = Address::new_unique();
         let mut lamports = 42;
         let mut data = vec![5; 80];
-        let data_str = format!("{:?}", Hex(&data[..MAX_DEBUG_ACCOUNT_DATA]));
+        let data_str: String = data[..MAX_DEBUG_ACCOUNT_DATA]
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect();
         let info = AccountInfo::new(&key, false, false, &mut lamports, &mut data, &key, false);
         assert_eq!(
             format!("{info:?}"),
@@ -553,7 +658,7 @@ mod tests {
         );
 
         let mut data = vec![5; 40];
-        let data_str = format!("{:?}", Hex(&data));
+        let data_str: String = data.iter().map(|byte| format!("{byte:02x}")).collect();
         let info = AccountInfo::new(&key, false, false, &mut lamports, &mut data, &key, false);
         assert_eq!(
             format!("{info:?}"),
@@ -0,0 +1,256 @@
+//! Traits abstracting over [`AccountInfo`] and [`AccountView`].
+//!
+//! Libraries that only need to inspect or modify accounts can accept any
+//! [`AccountRead`] or [`AccountWrite`] implementation instead of forcing
+//! their users onto a single entrypoint style. Data is accessed through
+//! closures, which bridges the different borrow tracking of the two account
+//! types.
+#![no_std]
+#![cfg_attr(docsrs, feature(doc_cfg))]
+
+use {
+    solana_account_info::AccountInfo, solana_account_view::AccountView, solana_address::Address,
+    solana_program_error::ProgramError,
+};
+
+/// Read access to an account.
+pub trait AccountRead {
+    fn address(&self) -> &Address;
+
+    fn lamports(&self) -> u64;
+
+    fn owner_is(&self, owner: &Address) -> bool;
+
+    fn data_len(&self) -> usize;
+
+    fn is_signer(&self) -> bool;
+
+    fn is_writable(&self) -> bool;
+
+    /// Call `f` with the account data, failing with
+    /// [`ProgramError::AccountBorrowFailed`] if the data is mutably borrowed.
+    fn with_data<R>(
+        &self,
+        f: impl FnOnce(&[u8]) -> Result<R, ProgramError>,
+    ) -> Result<R, ProgramError>;
+}
+
+/// Write access to an account.
+pub trait AccountWrite: AccountRead {
+    /// Call `f` with the mutable account data, failing with
+    /// [`ProgramError::AccountBorrowFailed`] if the data is already borrowed.
+    fn with_data_mut<R>(
+        &mut self,
+        f: impl FnOnce(&mut [u8]) -> Result<R, ProgramError>,
+    ) -> Result<R, ProgramError>;
+}
+
+impl AccountRead for AccountInfo<'_> {
+    #[inline]
+    fn address(&self) -> &Address {
+        self.key
+    }
+
+    #[inline]
+    fn lamports(&self) -> u64 {
+        AccountInfo::lamports(self)
+    }
+
+    #[inline]
+    fn owner_is(&self, owner: &Address) -> bool {
+        self.owner == owner
+    }
+
+    #[inline]
+    fn data_len(&self) -> usize {
+        AccountInfo::data_len(self)
+    }
+
+    #[inline]
+    fn is_signer(&self) -> bool {
+        self.is_signer
+    }
+
+    #[inline]
+    fn is_writable(&self) -> bool {
+        self.is_writable
+    }
+
+    #[inline]
+    fn with_data<R>(
+        &self,
+        f: impl FnOnce(&[u8]) -> Result<R, ProgramError>,
+    ) -> Result<R, ProgramError> {
+        f(&self.try_borrow_data()?)
+    }
+}
+
+impl AccountWrite for AccountInfo<'_> {
+    #[inline]
+    fn with_data_mut<R>(
+        &mut self,
+        f: impl FnOnce(&mut [u8]) -> Result<R, ProgramError>,
+    ) -> Result<R, ProgramError> {
+        f(&mut self.try_borrow_mut_data()?)
+    }
+}
+
+impl AccountRead for AccountView {
+    #[inline(always)]
+    fn address(&self) -> &Address {
+        AccountView::address(self)
+    }
+
+    #[inline(always)]
+    fn lamports(&self) -> u64 {
+        AccountView::lamports(self)
+    }
+
+    #[inline(always)]
+    fn owner_is(&self, owner: &Address) -> bool {
+        self.owned_by(owner)
+    }
+
+    #[inline(always)]
+    fn data_len(&self) -> usize {
+        AccountView::data_len(self)
+    }
+
+    #[inline(always)]
+    fn is_signer(&self) -> bool {
+        AccountView::is_signer(self)
+    }
+
+    #[inline(always)]
+    fn is_writable(&self) -> bool {
+        AccountView::is_writable(self)
+    }
+
+    #[inline(always)]
+    fn with_data<R>(
+        &self,
+        f: impl FnOnce(&[u8]) -> Result<R, ProgramError>,
+    ) -> Result<R, ProgramError> {
+        AccountView::with_data_scoped(self, f)?
+    }
+}
+
+impl AccountWrite for AccountView {
+    #[inline(always)]
+    fn with_data_mut<R>(
+        &mut self,
+        f: impl FnOnce(&mut [u8]) -> Result<R, ProgramError>,
+    ) -> Result<R, ProgramError> {
+        AccountView::with_data_mut_scoped(self, f)?
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use {
+        super::*,
+        core::mem::size_of,
+        solana_account_view::{RuntimeAccount, NOT_BORROWED},
+        std::vec,
+    };
+
+    const OWNER: Address = Address::new_from_array([1; 32]);
+    const KEY: Address = Address::new_from_array([2; 32]);
+
+    /// Read a little-endian u64 counter from an account owned by `OWNER`.
+    fn read_counter(account: &impl AccountRead) -> Result<u64, ProgramError> {
+        if !account.owner_is(&OWNER) {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        account.with_data(|data| {
+            data.get(..8)
+                .map(|bytes| u64::from_le_bytes(bytes.try_into().unwrap()))
+                .ok_or(ProgramError::AccountDataTooSmall)
+        })
+    }
+
+    fn increment_counter(account: &mut impl AccountWrite) -> Result<(), ProgramError> {
+        let counter = read_counter(account)?;
+        if !account.is_writable() {
+            return Err(ProgramError::InvalidArgument);
+        }
+        account.with_data_mut(|data| {
+            data[..8].copy_from_slice(&counter.saturating_add(1).to_le_bytes());
+            Ok(())
+        })
+    }
+
+    fn check_account(account: &mut impl AccountWrite) {
+        assert_eq!(account.address(), &KEY);
+        assert_eq!(account.lamports(), 42);
+        assert_eq!(account.data_len(), 8);
+        assert!(account.is_signer());
+        assert!(account.is_writable());
+        assert!(account.owner_is(&OWNER));
+        assert!(!account.owner_is(&KEY));
+
+        assert_eq!(read_counter(account), Ok(0));
+        increment_counter(account).unwrap();
+        increment_counter(account).unwrap();
+        assert_eq!(read_counter(account), Ok(2));
+
+        // errors returned by the closure are propagated
+        assert_eq!(
+            account.with_data(|_| Err::<(), _>(ProgramError::Custom(7))),
+            Err(ProgramError::Custom(7))
+        );
+    }
+
+    #[test]
+    fn test_account_info() {
+        let mut lamports = 42;
+        let mut data = vec![0; 8];
+        let mut account_info =
+            AccountInfo::new(&KEY, true, true, &mut lamports, &mut data, &OWNER, false);
+        check_account(&mut account_info);
+
+        let _borrowed = account_info.try_borrow_data().unwrap();
+        assert_eq!(
+            account_info.clone().with_data_mut(|_| Ok(())),
+            Err(ProgramError::AccountBorrowFailed)
+        );
+        assert_eq!(account_info.with_data(|data| Ok(data.len())), Ok(8));
+    }
+
+    #[test]
+    fn test_account_view() {
+        // 8-bytes aligned account data + 8 bytes of trailing data.
+        let mut data = [0u64; size_of::<RuntimeAccount>() / size_of::<u64>() + 1];
+        let raw = data.as_mut_ptr() as *mut RuntimeAccount;
+        unsafe {
+            (*raw).borrow_state = NOT_BORROWED;
+            (*raw).is_signer = 1;
+            (*raw).is_writable = 1;
+            (*raw).address = KEY;
+            (*raw).owner = OWNER;
+            (*raw).lamports = 42;
+            (*raw).data_len = 8;
+        }
+        let mut account_view = unsafe { AccountView::new_unchecked(raw) };
+        check_account(&mut account_view);
+
+        let mut other_view = unsafe { AccountView::new_unchecked(raw) };
+        let borrowed = account_view.try_borrow().unwrap();
+        assert_eq!(
+            other_view.with_data_mut(|_| Ok(())),
+            Err(ProgramError::AccountBorrowFailed)
+        );
+        assert_eq!(other_view.with_data(|data| Ok(data.len())), Ok(8));
+        drop(borrowed);
+
+        let borrowed = account_view.try_borrow_mut().unwrap();
+        assert_eq!(
+            other_view.with_data(|_| Ok(())),
+            Err(ProgramError::AccountBorrowFailed)
+        );
+        drop(borrowed);
+        assert_eq!(read_counter(&other_view), Ok(2));
+    }
+}
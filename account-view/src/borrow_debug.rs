@@ -0,0 +1,345 @@
+//! Diagnostics for account data borrow failures.
+//!
+//! When a program fails with [`ProgramError::AccountBorrowFailed`], it is
+//! often unclear which earlier borrow is still alive. Every borrow taken and
+//! released through an [`AccountView`] is recorded in a fixed-capacity side
+//! table keyed by the account's address, and [`borrow_state_report`]
+//! describes the outstanding borrows of an account so the program can log it
+//! before returning the error. On non-Solana targets a backtrace is also
+//! captured for every borrow, retrievable with [`borrow_backtraces`].
+//!
+//! The side table holds up to [`MAX_TRACKED_ACCOUNTS`] accounts with live
+//! borrows at a time. Borrows of further accounts are not recorded, so their
+//! report shows no outstanding borrows.
+//!
+//! [`ProgramError::AccountBorrowFailed`]: solana_program_error::ProgramError::AccountBorrowFailed
+
+#[cfg(not(target_os = "solana"))]
+use std::{backtrace::Backtrace, cell::RefCell, collections::VecDeque, string::String, vec::Vec};
+use {
+    crate::{AccountLayout, AccountView},
+    core::fmt,
+};
+
+/// Maximum number of accounts with live borrows tracked at the same time.
+pub const MAX_TRACKED_ACCOUNTS: usize = 64;
+
+/// Maximum number of backtraces kept on non-Solana targets. When full, the
+/// backtrace of the oldest live borrow is dropped.
+#[cfg(not(target_os = "solana"))]
+pub const MAX_BACKTRACES: usize = 64;
+
+/// Outstanding borrows of an account's data.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct BorrowReport {
+    /// Number of live immutable borrows.
+    pub outstanding_shared: u8,
+    /// Whether the data is mutably borrowed.
+    pub mutable: bool,
+}
+
+impl fmt::Display for BorrowReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.mutable {
+            f.write_str("account data is mutably borrowed")
+        } else {
+            write!(
+                f,
+                "account data has {} outstanding immutable borrow(s)",
+                self.outstanding_shared
+            )
+        }
+    }
+}
+
+/// Report the outstanding borrows of `account`'s data.
+#[inline]
+pub fn borrow_state_report<L: AccountLayout>(account: &AccountView<L>) -> BorrowReport {
+    let key = account.borrow_state_ptr() as usize;
+    with_borrow_table(|table| table.report(key))
+}
+
+/// Outstanding borrows of one account, keyed by the address of its borrow
+/// state. A zero key marks a free slot.
+#[derive(Clone, Copy)]
+struct BorrowEntry {
+    key: usize,
+    report: BorrowReport,
+}
+
+struct BorrowTable {
+    entries: [BorrowEntry; MAX_TRACKED_ACCOUNTS],
+}
+
+impl BorrowTable {
+    const fn new() -> Self {
+        Self {
+            entries: [BorrowEntry {
+                key: 0,
+                report: BorrowReport {
+                    outstanding_shared: 0,
+                    mutable: false,
+                },
+            }; MAX_TRACKED_ACCOUNTS],
+        }
+    }
+
+    fn report(&self, key: usize) -> BorrowReport {
+        self.entries
+            .iter()
+            .find(|entry| entry.key == key)
+            .map(|entry| entry.report)
+            .unwrap_or_default()
+    }
+
+    fn record_borrow(&mut self, key: usize, mutable: bool) {
+        let entry = match self.entries.iter().position(|entry| entry.key == key) {
+            Some(index) => &mut self.entries[index],
+            None => match self.entries.iter_mut().find(|entry| entry.key == 0) {
+                Some(entry) => {
+                    entry.key = key;
+                    entry
+                }
+                // The table is full, leave the borrow untracked.
+                None => return,
+            },
+        };
+        if mutable {
+            entry.report.mutable = true;
+        } else {
+            entry.report.outstanding_shared = entry.report.outstanding_shared.saturating_add(1);
+        }
+    }
+
+    fn record_release(&mut self, key: usize, mutable: bool) {
+        let Some(entry) = self.entries.iter_mut().find(|entry| entry.key == key) else {
+            return;
+        };
+        if mutable {
+            entry.report.mutable = false;
+        } else {
+            entry.report.outstanding_shared = entry.report.outstanding_shared.saturating_sub(1);
+        }
+        if entry.report == BorrowReport::default() {
+            entry.key = 0;
+        }
+    }
+}
+
+#[cfg(target_os = "solana")]
+static mut BORROW_TABLE: BorrowTable = BorrowTable::new();
+
+#[cfg(target_os = "solana")]
+#[inline(always)]
+fn with_borrow_table<R>(f: impl FnOnce(&mut BorrowTable) -> R) -> R {
+    // SAFETY: Programs are single-threaded and `f` never re-enters.
+    f(unsafe { &mut *core::ptr::addr_of_mut!(BORROW_TABLE) })
+}
+
+#[cfg(not(target_os = "solana"))]
+std::thread_local! {
+    static BORROW_TABLE: RefCell<BorrowTable> = const { RefCell::new(BorrowTable::new()) };
+
+    /// Backtraces of live borrows, keyed by the address of the borrow state.
+    static BORROW_BACKTRACES: RefCell<VecDeque<(usize, Backtrace)>> =
+        const { RefCell::new(VecDeque::new()) };
+}
+
+#[cfg(not(target_os = "solana"))]
+fn with_borrow_table<R>(f: impl FnOnce(&mut BorrowTable) -> R) -> R {
+    BORROW_TABLE.with(|table| f(&mut table.borrow_mut()))
+}
+
+/// Formatted backtraces of the live borrows of `account`'s data, oldest
+/// first.
+///
+/// Backtraces are only captured when enabled through `RUST_BACKTRACE` or
+/// `RUST_LIB_BACKTRACE`, and at most [`MAX_BACKTRACES`] are kept. Immutable
+/// borrows are assumed to be released in reverse order, so when they are
+/// not, the backtraces reported for immutable borrows may belong to an
+/// already released sibling borrow.
+#[cfg(not(target_os = "solana"))]
+pub fn borrow_backtraces<L: AccountLayout>(account: &AccountView<L>) -> Vec<String> {
+    let key = account.borrow_state_ptr() as usize;
+    BORROW_BACKTRACES.with(|backtraces| {
+        backtraces
+            .borrow()
+            .iter()
+            .filter(|(state, _)| *state == key)
+            .map(|(_, backtrace)| std::format!("{backtrace}"))
+            .collect()
+    })
+}
+
+#[inline(always)]
+pub(crate) fn record_borrow(state: *const u8, mutable: bool) {
+    with_borrow_table(|table| table.record_borrow(state as usize, mutable));
+
+    #[cfg(not(target_os = "solana"))]
+    BORROW_BACKTRACES.with(|backtraces| {
+        let mut backtraces = backtraces.borrow_mut();
+        if backtraces.len() == MAX_BACKTRACES {
+            backtraces.pop_front();
+        }
+        backtraces.push_back((state as usize, Backtrace::capture()))
+    });
+}
+
+#[inline(always)]
+pub(crate) fn record_release(state: *const u8, mutable: bool) {
+    with_borrow_table(|table| table.record_release(state as usize, mutable));
+
+    #[cfg(not(target_os = "solana"))]
+    BORROW_BACKTRACES.with(|backtraces| {
+        let mut backtraces = backtraces.borrow_mut();
+        if let Some(index) = backtraces
+            .iter()
+            .rposition(|(key, _)| *key == state as usize)
+        {
+            drop(backtraces.remove(index));
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{Ref, RuntimeAccount, NOT_BORROWED},
+        core::mem::size_of,
+        solana_program_error::ProgramError,
+        std::{string::ToString, vec},
+    };
+
+    fn account_data() -> [u64; size_of::<RuntimeAccount>() / size_of::<u64>() + 1] {
+        let mut data = [0u64; size_of::<RuntimeAccount>() / size_of::<u64>() + 1];
+        data[0] = NOT_BORROWED as u64;
+        let account = data.as_mut_ptr() as *mut RuntimeAccount;
+        unsafe { (*account).data_len = 8 };
+        data
+    }
+
+    #[test]
+    #[allow(clippy::clone_on_copy)]
+    fn test_report_nested_borrows() {
+        let mut data = account_data();
+        let mut account_view =
+            unsafe { AccountView::new_unchecked(data.as_mut_ptr() as *mut RuntimeAccount) };
+        assert_eq!(borrow_state_report(&account_view), BorrowReport::default());
+
+        let first = account_view.try_borrow().unwrap();
+        let second = account_view.try_borrow().unwrap();
+        let mapped = Ref::map(account_view.try_borrow().unwrap(), |data| &data[..4]);
+        assert_eq!(
+            borrow_state_report(&account_view),
+            BorrowReport {
+                outstanding_shared: 3,
+                mutable: false,
+            }
+        );
+        assert_eq!(borrow_backtraces(&account_view).len(), 3);
+        assert_eq!(
+            account_view.check_borrow_mut().unwrap_err(),
+            ProgramError::AccountBorrowFailed
+        );
+
+        drop(second);
+        assert_eq!(borrow_state_report(&account_view).outstanding_shared, 2);
+        drop(mapped);
+        drop(first);
+        assert_eq!(borrow_state_report(&account_view), BorrowReport::default());
+        assert!(borrow_backtraces(&account_view).is_empty());
+
+        let data_mut = account_view.try_borrow_mut().unwrap();
+        drop(data_mut);
+        let mut other_view = account_view.clone();
+        let data_mut = other_view.try_borrow_mut().unwrap();
+        assert_eq!(
+            borrow_state_report(&account_view),
+            BorrowReport {
+                outstanding_shared: 0,
+                mutable: true,
+            }
+        );
+        assert_eq!(borrow_backtraces(&account_view).len(), 1);
+        assert_eq!(
+            borrow_state_report(&account_view).to_string(),
+            "account data is mutably borrowed"
+        );
+
+        drop(data_mut);
+        assert_eq!(borrow_state_report(&account_view), BorrowReport::default());
+        assert!(borrow_backtraces(&account_view).is_empty());
+    }
+
+    #[test]
+    #[allow(clippy::clone_on_copy)]
+    fn test_report_failed_borrows_not_recorded() {
+        let mut data = account_data();
+        let mut account_view =
+            unsafe { AccountView::new_unchecked(data.as_mut_ptr() as *mut RuntimeAccount) };
+        let mut other_view = account_view.clone();
+
+        let data_mut = account_view.try_borrow_mut().unwrap();
+        assert!(other_view.try_borrow().is_err());
+        assert!(other_view.try_borrow_mut().is_err());
+        assert_eq!(borrow_backtraces(&other_view).len(), 1);
+        drop(data_mut);
+        assert!(borrow_backtraces(&other_view).is_empty());
+    }
+
+    #[test]
+    fn test_backtraces_capped() {
+        let mut data = account_data();
+        let account_view =
+            unsafe { AccountView::new_unchecked(data.as_mut_ptr() as *mut RuntimeAccount) };
+
+        let refs: Vec<_> = (0..MAX_BACKTRACES + 6)
+            .map(|_| account_view.try_borrow().unwrap())
+            .collect();
+        assert_eq!(
+            borrow_state_report(&account_view).outstanding_shared as usize,
+            MAX_BACKTRACES + 6
+        );
+        assert_eq!(borrow_backtraces(&account_view).len(), MAX_BACKTRACES);
+
+        drop(refs);
+        assert_eq!(borrow_state_report(&account_view), BorrowReport::default());
+        assert!(borrow_backtraces(&account_view).is_empty());
+    }
+
+    #[test]
+    fn test_table_full() {
+        let mut data = vec![account_data(); MAX_TRACKED_ACCOUNTS + 1];
+        let views: Vec<_> = data
+            .iter_mut()
+            .map(|data| unsafe {
+                AccountView::new_unchecked(data.as_mut_ptr() as *mut RuntimeAccount)
+            })
+            .collect();
+        let mut borrowers = views.clone();
+        let (untracked, tracked) = borrowers.split_last_mut().unwrap();
+
+        let refs: Vec<_> = tracked
+            .iter_mut()
+            .map(|view| view.try_borrow_mut().unwrap())
+            .collect();
+        let data_mut = untracked.try_borrow_mut().unwrap();
+        assert!(borrow_state_report(&views[0]).mutable);
+        assert_eq!(
+            borrow_state_report(&views[MAX_TRACKED_ACCOUNTS]),
+            BorrowReport::default()
+        );
+
+        // releasing a borrow frees its slot for another account
+        drop(refs);
+        drop(data_mut);
+        let data_mut = untracked.try_borrow_mut().unwrap();
+        assert!(borrow_state_report(&views[MAX_TRACKED_ACCOUNTS]).mutable);
+        drop(data_mut);
+        assert_eq!(
+            borrow_state_report(&views[MAX_TRACKED_ACCOUNTS]),
+            BorrowReport::default()
+        );
+    }
+}
@@ -0,0 +1,155 @@
+//! Conversion of accounts into account metas, for cross-program invocations.
+//!
+//! A program forwarding the accounts it received to another program builds
+//! the [`AccountMeta`]s of the invoked instruction from the flags of the
+//! accounts. Forwarding an account with [`AccountView::to_account_meta`]
+//! keeps its privileges, while [`AccountView::to_account_meta_readonly`]
+//! drops the writable flag, for accounts the invoked program must not modify.
+
+use {
+    crate::{AccountLayout, AccountView},
+    alloc::vec::Vec,
+    solana_address::Address,
+    solana_instruction::AccountMeta,
+};
+
+impl<L: AccountLayout> AccountView<L> {
+    /// The account meta of this account, with its current signer and
+    /// writable flags.
+    #[inline]
+    pub fn to_account_meta(&self) -> AccountMeta {
+        AccountMeta {
+            pubkey: Address::new_from_array(self.address().to_bytes()),
+            is_signer: self.is_signer(),
+            is_writable: self.is_writable(),
+        }
+    }
+
+    /// The account meta of this account, with its current signer flag and
+    /// without the writable flag.
+    #[inline]
+    pub fn to_account_meta_readonly(&self) -> AccountMeta {
+        AccountMeta {
+            is_writable: false,
+            ..self.to_account_meta()
+        }
+    }
+}
+
+/// The account metas of `accounts`, in order, with their current signer and
+/// writable flags.
+pub fn account_metas<L: AccountLayout>(accounts: &[AccountView<L>]) -> Vec<AccountMeta> {
+    accounts.iter().map(AccountView::to_account_meta).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{RuntimeAccount, NOT_BORROWED},
+        alloc::vec,
+        core::mem::size_of,
+        solana_instruction::{privileges::check_cpi_account_metas, Instruction},
+    };
+
+    const ACCOUNT_WORDS: usize = size_of::<RuntimeAccount>() / size_of::<u64>();
+
+    fn account_data(seed: u8, is_signer: bool, is_writable: bool) -> [u64; ACCOUNT_WORDS] {
+        let mut data = [0u64; ACCOUNT_WORDS];
+        let account = data.as_mut_ptr() as *mut RuntimeAccount;
+        unsafe {
+            (*account).borrow_state = NOT_BORROWED;
+            (*account).is_signer = u8::from(is_signer);
+            (*account).is_writable = u8::from(is_writable);
+            (*account).address = Address::new_from_array([seed; 32]);
+        }
+        data
+    }
+
+    #[test]
+    fn test_forward_accounts() {
+        let mut data = [
+            account_data(1, true, true),
+            account_data(2, false, true),
+            account_data(3, true, false),
+            account_data(4, false, false),
+        ];
+        let accounts: Vec<AccountView> = data
+            .iter_mut()
+            .map(|data| unsafe { AccountView::new_unchecked(data.as_mut_ptr() as *mut _) })
+            .collect();
+
+        assert_eq!(
+            account_metas(&accounts),
+            vec![
+                AccountMeta::new(Address::new_from_array([1; 32]), true),
+                AccountMeta::new(Address::new_from_array([2; 32]), false),
+                AccountMeta::new_readonly(Address::new_from_array([3; 32]), true),
+                AccountMeta::new_readonly(Address::new_from_array([4; 32]), false),
+            ]
+        );
+        assert!(account_metas::<crate::LayoutV1>(&[]).is_empty());
+
+        // forward the second and third accounts, demoting the second
+        let program_id = Address::new_from_array([9; 32]);
+        let instruction = Instruction::new_with_bytes(
+            program_id,
+            &[],
+            vec![
+                accounts[1].to_account_meta_readonly(),
+                accounts[2].to_account_meta(),
+            ],
+        );
+        for (meta, account) in instruction.accounts.iter().zip(&accounts[1..3]) {
+            assert_eq!(&meta.pubkey, account.address());
+            assert_eq!(meta.is_signer, account.is_signer());
+        }
+        assert!(accounts[1].is_writable());
+        assert!(!instruction.accounts[0].is_writable);
+        assert!(!instruction.accounts[1].is_writable);
+
+        // demoting keeps the signer flag
+        assert_eq!(
+            accounts[0].to_account_meta_readonly(),
+            AccountMeta::new_readonly(Address::new_from_array([1; 32]), true)
+        );
+    }
+
+    #[test]
+    fn test_check_privileges() {
+        let mut data = [
+            account_data(1, true, true),
+            account_data(2, false, true),
+            account_data(3, false, false),
+        ];
+        let accounts: Vec<AccountView> = data
+            .iter_mut()
+            .map(|data| unsafe { AccountView::new_unchecked(data.as_mut_ptr() as *mut _) })
+            .collect();
+        let caller_accounts = account_metas(&accounts);
+        let program_id = Address::new_from_array([3; 32]);
+
+        let instruction = Instruction::new_with_bytes(
+            program_id,
+            &[],
+            vec![
+                accounts[0].to_account_meta(),
+                accounts[1].to_account_meta_readonly(),
+            ],
+        );
+        assert_eq!(
+            check_cpi_account_metas(&caller_accounts, &instruction),
+            Ok(())
+        );
+
+        // the second account is not a signer of the caller
+        let instruction = Instruction::new_with_bytes(
+            program_id,
+            &[],
+            vec![AccountMeta::new(Address::new_from_array([2; 32]), true)],
+        );
+        let violation = check_cpi_account_metas(&caller_accounts, &instruction).unwrap_err();
+        assert_eq!(violation.address, Address::new_from_array([2; 32]));
+        assert!(violation.needs_signer && !violation.caller_has_signer);
+    }
+}
@@ -0,0 +1,145 @@
+//! Memory layouts of the account headers serialized by the runtime.
+//!
+//! [`AccountView`](crate::AccountView) reads every account field at an
+//! offset defined by an [`AccountLayout`]. The layout used by the current
+//! runtime is [`LayoutV1`], which is the default. With the `abi-v2` feature,
+//! the experimental [`LayoutV2`] can be used to test programs against the
+//! layout proposed for the next ABI.
+
+#[cfg(feature = "abi-v2")]
+use solana_address::Address;
+use {
+    crate::RuntimeAccount,
+    core::mem::{offset_of, size_of},
+};
+
+mod private {
+    pub trait Sealed {}
+}
+
+/// Offsets of the fields of an account header, relative to its start.
+///
+/// The account data is serialized directly after the header, at
+/// [`AccountLayout::DATA_OFFSET`]. This trait is sealed, as the runtime
+/// decides which layouts exist.
+pub trait AccountLayout: private::Sealed {
+    /// Offset of the borrow state byte.
+    const BORROW_STATE_OFFSET: usize;
+    /// Offset of the signer flag.
+    const IS_SIGNER_OFFSET: usize;
+    /// Offset of the writable flag.
+    const IS_WRITABLE_OFFSET: usize;
+    /// Offset of the executable flag.
+    const EXECUTABLE_OFFSET: usize;
+    /// Offset of the address of the account.
+    const ADDRESS_OFFSET: usize;
+    /// Offset of the owner of the account.
+    const OWNER_OFFSET: usize;
+    /// Offset of the `u64` lamports.
+    const LAMPORTS_OFFSET: usize;
+    /// Offset of the `u64` length of the account data.
+    const DATA_LEN_OFFSET: usize;
+    /// Offset of the `i32` difference between the data length and the
+    /// original data length, see
+    /// [`AccountView::original_data_len`](crate::AccountView::original_data_len).
+    const RESIZE_DELTA_OFFSET: usize;
+    /// Offset of the account data, i.e. the size of the header.
+    const DATA_OFFSET: usize;
+}
+
+/// The account layout of the current runtime, described by
+/// [`RuntimeAccount`].
+#[cfg_attr(feature = "copy", derive(Copy))]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LayoutV1;
+
+impl private::Sealed for LayoutV1 {}
+
+impl AccountLayout for LayoutV1 {
+    const BORROW_STATE_OFFSET: usize = offset_of!(RuntimeAccount, borrow_state);
+    const IS_SIGNER_OFFSET: usize = offset_of!(RuntimeAccount, is_signer);
+    const IS_WRITABLE_OFFSET: usize = offset_of!(RuntimeAccount, is_writable);
+    const EXECUTABLE_OFFSET: usize = offset_of!(RuntimeAccount, executable);
+    const ADDRESS_OFFSET: usize = offset_of!(RuntimeAccount, address);
+    const OWNER_OFFSET: usize = offset_of!(RuntimeAccount, owner);
+    const LAMPORTS_OFFSET: usize = offset_of!(RuntimeAccount, lamports);
+    const DATA_LEN_OFFSET: usize = offset_of!(RuntimeAccount, data_len);
+    const RESIZE_DELTA_OFFSET: usize = offset_of!(RuntimeAccount, padding);
+    const DATA_OFFSET: usize = size_of::<RuntimeAccount>();
+}
+
+/// The account layout proposed for ABIv2.
+///
+/// **Experimental:** this layout is not used by any runtime and may change
+/// or be removed at any time. It only exists to prototype programs against
+/// the proposed ABI.
+///
+/// The header starts with the 8-byte aligned fields and ends with the flags,
+/// followed by the resize delta that [`RuntimeAccount`] keeps in its padding:
+///
+/// | Offset | Size | Field          |
+/// |--------|------|----------------|
+/// | 0      | 32   | address        |
+/// | 32     | 32   | owner          |
+/// | 64     | 8    | lamports       |
+/// | 72     | 8    | data length    |
+/// | 80     | 1    | borrow state   |
+/// | 81     | 1    | is signer      |
+/// | 82     | 1    | is writable    |
+/// | 83     | 1    | executable     |
+/// | 84     | 4    | resize delta   |
+///
+/// The account data starts at offset 88, so that it stays 8-byte aligned.
+#[cfg(feature = "abi-v2")]
+#[cfg_attr(feature = "copy", derive(Copy))]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LayoutV2;
+
+#[cfg(feature = "abi-v2")]
+impl private::Sealed for LayoutV2 {}
+
+#[cfg(feature = "abi-v2")]
+impl AccountLayout for LayoutV2 {
+    const BORROW_STATE_OFFSET: usize = 80;
+    const IS_SIGNER_OFFSET: usize = 81;
+    const IS_WRITABLE_OFFSET: usize = 82;
+    const EXECUTABLE_OFFSET: usize = 83;
+    const ADDRESS_OFFSET: usize = 0;
+    const OWNER_OFFSET: usize = size_of::<Address>();
+    const LAMPORTS_OFFSET: usize = 2 * size_of::<Address>();
+    const DATA_LEN_OFFSET: usize = Self::LAMPORTS_OFFSET + size_of::<u64>();
+    const RESIZE_DELTA_OFFSET: usize = 84;
+    const DATA_OFFSET: usize = 88;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_layout_v1_offsets() {
+        assert_eq!(LayoutV1::BORROW_STATE_OFFSET, 0);
+        assert_eq!(LayoutV1::IS_SIGNER_OFFSET, 1);
+        assert_eq!(LayoutV1::IS_WRITABLE_OFFSET, 2);
+        assert_eq!(LayoutV1::EXECUTABLE_OFFSET, 3);
+        assert_eq!(LayoutV1::ADDRESS_OFFSET, 8);
+        assert_eq!(LayoutV1::OWNER_OFFSET, 40);
+        assert_eq!(LayoutV1::LAMPORTS_OFFSET, 72);
+        assert_eq!(LayoutV1::DATA_LEN_OFFSET, 80);
+        assert_eq!(LayoutV1::RESIZE_DELTA_OFFSET, 4);
+        assert_eq!(LayoutV1::DATA_OFFSET, 88);
+    }
+
+    #[cfg(feature = "abi-v2")]
+    #[test]
+    fn test_layout_v2_offsets() {
+        assert_eq!(LayoutV2::OWNER_OFFSET, 32);
+        assert_eq!(LayoutV2::LAMPORTS_OFFSET, 64);
+        assert_eq!(LayoutV2::DATA_LEN_OFFSET, 72);
+        assert_eq!(LayoutV2::RESIZE_DELTA_OFFSET, 84);
+        assert_eq!(LayoutV2::DATA_OFFSET, 88);
+        assert_eq!(LayoutV2::LAMPORTS_OFFSET % 8, 0);
+        assert_eq!(LayoutV2::DATA_LEN_OFFSET % 8, 0);
+        assert_eq!(LayoutV2::DATA_OFFSET % 8, 0);
+    }
+}
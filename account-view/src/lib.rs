@@ -3,19 +3,37 @@
 #![no_std]
 #![cfg_attr(docsrs, feature(doc_cfg))]
 #![allow(clippy::arithmetic_side_effects)]
+#[cfg(any(feature = "alloc", feature = "cpi"))]
+extern crate alloc;
+#[cfg(all(feature = "borrow-debug", not(target_os = "solana")))]
+extern crate std;
 
 use {
     core::{
         marker::PhantomData,
         mem::{size_of, ManuallyDrop},
         ops::{Deref, DerefMut},
-        ptr::{addr_of_mut, write, write_bytes, NonNull},
+        ptr::{write, write_bytes, NonNull},
         slice::{from_raw_parts, from_raw_parts_mut},
     },
-    solana_address::Address,
+    solana_address::{address_eq, Address},
     solana_program_error::{ProgramError, ProgramResult},
+    solana_program_memory::sol_memcmp,
 };
 
+#[cfg(feature = "borrow-debug")]
+pub mod borrow_debug;
+#[cfg(feature = "cpi")]
+pub mod cpi;
+pub mod layout;
+#[cfg(feature = "alloc")]
+pub mod snapshot;
+pub mod version;
+
+#[cfg(feature = "abi-v2")]
+pub use layout::LayoutV2;
+pub use layout::{AccountLayout, LayoutV1};
+
 /// Maximum number of bytes a program may add to an account during a
 /// single top-level instruction.
 pub const MAX_PERMITTED_DATA_INCREASE: usize = 1_024 * 10;
@@ -58,9 +76,9 @@ pub struct RuntimeAccount {
 
     /// Padding for alignment.
     ///
-    /// The value of this field is not directly used and always set to `0`.
-    /// Entrypoint implementations may use this space for their own purposes,
-    /// e.g., to track account resizing.
+    /// The runtime always sets this field to `0`. [`AccountView`] uses it to
+    /// track account resizing, as the native-endian `i32` difference between
+    /// the data length and the original data length.
     pub padding: [u8; 4],
 
     /// Address of the account.
@@ -82,24 +100,28 @@ pub struct RuntimeAccount {
 /// It is also used to track borrows of the account data, given that
 /// an account can be "shared" across multiple `AccountView` instances.
 ///
+/// The fields of the account are read at the offsets defined by the layout
+/// `L`, which defaults to the [`LayoutV1`] layout of `RuntimeAccount`.
+///
 /// # Invariants
 ///
-/// - The `raw` pointer must be valid and point to memory containing a
-///   `RuntimeAccount` struct, immediately followed by the account's data
-///   region.
-/// - The length of the account data must exactly match the value stored in
-///   `RuntimeAccount::data_len`.
+/// - The `raw` pointer must be valid and point to memory containing an
+///   account header in the layout `L`, immediately followed by the
+///   account's data region.
+/// - The length of the account data must exactly match the data length
+///   stored in the header.
 ///
 /// These conditions must always hold for any `AccountView` created from
 /// a raw pointer.
 #[repr(C)]
 #[cfg_attr(feature = "copy", derive(Copy))]
 #[derive(Clone, PartialEq, Eq, Debug)]
-pub struct AccountView {
+pub struct AccountView<L: AccountLayout = LayoutV1> {
     /// Raw (pointer to) account data.
     ///
     /// Note that this is a pointer can be shared across multiple `AccountView`.
-    raw: *mut RuntimeAccount,
+    raw: *mut u8,
+    layout: PhantomData<L>,
 }
 
 impl AccountView {
@@ -112,14 +134,50 @@ impl AccountView {
     /// the account's data region.
     #[inline(always)]
     pub unsafe fn new_unchecked(raw: *mut RuntimeAccount) -> Self {
-        Self { raw }
+        Self::from_raw(raw as *mut u8)
+    }
+
+    /// Returns a raw pointer to the `RuntimeAccount` struct.
+    pub fn account_ptr(&self) -> *const RuntimeAccount {
+        self.raw as *const _
+    }
+
+    /// Returns a mutable raw pointer to the `RuntimeAccount` struct.
+    pub fn account_mut_ptr(&mut self) -> *mut RuntimeAccount {
+        self.raw as *mut _
+    }
+}
+
+impl<L: AccountLayout> AccountView<L> {
+    /// Creates a new [`AccountView`] for a raw pointer to an account header
+    /// in the layout `L`.
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure that the `raw` pointer is valid, 8-byte aligned
+    /// and points to memory containing an account header in the layout `L`,
+    /// immediately followed by the account's data region.
+    #[inline(always)]
+    pub unsafe fn from_raw(raw: *mut u8) -> Self {
+        Self {
+            raw,
+            layout: PhantomData,
+        }
+    }
+
+    /// Returns a pointer to the header field at `offset`.
+    #[inline(always)]
+    const fn field_ptr<T>(&self, offset: usize) -> *mut T {
+        // SAFETY: The `raw` pointer is guaranteed to be valid and the layout
+        // offsets lie within the account header.
+        unsafe { self.raw.add(offset) as *mut T }
     }
 
     /// Address of the account.
     #[inline(always)]
     pub fn address(&self) -> &Address {
         // SAFETY: The `raw` pointer is guaranteed to be valid.
-        unsafe { &(*self.raw).address }
+        unsafe { &*self.field_ptr(L::ADDRESS_OFFSET) }
     }
 
     /// Return a reference to the address of the program that owns this account.
@@ -136,21 +194,21 @@ impl AccountView {
     #[inline(always)]
     pub fn owner(&self) -> &Address {
         // SAFETY: The `raw` pointer is guaranteed to be valid.
-        unsafe { &(*self.raw).owner }
+        unsafe { &*self.field_ptr(L::OWNER_OFFSET) }
     }
 
     /// Indicate whether the transaction was signed by this account.
     #[inline(always)]
     pub fn is_signer(&self) -> bool {
         // SAFETY: The `raw` pointer is guaranteed to be valid.
-        unsafe { (*self.raw).is_signer != 0 }
+        unsafe { *self.field_ptr::<u8>(L::IS_SIGNER_OFFSET) != 0 }
     }
 
     /// Indicate whether the account is writable or not.
     #[inline(always)]
     pub fn is_writable(&self) -> bool {
         // SAFETY: The `raw` pointer is guaranteed to be valid.
-        unsafe { (*self.raw).is_writable != 0 }
+        unsafe { *self.field_ptr::<u8>(L::IS_WRITABLE_OFFSET) != 0 }
     }
 
     /// Indicate whether this account represents an executable program
@@ -158,21 +216,33 @@ impl AccountView {
     #[inline(always)]
     pub fn executable(&self) -> bool {
         // SAFETY: The `raw` pointer is guaranteed to be valid.
-        unsafe { (*self.raw).executable != 0 }
+        unsafe { *self.field_ptr::<u8>(L::EXECUTABLE_OFFSET) != 0 }
     }
 
     /// Return the size of the account data.
     #[inline(always)]
     pub fn data_len(&self) -> usize {
         // SAFETY: The `raw` pointer is guaranteed to be valid.
-        unsafe { (*self.raw).data_len as usize }
+        unsafe { *self.field_ptr::<u64>(L::DATA_LEN_OFFSET) as usize }
+    }
+
+    /// Returns the length of the account data before any resize during the
+    /// current instruction.
+    ///
+    /// The data can grow up to [`MAX_PERMITTED_DATA_INCREASE`] bytes over
+    /// this length.
+    #[inline(always)]
+    pub fn original_data_len(&self) -> usize {
+        // SAFETY: The `raw` pointer is guaranteed to be valid.
+        let delta = unsafe { *self.field_ptr::<i32>(L::RESIZE_DELTA_OFFSET) };
+        (self.data_len() as i64 - i64::from(delta)) as usize
     }
 
     /// Return the lamports in the account.
     #[inline(always)]
     pub fn lamports(&self) -> u64 {
         // SAFETY: The `raw` pointer is guaranteed to be valid.
-        unsafe { (*self.raw).lamports }
+        unsafe { *self.field_ptr::<u64>(L::LAMPORTS_OFFSET) }
     }
 
     /// Set the lamports in the account.
@@ -180,7 +250,7 @@ impl AccountView {
     pub fn set_lamports(&mut self, lamports: u64) {
         // SAFETY: The `raw` pointer is guaranteed to be valid.
         unsafe {
-            (*self.raw).lamports = lamports;
+            *self.field_ptr::<u64>(L::LAMPORTS_OFFSET) = lamports;
         }
     }
 
@@ -197,7 +267,7 @@ impl AccountView {
     #[inline(always)]
     pub fn owned_by(&self, program: &Address) -> bool {
         // SAFETY: The `raw` pointer is guaranteed to be valid.
-        unsafe { (*self.raw).owner == *program }
+        unsafe { *self.field_ptr::<Address>(L::OWNER_OFFSET) == *program }
     }
 
     /// Changes the owner of the account.
@@ -209,19 +279,19 @@ impl AccountView {
     #[allow(clippy::clone_on_copy)]
     #[inline(always)]
     pub unsafe fn assign(&mut self, new_owner: &Address) {
-        write(addr_of_mut!((*self.raw).owner), new_owner.clone());
+        write(self.field_ptr(L::OWNER_OFFSET), new_owner.clone());
     }
 
     /// Return `true` if the account data is borrowed in any form.
     #[inline(always)]
     pub fn is_borrowed(&self) -> bool {
-        unsafe { (*self.raw).borrow_state != NOT_BORROWED }
+        unsafe { *self.borrow_state_ptr() != NOT_BORROWED }
     }
 
     /// Return `true` if the account data is mutably borrowed.
     #[inline(always)]
     pub fn is_borrowed_mut(&self) -> bool {
-        unsafe { (*self.raw).borrow_state == 0 }
+        unsafe { *self.borrow_state_ptr() == 0 }
     }
 
     /// Returns an immutable reference to the data in the account.
@@ -249,48 +319,135 @@ impl AccountView {
 
     /// Tries to get an immutable reference to the account data, failing if the account
     /// is already mutably borrowed.
+    ///
+    /// The borrow is held until the returned [`Ref`] is dropped. Holding it across a
+    /// CPI that uses this account makes the CPI fail, since the runtime cannot update
+    /// borrowed data; prefer [`Self::with_data_scoped`] to scope the borrow to a closure.
     pub fn try_borrow(&self) -> Result<Ref<'_, [u8]>, ProgramError> {
         // check if the account data can be borrowed
         self.check_borrow()?;
 
-        let borrow_state = self.raw as *mut u8;
+        // SAFETY: There is at least one immutable borrow available.
+        Ok(unsafe { self.take_borrow() })
+    }
+
+    /// Tries to get a mutable reference to the account data, failing if the account
+    /// is already borrowed in any form.
+    ///
+    /// The borrow is held until the returned [`RefMut`] is dropped. Holding it across
+    /// a CPI that uses this account makes the CPI fail; prefer [`Self::with_data_mut_scoped`]
+    /// to scope the borrow to a closure.
+    pub fn try_borrow_mut(&mut self) -> Result<RefMut<'_, [u8]>, ProgramError> {
+        // check if the account data can be mutably borrowed
+        self.check_borrow_mut()?;
+
+        // SAFETY: The account data is not borrowed in any form.
+        Ok(unsafe { self.take_borrow_mut() })
+    }
+
+    /// Takes an immutable borrow of the account data.
+    ///
+    /// # Safety
+    ///
+    /// There must be at least one immutable borrow available, see
+    /// [`Self::check_borrow`].
+    #[inline(always)]
+    unsafe fn take_borrow(&self) -> Ref<'_, [u8]> {
+        let borrow_state = self.borrow_state_ptr();
         // Use one immutable borrow for data by subtracting `1` from the data
-        // borrow counter bits; we are guaranteed that there is at least one
-        // immutable borrow available.
+        // borrow counter bits; the caller guarantees that there is at least
+        // one immutable borrow available.
         //
         // SAFETY: The `borrow_state` is a mutable pointer to the borrow state
         // of the account, which is guaranteed to be valid.
-        unsafe { *borrow_state -= 1 };
+        *borrow_state -= 1;
+
+        #[cfg(feature = "borrow-debug")]
+        borrow_debug::record_borrow(borrow_state, false);
 
         // return the reference to data
-        Ok(Ref {
-            value: unsafe { NonNull::from(from_raw_parts(self.data_ptr(), self.data_len())) },
-            state: unsafe { NonNull::new_unchecked(borrow_state) },
+        Ref {
+            value: NonNull::from(from_raw_parts(self.data_ptr(), self.data_len())),
+            state: NonNull::new_unchecked(borrow_state),
             marker: PhantomData,
-        })
+        }
     }
 
-    /// Tries to get a mutable reference to the account data, failing if the account
-    /// is already borrowed in any form.
-    pub fn try_borrow_mut(&mut self) -> Result<RefMut<'_, [u8]>, ProgramError> {
-        // check if the account data can be mutably borrowed
-        self.check_borrow_mut()?;
-
-        let borrow_state = self.raw as *mut u8;
-        // Set the mutable data borrow bit to `0`; we are guaranteed that account
-        // data is not already borrowed in any form.
+    /// Takes a mutable borrow of the account data.
+    ///
+    /// # Safety
+    ///
+    /// The account data must not be borrowed in any form, see
+    /// [`Self::check_borrow_mut`].
+    #[inline(always)]
+    unsafe fn take_borrow_mut(&self) -> RefMut<'_, [u8]> {
+        let borrow_state = self.borrow_state_ptr();
+        // Set the mutable data borrow bit to `0`; the caller guarantees that
+        // account data is not already borrowed in any form.
         //
         // SAFETY: The `borrow_state` is a mutable pointer to the borrow state
         // of the account, which is guaranteed to be valid.
-        unsafe { *borrow_state = 0 };
+        *borrow_state = 0;
+
+        #[cfg(feature = "borrow-debug")]
+        borrow_debug::record_borrow(borrow_state, true);
 
         // return the mutable reference to data
-        Ok(RefMut {
-            value: unsafe {
-                NonNull::from(from_raw_parts_mut(self.data_mut_ptr(), self.data_len()))
-            },
-            state: unsafe { NonNull::new_unchecked(borrow_state) },
+        RefMut {
+            value: NonNull::from(from_raw_parts_mut(
+                self.data_ptr() as *mut u8,
+                self.data_len(),
+            )),
+            state: NonNull::new_unchecked(borrow_state),
             marker: PhantomData,
+        }
+    }
+
+    /// Calls `f` with the account data, failing if the account is already mutably
+    /// borrowed.
+    ///
+    /// The data is borrowed only for the duration of the call, so the borrow cannot
+    /// accidentally be held across a CPI.
+    #[inline]
+    pub fn with_data_scoped<R>(&self, f: impl FnOnce(&[u8]) -> R) -> Result<R, ProgramError> {
+        let data = self.try_borrow()?;
+        Ok(f(&data))
+    }
+
+    /// Calls `f` with the mutable account data, failing if the account is already
+    /// borrowed in any form.
+    ///
+    /// The data is borrowed only for the duration of the call, so the borrow cannot
+    /// accidentally be held across a CPI.
+    #[inline]
+    pub fn with_data_mut_scoped<R>(
+        &mut self,
+        f: impl FnOnce(&mut [u8]) -> R,
+    ) -> Result<R, ProgramError> {
+        let mut data = self.try_borrow_mut()?;
+        Ok(f(&mut data))
+    }
+
+    /// Returns whether the account data is equal to `other`, failing if the
+    /// account is already mutably borrowed.
+    ///
+    /// The data is borrowed only for the comparison, which uses `sol_memcmp`
+    /// on chain.
+    #[inline]
+    pub fn data_eq(&self, other: &[u8]) -> Result<bool, ProgramError> {
+        self.with_data_scoped(|data| bytes_eq(data, other))
+    }
+
+    /// Returns whether the account data starts with `prefix`, failing if the
+    /// account is already mutably borrowed.
+    ///
+    /// The data is borrowed only for the comparison, which uses `sol_memcmp`
+    /// on chain.
+    #[inline]
+    pub fn data_starts_with(&self, prefix: &[u8]) -> Result<bool, ProgramError> {
+        self.with_data_scoped(|data| {
+            data.get(..prefix.len())
+                .is_some_and(|start| bytes_eq(start, prefix))
         })
     }
 
@@ -302,7 +459,7 @@ impl AccountView {
         // There must be at least one immutable borrow available.
         //
         // SAFETY: The `raw` pointer is guaranteed to be valid.
-        if unsafe { (*self.raw).borrow_state } < 2 {
+        if unsafe { *self.borrow_state_ptr() } < 2 {
             return Err(ProgramError::AccountBorrowFailed);
         }
 
@@ -314,7 +471,7 @@ impl AccountView {
     #[inline(always)]
     pub fn check_borrow_mut(&self) -> Result<(), ProgramError> {
         // SAFETY: The `raw` pointer is guaranteed to be valid.
-        if unsafe { (*self.raw).borrow_state } != NOT_BORROWED {
+        if unsafe { *self.borrow_state_ptr() } != NOT_BORROWED {
             return Err(ProgramError::AccountBorrowFailed);
         }
 
@@ -365,28 +522,84 @@ impl AccountView {
     /// borrowed. It should only be called when the account is not being used.
     ///
     /// It also makes assumptions about the layout and location of memory
-    /// referenced by `RuntimeAccount` fields. It should only be called for
+    /// referenced by the account header fields. It should only be called for
     /// instances of `AccountView` that were created by the runtime and received
     /// in the `process_instruction` entrypoint of a program.
     #[inline(always)]
     pub unsafe fn close_unchecked(&mut self) {
-        // We take advantage that the 48 bytes before the account data are:
-        // - 32 bytes for the owner
-        // - 8 bytes for the lamports
-        // - 8 bytes for the data_len
-        //
-        // So we can zero out them directly.
-        write_bytes(self.data_mut_ptr().sub(48), 0, 48);
+        // In the V1 layout, these are the 48 contiguous bytes before the
+        // account data, which the compiler zeroes out at once.
+        write_bytes(
+            self.field_ptr::<u8>(L::OWNER_OFFSET),
+            0,
+            size_of::<Address>(),
+        );
+        write(self.field_ptr::<u64>(L::LAMPORTS_OFFSET), 0);
+        // The original length is kept, so that the account can still grow up
+        // to `MAX_PERMITTED_DATA_INCREASE` bytes over it, and no more.
+        self.write_data_len(0);
     }
 
-    /// Returns a raw pointer to the `RuntimeAccount` struct.
-    pub fn account_ptr(&self) -> *const RuntimeAccount {
-        self.raw as *const _
+    /// Resizes the account data to `new_len` bytes.
+    ///
+    /// The bytes past the old length are zeroed when the data grows. Bytes
+    /// past the new length are left untouched when it shrinks, and zeroed by
+    /// the runtime at the end of the instruction.
+    ///
+    /// Returns [`ProgramError::AccountBorrowFailed`] if the account data is
+    /// borrowed, and [`ProgramError::InvalidRealloc`] if `new_len` exceeds
+    /// the [original length](Self::original_data_len) by more than
+    /// [`MAX_PERMITTED_DATA_INCREASE`] bytes.
+    ///
+    /// # Important
+    ///
+    /// The account must hold enough lamports to stay rent exempt at its new
+    /// size.
+    #[inline]
+    pub fn resize(&mut self, new_len: usize) -> ProgramResult {
+        if new_len.saturating_sub(self.original_data_len()) > MAX_PERMITTED_DATA_INCREASE {
+            return Err(ProgramError::InvalidRealloc);
+        }
+        self.check_borrow_mut()?;
+
+        // SAFETY: The account data is not borrowed, and the increase over the
+        // original length is at most `MAX_PERMITTED_DATA_INCREASE`.
+        unsafe { self.set_data_len(new_len) };
+
+        Ok(())
     }
 
-    /// Returns a mutable raw pointer to the `RuntimeAccount` struct.
-    pub fn account_mut_ptr(&mut self) -> *mut RuntimeAccount {
-        self.raw
+    /// Sets the length of the account data, zeroing the bytes past the old
+    /// length, and updates the difference with the original length.
+    ///
+    /// # Safety
+    ///
+    /// The account data must not be borrowed, and the runtime must have
+    /// serialized at least `new_len` bytes of data region for the account,
+    /// i.e. the increase over the original length is at most
+    /// [`MAX_PERMITTED_DATA_INCREASE`].
+    #[inline]
+    pub(crate) unsafe fn set_data_len(&mut self, new_len: usize) {
+        let old_len = self.data_len();
+        if new_len > old_len {
+            write_bytes(self.data_mut_ptr().add(old_len), 0, new_len - old_len);
+        }
+        self.write_data_len(new_len);
+    }
+
+    /// Writes the length of the account data and the difference with the
+    /// original length.
+    #[inline(always)]
+    unsafe fn write_data_len(&mut self, new_len: usize) {
+        let delta = new_len as i64 - self.original_data_len() as i64;
+        write(self.field_ptr::<i32>(L::RESIZE_DELTA_OFFSET), delta as i32);
+        write(self.field_ptr::<u64>(L::DATA_LEN_OFFSET), new_len as u64);
+    }
+
+    /// Returns a pointer to the borrow state of the account.
+    #[inline(always)]
+    pub(crate) const fn borrow_state_ptr(&self) -> *mut u8 {
+        self.field_ptr(L::BORROW_STATE_OFFSET)
     }
 
     /// Returns the memory address of the account data.
@@ -401,7 +614,7 @@ impl AccountView {
     #[inline(always)]
     pub const fn data_ptr(&self) -> *const u8 {
         // SAFETY: The `raw` pointer is guaranteed to be valid.
-        unsafe { (self.raw as *const u8).add(size_of::<RuntimeAccount>()) }
+        unsafe { (self.raw as *const u8).add(L::DATA_OFFSET) }
     }
 
     /// Returns the memory address of the account data.
@@ -416,20 +629,121 @@ impl AccountView {
     #[inline(always)]
     pub fn data_mut_ptr(&mut self) -> *mut u8 {
         // SAFETY: The `raw` pointer is guaranteed to be valid.
-        unsafe { (self.raw as *mut u8).add(size_of::<RuntimeAccount>()) }
+        unsafe { self.raw.add(L::DATA_OFFSET) }
     }
 }
 
 /// Allow `AccountView` to be used as a reference to itself
 /// for convenience.
-impl AsRef<AccountView> for AccountView {
+impl<L: AccountLayout> AsRef<AccountView<L>> for AccountView<L> {
     #[inline(always)]
-    fn as_ref(&self) -> &AccountView {
+    fn as_ref(&self) -> &AccountView<L> {
         self
     }
 }
 
+/// Find the account at `address` in `accounts`.
+///
+/// Addresses are compared with [`address_eq`], which is cheaper than the
+/// `PartialEq` implementation of [`Address`].
+#[inline]
+pub fn find_account<'a, L: AccountLayout>(
+    accounts: &'a [AccountView<L>],
+    address: &Address,
+) -> Option<&'a AccountView<L>> {
+    accounts
+        .iter()
+        .find(|account| address_eq(account.address(), address))
+}
+
+/// Returns whether the accounts `a` and `b` hold the same data.
+///
+/// If `a` and `b` are views of the same account, e.g. a duplicate account of
+/// the instruction, this returns `true` without borrowing the data, even if it
+/// is mutably borrowed. Otherwise, the data of both accounts is borrowed for
+/// the comparison, failing if either is already mutably borrowed.
+#[inline]
+pub fn accounts_data_eq<L: AccountLayout>(
+    a: &AccountView<L>,
+    b: &AccountView<L>,
+) -> Result<bool, ProgramError> {
+    if a.raw == b.raw {
+        return Ok(true);
+    }
+    let a = a.try_borrow()?;
+    let b = b.try_borrow()?;
+    Ok(bytes_eq(&a, &b))
+}
+
+/// Immutably borrows the data of all `accounts`, or none of them.
+///
+/// An account may appear several times, each entry then takes one of its
+/// immutable borrows. All the borrows are checked before any is taken, so on
+/// failure the borrow state of every account is left untouched.
+///
+/// # Errors
+///
+/// Returns [`ProgramError::AccountBorrowFailed`] if an account is mutably
+/// borrowed, or has fewer immutable borrows available than its entries.
+#[inline]
+pub fn borrow_many<'a, L: AccountLayout, const N: usize>(
+    accounts: [&'a AccountView<L>; N],
+) -> Result<[Ref<'a, [u8]>; N], ProgramError> {
+    for (i, account) in accounts.iter().enumerate() {
+        // Each account is checked at its first entry, for all its entries.
+        if accounts[..i].iter().any(|other| other.raw == account.raw) {
+            continue;
+        }
+        let entries = accounts[i..]
+            .iter()
+            .filter(|other| other.raw == account.raw)
+            .count();
+        // Taking a borrow must leave at least one available, as for
+        // `check_borrow`, since `0` marks a mutable borrow.
+        //
+        // SAFETY: The `raw` pointer is guaranteed to be valid.
+        if usize::from(unsafe { *account.borrow_state_ptr() }) <= entries {
+            return Err(ProgramError::AccountBorrowFailed);
+        }
+    }
+    // SAFETY: Every account has an immutable borrow available for each of its
+    // entries.
+    Ok(accounts.map(|account| unsafe { account.take_borrow() }))
+}
+
+/// Mutably borrows the data of all `accounts`, or none of them.
+///
+/// All the borrows are checked before any is taken, so on failure the borrow
+/// state of every account is left untouched.
+///
+/// # Errors
+///
+/// Returns [`ProgramError::AccountBorrowFailed`] if an account is already
+/// borrowed in any form, or appears more than once in `accounts`.
+#[inline]
+pub fn borrow_many_mut<'a, L: AccountLayout, const N: usize>(
+    accounts: [&'a AccountView<L>; N],
+) -> Result<[RefMut<'a, [u8]>; N], ProgramError> {
+    for (i, account) in accounts.iter().enumerate() {
+        account.check_borrow_mut()?;
+        if accounts[..i].iter().any(|other| other.raw == account.raw) {
+            return Err(ProgramError::AccountBorrowFailed);
+        }
+    }
+    // SAFETY: The accounts are distinct and none of them is borrowed.
+    Ok(accounts.map(|account| unsafe { account.take_borrow_mut() }))
+}
+
+/// Compares `a` and `b` with `sol_memcmp`, which is cheaper on chain than a
+/// byte by byte comparison.
+#[inline(always)]
+fn bytes_eq(a: &[u8], b: &[u8]) -> bool {
+    // SAFETY: Both slices are `a.len()` bytes long.
+    a.len() == b.len() && unsafe { sol_memcmp(a, b, a.len()) } == 0
+}
+
 /// Reference to account data with checked borrow rules.
+#[must_use = "the account data stays borrowed until the `Ref` is dropped"]
 #[derive(Debug)]
 pub struct Ref<'a, T: ?Sized> {
     value: NonNull<T>,
@@ -509,10 +823,14 @@ impl<T: ?Sized> Drop for Ref<'_, T> {
     fn drop(&mut self) {
         // Increment the available borrow count.
         unsafe { *self.state.as_mut() += 1 };
+
+        #[cfg(feature = "borrow-debug")]
+        borrow_debug::record_release(self.state.as_ptr(), false);
     }
 }
 
 /// Mutable reference to account data with checked borrow rules.
+#[must_use = "the account data stays borrowed until the `RefMut` is dropped"]
 #[derive(Debug)]
 pub struct RefMut<'a, T: ?Sized> {
     value: NonNull<T>,
@@ -597,6 +915,9 @@ impl<T: ?Sized> Drop for RefMut<'_, T> {
     fn drop(&mut self) {
         // Reset the borrow state.
         unsafe { *self.state.as_mut() = NOT_BORROWED };
+
+        #[cfg(feature = "borrow-debug")]
+        borrow_debug::record_release(self.state.as_ptr(), true);
     }
 }
 
@@ -690,7 +1011,7 @@ mod tests {
         let account = data.as_mut_ptr() as *mut RuntimeAccount;
         unsafe { (*account).data_len = 8 };
 
-        let mut account_view = AccountView { raw: account };
+        let mut account_view = unsafe { AccountView::new_unchecked(account) };
 
         // Check that we can borrow data and lamports.
         assert!(account_view.check_borrow().is_ok());
@@ -744,7 +1065,400 @@ mod tests {
         assert!(account_view.check_borrow().is_ok());
         assert!(account_view.check_borrow_mut().is_ok());
 
-        let borrow_state = unsafe { (*account_view.raw).borrow_state };
+        let borrow_state = unsafe { *account_view.borrow_state_ptr() };
         assert!(borrow_state == NOT_BORROWED);
     }
+
+    #[test]
+    fn test_find_account() {
+        let mut data = [[0u64; size_of::<RuntimeAccount>() / size_of::<u64>()]; 3];
+        for (seed, data) in data.iter_mut().enumerate() {
+            let account = data.as_mut_ptr() as *mut RuntimeAccount;
+            unsafe {
+                (*account).borrow_state = NOT_BORROWED;
+                (*account).address = Address::new_from_array([seed as u8; 32]);
+            }
+        }
+        let accounts = data
+            .each_mut()
+            .map(|data| unsafe { AccountView::new_unchecked(data.as_mut_ptr() as *mut _) });
+
+        for (seed, account) in accounts.iter().enumerate() {
+            let address = Address::new_from_array([seed as u8; 32]);
+            let found = find_account(&accounts, &address).unwrap();
+            assert_eq!(found.account_ptr(), account.account_ptr());
+        }
+        // only the last 8 bytes differ
+        let mut address = [2; 32];
+        address[31] = 3;
+        assert!(find_account(&accounts, &Address::new_from_array(address)).is_none());
+        assert!(find_account::<LayoutV1>(&[], &Address::default()).is_none());
+    }
+
+    #[test]
+    fn test_with_data_scoped() {
+        // 8-bytes aligned account data + 8 bytes of trailing data.
+        let mut data = [0u64; size_of::<RuntimeAccount>() / size_of::<u64>() + 1];
+        data[0] = NOT_BORROWED as u64;
+
+        let account = data.as_mut_ptr() as *mut RuntimeAccount;
+        unsafe { (*account).data_len = 8 };
+
+        let mut account_view = unsafe { AccountView::new_unchecked(account) };
+        let mut other_view = unsafe { AccountView::new_unchecked(account) };
+
+        account_view
+            .with_data_mut_scoped(|data| data[0] = 1)
+            .unwrap();
+
+        // Nested shared borrows are allowed, a mutable borrow is not.
+        let result = account_view.with_data_scoped(|data| {
+            let inner = other_view.with_data_scoped(|inner| inner[0]);
+            assert_eq!(inner, Ok(1));
+            assert_eq!(
+                other_view.with_data_mut_scoped(|_| ()),
+                Err(ProgramError::AccountBorrowFailed)
+            );
+            data[0]
+        });
+        assert_eq!(result, Ok(1));
+        assert_eq!(
+            unsafe { (*account).borrow_state },
+            NOT_BORROWED,
+            "borrow released after the closure returns"
+        );
+
+        // A shared borrow is rejected while the data is mutably borrowed.
+        let result = account_view.with_data_mut_scoped(|data| {
+            assert_eq!(
+                other_view.with_data_scoped(|_| ()),
+                Err(ProgramError::AccountBorrowFailed)
+            );
+            data[0] = 2;
+        });
+        assert_eq!(result, Ok(()));
+        assert_eq!(other_view.with_data_scoped(|data| data[0]), Ok(2));
+    }
+
+    #[test]
+    fn test_data_eq() {
+        // 8-bytes aligned account data + 8 bytes of trailing data.
+        let mut data = [0u64; size_of::<RuntimeAccount>() / size_of::<u64>() + 1];
+        data[0] = NOT_BORROWED as u64;
+
+        let account = data.as_mut_ptr() as *mut RuntimeAccount;
+        unsafe { (*account).data_len = 4 };
+
+        let mut account_view = unsafe { AccountView::new_unchecked(account) };
+        account_view
+            .with_data_mut_scoped(|data| data.copy_from_slice(&[1, 2, 3, 4]))
+            .unwrap();
+
+        assert_eq!(account_view.data_eq(&[1, 2, 3, 4]), Ok(true));
+        assert_eq!(account_view.data_eq(&[1, 2, 3, 5]), Ok(false));
+        // differing lengths, including the trailing bytes
+        assert_eq!(account_view.data_eq(&[1, 2, 3]), Ok(false));
+        assert_eq!(account_view.data_eq(&[1, 2, 3, 4, 0]), Ok(false));
+
+        assert_eq!(account_view.data_starts_with(&[]), Ok(true));
+        assert_eq!(account_view.data_starts_with(&[1, 2]), Ok(true));
+        assert_eq!(account_view.data_starts_with(&[1, 2, 3, 4]), Ok(true));
+        assert_eq!(account_view.data_starts_with(&[2]), Ok(false));
+        assert_eq!(account_view.data_starts_with(&[1, 2, 3, 4, 0]), Ok(false));
+        assert_eq!(unsafe { (*account).borrow_state }, NOT_BORROWED);
+
+        // the comparisons fail while the data is mutably borrowed
+        let other_view = unsafe { AccountView::new_unchecked(account) };
+        let ref_mut = account_view.try_borrow_mut().unwrap();
+        assert_eq!(
+            other_view.data_eq(&[1, 2, 3, 4]),
+            Err(ProgramError::AccountBorrowFailed)
+        );
+        assert_eq!(
+            other_view.data_starts_with(&[1]),
+            Err(ProgramError::AccountBorrowFailed)
+        );
+        drop(ref_mut);
+    }
+
+    #[test]
+    fn test_accounts_data_eq() {
+        let mut data = [[0u64; size_of::<RuntimeAccount>() / size_of::<u64>() + 1]; 3];
+        for (len, data) in [4, 4, 3].into_iter().zip(data.iter_mut()) {
+            let account = data.as_mut_ptr() as *mut RuntimeAccount;
+            unsafe {
+                (*account).borrow_state = NOT_BORROWED;
+                (*account).data_len = len;
+            }
+        }
+        let mut accounts = data
+            .each_mut()
+            .map(|data| unsafe { AccountView::new_unchecked(data.as_mut_ptr() as *mut _) });
+        for account in accounts.iter_mut() {
+            account
+                .with_data_mut_scoped(|data| data.copy_from_slice(&[1, 2, 3, 4][..data.len()]))
+                .unwrap();
+        }
+        let [mut a, b, c] = accounts;
+
+        assert_eq!(accounts_data_eq(&a, &b), Ok(true));
+        // differing lengths, with the same prefix
+        assert_eq!(accounts_data_eq(&a, &c), Ok(false));
+        b.clone().with_data_mut_scoped(|data| data[3] = 5).unwrap();
+        assert_eq!(accounts_data_eq(&a, &b), Ok(false));
+        assert_eq!(unsafe { *a.borrow_state_ptr() }, NOT_BORROWED);
+        assert_eq!(unsafe { *b.borrow_state_ptr() }, NOT_BORROWED);
+
+        // views of the same account are equal without borrowing the data
+        let alias = unsafe { AccountView::new_unchecked(a.account_mut_ptr()) };
+        let ref_mut = a.try_borrow_mut().unwrap();
+        assert_eq!(accounts_data_eq(&alias, &alias.clone()), Ok(true));
+        // other accounts cannot be compared with a mutably borrowed one
+        assert_eq!(
+            accounts_data_eq(&alias, &b),
+            Err(ProgramError::AccountBorrowFailed)
+        );
+        assert_eq!(
+            accounts_data_eq(&b, &alias),
+            Err(ProgramError::AccountBorrowFailed)
+        );
+        assert_eq!(unsafe { *b.borrow_state_ptr() }, NOT_BORROWED);
+        drop(ref_mut);
+    }
+
+    #[test]
+    #[allow(clippy::clone_on_copy)]
+    fn test_borrow_many_mut() {
+        let mut data = [[0u64; size_of::<RuntimeAccount>() / size_of::<u64>() + 1]; 3];
+        for data in data.iter_mut() {
+            let account = data.as_mut_ptr() as *mut RuntimeAccount;
+            unsafe {
+                (*account).borrow_state = NOT_BORROWED;
+                (*account).data_len = 4;
+            }
+        }
+        let [a, b, c] = data
+            .each_mut()
+            .map(|data| unsafe { AccountView::new_unchecked(data.as_mut_ptr() as *mut _) });
+        let borrow_states = || [&a, &b, &c].map(|account| unsafe { *account.borrow_state_ptr() });
+
+        // the guards are independent
+        let [mut data_a, mut data_c] = borrow_many_mut([&a, &c]).unwrap();
+        data_a.copy_from_slice(&[1; 4]);
+        data_c.copy_from_slice(&[3; 4]);
+        assert_eq!(borrow_states(), [0, NOT_BORROWED, 0]);
+        drop(data_a);
+        assert_eq!(borrow_states(), [NOT_BORROWED, NOT_BORROWED, 0]);
+        drop(data_c);
+        assert_eq!(a.data_eq(&[1; 4]), Ok(true));
+        assert_eq!(c.data_eq(&[3; 4]), Ok(true));
+
+        // aliased accounts fail without taking any borrow
+        let alias = a.clone();
+        assert!(matches!(
+            borrow_many_mut([&b, &a, &alias]),
+            Err(ProgramError::AccountBorrowFailed)
+        ));
+        assert_eq!(borrow_states(), [NOT_BORROWED; 3]);
+
+        // a borrowed account fails without taking the other borrows
+        let data_c = c.try_borrow().unwrap();
+        assert!(matches!(
+            borrow_many_mut([&a, &b, &c]),
+            Err(ProgramError::AccountBorrowFailed)
+        ));
+        assert_eq!(
+            borrow_states(),
+            [NOT_BORROWED, NOT_BORROWED, NOT_BORROWED - 1]
+        );
+        drop(data_c);
+
+        assert!(borrow_many_mut::<LayoutV1, 0>([]).is_ok());
+    }
+
+    #[test]
+    #[allow(clippy::clone_on_copy)]
+    fn test_borrow_many() {
+        let mut data = [[0u64; size_of::<RuntimeAccount>() / size_of::<u64>() + 1]; 2];
+        for data in data.iter_mut() {
+            let account = data.as_mut_ptr() as *mut RuntimeAccount;
+            unsafe {
+                (*account).borrow_state = NOT_BORROWED;
+                (*account).data_len = 4;
+            }
+        }
+        let [a, b] = data
+            .each_mut()
+            .map(|data| unsafe { AccountView::new_unchecked(data.as_mut_ptr() as *mut _) });
+        let borrow_states = || [&a, &b].map(|account| unsafe { *account.borrow_state_ptr() });
+
+        // an account may appear several times
+        let alias = a.clone();
+        let refs = borrow_many([&a, &b, &alias]).unwrap();
+        assert_eq!(borrow_states(), [NOT_BORROWED - 2, NOT_BORROWED - 1]);
+        drop(refs);
+        assert_eq!(borrow_states(), [NOT_BORROWED; 2]);
+
+        // a mutably borrowed account fails without taking the other borrows
+        let mut b_mut = b.clone();
+        let data_b = b_mut.try_borrow_mut().unwrap();
+        assert!(matches!(
+            borrow_many([&a, &b]),
+            Err(ProgramError::AccountBorrowFailed)
+        ));
+        assert_eq!(borrow_states(), [NOT_BORROWED, 0]);
+        drop(data_b);
+
+        // the entries of an account must not use up its immutable borrows
+        unsafe { *a.borrow_state_ptr() = 3 };
+        assert!(matches!(
+            borrow_many([&b, &a, &alias, &a]),
+            Err(ProgramError::AccountBorrowFailed)
+        ));
+        assert_eq!(borrow_states(), [3, NOT_BORROWED]);
+        let refs = borrow_many([&b, &a, &alias]).unwrap();
+        assert_eq!(borrow_states(), [1, NOT_BORROWED - 1]);
+        drop(refs);
+        assert_eq!(borrow_states(), [3, NOT_BORROWED]);
+    }
+
+    #[test]
+    fn test_with_data_panic_releases_borrow() {
+        extern crate std;
+        use std::panic::{catch_unwind, AssertUnwindSafe};
+
+        let mut data = [0u64; size_of::<RuntimeAccount>() / size_of::<u64>() + 1];
+        data[0] = NOT_BORROWED as u64;
+
+        let account = data.as_mut_ptr() as *mut RuntimeAccount;
+        unsafe { (*account).data_len = 8 };
+
+        let mut account_view = unsafe { AccountView::new_unchecked(account) };
+
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            account_view.with_data_scoped(|_| panic!("shared")).unwrap();
+        }));
+        assert!(result.is_err());
+        assert_eq!(unsafe { (*account).borrow_state }, NOT_BORROWED);
+
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            account_view
+                .with_data_mut_scoped(|_| panic!("mutable"))
+                .unwrap();
+        }));
+        assert!(result.is_err());
+        assert_eq!(unsafe { (*account).borrow_state }, NOT_BORROWED);
+
+        assert!(account_view.check_borrow_mut().is_ok());
+    }
+
+    #[test]
+    #[allow(clippy::clone_on_copy)]
+    fn test_layout_v1_fields() {
+        let mut data = [0u64; size_of::<RuntimeAccount>() / size_of::<u64>() + 1];
+        let account = data.as_mut_ptr() as *mut RuntimeAccount;
+        let address = Address::new_from_array([1; 32]);
+        let owner = Address::new_from_array([2; 32]);
+        unsafe {
+            (*account).borrow_state = NOT_BORROWED;
+            (*account).is_signer = 1;
+            (*account).executable = 1;
+            (*account).address = address.clone();
+            (*account).owner = owner.clone();
+            (*account).lamports = 42;
+            (*account).data_len = 8;
+        }
+
+        let mut account_view = unsafe { AccountView::new_unchecked(account) };
+        assert_eq!(account_view.address(), &address);
+        assert_eq!(account_view.owner(), &owner);
+        assert!(account_view.owned_by(&owner));
+        assert!(account_view.is_signer());
+        assert!(!account_view.is_writable());
+        assert!(account_view.executable());
+        assert_eq!(account_view.lamports(), 42);
+        assert_eq!(account_view.data_len(), 8);
+        assert_eq!(account_view.account_ptr(), account as *const _);
+        assert_eq!(account_view.data_ptr(), unsafe {
+            account.add(1) as *const u8
+        });
+
+        account_view.set_lamports(7);
+        let new_owner = Address::new_from_array([3; 32]);
+        unsafe { account_view.assign(&new_owner) };
+        assert_eq!(unsafe { (*account).lamports }, 7);
+        assert!(unsafe { (*account).owner == new_owner });
+
+        account_view
+            .with_data_mut_scoped(|data| data.fill(9))
+            .unwrap();
+        account_view.close().unwrap();
+        unsafe {
+            assert_eq!((*account).lamports, 0);
+            assert_eq!((*account).data_len, 0);
+            assert!((*account).owner == Address::new_from_array([0; 32]));
+            assert!((*account).address == address);
+            assert_eq!((*account).is_signer, 1);
+        }
+        // the data itself is left untouched
+        assert_eq!(data[data.len() - 1], u64::from_ne_bytes([9; 8]));
+    }
+
+    #[cfg(feature = "abi-v2")]
+    #[test]
+    #[allow(clippy::clone_on_copy)]
+    fn test_layout_v2_roundtrip() {
+        // 8-bytes aligned header + 16 bytes of trailing data.
+        let mut data = [0u64; LayoutV2::DATA_OFFSET / size_of::<u64>() + 2];
+        let bytes = data.as_mut_ptr() as *mut u8;
+        let header = unsafe { from_raw_parts_mut(bytes, LayoutV2::DATA_OFFSET) };
+        header[LayoutV2::ADDRESS_OFFSET..][..32].fill(1);
+        header[LayoutV2::OWNER_OFFSET..][..32].fill(2);
+        header[LayoutV2::LAMPORTS_OFFSET..][..8].copy_from_slice(&42u64.to_ne_bytes());
+        header[LayoutV2::DATA_LEN_OFFSET..][..8].copy_from_slice(&16u64.to_ne_bytes());
+        header[LayoutV2::BORROW_STATE_OFFSET] = NOT_BORROWED;
+        header[LayoutV2::IS_WRITABLE_OFFSET] = 1;
+
+        let mut account_view = unsafe { AccountView::<LayoutV2>::from_raw(bytes) };
+        assert_eq!(account_view.address(), &Address::new_from_array([1; 32]));
+        assert!(account_view.owned_by(&Address::new_from_array([2; 32])));
+        assert!(!account_view.is_signer());
+        assert!(account_view.is_writable());
+        assert!(!account_view.executable());
+        assert_eq!(account_view.lamports(), 42);
+        assert_eq!(account_view.data_len(), 16);
+        assert_eq!(account_view.data_ptr(), unsafe {
+            bytes.add(LayoutV2::DATA_OFFSET) as *const u8
+        });
+
+        // writes land at the V2 offsets
+        account_view.set_lamports(u64::MAX);
+        unsafe { account_view.assign(&Address::new_from_array([3; 32])) };
+        account_view
+            .with_data_mut_scoped(|data| data.copy_from_slice(&[5; 16]))
+            .unwrap();
+        let borrowed = account_view.try_borrow().unwrap();
+        let other_view = account_view.clone();
+        assert_eq!(
+            unsafe { *bytes.add(LayoutV2::BORROW_STATE_OFFSET) },
+            NOT_BORROWED - 1
+        );
+        assert_eq!(
+            other_view.check_borrow_mut(),
+            Err(ProgramError::AccountBorrowFailed)
+        );
+        assert_eq!(&*borrowed, &[5; 16]);
+        drop(borrowed);
+
+        let header = unsafe { from_raw_parts_mut(bytes, LayoutV2::DATA_OFFSET) };
+        assert_eq!(header[LayoutV2::LAMPORTS_OFFSET..][..8], [0xff; 8]);
+        assert_eq!(header[LayoutV2::OWNER_OFFSET..][..32], [3; 32]);
+        assert_eq!(header[LayoutV2::BORROW_STATE_OFFSET], NOT_BORROWED);
+
+        account_view.close().unwrap();
+        let header = unsafe { from_raw_parts_mut(bytes, LayoutV2::DATA_OFFSET) };
+        assert_eq!(header[LayoutV2::OWNER_OFFSET..][..48], [0; 48]);
+        assert_eq!(header[LayoutV2::ADDRESS_OFFSET..][..32], [1; 32]);
+        assert_eq!(header[LayoutV2::IS_WRITABLE_OFFSET], 1);
+    }
 }
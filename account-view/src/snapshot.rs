@@ -0,0 +1,234 @@
+//! Snapshots of accounts, for rolling back speculative changes.
+//!
+//! A program trying a mutation that may fail halfway takes an
+//! [`AccountSnapshot`] of the account first, and restores it if the mutation
+//! fails. [`with_rollback`] does both around a closure.
+//!
+//! # Compute units
+//!
+//! Taking and restoring a snapshot each copy the account data once. On SBF
+//! the copy is done by the `sol_memcpy_` syscall, which costs one compute
+//! unit per 250 bytes with a minimum of 10. The snapshot data is allocated
+//! on the heap, and the default bump allocator of programs never frees it,
+//! so every snapshot uses heap for the rest of the instruction: the
+//! `max_data_len` of [`AccountView::snapshot`] bounds this.
+
+use {
+    crate::{AccountLayout, AccountView},
+    alloc::vec::Vec,
+    solana_address::{address_eq, Address},
+    solana_program_error::{ProgramError, ProgramResult},
+};
+
+/// The data, lamports and owner of an account at some point of an
+/// instruction.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AccountSnapshot {
+    address: Address,
+    owner: Address,
+    lamports: u64,
+    data: Vec<u8>,
+}
+
+impl<L: AccountLayout> AccountView<L> {
+    /// Takes a snapshot of the data, lamports and owner of the account.
+    ///
+    /// Returns [`ProgramError::InvalidArgument`] if the data is longer than
+    /// `max_data_len` bytes, and [`ProgramError::AccountBorrowFailed`] if the
+    /// data is mutably borrowed.
+    #[allow(clippy::clone_on_copy)]
+    pub fn snapshot(&self, max_data_len: usize) -> Result<AccountSnapshot, ProgramError> {
+        if self.data_len() > max_data_len {
+            return Err(ProgramError::InvalidArgument);
+        }
+        Ok(AccountSnapshot {
+            address: self.address().clone(),
+            owner: self.owner().clone(),
+            lamports: self.lamports(),
+            data: self.with_data_scoped(|data| data.to_vec())?,
+        })
+    }
+}
+
+impl AccountSnapshot {
+    /// Length of the data of the account when the snapshot was taken.
+    #[inline]
+    pub fn data_len(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Writes the data, lamports and owner of the snapshot back to
+    /// `account`, resizing its data to the length of the snapshot.
+    ///
+    /// Returns [`ProgramError::InvalidArgument`] if `account` is not the
+    /// account of the snapshot, and [`ProgramError::AccountBorrowFailed`] if
+    /// its data is borrowed. The account is left unchanged on error.
+    ///
+    /// # Important
+    ///
+    /// Restoring the length is always permitted, since the account had this
+    /// length when the snapshot was taken, but the account must hold enough
+    /// lamports to stay rent exempt at the restored size, as with any resize.
+    #[allow(clippy::clone_on_copy)]
+    pub fn restore<L: AccountLayout>(&self, account: &mut AccountView<L>) -> ProgramResult {
+        if !address_eq(account.address(), &self.address) {
+            return Err(ProgramError::InvalidArgument);
+        }
+        account.check_borrow_mut()?;
+
+        if account.data_len() != self.data.len() {
+            // SAFETY: The account data is not borrowed, and the data region
+            // held `self.data.len()` bytes when the snapshot was taken.
+            unsafe { account.set_data_len(self.data.len()) };
+        }
+        account.with_data_mut_scoped(|data| data.copy_from_slice(&self.data))?;
+        account.set_lamports(self.lamports);
+        // SAFETY: `account` is borrowed mutably, so there is no reference to
+        // its owner.
+        unsafe { account.assign(&self.owner.clone()) };
+
+        Ok(())
+    }
+}
+
+/// Runs `f` on `account`, restoring the account as it was before if `f`
+/// returns an error.
+///
+/// The error of `f` is returned after the account is restored. Returns the
+/// errors of [`AccountView::snapshot`] without running `f`, and the errors
+/// of [`AccountSnapshot::restore`] instead of the error of `f`, e.g. if `f`
+/// leaves the account data borrowed.
+pub fn with_rollback<L: AccountLayout, R>(
+    account: &mut AccountView<L>,
+    max_data_len: usize,
+    f: impl FnOnce(&mut AccountView<L>) -> Result<R, ProgramError>,
+) -> Result<R, ProgramError> {
+    let snapshot = account.snapshot(max_data_len)?;
+    f(account).or_else(|error| {
+        snapshot.restore(account)?;
+        Err(error)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{RuntimeAccount, MAX_PERMITTED_DATA_INCREASE, NOT_BORROWED},
+        core::mem::size_of,
+    };
+
+    /// Words of account data available in the test buffers.
+    const CAPACITY: usize = 4;
+
+    const BUFFER_LEN: usize = size_of::<RuntimeAccount>() / size_of::<u64>() + CAPACITY;
+
+    fn account(buffer: &mut [u64; BUFFER_LEN], data: &[u8]) -> AccountView {
+        let account = buffer.as_mut_ptr() as *mut RuntimeAccount;
+        unsafe {
+            (*account).borrow_state = NOT_BORROWED;
+            (*account).address = Address::new_from_array([1; 32]);
+            (*account).owner = Address::new_from_array([2; 32]);
+            (*account).lamports = 100;
+            (*account).data_len = data.len() as u64;
+        }
+        let mut view = unsafe { AccountView::new_unchecked(account) };
+        view.with_data_mut_scoped(|d| d.copy_from_slice(data))
+            .unwrap();
+        view
+    }
+
+    /// Grows the data by 4 bytes and changes every field of the account,
+    /// then fails.
+    fn mutate_and_fail(account: &mut AccountView) -> Result<(), ProgramError> {
+        account.set_lamports(50);
+        unsafe {
+            account.assign(&Address::new_from_array([3; 32]));
+            account.set_data_len(account.data_len() + 4);
+        }
+        account.with_data_mut_scoped(|data| data.fill(0xff))?;
+        Err(ProgramError::Custom(1))
+    }
+
+    #[test]
+    fn test_rollback() {
+        let mut buffer = [0; BUFFER_LEN];
+        let mut view = account(&mut buffer, &[1, 2, 3, 4]);
+
+        assert_eq!(
+            with_rollback(&mut view, 4, mutate_and_fail),
+            Err(ProgramError::Custom(1))
+        );
+        assert_eq!(view.data_len(), 4);
+        assert_eq!(
+            view.with_data_scoped(|d| d.to_vec()),
+            Ok([1, 2, 3, 4].to_vec())
+        );
+        assert_eq!(view.lamports(), 100);
+        assert!(view.owned_by(&Address::new_from_array([2; 32])));
+
+        // restoring a longer snapshot after a shrink
+        let snapshot = view.snapshot(MAX_PERMITTED_DATA_INCREASE).unwrap();
+        view.close().unwrap();
+        assert_eq!(view.data_len(), 0);
+        snapshot.restore(&mut view).unwrap();
+        assert_eq!(view.snapshot(4), Ok(snapshot));
+    }
+
+    #[test]
+    fn test_success() {
+        let mut buffer = [0; BUFFER_LEN];
+        let mut view = account(&mut buffer, &[1, 2, 3, 4]);
+
+        let result = with_rollback(&mut view, 4, |account| {
+            let _ = mutate_and_fail(account);
+            Ok(7)
+        });
+        assert_eq!(result, Ok(7));
+        assert_eq!(view.data_len(), 8);
+        assert_eq!(
+            view.with_data_scoped(|d| d.to_vec()),
+            Ok([0xff; 8].to_vec())
+        );
+        assert_eq!(view.lamports(), 50);
+        assert!(view.owned_by(&Address::new_from_array([3; 32])));
+    }
+
+    #[test]
+    #[allow(clippy::clone_on_copy)]
+    fn test_guards() {
+        let mut buffer = [0; BUFFER_LEN];
+        let mut view = account(&mut buffer, &[1, 2, 3, 4]);
+
+        // the closure is not run if the data is too long to snapshot
+        assert_eq!(
+            with_rollback(&mut view, 3, |_| -> Result<(), _> { unreachable!() }),
+            Err(ProgramError::InvalidArgument)
+        );
+        assert_eq!(view.snapshot(3), Err(ProgramError::InvalidArgument));
+
+        let snapshot = view.snapshot(4).unwrap();
+        assert_eq!(snapshot.data_len(), 4);
+        view.set_lamports(1);
+
+        let other_view = view.clone();
+        let borrowed = other_view.try_borrow().unwrap();
+        assert_eq!(
+            snapshot.restore(&mut view),
+            Err(ProgramError::AccountBorrowFailed)
+        );
+        assert_eq!(view.lamports(), 1);
+        drop(borrowed);
+
+        let mut other_buffer = [0; BUFFER_LEN];
+        let mut other = account(&mut other_buffer, &[1, 2, 3, 4]);
+        unsafe { (*other.account_mut_ptr()).address = Address::new_from_array([9; 32]) };
+        assert_eq!(
+            snapshot.restore(&mut other),
+            Err(ProgramError::InvalidArgument)
+        );
+
+        snapshot.restore(&mut view).unwrap();
+        assert_eq!(view.lamports(), 100);
+    }
+}
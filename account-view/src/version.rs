@@ -0,0 +1,296 @@
+//! Versioned prologue of account data.
+//!
+//! Programs that evolve the layout of their accounts can prefix the data with
+//! a [`HEADER_LEN`]-byte header: a `u8` version, followed by the little-endian
+//! `u32` length of the data after the header. [`migrate`] upgrades accounts
+//! written by older versions of the program in place, one version at a time.
+
+use {
+    crate::{layout::AccountLayout, AccountView},
+    solana_program_error::{ProgramError, ProgramResult},
+};
+
+/// Length of the versioned header.
+pub const HEADER_LEN: usize = 5;
+
+/// Upgrades the account data from one version to the next.
+///
+/// The migrator receives the whole account data, header included, and is
+/// responsible for updating the length in the header if it changes.
+pub type Migrator = fn(&mut [u8]) -> ProgramResult;
+
+/// Reads the version of the account data.
+///
+/// Returns [`ProgramError::AccountDataTooSmall`] if the data is empty.
+#[inline]
+pub fn read_version(data: &[u8]) -> Result<u8, ProgramError> {
+    data.first()
+        .copied()
+        .ok_or(ProgramError::AccountDataTooSmall)
+}
+
+/// Writes the version of the account data.
+///
+/// Returns [`ProgramError::AccountDataTooSmall`] if the data is empty.
+#[inline]
+pub fn write_version(data: &mut [u8], version: u8) -> ProgramResult {
+    let byte = data.first_mut().ok_or(ProgramError::AccountDataTooSmall)?;
+    *byte = version;
+    Ok(())
+}
+
+/// Reads the length of the data after the header.
+///
+/// Returns [`ProgramError::AccountDataTooSmall`] if the data is shorter than
+/// the header.
+#[inline]
+pub fn read_len(data: &[u8]) -> Result<u32, ProgramError> {
+    let bytes = data
+        .get(1..HEADER_LEN)
+        .ok_or(ProgramError::AccountDataTooSmall)?;
+    Ok(u32::from_le_bytes(bytes.try_into().unwrap()))
+}
+
+/// Writes the length of the data after the header.
+///
+/// Returns [`ProgramError::AccountDataTooSmall`] if the data is shorter than
+/// the header.
+#[inline]
+pub fn write_len(data: &mut [u8], len: u32) -> ProgramResult {
+    data.get_mut(1..HEADER_LEN)
+        .ok_or(ProgramError::AccountDataTooSmall)?
+        .copy_from_slice(&len.to_le_bytes());
+    Ok(())
+}
+
+/// Upgrades the account data from its stored version to `CURRENT`.
+///
+/// `migrators[v]` upgrades the data from version `v` to `v + 1`, so there must
+/// be exactly `CURRENT` migrators. Before running `migrators[v]`, the account
+/// data is zero-extended to `required_len[v]` bytes if it is shorter; it is
+/// never shrunk. The version is written after each successful step, so if a
+/// migrator fails, the error is returned and the version is left at the last
+/// step that succeeded. The failing migrator may still have modified the
+/// data.
+///
+/// Returns [`ProgramError::InvalidArgument`] if either table does not have
+/// `CURRENT` entries, [`ProgramError::InvalidAccountData`] if the stored
+/// version is newer than `CURRENT`, [`ProgramError::AccountBorrowFailed`] if
+/// the account data is borrowed, and [`ProgramError::InvalidRealloc`] if the
+/// data would grow by more than
+/// [`MAX_PERMITTED_DATA_INCREASE`](crate::MAX_PERMITTED_DATA_INCREASE) bytes over
+/// its [original length](AccountView::original_data_len).
+///
+/// # Important
+///
+/// The account must hold enough lamports to stay rent exempt at its new
+/// size.
+pub fn migrate<const CURRENT: u8, L: AccountLayout>(
+    account: &mut AccountView<L>,
+    migrators: &[Migrator],
+    required_len: &[usize],
+) -> ProgramResult {
+    if migrators.len() != CURRENT as usize || required_len.len() != CURRENT as usize {
+        return Err(ProgramError::InvalidArgument);
+    }
+    let version = account.with_data_scoped(read_version)??;
+    if version > CURRENT {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    for version in version..CURRENT {
+        let step = version as usize;
+        if required_len[step] > account.data_len() {
+            account.resize(required_len[step])?;
+        }
+        account.with_data_mut_scoped(|data| {
+            migrators[step](data)?;
+            write_version(data, version + 1)
+        })??;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{RuntimeAccount, MAX_PERMITTED_DATA_INCREASE, NOT_BORROWED},
+        core::mem::size_of,
+    };
+
+    /// Words of account data available past the header.
+    const CAPACITY: usize = 4;
+
+    fn account(
+        buffer: &mut [u64; size_of::<RuntimeAccount>() / size_of::<u64>() + CAPACITY],
+        data: &[u8],
+    ) -> AccountView {
+        let account = buffer.as_mut_ptr() as *mut RuntimeAccount;
+        unsafe {
+            (*account).borrow_state = NOT_BORROWED;
+            (*account).padding = [0; 4];
+            (*account).data_len = data.len() as u64;
+        }
+        let mut view = unsafe { AccountView::new_unchecked(account) };
+        view.with_data_mut_scoped(|d| d.copy_from_slice(data))
+            .unwrap();
+        view
+    }
+
+    fn add_one(data: &mut [u8]) -> ProgramResult {
+        data[HEADER_LEN] += 1;
+        Ok(())
+    }
+
+    fn double(data: &mut [u8]) -> ProgramResult {
+        data[HEADER_LEN] *= 2;
+        Ok(())
+    }
+
+    fn fail(_data: &mut [u8]) -> ProgramResult {
+        Err(ProgramError::Custom(7))
+    }
+
+    /// Appends a `u64` after the `u8` payload of version 0.
+    fn append_u64(data: &mut [u8]) -> ProgramResult {
+        assert_eq!(data.len(), HEADER_LEN + 9);
+        data[HEADER_LEN + 1..].copy_from_slice(&u64::MAX.to_le_bytes());
+        write_len(data, 9)
+    }
+
+    #[test]
+    fn test_header() {
+        let mut data = [0; HEADER_LEN];
+        write_version(&mut data, 3).unwrap();
+        write_len(&mut data, 0x0102_0304).unwrap();
+        assert_eq!(data, [3, 4, 3, 2, 1]);
+        assert_eq!(read_version(&data), Ok(3));
+        assert_eq!(read_len(&data), Ok(0x0102_0304));
+
+        assert_eq!(read_version(&[]), Err(ProgramError::AccountDataTooSmall));
+        assert_eq!(
+            write_version(&mut [], 1),
+            Err(ProgramError::AccountDataTooSmall)
+        );
+        assert_eq!(read_len(&data[..4]), Err(ProgramError::AccountDataTooSmall));
+        assert_eq!(
+            write_len(&mut data[..4], 1),
+            Err(ProgramError::AccountDataTooSmall)
+        );
+    }
+
+    #[test]
+    fn test_migrate_chain() {
+        let mut buffer = [0; size_of::<RuntimeAccount>() / size_of::<u64>() + CAPACITY];
+        let mut view = account(&mut buffer, &[0, 1, 0, 0, 0, 5]);
+
+        migrate::<2, _>(&mut view, &[add_one, double], &[0, 0]).unwrap();
+        assert_eq!(
+            view.with_data_scoped(|d| <[u8; 6]>::try_from(d).unwrap()),
+            Ok([2, 1, 0, 0, 0, 12])
+        );
+
+        // already current
+        migrate::<2, _>(&mut view, &[fail, fail], &[0, 0]).unwrap();
+
+        // starts from the stored version
+        view.with_data_mut_scoped(|d| d[0] = 1).unwrap();
+        migrate::<2, _>(&mut view, &[fail, double], &[0, 0]).unwrap();
+        assert_eq!(
+            view.with_data_scoped(|d| <[u8; 6]>::try_from(d).unwrap()),
+            Ok([2, 1, 0, 0, 0, 24])
+        );
+
+        assert_eq!(
+            migrate::<1, _>(&mut view, &[add_one], &[0]),
+            Err(ProgramError::InvalidAccountData)
+        );
+        assert_eq!(
+            migrate::<3, _>(&mut view, &[add_one, double], &[0, 0, 0]),
+            Err(ProgramError::InvalidArgument)
+        );
+        assert_eq!(
+            migrate::<2, _>(&mut view, &[add_one, double], &[0]),
+            Err(ProgramError::InvalidArgument)
+        );
+    }
+
+    #[test]
+    #[allow(clippy::clone_on_copy)]
+    fn test_migrate_failure() {
+        let mut buffer = [0; size_of::<RuntimeAccount>() / size_of::<u64>() + CAPACITY];
+        let mut view = account(&mut buffer, &[0, 1, 0, 0, 0, 5]);
+
+        assert_eq!(
+            migrate::<3, _>(&mut view, &[add_one, fail, double], &[0, 0, 0]),
+            Err(ProgramError::Custom(7))
+        );
+        assert_eq!(
+            view.with_data_scoped(|d| <[u8; 6]>::try_from(d).unwrap()),
+            Ok([1, 1, 0, 0, 0, 6])
+        );
+
+        // retrying resumes after the last successful step
+        migrate::<3, _>(&mut view, &[fail, add_one, double], &[0, 0, 0]).unwrap();
+        assert_eq!(
+            view.with_data_scoped(|d| <[u8; 6]>::try_from(d).unwrap()),
+            Ok([3, 1, 0, 0, 0, 14])
+        );
+
+        let other_view = view.clone();
+        let borrowed = other_view.try_borrow().unwrap();
+        assert_eq!(
+            migrate::<4, _>(&mut view, &[fail, fail, fail, add_one], &[0, 0, 0, 0]),
+            Err(ProgramError::AccountBorrowFailed)
+        );
+        drop(borrowed);
+        assert_eq!(view.with_data_scoped(read_version), Ok(Ok(3)));
+    }
+
+    #[test]
+    fn test_migrate_resize() {
+        let mut buffer = [u64::MAX; size_of::<RuntimeAccount>() / size_of::<u64>() + CAPACITY];
+        let mut view = account(&mut buffer, &[0, 1, 0, 0, 0, 5]);
+
+        migrate::<1, _>(&mut view, &[append_u64], &[HEADER_LEN + 9]).unwrap();
+        assert_eq!(view.data_len(), HEADER_LEN + 9);
+        assert_eq!(
+            view.with_data_scoped(|d| (read_version(d), read_len(d), d[HEADER_LEN])),
+            Ok((Ok(1), Ok(9), 5))
+        );
+        assert_eq!(
+            view.with_data_scoped(|d| d[HEADER_LEN + 1..] == u64::MAX.to_le_bytes()),
+            Ok(true)
+        );
+
+        // the data is never shrunk, and the new bytes are zeroed
+        view.with_data_mut_scoped(|d| d[0] = 0).unwrap();
+        migrate::<2, _>(
+            &mut view,
+            &[
+                |_| Ok(()),
+                |d| {
+                    assert_eq!(d.len(), HEADER_LEN + 11);
+                    assert_eq!(d[HEADER_LEN + 9..], [0, 0]);
+                    Ok(())
+                },
+            ],
+            &[1, HEADER_LEN + 11],
+        )
+        .unwrap();
+        assert_eq!(view.data_len(), HEADER_LEN + 11);
+
+        view.with_data_mut_scoped(|d| d[0] = 0).unwrap();
+        assert_eq!(
+            migrate::<1, _>(
+                &mut view,
+                &[add_one],
+                &[HEADER_LEN + 12 + MAX_PERMITTED_DATA_INCREASE]
+            ),
+            Err(ProgramError::InvalidRealloc)
+        );
+        assert_eq!(view.data_len(), HEADER_LEN + 11);
+    }
+}
@@ -0,0 +1,267 @@
+//! Model-based test of the borrow, resize and close state machine of
+//! `AccountView`.
+//!
+//! Random sequences of operations are run against an account and a model of
+//! it, and the account is checked against the model after each operation.
+
+use {
+    proptest::prelude::*,
+    solana_account_view::{
+        AccountView, Ref, RefMut, RuntimeAccount, MAX_PERMITTED_DATA_INCREASE, NOT_BORROWED,
+    },
+    solana_address::Address,
+    solana_program_error::ProgramError,
+    std::mem::size_of,
+};
+
+/// Maximum length of the account data when the instruction starts.
+const MAX_ORIGINAL_LEN: usize = 64;
+
+/// Bytes of data region past the account header.
+const CAPACITY: usize = MAX_ORIGINAL_LEN + MAX_PERMITTED_DATA_INCREASE;
+
+/// Garbage in the data region past the original data, which the account must
+/// never expose.
+const GARBAGE: u8 = 0xaa;
+
+#[derive(Clone, Debug)]
+enum Op {
+    Borrow,
+    BorrowMut,
+    /// Fills the data through the last mutable borrow, if any.
+    Write(u8),
+    /// Drops the borrow at this index, modulo the number of borrows.
+    Drop(usize),
+    Resize(usize),
+    Close,
+    Assign(u8),
+    SetLamports(u64),
+}
+
+fn op() -> impl Strategy<Value = Op> {
+    prop_oneof![
+        Just(Op::Borrow),
+        Just(Op::BorrowMut),
+        any::<u8>().prop_map(Op::Write),
+        any::<usize>().prop_map(Op::Drop),
+        // up to a few bytes past the permitted increase
+        (0..CAPACITY + 8).prop_map(Op::Resize),
+        Just(Op::Close),
+        any::<u8>().prop_map(Op::Assign),
+        any::<u64>().prop_map(Op::SetLamports),
+    ]
+}
+
+enum Guard<'a> {
+    Shared(Ref<'a, [u8]>),
+    Exclusive(RefMut<'a, [u8]>),
+}
+
+#[derive(Debug)]
+struct Model {
+    original_len: usize,
+    data: Vec<u8>,
+    lamports: u64,
+    owner: Address,
+}
+
+impl Model {
+    fn num_shared(guards: &[Guard]) -> usize {
+        guards
+            .iter()
+            .filter(|guard| matches!(guard, Guard::Shared(_)))
+            .count()
+    }
+
+    fn apply<'a>(
+        &mut self,
+        op: Op,
+        account: &mut AccountView,
+        slot: Option<&'a mut AccountView>,
+        guards: &mut Vec<Guard<'a>>,
+    ) {
+        let exclusive = guards
+            .iter()
+            .any(|guard| matches!(guard, Guard::Exclusive(_)));
+        match op {
+            Op::Borrow => {
+                let Some(slot) = slot else { return };
+                // the borrow state counts down from `NOT_BORROWED`, and at
+                // least `2` is needed for a shared borrow
+                let allowed = !exclusive && Self::num_shared(guards) < NOT_BORROWED as usize - 1;
+                match slot.try_borrow() {
+                    Ok(data) => {
+                        assert!(allowed);
+                        assert_eq!(*data, self.data[..]);
+                        guards.push(Guard::Shared(data));
+                    }
+                    Err(error) => {
+                        assert!(!allowed);
+                        assert_eq!(error, ProgramError::AccountBorrowFailed);
+                    }
+                }
+            }
+            Op::BorrowMut => {
+                let Some(slot) = slot else { return };
+                match slot.try_borrow_mut() {
+                    Ok(data) => {
+                        assert!(guards.is_empty());
+                        assert_eq!(*data, self.data[..]);
+                        guards.push(Guard::Exclusive(data));
+                    }
+                    Err(error) => {
+                        assert!(!guards.is_empty());
+                        assert_eq!(error, ProgramError::AccountBorrowFailed);
+                    }
+                }
+            }
+            Op::Write(byte) => {
+                if let Some(Guard::Exclusive(data)) = guards.last_mut() {
+                    data.fill(byte);
+                    self.data.fill(byte);
+                }
+            }
+            Op::Drop(index) => {
+                if !guards.is_empty() {
+                    let index = index % guards.len();
+                    guards.remove(index);
+                }
+            }
+            Op::Resize(new_len) => {
+                let result = account.resize(new_len);
+                if new_len > self.original_len + MAX_PERMITTED_DATA_INCREASE {
+                    assert_eq!(result, Err(ProgramError::InvalidRealloc));
+                } else if !guards.is_empty() {
+                    assert_eq!(result, Err(ProgramError::AccountBorrowFailed));
+                } else {
+                    assert_eq!(result, Ok(()));
+                    // grown bytes are zeroed, even after a shrink
+                    self.data.resize(new_len, 0);
+                }
+            }
+            Op::Close => {
+                let result = account.close();
+                if guards.is_empty() {
+                    assert_eq!(result, Ok(()));
+                    self.data.clear();
+                    self.lamports = 0;
+                    self.owner = Address::new_from_array([0; 32]);
+                } else {
+                    assert_eq!(result, Err(ProgramError::AccountBorrowFailed));
+                }
+            }
+            Op::Assign(byte) => {
+                self.owner = Address::new_from_array([byte; 32]);
+                // SAFETY: There is no reference to the owner of the account.
+                unsafe { account.assign(&self.owner) };
+            }
+            Op::SetLamports(lamports) => {
+                account.set_lamports(lamports);
+                self.lamports = lamports;
+            }
+        }
+    }
+
+    fn check(&self, account: &AccountView, guards: &[Guard]) {
+        assert_eq!(account.data_len(), self.data.len());
+        assert_eq!(account.original_data_len(), self.original_len);
+        assert!(account.data_len() <= self.original_len + MAX_PERMITTED_DATA_INCREASE);
+        assert_eq!(account.lamports(), self.lamports);
+        assert!(account.owned_by(&self.owner));
+
+        assert_eq!(account.is_borrowed(), !guards.is_empty());
+        let exclusive = guards
+            .iter()
+            .any(|guard| matches!(guard, Guard::Exclusive(_)));
+        assert_eq!(account.is_borrowed_mut(), exclusive);
+        for guard in guards {
+            let data: &[u8] = match guard {
+                Guard::Shared(data) => data,
+                Guard::Exclusive(data) => data,
+            };
+            assert_eq!(data, &self.data[..]);
+        }
+        if !exclusive {
+            assert_eq!(account.with_data_scoped(|data| data == self.data), Ok(true));
+        }
+    }
+}
+
+fn run(original: Vec<u8>, ops: Vec<Op>) {
+    let mut buffer = vec![0u64; (size_of::<RuntimeAccount>() + CAPACITY) / size_of::<u64>()];
+    let raw = buffer.as_mut_ptr() as *mut RuntimeAccount;
+    // SAFETY: The buffer holds an account header followed by `CAPACITY`
+    // bytes of data region, and is 8-byte aligned.
+    unsafe {
+        (*raw).borrow_state = NOT_BORROWED;
+        (*raw).owner = Address::new_from_array([1; 32]);
+        (*raw).lamports = 1_000;
+        (*raw).data_len = original.len() as u64;
+        let data = raw.add(1) as *mut u8;
+        data.write_bytes(GARBAGE, CAPACITY);
+        data.copy_from_nonoverlapping(original.as_ptr(), original.len());
+    }
+
+    let mut model = Model {
+        original_len: original.len(),
+        data: original,
+        lamports: 1_000,
+        owner: Address::new_from_array([1; 32]),
+    };
+    // SAFETY: As above, and every view of the account is created here.
+    let mut account = unsafe { AccountView::new_unchecked(raw) };
+    let mut views: Vec<_> = (0..ops.len())
+        .map(|_| unsafe { AccountView::new_unchecked(raw) })
+        .collect();
+    let mut slots = views.iter_mut();
+    let mut guards = Vec::new();
+
+    for op in ops {
+        let slot = match op {
+            Op::Borrow | Op::BorrowMut => slots.next(),
+            _ => None,
+        };
+        model.apply(op, &mut account, slot, &mut guards);
+        model.check(&account, &guards);
+    }
+
+    drop(guards);
+    assert!(!account.is_borrowed());
+    // SAFETY: The account header is valid.
+    assert_eq!(
+        unsafe { (*account.account_ptr()).borrow_state },
+        NOT_BORROWED
+    );
+    model.check(&account, &[]);
+}
+
+proptest! {
+    #[test]
+    fn test_state_machine(
+        original in prop::collection::vec(any::<u8>(), 0..=MAX_ORIGINAL_LEN),
+        ops in prop::collection::vec(op(), 0..64),
+    ) {
+        run(original, ops);
+    }
+}
+
+#[test]
+fn test_close_then_resize() {
+    // the original length survives closing, so the account can grow back
+    // to the permitted increase over it, and no more
+    run(
+        vec![7; 16],
+        vec![
+            Op::Resize(16 + MAX_PERMITTED_DATA_INCREASE),
+            Op::Close,
+            Op::Resize(16 + MAX_PERMITTED_DATA_INCREASE + 1),
+            Op::Resize(16 + MAX_PERMITTED_DATA_INCREASE),
+            Op::Close,
+            Op::Resize(8),
+            Op::Borrow,
+            Op::Resize(16),
+            Op::Drop(0),
+            Op::Resize(16),
+        ],
+    );
+}
@@ -672,6 +672,23 @@ impl solana_account_info::Account for Account {
     }
 }
 
+/// Lend the fields of a shared account to an `AccountInfo`, so that the
+/// `IntoAccountInfo` conversions also accept `AccountSharedData`.
+///
+/// The data is unshared first, copying it if other `AccountSharedData`s
+/// hold it, so that writes through the `AccountInfo` are only seen by this
+/// account.
+impl solana_account_info::Account for AccountSharedData {
+    fn get(&mut self) -> (&mut u64, &mut [u8], &Pubkey, bool) {
+        (
+            &mut self.lamports,
+            Arc::make_mut(&mut self.data).as_mut_slice(),
+            &self.owner,
+            self.executable,
+        )
+    }
+}
+
 /// Create `AccountInfo`s
 pub fn create_is_signer_account_infos<'a>(
     accounts: &'a mut [(&'a Pubkey, bool, &'a mut Account)],
@@ -1098,4 +1115,39 @@ pub mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_into_account_info() {
+        use solana_account_info::{AccountInfo, IntoAccountInfo};
+
+        let key = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+        let (mut account, mut shared) = make_two_accounts(&owner);
+        let shared_clone = shared.clone();
+
+        for info in [
+            (&key, &mut account).into_account_info(),
+            AccountInfo::from((&key, true, &mut shared)),
+        ] {
+            assert_eq!(info.key, &key);
+            assert_eq!(info.owner, &owner);
+            assert!(info.executable);
+            assert_eq!(info.lamports(), 1);
+            **info.try_borrow_mut_lamports().unwrap() = 7;
+            info.try_borrow_mut_data().unwrap().copy_from_slice(&[8, 9]);
+        }
+
+        assert_eq!(account.lamports(), 7);
+        assert_eq!(account.data(), &[8, 9]);
+        assert_eq!(shared.lamports(), 7);
+        assert_eq!(shared.data(), &[8, 9]);
+        // the data shared before the conversion is copied on write
+        assert_eq!(shared_clone.lamports(), 1);
+        assert_eq!(shared_clone.data(), &[0, 0]);
+
+        let mut pair = (key, shared_clone);
+        let info = (&mut pair).into_account_info();
+        info.try_borrow_mut_data().unwrap()[0] = 1;
+        assert_eq!(pair.1.data(), &[1, 0]);
+    }
 }
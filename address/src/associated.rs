@@ -0,0 +1,268 @@
+//! Associated accounts: [program derived addresses][pda] of an owner and
+//! other addresses, such as associated token accounts.
+//!
+//! [pda]: https://solana.com/docs/core/cpi#program-derived-addresses
+//!
+//! An associated account of `owner` is the program address of a namespace
+//! program, derived from the bytes of `owner` followed by the bytes of other
+//! addresses. The associated token account program derives the token account
+//! of a wallet for a mint from the wallet, the token program and the mint, in
+//! this order.
+
+use crate::{Address, MAX_SEEDS};
+
+/// The associated token account program,
+/// `ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL`.
+pub const ASSOCIATED_TOKEN_PROGRAM_ID: Address = Address::new_from_array([
+    140, 151, 37, 143, 78, 36, 137, 241, 187, 61, 16, 41, 20, 142, 13, 131, 11, 90, 19, 153, 218,
+    255, 16, 132, 4, 142, 123, 216, 219, 233, 248, 89,
+]);
+
+/// Find the associated account of `owner` under `namespace_program`, derived
+/// from `owner` followed by `seeds`, and its bump seed.
+///
+/// Returns `None` if there are more than `MAX_SEEDS - 2` seeds, since the
+/// owner and the bump seed take two of the [`MAX_SEEDS`], or in the
+/// statistically improbable event that no bump seed derives a valid program
+/// address.
+pub fn try_derive_associated_address(
+    owner: &Address,
+    namespace_program: &Address,
+    seeds: &[&Address],
+) -> Option<(Address, u8)> {
+    if seeds.len() > MAX_SEEDS - 2 {
+        return None;
+    }
+    let mut buffer: [&[u8]; MAX_SEEDS - 1] = [&[]; MAX_SEEDS - 1];
+    buffer[0] = owner.as_ref();
+    for (slot, seed) in buffer[1..].iter_mut().zip(seeds) {
+        *slot = seed.as_ref();
+    }
+    Address::try_find_program_address(&buffer[..=seeds.len()], namespace_program)
+}
+
+/// Find the associated account of `owner` under `namespace_program`, derived
+/// from `owner` followed by `seeds`, and its bump seed.
+///
+/// # Panics
+///
+/// Panics if there are more than `MAX_SEEDS - 2` seeds, or if no bump seed
+/// derives a valid program address, see [`try_derive_associated_address`].
+pub fn derive_associated_address(
+    owner: &Address,
+    namespace_program: &Address,
+    seeds: &[&Address],
+) -> (Address, u8) {
+    try_derive_associated_address(owner, namespace_program, seeds)
+        .unwrap_or_else(|| panic!("Unable to derive the associated address"))
+}
+
+/// Find the associated token account of `wallet` for `mint`, whose token
+/// program is `token_program`.
+///
+/// # Examples
+///
+/// ```
+/// # use solana_address::{associated_token_address, Address};
+/// let wallet = Address::from_str_const("9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM");
+/// let usdc = Address::from_str_const("EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v");
+/// let token_program = Address::from_str_const("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
+/// assert_eq!(
+///     associated_token_address(&wallet, &usdc, &token_program),
+///     Address::from_str_const("FGETo8T8wMcN2wCjav8VK6eh3dLk63evNDPxzLSJra8B"),
+/// );
+/// ```
+pub fn associated_token_address(
+    wallet: &Address,
+    mint: &Address,
+    token_program: &Address,
+) -> Address {
+    associated_token_address_and_bump(wallet, mint, token_program).0
+}
+
+/// Find the associated token account of `wallet` for `mint`, whose token
+/// program is `token_program`, and its bump seed.
+pub fn associated_token_address_and_bump(
+    wallet: &Address,
+    mint: &Address,
+    token_program: &Address,
+) -> (Address, u8) {
+    derive_associated_address(wallet, &ASSOCIATED_TOKEN_PROGRAM_ID, &[token_program, mint])
+}
+
+/// Derive the associated token account of `wallet` for `mint`, whose token
+/// program is `token_program`, from its known bump seed.
+///
+/// Finding the bump seed takes curve checks, which cannot run in a `const`
+/// context, so this takes the bump seed returned by
+/// [`associated_token_address_and_bump`] instead. As
+/// [`Address::derive_address_const`], it does not check that the address is
+/// a valid program address.
+#[cfg(feature = "sha2")]
+pub const fn create_associated_token_address(
+    wallet: &Address,
+    mint: &Address,
+    token_program: &Address,
+    bump: u8,
+) -> Address {
+    Address::derive_address_const(
+        &[wallet.as_array(), token_program.as_array(), mint.as_array()],
+        Some(bump),
+        &ASSOCIATED_TOKEN_PROGRAM_ID,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        std::{vec, vec::Vec},
+    };
+
+    const TOKEN_PROGRAM: Address =
+        Address::from_str_const("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
+    const TOKEN_2022_PROGRAM: Address =
+        Address::from_str_const("TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb");
+    const USDC: Address = Address::from_str_const("EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v");
+    const WSOL: Address = Address::from_str_const("So11111111111111111111111111111111111111112");
+
+    /// `(wallet, mint, token program, associated token account, bump seed)`
+    const VECTORS: [(&str, Address, Address, &str, u8); 6] = [
+        (
+            "9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM",
+            USDC,
+            TOKEN_PROGRAM,
+            "FGETo8T8wMcN2wCjav8VK6eh3dLk63evNDPxzLSJra8B",
+            254,
+        ),
+        (
+            "9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM",
+            WSOL,
+            TOKEN_PROGRAM,
+            "8LjUgMjzZuHj8VdyxzkmLLQVmW4C3gd56md1nLd76TNW",
+            255,
+        ),
+        (
+            "9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM",
+            USDC,
+            TOKEN_2022_PROGRAM,
+            "GdjpegrtGwU3pgtzPivYVViSA8rmGL248qBVKzsrU3DD",
+            254,
+        ),
+        (
+            "5Q544fKrFoe6tsEbD7S8EmxGTJYAKtTVhAW5Q5pge4j1",
+            USDC,
+            TOKEN_PROGRAM,
+            "BmeV7UWExZeSboQXYW4biUVEx2SyYDVTdWhHoQEQcUFu",
+            255,
+        ),
+        (
+            "5Q544fKrFoe6tsEbD7S8EmxGTJYAKtTVhAW5Q5pge4j1",
+            WSOL,
+            TOKEN_PROGRAM,
+            "qx84tiXFKS93Md34ENxNBNu33vPsEJ4BU1o7KYHm6pV",
+            255,
+        ),
+        (
+            "5Q544fKrFoe6tsEbD7S8EmxGTJYAKtTVhAW5Q5pge4j1",
+            USDC,
+            TOKEN_2022_PROGRAM,
+            "22R8315P6d3jCRR9ZWKSf9uZZUS1n3DA8vkMtQpJWTn6",
+            255,
+        ),
+    ];
+
+    #[test]
+    fn test_associated_token_program_id() {
+        assert_eq!(
+            ASSOCIATED_TOKEN_PROGRAM_ID,
+            Address::from_str_const("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL")
+        );
+    }
+
+    #[test]
+    fn test_associated_token_address_vectors() {
+        for (wallet, mint, token_program, expected, expected_bump) in VECTORS {
+            let wallet = Address::from_str_const(wallet);
+            assert_eq!(
+                associated_token_address(&wallet, &mint, &token_program),
+                Address::from_str_const(expected)
+            );
+            assert_eq!(
+                associated_token_address_and_bump(&wallet, &mint, &token_program),
+                (Address::from_str_const(expected), expected_bump)
+            );
+            assert_eq!(
+                create_associated_token_address(&wallet, &mint, &token_program, expected_bump),
+                Address::from_str_const(expected)
+            );
+            assert_eq!(
+                derive_associated_address(
+                    &wallet,
+                    &ASSOCIATED_TOKEN_PROGRAM_ID,
+                    &[&token_program, &mint]
+                ),
+                (Address::from_str_const(expected), expected_bump)
+            );
+            // the seed order matters
+            assert_ne!(
+                derive_associated_address(
+                    &wallet,
+                    &ASSOCIATED_TOKEN_PROGRAM_ID,
+                    &[&mint, &token_program]
+                )
+                .0,
+                Address::from_str_const(expected)
+            );
+        }
+    }
+
+    #[test]
+    fn test_create_associated_token_address_const() {
+        const WALLET: Address =
+            Address::from_str_const("9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM");
+        const ACCOUNT: Address =
+            create_associated_token_address(&WALLET, &USDC, &TOKEN_PROGRAM, 254);
+        assert_eq!(
+            ACCOUNT,
+            Address::from_str_const("FGETo8T8wMcN2wCjav8VK6eh3dLk63evNDPxzLSJra8B")
+        );
+    }
+
+    #[test]
+    fn test_derive_associated_address_seeds() {
+        let owner = Address::new_unique();
+        let program = Address::new_unique();
+        let seeds: Vec<Address> = (0..MAX_SEEDS - 1).map(|_| Address::new_unique()).collect();
+        let seeds: Vec<&Address> = seeds.iter().collect();
+
+        let (address, bump) = derive_associated_address(&owner, &program, &[]);
+        assert_eq!(
+            Address::create_program_address(&[owner.as_ref(), &[bump]], &program),
+            Ok(address)
+        );
+
+        let max = &seeds[..MAX_SEEDS - 2];
+        let (address, bump) = derive_associated_address(&owner, &program, max);
+        let mut expected_seeds: Vec<&[u8]> = vec![owner.as_ref()];
+        expected_seeds.extend(max.iter().map(|seed| seed.as_ref()));
+        let bump_seed = [bump];
+        expected_seeds.push(&bump_seed);
+        assert_eq!(
+            Address::create_program_address(&expected_seeds, &program),
+            Ok(address)
+        );
+
+        assert_eq!(
+            try_derive_associated_address(&owner, &program, &seeds),
+            None
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Unable to derive the associated address")]
+    fn test_derive_associated_address_too_many_seeds() {
+        let seeds = [&ASSOCIATED_TOKEN_PROGRAM_ID; MAX_SEEDS - 1];
+        derive_associated_address(&Address::new_unique(), &Address::new_unique(), &seeds);
+    }
+}
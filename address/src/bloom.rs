@@ -0,0 +1,340 @@
+//! An approximate membership filter for addresses.
+
+#[cfg(feature = "serde")]
+use serde_derive::{Deserialize, Serialize};
+use {
+    crate::{Address, ADDRESS_BYTES},
+    core::hash::{BuildHasher, Hasher},
+    std::{collections::hash_map::RandomState, vec::Vec},
+};
+
+const WINDOW_BYTES: usize = 8;
+const NUM_WINDOWS: usize = ADDRESS_BYTES / WINDOW_BYTES;
+/// Upper bound of the number of hash functions, reached for false positive
+/// rates far below any practical use.
+const MAX_NUM_HASHES: u32 = 128;
+
+/// A bloom filter specialized for [`Address`] keys.
+///
+/// Addresses are public keys or hashes, so their bytes are already uniformly
+/// distributed. Instead of running a general purpose hash function, the
+/// filter derives its hash functions from the four disjoint 8-byte windows of
+/// the address: the `i`-th hash function reads window `i % 4`. Windows are
+/// mixed with a per-filter seed before being mapped to a bit, so that
+/// addresses ground to collide in one filter do not collide in another.
+///
+/// The filter never reports a false negative: after an address has been
+/// inserted, [`contains`](AddressBloom::contains) returns `true` for it until
+/// the filter is cleared.
+///
+/// Deserialization rejects filters without any bit, with a number of hash
+/// functions that [`new`](AddressBloom::new) cannot produce, or with counts
+/// of set bits and addresses that no sequence of insertions can produce.
+#[cfg_attr(
+    feature = "serde",
+    derive(Deserialize, Serialize),
+    serde(try_from = "RawAddressBloom")
+)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AddressBloom {
+    bits: Vec<u64>,
+    num_hashes: u32,
+    seed: u64,
+    num_bits_set: u64,
+    len: u64,
+}
+
+impl AddressBloom {
+    /// Create a filter sized for `num_items` addresses with the given target
+    /// false positive rate, using a random seed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `false_positive_rate` is not strictly between 0 and 1.
+    pub fn new(num_items: usize, false_positive_rate: f64) -> Self {
+        let seed = RandomState::new().build_hasher().finish();
+        Self::with_seed(num_items, false_positive_rate, seed)
+    }
+
+    /// Create a filter sized for `num_items` addresses with the given target
+    /// false positive rate and an explicit seed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `false_positive_rate` is not strictly between 0 and 1.
+    pub fn with_seed(num_items: usize, false_positive_rate: f64, seed: u64) -> Self {
+        assert!(
+            false_positive_rate > 0.0 && false_positive_rate < 1.0,
+            "false positive rate must be between 0 and 1"
+        );
+        let num_items = num_items.max(1) as f64;
+        // m = -n ln(p) / ln(2)^2, k = m / n ln(2)
+        let num_bits = (-num_items * false_positive_rate.ln()
+            / (core::f64::consts::LN_2 * core::f64::consts::LN_2))
+            .ceil()
+            .max(1.0);
+        let num_words = (num_bits / u64::BITS as f64).ceil() as usize;
+        let num_hashes = ((num_words as f64 * u64::BITS as f64 / num_items)
+            * core::f64::consts::LN_2)
+            .round()
+            .clamp(1.0, f64::from(MAX_NUM_HASHES)) as u32;
+        Self {
+            bits: std::vec![0; num_words],
+            num_hashes,
+            seed,
+            num_bits_set: 0,
+            len: 0,
+        }
+    }
+
+    /// Insert `address` into the filter.
+    pub fn insert(&mut self, address: &Address) {
+        let mut inserted = false;
+        for i in 0..self.num_hashes {
+            let (word, mask) = self.bit_position(address, i);
+            let word = &mut self.bits[word];
+            if *word & mask == 0 {
+                *word |= mask;
+                self.num_bits_set += 1;
+                inserted = true;
+            }
+        }
+        if inserted {
+            self.len += 1;
+        }
+    }
+
+    /// Returns `true` if `address` may have been inserted, and `false` if it
+    /// definitely was not.
+    pub fn contains(&self, address: &Address) -> bool {
+        !self.bits.is_empty()
+            && (0..self.num_hashes).all(|i| {
+                let (word, mask) = self.bit_position(address, i);
+                self.bits[word] & mask != 0
+            })
+    }
+
+    /// Remove all addresses from the filter, keeping its size and seed.
+    pub fn clear(&mut self) {
+        self.bits.fill(0);
+        self.num_bits_set = 0;
+        self.len = 0;
+    }
+
+    /// The number of inserted addresses that were not already reported as
+    /// contained by the filter.
+    ///
+    /// This undercounts when inserting false positives.
+    pub fn len(&self) -> usize {
+        self.len as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The number of bits in the filter.
+    pub fn num_bits(&self) -> u64 {
+        self.bits.len() as u64 * u64::from(u64::BITS)
+    }
+
+    /// The number of hash functions applied to each address.
+    pub fn num_hashes(&self) -> u32 {
+        self.num_hashes
+    }
+
+    /// The fraction of bits that are set, between 0 and 1.
+    ///
+    /// The false positive rate of the filter is roughly
+    /// `saturation().powi(num_hashes())`.
+    pub fn saturation(&self) -> f64 {
+        if self.bits.is_empty() {
+            return 0.0;
+        }
+        self.num_bits_set as f64 / self.num_bits() as f64
+    }
+
+    /// The word index and bit mask set by the `i`-th hash function.
+    fn bit_position(&self, address: &Address, i: u32) -> (usize, u64) {
+        let offset = (i as usize % NUM_WINDOWS) * WINDOW_BYTES;
+        let mut window = [0; WINDOW_BYTES];
+        window.copy_from_slice(&address.as_array()[offset..offset + WINDOW_BYTES]);
+        // hash functions past the fourth reuse a window with a different
+        // round constant
+        let round = u64::from(i / NUM_WINDOWS as u32);
+        let index =
+            mix(u64::from_le_bytes(window) ^ self.seed ^ round.wrapping_mul(0x9e37_79b9_7f4a_7c15))
+                % self.num_bits();
+        (
+            (index / u64::from(u64::BITS)) as usize,
+            1 << (index % u64::from(u64::BITS)),
+        )
+    }
+}
+
+/// The fields of a deserialized [`AddressBloom`], before validation.
+#[cfg(feature = "serde")]
+#[derive(Deserialize)]
+struct RawAddressBloom {
+    bits: Vec<u64>,
+    num_hashes: u32,
+    seed: u64,
+    num_bits_set: u64,
+    len: u64,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<RawAddressBloom> for AddressBloom {
+    type Error = &'static str;
+
+    fn try_from(raw: RawAddressBloom) -> Result<Self, Self::Error> {
+        if raw.bits.is_empty() {
+            return Err("address bloom filter has no bits");
+        }
+        if !(1..=MAX_NUM_HASHES).contains(&raw.num_hashes) {
+            return Err("address bloom filter has an invalid number of hash functions");
+        }
+        let num_bits_set: u64 = raw
+            .bits
+            .iter()
+            .map(|word| u64::from(word.count_ones()))
+            .sum();
+        // every counted insertion sets between one and `num_hashes` bits
+        if raw.num_bits_set != num_bits_set
+            || raw.num_bits_set < raw.len
+            || raw.num_bits_set > raw.len.saturating_mul(u64::from(raw.num_hashes))
+        {
+            return Err("address bloom filter has inconsistent counts");
+        }
+        Ok(Self {
+            bits: raw.bits,
+            num_hashes: raw.num_hashes,
+            seed: raw.seed,
+            num_bits_set: raw.num_bits_set,
+            len: raw.len,
+        })
+    }
+}
+
+/// The splitmix64 finalizer.
+fn mix(mut x: u64) -> u64 {
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    x ^ (x >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_no_false_negatives() {
+        let mut bloom = AddressBloom::new(1_000, 0.01);
+        let addresses: Vec<Address> = (0..1_000).map(|_| Address::new_unique()).collect();
+        for address in &addresses {
+            bloom.insert(address);
+        }
+        assert!(addresses.iter().all(|address| bloom.contains(address)));
+        assert!(bloom.len() <= addresses.len());
+        assert!(bloom.saturation() > 0.0 && bloom.saturation() < 1.0);
+
+        bloom.clear();
+        assert!(bloom.is_empty());
+        assert_eq!(bloom.saturation(), 0.0);
+        assert!(!addresses.iter().any(|address| bloom.contains(address)));
+    }
+
+    #[test]
+    fn test_false_positive_rate() {
+        const NUM_ITEMS: usize = 10_000;
+        const NUM_PROBES: usize = 100_000;
+        for false_positive_rate in [0.1, 0.01] {
+            let mut bloom = AddressBloom::with_seed(NUM_ITEMS, false_positive_rate, 42);
+            for _ in 0..NUM_ITEMS {
+                bloom.insert(&Address::new_unique());
+            }
+            let false_positives = (0..NUM_PROBES)
+                .filter(|_| bloom.contains(&Address::new_unique()))
+                .count();
+            let rate = false_positives as f64 / NUM_PROBES as f64;
+            assert!(
+                rate < 2.0 * false_positive_rate,
+                "false positive rate {rate} exceeds twice the target {false_positive_rate}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_seed_changes_bit_positions() {
+        let address = Address::new_from_array([7; 32]);
+        let mut a = AddressBloom::with_seed(100, 0.01, 1);
+        let mut b = AddressBloom::with_seed(100, 0.01, 2);
+        a.insert(&address);
+        b.insert(&address);
+        assert_eq!(a.num_bits(), b.num_bits());
+        assert_ne!(a.bits, b.bits);
+    }
+
+    #[test]
+    #[should_panic(expected = "false positive rate")]
+    fn test_invalid_false_positive_rate() {
+        AddressBloom::new(100, 1.0);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_roundtrip() {
+        let mut bloom = AddressBloom::new(100, 0.01);
+        let addresses: Vec<Address> = (0..100).map(|_| Address::new_unique()).collect();
+        for address in &addresses {
+            bloom.insert(address);
+        }
+        let serialized = bincode::serialize(&bloom).unwrap();
+        let deserialized: AddressBloom = bincode::deserialize(&serialized).unwrap();
+        assert_eq!(deserialized, bloom);
+        assert!(addresses
+            .iter()
+            .all(|address| deserialized.contains(address)));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_deserialize_invalid() {
+        let mut valid = AddressBloom::with_seed(100, 0.01, 42);
+        valid.insert(&Address::new_from_array([7; 32]));
+        let serialized = bincode::serialize(&valid).unwrap();
+        assert_eq!(
+            bincode::deserialize::<AddressBloom>(&serialized).unwrap(),
+            valid
+        );
+        for invalid in [
+            AddressBloom {
+                bits: Vec::new(),
+                ..valid.clone()
+            },
+            AddressBloom {
+                num_hashes: 0,
+                ..valid.clone()
+            },
+            AddressBloom {
+                num_hashes: MAX_NUM_HASHES + 1,
+                ..valid.clone()
+            },
+            AddressBloom {
+                num_bits_set: valid.num_bits_set + 1,
+                ..valid.clone()
+            },
+            AddressBloom {
+                len: 0,
+                ..valid.clone()
+            },
+            AddressBloom {
+                len: valid.num_bits_set + 1,
+                ..valid.clone()
+            },
+        ] {
+            let serialized = bincode::serialize(&invalid).unwrap();
+            assert!(bincode::deserialize::<AddressBloom>(&serialized).is_err());
+        }
+    }
+}
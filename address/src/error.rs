@@ -67,9 +67,126 @@ impl fmt::Display for ParseAddressError {
     }
 }
 
+/// Error returned by [`Address::parse_detailed`](crate::Address::parse_detailed).
+#[cfg_attr(feature = "serde", derive(serde_derive::Serialize))]
+#[cfg(feature = "decode")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DetailedParseAddressError {
+    /// The string does not decode to 32 bytes.
+    WrongSize,
+    /// The string holds a character outside of the base58 alphabet.
+    InvalidCharacter {
+        /// Byte offset of the first invalid character.
+        index: usize,
+        character: char,
+    },
+}
+
+#[cfg(feature = "decode")]
+impl DetailedParseAddressError {
+    /// The error for the invalid `byte` reported by five8, which is the first
+    /// invalid byte of `s`, and so the first byte of a character.
+    pub(crate) fn invalid_char(s: &str, byte: u8) -> Self {
+        s.char_indices()
+            .find(|&(index, _)| s.as_bytes()[index] == byte)
+            .map_or(Self::WrongSize, |(index, character)| {
+                Self::InvalidCharacter { index, character }
+            })
+    }
+}
+
+#[cfg(feature = "decode")]
+impl core::error::Error for DetailedParseAddressError {}
+
+#[cfg(feature = "decode")]
+impl fmt::Display for DetailedParseAddressError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::WrongSize => ParseAddressError::WrongSize.fmt(f),
+            Self::InvalidCharacter { index, character } => {
+                write!(
+                    f,
+                    "invalid base58 character {character:?} at position {index}"
+                )
+            }
+        }
+    }
+}
+
+#[cfg(feature = "decode")]
+impl From<DetailedParseAddressError> for ParseAddressError {
+    fn from(error: DetailedParseAddressError) -> Self {
+        match error {
+            DetailedParseAddressError::WrongSize => Self::WrongSize,
+            DetailedParseAddressError::InvalidCharacter { .. } => Self::Invalid,
+        }
+    }
+}
+
+/// Each error is converted into its code in
+/// [`solana_program_error::sdk_codes`].
+#[cfg(feature = "decode")]
+impl From<ParseAddressError> for ProgramError {
+    fn from(error: ParseAddressError) -> Self {
+        use solana_program_error::sdk_codes::{PARSE_ADDRESS_INVALID, PARSE_ADDRESS_WRONG_SIZE};
+        match error {
+            ParseAddressError::WrongSize => Self::Custom(PARSE_ADDRESS_WRONG_SIZE),
+            ParseAddressError::Invalid => Self::Custom(PARSE_ADDRESS_INVALID),
+        }
+    }
+}
+
 #[cfg(feature = "decode")]
 impl From<core::convert::Infallible> for ParseAddressError {
     fn from(_: core::convert::Infallible) -> Self {
         unreachable!("Infallible uninhabited");
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_address_error_codes() {
+        for (error, code) in [
+            (
+                AddressError::MaxSeedLengthExceeded,
+                solana_program_error::MAX_SEED_LENGTH_EXCEEDED,
+            ),
+            (
+                AddressError::InvalidSeeds,
+                solana_program_error::INVALID_SEEDS,
+            ),
+            (
+                AddressError::IllegalOwner,
+                solana_program_error::ILLEGAL_OWNER,
+            ),
+        ] {
+            assert_eq!(u64::from(ProgramError::from(error)), code);
+        }
+    }
+
+    #[cfg(feature = "decode")]
+    #[test]
+    fn test_parse_address_error_codes() {
+        use solana_program_error::sdk_codes;
+
+        for (error, code, name) in [
+            (
+                ParseAddressError::WrongSize,
+                0xffff_0100,
+                "ParseAddressError::WrongSize",
+            ),
+            (
+                ParseAddressError::Invalid,
+                0xffff_0101,
+                "ParseAddressError::Invalid",
+            ),
+        ] {
+            let program_error = ProgramError::from(error);
+            assert_eq!(program_error, ProgramError::Custom(code));
+            assert_eq!(sdk_codes::decode(&program_error), Some(name));
+        }
+    }
+}
@@ -8,21 +8,96 @@
 #![cfg_attr(feature = "frozen-abi", feature(min_specialization))]
 #![allow(clippy::arithmetic_side_effects)]
 
+#[cfg(any(target_os = "solana", target_arch = "bpf", feature = "curve25519"))]
+mod associated;
+#[cfg(feature = "std")]
+mod bloom;
 #[cfg(feature = "sha2")]
 mod derive;
 #[cfg(feature = "error")]
 pub mod error;
 #[cfg(feature = "rand")]
 mod hasher;
+#[cfg(feature = "std")]
+#[doc(hidden)]
+pub mod lru;
+#[cfg(all(
+    feature = "curve25519",
+    feature = "std",
+    not(any(target_os = "solana", target_arch = "bpf"))
+))]
+mod on_curve;
+#[cfg(all(
+    feature = "pda-cache",
+    not(any(target_os = "solana", target_arch = "bpf"))
+))]
+mod pda_cache;
+#[cfg(all(
+    feature = "profiling",
+    not(any(target_os = "solana", target_arch = "bpf"))
+))]
+pub mod profiling;
 #[cfg(any(feature = "curve25519", feature = "syscalls"))]
 pub mod syscalls;
+#[cfg(feature = "decode")]
+mod vanity;
+
+// Derivations are only counted by the `profiling` module. The derivations
+// calling these are themselves behind features.
+#[cfg(not(all(
+    feature = "profiling",
+    not(any(target_os = "solana", target_arch = "bpf"))
+)))]
+#[allow(dead_code)]
+mod profiling {
+    #[inline(always)]
+    pub(crate) fn count_create_program_address() {}
+
+    #[inline(always)]
+    pub(crate) fn count_find_program_address_iteration() {}
 
+    #[inline(always)]
+    pub(crate) fn count_create_with_seed() {}
+}
+
+#[cfg(all(
+    feature = "sha2",
+    any(target_os = "solana", target_arch = "bpf", feature = "curve25519")
+))]
+pub use crate::associated::create_associated_token_address;
+#[cfg(any(target_os = "solana", target_arch = "bpf", feature = "curve25519"))]
+pub use crate::associated::{
+    associated_token_address, associated_token_address_and_bump, derive_associated_address,
+    try_derive_associated_address, ASSOCIATED_TOKEN_PROGRAM_ID,
+};
+#[cfg(feature = "std")]
+pub use crate::bloom::AddressBloom;
 #[cfg(feature = "sha2")]
 use crate::error::AddressError;
 #[cfg(feature = "decode")]
-use crate::error::ParseAddressError;
+use crate::error::{DetailedParseAddressError, ParseAddressError};
 #[cfg(all(feature = "rand", not(any(target_os = "solana", target_arch = "bpf"))))]
 pub use crate::hasher::{AddressHasher, AddressHasherBuilder};
+#[cfg(all(
+    feature = "curve25519",
+    feature = "std",
+    not(any(target_os = "solana", target_arch = "bpf"))
+))]
+pub use crate::on_curve::{batch_is_on_curve, OnCurveCache};
+#[cfg(all(
+    feature = "pda-cache",
+    not(any(target_os = "solana", target_arch = "bpf"))
+))]
+pub use crate::pda_cache::PdaCache;
+#[cfg(any(target_os = "solana", target_arch = "bpf", feature = "curve25519"))]
+pub use crate::syscalls::{assert_pda, assert_pda_with_bump};
+#[cfg(all(
+    feature = "decode",
+    not(any(target_os = "solana", target_arch = "bpf"))
+))]
+pub use crate::vanity::grind;
+#[cfg(feature = "decode")]
+pub use crate::vanity::AddressPattern;
 
 #[cfg(feature = "alloc")]
 extern crate alloc;
@@ -108,17 +183,26 @@ impl FromStr for Address {
     type Err = ParseAddressError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse_detailed(s).map_err(ParseAddressError::from)
+    }
+}
+
+#[cfg(feature = "decode")]
+impl Address {
+    /// Parses a base58 encoded address like [`FromStr`], reporting where the
+    /// first invalid character is.
+    pub fn parse_detailed(s: &str) -> Result<Self, DetailedParseAddressError> {
         use five8::DecodeError;
         if s.len() > MAX_BASE58_LEN {
-            return Err(ParseAddressError::WrongSize);
+            return Err(DetailedParseAddressError::WrongSize);
         }
         let mut bytes = [0; ADDRESS_BYTES];
         five8::decode_32(s, &mut bytes).map_err(|e| match e {
-            DecodeError::InvalidChar(_) => ParseAddressError::Invalid,
+            DecodeError::InvalidChar(byte) => DetailedParseAddressError::invalid_char(s, byte),
             DecodeError::TooLong
             | DecodeError::TooShort
             | DecodeError::LargestTermTooHigh
-            | DecodeError::OutputTooLong => ParseAddressError::WrongSize,
+            | DecodeError::OutputTooLong => DetailedParseAddressError::WrongSize,
         })?;
         Ok(Address(bytes))
     }
@@ -224,6 +308,19 @@ impl Address {
         Self(address_array)
     }
 
+    /// Deserializes an `Address` from its borsh encoding, which must be
+    /// exactly [`ADDRESS_BYTES`] long.
+    #[cfg(feature = "borsh")]
+    pub fn try_from_borsh_slice(bytes: &[u8]) -> borsh::io::Result<Self> {
+        if bytes.len() != ADDRESS_BYTES {
+            return Err(borsh::io::Error::new(
+                borsh::io::ErrorKind::InvalidData,
+                "borsh encoding of an address must be 32 bytes long",
+            ));
+        }
+        borsh::from_slice(bytes)
+    }
+
     #[cfg(feature = "decode")]
     /// Decode a string into an `Address`, usable in a const context
     pub const fn from_str_const(s: &str) -> Self {
@@ -278,6 +375,7 @@ impl Address {
         seed: &str,
         owner: &Address,
     ) -> Result<Address, AddressError> {
+        crate::profiling::count_create_with_seed();
         if seed.len() > MAX_SEED_LEN {
             return Err(AddressError::MaxSeedLengthExceeded);
         }
@@ -303,6 +401,17 @@ impl Address {
         &self.0
     }
 
+    /// Derive a `u64` from the address and `seed`, for probabilistic data
+    /// structures like bloom filters and dedup tables.
+    ///
+    /// This is [`Hash::to_u64_seeded`](solana_hash::Hash::to_u64_seeded) of
+    /// the address bytes, so an address and a hash of the same bytes give the
+    /// same value. It is not a cryptographic hash function, and its output
+    /// must not be relied on to stay the same across releases.
+    pub const fn to_u64_seeded(&self, seed: u64) -> u64 {
+        solana_hash::Hash::new_from_array(self.0).to_u64_seeded(seed)
+    }
+
     /// Checks whether the given address lies on the Ed25519 curve.
     ///
     /// On-curve addresses correspond to valid Ed25519 public keys (and therefore
@@ -314,6 +423,14 @@ impl Address {
         bytes_are_curve_point(self)
     }
 
+    /// Checks whether the given address does not lie on the Ed25519 curve,
+    /// as program derived addresses.
+    #[cfg(any(feature = "curve25519", feature = "syscalls"))]
+    #[inline(always)]
+    pub fn is_off_curve(&self) -> bool {
+        !self.is_on_curve()
+    }
+
     /// Log an `Address` value.
     #[cfg(all(not(any(target_os = "solana", target_arch = "bpf")), feature = "std"))]
     pub fn log(&self) {
@@ -356,6 +473,33 @@ impl core::fmt::Display for Address {
     }
 }
 
+/// Describes an address as its base58 string, the form of [`Display`] and
+/// [`FromStr`], rather than the byte array the derived serde implementations
+/// produce.
+///
+/// [`Display`]: core::fmt::Display
+#[cfg(feature = "schemars")]
+impl schemars::JsonSchema for Address {
+    fn schema_name() -> alloc::borrow::Cow<'static, str> {
+        "Address".into()
+    }
+
+    fn schema_id() -> alloc::borrow::Cow<'static, str> {
+        "solana_address::Address".into()
+    }
+
+    fn json_schema(_generator: &mut schemars::SchemaGenerator) -> schemars::Schema {
+        schemars::json_schema!({
+            "description": "A base58 encoded Solana account address",
+            "type": "string",
+            "pattern": "^[1-9A-HJ-NP-Za-km-z]{32,44}$",
+            "minLength": 32,
+            "maxLength": MAX_BASE58_LEN,
+            "examples": ["11111111111111111111111111111111"],
+        })
+    }
+}
+
 /// Custom implementation of equality for `Address`.
 ///
 /// The implementation compares the address in 4 chunks of 8 bytes (`u64` values),
@@ -501,7 +645,7 @@ macro_rules! declare_deprecated_id {
 
 #[cfg(test)]
 mod tests {
-    use {super::*, core::str::from_utf8, std::string::String};
+    use {super::*, core::str::from_utf8, solana_program_error::ProgramError, std::string::String};
 
     fn encode_address(address: &[u8; 32]) -> String {
         let mut buffer = [0u8; 44];
@@ -557,6 +701,38 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_parse_detailed() {
+        let address = Address::new_unique();
+        let encoded = encode_address(&address.0);
+        assert_eq!(Address::parse_detailed(&encoded), Ok(address));
+
+        let last = encoded.len() - 1;
+        for (index, character) in [(0, '0'), (12, 'l'), (last, 'O'), (last, 'é')] {
+            let mut invalid = encoded.clone();
+            invalid.replace_range(index..index + 1, character.encode_utf8(&mut [0; 4]));
+            let error = Address::parse_detailed(&invalid).unwrap_err();
+            assert_eq!(
+                error,
+                DetailedParseAddressError::InvalidCharacter { index, character }
+            );
+            assert_eq!(invalid.parse::<Address>(), Err(ParseAddressError::Invalid));
+        }
+
+        let mut invalid = encoded.clone();
+        invalid.replace_range(12..13, "l");
+        invalid.replace_range(20..21, "I");
+        assert_eq!(
+            Address::parse_detailed(&invalid).unwrap_err().to_string(),
+            "invalid base58 character 'l' at position 12"
+        );
+
+        assert_eq!(
+            Address::parse_detailed(&encoded[..encoded.len() / 4]),
+            Err(DetailedParseAddressError::WrongSize)
+        );
+    }
+
     #[test]
     fn test_create_with_seed() {
         assert!(
@@ -734,6 +910,90 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_assert_pda() {
+        let program_id = Address::new_unique();
+        let seeds: &[&[u8]] = &[b"Lil'", b"Bits"];
+        let (address, bump_seed) = Address::find_program_address(seeds, &program_id);
+
+        assert_eq!(assert_pda(&address, seeds, &program_id), Ok(bump_seed));
+        assert_eq!(
+            assert_pda_with_bump(&address, &[b"Lil'", b"Bits", &[bump_seed]], &program_id),
+            Ok(())
+        );
+
+        // another address, or another program
+        let other = Address::new_unique();
+        assert_eq!(
+            assert_pda(&other, seeds, &program_id),
+            Err(ProgramError::InvalidSeeds)
+        );
+        assert_eq!(
+            assert_pda(&address, seeds, &other),
+            Err(ProgramError::InvalidSeeds)
+        );
+        assert_eq!(
+            assert_pda_with_bump(&other, &[b"Lil'", b"Bits", &[bump_seed]], &program_id),
+            Err(ProgramError::InvalidSeeds)
+        );
+    }
+
+    #[test]
+    fn test_assert_pda_non_canonical_bump() {
+        let program_id = Address::new_unique();
+        let seeds: &[&[u8]] = &[b"vault"];
+        let (_, canonical_bump) = Address::find_program_address(seeds, &program_id);
+        // about half of the bump seeds derive an address
+        let (address, bump_seed) = (0..canonical_bump)
+            .rev()
+            .find_map(|bump_seed| {
+                Address::create_program_address(&[b"vault", &[bump_seed]], &program_id)
+                    .ok()
+                    .map(|address| (address, bump_seed))
+            })
+            .unwrap();
+
+        // a trusted bump seed is accepted, but it is not the canonical one
+        assert_eq!(
+            assert_pda_with_bump(&address, &[b"vault", &[bump_seed]], &program_id),
+            Ok(())
+        );
+        assert_eq!(
+            assert_pda(&address, seeds, &program_id),
+            Err(ProgramError::InvalidSeeds)
+        );
+    }
+
+    #[test]
+    fn test_assert_pda_seed_length() {
+        let program_id = Address::new_unique();
+        let address = Address::new_unique();
+        let long_seed = &[0; MAX_SEED_LEN + 1];
+        let seeds: std::vec::Vec<&[u8]> = std::vec![&[1]; MAX_SEEDS];
+
+        assert_eq!(
+            assert_pda(&address, &[long_seed], &program_id),
+            Err(ProgramError::MaxSeedLengthExceeded)
+        );
+        // no room left for the bump seed
+        assert_eq!(
+            assert_pda(&address, &seeds, &program_id),
+            Err(ProgramError::MaxSeedLengthExceeded)
+        );
+        assert_eq!(
+            assert_pda(&address, &seeds[1..], &program_id),
+            Err(ProgramError::InvalidSeeds)
+        );
+        assert_eq!(
+            assert_pda_with_bump(&address, &[long_seed, &[255]], &program_id),
+            Err(ProgramError::MaxSeedLengthExceeded)
+        );
+        assert_eq!(
+            assert_pda_with_bump(&address, &[&seeds[..], &[&[255]]].concat(), &program_id),
+            Err(ProgramError::MaxSeedLengthExceeded)
+        );
+    }
+
     fn address_from_seed_by_marker(marker: &[u8]) -> Result<Address, AddressError> {
         let key = Address::new_unique();
         let owner = Address::default();
@@ -780,6 +1040,24 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_to_u64_seeded() {
+        let address = Address::new_from_array([7; 32]);
+        assert_eq!(address.to_u64_seeded(42), address.to_u64_seeded(42));
+        assert_ne!(address.to_u64_seeded(42), address.to_u64_seeded(43));
+        const SEEDED: u64 = Address::new_from_array([7; 32]).to_u64_seeded(42);
+        assert_eq!(SEEDED, address.to_u64_seeded(42));
+
+        // the same function as for hashes
+        for (i, seed) in [0, 1, 42, u64::MAX].into_iter().enumerate() {
+            let bytes = core::array::from_fn(|j| (i * 32 + j * 7) as u8);
+            assert_eq!(
+                Address::new_from_array(bytes).to_u64_seeded(seed),
+                solana_hash::Hash::new_from_array(bytes).to_u64_seeded(seed)
+            );
+        }
+    }
+
     #[test]
     fn test_address_eq_matches_default_eq() {
         for i in 0..u8::MAX {
@@ -801,4 +1079,42 @@ mod tests {
             assert!(!address_eq(&p1, &p3));
         }
     }
+
+    /// Checks `value` against the string constraints of `schema`.
+    fn assert_matches_string_schema(schema: &serde_json::Value, value: &serde_json::Value) {
+        assert_eq!(schema["type"], "string");
+        let value = value.as_str().unwrap();
+        let pattern = regex::Regex::new(schema["pattern"].as_str().unwrap()).unwrap();
+        assert!(pattern.is_match(value), "{value}");
+        let len = value.len() as u64;
+        assert!(schema["minLength"].as_u64().unwrap() <= len, "{value}");
+        assert!(len <= schema["maxLength"].as_u64().unwrap(), "{value}");
+    }
+
+    #[test]
+    fn test_json_schema() {
+        let schema = schemars::schema_for!(Address);
+        let schema = schema.as_value();
+        assert_eq!(schema["type"], "string");
+        assert_eq!(schema["pattern"], "^[1-9A-HJ-NP-Za-km-z]{32,44}$");
+        assert_eq!(schema["minLength"], 32);
+        assert_eq!(schema["maxLength"], 44);
+
+        for example in schema["examples"].as_array().unwrap() {
+            assert_matches_string_schema(schema, example);
+            assert!(example.as_str().unwrap().parse::<Address>().is_ok());
+        }
+        for address in [
+            Address::default(),
+            Address::new_from_array([u8::MAX; 32]),
+            Address::new_unique(),
+        ] {
+            let value = serde_json::to_value(address.to_string()).unwrap();
+            assert_matches_string_schema(schema, &value);
+        }
+        // not base58
+        assert!(!regex::Regex::new(schema["pattern"].as_str().unwrap())
+            .unwrap()
+            .is_match("0OIl0OIl0OIl0OIl0OIl0OIl0OIl0OIl"));
+    }
 }
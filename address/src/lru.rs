@@ -0,0 +1,112 @@
+//! A least-recently-used map, backing the caches of this crate.
+//!
+//! Not part of the public API: it is public only to be shared with
+//! `solana-packet`, and may change at any time.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    hash::Hash,
+};
+
+/// A map holding up to `capacity` entries, evicting the least recently used
+/// one when a new key would exceed it.
+#[derive(Debug)]
+pub struct Lru<K, V> {
+    capacity: usize,
+    /// Values and the tick of their last use.
+    entries: HashMap<K, (V, u64)>,
+    /// Keys ordered by last use, oldest first.
+    recency: BTreeMap<u64, K>,
+    tick: u64,
+}
+
+impl<K: Copy + Eq + Hash, V> Lru<K, V> {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: HashMap::new(),
+            recency: BTreeMap::new(),
+            tick: 0,
+        }
+    }
+
+    /// Returns the value of `key`, marking it as the most recently used.
+    pub fn get(&mut self, key: &K) -> Option<&mut V> {
+        let (value, last_used) = self.entries.get_mut(key)?;
+        self.tick += 1;
+        self.recency.remove(last_used);
+        self.recency.insert(self.tick, *key);
+        *last_used = self.tick;
+        Some(value)
+    }
+
+    /// Sets the value of `key`, marking it as the most recently used.
+    ///
+    /// Replacing the value of a key already present evicts nothing.
+    pub fn insert(&mut self, key: K, value: V) {
+        self.tick += 1;
+        if let Some((_, previous)) = self.entries.insert(key, (value, self.tick)) {
+            self.recency.remove(&previous);
+        } else if self.entries.len() > self.capacity {
+            if let Some((_, oldest)) = self.recency.pop_first() {
+                self.entries.remove(&oldest);
+            }
+        }
+        self.recency.insert(self.tick, key);
+    }
+
+    /// Returns the value of `key`, inserting the value returned by `f` if
+    /// the key is absent, and marks it as the most recently used.
+    pub fn get_or_insert_with(&mut self, key: K, f: impl FnOnce() -> V) -> &mut V {
+        if !self.entries.contains_key(&key) {
+            self.insert(key, f());
+        }
+        self.get(&key).expect("the key was just inserted")
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.recency.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_eviction_order() {
+        let mut lru = Lru::new(2);
+        lru.insert(1, "a");
+        lru.insert(2, "b");
+        // replacing a value refreshes the key without evicting
+        lru.insert(1, "c");
+        assert_eq!(lru.len(), 2);
+        lru.insert(3, "d");
+        assert_eq!(lru.get(&2), None);
+        assert_eq!(lru.get(&1), Some(&mut "c"));
+
+        // reading a key refreshes it too
+        lru.insert(4, "e");
+        assert_eq!(lru.get(&3), None);
+        assert_eq!(lru.get(&1), Some(&mut "c"));
+        assert_eq!(lru.len(), 2);
+
+        // only absent keys are inserted
+        assert_eq!(*lru.get_or_insert_with(1, || "f"), "c");
+        assert_eq!(*lru.get_or_insert_with(5, || "f"), "f");
+        assert_eq!(lru.get(&4), None);
+
+        lru.clear();
+        assert!(lru.is_empty());
+        assert_eq!(lru.get(&1), None);
+    }
+}
@@ -0,0 +1,227 @@
+//! Curve checks of many addresses.
+
+use {
+    crate::{lru::Lru, Address, ADDRESS_BYTES},
+    core::sync::atomic::{AtomicU64, Ordering},
+    std::{sync::Mutex, vec::Vec},
+};
+
+/// Check whether each of `addresses` lies on the Ed25519 curve, as
+/// [`Address::is_on_curve`].
+///
+/// With the `rayon` feature, the addresses are checked on the threads of the
+/// global rayon pool. The results are in the order of `addresses` either way.
+pub fn batch_is_on_curve(addresses: &[Address]) -> Vec<bool> {
+    #[cfg(feature = "rayon")]
+    {
+        use rayon::prelude::*;
+
+        addresses.par_iter().map(Address::is_on_curve).collect()
+    }
+    #[cfg(not(feature = "rayon"))]
+    {
+        addresses.iter().map(Address::is_on_curve).collect()
+    }
+}
+
+/// A least-recently-used cache of whether addresses lie on the Ed25519 curve.
+///
+/// Checking an address decompresses it as a curve point. Services that
+/// validate user-supplied addresses and see the same ones over and over, such
+/// as rejecting program derived addresses where a wallet is expected, can
+/// look them up here instead.
+///
+/// The cache evicts its least recently used address once it holds
+/// `capacity` addresses.
+#[derive(Debug)]
+pub struct OnCurveCache {
+    inner: Mutex<Lru<[u8; ADDRESS_BYTES], bool>>,
+    capacity: usize,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl OnCurveCache {
+    /// Create a cache holding up to `capacity` addresses.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero.
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "capacity must be positive");
+        Self {
+            inner: Mutex::new(Lru::new(capacity)),
+            capacity,
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// Returns whether `address` lies on the Ed25519 curve, as
+    /// [`Address::is_on_curve`].
+    ///
+    /// On a miss the address is checked without holding the lock, so
+    /// concurrent misses for the same address may each check it.
+    pub fn is_on_curve(&self, address: &Address) -> bool {
+        let key = address.to_bytes();
+        if let Some(&mut on_curve) = self.inner.lock().unwrap().get(&key) {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            return on_curve;
+        }
+        self.misses.fetch_add(1, Ordering::Relaxed);
+
+        let on_curve = address.is_on_curve();
+        // another thread may have checked the same address concurrently,
+        // inserting it again only refreshes it
+        self.inner.lock().unwrap().insert(key, on_curve);
+        on_curve
+    }
+
+    /// Returns whether `address` does not lie on the Ed25519 curve, as
+    /// [`Address::is_off_curve`].
+    pub fn is_off_curve(&self, address: &Address) -> bool {
+        !self.is_on_curve(address)
+    }
+
+    /// The number of cached addresses.
+    pub fn len(&self) -> usize {
+        self.inner.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The maximum number of cached addresses.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// The number of lookups answered from the cache.
+    pub fn hits(&self) -> u64 {
+        self.hits.load(Ordering::Relaxed)
+    }
+
+    /// The number of lookups that had to check the address.
+    pub fn misses(&self) -> u64 {
+        self.misses.load(Ordering::Relaxed)
+    }
+
+    /// Remove all cached addresses, keeping the hit and miss counters.
+    pub fn clear(&self) {
+        self.inner.lock().unwrap().clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, std::sync::Arc};
+
+    /// Random addresses, about half of which are on the curve, and program
+    /// derived addresses, which are all off the curve.
+    fn addresses() -> Vec<Address> {
+        let program_id = Address::new_unique();
+        let mut addresses: Vec<Address> = (0..256).map(|_| Address::new_unique()).collect();
+        addresses
+            .extend((0..64u8).map(|seed| Address::find_program_address(&[&[seed]], &program_id).0));
+        addresses.extend([
+            // a wallet and its USDC associated token account
+            Address::from_str_const("9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM"),
+            Address::from_str_const("FGETo8T8wMcN2wCjav8VK6eh3dLk63evNDPxzLSJra8B"),
+        ]);
+        addresses
+    }
+
+    #[test]
+    fn test_batch_matches_scalar() {
+        let addresses = addresses();
+        let expected: Vec<bool> = addresses.iter().map(Address::is_on_curve).collect();
+        assert_eq!(batch_is_on_curve(&addresses), expected);
+        assert!(expected.iter().any(|on_curve| *on_curve));
+        assert!(expected[256..256 + 64].iter().all(|on_curve| !on_curve));
+        assert_eq!(expected[expected.len() - 2..], [true, false]);
+        assert!(batch_is_on_curve(&[]).is_empty());
+
+        // the order of the results does not depend on the scheduling of
+        // the checks
+        for _ in 0..10 {
+            assert_eq!(batch_is_on_curve(&addresses), expected);
+        }
+    }
+
+    #[test]
+    fn test_is_off_curve() {
+        for address in addresses() {
+            assert_eq!(address.is_off_curve(), !address.is_on_curve());
+        }
+    }
+
+    #[test]
+    fn test_cache_matches_scalar() {
+        let addresses = addresses();
+        let cache = OnCurveCache::new(addresses.len());
+        for address in &addresses {
+            assert_eq!(cache.is_on_curve(address), address.is_on_curve());
+            assert_eq!(cache.is_off_curve(address), address.is_off_curve());
+        }
+        assert_eq!(cache.len(), addresses.len());
+        assert_eq!(cache.misses(), addresses.len() as u64);
+        assert_eq!(cache.hits(), addresses.len() as u64);
+    }
+
+    #[test]
+    fn test_eviction() {
+        let cache = OnCurveCache::new(2);
+        let [a, b, c] = [(); 3].map(|_| Address::new_unique());
+        cache.is_on_curve(&a);
+        cache.is_on_curve(&b);
+        // touch a so that b is the least recently used
+        cache.is_on_curve(&a);
+        cache.is_on_curve(&c);
+        assert_eq!(cache.len(), 2);
+        assert_eq!((cache.hits(), cache.misses()), (1, 3));
+
+        cache.is_on_curve(&a);
+        cache.is_on_curve(&c);
+        assert_eq!((cache.hits(), cache.misses()), (3, 3));
+        cache.is_on_curve(&b);
+        assert_eq!((cache.hits(), cache.misses()), (3, 4));
+        assert_eq!(cache.len(), 2);
+
+        cache.clear();
+        assert!(cache.is_empty());
+        assert_eq!(cache.capacity(), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "capacity must be positive")]
+    fn test_zero_capacity() {
+        OnCurveCache::new(0);
+    }
+
+    #[test]
+    fn test_concurrent_lookups() {
+        const NUM_THREADS: usize = 8;
+        let addresses = Arc::new(addresses());
+        let cache = Arc::new(OnCurveCache::new(1024));
+        let handles: Vec<_> = (0..NUM_THREADS)
+            .map(|_| {
+                let addresses = Arc::clone(&addresses);
+                let cache = Arc::clone(&cache);
+                std::thread::spawn(move || {
+                    for address in addresses.iter() {
+                        assert_eq!(cache.is_on_curve(address), address.is_on_curve());
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        assert_eq!(cache.len(), addresses.len());
+        assert_eq!(
+            cache.hits() + cache.misses(),
+            (NUM_THREADS * addresses.len()) as u64
+        );
+    }
+}
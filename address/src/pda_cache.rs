@@ -0,0 +1,300 @@
+//! A cache of program derived addresses.
+
+use {
+    crate::{error::AddressError, lru::Lru, Address, ADDRESS_BYTES, MAX_SEEDS, MAX_SEED_LEN},
+    core::sync::atomic::{AtomicU64, Ordering},
+    solana_sha256_hasher::Hasher,
+    std::{sync::Mutex, vec::Vec},
+};
+
+/// Default number of shards of a [`PdaCache`].
+const DEFAULT_NUM_SHARDS: usize = 16;
+
+/// Cache key: the program id and a hash of the seeds.
+type Key = ([u8; ADDRESS_BYTES], [u8; 32]);
+
+/// A shard of the cache, mapping keys to the address and its bump seed.
+type Shard = Lru<Key, ([u8; ADDRESS_BYTES], u8)>;
+
+/// A least-recently-used cache of [program derived addresses][pda] and their
+/// bump seeds.
+///
+/// [pda]: https://solana.com/docs/core/pda
+///
+/// Finding a program address costs a sha256 hash and a curve check per bump
+/// seed tried. Services deriving the same addresses over and over, such as
+/// indexers resolving associated token accounts, can look them up here
+/// instead.
+///
+/// The cache is split into shards, each behind its own mutex, so that it can
+/// be shared between threads. Each shard evicts its least recently used entry
+/// once it holds its share of the capacity.
+#[derive(Debug)]
+pub struct PdaCache {
+    shards: Vec<Mutex<Shard>>,
+    shard_capacity: usize,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl PdaCache {
+    /// Create a cache holding up to `capacity` addresses.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero.
+    pub fn new(capacity: usize) -> Self {
+        Self::with_shards(capacity, DEFAULT_NUM_SHARDS.min(capacity.max(1)))
+    }
+
+    /// Create a cache holding up to `capacity` addresses, split into
+    /// `num_shards` shards.
+    ///
+    /// Each shard holds up to `capacity / num_shards` addresses, rounded up.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` or `num_shards` is zero.
+    pub fn with_shards(capacity: usize, num_shards: usize) -> Self {
+        assert!(capacity > 0, "capacity must be positive");
+        assert!(num_shards > 0, "number of shards must be positive");
+        let shard_capacity = capacity.div_ceil(num_shards);
+        Self {
+            shards: (0..num_shards)
+                .map(|_| Mutex::new(Shard::new(shard_capacity)))
+                .collect(),
+            shard_capacity,
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// Returns the program address derived from `seeds` and `program_id`,
+    /// and its bump seed, as found by [`Address::find_program_address`].
+    ///
+    /// On a miss the address is derived without holding any lock, so
+    /// concurrent misses for the same address may each derive it.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AddressError::MaxSeedLengthExceeded`] if there are too many
+    /// seeds, leaving no room for the bump seed, or if a seed is too long, and
+    /// [`AddressError::InvalidSeeds`] if no bump seed yields a valid program
+    /// address.
+    pub fn get_or_derive(
+        &self,
+        seeds: &[&[u8]],
+        program_id: &Address,
+    ) -> Result<(Address, u8), AddressError> {
+        if seeds.len() >= MAX_SEEDS || seeds.iter().any(|seed| seed.len() > MAX_SEED_LEN) {
+            return Err(AddressError::MaxSeedLengthExceeded);
+        }
+        let key = (program_id.to_bytes(), hash_seeds(seeds));
+        let shard = &self.shards[shard_index(&key, self.shards.len())];
+
+        if let Some(&mut (address, bump)) = shard.lock().unwrap().get(&key) {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            return Ok((Address::new_from_array(address), bump));
+        }
+        self.misses.fetch_add(1, Ordering::Relaxed);
+
+        let (address, bump) = Address::try_find_program_address(seeds, program_id)
+            .ok_or(AddressError::InvalidSeeds)?;
+        // another thread may have derived the same address concurrently,
+        // inserting it again only refreshes it
+        shard
+            .lock()
+            .unwrap()
+            .insert(key, (address.to_bytes(), bump));
+        Ok((address, bump))
+    }
+
+    /// The number of cached addresses.
+    pub fn len(&self) -> usize {
+        self.shards
+            .iter()
+            .map(|shard| shard.lock().unwrap().len())
+            .sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The maximum number of cached addresses.
+    pub fn capacity(&self) -> usize {
+        self.shard_capacity * self.shards.len()
+    }
+
+    /// The number of lookups answered from the cache.
+    pub fn hits(&self) -> u64 {
+        self.hits.load(Ordering::Relaxed)
+    }
+
+    /// The number of lookups that had to derive the address.
+    pub fn misses(&self) -> u64 {
+        self.misses.load(Ordering::Relaxed)
+    }
+
+    /// Remove all cached addresses, keeping the hit and miss counters.
+    pub fn clear(&self) {
+        for shard in &self.shards {
+            shard.lock().unwrap().clear();
+        }
+    }
+}
+
+/// Hash `seeds` with length prefixes, so that different seed lists never
+/// hash the same concatenated bytes.
+fn hash_seeds(seeds: &[&[u8]]) -> [u8; 32] {
+    let mut hasher = Hasher::default();
+    hasher.hash(&(seeds.len() as u64).to_le_bytes());
+    for seed in seeds {
+        hasher.hashv(&[(seed.len() as u64).to_le_bytes().as_slice(), seed]);
+    }
+    hasher.result().to_bytes()
+}
+
+fn shard_index(key: &Key, num_shards: usize) -> usize {
+    let mut bytes = [0; 8];
+    bytes.copy_from_slice(&key.1[..8]);
+    (u64::from_le_bytes(bytes) % num_shards as u64) as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, std::sync::Arc};
+
+    #[test]
+    fn test_matches_find_program_address() {
+        let cache = PdaCache::new(100);
+        let program_id = Address::new_unique();
+        let owner = Address::new_unique();
+        let seed_lists: [&[&[u8]]; 4] = [
+            &[],
+            &[b"vault"],
+            &[owner.as_ref(), b"vault"],
+            &[[0; MAX_SEED_LEN].as_slice(); MAX_SEEDS - 1],
+        ];
+        for seeds in seed_lists {
+            let expected = Address::find_program_address(seeds, &program_id);
+            assert_eq!(
+                cache.get_or_derive(seeds, &program_id).as_ref(),
+                Ok(&expected)
+            );
+            assert_eq!(cache.get_or_derive(seeds, &program_id), Ok(expected));
+        }
+        assert_eq!(cache.len(), seed_lists.len());
+        assert_eq!(cache.misses(), seed_lists.len() as u64);
+        assert_eq!(cache.hits(), seed_lists.len() as u64);
+
+        // the same seeds under another program are a different entry
+        let other_program_id = Address::new_unique();
+        assert_eq!(
+            cache.get_or_derive(&[b"vault"], &other_program_id),
+            Ok(Address::find_program_address(
+                &[b"vault"],
+                &other_program_id
+            ))
+        );
+        assert_eq!(cache.misses(), seed_lists.len() as u64 + 1);
+    }
+
+    #[test]
+    fn test_seed_boundaries() {
+        // seed lists with the same concatenation are distinct cache entries
+        let cache = PdaCache::new(100);
+        let program_id = Address::new_unique();
+        let seed_lists: [&[&[u8]]; 3] = [&[b"ab"], &[b"a", b"b"], &[b"ab", b""]];
+        for seeds in seed_lists {
+            assert_eq!(
+                cache.get_or_derive(seeds, &program_id),
+                Ok(Address::find_program_address(seeds, &program_id))
+            );
+        }
+        assert_eq!(cache.len(), 3);
+        assert_eq!(cache.misses(), 3);
+        assert_eq!(cache.hits(), 0);
+    }
+
+    #[test]
+    fn test_invalid_seeds() {
+        let cache = PdaCache::new(100);
+        let program_id = Address::new_unique();
+        assert_eq!(
+            cache.get_or_derive(&[&[0; MAX_SEED_LEN + 1]], &program_id),
+            Err(AddressError::MaxSeedLengthExceeded)
+        );
+        assert_eq!(
+            cache.get_or_derive(&[b"seed".as_slice(); MAX_SEEDS], &program_id),
+            Err(AddressError::MaxSeedLengthExceeded)
+        );
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn test_eviction() {
+        let cache = PdaCache::with_shards(2, 1);
+        let program_id = Address::new_unique();
+        cache.get_or_derive(&[b"a"], &program_id).unwrap();
+        cache.get_or_derive(&[b"b"], &program_id).unwrap();
+        // touch "a" so that "b" is the least recently used
+        cache.get_or_derive(&[b"a"], &program_id).unwrap();
+        cache.get_or_derive(&[b"c"], &program_id).unwrap();
+        assert_eq!(cache.len(), 2);
+        assert_eq!((cache.hits(), cache.misses()), (1, 3));
+
+        cache.get_or_derive(&[b"a"], &program_id).unwrap();
+        cache.get_or_derive(&[b"c"], &program_id).unwrap();
+        assert_eq!((cache.hits(), cache.misses()), (3, 3));
+        cache.get_or_derive(&[b"b"], &program_id).unwrap();
+        assert_eq!((cache.hits(), cache.misses()), (3, 4));
+        assert_eq!(cache.len(), 2);
+
+        cache.clear();
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn test_capacity() {
+        assert_eq!(PdaCache::new(1).capacity(), 1);
+        assert_eq!(PdaCache::new(100).capacity(), 112);
+        assert_eq!(PdaCache::with_shards(10, 3).capacity(), 12);
+    }
+
+    #[test]
+    #[should_panic(expected = "capacity must be positive")]
+    fn test_zero_capacity() {
+        PdaCache::new(0);
+    }
+
+    #[test]
+    fn test_concurrent_lookups() {
+        const NUM_THREADS: usize = 8;
+        const NUM_SEEDS: u8 = 32;
+        let cache = Arc::new(PdaCache::new(1024));
+        let program_id = Address::new_unique();
+        let handles: Vec<_> = (0..NUM_THREADS)
+            .map(|_| {
+                let cache = Arc::clone(&cache);
+                let program_id = program_id.to_bytes();
+                std::thread::spawn(move || {
+                    let program_id = Address::new_from_array(program_id);
+                    for seed in 0..NUM_SEEDS {
+                        let expected = Address::find_program_address(&[&[seed]], &program_id);
+                        assert_eq!(cache.get_or_derive(&[&[seed]], &program_id), Ok(expected));
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        assert_eq!(cache.len(), NUM_SEEDS as usize);
+        assert_eq!(
+            cache.hits() + cache.misses(),
+            (NUM_THREADS * NUM_SEEDS as usize) as u64
+        );
+        assert!(cache.misses() >= u64::from(NUM_SEEDS));
+    }
+}
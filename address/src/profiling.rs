@@ -0,0 +1,172 @@
+//! Counters of address derivations, for profiling on the host.
+//!
+//! Each derivation costs compute units on-chain: `create_program_address` is a
+//! syscall, and `find_program_address` tries one bump seed after another until
+//! it finds an address off the curve. Running a code path under
+//! [`with_profiling`] reveals how many derivations it performs, bump seeds
+//! included, to find where caching the addresses or their bump seeds pays off.
+//!
+//! The counters are thread-local. Without the `profiling` feature, and on
+//! SBF targets, counting compiles to nothing.
+
+use core::cell::Cell;
+
+/// Numbers of address derivations performed by the current thread.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct DerivationCounts {
+    /// Calls to [`Address::create_program_address`](crate::Address::create_program_address).
+    pub create_program_address: u64,
+    /// Bump seeds tried by
+    /// [`Address::find_program_address`](crate::Address::find_program_address)
+    /// and [`Address::try_find_program_address`](crate::Address::try_find_program_address).
+    pub find_program_address_iterations: u64,
+    /// Calls to [`Address::create_with_seed`](crate::Address::create_with_seed).
+    pub create_with_seed: u64,
+}
+
+std::thread_local!(static COUNTS: Cell<DerivationCounts> = const {
+    Cell::new(DerivationCounts {
+        create_program_address: 0,
+        find_program_address_iterations: 0,
+        create_with_seed: 0,
+    })
+});
+
+/// Returns the derivations counted by the current thread, resetting its
+/// counters.
+pub fn take_counts() -> DerivationCounts {
+    COUNTS.with(Cell::take)
+}
+
+/// Calls `f`, returning its result along with the derivations it performed.
+///
+/// The derivations are also counted by the counters of the current thread,
+/// so that calls can be nested.
+pub fn with_profiling<R>(f: impl FnOnce() -> R) -> (R, DerivationCounts) {
+    let outer = take_counts();
+    let result = f();
+    let counts = take_counts();
+    COUNTS.with(|cell| {
+        cell.set(DerivationCounts {
+            create_program_address: outer
+                .create_program_address
+                .saturating_add(counts.create_program_address),
+            find_program_address_iterations: outer
+                .find_program_address_iterations
+                .saturating_add(counts.find_program_address_iterations),
+            create_with_seed: outer
+                .create_with_seed
+                .saturating_add(counts.create_with_seed),
+        })
+    });
+    (result, counts)
+}
+
+fn count(update: impl FnOnce(&mut DerivationCounts)) {
+    COUNTS.with(|cell| {
+        let mut counts = cell.get();
+        update(&mut counts);
+        cell.set(counts);
+    });
+}
+
+#[inline]
+pub(crate) fn count_create_program_address() {
+    count(|counts| counts.create_program_address = counts.create_program_address.saturating_add(1));
+}
+
+#[inline]
+pub(crate) fn count_find_program_address_iteration() {
+    count(|counts| {
+        counts.find_program_address_iterations =
+            counts.find_program_address_iterations.saturating_add(1)
+    });
+}
+
+#[inline]
+pub(crate) fn count_create_with_seed() {
+    count(|counts| counts.create_with_seed = counts.create_with_seed.saturating_add(1));
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::Address};
+
+    #[test]
+    fn test_counts() {
+        let program_id = Address::new_from_array([7; 32]);
+        take_counts();
+
+        let ((), counts) = with_profiling(|| {
+            let (address, bump) = Address::find_program_address(&[b"vault"], &program_id);
+            assert_eq!(
+                Address::create_program_address(&[b"vault", &[bump]], &program_id),
+                Ok(address)
+            );
+            Address::create_with_seed(&program_id, "seed", &Address::default()).unwrap();
+            Address::create_with_seed(&program_id, "seed", &Address::default()).unwrap();
+        });
+        let (_, bump) = Address::find_program_address(&[b"vault"], &program_id);
+        assert_eq!(
+            counts,
+            DerivationCounts {
+                create_program_address: 1,
+                find_program_address_iterations: u64::from(u8::MAX - bump) + 1,
+                create_with_seed: 2,
+            }
+        );
+
+        // the derivations of `with_profiling` are also counted outside
+        let counts = take_counts();
+        assert_eq!(counts.create_program_address, 1);
+        assert_eq!(counts.create_with_seed, 2);
+        assert_eq!(take_counts(), DerivationCounts::default());
+    }
+
+    #[test]
+    fn test_find_iterations() {
+        let program_id = Address::new_from_array([7; 32]);
+        // find seeds for which the first bump seeds derive addresses on the
+        // curve
+        let (seed, bump) = (0u32..)
+            .map(|i| {
+                let (_, bump) = Address::find_program_address(&[&i.to_le_bytes()], &program_id);
+                (i, bump)
+            })
+            .find(|&(_, bump)| bump <= u8::MAX - 2)
+            .unwrap();
+
+        let (_, counts) =
+            with_profiling(|| Address::find_program_address(&[&seed.to_le_bytes()], &program_id));
+        assert_eq!(
+            counts,
+            DerivationCounts {
+                find_program_address_iterations: u64::from(u8::MAX - bump) + 1,
+                ..DerivationCounts::default()
+            }
+        );
+        assert!(counts.find_program_address_iterations >= 3);
+
+        // invalid seeds stop the search
+        let (result, counts) =
+            with_profiling(|| Address::try_find_program_address(&[&[0; 33]], &program_id));
+        assert_eq!(result, None);
+        assert_eq!(counts.find_program_address_iterations, 1);
+    }
+
+    #[test]
+    fn test_nested() {
+        let program_id = Address::new_from_array([7; 32]);
+        take_counts();
+        let (inner, outer) = with_profiling(|| {
+            Address::create_with_seed(&program_id, "a", &Address::default()).unwrap();
+            let ((), inner) = with_profiling(|| {
+                Address::create_with_seed(&program_id, "b", &Address::default()).unwrap();
+            });
+            inner
+        });
+        assert_eq!(inner.create_with_seed, 1);
+        assert_eq!(outer.create_with_seed, 2);
+        assert_eq!(take_counts().create_with_seed, 2);
+    }
+}
@@ -3,8 +3,6 @@
     feature = "curve25519"
 ))]
 use crate::bytes_are_curve_point;
-#[cfg(any(target_os = "solana", target_arch = "bpf", feature = "curve25519"))]
-use crate::error::AddressError;
 use crate::Address;
 /// Syscall definitions used by `solana_address`.
 #[cfg(any(target_os = "solana", target_arch = "bpf"))]
@@ -12,6 +10,11 @@ pub use solana_define_syscall::definitions::{
     sol_create_program_address, sol_curve_validate_point, sol_log_pubkey,
     sol_try_find_program_address,
 };
+#[cfg(any(target_os = "solana", target_arch = "bpf", feature = "curve25519"))]
+use {
+    crate::{address_eq, error::AddressError},
+    solana_program_error::ProgramError,
+};
 
 /// Copied from `solana_program::entrypoint::SUCCESS`
 /// to avoid a `solana_program` dependency
@@ -310,7 +313,8 @@ impl Address {
                 {
                     let mut seeds_with_bump = seeds.to_vec();
                     seeds_with_bump.push(&bump_seed);
-                    match Self::create_program_address(&seeds_with_bump, program_id) {
+                    crate::profiling::count_find_program_address_iteration();
+                    match Self::create_program_address_uncounted(&seeds_with_bump, program_id) {
                         Ok(address) => return Some((address, bump_seed[0])),
                         Err(AddressError::InvalidSeeds) => (),
                         _ => break,
@@ -392,6 +396,18 @@ impl Address {
     pub fn create_program_address(
         seeds: &[&[u8]],
         program_id: &Address,
+    ) -> Result<Address, AddressError> {
+        crate::profiling::count_create_program_address();
+        Self::create_program_address_uncounted(seeds, program_id)
+    }
+
+    /// [`create_program_address`](Address::create_program_address), without
+    /// counting the derivation, for the bump seed search.
+    #[cfg(any(target_os = "solana", target_arch = "bpf", feature = "curve25519"))]
+    #[inline(always)]
+    fn create_program_address_uncounted(
+        seeds: &[&[u8]],
+        program_id: &Address,
     ) -> Result<Address, AddressError> {
         use crate::{MAX_SEEDS, MAX_SEED_LEN};
 
@@ -441,3 +457,74 @@ impl Address {
         }
     }
 }
+
+/// Check that `account_address` is the [program derived address][pda] of
+/// `seeds` and `program_id`, returning its canonical bump seed.
+///
+/// [pda]: https://solana.com/docs/core/cpi#program-derived-addresses
+///
+/// The bump seed is searched for with [`Address::find_program_address`], so
+/// only the canonical bump seed is accepted, whatever bump seed the caller
+/// may have been given. When the bump seed is stored in the account state,
+/// [`assert_pda_with_bump`] is cheaper.
+///
+/// # Errors
+///
+/// Returns [`ProgramError::MaxSeedLengthExceeded`] if there are too many
+/// seeds, leaving no room for the bump seed, or a seed is too long, and
+/// [`ProgramError::InvalidSeeds`] if the address does not match.
+///
+/// # Examples
+///
+/// ```
+/// # use solana_address::{assert_pda, Address};
+/// # let program_id = Address::new_unique();
+/// let (vault, bump_seed) = Address::find_program_address(&[b"vault"], &program_id);
+/// assert_eq!(assert_pda(&vault, &[b"vault"], &program_id), Ok(bump_seed));
+/// ```
+#[cfg(any(target_os = "solana", target_arch = "bpf", feature = "curve25519"))]
+pub fn assert_pda(
+    account_address: &Address,
+    seeds: &[&[u8]],
+    program_id: &Address,
+) -> Result<u8, ProgramError> {
+    use crate::{MAX_SEEDS, MAX_SEED_LEN};
+
+    // `try_find_program_address` does not tell why it fails
+    if seeds.len() >= MAX_SEEDS || seeds.iter().any(|seed| seed.len() > MAX_SEED_LEN) {
+        return Err(ProgramError::MaxSeedLengthExceeded);
+    }
+    match Address::try_find_program_address(seeds, program_id) {
+        Some((address, bump_seed)) if address_eq(&address, account_address) => Ok(bump_seed),
+        _ => Err(ProgramError::InvalidSeeds),
+    }
+}
+
+/// Check that `account_address` is the [program derived address][pda] of
+/// `seeds_with_bump`, the seeds followed by the bump seed, and `program_id`.
+///
+/// [pda]: https://solana.com/docs/core/cpi#program-derived-addresses
+///
+/// This derives a single address with [`Address::create_program_address`],
+/// and so accepts any bump seed deriving the address, not only the canonical
+/// one: the bump seed should come from a trusted source, such as the state
+/// of the account when it was created with the canonical bump seed.
+///
+/// # Errors
+///
+/// Returns [`ProgramError::MaxSeedLengthExceeded`] if there are too many
+/// seeds or a seed is too long, and [`ProgramError::InvalidSeeds`] if the
+/// seeds derive no address, or another address.
+#[cfg(any(target_os = "solana", target_arch = "bpf", feature = "curve25519"))]
+pub fn assert_pda_with_bump(
+    account_address: &Address,
+    seeds_with_bump: &[&[u8]],
+    program_id: &Address,
+) -> Result<(), ProgramError> {
+    let address = Address::create_program_address(seeds_with_bump, program_id)?;
+    if address_eq(&address, account_address) {
+        Ok(())
+    } else {
+        Err(ProgramError::InvalidSeeds)
+    }
+}
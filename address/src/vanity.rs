@@ -0,0 +1,308 @@
+//! Matching of base58 encoded addresses, for vanity address tooling.
+
+use {
+    crate::{error::ParseAddressError, Address, MAX_BASE58_LEN},
+    core::fmt,
+};
+
+/// Characters of the base58 alphabet, in order.
+const BASE58_ALPHABET: &[u8; 58] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+/// A pattern on the base58 encoding of an address.
+///
+/// The pattern is validated once when it is built, so that matching an
+/// address only encodes it and compares bytes, without allocating.
+///
+/// # Examples
+///
+/// ```
+/// use solana_address::{Address, AddressPattern};
+///
+/// let token_program: Address = "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA".parse().unwrap();
+/// let pattern = AddressPattern::new_ignore_case("tokenkeg", "").unwrap();
+/// assert!(token_program.matches_pattern(&pattern));
+/// assert!(!token_program.matches_pattern(&AddressPattern::new("", "DAX").unwrap()));
+/// ```
+#[derive(Clone, PartialEq, Eq)]
+pub struct AddressPattern {
+    prefix: [u8; MAX_BASE58_LEN],
+    prefix_len: usize,
+    suffix: [u8; MAX_BASE58_LEN],
+    suffix_len: usize,
+    ignore_case: bool,
+}
+
+impl AddressPattern {
+    /// Create a pattern matching addresses whose base58 encoding starts with
+    /// `prefix` and ends with `suffix`. Either may be empty.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ParseAddressError::Invalid`] if `prefix` or `suffix` has a
+    /// character outside of the base58 alphabet, and
+    /// [`ParseAddressError::WrongSize`] if either is longer than an encoded
+    /// address.
+    pub fn new(prefix: &str, suffix: &str) -> Result<Self, ParseAddressError> {
+        Self::build(prefix, suffix, false)
+    }
+
+    /// Create a pattern like [`AddressPattern::new`], ignoring ASCII case.
+    ///
+    /// Characters that are only in the base58 alphabet in one case, such as
+    /// `l` or `O`, are accepted and match the character of the other case.
+    pub fn new_ignore_case(prefix: &str, suffix: &str) -> Result<Self, ParseAddressError> {
+        Self::build(prefix, suffix, true)
+    }
+
+    fn build(prefix: &str, suffix: &str, ignore_case: bool) -> Result<Self, ParseAddressError> {
+        let (prefix, prefix_len) = compile(prefix, ignore_case)?;
+        let (suffix, suffix_len) = compile(suffix, ignore_case)?;
+        Ok(Self {
+            prefix,
+            prefix_len,
+            suffix,
+            suffix_len,
+            ignore_case,
+        })
+    }
+
+    /// The base58 prefix of matching addresses, lowercase if case is ignored.
+    pub fn prefix(&self) -> &str {
+        // SAFETY: only base58 characters are stored
+        unsafe { core::str::from_utf8_unchecked(&self.prefix[..self.prefix_len]) }
+    }
+
+    /// The base58 suffix of matching addresses, lowercase if case is ignored.
+    pub fn suffix(&self) -> &str {
+        // SAFETY: only base58 characters are stored
+        unsafe { core::str::from_utf8_unchecked(&self.suffix[..self.suffix_len]) }
+    }
+
+    /// Whether the pattern ignores ASCII case.
+    pub fn ignores_case(&self) -> bool {
+        self.ignore_case
+    }
+
+    fn matches_encoded(&self, encoded: &[u8]) -> bool {
+        let Some(start) = encoded.get(..self.prefix_len) else {
+            return false;
+        };
+        let Some(end) = encoded
+            .len()
+            .checked_sub(self.suffix_len)
+            .map(|offset| &encoded[offset..])
+        else {
+            return false;
+        };
+        if self.ignore_case {
+            start.eq_ignore_ascii_case(self.prefix().as_bytes())
+                && end.eq_ignore_ascii_case(self.suffix().as_bytes())
+        } else {
+            start == self.prefix().as_bytes() && end == self.suffix().as_bytes()
+        }
+    }
+}
+
+impl fmt::Debug for AddressPattern {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("AddressPattern")
+            .field("prefix", &self.prefix())
+            .field("suffix", &self.suffix())
+            .field("ignore_case", &self.ignore_case)
+            .finish()
+    }
+}
+
+fn compile(s: &str, ignore_case: bool) -> Result<([u8; MAX_BASE58_LEN], usize), ParseAddressError> {
+    if s.len() > MAX_BASE58_LEN {
+        return Err(ParseAddressError::WrongSize);
+    }
+    let mut out = [0; MAX_BASE58_LEN];
+    for (out, &c) in out.iter_mut().zip(s.as_bytes()) {
+        let is_valid = if ignore_case {
+            BASE58_ALPHABET.contains(&c.to_ascii_lowercase())
+                || BASE58_ALPHABET.contains(&c.to_ascii_uppercase())
+        } else {
+            BASE58_ALPHABET.contains(&c)
+        };
+        if !is_valid {
+            return Err(ParseAddressError::Invalid);
+        }
+        *out = if ignore_case {
+            c.to_ascii_lowercase()
+        } else {
+            c
+        };
+    }
+    Ok((out, s.len()))
+}
+
+impl Address {
+    /// Returns true if the base58 encoding of this address starts with
+    /// `prefix`.
+    ///
+    /// The address is encoded on the stack, without allocating.
+    pub fn starts_with_base58(&self, prefix: &str) -> bool {
+        let mut out = [0; MAX_BASE58_LEN];
+        let len = five8::encode_32(&self.0, &mut out) as usize;
+        out[..len].starts_with(prefix.as_bytes())
+    }
+
+    /// Returns true if the base58 encoding of this address matches
+    /// `pattern`.
+    pub fn matches_pattern(&self, pattern: &AddressPattern) -> bool {
+        let mut out = [0; MAX_BASE58_LEN];
+        let len = five8::encode_32(&self.0, &mut out) as usize;
+        pattern.matches_encoded(&out[..len])
+    }
+}
+
+/// Generate keypairs with `keypair_gen` until one has an address matching
+/// `pattern`, giving up after `max_iters` attempts.
+///
+/// `keypair_gen` returns a secret key and its address. With the `rayon`
+/// feature, keypairs are generated on all the threads of the global rayon
+/// pool, and the match returned is not necessarily the first one generated.
+///
+/// Each additional base58 character of the pattern multiplies the expected
+/// number of attempts by 58, or by about 34 for letters when case is
+/// ignored.
+#[cfg(not(any(target_os = "solana", target_arch = "bpf")))]
+pub fn grind(
+    pattern: &AddressPattern,
+    keypair_gen: impl Fn() -> ([u8; 32], Address) + Sync,
+    max_iters: u64,
+) -> Option<([u8; 32], Address)> {
+    #[cfg(feature = "rayon")]
+    {
+        use rayon::prelude::*;
+
+        (0..max_iters)
+            .into_par_iter()
+            .map(|_| keypair_gen())
+            .find_any(|(_, address)| address.matches_pattern(pattern))
+    }
+    #[cfg(not(feature = "rayon"))]
+    {
+        (0..max_iters)
+            .map(|_| keypair_gen())
+            .find(|(_, address)| address.matches_pattern(pattern))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        core::str::FromStr,
+        std::sync::atomic::{AtomicU64, Ordering},
+    };
+
+    const SYSTEM_PROGRAM: &str = "11111111111111111111111111111111";
+    const TOKEN_PROGRAM: &str = "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA";
+
+    #[test]
+    fn test_starts_with_base58() {
+        let token = Address::from_str(TOKEN_PROGRAM).unwrap();
+        assert!(token.starts_with_base58(""));
+        assert!(token.starts_with_base58("Token"));
+        assert!(token.starts_with_base58(TOKEN_PROGRAM));
+        assert!(!token.starts_with_base58("token"));
+        assert!(!token.starts_with_base58("Tokenz"));
+        assert!(!token.starts_with_base58(&[TOKEN_PROGRAM, "1"].concat()));
+
+        let system = Address::from_str(SYSTEM_PROGRAM).unwrap();
+        assert!(system.starts_with_base58("1111"));
+        assert!(!system.starts_with_base58(&"1".repeat(33)));
+    }
+
+    #[test]
+    fn test_matches_pattern() {
+        let token = Address::from_str(TOKEN_PROGRAM).unwrap();
+        let matches =
+            |prefix, suffix| token.matches_pattern(&AddressPattern::new(prefix, suffix).unwrap());
+        assert!(matches("", ""));
+        assert!(matches("Token", ""));
+        assert!(matches("", "5DA"));
+        assert!(matches("Tokenkeg", "VQ5DA"));
+        assert!(matches(TOKEN_PROGRAM, TOKEN_PROGRAM));
+        assert!(!matches("Token", "5Da"));
+        assert!(!matches("token", "5DA"));
+        assert!(!matches("", "Token"));
+
+        // patterns longer than the encoding of the address never match
+        let system = Address::from_str(SYSTEM_PROGRAM).unwrap();
+        let pattern = AddressPattern::new("", &"1".repeat(33)).unwrap();
+        assert!(!system.matches_pattern(&pattern));
+        assert!(system.matches_pattern(&AddressPattern::new("1", &"1".repeat(32)).unwrap()));
+    }
+
+    #[test]
+    fn test_matches_pattern_ignore_case() {
+        let token = Address::from_str(TOKEN_PROGRAM).unwrap();
+        let pattern = AddressPattern::new_ignore_case("TOKENKEG", "vq5da").unwrap();
+        assert_eq!(pattern.prefix(), "tokenkeg");
+        assert_eq!(pattern.suffix(), "vq5da");
+        assert!(pattern.ignores_case());
+        assert!(token.matches_pattern(&pattern));
+        assert!(!token.matches_pattern(&AddressPattern::new_ignore_case("tokens", "").unwrap()));
+
+        // `l` and `O` are not base58 characters, but `L` and `o` are
+        assert_eq!(
+            AddressPattern::new("l", ""),
+            Err(ParseAddressError::Invalid)
+        );
+        assert_eq!(
+            AddressPattern::new("", "O"),
+            Err(ParseAddressError::Invalid)
+        );
+        assert!(AddressPattern::new_ignore_case("l", "O").is_ok());
+        assert_eq!(
+            AddressPattern::new_ignore_case("0", ""),
+            Err(ParseAddressError::Invalid)
+        );
+    }
+
+    #[test]
+    fn test_invalid_pattern() {
+        assert_eq!(
+            AddressPattern::new(&"1".repeat(MAX_BASE58_LEN + 1), ""),
+            Err(ParseAddressError::WrongSize)
+        );
+        assert_eq!(
+            AddressPattern::new("", "abc-"),
+            Err(ParseAddressError::Invalid)
+        );
+        assert_eq!(
+            AddressPattern::new("é", ""),
+            Err(ParseAddressError::Invalid)
+        );
+    }
+
+    #[test]
+    fn test_grind() {
+        // addresses counting up from a fixed seed, with a dummy secret key
+        let counter = AtomicU64::new(0);
+        let keypair_gen = || {
+            let i = counter.fetch_add(1, Ordering::Relaxed);
+            let mut bytes = [7; 32];
+            bytes[..8].copy_from_slice(&i.to_le_bytes());
+            (bytes, Address::new_from_array(bytes))
+        };
+
+        let pattern = AddressPattern::new("A", "").unwrap();
+        let (secret, address) = grind(&pattern, keypair_gen, 100_000).unwrap();
+        assert!(address.starts_with_base58("A"));
+        assert_eq!(secret, address.to_bytes());
+
+        let pattern = AddressPattern::new_ignore_case("z", "").unwrap();
+        let (_, address) = grind(&pattern, keypair_gen, 100_000).unwrap();
+        assert!(address.starts_with_base58("z") || address.starts_with_base58("Z"));
+
+        // no address of 32 bytes encodes to more than 44 characters
+        let pattern = AddressPattern::new(&"z".repeat(MAX_BASE58_LEN), "").unwrap();
+        let iters = counter.load(Ordering::Relaxed);
+        assert_eq!(grind(&pattern, keypair_gen, 100), None);
+        assert_eq!(counter.load(Ordering::Relaxed), iters + 100);
+    }
+}
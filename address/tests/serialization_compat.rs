@@ -0,0 +1,121 @@
+//! Pins the borsh and bincode encodings of `Address` and `Hash`.
+//!
+//! Both types are stored in account data written and read by different
+//! programs, so their encodings, and the borsh schemas describing them, must
+//! stay the raw 32 bytes.
+
+use {
+    borsh::{
+        schema::{BorshSchemaContainer, Definition, Fields},
+        BorshSchema,
+    },
+    proptest::prelude::*,
+    solana_address::{Address, ADDRESS_BYTES},
+    solana_hash::Hash,
+    std::collections::BTreeMap,
+};
+
+fn samples() -> [[u8; 32]; 3] {
+    [[0; 32], [0xff; 32], core::array::from_fn(|i| i as u8)]
+}
+
+fn expected_schema(declaration: &str) -> BorshSchemaContainer {
+    let definitions = BTreeMap::from([
+        (
+            declaration.to_string(),
+            Definition::Struct {
+                fields: Fields::UnnamedFields(vec!["[u8; 32]".to_string()]),
+            },
+        ),
+        (
+            "[u8; 32]".to_string(),
+            Definition::Sequence {
+                length_width: 0,
+                length_range: 32..=32,
+                elements: "u8".to_string(),
+            },
+        ),
+        ("u8".to_string(), Definition::Primitive(1)),
+    ]);
+    BorshSchemaContainer::new(declaration.to_string(), definitions)
+}
+
+#[test]
+fn test_borsh_bytes() {
+    for bytes in samples() {
+        assert_eq!(borsh::to_vec(&Address::from(bytes)).unwrap(), bytes);
+        assert_eq!(borsh::to_vec(&Hash::from(bytes)).unwrap(), bytes);
+    }
+}
+
+#[test]
+fn test_bincode_bytes() {
+    for bytes in samples() {
+        assert_eq!(bincode::serialize(&Address::from(bytes)).unwrap(), bytes);
+        assert_eq!(bincode::serialize(&Hash::from(bytes)).unwrap(), bytes);
+    }
+}
+
+#[test]
+fn test_borsh_schema() {
+    assert_eq!(Address::declaration(), "Address");
+    assert_eq!(Hash::declaration(), "Hash");
+    assert_eq!(
+        BorshSchemaContainer::for_type::<Address>(),
+        expected_schema("Address")
+    );
+    assert_eq!(
+        BorshSchemaContainer::for_type::<Hash>(),
+        expected_schema("Hash")
+    );
+    assert_eq!(borsh::max_serialized_size::<Address>(), Ok(ADDRESS_BYTES));
+    assert_eq!(borsh::max_serialized_size::<Hash>(), Ok(ADDRESS_BYTES));
+}
+
+#[test]
+fn test_try_from_borsh_slice() {
+    let bytes = samples()[2];
+    assert_eq!(
+        Address::try_from_borsh_slice(&bytes).unwrap(),
+        Address::from(bytes)
+    );
+    assert_eq!(
+        Hash::try_from_borsh_slice(&bytes).unwrap(),
+        Hash::from(bytes)
+    );
+
+    for len in [0, 31, 33] {
+        let bytes = vec![7; len];
+        let error = Address::try_from_borsh_slice(&bytes).unwrap_err();
+        assert_eq!(error.kind(), borsh::io::ErrorKind::InvalidData);
+        let error = Hash::try_from_borsh_slice(&bytes).unwrap_err();
+        assert_eq!(error.kind(), borsh::io::ErrorKind::InvalidData);
+    }
+}
+
+proptest! {
+    #[test]
+    fn test_round_trips(bytes in any::<[u8; 32]>()) {
+        let address = Address::from(bytes);
+        let hash = Hash::from(bytes);
+
+        let address_borsh = borsh::to_vec(&address).unwrap();
+        let hash_borsh = borsh::to_vec(&hash).unwrap();
+        // the layouts are identical
+        prop_assert_eq!(&address_borsh, &hash_borsh);
+        let from_hash_borsh = borsh::from_slice::<Address>(&hash_borsh).unwrap();
+        prop_assert_eq!(&from_hash_borsh, &address);
+        let decoded = Address::try_from_borsh_slice(&address_borsh).unwrap();
+        prop_assert_eq!(&decoded, &address);
+        let decoded = Hash::try_from_borsh_slice(&hash_borsh).unwrap();
+        prop_assert_eq!(&decoded, &hash);
+
+        let address_bincode = bincode::serialize(&address).unwrap();
+        let hash_bincode = bincode::serialize(&hash).unwrap();
+        prop_assert_eq!(&address_bincode, &hash_bincode);
+        let decoded = bincode::deserialize::<Address>(&address_bincode).unwrap();
+        prop_assert_eq!(&decoded, &address);
+        let decoded = bincode::deserialize::<Hash>(&hash_bincode).unwrap();
+        prop_assert_eq!(&decoded, &hash);
+    }
+}
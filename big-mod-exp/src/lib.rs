@@ -9,6 +9,144 @@ pub struct BigModExpParams {
     pub modulus_len: u64,
 }
 
+/// Maximum length in bytes of each of the base, exponent and modulus accepted
+/// by the `sol_big_mod_exp` syscall.
+pub const MAX_BIG_MOD_EXP_INPUT_LEN: usize = 512;
+
+/// Errors returned by [`BigModExpInput`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BigModExpError {
+    /// The modulus has no bytes.
+    EmptyModulus,
+    /// The base, exponent or modulus is longer than
+    /// [`MAX_BIG_MOD_EXP_INPUT_LEN`].
+    InputTooLong,
+    /// The syscall returned a non-zero status code.
+    Syscall(u64),
+}
+
+impl core::error::Error for BigModExpError {}
+
+impl core::fmt::Display for BigModExpError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            BigModExpError::EmptyModulus => f.write_str("modulus is empty"),
+            BigModExpError::InputTooLong => {
+                write!(f, "input is longer than {MAX_BIG_MOD_EXP_INPUT_LEN} bytes")
+            }
+            BigModExpError::Syscall(code) => write!(f, "big_mod_exp syscall failed: {code}"),
+        }
+    }
+}
+
+/// Validated input of a big integer modular exponentiation.
+///
+/// All numbers are big-endian. The result of [`compute`](Self::compute) is
+/// left-padded with zeros to the length of the modulus, so that it is laid
+/// out exactly like the output of the `sol_big_mod_exp` syscall.
+///
+/// # Examples
+///
+/// ```
+/// use solana_big_mod_exp::BigModExpInput;
+///
+/// // 4^13 mod 497 = 445
+/// let input = BigModExpInput::new(&[4], &[13], &[0x01, 0xf1]).unwrap();
+/// assert!(input.is_modulus_odd());
+/// assert_eq!(input.compute().unwrap(), vec![0x01, 0xbd]);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BigModExpInput<'a> {
+    base: &'a [u8],
+    exponent: &'a [u8],
+    modulus: &'a [u8],
+}
+
+impl<'a> BigModExpInput<'a> {
+    /// Validate the lengths of `base`, `exponent` and `modulus`.
+    ///
+    /// The modulus must not be empty, and no input may be longer than
+    /// [`MAX_BIG_MOD_EXP_INPUT_LEN`], which the syscall rejects.
+    pub fn new(
+        base: &'a [u8],
+        exponent: &'a [u8],
+        modulus: &'a [u8],
+    ) -> Result<Self, BigModExpError> {
+        if modulus.is_empty() {
+            return Err(BigModExpError::EmptyModulus);
+        }
+        if base.len() > MAX_BIG_MOD_EXP_INPUT_LEN
+            || exponent.len() > MAX_BIG_MOD_EXP_INPUT_LEN
+            || modulus.len() > MAX_BIG_MOD_EXP_INPUT_LEN
+        {
+            return Err(BigModExpError::InputTooLong);
+        }
+        Ok(Self {
+            base,
+            exponent,
+            modulus,
+        })
+    }
+
+    pub fn base(&self) -> &'a [u8] {
+        self.base
+    }
+
+    pub fn exponent(&self) -> &'a [u8] {
+        self.exponent
+    }
+
+    pub fn modulus(&self) -> &'a [u8] {
+        self.modulus
+    }
+
+    /// Whether the modulus is odd, in which case implementations can use
+    /// Montgomery multiplication.
+    pub fn is_modulus_odd(&self) -> bool {
+        // `new` rejects empty moduli
+        self.modulus.last().is_some_and(|byte| byte & 1 == 1)
+    }
+
+    /// The raw parameters passed to the `sol_big_mod_exp` syscall.
+    ///
+    /// The pointers are only valid while the borrowed inputs are alive.
+    pub fn params(&self) -> BigModExpParams {
+        BigModExpParams {
+            base: self.base.as_ptr(),
+            base_len: self.base.len() as u64,
+            exponent: self.exponent.as_ptr(),
+            exponent_len: self.exponent.len() as u64,
+            modulus: self.modulus.as_ptr(),
+            modulus_len: self.modulus.len() as u64,
+        }
+    }
+
+    /// Compute `base ^ exponent mod modulus`, left-padded to the length of
+    /// the modulus.
+    pub fn compute(&self) -> Result<Vec<u8>, BigModExpError> {
+        #[cfg(not(target_os = "solana"))]
+        {
+            Ok(big_mod_exp(self.base, self.exponent, self.modulus))
+        }
+
+        #[cfg(target_os = "solana")]
+        {
+            let mut return_value = vec![0_u8; self.modulus.len()];
+            let param = self.params();
+            let result = unsafe {
+                solana_define_syscall::definitions::sol_big_mod_exp(
+                    &param as *const _ as *const u8,
+                    return_value.as_mut_ptr(),
+                )
+            };
+            match result {
+                0 => Ok(return_value),
+                code => Err(BigModExpError::Syscall(code)),
+            }
+        }
+    }
+}
+
 /// Big integer modular exponentiation
 pub fn big_mod_exp(base: &[u8], exponent: &[u8], modulus: &[u8]) -> Vec<u8> {
     #[cfg(not(target_os = "solana"))]
@@ -84,4 +222,96 @@ mod tests {
             assert_eq!(result, expected);
         });
     }
+
+    #[derive(serde_derive::Deserialize)]
+    #[serde(rename_all = "PascalCase")]
+    struct TestCase {
+        base: String,
+        exponent: String,
+        modulus: String,
+        expected: String,
+    }
+
+    fn check_test_cases(test_data: &str) {
+        let test_cases: Vec<TestCase> = serde_json::from_str(test_data).unwrap();
+        test_cases.iter().for_each(|test| {
+            let base = array_bytes::hex2bytes_unchecked(&test.base);
+            let exponent = array_bytes::hex2bytes_unchecked(&test.exponent);
+            let modulus = array_bytes::hex2bytes_unchecked(&test.modulus);
+            let expected = array_bytes::hex2bytes_unchecked(&test.expected);
+            let input = BigModExpInput::new(&base, &exponent, &modulus).unwrap();
+            let result = input.compute().unwrap();
+            assert_eq!(result.len(), modulus.len());
+            assert_eq!(result, expected);
+        });
+    }
+
+    #[test]
+    fn big_mod_exp_input_test() {
+        // agrees with the known syscall outputs
+        check_test_cases(include_str!("../tests/data/big_mod_exp_cases.json"));
+    }
+
+    #[test]
+    fn big_mod_exp_input_rsa_2048_test() {
+        // signature ^ 65537 mod n recovers the PKCS#1 v1.5 encoded digest, and
+        // the encoded digest ^ d mod n is the signature
+        check_test_cases(include_str!("../tests/data/rsa_2048_cases.json"));
+    }
+
+    #[test]
+    fn big_mod_exp_input_validation_test() {
+        let long = [1; MAX_BIG_MOD_EXP_INPUT_LEN + 1];
+        let max = [1; MAX_BIG_MOD_EXP_INPUT_LEN];
+        assert_eq!(
+            BigModExpInput::new(&[2], &[3], &[]),
+            Err(BigModExpError::EmptyModulus)
+        );
+        assert_eq!(
+            BigModExpInput::new(&long, &[3], &[5]),
+            Err(BigModExpError::InputTooLong)
+        );
+        assert_eq!(
+            BigModExpInput::new(&[2], &long, &[5]),
+            Err(BigModExpError::InputTooLong)
+        );
+        assert_eq!(
+            BigModExpInput::new(&[2], &[3], &long),
+            Err(BigModExpError::InputTooLong)
+        );
+        assert!(BigModExpInput::new(&max, &max, &max).is_ok());
+        // empty base and exponent are zero
+        assert_eq!(
+            BigModExpInput::new(&[], &[], &[0, 7]).unwrap().compute(),
+            Ok(vec![0, 1])
+        );
+    }
+
+    #[test]
+    fn big_mod_exp_input_padding_test() {
+        // 2^3 mod 0x0100 = 8, padded to the modulus length
+        let input = BigModExpInput::new(&[2], &[3], &[0, 0, 1, 0]).unwrap();
+        assert!(!input.is_modulus_odd());
+        assert_eq!(input.compute(), Ok(vec![0, 0, 0, 8]));
+
+        // a modulus of zero or one yields zeros
+        for modulus in [[0, 0], [0, 1]] {
+            let input = BigModExpInput::new(&[2], &[3], &modulus).unwrap();
+            assert_eq!(input.compute(), Ok(vec![0, 0]));
+        }
+    }
+
+    #[test]
+    fn big_mod_exp_input_params_test() {
+        let (base, exponent, modulus) = ([1, 2], [3], [4, 5, 7]);
+        let params = BigModExpInput::new(&base, &exponent, &modulus)
+            .unwrap()
+            .params();
+        assert_eq!(params.base, base.as_ptr());
+        assert_eq!(params.base_len, 2);
+        assert_eq!(params.exponent, exponent.as_ptr());
+        assert_eq!(params.exponent_len, 1);
+        assert_eq!(params.modulus, modulus.as_ptr());
+        assert_eq!(params.modulus_len, 3);
+    }
 }
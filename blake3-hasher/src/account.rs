@@ -0,0 +1,224 @@
+//! Content hashes of accounts.
+//!
+//! The hash of an account is the blake3 hash of the following bytes, in
+//! order:
+//!
+//! | Size         | Field                               |
+//! |--------------|-------------------------------------|
+//! | 8            | lamports, as a little-endian `u64`  |
+//! | `data.len()` | account data                        |
+//! | 1            | executable flag, `0` or `1`         |
+//! | 32           | owner address                       |
+//! | 32           | account address                     |
+//!
+//! The data is not length prefixed. Accounts without lamports do not exist
+//! as far as the runtime is concerned, so their hash is [`Hash::default`]
+//! regardless of their other fields.
+
+use {crate::hashv, alloc::vec::Vec, solana_address::Address, solana_hash::Hash};
+
+/// The fields of an account that make up its hash.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AccountRef<'a> {
+    pub lamports: u64,
+    pub data: &'a [u8],
+    pub executable: bool,
+    pub owner: &'a Address,
+    pub address: &'a Address,
+}
+
+impl AccountRef<'_> {
+    /// The hash of this account, see [`hash_account`].
+    pub fn hash(&self) -> Hash {
+        hash_account(
+            self.lamports,
+            self.data,
+            self.executable,
+            self.owner,
+            self.address,
+        )
+    }
+}
+
+/// Return the content hash of the account at `address`.
+///
+/// See the [module documentation](self) for the hashed bytes.
+pub fn hash_account(
+    lamports: u64,
+    data: &[u8],
+    executable: bool,
+    owner: &Address,
+    address: &Address,
+) -> Hash {
+    if lamports == 0 {
+        return Hash::default();
+    }
+    hashv(&[
+        &lamports.to_le_bytes(),
+        data,
+        &[u8::from(executable)],
+        owner.as_ref(),
+        address.as_ref(),
+    ])
+}
+
+/// Return the content hashes of `accounts`, in order.
+///
+/// With the `rayon` feature, the accounts are hashed in parallel on the
+/// host.
+pub fn hash_accounts(accounts: &[AccountRef]) -> Vec<Hash> {
+    #[cfg(all(feature = "rayon", not(any(target_os = "solana", target_arch = "bpf"))))]
+    {
+        use rayon::prelude::*;
+
+        accounts.par_iter().map(AccountRef::hash).collect()
+    }
+    #[cfg(not(all(feature = "rayon", not(any(target_os = "solana", target_arch = "bpf")))))]
+    {
+        accounts.iter().map(AccountRef::hash).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A port of `AccountsDb::hash_account_data` from `solana-accounts-db`
+    /// 1.18.0, hashing the same bytes with the same buffering. That version
+    /// hashes the rent epoch after the lamports; later versions dropped it.
+    fn agave_hash_account_data(
+        lamports: u64,
+        owner: &Address,
+        executable: bool,
+        rent_epoch: Option<u64>,
+        data: &[u8],
+        pubkey: &Address,
+    ) -> Hash {
+        if lamports == 0 {
+            return Hash::default();
+        }
+        let mut hasher = blake3::Hasher::new();
+        const BUF_SIZE: usize = 128;
+        const TOTAL_FIELD_SIZE: usize = 8 + 8 + 8 + 1 + 32 + 32;
+        let mut buffer = Vec::with_capacity(BUF_SIZE);
+        buffer.extend_from_slice(&lamports.to_le_bytes());
+        if let Some(rent_epoch) = rent_epoch {
+            buffer.extend_from_slice(&rent_epoch.to_le_bytes());
+        }
+        if data.len() > BUF_SIZE - TOTAL_FIELD_SIZE {
+            hasher.update(&buffer);
+            buffer.clear();
+            hasher.update(data);
+        } else {
+            buffer.extend_from_slice(data);
+        }
+        buffer.push(u8::from(executable));
+        buffer.extend_from_slice(owner.as_ref());
+        buffer.extend_from_slice(pubkey.as_ref());
+        hasher.update(&buffer);
+        Hash::new_from_array(hasher.finalize().into())
+    }
+
+    #[test]
+    fn test_agave_vector() {
+        // `test_hash_stored_account` of `solana-accounts-db` 1.18.0
+        let pubkey = Address::new_from_array(core::array::from_fn(|i| 0x19 + i as u8));
+        let owner = Address::new_from_array(core::array::from_fn(|i| 0x49 + i as u8));
+        assert_eq!(
+            agave_hash_account_data(
+                0x39_3a_3b_3c_3d_3e_3f_40,
+                &owner,
+                false,
+                Some(0x41_42_43_44_45_46_47_48),
+                &[0x69, 0x6a, 0x6b],
+                &pubkey,
+            ),
+            "4xuaE8UfH8EYsPyDZvJXUScoZSyxUJf2BpzVMLTFh497"
+                .parse::<Hash>()
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_matches_agave_without_rent_epoch() {
+        let pubkey = Address::new_from_array([1; 32]);
+        let owner = Address::new_from_array([2; 32]);
+        let data: Vec<u8> = (0..=255).collect();
+        // around the size of the data hashed in the buffer
+        for len in [0, 3, 39, 40, 41, 256] {
+            for executable in [false, true] {
+                assert_eq!(
+                    hash_account(1_000_000_000, &data[..len], executable, &owner, &pubkey),
+                    agave_hash_account_data(
+                        1_000_000_000,
+                        &owner,
+                        executable,
+                        None,
+                        &data[..len],
+                        &pubkey,
+                    )
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_zero_lamports() {
+        let owner = Address::new_from_array([2; 32]);
+        let address = Address::new_from_array([1; 32]);
+        assert_eq!(
+            hash_account(0, b"hello world", true, &owner, &address),
+            Hash::default()
+        );
+        assert_eq!(
+            hash_account(0, &[], false, &owner, &address),
+            Hash::default()
+        );
+    }
+
+    #[test]
+    fn test_fields_are_hashed() {
+        let owner = Address::new_from_array([2; 32]);
+        let address = Address::new_from_array([1; 32]);
+        let base = hash_account(5, &[1, 2], false, &owner, &address);
+        assert_ne!(hash_account(6, &[1, 2], false, &owner, &address), base);
+        assert_ne!(hash_account(5, &[1, 3], false, &owner, &address), base);
+        assert_ne!(hash_account(5, &[1, 2], true, &owner, &address), base);
+        assert_ne!(hash_account(5, &[1, 2], false, &address, &address), base);
+        assert_ne!(hash_account(5, &[1, 2], false, &owner, &owner), base);
+    }
+
+    #[test]
+    fn test_hash_accounts() {
+        let addresses: Vec<_> = (0..64u8)
+            .map(|i| Address::new_from_array([i; 32]))
+            .collect();
+        let data: Vec<u8> = (0..=255).collect();
+        let accounts: Vec<_> = addresses
+            .iter()
+            .enumerate()
+            .map(|(i, address)| AccountRef {
+                lamports: i as u64 % 3,
+                data: &data[..i * 4],
+                executable: i % 2 == 0,
+                owner: &addresses[63 - i],
+                address,
+            })
+            .collect();
+        let hashes = hash_accounts(&accounts);
+        assert_eq!(hashes.len(), accounts.len());
+        for (account, hash) in accounts.iter().zip(&hashes) {
+            assert_eq!(
+                *hash,
+                hash_account(
+                    account.lamports,
+                    account.data,
+                    account.executable,
+                    account.owner,
+                    account.address
+                )
+            );
+        }
+        assert!(hash_accounts(&[]).is_empty());
+    }
+}
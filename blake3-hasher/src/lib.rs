@@ -3,6 +3,11 @@
 //! [blake3]: https://github.com/BLAKE3-team/BLAKE3
 #![no_std]
 #![cfg_attr(docsrs, feature(doc_cfg))]
+#[cfg(feature = "account-hash")]
+extern crate alloc;
+
+#[cfg(feature = "account-hash")]
+pub mod account;
 
 pub use solana_hash::{Hash, ParseHashError, HASH_BYTES, MAX_BASE58_LEN};
 
@@ -3,6 +3,8 @@ pub(crate) mod addition;
 pub mod compression;
 pub(crate) mod multiplication;
 pub(crate) mod pairing;
+#[cfg(not(target_os = "solana"))]
+pub(crate) mod validation;
 
 /// This module contains the versioned syscall implementations and is intended for use
 /// primarily by validator code.
@@ -86,6 +88,15 @@ pub mod prelude {
         },
         AltBn128Error,
     };
+    #[cfg(not(target_os = "solana"))]
+    pub use crate::{
+        target_arch::Endianness,
+        validation::{
+            alt_bn128_g1_multiplication_with_options, alt_bn128_g2_addition_with_options,
+            alt_bn128_g2_multiplication_with_options, alt_bn128_pairing_with_options,
+            Bn254ValidationOptions,
+        },
+    };
 }
 
 #[cfg(not(target_os = "solana"))]
@@ -126,6 +137,12 @@ pub enum AltBn128Error {
     TryIntoVecError(Vec<u8>),
     #[error("Failed to convert projective to affine g1")]
     ProjectiveToG1Failed,
+    // the following are only returned by the host-side strict validation,
+    // and never by the syscalls
+    #[error("The scalar is not less than the group order")]
+    NonCanonicalScalar,
+    #[error("The point is not in the prime-order subgroup")]
+    NotInSubgroup,
 }
 
 impl From<u64> for AltBn128Error {
@@ -136,6 +153,8 @@ impl From<u64> for AltBn128Error {
             3 => AltBn128Error::SliceOutOfBounds,
             4 => AltBn128Error::TryIntoVecError(Vec::new()),
             5 => AltBn128Error::ProjectiveToG1Failed,
+            7 => AltBn128Error::NonCanonicalScalar,
+            8 => AltBn128Error::NotInSubgroup,
             _ => AltBn128Error::UnexpectedError,
         }
     }
@@ -151,6 +170,8 @@ impl From<AltBn128Error> for u64 {
             AltBn128Error::TryIntoVecError(_) => 4,
             AltBn128Error::ProjectiveToG1Failed => 5,
             AltBn128Error::UnexpectedError => 6,
+            AltBn128Error::NonCanonicalScalar => 7,
+            AltBn128Error::NotInSubgroup => 8,
         }
     }
 }
@@ -0,0 +1,353 @@
+//! Stricter validation of the inputs of the `alt_bn128` operations.
+//!
+//! The syscalls accept any 32-byte scalar, reducing it modulo the order of
+//! the groups, and the G2 addition accepts points on the curve outside of the
+//! prime-order subgroup. The functions of this module run the same operations
+//! on the host with opt-in checks rejecting such inputs, e.g. to validate user
+//! input before paying for its verification on chain. With the default
+//! options, they return exactly the results of the syscalls.
+use {
+    crate::{
+        addition::{
+            alt_bn128_versioned_g2_addition, VersionedG2Addition, ALT_BN128_G2_ADDITION_INPUT_SIZE,
+        },
+        consts::{ALT_BN128_FIELD_SIZE, ALT_BN128_G1_POINT_SIZE, ALT_BN128_G2_POINT_SIZE},
+        multiplication::{
+            alt_bn128_versioned_g1_multiplication, alt_bn128_versioned_g2_multiplication,
+            VersionedG1Multiplication, VersionedG2Multiplication,
+            ALT_BN128_G1_MULTIPLICATION_INPUT_SIZE, ALT_BN128_G2_MULTIPLICATION_INPUT_SIZE,
+        },
+        pairing::{alt_bn128_versioned_pairing, VersionedPairing, ALT_BN128_PAIRING_ELEMENT_SIZE},
+        target_arch::{convert_endianness, Endianness},
+        AltBn128Error, PodG2,
+    },
+    ark_bn254::Fr,
+    ark_ff::{BigInteger256, PrimeField},
+    ark_serialize::CanonicalDeserialize,
+};
+
+/// The checks run by the `*_with_options` functions, on top of the checks of
+/// the syscalls.
+///
+/// The default options run no additional check.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Bn254ValidationOptions {
+    /// Rejects scalars that are not less than the order of the groups with
+    /// [`AltBn128Error::NonCanonicalScalar`].
+    pub require_canonical_scalar: bool,
+    /// Rejects G2 points outside of the prime-order subgroup with
+    /// [`AltBn128Error::NotInSubgroup`].
+    ///
+    /// G1 has a cofactor of 1, so all the points on the curve are in its
+    /// subgroup.
+    pub require_subgroup_check: bool,
+}
+
+/// Checks that a scalar encoded with `endianness` is less than the order of
+/// the groups.
+fn check_scalar(
+    bytes: &[u8; ALT_BN128_FIELD_SIZE],
+    endianness: &Endianness,
+) -> Result<(), AltBn128Error> {
+    let le_bytes = match endianness {
+        Endianness::BE => convert_endianness::<ALT_BN128_FIELD_SIZE, ALT_BN128_FIELD_SIZE>(bytes),
+        Endianness::LE => *bytes,
+    };
+    let scalar = BigInteger256::deserialize_uncompressed_unchecked(le_bytes.as_slice())
+        .map_err(|_| AltBn128Error::InvalidInputData)?;
+    if scalar >= Fr::MODULUS {
+        return Err(AltBn128Error::NonCanonicalScalar);
+    }
+    Ok(())
+}
+
+/// Checks that a G2 point encoded with `endianness` is in the prime-order
+/// subgroup, if it is on the curve.
+///
+/// Invalid encodings are left to the operations to reject, with the errors
+/// of the syscalls.
+fn check_g2_subgroup(bytes: &[u8], endianness: &Endianness) -> Result<(), AltBn128Error> {
+    let pod = match endianness {
+        Endianness::BE => PodG2::from_be_bytes(bytes)?,
+        Endianness::LE => PodG2::from_le_bytes(bytes)?,
+    };
+    match pod.into_affine_unchecked() {
+        Ok(point) if !point.is_in_correct_subgroup_assuming_on_curve() => {
+            Err(AltBn128Error::NotInSubgroup)
+        }
+        _ => Ok(()),
+    }
+}
+
+/// The G1 multiplication, as with `alt_bn128_g1_multiplication_be` and
+/// `alt_bn128_g1_multiplication_le`, with the additional checks of
+/// `options`.
+///
+/// Big-endian inputs shorter than [`ALT_BN128_G1_MULTIPLICATION_INPUT_SIZE`]
+/// are padded with zeros, as with the syscall.
+pub fn alt_bn128_g1_multiplication_with_options(
+    input: &[u8],
+    endianness: Endianness,
+    options: Bn254ValidationOptions,
+) -> Result<Vec<u8>, AltBn128Error> {
+    let valid_len = match endianness {
+        Endianness::BE => input.len() <= ALT_BN128_G1_MULTIPLICATION_INPUT_SIZE,
+        Endianness::LE => input.len() == ALT_BN128_G1_MULTIPLICATION_INPUT_SIZE,
+    };
+    if options.require_canonical_scalar && valid_len {
+        let mut scalar = [0u8; ALT_BN128_FIELD_SIZE];
+        if let Some(bytes) = input.get(ALT_BN128_G1_POINT_SIZE..) {
+            scalar[..bytes.len()].copy_from_slice(bytes);
+        }
+        check_scalar(&scalar, &endianness)?;
+    }
+    alt_bn128_versioned_g1_multiplication(VersionedG1Multiplication::V1, input, endianness)
+}
+
+/// The G2 multiplication, as with `alt_bn128_g2_multiplication_be` and
+/// `alt_bn128_g2_multiplication_le`, with the additional checks of
+/// `options`.
+pub fn alt_bn128_g2_multiplication_with_options(
+    input: &[u8],
+    endianness: Endianness,
+    options: Bn254ValidationOptions,
+) -> Result<Vec<u8>, AltBn128Error> {
+    if input.len() == ALT_BN128_G2_MULTIPLICATION_INPUT_SIZE {
+        let (point, scalar) = input.split_at(ALT_BN128_G2_POINT_SIZE);
+        if options.require_subgroup_check {
+            check_g2_subgroup(point, &endianness)?;
+        }
+        if options.require_canonical_scalar {
+            check_scalar(scalar.try_into().unwrap(), &endianness)?;
+        }
+    }
+    alt_bn128_versioned_g2_multiplication(VersionedG2Multiplication::V0, input, endianness)
+}
+
+/// The G2 addition, as with `alt_bn128_g2_addition_be` and
+/// `alt_bn128_g2_addition_le`, with the additional checks of `options`.
+///
+/// There is no G1 counterpart, as none of the checks applies to the G1
+/// addition.
+pub fn alt_bn128_g2_addition_with_options(
+    input: &[u8],
+    endianness: Endianness,
+    options: Bn254ValidationOptions,
+) -> Result<Vec<u8>, AltBn128Error> {
+    if options.require_subgroup_check && input.len() == ALT_BN128_G2_ADDITION_INPUT_SIZE {
+        for point in input.chunks_exact(ALT_BN128_G2_POINT_SIZE) {
+            check_g2_subgroup(point, &endianness)?;
+        }
+    }
+    alt_bn128_versioned_g2_addition(VersionedG2Addition::V0, input, endianness)
+}
+
+/// The pairing, as with `alt_bn128_pairing_be` and `alt_bn128_pairing_le`,
+/// with the additional checks of `options`.
+pub fn alt_bn128_pairing_with_options(
+    input: &[u8],
+    endianness: Endianness,
+    options: Bn254ValidationOptions,
+) -> Result<Vec<u8>, AltBn128Error> {
+    #[allow(clippy::manual_is_multiple_of)]
+    if options.require_subgroup_check && input.len() % ALT_BN128_PAIRING_ELEMENT_SIZE == 0 {
+        for element in input.chunks_exact(ALT_BN128_PAIRING_ELEMENT_SIZE) {
+            check_g2_subgroup(&element[ALT_BN128_G1_POINT_SIZE..], &endianness)?;
+        }
+    }
+    alt_bn128_versioned_pairing(VersionedPairing::V1, input, endianness)
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{
+            addition::{alt_bn128_g2_addition_be, alt_bn128_g2_addition_le},
+            consts::ALT_BN128_FQ2_SIZE,
+            multiplication::{alt_bn128_g1_multiplication_be, alt_bn128_g2_multiplication_be},
+            pairing::alt_bn128_pairing_be,
+            target_arch::G2,
+        },
+        ark_bn254::Fq2,
+        ark_ec::AffineRepr,
+        ark_ff::BigInteger,
+        ark_serialize::{CanonicalSerialize, Compress},
+    };
+
+    const STRICT: Bn254ValidationOptions = Bn254ValidationOptions {
+        require_canonical_scalar: true,
+        require_subgroup_check: true,
+    };
+
+    fn g1_be(point: ark_bn254::G1Affine) -> Vec<u8> {
+        let mut bytes = [0u8; ALT_BN128_G1_POINT_SIZE];
+        point
+            .x
+            .serialize_with_mode(&mut bytes[..ALT_BN128_FIELD_SIZE], Compress::No)
+            .unwrap();
+        point
+            .y
+            .serialize_with_mode(&mut bytes[ALT_BN128_FIELD_SIZE..], Compress::No)
+            .unwrap();
+        convert_endianness::<ALT_BN128_FIELD_SIZE, ALT_BN128_G1_POINT_SIZE>(&bytes).to_vec()
+    }
+
+    fn g2_be(point: G2) -> Vec<u8> {
+        let mut bytes = [0u8; ALT_BN128_G2_POINT_SIZE];
+        point
+            .x
+            .serialize_with_mode(&mut bytes[..ALT_BN128_FQ2_SIZE], Compress::No)
+            .unwrap();
+        point
+            .y
+            .serialize_with_mode(&mut bytes[ALT_BN128_FQ2_SIZE..], Compress::No)
+            .unwrap();
+        convert_endianness::<ALT_BN128_FQ2_SIZE, ALT_BN128_G2_POINT_SIZE>(&bytes).to_vec()
+    }
+
+    /// A point on the G2 curve, outside of the prime-order subgroup.
+    fn g2_outside_subgroup() -> G2 {
+        (1u64..)
+            .filter_map(|x| G2::get_point_from_x_unchecked(Fq2::from(x), false))
+            .find(|point| !point.is_in_correct_subgroup_assuming_on_curve())
+            .unwrap()
+    }
+
+    /// The order of the groups, as a big-endian scalar.
+    fn order_be() -> Vec<u8> {
+        Fr::MODULUS.to_bytes_be()
+    }
+
+    #[test]
+    fn test_non_canonical_scalar() {
+        let mut order_minus_one = Fr::MODULUS;
+        order_minus_one.sub_with_borrow(&BigInteger256::from(1u64));
+        for (scalar, canonical) in [
+            (order_minus_one.to_bytes_be(), true),
+            (order_be(), false),
+            ([0xff; 32].to_vec(), false),
+        ] {
+            let input = [g1_be(ark_bn254::G1Affine::generator()), scalar.clone()].concat();
+            let result = alt_bn128_g1_multiplication_with_options(&input, Endianness::BE, STRICT);
+            if canonical {
+                assert!(result.is_ok());
+            } else {
+                assert_eq!(result, Err(AltBn128Error::NonCanonicalScalar));
+            }
+
+            let input = [g2_be(G2::generator()), scalar].concat();
+            let result = alt_bn128_g2_multiplication_with_options(&input, Endianness::BE, STRICT);
+            if canonical {
+                assert!(result.is_ok());
+            } else {
+                assert_eq!(result, Err(AltBn128Error::NonCanonicalScalar));
+            }
+        }
+
+        // the scalar is checked in the byte order of the input
+        let input = [
+            g1_be(ark_bn254::G1Affine::generator()),
+            order_minus_one.to_bytes_be(),
+        ]
+        .concat();
+        let input_le = convert_endianness::<
+            ALT_BN128_FIELD_SIZE,
+            ALT_BN128_G1_MULTIPLICATION_INPUT_SIZE,
+        >(&input.try_into().unwrap());
+        assert!(
+            alt_bn128_g1_multiplication_with_options(&input_le, Endianness::LE, STRICT).is_ok()
+        );
+
+        // short big-endian inputs are padded
+        let input = [g1_be(ark_bn254::G1Affine::generator()), order_be()].concat();
+        assert_eq!(
+            alt_bn128_g1_multiplication_with_options(&input[..90], Endianness::BE, STRICT),
+            alt_bn128_g1_multiplication_be(&input[..90]),
+        );
+    }
+
+    #[test]
+    fn test_g2_outside_subgroup() {
+        let point = g2_outside_subgroup();
+        assert!(point.is_on_curve());
+        let point = g2_be(point);
+        let generator = g2_be(G2::generator());
+
+        let input = [point.clone(), [1; 32].to_vec()].concat();
+        assert_eq!(
+            alt_bn128_g2_multiplication_with_options(&input, Endianness::BE, STRICT),
+            Err(AltBn128Error::NotInSubgroup)
+        );
+
+        let input = [point.clone(), generator.clone()].concat();
+        assert!(alt_bn128_g2_addition_be(&input.clone().try_into().unwrap()).is_ok());
+        assert_eq!(
+            alt_bn128_g2_addition_with_options(&input, Endianness::BE, STRICT),
+            Err(AltBn128Error::NotInSubgroup)
+        );
+        let input = [generator.clone(), point.clone()].concat();
+        let input_le = convert_endianness::<ALT_BN128_FQ2_SIZE, ALT_BN128_G2_ADDITION_INPUT_SIZE>(
+            &input.try_into().unwrap(),
+        );
+        assert!(alt_bn128_g2_addition_le(&input_le).is_ok());
+        assert_eq!(
+            alt_bn128_g2_addition_with_options(&input_le, Endianness::LE, STRICT),
+            Err(AltBn128Error::NotInSubgroup)
+        );
+
+        let g1 = g1_be(ark_bn254::G1Affine::generator());
+        let input = [g1.clone(), generator, g1, point].concat();
+        assert_eq!(
+            alt_bn128_pairing_with_options(&input, Endianness::BE, STRICT),
+            Err(AltBn128Error::NotInSubgroup)
+        );
+    }
+
+    #[test]
+    fn test_default_options() {
+        let options = Bn254ValidationOptions::default();
+        let g1 = g1_be(ark_bn254::G1Affine::generator());
+        let g2 = g2_be(G2::generator());
+        let outside = g2_be(g2_outside_subgroup());
+        let scalars = [vec![0; 32], vec![7; 32], order_be(), vec![0xff; 32]];
+
+        for scalar in &scalars {
+            let input = [g1.clone(), scalar.clone()].concat();
+            for len in [0, 64, 80, input.len(), input.len() + 1] {
+                let input = [input.clone(), vec![1]].concat();
+                assert_eq!(
+                    alt_bn128_g1_multiplication_with_options(
+                        &input[..len],
+                        Endianness::BE,
+                        options
+                    ),
+                    alt_bn128_g1_multiplication_be(&input[..len]),
+                );
+            }
+            for point in [&g2, &outside] {
+                let input = [point.clone(), scalar.clone()].concat();
+                assert_eq!(
+                    alt_bn128_g2_multiplication_with_options(&input, Endianness::BE, options),
+                    alt_bn128_g2_multiplication_be(&input.try_into().unwrap()),
+                );
+            }
+        }
+
+        let input = [outside.clone(), g2.clone()].concat();
+        assert_eq!(
+            alt_bn128_g2_addition_with_options(&input, Endianness::BE, options),
+            alt_bn128_g2_addition_be(&input.try_into().unwrap()),
+        );
+        for input in [
+            [g1.clone(), g2].concat(),
+            [g1.clone(), outside].concat(),
+            vec![0; 191],
+        ] {
+            assert_eq!(
+                alt_bn128_pairing_with_options(&input, Endianness::BE, options),
+                alt_bn128_pairing_be(&input),
+            );
+        }
+    }
+}
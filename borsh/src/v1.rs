@@ -6,15 +6,120 @@ use {
     crate::macros::{
         impl_get_instance_packed_len, impl_get_packed_len_v1, impl_try_from_slice_unchecked,
     },
-    borsh::io,
+    borsh::{io, BorshDeserialize, BorshSerialize},
+    solana_program_error::ProgramError,
 };
 
 impl_get_packed_len_v1!(borsh);
 impl_try_from_slice_unchecked!(borsh, io);
 impl_get_instance_packed_len!(borsh, io);
 
+/// Migrates account data in place from the `Old` layout to the `New` layout
+///
+/// The data is read with [`try_from_slice_unchecked`], so trailing bytes
+/// after the old value are ignored. It is then transformed with `f`, and the
+/// result is written back to the start of `data`, with the rest of the
+/// account zeroed. `data` is typically the contents of
+/// `AccountInfo::try_borrow_mut_data`.
+///
+/// Layout differences between borsh versions, such as enums with explicit
+/// discriminants, must be expressed in the type definition of `Old` (e.g.
+/// with `#[borsh(use_discriminant = false)]`).
+///
+/// Returns `ProgramError::AccountDataTooSmall`, leaving `data` untouched, if
+/// the new serialized value doesn't fit in the account.
+pub fn migrate_account<Old: BorshDeserialize, New: BorshSerialize>(
+    data: &mut &mut [u8],
+    f: impl FnOnce(Old) -> New,
+) -> Result<(), ProgramError> {
+    let old = try_from_slice_unchecked::<Old>(data)?;
+    let new = f(old);
+    let new_len = get_instance_packed_len(&new)?;
+    if new_len > data.len() {
+        return Err(ProgramError::AccountDataTooSmall);
+    }
+    let (head, tail) = data.split_at_mut(new_len);
+    let mut writer: &mut [u8] = head;
+    new.serialize(&mut writer)?;
+    tail.fill(0);
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use {crate::macros::impl_tests, borsh::io};
     impl_tests!(borsh, io);
+
+    #[derive(BorshSerialize, BorshDeserialize, Debug, PartialEq)]
+    struct OldState {
+        owner: [u8; 4],
+        amount: u32,
+    }
+
+    #[derive(BorshSerialize, BorshDeserialize, Debug, PartialEq)]
+    struct NewState {
+        version: u8,
+        owner: [u8; 4],
+        amount: u64,
+        delegates: Vec<[u8; 4]>,
+    }
+
+    fn upgrade(old: OldState) -> NewState {
+        NewState {
+            version: 1,
+            owner: old.owner,
+            amount: u64::from(old.amount),
+            delegates: vec![old.owner],
+        }
+    }
+
+    #[test]
+    fn migrate_account_in_place() {
+        let old = OldState {
+            owner: [1, 2, 3, 4],
+            amount: 42,
+        };
+        let mut account = vec![0xaa; 32];
+        old.serialize(&mut account.as_mut_slice()).unwrap();
+        let mut data = account.as_mut_slice();
+
+        migrate_account(&mut data, upgrade).unwrap();
+
+        let expected = upgrade(old);
+        let new_len = get_instance_packed_len(&expected).unwrap();
+        assert_eq!(new_len, 21);
+        assert_eq!(
+            NewState::try_from_slice(&account[..new_len]).unwrap(),
+            expected
+        );
+        assert!(account[new_len..].iter().all(|byte| *byte == 0));
+    }
+
+    #[test]
+    fn migrate_account_too_small() {
+        let old = OldState {
+            owner: [1, 2, 3, 4],
+            amount: 42,
+        };
+        let mut account = vec![0; 16];
+        old.serialize(&mut account.as_mut_slice()).unwrap();
+        let original = account.clone();
+        let mut data = account.as_mut_slice();
+
+        assert_eq!(
+            migrate_account(&mut data, upgrade),
+            Err(ProgramError::AccountDataTooSmall)
+        );
+        assert_eq!(account, original);
+    }
+
+    #[test]
+    fn migrate_account_invalid_old_data() {
+        let mut account = vec![0; 4];
+        let mut data = account.as_mut_slice();
+        assert_eq!(
+            migrate_account(&mut data, upgrade),
+            Err(ProgramError::BorshIoError)
+        );
+    }
 }
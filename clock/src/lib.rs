@@ -24,6 +24,7 @@
 
 #[cfg(feature = "sysvar")]
 pub mod sysvar;
+pub mod typed;
 
 #[cfg(feature = "serde")]
 use serde_derive::{Deserialize, Serialize};
@@ -0,0 +1,287 @@
+//! Newtypes for slots and epochs.
+//!
+//! APIs take slots and epochs as the [`crate::Slot`] and [`crate::Epoch`]
+//! aliases of `u64`, which the compiler does not tell apart. The [`Slot`]
+//! and [`Epoch`] newtypes of this module are for code that wants to, such
+//! as command line tools: they parse with underscore separators, and
+//! convert to and from `u64` for the existing APIs.
+//!
+//! ```
+//! use solana_clock::typed::{Epoch, Slot, SlotOrEpoch};
+//!
+//! let slot: Slot = "250_000_000".parse().unwrap();
+//! assert_eq!(u64::from(slot), 250_000_000);
+//! assert_eq!(
+//!     "epoch:600".parse::<SlotOrEpoch>(),
+//!     Ok(SlotOrEpoch::Epoch(Epoch::new(600)))
+//! );
+//! ```
+
+use core::{fmt, ops::Deref, str::FromStr};
+#[cfg(feature = "serde")]
+use serde_derive::{Deserialize, Serialize};
+
+/// Error parsing a [`Slot`], an [`Epoch`] or a [`SlotOrEpoch`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ParseClockError {
+    /// The string has no digits.
+    Empty,
+    /// The string has a character other than digits and underscores, or
+    /// starts with an underscore.
+    InvalidDigit,
+    /// The number does not fit in a `u64`.
+    Overflow,
+    /// The prefix before `:` is not `slot` nor `epoch`.
+    UnknownPrefix,
+}
+
+impl core::error::Error for ParseClockError {}
+
+impl fmt::Display for ParseClockError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Empty => f.write_str("cannot parse a number from an empty string"),
+            Self::InvalidDigit => f.write_str("invalid digit found in string"),
+            Self::Overflow => f.write_str("number too large to fit in a u64"),
+            Self::UnknownPrefix => f.write_str("unknown prefix, expected `slot:` or `epoch:`"),
+        }
+    }
+}
+
+/// Parses a decimal `u64`, with optional underscore separators after the
+/// first digit.
+fn parse_u64(s: &str) -> Result<u64, ParseClockError> {
+    let bytes = s.as_bytes();
+    match bytes.first() {
+        None => return Err(ParseClockError::Empty),
+        Some(b'_') => return Err(ParseClockError::InvalidDigit),
+        Some(_) => (),
+    }
+    let mut value: u64 = 0;
+    for &byte in bytes {
+        match byte {
+            b'0'..=b'9' => {
+                value = value
+                    .checked_mul(10)
+                    .and_then(|value| value.checked_add(u64::from(byte - b'0')))
+                    .ok_or(ParseClockError::Overflow)?;
+            }
+            b'_' => (),
+            _ => return Err(ParseClockError::InvalidDigit),
+        }
+    }
+    Ok(value)
+}
+
+macro_rules! clock_newtype {
+    ($(#[$attr:meta])* $name:ident) => {
+        $(#[$attr])*
+        #[repr(transparent)]
+        #[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(transparent))]
+        #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        pub struct $name(pub u64);
+
+        impl $name {
+            /// Wraps `value`.
+            pub const fn new(value: u64) -> Self {
+                Self(value)
+            }
+
+            /// Returns the wrapped `u64`.
+            pub const fn get(self) -> u64 {
+                self.0
+            }
+
+            /// Adds `count`, returning `None` on overflow.
+            pub const fn checked_add(self, count: u64) -> Option<Self> {
+                match self.0.checked_add(count) {
+                    Some(value) => Some(Self(value)),
+                    None => None,
+                }
+            }
+
+            /// Subtracts `count`, returning `None` on underflow.
+            pub const fn checked_sub(self, count: u64) -> Option<Self> {
+                match self.0.checked_sub(count) {
+                    Some(value) => Some(Self(value)),
+                    None => None,
+                }
+            }
+
+            /// Returns how many are between `earlier` and `self`, or `None`
+            /// if `earlier` is after `self`.
+            pub const fn checked_since(self, earlier: Self) -> Option<u64> {
+                self.0.checked_sub(earlier.0)
+            }
+        }
+
+        impl From<u64> for $name {
+            fn from(value: u64) -> Self {
+                Self(value)
+            }
+        }
+
+        impl From<$name> for u64 {
+            fn from(value: $name) -> Self {
+                value.0
+            }
+        }
+
+        impl Deref for $name {
+            type Target = u64;
+
+            fn deref(&self) -> &u64 {
+                &self.0
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                fmt::Display::fmt(&self.0, f)
+            }
+        }
+
+        impl FromStr for $name {
+            type Err = ParseClockError;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                parse_u64(s).map(Self)
+            }
+        }
+    };
+}
+
+clock_newtype!(
+    /// A slot number, see [`crate::Slot`].
+    Slot
+);
+
+clock_newtype!(
+    /// An epoch number, see [`crate::Epoch`].
+    Epoch
+);
+
+/// A slot or an epoch, parsed from a string such as `"slot:250000000"` or
+/// `"epoch:600"`.
+///
+/// A number without prefix is a slot. [`Display`](fmt::Display) writes the
+/// prefix.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SlotOrEpoch {
+    Slot(Slot),
+    Epoch(Epoch),
+}
+
+impl fmt::Display for SlotOrEpoch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Slot(slot) => write!(f, "slot:{slot}"),
+            Self::Epoch(epoch) => write!(f, "epoch:{epoch}"),
+        }
+    }
+}
+
+impl FromStr for SlotOrEpoch {
+    type Err = ParseClockError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once(':') {
+            None => s.parse().map(Self::Slot),
+            Some(("slot", slot)) => slot.parse().map(Self::Slot),
+            Some(("epoch", epoch)) => epoch.parse().map(Self::Epoch),
+            Some(_) => Err(ParseClockError::UnknownPrefix),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use {super::*, core::mem, std::string::ToString};
+
+    static_assertions::assert_eq_size!(Slot, u64);
+    static_assertions::assert_eq_align!(Slot, u64);
+    static_assertions::assert_eq_size!(Epoch, u64);
+    static_assertions::assert_eq_align!(Epoch, u64);
+
+    #[test]
+    fn test_parse() {
+        for (s, expected) in [
+            ("0", Ok(0)),
+            ("250000000", Ok(250_000_000)),
+            ("250_000_000", Ok(250_000_000)),
+            ("1__0_", Ok(10)),
+            ("18446744073709551615", Ok(u64::MAX)),
+            ("18446744073709551616", Err(ParseClockError::Overflow)),
+            ("", Err(ParseClockError::Empty)),
+            ("_1", Err(ParseClockError::InvalidDigit)),
+            ("-1", Err(ParseClockError::InvalidDigit)),
+            ("+1", Err(ParseClockError::InvalidDigit)),
+            (" 1", Err(ParseClockError::InvalidDigit)),
+            ("1.5", Err(ParseClockError::InvalidDigit)),
+            ("0x10", Err(ParseClockError::InvalidDigit)),
+        ] {
+            assert_eq!(s.parse::<Slot>(), expected.clone().map(Slot), "{s}");
+            assert_eq!(s.parse::<Epoch>(), expected.map(Epoch), "{s}");
+        }
+    }
+
+    #[test]
+    fn test_slot_or_epoch() {
+        for (s, expected) in [
+            ("250_000_000", Ok(SlotOrEpoch::Slot(Slot(250_000_000)))),
+            ("slot:42", Ok(SlotOrEpoch::Slot(Slot(42)))),
+            ("epoch:600", Ok(SlotOrEpoch::Epoch(Epoch(600)))),
+            ("epoch:", Err(ParseClockError::Empty)),
+            ("epoch:x", Err(ParseClockError::InvalidDigit)),
+            ("Epoch:600", Err(ParseClockError::UnknownPrefix)),
+            ("block:1", Err(ParseClockError::UnknownPrefix)),
+            ("slot:epoch:1", Err(ParseClockError::InvalidDigit)),
+        ] {
+            assert_eq!(s.parse::<SlotOrEpoch>(), expected, "{s}");
+        }
+    }
+
+    #[test]
+    fn test_display_round_trip() {
+        assert_eq!(Slot(250_000_000).to_string(), "250000000");
+        for value in [
+            SlotOrEpoch::Slot(Slot(0)),
+            SlotOrEpoch::Slot(Slot(u64::MAX)),
+            SlotOrEpoch::Epoch(Epoch(600)),
+        ] {
+            assert_eq!(value.to_string().parse(), Ok(value));
+        }
+        assert_eq!(SlotOrEpoch::Epoch(Epoch(600)).to_string(), "epoch:600");
+    }
+
+    #[test]
+    fn test_checked_arithmetic() {
+        let slot = Slot::new(10);
+        assert_eq!(slot.checked_add(5), Some(Slot(15)));
+        assert_eq!(slot.checked_sub(10), Some(Slot(0)));
+        assert_eq!(slot.checked_sub(11), None);
+        assert_eq!(Slot(u64::MAX).checked_add(1), None);
+        assert_eq!(slot.checked_since(Slot(4)), Some(6));
+        assert_eq!(Slot(4).checked_since(slot), None);
+        assert_eq!(Epoch(u64::MAX).checked_add(0), Some(Epoch(u64::MAX)));
+        assert_eq!(Epoch(0).checked_sub(1), None);
+    }
+
+    #[test]
+    fn test_conversions() {
+        const SLOT: Slot = Slot::new(7);
+        assert_eq!(SLOT.get(), 7);
+        assert_eq!(u64::from(SLOT), 7);
+        assert_eq!(Slot::from(7), SLOT);
+        // the existing APIs take `u64`s
+        let slot: crate::Slot = *SLOT;
+        assert_eq!(slot, 7);
+        assert_eq!(SLOT.pow(2), 49);
+        assert_eq!(
+            mem::size_of::<Option<Epoch>>(),
+            mem::size_of::<Option<u64>>()
+        );
+    }
+}
@@ -7,6 +7,10 @@ use borsh::{BorshDeserialize, BorshSerialize};
 use solana_instruction::Instruction;
 pub use solana_sdk_ids::compute_budget::{check_id, id, ID};
 
+/// Maximum heap region size in bytes a transaction can request with
+/// [`ComputeBudgetInstruction::RequestHeapFrame`].
+pub const MAX_HEAP_FRAME_BYTES: u32 = 256 * 1024;
+
 /// Compute Budget Instructions
 #[cfg_attr(
     feature = "frozen-abi",
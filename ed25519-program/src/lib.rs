@@ -6,7 +6,9 @@
 use {
     bytemuck::bytes_of,
     bytemuck_derive::{Pod, Zeroable},
+    core::fmt,
     solana_instruction::Instruction,
+    solana_precompile_layout::{Entry, LayoutError, SignatureOffsetsLayout},
 };
 
 pub const PUBKEY_SERIALIZED_SIZE: usize = 32;
@@ -28,12 +30,45 @@ pub struct Ed25519SignatureOffsets {
     pub message_instruction_index: u16, // index of instruction data to get message data
 }
 
+/// The [`SignatureOffsetsLayout`] of ed25519 instructions, to build and parse
+/// them with the functions of [`solana_precompile_layout`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Ed25519Layout;
+
+impl SignatureOffsetsLayout for Ed25519Layout {
+    type Offsets = Ed25519SignatureOffsets;
+
+    const PUBKEY_SERIALIZED_SIZE: usize = PUBKEY_SERIALIZED_SIZE;
+    const SIGNATURE_SERIALIZED_SIZE: usize = SIGNATURE_SERIALIZED_SIZE;
+    const SIGNATURE_OFFSETS_SERIALIZED_SIZE: usize = SIGNATURE_OFFSETS_SERIALIZED_SIZE;
+    const SIGNATURE_OFFSETS_START: usize = SIGNATURE_OFFSETS_START;
+    const MAX_SIGNATURES: usize = MAX_SIGNATURES;
+
+    fn inline_offsets(
+        public_key_offset: u16,
+        signature_offset: u16,
+        message_data_offset: u16,
+        message_data_size: u16,
+    ) -> Ed25519SignatureOffsets {
+        Ed25519SignatureOffsets {
+            signature_offset,
+            signature_instruction_index: u16::MAX,
+            public_key_offset,
+            public_key_instruction_index: u16::MAX,
+            message_data_offset,
+            message_data_size,
+            message_instruction_index: u16::MAX,
+        }
+    }
+}
+
 /// Encode just the signature offsets in a single ed25519 instruction.
 ///
 /// This is a convenience function for rare cases where we wish to verify multiple messages in
 /// the same instruction. The verification data can be stored in a separate instruction specified
 /// by the `*_instruction_index` fields of `offsets`, or in this instruction by extending the data
-/// buffer.
+/// buffer. [`Ed25519InstructionBuilder`] does the latter, computing the offsets of the data it
+/// appends.
 ///
 /// Note: If the signer for these messages are the same, it is cheaper to concatenate the messages
 /// and have the signer sign the single buffer and use [`new_ed25519_instruction_with_signature`].
@@ -57,53 +92,487 @@ pub fn offsets_to_ed25519_instruction(offsets: &[Ed25519SignatureOffsets]) -> In
     }
 }
 
+/// Maximum number of signatures in a single ed25519 instruction, as the
+/// signature count is encoded in one byte.
+pub const MAX_SIGNATURES: usize = u8::MAX as usize;
+
+/// Reasons [`Ed25519InstructionBuilder::build`] and
+/// [`new_ed25519_instruction_with_signatures`] can fail.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Ed25519InstructionError {
+    /// More than [`MAX_SIGNATURES`] signatures were added.
+    TooManySignatures,
+    /// An offset or message length of an inline signature does not fit in
+    /// the `u16` fields of [`Ed25519SignatureOffsets`].
+    DataTooLarge,
+}
+
+impl core::error::Error for Ed25519InstructionError {}
+
+impl fmt::Display for Ed25519InstructionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::TooManySignatures => f.write_str("too many signatures"),
+            Self::DataTooLarge => f.write_str("instruction data too large"),
+        }
+    }
+}
+
+impl From<LayoutError> for Ed25519InstructionError {
+    fn from(error: LayoutError) -> Self {
+        match error {
+            LayoutError::TooManySignatures => Self::TooManySignatures,
+            LayoutError::DataTooLarge => Self::DataTooLarge,
+        }
+    }
+}
+
+/// Builder of an ed25519 instruction mixing signatures whose data is in other
+/// instructions with signatures whose data is in the instruction itself.
+///
+/// The data of the inline signatures follows the offsets of all the
+/// signatures, in the order they were added, as in
+/// [`new_ed25519_instruction_with_signatures`]. Their offsets are only
+/// computed by [`build`](Self::build), once the number of signatures, and so
+/// the size of the offsets, is known.
+#[derive(Debug, Clone, Default)]
+pub struct Ed25519InstructionBuilder<'a> {
+    entries: Vec<Entry<'a, Ed25519SignatureOffsets>>,
+}
+
+impl<'a> Ed25519InstructionBuilder<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a signature whose data is located by `offsets`, usually in other
+    /// instructions. The offsets are encoded as is.
+    #[must_use]
+    pub fn add_external(mut self, offsets: Ed25519SignatureOffsets) -> Self {
+        self.entries.push(Entry::External(offsets));
+        self
+    }
+
+    /// Add a signature whose data is appended to the instruction data.
+    #[must_use]
+    pub fn add_inline(
+        mut self,
+        message: &'a [u8],
+        signature: &'a [u8; SIGNATURE_SERIALIZED_SIZE],
+        pubkey: &'a [u8; PUBKEY_SERIALIZED_SIZE],
+    ) -> Self {
+        self.entries.push(Entry::Inline {
+            message,
+            signature,
+            pubkey,
+        });
+        self
+    }
+
+    /// Build the instruction, with the offsets of the inline signatures
+    /// pointing into its own data.
+    pub fn build(self) -> Result<Instruction, Ed25519InstructionError> {
+        Ok(Instruction {
+            program_id: solana_sdk_ids::ed25519_program::id(),
+            accounts: vec![],
+            data: solana_precompile_layout::build_many::<Ed25519Layout>(&self.entries)?,
+        })
+    }
+}
+
+/// Returns the length of the instruction data built by
+/// [`new_ed25519_instruction_with_signatures`] for `num_signatures`
+/// signatures over messages of `message_lens` bytes.
+///
+/// Returns `None` if `message_lens` does not hold exactly `num_signatures`
+/// lengths, if `num_signatures` exceeds [`MAX_SIGNATURES`], or if an offset
+/// or message length does not fit in the `u16` fields of
+/// [`Ed25519SignatureOffsets`].
+pub fn instruction_size(num_signatures: usize, message_lens: &[usize]) -> Option<usize> {
+    solana_precompile_layout::instruction_size::<Ed25519Layout>(num_signatures, message_lens)
+}
+
+/// Returns the largest message length such that an instruction verifying
+/// `num_signatures` messages of that length fits in `budget` bytes of
+/// instruction data.
+///
+/// Returns `None` if `num_signatures` is zero or exceeds [`MAX_SIGNATURES`],
+/// or if not even empty messages fit in `budget`.
+pub fn max_message_len_for(num_signatures: usize, budget: usize) -> Option<usize> {
+    solana_precompile_layout::max_message_len_for::<Ed25519Layout>(num_signatures, budget)
+}
+
 pub fn new_ed25519_instruction_with_signature(
     message: &[u8],
     signature: &[u8; SIGNATURE_SERIALIZED_SIZE],
     pubkey: &[u8; PUBKEY_SERIALIZED_SIZE],
 ) -> Instruction {
-    let mut instruction_data = Vec::with_capacity(
-        DATA_START
-            .saturating_add(SIGNATURE_SERIALIZED_SIZE)
-            .saturating_add(PUBKEY_SERIALIZED_SIZE)
-            .saturating_add(message.len()),
-    );
+    Instruction {
+        program_id: solana_sdk_ids::ed25519_program::id(),
+        accounts: vec![],
+        data: solana_precompile_layout::encode_many::<Ed25519Layout>(&[Entry::Inline {
+            message,
+            signature,
+            pubkey,
+        }]),
+    }
+}
 
-    let num_signatures: u8 = 1;
-    let public_key_offset = DATA_START;
-    let signature_offset = public_key_offset.saturating_add(PUBKEY_SERIALIZED_SIZE);
-    let message_data_offset = signature_offset.saturating_add(SIGNATURE_SERIALIZED_SIZE);
+/// Build an ed25519 instruction verifying several `(message, signature,
+/// pubkey)` triples, all stored in the instruction data.
+///
+/// The data starts with the signature offsets, followed by the public key,
+/// signature and message of each triple in order. Its length is given by
+/// [`instruction_size`].
+///
+/// Fails with [`Ed25519InstructionError::TooManySignatures`] if there are
+/// more than [`MAX_SIGNATURES`] triples, or with
+/// [`Ed25519InstructionError::DataTooLarge`] if an offset or message length
+/// does not fit in the `u16` fields of [`Ed25519SignatureOffsets`].
+pub fn new_ed25519_instruction_with_signatures(
+    signatures: &[(
+        &[u8],
+        &[u8; SIGNATURE_SERIALIZED_SIZE],
+        &[u8; PUBKEY_SERIALIZED_SIZE],
+    )],
+) -> Result<Instruction, Ed25519InstructionError> {
+    let entries: Vec<_> = signatures
+        .iter()
+        .map(|&(message, signature, pubkey)| Entry::Inline {
+            message,
+            signature,
+            pubkey,
+        })
+        .collect();
 
-    // add padding byte so that offset structure is aligned
-    instruction_data.extend_from_slice(bytes_of(&[num_signatures, 0]));
+    Ok(Instruction {
+        program_id: solana_sdk_ids::ed25519_program::id(),
+        accounts: vec![],
+        data: solana_precompile_layout::build_many::<Ed25519Layout>(&entries)?,
+    })
+}
 
-    let offsets = Ed25519SignatureOffsets {
-        signature_offset: signature_offset as u16,
-        signature_instruction_index: u16::MAX,
-        public_key_offset: public_key_offset as u16,
-        public_key_instruction_index: u16::MAX,
-        message_data_offset: message_data_offset as u16,
-        message_data_size: message.len() as u16,
-        message_instruction_index: u16::MAX,
-    };
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    instruction_data.extend_from_slice(bytes_of(&offsets));
+    const MESSAGE_LENS: [usize; 5] = [0, 1, 32, 255, 1000];
 
-    debug_assert_eq!(instruction_data.len(), public_key_offset);
+    #[test]
+    fn test_instruction_size_matches_builders() {
+        let signature = [1; SIGNATURE_SERIALIZED_SIZE];
+        let pubkey = [2; PUBKEY_SERIALIZED_SIZE];
+        let messages: Vec<Vec<u8>> = MESSAGE_LENS.iter().map(|&len| vec![3; len]).collect();
 
-    instruction_data.extend_from_slice(pubkey);
+        for message in &messages {
+            let instruction = new_ed25519_instruction_with_signature(message, &signature, &pubkey);
+            assert_eq!(
+                instruction_size(1, &[message.len()]),
+                Some(instruction.data.len())
+            );
+        }
 
-    debug_assert_eq!(instruction_data.len(), signature_offset);
+        for num_signatures in 1..=8 {
+            let signatures: Vec<_> = messages
+                .iter()
+                .cycle()
+                .skip(num_signatures)
+                .take(num_signatures)
+                .map(|message| (message.as_slice(), &signature, &pubkey))
+                .collect();
+            let message_lens: Vec<_> = signatures
+                .iter()
+                .map(|(message, _, _)| message.len())
+                .collect();
+            let instruction = new_ed25519_instruction_with_signatures(&signatures).unwrap();
+            assert_eq!(instruction.data[0] as usize, num_signatures);
+            assert_eq!(
+                instruction_size(num_signatures, &message_lens),
+                Some(instruction.data.len())
+            );
+        }
+    }
 
-    instruction_data.extend_from_slice(signature);
+    #[test]
+    fn test_instruction_size_invalid() {
+        assert_eq!(instruction_size(0, &[]), Some(SIGNATURE_OFFSETS_START));
+        assert_eq!(instruction_size(2, &[10]), None);
+        assert_eq!(
+            instruction_size(MAX_SIGNATURES + 1, &[0; MAX_SIGNATURES + 1]),
+            None
+        );
+        assert!(instruction_size(MAX_SIGNATURES, &[0; MAX_SIGNATURES]).is_some());
+        assert_eq!(instruction_size(1, &[usize::from(u16::MAX) + 1]), None);
+        // the last message starts past the u16 offset range
+        assert_eq!(instruction_size(2, &[usize::from(u16::MAX), 0]), None);
+        assert_eq!(instruction_size(1, &[usize::MAX]), None);
+    }
 
-    debug_assert_eq!(instruction_data.len(), message_data_offset);
+    #[test]
+    fn test_max_message_len_for() {
+        const PACKET_BUDGET: usize = 1232;
+        for num_signatures in 1..=8 {
+            let max = max_message_len_for(num_signatures, PACKET_BUDGET).unwrap();
+            let size = |len| instruction_size(num_signatures, &vec![len; num_signatures]);
+            assert!(size(max).unwrap() <= PACKET_BUDGET);
+            assert!(size(max + 1).unwrap() > PACKET_BUDGET);
+        }
+        assert_eq!(max_message_len_for(1, DATA_START + 96), Some(0));
+        assert_eq!(max_message_len_for(1, DATA_START + 95), None);
+        assert_eq!(max_message_len_for(0, PACKET_BUDGET), None);
+        assert_eq!(max_message_len_for(MAX_SIGNATURES + 1, usize::MAX), None);
+        assert_eq!(
+            max_message_len_for(1, usize::MAX),
+            Some(usize::from(u16::MAX))
+        );
+    }
 
-    instruction_data.extend_from_slice(message);
+    fn parse_offsets(data: &[u8]) -> Vec<Ed25519SignatureOffsets> {
+        (0..usize::from(data[0]))
+            .map(|i| {
+                let start = SIGNATURE_OFFSETS_START + i * SIGNATURE_OFFSETS_SERIALIZED_SIZE;
+                bytemuck::pod_read_unaligned(
+                    &data[start..start + SIGNATURE_OFFSETS_SERIALIZED_SIZE],
+                )
+            })
+            .collect()
+    }
 
-    Instruction {
-        program_id: solana_sdk_ids::ed25519_program::id(),
-        accounts: vec![],
-        data: instruction_data,
+    /// The message, signature and public key located by inline `offsets`.
+    fn parse_inline<'a>(
+        data: &'a [u8],
+        offsets: &Ed25519SignatureOffsets,
+    ) -> (&'a [u8], &'a [u8], &'a [u8]) {
+        assert_eq!(offsets.signature_instruction_index, u16::MAX);
+        assert_eq!(offsets.public_key_instruction_index, u16::MAX);
+        assert_eq!(offsets.message_instruction_index, u16::MAX);
+        let slice = |offset: u16, len: usize| &data[usize::from(offset)..][..len];
+        (
+            slice(
+                offsets.message_data_offset,
+                usize::from(offsets.message_data_size),
+            ),
+            slice(offsets.signature_offset, SIGNATURE_SERIALIZED_SIZE),
+            slice(offsets.public_key_offset, PUBKEY_SERIALIZED_SIZE),
+        )
+    }
+
+    fn external_offsets() -> Ed25519SignatureOffsets {
+        Ed25519SignatureOffsets {
+            signature_offset: 100,
+            signature_instruction_index: 1,
+            public_key_offset: 200,
+            public_key_instruction_index: 2,
+            message_data_offset: 300,
+            message_data_size: 40,
+            message_instruction_index: 3,
+        }
+    }
+
+    #[test]
+    fn test_builder_mixed() {
+        let (signature_a, pubkey_a) = ([1; SIGNATURE_SERIALIZED_SIZE], [2; PUBKEY_SERIALIZED_SIZE]);
+        let (signature_b, pubkey_b) = ([3; SIGNATURE_SERIALIZED_SIZE], [4; PUBKEY_SERIALIZED_SIZE]);
+        let instruction = Ed25519InstructionBuilder::new()
+            .add_inline(b"first", &signature_a, &pubkey_a)
+            .add_external(external_offsets())
+            .add_inline(b"second message", &signature_b, &pubkey_b)
+            .build()
+            .unwrap();
+        assert_eq!(
+            instruction.program_id,
+            solana_sdk_ids::ed25519_program::id()
+        );
+
+        let data = &instruction.data;
+        assert_eq!(data[..2], [3, 0]);
+        let offsets = parse_offsets(data);
+        assert_eq!(offsets.len(), 3);
+        assert_eq!(offsets[1], external_offsets());
+        assert_eq!(
+            parse_inline(data, &offsets[0]),
+            (&b"first"[..], &signature_a[..], &pubkey_a[..])
+        );
+        assert_eq!(
+            parse_inline(data, &offsets[2]),
+            (&b"second message"[..], &signature_b[..], &pubkey_b[..])
+        );
+        // the inline data directly follows the three offsets
+        assert_eq!(
+            usize::from(offsets[0].public_key_offset),
+            SIGNATURE_OFFSETS_START + 3 * SIGNATURE_OFFSETS_SERIALIZED_SIZE
+        );
+        assert_eq!(
+            data.len(),
+            usize::from(offsets[2].message_data_offset) + b"second message".len()
+        );
+    }
+
+    #[test]
+    fn test_builder_inline_offsets() {
+        let signature = [5; SIGNATURE_SERIALIZED_SIZE];
+        let pubkey = [6; PUBKEY_SERIALIZED_SIZE];
+        let inline_first = Ed25519InstructionBuilder::new()
+            .add_inline(b"message", &signature, &pubkey)
+            .add_external(external_offsets())
+            .build()
+            .unwrap();
+        let inline_last = Ed25519InstructionBuilder::new()
+            .add_external(external_offsets())
+            .add_inline(b"message", &signature, &pubkey)
+            .build()
+            .unwrap();
+        // only the number of signatures moves the inline data
+        let first = parse_offsets(&inline_first.data);
+        let last = parse_offsets(&inline_last.data);
+        assert_eq!(first[0], last[1]);
+        assert_eq!(first[1], external_offsets());
+        assert_eq!(last[0], external_offsets());
+        assert_eq!(
+            inline_first.data[DATA_START * 2 - 2..],
+            inline_last.data[DATA_START * 2 - 2..]
+        );
+        assert_eq!(
+            parse_inline(&inline_last.data, &last[1]),
+            (&b"message"[..], &signature[..], &pubkey[..])
+        );
+
+        // without external signatures, the instruction is the one of
+        // `new_ed25519_instruction_with_signatures`
+        let all_inline = Ed25519InstructionBuilder::new()
+            .add_inline(b"message", &signature, &pubkey)
+            .add_inline(b"", &signature, &pubkey)
+            .build()
+            .unwrap();
+        assert_eq!(
+            all_inline,
+            new_ed25519_instruction_with_signatures(&[
+                (&b"message"[..], &signature, &pubkey),
+                (&b""[..], &signature, &pubkey),
+            ])
+            .unwrap()
+        );
+        // and without inline signatures, the one of
+        // `offsets_to_ed25519_instruction`
+        let all_external = Ed25519InstructionBuilder::new()
+            .add_external(external_offsets())
+            .build()
+            .unwrap();
+        assert_eq!(
+            all_external,
+            offsets_to_ed25519_instruction(&[external_offsets()])
+        );
+    }
+
+    #[test]
+    fn test_builder_limits() {
+        let signature = [0; SIGNATURE_SERIALIZED_SIZE];
+        let pubkey = [0; PUBKEY_SERIALIZED_SIZE];
+
+        let mut builder = Ed25519InstructionBuilder::new();
+        for _ in 0..MAX_SIGNATURES {
+            builder = builder.add_external(external_offsets());
+        }
+        assert!(builder.clone().build().is_ok());
+        assert_eq!(
+            builder.add_external(external_offsets()).build(),
+            Err(Ed25519InstructionError::TooManySignatures)
+        );
+
+        let message = vec![0; usize::from(u16::MAX)];
+        assert_eq!(
+            Ed25519InstructionBuilder::new()
+                .add_inline(&message[..1000], &signature, &pubkey)
+                .add_inline(&message, &signature, &pubkey)
+                .build(),
+            new_ed25519_instruction_with_signatures(&[
+                (&message[..1000], &signature, &pubkey),
+                (&message, &signature, &pubkey),
+            ])
+        );
+        // the second message starts past the u16 offset range
+        assert_eq!(
+            Ed25519InstructionBuilder::new()
+                .add_inline(&message, &signature, &pubkey)
+                .add_inline(b"", &signature, &pubkey)
+                .build(),
+            Err(Ed25519InstructionError::DataTooLarge)
+        );
+        let too_long = vec![0; usize::from(u16::MAX) + 1];
+        assert_eq!(
+            Ed25519InstructionBuilder::new()
+                .add_inline(&too_long, &signature, &pubkey)
+                .build(),
+            Err(Ed25519InstructionError::DataTooLarge)
+        );
+        assert_eq!(
+            new_ed25519_instruction_with_signatures(&[(&too_long, &signature, &pubkey)]),
+            Err(Ed25519InstructionError::DataTooLarge)
+        );
+
+        // the signature count is a single byte, so it is never truncated
+        for num_signatures in [MAX_SIGNATURES + 1, usize::from(u16::MAX) + 2] {
+            let signatures = vec![(&b""[..], &signature, &pubkey); num_signatures];
+            assert_eq!(
+                new_ed25519_instruction_with_signatures(&signatures),
+                Err(Ed25519InstructionError::TooManySignatures)
+            );
+        }
+    }
+
+    /// Checks `data` byte for byte, against `head` in hex for the count and
+    /// offsets, and against the concatenation of `tail` for the rest.
+    fn assert_golden(data: &[u8], head: &str, tail: &[&[u8]]) {
+        let head: Vec<u8> = (0..head.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&head[i..i + 2], 16).unwrap())
+            .collect();
+        assert_eq!(data[..head.len()], head[..]);
+        assert_eq!(data[head.len()..], tail.concat()[..]);
+    }
+
+    #[test]
+    fn test_golden_instruction_data() {
+        let signature = [1; SIGNATURE_SERIALIZED_SIZE];
+        let pubkey = [2; PUBKEY_SERIALIZED_SIZE];
+        let signature_2 = [3; SIGNATURE_SERIALIZED_SIZE];
+        let pubkey_2 = [4; PUBKEY_SERIALIZED_SIZE];
+
+        let instruction = new_ed25519_instruction_with_signature(b"hello", &signature, &pubkey);
+        assert_golden(
+            &instruction.data,
+            "01003000ffff1000ffff70000500ffff",
+            &[&pubkey, &signature, b"hello"],
+        );
+
+        let instruction = new_ed25519_instruction_with_signatures(&[
+            (&b""[..], &signature, &pubkey),
+            (&b"ab"[..], &signature_2, &pubkey_2),
+        ])
+        .unwrap();
+        assert_golden(
+            &instruction.data,
+            "02003e00ffff1e00ffff7e000000ffff9e00ffff7e00ffffde000200ffff",
+            &[&pubkey, &signature, &pubkey_2, &signature_2, b"ab"],
+        );
+
+        let instruction = Ed25519InstructionBuilder::new()
+            .add_inline(b"a", &signature, &pubkey)
+            .add_external(external_offsets())
+            .add_inline(b"", &signature_2, &pubkey_2)
+            .build()
+            .unwrap();
+        assert_golden(
+            &instruction.data,
+            "03004c00ffff2c00ffff8c000100ffff64000100c80002002c0128000300ad00ffff8d00ffffed000000ffff",
+            &[&pubkey, &signature, b"a", &pubkey_2, &signature_2],
+        );
+
+        let instruction = offsets_to_ed25519_instruction(&[external_offsets(), external_offsets()]);
+        assert_golden(
+            &instruction.data,
+            "020064000100c80002002c012800030064000100c80002002c0128000300",
+            &[],
+        );
     }
 }
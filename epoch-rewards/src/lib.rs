@@ -17,8 +17,23 @@ pub mod sysvar;
 extern crate std;
 #[cfg(feature = "serde")]
 use serde_derive::{Deserialize, Serialize};
-use {solana_hash::Hash, solana_sdk_macro::CloneZeroed};
-
+use {
+    solana_hash::Hash,
+    solana_program_error::{ProgramError, ProgramResult},
+    solana_sdk_macro::CloneZeroed,
+};
+
+/// Rewards distribution state of the current epoch.
+///
+/// The runtime resets the sysvar in the first block of every epoch, with
+/// `active` set while it calculates the rewards, then distributes them over
+/// the `num_partitions` blocks from `distribution_starting_block_height`.
+/// `active` is cleared once the last partition is distributed, so that it
+/// is only set during the first blocks of an epoch, and the sysvar then
+/// keeps the totals of the epoch until the next one.
+///
+/// Stake accounts cannot be modified while the distribution is active, see
+/// [`assert_distribution_inactive`](Self::assert_distribution_inactive).
 #[repr(C, align(16))]
 #[cfg_attr(feature = "frozen-abi", derive(solana_frozen_abi_macro::AbiExample))]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
@@ -63,6 +78,30 @@ impl EpochRewards {
         assert!(new_distributed_rewards <= self.total_rewards);
         self.distributed_rewards = new_distributed_rewards;
     }
+
+    /// Returns an error if the rewards distribution is active.
+    ///
+    /// The runtime makes the stake accounts read-only during the
+    /// distribution, so that instructions writing them fail. Stake programs
+    /// call this to fail such instructions early.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ProgramError::Immutable`] if `active` is set.
+    pub fn assert_distribution_inactive(&self) -> ProgramResult {
+        if self.active {
+            Err(ProgramError::Immutable)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Returns the distributed and the total rewards of the epoch, in
+    /// lamports, while the distribution is active, and `None` otherwise.
+    pub fn distribution_progress(&self) -> Option<(u64, u64)> {
+        self.active
+            .then_some((self.distributed_rewards, self.total_rewards))
+    }
 }
 
 #[cfg(test)]
@@ -102,6 +141,22 @@ mod tests {
         assert_eq!(epoch_rewards.distributed_rewards, 100);
     }
 
+    #[test]
+    fn test_epoch_rewards_distribution_state() {
+        let mut epoch_rewards = EpochRewards::new(100, 40, 64);
+        assert_eq!(epoch_rewards.assert_distribution_inactive(), Ok(()));
+        assert_eq!(epoch_rewards.distribution_progress(), None);
+
+        epoch_rewards.active = true;
+        assert_eq!(
+            epoch_rewards.assert_distribution_inactive(),
+            Err(ProgramError::Immutable)
+        );
+        assert_eq!(epoch_rewards.distribution_progress(), Some((40, 100)));
+        epoch_rewards.distribute(60);
+        assert_eq!(epoch_rewards.distribution_progress(), Some((100, 100)));
+    }
+
     #[test]
     #[should_panic(expected = "new_distributed_rewards <= self.total_rewards")]
     fn test_epoch_rewards_distribute_panic() {
@@ -0,0 +1,25 @@
+//! Compile-time checks that the workspace error types implement
+//! [`core::error::Error`] without relying on `std`.
+//!
+//! This crate has no runtime behavior: if it builds, every listed type can be
+//! used as `Box<dyn core::error::Error>` in `no_std` + `alloc` contexts.
+#![no_std]
+
+const fn assert_err<E: core::error::Error>() {}
+
+const _: () = {
+    assert_err::<solana_program_error::ProgramError>();
+    assert_err::<solana_instruction_error::InstructionError>();
+    assert_err::<solana_instruction_error::LamportsError>();
+    assert_err::<solana_address::error::AddressError>();
+    assert_err::<solana_address::error::ParseAddressError>();
+    assert_err::<solana_hash::ParseHashError>();
+    assert_err::<solana_packet::codec::PacketCodecError>();
+    assert_err::<solana_packet::quic::PacketSizeError>();
+    assert_err::<solana_packet::MetaValidationError>();
+    assert_err::<solana_packet::SignRegionError>();
+    assert_err::<solana_secp256k1_recover::Secp256k1RecoverError>();
+    // errors of the base2 and base3 signer encodings
+    assert_err::<solana_signer_store::EncodeError>();
+    assert_err::<solana_signer_store::DecodeError>();
+};
@@ -18,12 +18,13 @@ pub mod solana_rpc_client {
     pub mod rpc_client {
         use {
             super::super::{
-                solana_rpc_client_api::client_error::Result as ClientResult,
+                solana_rpc_client_api::client_error::{ClientError, Result as ClientResult},
                 solana_sdk::{
                     account::Account, hash::Hash, pubkey::Pubkey, signature::Signature,
                     transaction::Transaction,
                 },
             },
+            solana_sdk_ids::feature,
             std::{cell::RefCell, collections::HashMap, rc::Rc},
         };
 
@@ -37,6 +38,14 @@ pub mod solana_rpc_client {
                 RpcClient::default()
             }
 
+            /// A client returning `accounts` from [`RpcClient::get_account`]
+            /// and the other account queries.
+            pub fn new_mock_with_accounts(accounts: Vec<(Pubkey, Account)>) -> Self {
+                RpcClient {
+                    get_account_responses: Rc::new(RefCell::new(accounts.into_iter().collect())),
+                }
+            }
+
             pub fn get_latest_blockhash(&self) -> ClientResult<Hash> {
                 Ok(Hash::default())
             }
@@ -55,13 +64,52 @@ pub mod solana_rpc_client {
                 Ok(0)
             }
 
+            /// Returns the account set for `pubkey`, or an error if there is
+            /// none, as the RPC client does for missing accounts.
             pub fn get_account(&self, pubkey: &Pubkey) -> ClientResult<Account> {
-                Ok(self
-                    .get_account_responses
+                self.get_account_responses
                     .borrow()
                     .get(pubkey)
                     .cloned()
-                    .unwrap())
+                    .ok_or(ClientError)
+            }
+
+            pub fn get_multiple_accounts(
+                &self,
+                pubkeys: &[Pubkey],
+            ) -> ClientResult<Vec<Option<Account>>> {
+                let accounts = self.get_account_responses.borrow();
+                Ok(pubkeys
+                    .iter()
+                    .map(|pubkey| accounts.get(pubkey).cloned())
+                    .collect())
+            }
+
+            /// Returns the activation slot of the feature `feature_id`, or
+            /// `None` if the feature account is missing or the feature is
+            /// pending activation.
+            ///
+            /// The feature account holds a bincode encoded `Feature`: an
+            /// `Option<u64>` activation slot.
+            pub fn get_feature_activation_slot(
+                &self,
+                feature_id: &Pubkey,
+            ) -> ClientResult<Option<u64>> {
+                let accounts = self.get_account_responses.borrow();
+                let Some(account) = accounts.get(feature_id) else {
+                    return Ok(None);
+                };
+                if account.owner != feature::id() {
+                    return Err(ClientError);
+                }
+                match account.data.split_first() {
+                    Some((0, _)) => Ok(None),
+                    Some((1, slot)) => slot
+                        .get(..8)
+                        .map(|slot| Some(u64::from_le_bytes(slot.try_into().unwrap())))
+                        .ok_or(ClientError),
+                    _ => Err(ClientError),
+                }
             }
 
             pub fn set_get_account_response(&self, pubkey: Pubkey, account: Account) {
@@ -262,3 +310,68 @@ pub mod solana_sdk {
         pub use crate::solana_transaction::Transaction;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::{solana_rpc_client::rpc_client::RpcClient, solana_sdk::account::Account},
+        solana_pubkey::Pubkey,
+        solana_sdk_ids::{feature, system_program},
+    };
+
+    fn account(owner: Pubkey, data: Vec<u8>) -> Account {
+        Account {
+            lamports: 1,
+            data,
+            owner,
+            executable: false,
+        }
+    }
+
+    #[test]
+    fn test_get_account() {
+        let present = Pubkey::new_unique();
+        let missing = Pubkey::new_unique();
+        let client = RpcClient::new_mock_with_accounts(vec![(
+            present,
+            account(system_program::id(), vec![7]),
+        )]);
+
+        assert_eq!(client.get_account(&present).unwrap().data, [7]);
+        assert!(client.get_account(&missing).is_err());
+        let accounts = client.get_multiple_accounts(&[missing, present]).unwrap();
+        assert!(accounts[0].is_none());
+        assert_eq!(accounts[1].as_ref().unwrap().data, [7]);
+
+        client.set_get_account_response(missing, account(system_program::id(), vec![8]));
+        assert_eq!(client.get_account(&missing).unwrap().data, [8]);
+        assert!(RpcClient::new(String::new()).get_account(&present).is_err());
+    }
+
+    #[test]
+    fn test_get_feature_activation_slot() {
+        let active = Pubkey::new_unique();
+        let pending = Pubkey::new_unique();
+        let not_feature = Pubkey::new_unique();
+        let truncated = Pubkey::new_unique();
+        let client = RpcClient::new_mock_with_accounts(vec![
+            (
+                active,
+                account(feature::id(), [&[1][..], &42u64.to_le_bytes()].concat()),
+            ),
+            (pending, account(feature::id(), vec![0; 9])),
+            (not_feature, account(system_program::id(), vec![0; 9])),
+            (truncated, account(feature::id(), vec![1, 42])),
+        ]);
+
+        assert_eq!(
+            client.get_feature_activation_slot(&active).unwrap(),
+            Some(42)
+        );
+        assert_eq!(client.get_feature_activation_slot(&pending).unwrap(), None);
+        let missing = Pubkey::new_unique();
+        assert_eq!(client.get_feature_activation_slot(&missing).unwrap(), None);
+        assert!(client.get_feature_activation_slot(&not_feature).is_err());
+        assert!(client.get_feature_activation_slot(&truncated).is_err());
+    }
+}
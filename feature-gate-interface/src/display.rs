@@ -0,0 +1,304 @@
+//! Human readable activation status of features, for command line tools.
+
+use {
+    crate::state::Feature,
+    solana_clock::{Clock, DEFAULT_MS_PER_SLOT},
+    solana_epoch_schedule::EpochSchedule,
+    solana_pubkey::Pubkey,
+    std::{fmt, time::Duration},
+};
+
+/// Displays the activation status of a feature on a single line.
+///
+/// An active feature is rendered as
+/// `<address> active since slot 12345 (epoch 28, ~2024-05-01T12:00:00Z)`, and
+/// a feature not yet activated as `<address> pending activation`. The epoch is
+/// only rendered when an [`EpochSchedule`] is given, and the time only when a
+/// [`Clock`] is given.
+///
+/// The time of activation is approximated from the timestamp of the clock,
+/// assuming every slot between the activation slot and the slot of the clock
+/// lasted [`DEFAULT_MS_PER_SLOT`] milliseconds. Actual slot times vary, so the
+/// further apart the two slots, the less accurate the estimate. The duration
+/// of a slot can be changed with [`FeatureDisplay::with_slot_duration`].
+#[derive(Clone, Copy, Debug)]
+pub struct FeatureDisplay<'a> {
+    address: &'a Pubkey,
+    feature: &'a Feature,
+    clock: Option<&'a Clock>,
+    epoch_schedule: Option<&'a EpochSchedule>,
+    slot_duration: Duration,
+}
+
+impl<'a> FeatureDisplay<'a> {
+    pub fn new(
+        address: &'a Pubkey,
+        feature: &'a Feature,
+        clock: Option<&'a Clock>,
+        epoch_schedule: Option<&'a EpochSchedule>,
+    ) -> Self {
+        Self {
+            address,
+            feature,
+            clock,
+            epoch_schedule,
+            slot_duration: Duration::from_millis(DEFAULT_MS_PER_SLOT),
+        }
+    }
+
+    /// Use `slot_duration` as the duration of a slot when approximating the
+    /// time of activation.
+    pub fn with_slot_duration(mut self, slot_duration: Duration) -> Self {
+        self.slot_duration = slot_duration;
+        self
+    }
+
+    /// The epoch of the activation slot, if active and an epoch schedule was
+    /// given.
+    pub fn activation_epoch(&self) -> Option<u64> {
+        let slot = self.feature.activated_at?;
+        Some(self.epoch_schedule?.get_epoch(slot))
+    }
+
+    /// The approximate unix timestamp of the activation slot, if active and a
+    /// clock was given.
+    pub fn approximate_activation_timestamp(&self) -> Option<i64> {
+        let slot = self.feature.activated_at?;
+        let clock = self.clock?;
+        let slots = i128::from(slot) - i128::from(clock.slot);
+        let millis = slots.checked_mul(i128::try_from(self.slot_duration.as_millis()).ok()?)?;
+        let timestamp = i128::from(clock.unix_timestamp).checked_add(millis.div_euclid(1_000))?;
+        i64::try_from(timestamp).ok()
+    }
+
+    /// The activation status as a JSON object, with the fields `address`,
+    /// `active`, `activated_at`, `epoch` and `approximate_time`. Fields that
+    /// are not known are `null`.
+    #[cfg(feature = "json")]
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "address": self.address.to_string(),
+            "active": self.feature.activated_at.is_some(),
+            "activated_at": self.feature.activated_at,
+            "epoch": self.activation_epoch(),
+            "approximate_time": self
+                .approximate_activation_timestamp()
+                .and_then(Rfc3339::new)
+                .map(|time| time.to_string()),
+        })
+    }
+}
+
+impl fmt::Display for FeatureDisplay<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let Some(slot) = self.feature.activated_at else {
+            return write!(f, "{} pending activation", self.address);
+        };
+        write!(f, "{} active since slot {slot}", self.address)?;
+        let epoch = self.activation_epoch();
+        let time = self
+            .approximate_activation_timestamp()
+            .and_then(Rfc3339::new);
+        match (epoch, time) {
+            (Some(epoch), Some(time)) => write!(f, " (epoch {epoch}, ~{time})"),
+            (Some(epoch), None) => write!(f, " (epoch {epoch})"),
+            (None, Some(time)) => write!(f, " (~{time})"),
+            (None, None) => Ok(()),
+        }
+    }
+}
+
+/// A unix timestamp formatted as an RFC 3339 UTC date and time, such as
+/// `2024-05-01T12:00:00Z`.
+struct Rfc3339 {
+    year: i64,
+    month: u32,
+    day: u32,
+    seconds_of_day: i64,
+}
+
+impl Rfc3339 {
+    /// Returns `None` if the year does not fit in four digits.
+    fn new(timestamp: i64) -> Option<Self> {
+        let days = timestamp.div_euclid(86_400);
+        let seconds_of_day = timestamp.rem_euclid(86_400);
+        // Howard Hinnant's `civil_from_days`, with days counted from 0000-03-01
+        let days = days + 719_468;
+        let era = days.div_euclid(146_097);
+        let day_of_era = days.rem_euclid(146_097);
+        let year_of_era =
+            (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let shifted_month = (5 * day_of_year + 2) / 153;
+        let day = (day_of_year - (153 * shifted_month + 2) / 5 + 1) as u32;
+        let month = if shifted_month < 10 {
+            shifted_month + 3
+        } else {
+            shifted_month - 9
+        } as u32;
+        let year = year_of_era + era * 400 + i64::from(month <= 2);
+        (0..=9999).contains(&year).then_some(Self {
+            year,
+            month,
+            day,
+            seconds_of_day,
+        })
+    }
+}
+
+impl fmt::Display for Rfc3339 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+            self.year,
+            self.month,
+            self.day,
+            self.seconds_of_day / 3_600,
+            self.seconds_of_day / 60 % 60,
+            self.seconds_of_day % 60,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 2024-05-01T12:00:00Z plus 7655 slots of 400ms
+    const CLOCK: Clock = Clock {
+        slot: 20_000,
+        epoch_start_timestamp: 0,
+        epoch: 46,
+        leader_schedule_epoch: 47,
+        unix_timestamp: 1_714_567_862,
+    };
+
+    fn address() -> Pubkey {
+        Pubkey::new_from_array([1; 32])
+    }
+
+    fn active() -> Feature {
+        Feature {
+            activated_at: Some(12_345),
+        }
+    }
+
+    #[test]
+    fn test_display_active() {
+        let address = address();
+        let feature = active();
+        let epoch_schedule = EpochSchedule::custom(432, 432, false);
+        assert_eq!(
+            FeatureDisplay::new(&address, &feature, Some(&CLOCK), Some(&epoch_schedule))
+                .to_string(),
+            format!("{address} active since slot 12345 (epoch 28, ~2024-05-01T12:00:00Z)")
+        );
+        assert_eq!(
+            FeatureDisplay::new(&address, &feature, Some(&CLOCK), None).to_string(),
+            format!("{address} active since slot 12345 (~2024-05-01T12:00:00Z)")
+        );
+        assert_eq!(
+            FeatureDisplay::new(&address, &feature, None, Some(&epoch_schedule)).to_string(),
+            format!("{address} active since slot 12345 (epoch 28)")
+        );
+        assert_eq!(
+            FeatureDisplay::new(&address, &feature, None, None).to_string(),
+            format!("{address} active since slot 12345")
+        );
+    }
+
+    #[test]
+    fn test_display_pending() {
+        let address = address();
+        let feature = Feature::default();
+        let epoch_schedule = EpochSchedule::default();
+        assert_eq!(
+            FeatureDisplay::new(&address, &feature, Some(&CLOCK), Some(&epoch_schedule))
+                .to_string(),
+            format!("{address} pending activation")
+        );
+        assert_eq!(
+            FeatureDisplay::new(&address, &feature, None, None).to_string(),
+            format!("{address} pending activation")
+        );
+    }
+
+    #[test]
+    fn test_slot_duration() {
+        let address = address();
+        let feature = active();
+        let display = FeatureDisplay::new(&address, &feature, Some(&CLOCK), None);
+        assert_eq!(
+            display
+                .with_slot_duration(Duration::from_millis(1_000))
+                .approximate_activation_timestamp(),
+            Some(CLOCK.unix_timestamp - 7_655)
+        );
+        assert_eq!(
+            display
+                .with_slot_duration(Duration::ZERO)
+                .approximate_activation_timestamp(),
+            Some(CLOCK.unix_timestamp)
+        );
+
+        // activation after the clock slot
+        let feature = Feature {
+            activated_at: Some(CLOCK.slot + 9_000),
+        };
+        assert_eq!(
+            FeatureDisplay::new(&address, &feature, Some(&CLOCK), None).to_string(),
+            format!("{address} active since slot 29000 (~2024-05-01T13:51:02Z)")
+        );
+
+        // times that cannot be rendered are left out
+        let feature = Feature {
+            activated_at: Some(u64::MAX),
+        };
+        assert_eq!(
+            FeatureDisplay::new(&address, &feature, Some(&CLOCK), None).to_string(),
+            format!("{address} active since slot {}", u64::MAX)
+        );
+    }
+
+    #[test]
+    fn test_rfc3339() {
+        let format = |timestamp| Rfc3339::new(timestamp).map(|time| time.to_string());
+        assert_eq!(format(0).unwrap(), "1970-01-01T00:00:00Z");
+        assert_eq!(format(-1).unwrap(), "1969-12-31T23:59:59Z");
+        assert_eq!(format(951_782_400).unwrap(), "2000-02-29T00:00:00Z");
+        assert_eq!(format(253_402_300_799).unwrap(), "9999-12-31T23:59:59Z");
+        assert_eq!(format(253_402_300_800), None);
+        assert_eq!(format(-62_167_219_201), None);
+        assert_eq!(format(i64::MIN), None);
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_to_json() {
+        let address = address();
+        let feature = active();
+        let epoch_schedule = EpochSchedule::custom(432, 432, false);
+        assert_eq!(
+            FeatureDisplay::new(&address, &feature, Some(&CLOCK), Some(&epoch_schedule)).to_json(),
+            serde_json::json!({
+                "address": address.to_string(),
+                "active": true,
+                "activated_at": 12345,
+                "epoch": 28,
+                "approximate_time": "2024-05-01T12:00:00Z",
+            })
+        );
+        let feature = Feature::default();
+        assert_eq!(
+            FeatureDisplay::new(&address, &feature, None, None).to_json(),
+            serde_json::json!({
+                "address": address.to_string(),
+                "active": false,
+                "activated_at": null,
+                "epoch": null,
+                "approximate_time": null,
+            })
+        );
+    }
+}
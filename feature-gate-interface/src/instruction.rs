@@ -1,3 +1,4 @@
+use solana_program_error::ProgramError;
 #[cfg(feature = "bincode")]
 use {
     crate::state::Feature,
@@ -8,6 +9,52 @@ use {
     solana_system_interface::instruction as system_instruction,
 };
 
+/// Instructions of the feature program.
+///
+/// The data of an instruction is its discriminant, in a single byte.
+/// Activating a feature takes system program instructions instead, see
+/// `activate_with_lamports`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum FeatureInstruction {
+    /// Revoke the pending activation of a feature, burning the lamports of
+    /// its account.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[WRITE, SIGNER]` Feature account
+    /// 1. `[WRITE]` Incinerator
+    /// 2. `[]` System program
+    RevokePendingActivation = 0,
+
+    /// Close the account of a feature active on the whole cluster, moving
+    /// its lamports to a destination account. Only the feature authority can
+    /// close feature accounts, and only once the runtime supports it.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[WRITE]` Feature account
+    /// 1. `[WRITE]` Lamport destination
+    /// 2. `[SIGNER]` Feature authority
+    CloseActivatedFeature = 1,
+}
+
+impl FeatureInstruction {
+    /// Serialize the instruction into its instruction data.
+    pub fn pack(&self) -> Vec<u8> {
+        vec![*self as u8]
+    }
+
+    /// Deserialize an instruction from its instruction data.
+    pub fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
+        match data {
+            [0] => Ok(Self::RevokePendingActivation),
+            [1] => Ok(Self::CloseActivatedFeature),
+            _ => Err(ProgramError::InvalidInstructionData),
+        }
+    }
+}
+
 /// Activate a feature
 #[cfg(feature = "bincode")]
 #[deprecated(
@@ -47,6 +94,94 @@ pub fn revoke_pending_activation(feature_id: &Pubkey) -> Instruction {
     Instruction {
         program_id: crate::id(),
         accounts,
-        data: vec![0],
+        data: FeatureInstruction::RevokePendingActivation.pack(),
+    }
+}
+
+/// Creates a 'CloseActivatedFeature' instruction.
+#[cfg(feature = "bincode")]
+pub fn close_activated_feature(
+    feature_id: &Pubkey,
+    lamport_destination: &Pubkey,
+    authority: &Pubkey,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(*feature_id, false),
+        AccountMeta::new(*lamport_destination, false),
+        AccountMeta::new_readonly(*authority, true),
+    ];
+
+    Instruction {
+        program_id: crate::id(),
+        accounts,
+        data: FeatureInstruction::CloseActivatedFeature.pack(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pack_unpack() {
+        for instruction in [
+            FeatureInstruction::RevokePendingActivation,
+            FeatureInstruction::CloseActivatedFeature,
+        ] {
+            assert_eq!(
+                FeatureInstruction::unpack(&instruction.pack()),
+                Ok(instruction)
+            );
+        }
+        assert_eq!(FeatureInstruction::RevokePendingActivation.pack(), [0]);
+        assert_eq!(FeatureInstruction::CloseActivatedFeature.pack(), [1]);
+
+        for data in [&[][..], &[2], &[0, 0], &[1, 0]] {
+            assert_eq!(
+                FeatureInstruction::unpack(data),
+                Err(ProgramError::InvalidInstructionData)
+            );
+        }
+    }
+
+    #[test]
+    fn test_revoke_pending_activation() {
+        let feature_id = Pubkey::new_unique();
+        let instruction = revoke_pending_activation(&feature_id);
+        assert_eq!(instruction.program_id, id());
+        assert_eq!(
+            instruction.accounts,
+            [
+                AccountMeta::new(feature_id, true),
+                AccountMeta::new(incinerator::id(), false),
+                AccountMeta::new_readonly(system_program::id(), false),
+            ]
+        );
+        assert_eq!(instruction.data, [0]);
+        assert_eq!(
+            FeatureInstruction::unpack(&instruction.data),
+            Ok(FeatureInstruction::RevokePendingActivation)
+        );
+    }
+
+    #[test]
+    fn test_close_activated_feature() {
+        let feature_id = Pubkey::new_unique();
+        let lamport_destination = Pubkey::new_unique();
+        let authority = Pubkey::new_unique();
+        let instruction = close_activated_feature(&feature_id, &lamport_destination, &authority);
+        assert_eq!(instruction.program_id, id());
+        assert_eq!(
+            instruction.accounts,
+            [
+                AccountMeta::new(feature_id, false),
+                AccountMeta::new(lamport_destination, false),
+                AccountMeta::new_readonly(authority, true),
+            ]
+        );
+        assert_eq!(
+            FeatureInstruction::unpack(&instruction.data),
+            Ok(FeatureInstruction::CloseActivatedFeature)
+        );
     }
 }
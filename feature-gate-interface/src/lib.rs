@@ -12,10 +12,15 @@
 //!    active them.  When this occurs, the activation slot is recorded in the feature account
 #![cfg_attr(docsrs, feature(doc_cfg))]
 
+#[cfg(feature = "std")]
+pub mod display;
 pub mod error;
 pub mod instruction;
+pub mod registry;
 pub mod state;
 
+#[cfg(feature = "std")]
+pub use crate::display::FeatureDisplay;
 #[cfg(feature = "bincode")]
 #[allow(deprecated)]
 pub use crate::instruction::activate;
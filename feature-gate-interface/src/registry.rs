@@ -0,0 +1,209 @@
+//! Declarations of runtime features, shared by programs and tools.
+//!
+//! A feature is declared with [`declare_feature!`](crate::declare_feature),
+//! which creates a module holding its id and description, and the features
+//! of a crate are listed with [`feature_registry!`](crate::feature_registry),
+//! which creates a `registry()` function returning their declarations:
+//!
+//! ```
+//! use solana_feature_gate_interface::{declare_feature, feature_registry, registry};
+//!
+//! declare_feature!(
+//!     enable_foo,
+//!     "7txXZZD6Um59YoLMF7XUNimbMjsqsWhc7g2EniiTrmp1",
+//!     desc: "enable foo"
+//! );
+//! declare_feature!(
+//!     disable_bar,
+//!     "9onWzzvCzNC2jfhxxeqRgs5q7nFAAKpCUvkj6T6GJK9i",
+//!     desc: "disable bar"
+//! );
+//! feature_registry!(enable_foo, disable_bar);
+//!
+//! assert!(enable_foo::check_id(&enable_foo::id()));
+//! assert_eq!(enable_foo::DESCRIPTION, "enable foo");
+//! assert_eq!(registry()[1].name, "disable_bar");
+//! assert_eq!(registry::check_unique_ids(registry()), Ok(()));
+//! ```
+//!
+//! The registry is a static slice rather than a list collected at link time,
+//! so that it is available in `no_std` crates and on chain.
+
+#[doc(hidden)]
+pub use solana_pubkey::declare_id as __declare_id;
+use {core::fmt, solana_pubkey::Pubkey};
+
+/// The declaration of a runtime feature.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FeatureDecl {
+    /// Address of the feature account.
+    pub id: Pubkey,
+    /// Name of the module declaring the feature.
+    pub name: &'static str,
+    /// Human readable description of the feature.
+    pub description: &'static str,
+}
+
+/// Declares a runtime feature in a module named `$name`.
+///
+/// The module holds the `ID` of the feature with its `id()` and `check_id()`
+/// functions, as [`declare_id!`](solana_pubkey::declare_id) does, the
+/// `DESCRIPTION` of the feature, and its [`FeatureDecl`] as `DECL`.
+#[macro_export]
+macro_rules! declare_feature {
+    ($name:ident, $address:expr, desc: $description:expr $(,)?) => {
+        #[doc = $description]
+        pub mod $name {
+            $crate::registry::__declare_id!($address);
+
+            /// Human readable description of the feature.
+            pub const DESCRIPTION: &str = $description;
+
+            /// The declaration of the feature, for its registry.
+            pub const DECL: $crate::registry::FeatureDecl = $crate::registry::FeatureDecl {
+                id: id(),
+                name: stringify!($name),
+                description: DESCRIPTION,
+            };
+        }
+    };
+}
+
+/// Defines a `registry()` function returning the [`FeatureDecl`]s of the
+/// features declared by [`declare_feature!`](crate::declare_feature) in the
+/// given modules, in order.
+///
+/// The ids of the features are not checked, see [`check_unique_ids`].
+#[macro_export]
+macro_rules! feature_registry {
+    ($($($feature:ident)::+),* $(,)?) => {
+        /// The declarations of the features, in order.
+        pub fn registry() -> &'static [$crate::registry::FeatureDecl] {
+            const REGISTRY: &[$crate::registry::FeatureDecl] = &[$($($feature)::+::DECL),*];
+            REGISTRY
+        }
+    };
+}
+
+/// Two features of a registry have the same id.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DuplicateFeatureId {
+    /// The duplicated id.
+    pub id: Pubkey,
+    /// Name of the first feature with the id.
+    pub first: &'static str,
+    /// Name of the second feature with the id.
+    pub second: &'static str,
+}
+
+impl core::error::Error for DuplicateFeatureId {}
+
+impl fmt::Display for DuplicateFeatureId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "features {} and {} have the same id {}",
+            self.first, self.second, self.id
+        )
+    }
+}
+
+/// Checks that the features of `registry` have distinct ids, returning the
+/// first pair of features sharing an id otherwise.
+pub fn check_unique_ids(registry: &[FeatureDecl]) -> Result<(), DuplicateFeatureId> {
+    for (index, first) in registry.iter().enumerate() {
+        if let Some(second) = registry[index + 1..]
+            .iter()
+            .find(|second| second.id == first.id)
+        {
+            return Err(DuplicateFeatureId {
+                id: first.id,
+                first: first.name,
+                second: second.name,
+            });
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    mod features {
+        crate::declare_feature!(
+            enable_foo,
+            "7txXZZD6Um59YoLMF7XUNimbMjsqsWhc7g2EniiTrmp1",
+            desc: "enable foo"
+        );
+        crate::declare_feature!(
+            disable_bar,
+            "9onWzzvCzNC2jfhxxeqRgs5q7nFAAKpCUvkj6T6GJK9i",
+            desc: "disable bar",
+        );
+        crate::declare_feature!(
+            enable_foo_again,
+            "7txXZZD6Um59YoLMF7XUNimbMjsqsWhc7g2EniiTrmp1",
+            desc: "enable foo, again"
+        );
+    }
+
+    crate::feature_registry!(features::enable_foo, features::disable_bar);
+
+    mod duplicates {
+        crate::feature_registry!(
+            super::features::enable_foo,
+            super::features::disable_bar,
+            super::features::enable_foo_again,
+        );
+    }
+
+    #[test]
+    fn test_declare_feature() {
+        use features::enable_foo;
+
+        assert_eq!(
+            enable_foo::id(),
+            Pubkey::from_str_const("7txXZZD6Um59YoLMF7XUNimbMjsqsWhc7g2EniiTrmp1")
+        );
+        assert!(enable_foo::check_id(&enable_foo::ID));
+        assert!(!enable_foo::check_id(&features::disable_bar::ID));
+        assert_eq!(enable_foo::DESCRIPTION, "enable foo");
+        assert_eq!(
+            enable_foo::DECL,
+            FeatureDecl {
+                id: enable_foo::ID,
+                name: "enable_foo",
+                description: "enable foo",
+            }
+        );
+    }
+
+    #[test]
+    fn test_registry() {
+        let names: Vec<_> = registry().iter().map(|decl| decl.name).collect();
+        assert_eq!(names, ["enable_foo", "disable_bar"]);
+        assert_eq!(registry()[1].id, features::disable_bar::id());
+        assert_eq!(registry()[1].description, "disable bar");
+        assert_eq!(check_unique_ids(registry()), Ok(()));
+        assert_eq!(check_unique_ids(&[]), Ok(()));
+    }
+
+    #[test]
+    fn test_duplicate_ids() {
+        let error = check_unique_ids(duplicates::registry()).unwrap_err();
+        assert_eq!(
+            error,
+            DuplicateFeatureId {
+                id: features::enable_foo::ID,
+                first: "enable_foo",
+                second: "enable_foo_again",
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            "features enable_foo and enable_foo_again have the same id \
+             7txXZZD6Um59YoLMF7XUNimbMjsqsWhc7g2EniiTrmp1"
+        );
+    }
+}
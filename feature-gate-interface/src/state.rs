@@ -6,6 +6,29 @@ use {
     solana_sdk_ids::feature::id,
 };
 
+/// The state of a feature account: the slot the feature was activated at, if
+/// it is active.
+///
+/// Clients can fetch a feature account and decode it with bincode:
+///
+/// ```
+/// # use {
+/// #     solana_example_mocks::{solana_account::Account, solana_rpc_client::rpc_client::RpcClient},
+/// #     solana_feature_gate_interface::Feature,
+/// #     solana_pubkey::Pubkey,
+/// #     solana_sdk_ids::feature,
+/// # };
+/// # let feature_id = Pubkey::new_unique();
+/// # let data = bincode::serialize(&Feature { activated_at: Some(42) }).unwrap();
+/// # let client = RpcClient::new_mock_with_accounts(vec![(
+/// #     feature_id,
+/// #     Account { lamports: 1, data, owner: feature::id(), executable: false },
+/// # )]);
+/// let account = client.get_account(&feature_id)?;
+/// let feature: Feature = bincode::deserialize(&account.data)?;
+/// assert_eq!(feature.activated_at, Some(42));
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
 #[cfg_attr(
     feature = "serde",
     derive(serde_derive::Deserialize, serde_derive::Serialize)
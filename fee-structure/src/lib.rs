@@ -2,9 +2,13 @@
 #![cfg_attr(docsrs, feature(doc_cfg))]
 #![cfg_attr(feature = "frozen-abi", feature(min_specialization))]
 
-use std::num::NonZeroU32;
+use std::{fmt, num::NonZeroU32};
 
 /// A fee and its associated compute unit limit
+#[cfg_attr(
+    feature = "serde",
+    derive(serde_derive::Deserialize, serde_derive::Serialize)
+)]
 #[derive(Debug, Default, Clone, Eq, PartialEq)]
 pub struct FeeBin {
     /// maximum compute units for which this fee will be charged
@@ -13,6 +17,100 @@ pub struct FeeBin {
     pub fee: u64,
 }
 
+/// Invalid parameters of a [`FeeBin`] ladder constructor
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum FeeBinLadderError {
+    /// The ladder would have no bins
+    NoBins,
+    /// There are more bins than compute units, so the limits could not
+    /// strictly increase
+    TooManyBins,
+    /// The ratio between limits is not greater than one
+    InvalidRatio,
+    /// A limit does not fit in a `u64`
+    LimitOverflow,
+}
+
+impl core::error::Error for FeeBinLadderError {}
+
+impl fmt::Display for FeeBinLadderError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::NoBins => f.write_str("fee bin ladder has no bins"),
+            Self::TooManyBins => f.write_str("fee bin ladder has more bins than compute units"),
+            Self::InvalidRatio => f.write_str("fee bin ladder ratio is not greater than one"),
+            Self::LimitOverflow => f.write_str("fee bin ladder limit overflows"),
+        }
+    }
+}
+
+impl FeeBin {
+    /// Create `num_bins` bins evenly splitting `0..=max_cu`, with fees
+    /// growing linearly up to `max_fee`.
+    ///
+    /// The limit of bin `i` is `max_cu * (i + 1) / num_bins`, and its fee
+    /// `max_fee * (i + 1) / num_bins`, rounding down, so the last bin has
+    /// the limit `max_cu` and the fee `max_fee`.
+    pub fn linear_ladder(
+        max_cu: u64,
+        num_bins: usize,
+        max_fee: u64,
+    ) -> Result<Vec<FeeBin>, FeeBinLadderError> {
+        if num_bins == 0 {
+            return Err(FeeBinLadderError::NoBins);
+        }
+        let bins = num_bins as u128;
+        if bins > u128::from(max_cu) {
+            return Err(FeeBinLadderError::TooManyBins);
+        }
+        // `step <= bins`, so the results are at most `max_cu` and `max_fee`
+        let scale =
+            |max: u64, step: u128| u128::from(max).saturating_mul(step).saturating_div(bins) as u64;
+        Ok((1..=bins)
+            .map(|step| FeeBin {
+                limit: scale(max_cu, step),
+                fee: scale(max_fee, step),
+            })
+            .collect())
+    }
+
+    /// Create `num_bins` bins whose limits grow by `ratio_num / ratio_den`
+    /// from `first_limit`, with `fee_per_bin(i)` as the fee of bin `i`.
+    ///
+    /// Limits are rounded down, but always increase by at least one compute
+    /// unit.
+    pub fn geometric_ladder(
+        first_limit: u64,
+        ratio_num: u64,
+        ratio_den: u64,
+        num_bins: usize,
+        fee_per_bin: impl Fn(usize) -> u64,
+    ) -> Result<Vec<FeeBin>, FeeBinLadderError> {
+        if num_bins == 0 {
+            return Err(FeeBinLadderError::NoBins);
+        }
+        if ratio_den == 0 || ratio_num <= ratio_den {
+            return Err(FeeBinLadderError::InvalidRatio);
+        }
+        let mut bins = Vec::with_capacity(num_bins);
+        let mut limit = first_limit;
+        for i in 0..num_bins {
+            if i > 0 {
+                let next = u128::from(limit)
+                    .saturating_mul(u128::from(ratio_num))
+                    .saturating_div(u128::from(ratio_den));
+                limit = u64::try_from(next.max(u128::from(limit).saturating_add(1)))
+                    .map_err(|_| FeeBinLadderError::LimitOverflow)?;
+            }
+            bins.push(FeeBin {
+                limit,
+                fee: fee_per_bin(i),
+            });
+        }
+        Ok(bins)
+    }
+}
+
 pub struct FeeBudgetLimits {
     pub loaded_accounts_data_size_limit: NonZeroU32,
     pub heap_cost: u64,
@@ -20,8 +118,33 @@ pub struct FeeBudgetLimits {
     pub prioritization_fee: u64,
 }
 
+/// How [`FeeStructure::calculate_compute_fee`] prices compute units between
+/// the limits of the compute fee bins
+#[cfg_attr(
+    feature = "serde",
+    derive(serde_derive::Deserialize, serde_derive::Serialize)
+)]
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+pub enum FeeCurve {
+    /// Charge the fee of the first bin whose limit covers the compute units
+    #[default]
+    Step,
+    /// Interpolate linearly between the fees of the bins surrounding the
+    /// compute units, rounding down
+    Linear,
+}
+
 /// Information used to calculate fees
+///
+/// New fields may be added, so outside of this crate it is built from
+/// [`FeeStructure::default`], then adjusted with the `with_*` methods or by
+/// setting its fields.
+#[cfg_attr(
+    feature = "serde",
+    derive(serde_derive::Deserialize, serde_derive::Serialize)
+)]
 #[derive(Debug, Clone, Eq, PartialEq)]
+#[non_exhaustive]
 pub struct FeeStructure {
     /// lamports per signature
     pub lamports_per_signature: u64,
@@ -29,6 +152,40 @@ pub struct FeeStructure {
     pub lamports_per_write_lock: u64,
     /// Compute unit fee bins
     pub compute_fee_bins: Vec<FeeBin>,
+    /// Optional multiplier applied to the transaction fee, e.g. to
+    /// experiment with dynamic base fees
+    pub congestion_multiplier: Option<CongestionMultiplier>,
+    /// Pricing of compute units between the compute fee bins
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub fee_curve: FeeCurve,
+}
+
+/// A rational multiplier applied to fees by [`FeeStructure::calculate_fee_details`]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde_derive::Deserialize, serde_derive::Serialize)
+)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct CongestionMultiplier {
+    pub numerator: u64,
+    pub denominator: u64,
+    /// Whether the multiplier also applies to the prioritization fee
+    pub applies_to_prioritization: bool,
+}
+
+impl CongestionMultiplier {
+    /// Multiply `fee` by `numerator / denominator`, rounding up and
+    /// saturating at `u64::MAX`. A zero denominator saturates as well.
+    pub fn apply(&self, fee: u64) -> u64 {
+        let product = u128::from(fee).saturating_mul(u128::from(self.numerator));
+        let Some(multiplied) = product
+            .checked_add(u128::from(self.denominator).saturating_sub(1))
+            .and_then(|rounded| rounded.checked_div(u128::from(self.denominator)))
+        else {
+            return u64::MAX;
+        };
+        u64::try_from(multiplied).unwrap_or(u64::MAX)
+    }
 }
 
 #[cfg_attr(
@@ -74,6 +231,87 @@ impl FeeDetails {
 pub const ACCOUNT_DATA_COST_PAGE_SIZE: u64 = 32_u64.saturating_mul(1024);
 
 impl FeeStructure {
+    pub fn with_congestion_multiplier(
+        mut self,
+        congestion_multiplier: Option<CongestionMultiplier>,
+    ) -> Self {
+        self.congestion_multiplier = congestion_multiplier;
+        self
+    }
+
+    pub fn with_fee_curve(mut self, fee_curve: FeeCurve) -> Self {
+        self.fee_curve = fee_curve;
+        self
+    }
+
+    /// Calculate the fee of `compute_units` from the compute fee bins, which
+    /// are expected to be sorted by increasing limit.
+    ///
+    /// Compute units below the limit of the first bin are charged its fee,
+    /// and compute units above the limit of the last bin are charged the
+    /// fee of the last bin. In between, [`FeeCurve::Step`] charges the fee
+    /// of the first bin whose limit covers `compute_units`, while
+    /// [`FeeCurve::Linear`] interpolates between the fees of that bin and
+    /// the previous one. Without bins the fee is zero.
+    pub fn calculate_compute_fee(&self, compute_units: u64) -> u64 {
+        let Some(index) = self
+            .compute_fee_bins
+            .iter()
+            .position(|bin| compute_units <= bin.limit)
+        else {
+            return self
+                .compute_fee_bins
+                .last()
+                .map(|bin| bin.fee)
+                .unwrap_or_default();
+        };
+        let bin = &self.compute_fee_bins[index];
+        match (self.fee_curve, index.checked_sub(1)) {
+            (FeeCurve::Step, _) | (FeeCurve::Linear, None) => bin.fee,
+            (FeeCurve::Linear, Some(previous)) => {
+                let previous = &self.compute_fee_bins[previous];
+                // `previous.limit < compute_units <= bin.limit`, since `bin`
+                // is the first bin covering `compute_units`
+                let span = u128::from(bin.limit.saturating_sub(previous.limit));
+                let offset = u128::from(compute_units.saturating_sub(previous.limit));
+                // the weights sum to `span`, so neither the products nor
+                // their sum overflow
+                let weighted = u128::from(previous.fee)
+                    .saturating_mul(span.saturating_sub(offset))
+                    .saturating_add(u128::from(bin.fee).saturating_mul(offset));
+                weighted
+                    .checked_div(span)
+                    .and_then(|fee| u64::try_from(fee).ok())
+                    .unwrap_or(bin.fee)
+            }
+        }
+    }
+
+    /// Calculate the fees of a transaction with `num_signatures` signatures
+    /// and `num_write_locks` write locks, applying the congestion multiplier
+    /// if one is set.
+    pub fn calculate_fee_details(
+        &self,
+        num_signatures: u64,
+        num_write_locks: u64,
+        prioritization_fee: u64,
+    ) -> FeeDetails {
+        let transaction_fee = num_signatures
+            .saturating_mul(self.lamports_per_signature)
+            .saturating_add(num_write_locks.saturating_mul(self.lamports_per_write_lock));
+        match &self.congestion_multiplier {
+            None => FeeDetails::new(transaction_fee, prioritization_fee),
+            Some(multiplier) => FeeDetails::new(
+                multiplier.apply(transaction_fee),
+                if multiplier.applies_to_prioritization {
+                    multiplier.apply(prioritization_fee)
+                } else {
+                    prioritization_fee
+                },
+            ),
+        }
+    }
+
     pub fn get_max_fee(&self, num_signatures: u64, num_write_locks: u64) -> u64 {
         num_signatures
             .saturating_mul(self.lamports_per_signature)
@@ -106,6 +344,8 @@ impl Default for FeeStructure {
                 limit: 1_400_000,
                 fee: 0,
             }],
+            congestion_multiplier: None,
+            fee_curve: FeeCurve::Step,
         }
     }
 }
@@ -119,7 +359,18 @@ impl ::solana_frozen_abi::abi_example::AbiExample for FeeStructure {
 
 #[cfg(test)]
 mod tests {
-    use super::*;
+    use {super::*, proptest::proptest};
+
+    fn fee_structure(bins: &[(u64, u64)], fee_curve: FeeCurve) -> FeeStructure {
+        FeeStructure {
+            compute_fee_bins: bins
+                .iter()
+                .map(|&(limit, fee)| FeeBin { limit, fee })
+                .collect(),
+            ..FeeStructure::default()
+        }
+        .with_fee_curve(fee_curve)
+    }
 
     #[test]
     fn test_calculate_memory_usage_cost() {
@@ -152,4 +403,331 @@ mod tests {
             FeeStructure::calculate_memory_usage_cost(64 * K, heap_cost)
         );
     }
+
+    #[test]
+    fn test_calculate_fee_details_without_multiplier() {
+        let fee_structure = FeeStructure::default();
+        assert_eq!(
+            fee_structure.calculate_fee_details(2, 3, 7),
+            FeeDetails::new(10_000, 7)
+        );
+    }
+
+    #[test]
+    fn test_congestion_multiplier_identity() {
+        let multiplier = CongestionMultiplier {
+            numerator: 1,
+            denominator: 1,
+            applies_to_prioritization: true,
+        };
+        let fee_structure = FeeStructure::default().with_congestion_multiplier(Some(multiplier));
+        assert_eq!(
+            fee_structure.calculate_fee_details(2, 3, 7),
+            FeeStructure::default().calculate_fee_details(2, 3, 7)
+        );
+        for fee in [0, 1, 5000, u64::MAX] {
+            assert_eq!(multiplier.apply(fee), fee);
+        }
+    }
+
+    #[test]
+    fn test_congestion_multiplier_rounds_up() {
+        let multiplier = CongestionMultiplier {
+            numerator: 3,
+            denominator: 2,
+            applies_to_prioritization: false,
+        };
+        assert_eq!(multiplier.apply(0), 0);
+        assert_eq!(multiplier.apply(1), 2);
+        assert_eq!(multiplier.apply(2), 3);
+        assert_eq!(multiplier.apply(3), 5);
+
+        let fee_structure = FeeStructure {
+            lamports_per_signature: 5001,
+            ..FeeStructure::default()
+        }
+        .with_congestion_multiplier(Some(multiplier));
+        // 5001 * 3 / 2 = 7501.5, and the prioritization fee is untouched
+        assert_eq!(
+            fee_structure.calculate_fee_details(1, 0, 3),
+            FeeDetails::new(7502, 3)
+        );
+
+        let fee_structure = fee_structure.with_congestion_multiplier(Some(CongestionMultiplier {
+            applies_to_prioritization: true,
+            ..multiplier
+        }));
+        assert_eq!(
+            fee_structure.calculate_fee_details(1, 0, 3),
+            FeeDetails::new(7502, 5)
+        );
+    }
+
+    #[test]
+    fn test_congestion_multiplier_large_values() {
+        // fee * numerator overflows u64 but the result fits
+        let multiplier = CongestionMultiplier {
+            numerator: u64::MAX,
+            denominator: u64::MAX / 2,
+            applies_to_prioritization: false,
+        };
+        assert_eq!(multiplier.apply(1_000_000_000_000), 2_000_000_000_001);
+
+        // the result does not fit in u64
+        let multiplier = CongestionMultiplier {
+            numerator: u64::MAX,
+            denominator: 2,
+            applies_to_prioritization: false,
+        };
+        assert_eq!(multiplier.apply(5000), u64::MAX);
+        assert_eq!(multiplier.apply(u64::MAX), u64::MAX);
+
+        let multiplier = CongestionMultiplier {
+            numerator: 1,
+            denominator: 0,
+            applies_to_prioritization: false,
+        };
+        assert_eq!(multiplier.apply(5000), u64::MAX);
+    }
+
+    #[test]
+    fn test_compute_fee_bin_boundaries() {
+        let bins = [(200_000, 1_000), (600_000, 5_000), (1_400_000, 9_000)];
+        let step = fee_structure(&bins, FeeCurve::Step);
+        let linear = fee_structure(&bins, FeeCurve::Linear);
+        for compute_units in [0, 1, 200_000, 600_000, 1_400_000, 1_400_001, u64::MAX] {
+            assert_eq!(
+                linear.calculate_compute_fee(compute_units),
+                step.calculate_compute_fee(compute_units),
+            );
+        }
+        assert_eq!(step.calculate_compute_fee(200_001), 5_000);
+        assert_eq!(step.calculate_compute_fee(u64::MAX), 9_000);
+        assert_eq!(linear.calculate_compute_fee(0), 1_000);
+        assert_eq!(linear.calculate_compute_fee(u64::MAX), 9_000);
+    }
+
+    #[test]
+    fn test_compute_fee_linear_midpoints() {
+        let linear = fee_structure(
+            &[(200_000, 1_000), (600_000, 5_000), (1_400_000, 2_000)],
+            FeeCurve::Linear,
+        );
+        // 1_000 + 4_000 * 200_000 / 400_000
+        assert_eq!(linear.calculate_compute_fee(400_000), 3_000);
+        // 1_000 + 4_000 * 1 / 400_000 = 1_000.01
+        assert_eq!(linear.calculate_compute_fee(200_001), 1_000);
+        // 1_000 + 4_000 * 399_999 / 400_000 = 4_999.99
+        assert_eq!(linear.calculate_compute_fee(599_999), 4_999);
+        // 5_000 - 3_000 * 100_000 / 800_000 = 4_625
+        assert_eq!(linear.calculate_compute_fee(700_000), 4_625);
+        // 5_000 - 3_000 * 1 / 800_000 = 4_999.99625
+        assert_eq!(linear.calculate_compute_fee(600_001), 4_999);
+
+        // fees and limits near `u64::MAX` do not overflow
+        let linear = fee_structure(&[(0, u64::MAX - 2), (u64::MAX, u64::MAX)], FeeCurve::Linear);
+        assert_eq!(linear.calculate_compute_fee(u64::MAX / 2), u64::MAX - 2);
+        assert_eq!(linear.calculate_compute_fee(u64::MAX - 1), u64::MAX - 1);
+    }
+
+    #[test]
+    fn test_compute_fee_single_bin() {
+        for fee_curve in [FeeCurve::Step, FeeCurve::Linear] {
+            let fee_structure = fee_structure(&[(1_400_000, 7)], fee_curve);
+            for compute_units in [0, 700_000, 1_400_000, u64::MAX] {
+                assert_eq!(fee_structure.calculate_compute_fee(compute_units), 7);
+            }
+            let no_bins = self::fee_structure(&[], fee_curve);
+            assert_eq!(no_bins.calculate_compute_fee(1), 0);
+        }
+        assert_eq!(FeeStructure::default().calculate_compute_fee(200_000), 0);
+    }
+
+    fn assert_strictly_increasing(bins: &[FeeBin]) {
+        assert!(bins.windows(2).all(|pair| pair[0].limit < pair[1].limit));
+    }
+
+    #[test]
+    fn test_linear_ladder() {
+        let bins = FeeBin::linear_ladder(1_400_000, 4, 10_000).unwrap();
+        assert_eq!(
+            bins,
+            [
+                FeeBin {
+                    limit: 350_000,
+                    fee: 2_500
+                },
+                FeeBin {
+                    limit: 700_000,
+                    fee: 5_000
+                },
+                FeeBin {
+                    limit: 1_050_000,
+                    fee: 7_500
+                },
+                FeeBin {
+                    limit: 1_400_000,
+                    fee: 10_000
+                },
+            ]
+        );
+
+        // limits and fees are rounded down, and the last bin is clamped
+        let bins = FeeBin::linear_ladder(10, 3, 7).unwrap();
+        assert_eq!(
+            bins.iter()
+                .map(|bin| (bin.limit, bin.fee))
+                .collect::<Vec<_>>(),
+            [(3, 2), (6, 4), (10, 7)]
+        );
+        let bins = FeeBin::linear_ladder(u64::MAX, 7, u64::MAX).unwrap();
+        assert_strictly_increasing(&bins);
+        assert_eq!(
+            bins.last(),
+            Some(&FeeBin {
+                limit: u64::MAX,
+                fee: u64::MAX
+            })
+        );
+        // one compute unit per bin
+        let bins = FeeBin::linear_ladder(5, 5, 0).unwrap();
+        assert_eq!(
+            bins.iter().map(|bin| bin.limit).collect::<Vec<_>>(),
+            [1, 2, 3, 4, 5]
+        );
+
+        assert_eq!(
+            FeeBin::linear_ladder(1_400_000, 0, 10_000),
+            Err(FeeBinLadderError::NoBins)
+        );
+        assert_eq!(
+            FeeBin::linear_ladder(4, 5, 10_000),
+            Err(FeeBinLadderError::TooManyBins)
+        );
+    }
+
+    #[test]
+    fn test_geometric_ladder() {
+        let bins = FeeBin::geometric_ladder(50_000, 2, 1, 10, |i| 1_000 * i as u64).unwrap();
+        assert_eq!(
+            bins.iter()
+                .map(|bin| (bin.limit, bin.fee))
+                .collect::<Vec<_>>(),
+            [
+                (50_000, 0),
+                (100_000, 1_000),
+                (200_000, 2_000),
+                (400_000, 3_000),
+                (800_000, 4_000),
+                (1_600_000, 5_000),
+                (3_200_000, 6_000),
+                (6_400_000, 7_000),
+                (12_800_000, 8_000),
+                (25_600_000, 9_000),
+            ]
+        );
+
+        // limits always increase, even when rounding down
+        let bins = FeeBin::geometric_ladder(0, 3, 2, 6, |_| 0).unwrap();
+        assert_eq!(
+            bins.iter().map(|bin| bin.limit).collect::<Vec<_>>(),
+            [0, 1, 2, 3, 4, 6]
+        );
+
+        assert_eq!(
+            FeeBin::geometric_ladder(50_000, 2, 1, 0, |_| 0),
+            Err(FeeBinLadderError::NoBins)
+        );
+        for (ratio_num, ratio_den) in [(1, 1), (1, 2), (0, 1), (2, 0)] {
+            assert_eq!(
+                FeeBin::geometric_ladder(50_000, ratio_num, ratio_den, 10, |_| 0),
+                Err(FeeBinLadderError::InvalidRatio)
+            );
+        }
+        assert_eq!(
+            FeeBin::geometric_ladder(u64::MAX / 2 + 1, 2, 1, 2, |_| 0),
+            Err(FeeBinLadderError::LimitOverflow)
+        );
+        assert!(FeeBin::geometric_ladder(u64::MAX, 2, 1, 1, |_| 0).is_ok());
+    }
+
+    proptest! {
+        #[test]
+        fn test_compute_fee_linear_monotonic(
+            mut limits in proptest::collection::vec(0u64..2_000_000, 1..8),
+            mut fees in proptest::collection::vec(0u64..u64::MAX, 8),
+            a in 0u64..2_100_000,
+            b in 0u64..2_100_000,
+        ) {
+            limits.sort_unstable();
+            limits.dedup();
+            fees.sort_unstable();
+            let bins: Vec<_> = limits.into_iter().zip(fees).collect();
+            let linear = fee_structure(&bins, FeeCurve::Linear);
+            let step = fee_structure(&bins, FeeCurve::Step);
+            let (low, high) = (a.min(b), a.max(b));
+            assert!(linear.calculate_compute_fee(low) <= linear.calculate_compute_fee(high));
+            // with increasing fees, interpolating never charges more
+            assert!(linear.calculate_compute_fee(low) <= step.calculate_compute_fee(low));
+        }
+
+        #[test]
+        fn test_ladders_strictly_increasing(
+            max_cu in 1u64..u64::MAX,
+            num_bins in 1usize..64,
+            first_limit in 0u64..2_000_000,
+            ratio_num in 2u64..16,
+        ) {
+            if let Ok(bins) = FeeBin::linear_ladder(max_cu, num_bins, 5_000) {
+                assert_eq!(bins.len(), num_bins);
+                assert_strictly_increasing(&bins);
+                assert_eq!(bins.last().map(|bin| bin.limit), Some(max_cu));
+            }
+            if let Ok(bins) =
+                FeeBin::geometric_ladder(first_limit, ratio_num, ratio_num - 1, num_bins, |i| i as u64)
+            {
+                assert_eq!(bins.len(), num_bins);
+                assert_strictly_increasing(&bins);
+            }
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_congestion_multiplier_serde() {
+        let multiplier = CongestionMultiplier {
+            numerator: 3,
+            denominator: 2,
+            applies_to_prioritization: true,
+        };
+        let serialized = bincode::serialize(&multiplier).unwrap();
+        assert_eq!(
+            bincode::deserialize::<CongestionMultiplier>(&serialized).unwrap(),
+            multiplier
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_fee_curve_serde() {
+        // configs predating the fee curve default to `FeeCurve::Step`
+        let fee_structure: FeeStructure = serde_json::from_str(
+            r#"{
+                "lamports_per_signature": 5000,
+                "lamports_per_write_lock": 0,
+                "compute_fee_bins": [{ "limit": 1400000, "fee": 0 }],
+                "congestion_multiplier": null
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(fee_structure, FeeStructure::default());
+
+        let linear = FeeStructure::default().with_fee_curve(FeeCurve::Linear);
+        let serialized = serde_json::to_string(&linear).unwrap();
+        assert!(serialized.contains(r#""fee_curve":"Linear""#));
+        assert_eq!(
+            serde_json::from_str::<FeeStructure>(&serialized).unwrap(),
+            linear
+        );
+    }
 }
@@ -180,6 +180,7 @@ example_impls! { u16, 0 }
 example_impls! { u32, 0 }
 example_impls! { u64, 0 }
 example_impls! { u128, 0 }
+example_impls! { std::num::NonZeroU8, std::num::NonZeroU8::MIN }
 
 example_impls! { isize, 0 }
 example_impls! { i8, 0 }
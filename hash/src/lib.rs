@@ -16,7 +16,7 @@ use core::{
 use serde_derive::{Deserialize, Serialize};
 #[cfg(feature = "sanitize")]
 use solana_sanitize::Sanitize;
-#[cfg(feature = "borsh")]
+#[cfg(any(feature = "borsh", feature = "schemars"))]
 extern crate alloc;
 #[cfg(feature = "borsh")]
 use alloc::string::ToString;
@@ -89,6 +89,34 @@ impl fmt::Display for Hash {
     }
 }
 
+/// Describes a hash as its base58 string, the form of [`Display`] and
+/// [`FromStr`], rather than the byte array the derived serde implementations
+/// produce.
+///
+/// [`Display`]: core::fmt::Display
+/// [`FromStr`]: core::str::FromStr
+#[cfg(feature = "schemars")]
+impl schemars::JsonSchema for Hash {
+    fn schema_name() -> alloc::borrow::Cow<'static, str> {
+        "Hash".into()
+    }
+
+    fn schema_id() -> alloc::borrow::Cow<'static, str> {
+        "solana_hash::Hash".into()
+    }
+
+    fn json_schema(_generator: &mut schemars::SchemaGenerator) -> schemars::Schema {
+        schemars::json_schema!({
+            "description": "A base58 encoded 32-byte hash",
+            "type": "string",
+            "pattern": "^[1-9A-HJ-NP-Za-km-z]{32,44}$",
+            "minLength": 32,
+            "maxLength": MAX_BASE58_LEN,
+            "examples": ["11111111111111111111111111111111"],
+        })
+    }
+}
+
 #[cfg(feature = "decode")]
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ParseHashError {
@@ -109,22 +137,86 @@ impl fmt::Display for ParseHashError {
     }
 }
 
+/// Error returned by [`Hash::parse_detailed`].
+#[cfg(feature = "decode")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DetailedParseHashError {
+    /// The string does not decode to 32 bytes.
+    WrongSize,
+    /// The string holds a character outside of the base58 alphabet.
+    InvalidCharacter {
+        /// Byte offset of the first invalid character.
+        index: usize,
+        character: char,
+    },
+}
+
+#[cfg(feature = "decode")]
+impl DetailedParseHashError {
+    /// The error for the invalid `byte` reported by five8, which is the first
+    /// invalid byte of `s`, and so the first byte of a character.
+    fn invalid_char(s: &str, byte: u8) -> Self {
+        s.char_indices()
+            .find(|&(index, _)| s.as_bytes()[index] == byte)
+            .map_or(Self::WrongSize, |(index, character)| {
+                Self::InvalidCharacter { index, character }
+            })
+    }
+}
+
+#[cfg(feature = "decode")]
+impl core::error::Error for DetailedParseHashError {}
+
+#[cfg(feature = "decode")]
+impl fmt::Display for DetailedParseHashError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::WrongSize => ParseHashError::WrongSize.fmt(f),
+            Self::InvalidCharacter { index, character } => {
+                write!(
+                    f,
+                    "invalid base58 character {character:?} at position {index}"
+                )
+            }
+        }
+    }
+}
+
+#[cfg(feature = "decode")]
+impl From<DetailedParseHashError> for ParseHashError {
+    fn from(error: DetailedParseHashError) -> Self {
+        match error {
+            DetailedParseHashError::WrongSize => Self::WrongSize,
+            DetailedParseHashError::InvalidCharacter { .. } => Self::Invalid,
+        }
+    }
+}
+
 #[cfg(feature = "decode")]
 impl FromStr for Hash {
     type Err = ParseHashError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse_detailed(s).map_err(ParseHashError::from)
+    }
+}
+
+#[cfg(feature = "decode")]
+impl Hash {
+    /// Parses a base58 encoded hash like [`FromStr`], reporting where the
+    /// first invalid character is.
+    pub fn parse_detailed(s: &str) -> Result<Self, DetailedParseHashError> {
         use five8::DecodeError;
         if s.len() > MAX_BASE58_LEN {
-            return Err(ParseHashError::WrongSize);
+            return Err(DetailedParseHashError::WrongSize);
         }
         let mut bytes = [0; HASH_BYTES];
         five8::decode_32(s, &mut bytes).map_err(|e| match e {
-            DecodeError::InvalidChar(_) => ParseHashError::Invalid,
+            DecodeError::InvalidChar(byte) => DetailedParseHashError::invalid_char(s, byte),
             DecodeError::TooLong
             | DecodeError::TooShort
             | DecodeError::LargestTermTooHigh
-            | DecodeError::OutputTooLong => ParseHashError::WrongSize,
+            | DecodeError::OutputTooLong => DetailedParseHashError::WrongSize,
         })?;
         Ok(Self::from(bytes))
     }
@@ -135,6 +227,19 @@ impl Hash {
         Self(hash_array)
     }
 
+    /// Deserializes a `Hash` from its borsh encoding, which must be exactly
+    /// [`HASH_BYTES`] long.
+    #[cfg(feature = "borsh")]
+    pub fn try_from_borsh_slice(bytes: &[u8]) -> borsh::io::Result<Self> {
+        if bytes.len() != HASH_BYTES {
+            return Err(borsh::io::Error::new(
+                borsh::io::ErrorKind::InvalidData,
+                "borsh encoding of a hash must be 32 bytes long",
+            ));
+        }
+        borsh::from_slice(bytes)
+    }
+
     /// unique Hash for tests and benchmarks.
     #[cfg(feature = "atomic")]
     pub fn new_unique() -> Self {
@@ -154,6 +259,64 @@ impl Hash {
     pub const fn as_bytes(&self) -> &[u8; HASH_BYTES] {
         &self.0
     }
+
+    /// Return the hash as four little-endian `u64`s, in byte order.
+    pub const fn to_u64_lanes(&self) -> [u64; 4] {
+        let mut lanes = [0; 4];
+        let mut i = 0;
+        while i < lanes.len() {
+            let b = &self.0;
+            let o = i * 8;
+            lanes[i] = u64::from_le_bytes([
+                b[o],
+                b[o + 1],
+                b[o + 2],
+                b[o + 3],
+                b[o + 4],
+                b[o + 5],
+                b[o + 6],
+                b[o + 7],
+            ]);
+            i += 1;
+        }
+        lanes
+    }
+
+    /// Derive a `u64` from the hash and `seed`, for probabilistic data
+    /// structures like bloom filters and dedup tables.
+    ///
+    /// With a random per-process seed, inputs ground to collide for one seed
+    /// do not collide for another. Each lane of [`Self::to_u64_lanes`] is
+    /// xor-folded into the state, which is then multiplied by an odd
+    /// constant derived from the seed, and the result is finalized with a
+    /// second xor-fold and multiply round. Flipping any bit of the hash or
+    /// the seed flips about half the bits of the output.
+    ///
+    /// This is not a cryptographic hash function, and its output must not
+    /// be relied on to stay the same across releases.
+    pub const fn to_u64_seeded(&self, seed: u64) -> u64 {
+        seeded_u64(self.to_u64_lanes(), seed)
+    }
+}
+
+/// The mixing function of [`Hash::to_u64_seeded`], also used by
+/// `Address::to_u64_seeded` in `solana-address`.
+const fn seeded_u64(lanes: [u64; 4], seed: u64) -> u64 {
+    // splitmix64 finalizer of the seed, made odd so that the multiplications
+    // are bijective
+    let mut k = (seed ^ (seed >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    k = (k ^ (k >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    k = (k ^ (k >> 31)) | 1;
+
+    let mut h = seed;
+    let mut i = 0;
+    while i < lanes.len() {
+        h = (h ^ lanes[i]).wrapping_mul(k);
+        h ^= h >> 32;
+        i += 1;
+    }
+    h = (h ^ (h >> 29)).wrapping_mul(0x9e37_79b9_7f4a_7c15);
+    h ^ (h >> 32)
 }
 
 #[cfg(test)]
@@ -165,6 +328,77 @@ mod tests {
         assert!(Hash::new_unique() != Hash::new_unique());
     }
 
+    /// Deterministic pseudorandom `u64`s, with the splitmix64 generator.
+    fn pseudorandom(state: &mut u64) -> u64 {
+        *state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut x = *state;
+        x = (x ^ (x >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        x = (x ^ (x >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        x ^ (x >> 31)
+    }
+
+    #[test]
+    fn test_to_u64_lanes() {
+        let hash = Hash::new_from_array(core::array::from_fn(|i| i as u8));
+        assert_eq!(
+            hash.to_u64_lanes(),
+            [
+                0x0706_0504_0302_0100,
+                0x0f0e_0d0c_0b0a_0908,
+                0x1716_1514_1312_1110,
+                0x1f1e_1d1c_1b1a_1918,
+            ]
+        );
+        const LANES: [u64; 4] = Hash::new_from_array([0xff; HASH_BYTES]).to_u64_lanes();
+        assert_eq!(LANES, [u64::MAX; 4]);
+    }
+
+    #[test]
+    fn test_to_u64_seeded_deterministic() {
+        let hash = Hash::new_from_array([7; HASH_BYTES]);
+        assert_eq!(hash.to_u64_seeded(42), hash.to_u64_seeded(42));
+        assert_eq!(hash.clone().to_u64_seeded(42), hash.to_u64_seeded(42));
+        assert_ne!(hash.to_u64_seeded(42), hash.to_u64_seeded(43));
+        assert_ne!(
+            hash.to_u64_seeded(42),
+            Hash::new_from_array([8; HASH_BYTES]).to_u64_seeded(42)
+        );
+        const SEEDED: u64 = Hash::new_from_array([7; HASH_BYTES]).to_u64_seeded(42);
+        assert_eq!(SEEDED, hash.to_u64_seeded(42));
+    }
+
+    #[test]
+    fn test_to_u64_seeded_avalanche() {
+        const SAMPLES: u32 = 200;
+        let mut state = 1;
+        let mut total = 0;
+        // every bit of the hash, and then of the seed
+        for bit in 0..HASH_BYTES * 8 + 64 {
+            let mut flipped_bits = 0;
+            for _ in 0..SAMPLES {
+                let mut bytes = [0; HASH_BYTES];
+                for chunk in bytes.chunks_mut(8) {
+                    chunk.copy_from_slice(&pseudorandom(&mut state).to_le_bytes());
+                }
+                let mut seed = pseudorandom(&mut state);
+                let output = Hash::new_from_array(bytes).to_u64_seeded(seed);
+                if bit < HASH_BYTES * 8 {
+                    bytes[bit / 8] ^= 1 << (bit % 8);
+                } else {
+                    seed ^= 1 << (bit - HASH_BYTES * 8);
+                }
+                let flipped = Hash::new_from_array(bytes).to_u64_seeded(seed);
+                flipped_bits += (output ^ flipped).count_ones();
+            }
+            // about half of the 64 output bits flip on average
+            let average = flipped_bits / SAMPLES;
+            assert!((28..=36).contains(&average), "bit {bit}: {average}");
+            total += flipped_bits;
+        }
+        let average = total as f64 / f64::from(SAMPLES) / (HASH_BYTES * 8 + 64) as f64;
+        assert!((31.5..=32.5).contains(&average), "{average}");
+    }
+
     #[test]
     fn test_hash_fromstr() {
         let hash = Hash::new_from_array([1; 32]);
@@ -205,4 +439,73 @@ mod tests {
             Err(ParseHashError::Invalid)
         );
     }
+
+    #[test]
+    fn test_parse_detailed() {
+        extern crate std;
+        use std::string::ToString;
+
+        let hash = Hash::new_from_array([1; 32]);
+        let encoded = bs58::encode(hash).into_string();
+        assert_eq!(Hash::parse_detailed(&encoded), Ok(hash));
+
+        let last = encoded.len() - 1;
+        for (index, character) in [(0, '0'), (12, 'l'), (last, 'O'), (last, 'é')] {
+            let mut invalid = encoded.clone();
+            invalid.replace_range(index..index + 1, character.encode_utf8(&mut [0; 4]));
+            let error = Hash::parse_detailed(&invalid).unwrap_err();
+            assert_eq!(
+                error,
+                DetailedParseHashError::InvalidCharacter { index, character }
+            );
+            assert_eq!(invalid.parse::<Hash>(), Err(ParseHashError::Invalid));
+        }
+
+        let mut invalid = encoded.clone();
+        invalid.replace_range(12..13, "l");
+        invalid.replace_range(20..21, "I");
+        assert_eq!(
+            Hash::parse_detailed(&invalid).unwrap_err().to_string(),
+            "invalid base58 character 'l' at position 12"
+        );
+
+        assert_eq!(
+            Hash::parse_detailed(&encoded[..encoded.len() / 4]),
+            Err(DetailedParseHashError::WrongSize)
+        );
+    }
+
+    /// Checks `value` against the string constraints of `schema`.
+    fn assert_matches_string_schema(schema: &serde_json::Value, value: &serde_json::Value) {
+        assert_eq!(schema["type"], "string");
+        let value = value.as_str().unwrap();
+        let pattern = regex::Regex::new(schema["pattern"].as_str().unwrap()).unwrap();
+        assert!(pattern.is_match(value), "{value}");
+        let len = value.len() as u64;
+        assert!(schema["minLength"].as_u64().unwrap() <= len, "{value}");
+        assert!(len <= schema["maxLength"].as_u64().unwrap(), "{value}");
+    }
+
+    #[test]
+    fn test_json_schema() {
+        let schema = schemars::schema_for!(Hash);
+        let schema = schema.as_value();
+        assert_eq!(schema["type"], "string");
+        assert_eq!(schema["pattern"], "^[1-9A-HJ-NP-Za-km-z]{32,44}$");
+        assert_eq!(schema["minLength"], 32);
+        assert_eq!(schema["maxLength"], MAX_BASE58_LEN);
+
+        for example in schema["examples"].as_array().unwrap() {
+            assert_matches_string_schema(schema, example);
+            assert!(example.as_str().unwrap().parse::<Hash>().is_ok());
+        }
+        for hash in [
+            Hash::default(),
+            Hash::new_from_array([u8::MAX; HASH_BYTES]),
+            Hash::new_unique(),
+        ] {
+            let value = serde_json::to_value(std::string::ToString::to_string(&hash)).unwrap();
+            assert_matches_string_schema(schema, &value);
+        }
+    }
 }
@@ -0,0 +1,335 @@
+//! Allocation-free hex encoding and decoding.
+//!
+//! Bytes are encoded as lowercase hex, two characters per byte. Decoding
+//! accepts both lowercase and uppercase characters.
+//!
+//! [`HexDisplay`] formats bytes as hex without allocating, for `Debug` and
+//! `Display` implementations and log messages.
+#![no_std]
+#![cfg_attr(docsrs, feature(doc_cfg))]
+
+use core::fmt;
+
+const HEX_CHARS: &[u8; 16] = b"0123456789abcdef";
+
+/// Number of bytes encoded at once by [`HexDisplay`].
+const DISPLAY_CHUNK_LEN: usize = 32;
+
+/// Errors returned when encoding or decoding hex.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HexError {
+    /// The hex input has an odd number of characters.
+    OddLength,
+    /// The character at `index` of the hex input is not a hex digit.
+    InvalidCharacter { index: usize, character: u8 },
+    /// The output buffer is shorter than the `needed` bytes.
+    BufferTooSmall { needed: usize },
+}
+
+impl core::error::Error for HexError {}
+
+impl fmt::Display for HexError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::OddLength => f.write_str("hex input has an odd length"),
+            Self::InvalidCharacter { index, character } => write!(
+                f,
+                "invalid hex character {:?} at index {index}",
+                char::from(*character)
+            ),
+            Self::BufferTooSmall { needed } => {
+                write!(f, "output buffer too small, {needed} bytes needed")
+            }
+        }
+    }
+}
+
+/// Encode `src` as hex into `dst`, returning the number of bytes written.
+///
+/// # Errors
+///
+/// Returns [`HexError::BufferTooSmall`] if `dst` is shorter than twice the
+/// length of `src`; `dst` is left untouched.
+pub fn encode_to_slice(src: &[u8], dst: &mut [u8]) -> Result<usize, HexError> {
+    let needed = src.len().saturating_mul(2);
+    let dst = dst
+        .get_mut(..needed)
+        .ok_or(HexError::BufferTooSmall { needed })?;
+    for (byte, out) in src.iter().zip(dst.chunks_exact_mut(2)) {
+        out[0] = HEX_CHARS[usize::from(byte >> 4)];
+        out[1] = HEX_CHARS[usize::from(byte & 0x0f)];
+    }
+    Ok(needed)
+}
+
+/// Decode the hex characters of `src` into `dst`, returning the number of
+/// bytes written.
+///
+/// # Errors
+///
+/// Returns [`HexError::OddLength`] if `src` has an odd length,
+/// [`HexError::BufferTooSmall`] if `dst` is shorter than half the length of
+/// `src`, and [`HexError::InvalidCharacter`] if `src` contains a character
+/// other than a hex digit. The bytes of `dst` decoded before an invalid
+/// character is found are overwritten.
+pub fn decode_to_slice(src: &[u8], dst: &mut [u8]) -> Result<usize, HexError> {
+    #[allow(clippy::manual_is_multiple_of)]
+    if src.len() % 2 != 0 {
+        return Err(HexError::OddLength);
+    }
+    let needed = src.len() / 2;
+    let dst = dst
+        .get_mut(..needed)
+        .ok_or(HexError::BufferTooSmall { needed })?;
+    for (i, (pair, out)) in src.chunks_exact(2).zip(dst.iter_mut()).enumerate() {
+        *out = (decode_digit(pair[0], i * 2)? << 4) | decode_digit(pair[1], i * 2 + 1)?;
+    }
+    Ok(needed)
+}
+
+/// Encode the `N` bytes of `src` as `M` hex characters, where `M` must be
+/// `2 * N`.
+///
+/// Compile-time checked: using a mismatched `M` fails to build.
+///
+/// # Examples
+///
+/// ```
+/// let hex: [u8; 4] = solana_hex::encode_fixed(&[0xbe, 0xef]);
+/// assert_eq!(&hex, b"beef");
+/// ```
+pub const fn encode_fixed<const N: usize, const M: usize>(src: &[u8; N]) -> [u8; M] {
+    const {
+        assert!(
+            M == 2 * N,
+            "the output of encode_fixed must be twice the input length"
+        );
+    }
+    let mut out = [0; M];
+    let mut i = 0;
+    while i < N {
+        out[2 * i] = HEX_CHARS[(src[i] >> 4) as usize];
+        out[2 * i + 1] = HEX_CHARS[(src[i] & 0x0f) as usize];
+        i += 1;
+    }
+    out
+}
+
+fn decode_digit(character: u8, index: usize) -> Result<u8, HexError> {
+    match character {
+        b'0'..=b'9' => Ok(character - b'0'),
+        b'a'..=b'f' => Ok(character - b'a' + 10),
+        b'A'..=b'F' => Ok(character - b'A' + 10),
+        _ => Err(HexError::InvalidCharacter { index, character }),
+    }
+}
+
+/// Formats bytes as lowercase hex, for both `Display` and `Debug`.
+///
+/// By default the bytes are formatted back to back, without prefix.
+///
+/// # Examples
+///
+/// ```
+/// use solana_hex::HexDisplay;
+///
+/// let data = [0xde, 0xad, 0xbe, 0xef, 0x01];
+/// assert_eq!(HexDisplay::new(&data).to_string(), "deadbeef01");
+/// assert_eq!(HexDisplay::new(&data).with_prefix().to_string(), "0xdeadbeef01");
+/// assert_eq!(HexDisplay::new(&data).with_grouping(2).to_string(), "dead beef 01");
+/// ```
+#[derive(Clone, Copy)]
+pub struct HexDisplay<'a> {
+    bytes: &'a [u8],
+    prefix: bool,
+    group_len: Option<usize>,
+}
+
+impl<'a> HexDisplay<'a> {
+    pub const fn new(bytes: &'a [u8]) -> Self {
+        Self {
+            bytes,
+            prefix: false,
+            group_len: None,
+        }
+    }
+
+    /// Prefix the output with `0x`.
+    pub const fn with_prefix(mut self) -> Self {
+        self.prefix = true;
+        self
+    }
+
+    /// Separate groups of `group_len` bytes with a space. A `group_len` of
+    /// zero disables grouping.
+    pub const fn with_grouping(mut self, group_len: usize) -> Self {
+        self.group_len = if group_len == 0 {
+            None
+        } else {
+            Some(group_len)
+        };
+        self
+    }
+
+    fn write_hex(f: &mut fmt::Formatter, bytes: &[u8]) -> fmt::Result {
+        let mut buf = [0; 2 * DISPLAY_CHUNK_LEN];
+        for chunk in bytes.chunks(DISPLAY_CHUNK_LEN) {
+            let len = encode_to_slice(chunk, &mut buf).map_err(|_| fmt::Error)?;
+            // hex characters are ASCII
+            f.write_str(core::str::from_utf8(&buf[..len]).map_err(|_| fmt::Error)?)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for HexDisplay<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.prefix {
+            f.write_str("0x")?;
+        }
+        let Some(group_len) = self.group_len else {
+            return Self::write_hex(f, self.bytes);
+        };
+        for (i, group) in self.bytes.chunks(group_len).enumerate() {
+            if i > 0 {
+                f.write_str(" ")?;
+            }
+            Self::write_hex(f, group)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for HexDisplay<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use {
+        super::*,
+        std::{
+            format,
+            string::{String, ToString},
+            vec,
+        },
+    };
+
+    /// The formatting of the hex `Debug` wrapper of `solana-account-info`
+    /// this crate replaces.
+    fn legacy_hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let data: vec::Vec<u8> = (0..=255).collect();
+        let mut hex = vec![0; 512];
+        assert_eq!(encode_to_slice(&data, &mut hex), Ok(512));
+        assert_eq!(hex, legacy_hex(&data).into_bytes());
+
+        let mut decoded = vec![0; 256];
+        assert_eq!(decode_to_slice(&hex, &mut decoded), Ok(256));
+        assert_eq!(decoded, data);
+
+        let upper = hex.to_ascii_uppercase();
+        let mut decoded = vec![0; 256];
+        assert_eq!(decode_to_slice(&upper, &mut decoded), Ok(256));
+        assert_eq!(decoded, data);
+
+        assert_eq!(encode_to_slice(&[], &mut []), Ok(0));
+        assert_eq!(decode_to_slice(&[], &mut []), Ok(0));
+    }
+
+    #[test]
+    fn test_encode_fixed() {
+        let hex: [u8; 8] = encode_fixed(&[0x00, 0x1f, 0xa0, 0xff]);
+        assert_eq!(&hex, b"001fa0ff");
+        const HEX: [u8; 64] = encode_fixed(&[0xab; 32]);
+        assert_eq!(HEX, [b'a', b'b'].repeat(32).as_slice());
+        let hex: [u8; 0] = encode_fixed(&[]);
+        assert_eq!(hex, []);
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut out = [0; 4];
+        assert_eq!(decode_to_slice(b"abc", &mut out), Err(HexError::OddLength));
+        assert_eq!(
+            decode_to_slice(b"0g", &mut out),
+            Err(HexError::InvalidCharacter {
+                index: 1,
+                character: b'g'
+            })
+        );
+        assert_eq!(
+            decode_to_slice(b"00 1", &mut out),
+            Err(HexError::InvalidCharacter {
+                index: 2,
+                character: b' '
+            })
+        );
+        assert_eq!(
+            decode_to_slice(b"0x00", &mut out),
+            Err(HexError::InvalidCharacter {
+                index: 1,
+                character: b'x'
+            })
+        );
+        assert_eq!(
+            decode_to_slice(b"0011223344", &mut out),
+            Err(HexError::BufferTooSmall { needed: 5 })
+        );
+
+        let mut out = [0xff; 5];
+        assert_eq!(
+            encode_to_slice(&[1, 2, 3], &mut out),
+            Err(HexError::BufferTooSmall { needed: 6 })
+        );
+        assert_eq!(out, [0xff; 5]);
+        assert_eq!(
+            HexError::InvalidCharacter {
+                index: 3,
+                character: b'z'
+            }
+            .to_string(),
+            "invalid hex character 'z' at index 3"
+        );
+    }
+
+    #[test]
+    fn test_hex_display() {
+        for len in [0, 1, 31, 32, 33, 64, 100] {
+            let data: vec::Vec<u8> = (0..len).map(|i| (i * 37) as u8).collect();
+            let hex = legacy_hex(&data);
+            assert_eq!(HexDisplay::new(&data).to_string(), hex);
+            assert_eq!(format!("{:?}", HexDisplay::new(&data)), hex);
+            assert_eq!(
+                HexDisplay::new(&data).with_prefix().to_string(),
+                format!("0x{hex}")
+            );
+        }
+
+        let data = [0x01, 0x23, 0x45, 0x67, 0x89];
+        assert_eq!(
+            HexDisplay::new(&data).with_grouping(1).to_string(),
+            "01 23 45 67 89"
+        );
+        assert_eq!(
+            HexDisplay::new(&data)
+                .with_grouping(4)
+                .with_prefix()
+                .to_string(),
+            "0x01234567 89"
+        );
+        assert_eq!(
+            HexDisplay::new(&data).with_grouping(0).to_string(),
+            "0123456789"
+        );
+        assert_eq!(HexDisplay::new(&[]).with_grouping(2).to_string(), "");
+    }
+}
@@ -5,9 +5,17 @@
 use num_traits::ToPrimitive;
 #[cfg(feature = "frozen-abi")]
 extern crate std;
+#[macro_use]
+mod macros;
+mod parse;
+#[cfg(feature = "serde")]
+mod serde_compat;
+mod stable_hash;
+
 use {core::fmt, solana_program_error::ProgramError};
 pub use {
     instruction_error_module::*,
+    parse::ParseInstructionErrorError,
     solana_program_error::{
         ACCOUNT_ALREADY_INITIALIZED, ACCOUNT_BORROW_FAILED, ACCOUNT_DATA_TOO_SMALL,
         ACCOUNT_NOT_RENT_EXEMPT, ARITHMETIC_OVERFLOW, BORSH_IO_ERROR,
@@ -19,6 +27,7 @@ pub use {
         MISSING_REQUIRED_SIGNATURES, NOT_ENOUGH_ACCOUNT_KEYS, UNINITIALIZED_ACCOUNT,
         UNSUPPORTED_SYSVAR,
     },
+    stable_hash::TransactionErrorFingerprint,
 };
 
 #[allow(deprecated)]
@@ -33,11 +42,10 @@ mod instruction_error_module {
     /// an error be consistent across software versions.  For example, it is
     /// dangerous to include error strings from 3rd party crates because they could
     /// change at any time and changes to them are difficult to detect.
+    ///
+    /// With the `serde` feature, legacy JSON payloads are also accepted when
+    /// deserializing, see the `serde_compat` module.
     #[cfg_attr(feature = "frozen-abi", derive(AbiExample, AbiEnumVisitor))]
-    #[cfg_attr(
-        feature = "serde",
-        derive(serde_derive::Serialize, serde_derive::Deserialize)
-    )]
     #[cfg_attr(feature = "wincode", derive(wincode::SchemaWrite, wincode::SchemaRead))]
     #[derive(Debug, PartialEq, Eq, Clone)]
     pub enum InstructionError {
@@ -0,0 +1,68 @@
+/// Invokes `$callback!` with the variants of [`InstructionError`], in the
+/// order of their declaration, which is also the order of their bincode tags.
+///
+/// This is the one list of the variants used to derive the serde
+/// representation, the stable hashes and the parsing of the error. Variants
+/// carrying data are followed by the type of their payload.
+///
+/// [`InstructionError`]: crate::InstructionError
+macro_rules! with_variants {
+    ($callback:ident) => {
+        $callback! {
+            GenericError,
+            InvalidArgument,
+            InvalidInstructionData,
+            InvalidAccountData,
+            AccountDataTooSmall,
+            InsufficientFunds,
+            IncorrectProgramId,
+            MissingRequiredSignature,
+            AccountAlreadyInitialized,
+            UninitializedAccount,
+            UnbalancedInstruction,
+            ModifiedProgramId,
+            ExternalAccountLamportSpend,
+            ExternalAccountDataModified,
+            ReadonlyLamportChange,
+            ReadonlyDataModified,
+            DuplicateAccountIndex,
+            ExecutableModified,
+            RentEpochModified,
+            NotEnoughAccountKeys,
+            AccountDataSizeChanged,
+            AccountNotExecutable,
+            AccountBorrowFailed,
+            AccountBorrowOutstanding,
+            DuplicateAccountOutOfSync,
+            Custom(u32),
+            InvalidError,
+            ExecutableDataModified,
+            ExecutableLamportChange,
+            ExecutableAccountNotRentExempt,
+            UnsupportedProgramId,
+            CallDepth,
+            MissingAccount,
+            ReentrancyNotAllowed,
+            MaxSeedLengthExceeded,
+            InvalidSeeds,
+            InvalidRealloc,
+            ComputationalBudgetExceeded,
+            PrivilegeEscalation,
+            ProgramEnvironmentSetupFailure,
+            ProgramFailedToComplete,
+            ProgramFailedToCompile,
+            Immutable,
+            IncorrectAuthority,
+            BorshIoError,
+            AccountNotRentExempt,
+            InvalidAccountOwner,
+            ArithmeticOverflow,
+            UnsupportedSysvar,
+            IllegalOwner,
+            MaxAccountsDataAllocationsExceeded,
+            MaxAccountsExceeded,
+            MaxInstructionTraceLengthExceeded,
+            BuiltinProgramsMustConsumeComputeUnits,
+        }
+    };
+}
@@ -0,0 +1,154 @@
+//! Parsing of [`InstructionError`] from its `Display` output.
+
+use {
+    crate::InstructionError,
+    core::{
+        fmt::{self, Write},
+        str::FromStr,
+    },
+};
+
+/// The `Display` prefix of [`InstructionError::Custom`], followed by the code
+/// in hex.
+const CUSTOM_PREFIX: &str = "custom program error: ";
+
+macro_rules! variants {
+    ($($variant:ident $(($payload:ty))?),* $(,)?) => {
+        &[$(InstructionError::$variant $((<$payload>::MIN))?),*]
+    };
+}
+
+/// Every variant of [`InstructionError`], in the order of declaration, with
+/// zero payloads.
+#[allow(deprecated)]
+pub(crate) const VARIANTS: &[InstructionError] = with_variants!(variants);
+
+/// The string is not the `Display` output of an [`InstructionError`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ParseInstructionErrorError;
+
+impl core::error::Error for ParseInstructionErrorError {}
+
+impl fmt::Display for ParseInstructionErrorError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("unrecognized instruction error")
+    }
+}
+
+/// Parses the `Display` output of an [`InstructionError`], e.g. from
+/// transaction logs.
+///
+/// The hex code of [`InstructionError::Custom`] may use either case, as may
+/// its `0x` prefix.
+///
+/// Unlike [`ProgramError`](solana_program_error::ProgramError), there is no
+/// `TryFrom<&str>` implementation, which would conflict with the conversion
+/// from primitives of the `num-traits` feature.
+///
+/// # Examples
+///
+/// ```
+/// use solana_instruction_error::InstructionError;
+///
+/// assert_eq!(
+///     "custom program error: 0x1F".parse(),
+///     Ok(InstructionError::Custom(0x1f))
+/// );
+/// let error = InstructionError::InvalidSeeds;
+/// assert_eq!(error.to_string().parse(), Ok(error));
+/// ```
+impl FromStr for InstructionError {
+    type Err = ParseInstructionErrorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(code) = s.strip_prefix(CUSTOM_PREFIX) {
+            return parse_hex_code(code)
+                .map(InstructionError::Custom)
+                .ok_or(ParseInstructionErrorError);
+        }
+        VARIANTS
+            .iter()
+            .find(|error| displays_as(error, s))
+            .cloned()
+            .ok_or(ParseInstructionErrorError)
+    }
+}
+
+/// Parses the `{:#x}` formatting of a `u32`, with a `0x` or `0X` prefix.
+fn parse_hex_code(s: &str) -> Option<u32> {
+    let digits = s.strip_prefix("0x").or_else(|| s.strip_prefix("0X"))?;
+    // `from_str_radix` accepts a leading `+`
+    if !digits.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+        return None;
+    }
+    u32::from_str_radix(digits, 16).ok()
+}
+
+/// Returns true if `value` is displayed as `s`, without allocating.
+fn displays_as(value: &impl fmt::Display, s: &str) -> bool {
+    /// Consumes the expected string as the output is written, failing on
+    /// the first mismatch.
+    struct Matcher<'a>(&'a str);
+
+    impl Write for Matcher<'_> {
+        fn write_str(&mut self, s: &str) -> fmt::Result {
+            self.0 = self.0.strip_prefix(s).ok_or(fmt::Error)?;
+            Ok(())
+        }
+    }
+
+    let mut matcher = Matcher(s);
+    write!(matcher, "{value}").is_ok() && matcher.0.is_empty()
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use {super::*, std::string::ToString};
+
+    #[test]
+    fn test_round_trip() {
+        for error in VARIANTS {
+            assert_eq!(error.to_string().parse(), Ok(error.clone()));
+        }
+        for code in [0, 1, 0xab, 0x1000_0000, u32::MAX] {
+            let error = InstructionError::Custom(code);
+            assert_eq!(error.to_string().parse(), Ok(error));
+        }
+    }
+
+    #[test]
+    fn test_custom_case() {
+        for s in [
+            "custom program error: 0xabcdef",
+            "custom program error: 0xABCDEF",
+            "custom program error: 0XAbCdEf",
+            "custom program error: 0x00abcdef",
+        ] {
+            assert_eq!(s.parse(), Ok(InstructionError::Custom(0xabcdef)));
+        }
+    }
+
+    #[test]
+    fn test_unrecognized() {
+        for s in [
+            "",
+            "custom program error: ",
+            "custom program error: 0x",
+            "custom program error: 0x+1",
+            "custom program error: 1",
+            "custom program error: 0x100000000",
+            "Custom program error: 0x1",
+            "invalid program argument ",
+            "invalid program",
+            "Invalid program argument",
+            "InvalidArgument",
+        ] {
+            assert_eq!(
+                s.parse::<InstructionError>(),
+                Err(ParseInstructionErrorError)
+            );
+        }
+    }
+}
@@ -0,0 +1,218 @@
+//! Serde support for [`InstructionError`].
+//!
+//! Binary formats use the derived representation of [`InstructionErrorDef`],
+//! generated from the list of variants in `macros.rs`.
+//! Self-describing formats additionally accept legacy payload shapes found in
+//! historical RPC responses:
+//!
+//! - `BorshIoError` used to carry an error message, as in
+//!   `{"BorshIoError": "Unknown"}`. The message is discarded.
+//!
+//! Only the current shapes are ever serialized.
+
+#![allow(deprecated)]
+
+use {
+    crate::InstructionError,
+    core::fmt,
+    serde::{
+        de::{self, IgnoredAny, IntoDeserializer, MapAccess, Visitor},
+        Deserialize, Deserializer, Serialize, Serializer,
+    },
+};
+
+macro_rules! instruction_error_def {
+    ($($variant:ident $(($payload:ty))?),* $(,)?) => {
+        /// Mirror of [`InstructionError`] deriving its serde representation.
+        ///
+        /// The remote `Serialize` derive matches exhaustively on
+        /// [`InstructionError`], so a variant missing here fails to compile.
+        #[derive(serde_derive::Serialize, serde_derive::Deserialize)]
+        #[serde(remote = "InstructionError", rename = "InstructionError")]
+        enum InstructionErrorDef {
+            $($variant $(($payload))?),*
+        }
+    };
+}
+
+with_variants!(instruction_error_def);
+
+impl Serialize for InstructionError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        InstructionErrorDef::serialize(self, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for InstructionError {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_any(CompatVisitor)
+        } else {
+            InstructionErrorDef::deserialize(deserializer)
+        }
+    }
+}
+
+/// Deserializes unit variants from their name, and data carrying variants
+/// from single entry maps, accepting legacy payloads.
+struct CompatVisitor;
+
+impl<'de> Visitor<'de> for CompatVisitor {
+    type Value = InstructionError;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("an InstructionError variant")
+    }
+
+    fn visit_str<E: de::Error>(self, name: &str) -> Result<Self::Value, E> {
+        InstructionErrorDef::deserialize(name.into_deserializer())
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let error = match map.next_key()? {
+            Some(VariantKey::Custom) => InstructionError::Custom(map.next_value()?),
+            Some(VariantKey::BorshIoError) => {
+                // legacy payloads carry an error message
+                map.next_value::<IgnoredAny>()?;
+                InstructionError::BorshIoError
+            }
+            Some(VariantKey::Unit(error)) => {
+                map.next_value::<()>()?;
+                error
+            }
+            None => return Err(de::Error::invalid_length(0, &self)),
+        };
+        if map.next_key::<IgnoredAny>()?.is_some() {
+            return Err(de::Error::invalid_length(2, &self));
+        }
+        Ok(error)
+    }
+}
+
+/// The key of a single entry map holding a variant.
+enum VariantKey {
+    Custom,
+    BorshIoError,
+    Unit(InstructionError),
+}
+
+impl<'de> Deserialize<'de> for VariantKey {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct KeyVisitor;
+
+        impl Visitor<'_> for KeyVisitor {
+            type Value = VariantKey;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("an InstructionError variant name")
+            }
+
+            fn visit_str<E: de::Error>(self, name: &str) -> Result<Self::Value, E> {
+                match name {
+                    "Custom" => Ok(VariantKey::Custom),
+                    "BorshIoError" => Ok(VariantKey::BorshIoError),
+                    name => CompatVisitor.visit_str(name).map(VariantKey::Unit),
+                }
+            }
+        }
+
+        deserializer.deserialize_str(KeyVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_current_form() {
+        for (json, error) in [
+            (r#""GenericError""#, InstructionError::GenericError),
+            (r#""InvalidArgument""#, InstructionError::InvalidArgument),
+            (r#""BorshIoError""#, InstructionError::BorshIoError),
+            (
+                r#""BuiltinProgramsMustConsumeComputeUnits""#,
+                InstructionError::BuiltinProgramsMustConsumeComputeUnits,
+            ),
+            (r#"{"Custom":6001}"#, InstructionError::Custom(6001)),
+        ] {
+            assert_eq!(serde_json::to_string(&error).unwrap(), json);
+            assert_eq!(
+                serde_json::from_str::<InstructionError>(json).unwrap(),
+                error
+            );
+        }
+    }
+
+    #[test]
+    fn test_json_legacy_form() {
+        // as found in historical transaction status metadata
+        for (json, error) in [
+            (
+                r#"{"BorshIoError":"Unknown"}"#,
+                InstructionError::BorshIoError,
+            ),
+            (
+                r#"{"BorshIoError":"Unexpected length of input"}"#,
+                InstructionError::BorshIoError,
+            ),
+            (r#"{"BorshIoError":null}"#, InstructionError::BorshIoError),
+            (r#"{"GenericError":null}"#, InstructionError::GenericError),
+            (
+                r#"{"InvalidArgument":null}"#,
+                InstructionError::InvalidArgument,
+            ),
+        ] {
+            assert_eq!(
+                serde_json::from_str::<InstructionError>(json).unwrap(),
+                error
+            );
+        }
+        // only the current form is serialized
+        assert_eq!(
+            serde_json::to_string(&InstructionError::BorshIoError).unwrap(),
+            r#""BorshIoError""#
+        );
+    }
+
+    #[test]
+    fn test_json_invalid() {
+        for json in [
+            r#""NotAnError""#,
+            r#""Custom""#,
+            r#"{"Custom":"6001"}"#,
+            r#"{"InvalidArgument":1}"#,
+            r#"{"NotAnError":null}"#,
+            r#"{}"#,
+            r#"{"Custom":1,"GenericError":null}"#,
+            "42",
+        ] {
+            assert!(
+                serde_json::from_str::<InstructionError>(json).is_err(),
+                "{json}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_bincode_layout() {
+        // variant indexes must not change
+        let cases: [(InstructionError, &[u8]); 4] = [
+            (InstructionError::GenericError, &[0, 0, 0, 0]),
+            (InstructionError::Custom(1), &[25, 0, 0, 0, 1, 0, 0, 0]),
+            (InstructionError::BorshIoError, &[44, 0, 0, 0]),
+            (
+                InstructionError::BuiltinProgramsMustConsumeComputeUnits,
+                &[53, 0, 0, 0],
+            ),
+        ];
+        for (error, bytes) in cases {
+            assert_eq!(bincode::serialize(&error).unwrap(), bytes);
+            assert_eq!(
+                bincode::deserialize::<InstructionError>(bytes).unwrap(),
+                error
+            );
+        }
+        assert!(bincode::deserialize::<InstructionError>(&[54, 0, 0, 0]).is_err());
+    }
+}
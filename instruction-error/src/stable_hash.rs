@@ -0,0 +1,284 @@
+//! Stable hashing of [`InstructionError`], to bucket errors in analytics.
+
+use crate::InstructionError;
+
+macro_rules! variant_index {
+    ($($variant:ident $(($payload:ty))?),* $(,)?) => {
+        /// The index of the variant in the declaration of the enum.
+        ///
+        /// Reordering the variants breaks the golden tests, rather than
+        /// silently changing the hashes.
+        #[allow(deprecated)]
+        const fn variant_index(&self) -> u32 {
+            enum Tag {
+                $($variant),*
+            }
+            match self {
+                $(InstructionError::$variant { .. } => Tag::$variant as u32),*
+            }
+        }
+    };
+}
+
+/// Mixed into the hashed code, so that instruction error hashes differ from
+/// the hashes of other error types with the same code.
+const DOMAIN: u64 = 0x696e_7374_6572_7221; // "insterr!"
+
+/// Mixed into [`TransactionErrorFingerprint`]s.
+const FINGERPRINT_DOMAIN: u64 = 0x7478_6572_726f_7221; // "txerror!"
+
+impl InstructionError {
+    /// A 64-bit fingerprint of the error, stable across versions.
+    ///
+    /// The hash is computed from the index of the variant, which matches its
+    /// bincode tag, and the code of [`InstructionError::Custom`] errors. It
+    /// does not depend on the `Debug` or serde representations, and never
+    /// changes for existing variants. Distinct errors have distinct hashes.
+    pub fn stable_hash(&self) -> u64 {
+        let payload = match self {
+            InstructionError::Custom(code) => u64::from(*code),
+            _ => 0,
+        };
+        mix(((u64::from(self.variant_index()) << 32) | payload) ^ DOMAIN)
+    }
+
+    with_variants!(variant_index);
+}
+
+/// A stable fingerprint of the error of a failed transaction, combining the
+/// index of the failing instruction with [`InstructionError::stable_hash`].
+///
+/// Equal fingerprints identify the same instruction index and error, so they
+/// can be used to bucket transaction errors across versions.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TransactionErrorFingerprint(u64);
+
+impl TransactionErrorFingerprint {
+    /// The fingerprint of `error` returned by the instruction at
+    /// `instruction_index`.
+    pub fn new(instruction_index: u8, error: &InstructionError) -> Self {
+        let hash = error.stable_hash() ^ FINGERPRINT_DOMAIN;
+        Self(mix(hash.rotate_left(8) ^ u64::from(instruction_index)))
+    }
+
+    /// The fingerprint as an integer.
+    pub const fn get(self) -> u64 {
+        self.0
+    }
+}
+
+/// The splitmix64 finalizer, a bijection on `u64`.
+const fn mix(mut x: u64) -> u64 {
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    x ^ (x >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use {super::*, std::vec::Vec};
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_stable_hash_golden() {
+        for (error, hash) in [
+            (InstructionError::GenericError, 0xa165_cb4e_d19d_33c7),
+            (InstructionError::InvalidArgument, 0x6bfa_0cd0_0c25_c8f0),
+            (
+                InstructionError::InvalidInstructionData,
+                0x85f8_30da_85ae_6638,
+            ),
+            (InstructionError::InvalidAccountData, 0x6d95_1f28_9b2b_5e98),
+            (InstructionError::AccountDataTooSmall, 0x9aaf_c277_c9b5_37d8),
+            (InstructionError::InsufficientFunds, 0xfb95_6ba3_b28c_c9a7),
+            (InstructionError::IncorrectProgramId, 0x5d5a_66fc_b8b9_15c4),
+            (
+                InstructionError::MissingRequiredSignature,
+                0xd796_a40d_71fa_909b,
+            ),
+            (
+                InstructionError::AccountAlreadyInitialized,
+                0x3098_20dc_4f0f_34bf,
+            ),
+            (
+                InstructionError::UninitializedAccount,
+                0x28fa_3d31_4062_084f,
+            ),
+            (
+                InstructionError::UnbalancedInstruction,
+                0x7616_a350_bad8_ae5e,
+            ),
+            (InstructionError::ModifiedProgramId, 0xd6ff_d0c2_4048_b1e3),
+            (
+                InstructionError::ExternalAccountLamportSpend,
+                0x6d70_505e_cb92_9125,
+            ),
+            (
+                InstructionError::ExternalAccountDataModified,
+                0xd9be_3ed4_2e6a_9f21,
+            ),
+            (
+                InstructionError::ReadonlyLamportChange,
+                0x38e3_3326_0452_ab2c,
+            ),
+            (
+                InstructionError::ReadonlyDataModified,
+                0xb9dd_37ef_f618_d323,
+            ),
+            (
+                InstructionError::DuplicateAccountIndex,
+                0x29c6_bd7a_db6b_393e,
+            ),
+            (InstructionError::ExecutableModified, 0xc695_5aa6_3a39_1d13),
+            (InstructionError::RentEpochModified, 0x8d43_2a3c_93ae_f549),
+            (
+                InstructionError::NotEnoughAccountKeys,
+                0x2b76_0eaf_b2f9_a634,
+            ),
+            (
+                InstructionError::AccountDataSizeChanged,
+                0x13f4_2319_06d2_861a,
+            ),
+            (
+                InstructionError::AccountNotExecutable,
+                0xc59f_3678_4f51_e7e5,
+            ),
+            (InstructionError::AccountBorrowFailed, 0xa212_54f8_a8ae_7187),
+            (
+                InstructionError::AccountBorrowOutstanding,
+                0xab3d_3560_406d_20a7,
+            ),
+            (
+                InstructionError::DuplicateAccountOutOfSync,
+                0xfc52_8751_4517_517b,
+            ),
+            (InstructionError::Custom(0), 0x6994_4150_7561_c689),
+            (InstructionError::Custom(1), 0xbb5f_78b5_577a_8e77),
+            (InstructionError::Custom(u32::MAX), 0xbba1_9884_1c1c_106e),
+            (InstructionError::InvalidError, 0x2dbc_d1fe_ff54_b65c),
+            (
+                InstructionError::ExecutableDataModified,
+                0x6b45_21b7_d963_5569,
+            ),
+            (
+                InstructionError::ExecutableLamportChange,
+                0xa9db_764d_3c3a_9fd4,
+            ),
+            (
+                InstructionError::ExecutableAccountNotRentExempt,
+                0x4507_0e11_743e_af31,
+            ),
+            (
+                InstructionError::UnsupportedProgramId,
+                0xe049_d8f9_5549_7c32,
+            ),
+            (InstructionError::CallDepth, 0x8f1b_ef63_a9c5_7704),
+            (InstructionError::MissingAccount, 0xa180_d5cc_d918_2205),
+            (
+                InstructionError::ReentrancyNotAllowed,
+                0xa2bf_990e_08fe_e131,
+            ),
+            (
+                InstructionError::MaxSeedLengthExceeded,
+                0x0575_e614_0c5a_bd7d,
+            ),
+            (InstructionError::InvalidSeeds, 0xd42b_8557_03f7_314e),
+            (InstructionError::InvalidRealloc, 0x0140_2619_cfc6_1703),
+            (
+                InstructionError::ComputationalBudgetExceeded,
+                0xde62_4f01_9010_ffc9,
+            ),
+            (InstructionError::PrivilegeEscalation, 0x1f79_d72a_4df6_38e3),
+            (
+                InstructionError::ProgramEnvironmentSetupFailure,
+                0x2798_0f2b_94b9_96c7,
+            ),
+            (
+                InstructionError::ProgramFailedToComplete,
+                0xca50_3942_8eca_508b,
+            ),
+            (
+                InstructionError::ProgramFailedToCompile,
+                0xba97_a507_64e8_6b97,
+            ),
+            (InstructionError::Immutable, 0x766e_e408_058c_3708),
+            (InstructionError::IncorrectAuthority, 0xbdc8_b8e0_817f_5969),
+            (InstructionError::BorshIoError, 0x18c7_4a51_51a4_895d),
+            (
+                InstructionError::AccountNotRentExempt,
+                0xd173_1153_68c1_5aa0,
+            ),
+            (InstructionError::InvalidAccountOwner, 0xd201_2034_4d44_30ad),
+            (InstructionError::ArithmeticOverflow, 0x2ee6_d634_b08b_76e5),
+            (InstructionError::UnsupportedSysvar, 0x6f39_8a51_27af_0432),
+            (InstructionError::IllegalOwner, 0x571e_f65f_221e_efc8),
+            (
+                InstructionError::MaxAccountsDataAllocationsExceeded,
+                0x3208_4bee_2c58_ad14,
+            ),
+            (InstructionError::MaxAccountsExceeded, 0x8749_54ad_2669_459e),
+            (
+                InstructionError::MaxInstructionTraceLengthExceeded,
+                0xf6de_aa71_b1ed_3d43,
+            ),
+            (
+                InstructionError::BuiltinProgramsMustConsumeComputeUnits,
+                0xc39e_fbd9_937a_3164,
+            ),
+        ] {
+            assert_eq!(error.stable_hash(), hash, "{error:?}");
+        }
+    }
+
+    #[test]
+    fn test_variant_index_matches_bincode_tag() {
+        for error in crate::parse::VARIANTS {
+            let tag = bincode::serialize(&error).unwrap();
+            assert_eq!(tag[..4], error.variant_index().to_le_bytes(), "{error:?}");
+        }
+    }
+
+    #[test]
+    fn test_stable_hash_collisions() {
+        let mut hashes = crate::parse::VARIANTS
+            .iter()
+            .cloned()
+            .chain((1..4096).map(InstructionError::Custom))
+            .chain((12..32).map(|shift| InstructionError::Custom(1 << shift)))
+            .map(|error| error.stable_hash())
+            .collect::<Vec<_>>();
+        let len = hashes.len();
+        hashes.sort_unstable();
+        hashes.dedup();
+        assert_eq!(hashes.len(), len);
+    }
+
+    #[test]
+    fn test_fingerprint() {
+        let error = InstructionError::Custom(3);
+        assert_eq!(
+            TransactionErrorFingerprint::new(1, &error),
+            TransactionErrorFingerprint::new(1, &error.clone())
+        );
+        assert_eq!(
+            TransactionErrorFingerprint::new(2, &InstructionError::InvalidSeeds).get(),
+            0xa275_ec3b_b4aa_40de
+        );
+
+        let mut fingerprints = (0..=u8::MAX)
+            .flat_map(|index| {
+                crate::parse::VARIANTS
+                    .iter()
+                    .cloned()
+                    .chain((1..16).map(InstructionError::Custom))
+                    .map(move |error| TransactionErrorFingerprint::new(index, &error))
+            })
+            .collect::<Vec<_>>();
+        let len = fingerprints.len();
+        fingerprints.sort_unstable();
+        fingerprints.dedup();
+        assert_eq!(fingerprints.len(), len);
+    }
+}
@@ -0,0 +1,325 @@
+//! Self-describing envelope for compressed instruction data.
+//!
+//! Instruction data that does not fit in a transaction may fit once
+//! compressed. An envelope prefixes the payload with a header identifying
+//! the compression method, so that clients and programs agree on how to
+//! recover the original data:
+//!
+//! | Size | Field                                          |
+//! |------|------------------------------------------------|
+//! | 4    | [`ENVELOPE_MAGIC`]                             |
+//! | 1    | compression method, see [`CompressionMethod`]  |
+//! | 4    | length of the raw data, as a little-endian u32 |
+//! | ...  | payload                                        |
+//!
+//! Decompression allocates the inflate state, about 11 KiB, and the output on
+//! the heap, so that it fits within the default program heap for outputs of a
+//! few kilobytes.
+
+use {
+    crate::{AccountMeta, Instruction},
+    alloc::vec::Vec,
+    core::fmt,
+    solana_pubkey::Pubkey,
+};
+
+/// The first bytes of every envelope.
+pub const ENVELOPE_MAGIC: [u8; 4] = *b"SIXZ";
+
+/// The length of the envelope header.
+pub const ENVELOPE_HEADER_LEN: usize = 9;
+
+/// The compression level of deflate streams, favoring size over speed.
+const DEFLATE_LEVEL: u8 = 9;
+
+/// Compression method of the payload of an envelope.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CompressionMethod {
+    /// The payload is the raw data.
+    None = 0,
+    /// The payload is a raw deflate stream, without zlib header.
+    Deflate = 1,
+}
+
+impl TryFrom<u8> for CompressionMethod {
+    type Error = EnvelopeError;
+
+    fn try_from(method: u8) -> Result<Self, Self::Error> {
+        match method {
+            0 => Ok(Self::None),
+            1 => Ok(Self::Deflate),
+            _ => Err(EnvelopeError::UnknownMethod(method)),
+        }
+    }
+}
+
+/// Errors returned by [`decompress_instruction_data`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EnvelopeError {
+    /// The data is shorter than the envelope header.
+    TooShort,
+    /// The data does not start with [`ENVELOPE_MAGIC`].
+    InvalidMagic,
+    /// The compression method is not known.
+    UnknownMethod(u8),
+    /// The raw data is larger than the maximum length allowed.
+    TooLarge { len: usize, max_len: usize },
+    /// The payload does not decompress into the raw length of the header.
+    LengthMismatch,
+    /// The payload is not a valid compressed stream.
+    CorruptStream,
+}
+
+impl core::error::Error for EnvelopeError {}
+
+impl fmt::Display for EnvelopeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::TooShort => f.write_str("data is shorter than the envelope header"),
+            Self::InvalidMagic => f.write_str("invalid envelope magic"),
+            Self::UnknownMethod(method) => write!(f, "unknown compression method {method}"),
+            Self::TooLarge { len, max_len } => write!(
+                f,
+                "raw data length {len} exceeds the maximum length {max_len}"
+            ),
+            Self::LengthMismatch => {
+                f.write_str("payload length does not match the envelope header")
+            }
+            Self::CorruptStream => f.write_str("corrupt compressed stream"),
+        }
+    }
+}
+
+/// Wrap `data` into an envelope, compressed with `method`.
+///
+/// # Panics
+///
+/// Panics if `data` is longer than `u32::MAX` bytes.
+pub fn compress_instruction_data(data: &[u8], method: CompressionMethod) -> Vec<u8> {
+    let len = u32::try_from(data.len()).expect("instruction data longer than u32::MAX");
+    let payload = match method {
+        CompressionMethod::None => None,
+        CompressionMethod::Deflate => {
+            Some(miniz_oxide::deflate::compress_to_vec(data, DEFLATE_LEVEL))
+        }
+    };
+    let payload = payload.as_deref().unwrap_or(data);
+
+    let mut envelope = Vec::with_capacity(ENVELOPE_HEADER_LEN + payload.len());
+    envelope.extend_from_slice(&ENVELOPE_MAGIC);
+    envelope.push(method as u8);
+    envelope.extend_from_slice(&len.to_le_bytes());
+    envelope.extend_from_slice(payload);
+    envelope
+}
+
+/// Recover the raw data of an envelope.
+///
+/// The raw length of the header is checked against `max_len` before any
+/// allocation, and decompression stops as soon as the output would exceed
+/// it, so that a small envelope cannot exhaust the heap.
+pub fn decompress_instruction_data(data: &[u8], max_len: usize) -> Result<Vec<u8>, EnvelopeError> {
+    let (header, payload) = data
+        .split_at_checked(ENVELOPE_HEADER_LEN)
+        .ok_or(EnvelopeError::TooShort)?;
+    if header[..4] != ENVELOPE_MAGIC {
+        return Err(EnvelopeError::InvalidMagic);
+    }
+    let method = CompressionMethod::try_from(header[4])?;
+    let len = u32::from_le_bytes(header[5..].try_into().unwrap()) as usize;
+    if len > max_len {
+        return Err(EnvelopeError::TooLarge { len, max_len });
+    }
+
+    let raw = match method {
+        CompressionMethod::None => payload.to_vec(),
+        CompressionMethod::Deflate => {
+            miniz_oxide::inflate::decompress_to_vec_with_limit(payload, len).map_err(|err| {
+                match err.status {
+                    miniz_oxide::inflate::TINFLStatus::HasMoreOutput => {
+                        EnvelopeError::LengthMismatch
+                    }
+                    _ => EnvelopeError::CorruptStream,
+                }
+            })?
+        }
+    };
+    if raw.len() != len {
+        return Err(EnvelopeError::LengthMismatch);
+    }
+    Ok(raw)
+}
+
+impl Instruction {
+    /// Create a new instruction with `payload` wrapped into an envelope.
+    ///
+    /// The payload is deflated, unless that makes it larger, in which case
+    /// it is stored as is. Programs recover the payload with
+    /// [`decompress_instruction_data`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `payload` is longer than `u32::MAX` bytes.
+    pub fn new_with_compressed_payload(
+        program_id: Pubkey,
+        accounts: Vec<AccountMeta>,
+        payload: &[u8],
+    ) -> Self {
+        let deflated = compress_instruction_data(payload, CompressionMethod::Deflate);
+        let data = if deflated.len() < ENVELOPE_HEADER_LEN + payload.len() {
+            deflated
+        } else {
+            compress_instruction_data(payload, CompressionMethod::None)
+        };
+        Self {
+            program_id,
+            accounts,
+            data,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, alloc::vec};
+
+    fn governance_payload() -> Vec<u8> {
+        // repetitive, like serialized proposals
+        (0..4_000u32).flat_map(|i| (i % 50).to_le_bytes()).collect()
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let payload = governance_payload();
+        for method in [CompressionMethod::None, CompressionMethod::Deflate] {
+            let envelope = compress_instruction_data(&payload, method);
+            assert_eq!(envelope[..4], ENVELOPE_MAGIC);
+            assert_eq!(envelope[4], method as u8);
+            assert_eq!(envelope[5..9], (payload.len() as u32).to_le_bytes());
+            assert_eq!(
+                decompress_instruction_data(&envelope, payload.len()),
+                Ok(payload.clone())
+            );
+
+            let empty = compress_instruction_data(&[], method);
+            assert_eq!(decompress_instruction_data(&empty, 0), Ok(vec![]));
+        }
+        let deflated = compress_instruction_data(&payload, CompressionMethod::Deflate);
+        assert!(deflated.len() < payload.len() / 10);
+    }
+
+    #[test]
+    fn test_none_is_passthrough() {
+        let envelope = compress_instruction_data(b"hello", CompressionMethod::None);
+        assert_eq!(envelope, b"SIXZ\x00\x05\x00\x00\x00hello");
+        assert_eq!(
+            decompress_instruction_data(&envelope, 5),
+            Ok(b"hello".to_vec())
+        );
+    }
+
+    #[test]
+    fn test_max_len() {
+        let payload = governance_payload();
+        for method in [CompressionMethod::None, CompressionMethod::Deflate] {
+            let envelope = compress_instruction_data(&payload, method);
+            assert_eq!(
+                decompress_instruction_data(&envelope, payload.len() - 1),
+                Err(EnvelopeError::TooLarge {
+                    len: payload.len(),
+                    max_len: payload.len() - 1,
+                })
+            );
+        }
+
+        // a header understating the raw length cannot inflate past it
+        let mut envelope = compress_instruction_data(&payload, CompressionMethod::Deflate);
+        envelope[5..9].copy_from_slice(&100u32.to_le_bytes());
+        assert_eq!(
+            decompress_instruction_data(&envelope, payload.len()),
+            Err(EnvelopeError::LengthMismatch)
+        );
+        let mut envelope = compress_instruction_data(&payload, CompressionMethod::None);
+        envelope[5..9].copy_from_slice(&100u32.to_le_bytes());
+        assert_eq!(
+            decompress_instruction_data(&envelope, payload.len()),
+            Err(EnvelopeError::LengthMismatch)
+        );
+    }
+
+    #[test]
+    fn test_corrupted_header() {
+        let envelope = compress_instruction_data(b"hello", CompressionMethod::Deflate);
+        for len in 0..ENVELOPE_HEADER_LEN {
+            assert_eq!(
+                decompress_instruction_data(&envelope[..len], 100),
+                Err(EnvelopeError::TooShort)
+            );
+        }
+
+        let mut corrupted = envelope.clone();
+        corrupted[0] ^= 1;
+        assert_eq!(
+            decompress_instruction_data(&corrupted, 100),
+            Err(EnvelopeError::InvalidMagic)
+        );
+
+        let mut corrupted = envelope.clone();
+        corrupted[4] = 2;
+        assert_eq!(
+            decompress_instruction_data(&corrupted, 100),
+            Err(EnvelopeError::UnknownMethod(2))
+        );
+        corrupted[4] = 0xff;
+        assert_eq!(
+            decompress_instruction_data(&corrupted, 100),
+            Err(EnvelopeError::UnknownMethod(0xff))
+        );
+    }
+
+    #[test]
+    fn test_corrupted_stream() {
+        let payload = governance_payload();
+        let envelope = compress_instruction_data(&payload, CompressionMethod::Deflate);
+
+        let truncated = &envelope[..envelope.len() / 2];
+        assert!(decompress_instruction_data(truncated, payload.len()).is_err());
+
+        let mut garbage = envelope[..ENVELOPE_HEADER_LEN].to_vec();
+        garbage.extend_from_slice(&[0xff; 64]);
+        assert_eq!(
+            decompress_instruction_data(&garbage, payload.len()),
+            Err(EnvelopeError::CorruptStream)
+        );
+
+        // flipping any payload bit never panics nor overruns the raw length
+        for i in ENVELOPE_HEADER_LEN..envelope.len() {
+            let mut corrupted = envelope.clone();
+            corrupted[i] ^= 0x10;
+            if let Ok(raw) = decompress_instruction_data(&corrupted, payload.len()) {
+                assert_eq!(raw.len(), payload.len());
+            }
+        }
+    }
+
+    #[test]
+    fn test_new_with_compressed_payload() {
+        let program_id = Pubkey::new_unique();
+        let accounts = vec![AccountMeta::new(Pubkey::new_unique(), true)];
+        let payload = governance_payload();
+        let instruction =
+            Instruction::new_with_compressed_payload(program_id, accounts.clone(), &payload);
+        assert_eq!(instruction.program_id, program_id);
+        assert_eq!(instruction.accounts, accounts);
+        assert_eq!(instruction.data[4], CompressionMethod::Deflate as u8);
+        assert_eq!(
+            decompress_instruction_data(&instruction.data, payload.len()),
+            Ok(payload)
+        );
+
+        // incompressible payloads are stored as is
+        let instruction = Instruction::new_with_compressed_payload(program_id, vec![], &[7]);
+        assert_eq!(instruction.data, b"SIXZ\x00\x01\x00\x00\x00\x07");
+    }
+}
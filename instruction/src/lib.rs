@@ -17,11 +17,18 @@
 extern crate alloc;
 
 pub mod account_meta;
+#[cfg(feature = "envelope")]
+pub mod envelope;
+pub mod privileges;
 #[cfg(any(feature = "syscalls", target_os = "solana"))]
 pub mod syscalls;
 
 pub use {account_meta::AccountMeta, solana_instruction_error as error};
-use {alloc::vec::Vec, solana_pubkey::Pubkey};
+use {
+    alloc::vec::Vec,
+    solana_pubkey::Pubkey,
+    solana_short_vec::{encode_short_u16, MAX_ENCODING_LENGTH},
+};
 
 /// A directive for a single invocation of a Solana program.
 ///
@@ -309,6 +316,51 @@ impl Instruction {
             data: data.to_vec(),
         }
     }
+
+    /// Returns the number of bytes the instruction takes in a compiled
+    /// message, assuming its program id and accounts are already in the
+    /// account keys of the message.
+    ///
+    /// A compiled instruction is the one byte index of its program id, then
+    /// the one byte indexes of its accounts and its data, both prefixed with
+    /// their length in the compact `short_vec` encoding.
+    pub fn compiled_size(&self) -> usize {
+        1 + short_vec_len(self.accounts.len())
+            + self.accounts.len()
+            + short_vec_len(self.data.len())
+            + self.data.len()
+    }
+
+    /// Returns the number of bytes the instruction takes in a compiled
+    /// message whose account keys are `existing_keys`, and the number of
+    /// bytes its program id and accounts missing from `existing_keys` add to
+    /// the account keys.
+    ///
+    /// Each missing key adds 32 bytes, and the length prefix of the account
+    /// keys grows by a byte when their number crosses a `short_vec` length
+    /// boundary, 128 or 16,384 keys. The message header keeps its size.
+    pub fn compiled_size_with_new_accounts(&self, existing_keys: &[Pubkey]) -> (usize, usize) {
+        let mut new_keys: Vec<&Pubkey> = Vec::new();
+        for key in core::iter::once(&self.program_id)
+            .chain(self.accounts.iter().map(|account| &account.pubkey))
+        {
+            if !existing_keys.contains(key) && !new_keys.contains(&key) {
+                new_keys.push(key);
+            }
+        }
+        let num_keys = existing_keys.len() + new_keys.len();
+        let additional =
+            new_keys.len() * 32 + short_vec_len(num_keys) - short_vec_len(existing_keys.len());
+        (self.compiled_size(), additional)
+    }
+}
+
+/// Number of bytes of the `short_vec` encoding of `len`.
+///
+/// Lengths past `u16::MAX` cannot be encoded and are counted as the longest
+/// encoding.
+fn short_vec_len(len: usize) -> usize {
+    u16::try_from(len).map_or(MAX_ENCODING_LENGTH, |len| encode_short_u16(len).1)
 }
 
 // Stack height when processing transaction-level instructions
@@ -344,3 +396,126 @@ pub struct BorrowedInstruction<'a> {
     pub accounts: Vec<BorrowedAccountMeta<'a>>,
     pub data: &'a [u8],
 }
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        alloc::vec,
+        solana_short_vec::{decode_len, encode_len},
+    };
+
+    /// Compiles `instructions` like a message does, to the account keys and
+    /// the serialized instructions.
+    fn compile(instructions: &[&Instruction]) -> (Vec<Pubkey>, Vec<u8>) {
+        let mut keys = vec![];
+        let mut index_of = |key: &Pubkey| {
+            let index = keys.iter().position(|k| k == key).unwrap_or_else(|| {
+                keys.push(*key);
+                keys.len() - 1
+            });
+            u8::try_from(index).unwrap()
+        };
+        let mut bytes = vec![];
+        encode_len(&mut bytes, instructions.len()).unwrap();
+        for instruction in instructions {
+            bytes.push(index_of(&instruction.program_id));
+            encode_len(&mut bytes, instruction.accounts.len()).unwrap();
+            for account in &instruction.accounts {
+                bytes.push(index_of(&account.pubkey));
+            }
+            encode_len(&mut bytes, instruction.data.len()).unwrap();
+            bytes.extend_from_slice(&instruction.data);
+        }
+        (keys, bytes)
+    }
+
+    /// Size of a compiled message without its instructions: the header, the
+    /// account keys and the recent blockhash.
+    fn keys_size(keys: &[Pubkey]) -> usize {
+        3 + short_vec_len(keys.len()) + keys.len() * 32 + 32
+    }
+
+    fn instruction(num_keys: usize, num_accounts: usize, data_len: usize) -> Instruction {
+        let keys: Vec<Pubkey> = (0..num_keys.max(1)).map(|_| Pubkey::new_unique()).collect();
+        let accounts = (0..num_accounts)
+            .map(|i| AccountMeta::new(keys[i % keys.len()], i % 2 == 0))
+            .collect();
+        Instruction::new_with_bytes(Pubkey::new_unique(), &vec![7; data_len], accounts)
+    }
+
+    fn shapes() -> Vec<Instruction> {
+        let mut shapes = vec![];
+        for (num_keys, num_accounts) in [(0, 0), (1, 1), (3, 8), (10, 127), (10, 128), (200, 300)] {
+            for data_len in [0, 1, 127, 128, 0x3fff, 0x4000] {
+                shapes.push(instruction(num_keys, num_accounts, data_len));
+            }
+        }
+        shapes
+    }
+
+    #[test]
+    fn test_short_vec_len() {
+        for (len, expected) in [
+            (0, 1),
+            (0x7f, 1),
+            (0x80, 2),
+            (0x3fff, 2),
+            (0x4000, 3),
+            (0xffff, 3),
+        ] {
+            assert_eq!(short_vec_len(len), expected, "{len}");
+            let mut bytes = vec![];
+            encode_len(&mut bytes, len).unwrap();
+            assert_eq!(decode_len(&bytes).unwrap(), (len, expected));
+        }
+    }
+
+    #[test]
+    fn test_compiled_size() {
+        for shape in shapes() {
+            let (_, bytes) = compile(&[&shape]);
+            // without the length prefix of the instructions
+            assert_eq!(shape.compiled_size(), bytes.len() - 1);
+        }
+    }
+
+    #[test]
+    fn test_compiled_size_with_new_accounts() {
+        let first = instruction(4, 6, 10);
+        let (existing_keys, _) = compile(&[&first]);
+        for shape in shapes().iter().chain([&first]) {
+            // sharing some of the keys of the first instruction
+            let mut shape = shape.clone();
+            shape
+                .accounts
+                .push(AccountMeta::new_readonly(first.program_id, false));
+            shape.accounts.push(first.accounts[0].clone());
+
+            let (size, additional) = shape.compiled_size_with_new_accounts(&existing_keys);
+            let (before_keys, before) = compile(&[&first]);
+            let (after_keys, after) = compile(&[&first, &shape]);
+            assert_eq!(size, after.len() - before.len());
+            assert_eq!(additional, keys_size(&after_keys) - keys_size(&before_keys));
+        }
+    }
+
+    #[test]
+    fn test_compiled_size_with_new_accounts_prefix_growth() {
+        let keys: Vec<Pubkey> = (0..127).map(|_| Pubkey::new_unique()).collect();
+        let shape = instruction(2, 2, 0);
+        // the program id and two accounts are new, crossing 128 keys
+        assert_eq!(
+            shape.compiled_size_with_new_accounts(&keys),
+            (5, 3 * 32 + 1)
+        );
+        assert_eq!(
+            shape.compiled_size_with_new_accounts(&keys[..100]),
+            (5, 3 * 32)
+        );
+        let mut all_keys = keys.clone();
+        all_keys.push(shape.program_id);
+        all_keys.extend(shape.accounts.iter().map(|account| account.pubkey));
+        assert_eq!(shape.compiled_size_with_new_accounts(&all_keys), (5, 0));
+    }
+}
@@ -0,0 +1,346 @@
+//! Checks of the account privileges of cross-program invocations.
+//!
+//! The runtime fails an invocation with
+//! [`InstructionError::PrivilegeEscalation`] if the invoked instruction needs
+//! an account to be a signer or writable while the calling instruction does
+//! not have it as such, without telling which account. [`check_cpi_privileges`]
+//! runs the same checks in the program before invoking, so that the offending
+//! account can be logged:
+//!
+//! ```
+//! # use solana_instruction::{privileges::check_cpi_privileges, AccountMeta, Instruction};
+//! # use solana_pubkey::Pubkey;
+//! let program_id = Pubkey::new_unique();
+//! let payer = Pubkey::new_unique();
+//! let caller_accounts = [(payer, false, true), (program_id, false, false)];
+//! let instruction = Instruction::new_with_bytes(program_id, &[], vec![AccountMeta::new(payer, true)]);
+//!
+//! let violation = check_cpi_privileges(&caller_accounts, &instruction).unwrap_err();
+//! assert_eq!(
+//!     violation.explain().to_string(),
+//!     format!("account {payer} needs to be a signer and writable, but the caller has it writable"),
+//! );
+//! ```
+//!
+//! Accounts that the program signs for with seeds, through `invoke_signed`,
+//! must be passed as signers.
+
+use {
+    crate::{AccountMeta, Instruction},
+    core::fmt,
+    solana_instruction_error::InstructionError,
+    solana_pubkey::Pubkey,
+};
+
+/// An account of an invoked instruction that the calling instruction cannot
+/// pass on.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PrivilegeViolation {
+    /// Address of the account.
+    pub address: Pubkey,
+    /// Whether the invoked instruction needs the account to be a signer.
+    pub needs_signer: bool,
+    /// Whether the invoked instruction needs the account to be writable.
+    pub needs_writable: bool,
+    /// Whether the account is one of the accounts of the calling instruction.
+    pub caller_has_account: bool,
+    /// Whether the account is a signer of the calling instruction.
+    pub caller_has_signer: bool,
+    /// Whether the account is writable in the calling instruction.
+    pub caller_has_writable: bool,
+}
+
+impl PrivilegeViolation {
+    /// A description of the privileges needed and held, for logging.
+    pub fn explain(&self) -> impl fmt::Display + '_ {
+        Explanation(self)
+    }
+}
+
+impl core::error::Error for PrivilegeViolation {}
+
+impl fmt::Display for PrivilegeViolation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.caller_has_account {
+            write!(f, "privilege escalation of account {}", self.address)
+        } else {
+            write!(f, "account {} is missing from the caller", self.address)
+        }
+    }
+}
+
+impl From<PrivilegeViolation> for InstructionError {
+    fn from(violation: PrivilegeViolation) -> Self {
+        if violation.caller_has_account {
+            InstructionError::PrivilegeEscalation
+        } else {
+            InstructionError::MissingAccount
+        }
+    }
+}
+
+struct Explanation<'a>(&'a PrivilegeViolation);
+
+fn privileges(is_signer: bool, is_writable: bool) -> &'static str {
+    match (is_signer, is_writable) {
+        (true, true) => "a signer and writable",
+        (true, false) => "a signer",
+        (false, true) => "writable",
+        (false, false) => "read-only",
+    }
+}
+
+impl fmt::Display for Explanation<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let violation = self.0;
+        let needs = privileges(violation.needs_signer, violation.needs_writable);
+        if !violation.caller_has_account {
+            return write!(
+                f,
+                "account {} needs to be {needs}, but the caller does not have it",
+                violation.address
+            );
+        }
+        write!(
+            f,
+            "account {} needs to be {needs}, but the caller has it {}",
+            violation.address,
+            privileges(violation.caller_has_signer, violation.caller_has_writable)
+        )
+    }
+}
+
+/// Checks that the calling instruction, with the `(address, is_signer,
+/// is_writable)` accounts `caller_accounts`, can invoke `callee_instruction`.
+///
+/// Every account of `callee_instruction` must be an account of the caller,
+/// and may only be a signer or writable if it is so in the caller. The
+/// program invoked must also be an account of the caller, with any
+/// privileges. Returns the first account that fails these checks, in the
+/// order of the accounts of `callee_instruction`, followed by its program.
+pub fn check_cpi_privileges(
+    caller_accounts: &[(Pubkey, bool, bool)],
+    callee_instruction: &Instruction,
+) -> Result<(), PrivilegeViolation> {
+    check(
+        caller_accounts
+            .iter()
+            .map(|(address, is_signer, is_writable)| (address, *is_signer, *is_writable)),
+        callee_instruction,
+    )
+}
+
+/// Checks that the calling instruction, with the accounts `caller_accounts`,
+/// can invoke `callee_instruction`.
+///
+/// This is [`check_cpi_privileges`] for callers holding account metas, such
+/// as the ones built from the accounts of a program.
+pub fn check_cpi_account_metas(
+    caller_accounts: &[AccountMeta],
+    callee_instruction: &Instruction,
+) -> Result<(), PrivilegeViolation> {
+    check(
+        caller_accounts
+            .iter()
+            .map(|meta| (&meta.pubkey, meta.is_signer, meta.is_writable)),
+        callee_instruction,
+    )
+}
+
+fn check<'a>(
+    caller_accounts: impl Iterator<Item = (&'a Pubkey, bool, bool)> + Clone,
+    callee_instruction: &Instruction,
+) -> Result<(), PrivilegeViolation> {
+    // the runtime merges the privileges of duplicate accounts
+    let caller_privileges = |address: &Pubkey| {
+        caller_accounts
+            .clone()
+            .filter(|(caller_address, _, _)| *caller_address == address)
+            .fold(None, |privileges, (_, is_signer, is_writable)| {
+                let (signer, writable) = privileges.unwrap_or((false, false));
+                Some((signer || is_signer, writable || is_writable))
+            })
+    };
+
+    let metas = callee_instruction
+        .accounts
+        .iter()
+        .map(|meta| (&meta.pubkey, meta.is_signer, meta.is_writable));
+    let program = core::iter::once((&callee_instruction.program_id, false, false));
+    for (address, needs_signer, needs_writable) in metas.chain(program) {
+        let privileges = caller_privileges(address);
+        let (caller_has_signer, caller_has_writable) = privileges.unwrap_or((false, false));
+        if privileges.is_none()
+            || (needs_signer && !caller_has_signer)
+            || (needs_writable && !caller_has_writable)
+        {
+            return Err(PrivilegeViolation {
+                address: *address,
+                needs_signer,
+                needs_writable,
+                caller_has_account: privileges.is_some(),
+                caller_has_signer,
+                caller_has_writable,
+            });
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        alloc::{format, string::ToString, vec},
+    };
+
+    fn caller_accounts() -> [(Pubkey, bool, bool); 4] {
+        [
+            (Pubkey::new_from_array([1; 32]), true, true),
+            (Pubkey::new_from_array([2; 32]), false, true),
+            (Pubkey::new_from_array([3; 32]), true, false),
+            (Pubkey::new_from_array([4; 32]), false, false),
+        ]
+    }
+
+    fn instruction(accounts: vec::Vec<AccountMeta>) -> Instruction {
+        Instruction::new_with_bytes(Pubkey::new_from_array([4; 32]), &[], accounts)
+    }
+
+    #[test]
+    fn test_all_clear() {
+        let accounts = caller_accounts();
+        let [(a, ..), (b, ..), (c, ..), (d, ..)] = accounts;
+        let metas = vec![
+            AccountMeta::new(a, true),
+            AccountMeta::new(b, false),
+            AccountMeta::new_readonly(c, true),
+            AccountMeta::new_readonly(d, false),
+            // privileges can be dropped
+            AccountMeta::new_readonly(a, false),
+        ];
+        assert_eq!(
+            check_cpi_privileges(&accounts, &instruction(metas.clone())),
+            Ok(())
+        );
+        assert_eq!(
+            check_cpi_privileges(&accounts, &instruction(vec![])),
+            Ok(())
+        );
+
+        let caller_metas: vec::Vec<_> = accounts
+            .iter()
+            .map(|(pubkey, is_signer, is_writable)| AccountMeta {
+                pubkey: *pubkey,
+                is_signer: *is_signer,
+                is_writable: *is_writable,
+            })
+            .collect();
+        assert_eq!(
+            check_cpi_account_metas(&caller_metas, &instruction(metas)),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_writable_escalation() {
+        let accounts = caller_accounts();
+        let c = accounts[2].0;
+        let metas = vec![
+            AccountMeta::new_readonly(accounts[3].0, false),
+            AccountMeta::new(c, true),
+        ];
+        let violation = check_cpi_privileges(&accounts, &instruction(metas)).unwrap_err();
+        assert_eq!(
+            violation,
+            PrivilegeViolation {
+                address: c,
+                needs_signer: true,
+                needs_writable: true,
+                caller_has_account: true,
+                caller_has_signer: true,
+                caller_has_writable: false,
+            }
+        );
+        assert_eq!(
+            violation.explain().to_string(),
+            format!(
+                "account {c} needs to be a signer and writable, but the caller has it a signer"
+            )
+        );
+        assert_eq!(
+            InstructionError::from(violation),
+            InstructionError::PrivilegeEscalation
+        );
+    }
+
+    #[test]
+    fn test_signer_escalation() {
+        let accounts = caller_accounts();
+        let b = accounts[1].0;
+        let violation =
+            check_cpi_privileges(&accounts, &instruction(vec![AccountMeta::new(b, true)]))
+                .unwrap_err();
+        assert_eq!(
+            violation.explain().to_string(),
+            format!(
+                "account {b} needs to be a signer and writable, but the caller has it writable"
+            )
+        );
+        assert_eq!(
+            violation.to_string(),
+            format!("privilege escalation of account {b}")
+        );
+
+        // a duplicate of the caller grants the privilege, as in the runtime
+        let mut accounts = accounts.to_vec();
+        accounts.push((b, true, false));
+        assert_eq!(
+            check_cpi_privileges(&accounts, &instruction(vec![AccountMeta::new(b, true)])),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_program_id() {
+        let accounts = caller_accounts();
+        let program_id = accounts[3].0;
+
+        // the program account is read-only in the caller, and only needs to
+        // be present, unless the instruction lists it with privileges
+        let metas = vec![AccountMeta::new_readonly(program_id, false)];
+        assert_eq!(check_cpi_privileges(&accounts, &instruction(metas)), Ok(()));
+        let metas = vec![AccountMeta::new(program_id, false)];
+        let violation = check_cpi_privileges(&accounts, &instruction(metas)).unwrap_err();
+        assert_eq!(violation.address, program_id);
+        assert!(violation.needs_writable);
+
+        let unknown_program = Pubkey::new_from_array([9; 32]);
+        let violation = check_cpi_privileges(
+            &accounts,
+            &Instruction::new_with_bytes(unknown_program, &[], vec![]),
+        )
+        .unwrap_err();
+        assert_eq!(
+            violation,
+            PrivilegeViolation {
+                address: unknown_program,
+                needs_signer: false,
+                needs_writable: false,
+                caller_has_account: false,
+                caller_has_signer: false,
+                caller_has_writable: false,
+            }
+        );
+        assert_eq!(
+            violation.explain().to_string(),
+            format!(
+                "account {unknown_program} needs to be read-only, but the caller does not have it"
+            )
+        );
+        assert_eq!(
+            InstructionError::from(violation),
+            InstructionError::MissingAccount
+        );
+    }
+}
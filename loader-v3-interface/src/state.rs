@@ -1,4 +1,4 @@
-use solana_pubkey::Pubkey;
+use {core::fmt, solana_pubkey::Pubkey};
 
 /// Upgradeable loader account states
 #[cfg_attr(feature = "frozen-abi", derive(solana_frozen_abi_macro::AbiExample))]
@@ -64,6 +64,116 @@ impl UpgradeableLoaderState {
     }
 }
 
+/// Offset of the program in the data of a buffer account.
+pub const BUFFER_ELF_OFFSET: usize = UpgradeableLoaderState::size_of_buffer_metadata();
+
+/// Offset of the program in the data of a programdata account.
+pub const PROGRAMDATA_ELF_OFFSET: usize = UpgradeableLoaderState::size_of_programdata_metadata();
+
+/// Reasons the data of an upgradeable loader account can fail to parse.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LoaderStateError {
+    /// The data is not a serialized [`UpgradeableLoaderState`].
+    InvalidData,
+    /// The account is not initialized.
+    Uninitialized,
+}
+
+impl core::error::Error for LoaderStateError {}
+
+impl fmt::Display for LoaderStateError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LoaderStateError::InvalidData => f.write_str("invalid upgradeable loader account data"),
+            LoaderStateError::Uninitialized => {
+                f.write_str("upgradeable loader account is not initialized")
+            }
+        }
+    }
+}
+
+/// An initialized upgradeable loader account, as parsed by
+/// [`parse_program_account`].
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ProgramAccountInfo {
+    /// A program account.
+    Program {
+        /// Address of the ProgramData account.
+        programdata_address: Pubkey,
+    },
+    /// A ProgramData account.
+    ProgramData {
+        /// Slot that the program was last modified.
+        slot: u64,
+        /// Address of the Program's upgrade authority.
+        upgrade_authority_address: Option<Pubkey>,
+        /// Offset of the program in the account data, always
+        /// [`PROGRAMDATA_ELF_OFFSET`].
+        elf_offset: usize,
+    },
+    /// A Buffer account.
+    Buffer {
+        /// Authority address
+        authority_address: Option<Pubkey>,
+        /// Offset of the program in the account data, always
+        /// [`BUFFER_ELF_OFFSET`].
+        elf_offset: usize,
+    },
+}
+
+/// Parses the data of an upgradeable loader account.
+///
+/// The program of a buffer or programdata account is the account data past
+/// the returned `elf_offset`.
+pub fn parse_program_account(data: &[u8]) -> Result<ProgramAccountInfo, LoaderStateError> {
+    let tag = read_array::<4>(data, 0).map(u32::from_le_bytes)?;
+    match tag {
+        0 => Err(LoaderStateError::Uninitialized),
+        1 => {
+            // the metadata is reserved even without an authority
+            if data.len() < BUFFER_ELF_OFFSET {
+                return Err(LoaderStateError::InvalidData);
+            }
+            Ok(ProgramAccountInfo::Buffer {
+                authority_address: read_option_pubkey(data, 4)?,
+                elf_offset: BUFFER_ELF_OFFSET,
+            })
+        }
+        2 => Ok(ProgramAccountInfo::Program {
+            programdata_address: Pubkey::new_from_array(read_array(data, 4)?),
+        }),
+        3 => {
+            if data.len() < PROGRAMDATA_ELF_OFFSET {
+                return Err(LoaderStateError::InvalidData);
+            }
+            Ok(ProgramAccountInfo::ProgramData {
+                slot: read_array::<8>(data, 4).map(u64::from_le_bytes)?,
+                upgrade_authority_address: read_option_pubkey(data, 12)?,
+                elf_offset: PROGRAMDATA_ELF_OFFSET,
+            })
+        }
+        _ => Err(LoaderStateError::InvalidData),
+    }
+}
+
+fn read_array<const N: usize>(data: &[u8], offset: usize) -> Result<[u8; N], LoaderStateError> {
+    data.get(offset..offset.saturating_add(N))
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or(LoaderStateError::InvalidData)
+}
+
+/// Reads a bincode `Option<Pubkey>`: a one byte tag, followed by the address
+/// if the tag is one.
+fn read_option_pubkey(data: &[u8], offset: usize) -> Result<Option<Pubkey>, LoaderStateError> {
+    match read_array::<1>(data, offset)? {
+        [0] => Ok(None),
+        [1] => read_array(data, offset.saturating_add(1))
+            .map(Pubkey::new_from_array)
+            .map(Some),
+        _ => Err(LoaderStateError::InvalidData),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use {super::*, bincode::serialized_size};
@@ -108,4 +218,154 @@ mod tests {
         let size = serialized_size(&program_state).unwrap();
         assert_eq!(UpgradeableLoaderState::size_of_program() as u64, size);
     }
+
+    #[test]
+    fn test_elf_offsets() {
+        assert_eq!(BUFFER_ELF_OFFSET, 37);
+        assert_eq!(PROGRAMDATA_ELF_OFFSET, 45);
+    }
+
+    /// Serializes `state` into account data with room for its metadata,
+    /// followed by `elf`.
+    fn account_data(state: &UpgradeableLoaderState, metadata_len: usize, elf: &[u8]) -> Vec<u8> {
+        let mut data = vec![0; metadata_len];
+        bincode::serialize_into(&mut data[..], state).unwrap();
+        data.extend_from_slice(elf);
+        data
+    }
+
+    #[test]
+    fn test_parse_program_account() {
+        let programdata_address = Pubkey::new_from_array([7; 32]);
+        let mut data = vec![2, 0, 0, 0];
+        data.extend_from_slice(programdata_address.as_ref());
+        assert_eq!(
+            bincode::serialize(&UpgradeableLoaderState::Program {
+                programdata_address
+            })
+            .unwrap(),
+            data
+        );
+        assert_eq!(
+            parse_program_account(&data),
+            Ok(ProgramAccountInfo::Program {
+                programdata_address
+            })
+        );
+        assert_eq!(
+            parse_program_account(&data[..35]),
+            Err(LoaderStateError::InvalidData)
+        );
+    }
+
+    #[test]
+    fn test_parse_programdata_account() {
+        let authority = Pubkey::new_from_array([9; 32]);
+        let elf = b"\x7fELF program";
+        let mut data = vec![3, 0, 0, 0, 0x2a, 1, 0, 0, 0, 0, 0, 0, 1];
+        data.extend_from_slice(authority.as_ref());
+        data.extend_from_slice(elf);
+        let state = UpgradeableLoaderState::ProgramData {
+            slot: 0x12a,
+            upgrade_authority_address: Some(authority),
+        };
+        assert_eq!(account_data(&state, PROGRAMDATA_ELF_OFFSET, elf), data);
+
+        let info = parse_program_account(&data).unwrap();
+        assert_eq!(
+            info,
+            ProgramAccountInfo::ProgramData {
+                slot: 0x12a,
+                upgrade_authority_address: Some(authority),
+                elf_offset: PROGRAMDATA_ELF_OFFSET,
+            }
+        );
+        assert_eq!(&data[PROGRAMDATA_ELF_OFFSET..], elf);
+
+        // without an authority the metadata keeps its size
+        let state = UpgradeableLoaderState::ProgramData {
+            slot: 5,
+            upgrade_authority_address: None,
+        };
+        let data = account_data(&state, PROGRAMDATA_ELF_OFFSET, elf);
+        assert_eq!(
+            parse_program_account(&data),
+            Ok(ProgramAccountInfo::ProgramData {
+                slot: 5,
+                upgrade_authority_address: None,
+                elf_offset: PROGRAMDATA_ELF_OFFSET,
+            })
+        );
+        assert_eq!(&data[PROGRAMDATA_ELF_OFFSET..], elf);
+        assert_eq!(
+            parse_program_account(&data[..PROGRAMDATA_ELF_OFFSET - 1]),
+            Err(LoaderStateError::InvalidData)
+        );
+    }
+
+    #[test]
+    fn test_parse_buffer_account() {
+        let authority = Pubkey::new_from_array([3; 32]);
+        let mut data = vec![1, 0, 0, 0, 1];
+        data.extend_from_slice(authority.as_ref());
+        assert_eq!(
+            account_data(
+                &UpgradeableLoaderState::Buffer {
+                    authority_address: Some(authority)
+                },
+                BUFFER_ELF_OFFSET,
+                &[]
+            ),
+            data
+        );
+        assert_eq!(
+            parse_program_account(&data),
+            Ok(ProgramAccountInfo::Buffer {
+                authority_address: Some(authority),
+                elf_offset: BUFFER_ELF_OFFSET,
+            })
+        );
+
+        let data = account_data(
+            &UpgradeableLoaderState::Buffer {
+                authority_address: None,
+            },
+            BUFFER_ELF_OFFSET,
+            b"elf",
+        );
+        assert_eq!(
+            parse_program_account(&data),
+            Ok(ProgramAccountInfo::Buffer {
+                authority_address: None,
+                elf_offset: BUFFER_ELF_OFFSET,
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid_account() {
+        let uninitialized = bincode::serialize(&UpgradeableLoaderState::Uninitialized).unwrap();
+        assert_eq!(uninitialized, [0, 0, 0, 0]);
+        assert_eq!(
+            parse_program_account(&uninitialized),
+            Err(LoaderStateError::Uninitialized)
+        );
+
+        assert_eq!(
+            parse_program_account(&[]),
+            Err(LoaderStateError::InvalidData)
+        );
+        assert_eq!(
+            parse_program_account(&[4, 0, 0, 0]),
+            Err(LoaderStateError::InvalidData)
+        );
+        // invalid option tag
+        let mut data = vec![0; PROGRAMDATA_ELF_OFFSET];
+        data[0] = 3;
+        data[12] = 2;
+        assert_eq!(
+            parse_program_account(&data),
+            Err(LoaderStateError::InvalidData)
+        );
+    }
 }
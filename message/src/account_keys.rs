@@ -29,6 +29,18 @@ impl<'a> AccountKeys<'a> {
         }
     }
 
+    /// Returns the static keys of the message.
+    #[inline]
+    pub(crate) fn static_keys(&self) -> &'a [Address] {
+        self.static_keys
+    }
+
+    /// Returns the addresses loaded from lookup tables, if any.
+    #[inline]
+    pub(crate) fn dynamic_keys(&self) -> Option<&'a LoadedAddresses> {
+        self.dynamic_keys
+    }
+
     /// Returns an iterator of account key segments. The ordering of segments
     /// affects how account indexes from compiled instructions are resolved and
     /// so should not be changed.
@@ -0,0 +1,276 @@
+//! Conversion of compiled instructions back into [`Instruction`]s.
+
+use {
+    crate::{
+        compiled_instruction::CompiledInstruction, v0::LoadedAddresses, AccountKeys, MessageHeader,
+        VersionedMessage,
+    },
+    solana_instruction::{AccountMeta, Instruction},
+    std::fmt,
+};
+
+/// Errors returned when decompiling instructions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecompileError {
+    /// The program id index does not refer to an account key.
+    ProgramIdIndexOutOfBounds(u8),
+    /// An account index does not refer to an account key.
+    AccountIndexOutOfBounds(u8),
+    /// The message uses address lookup tables but no loaded addresses were
+    /// provided.
+    MissingLoadedAddresses,
+    /// The number of loaded addresses does not match the address lookup
+    /// tables of the message.
+    LoadedAddressesMismatch,
+}
+
+impl core::error::Error for DecompileError {}
+
+impl fmt::Display for DecompileError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DecompileError::ProgramIdIndexOutOfBounds(index) => {
+                write!(f, "program id index {index} is out of bounds")
+            }
+            DecompileError::AccountIndexOutOfBounds(index) => {
+                write!(f, "account index {index} is out of bounds")
+            }
+            DecompileError::MissingLoadedAddresses => {
+                f.write_str("message uses address lookup tables but no addresses were loaded")
+            }
+            DecompileError::LoadedAddressesMismatch => {
+                f.write_str("loaded addresses do not match the message address lookup tables")
+            }
+        }
+    }
+}
+
+/// Reconstruct the [`Instruction`] compiled into `instruction`.
+///
+/// Account indexes are resolved against `account_keys`. Static keys are
+/// signers and writable according to `header`, addresses loaded as writable
+/// from lookup tables are writable, and all other loaded addresses are
+/// readonly.
+///
+/// Since a message stores a single set of permissions per account, an
+/// account that one instruction requested as writable is writable in every
+/// decompiled instruction referencing it. Write lock demotion of reserved
+/// accounts and invoked programs is not applied.
+pub fn decompile_instruction(
+    instruction: &CompiledInstruction,
+    account_keys: &AccountKeys,
+    header: &MessageHeader,
+) -> Result<Instruction, DecompileError> {
+    let program_id = account_keys
+        .get(usize::from(instruction.program_id_index))
+        .ok_or(DecompileError::ProgramIdIndexOutOfBounds(
+            instruction.program_id_index,
+        ))?;
+    let accounts = instruction
+        .accounts
+        .iter()
+        .map(|&index| {
+            let pubkey = account_keys
+                .get(usize::from(index))
+                .ok_or(DecompileError::AccountIndexOutOfBounds(index))?;
+            let index = usize::from(index);
+            Ok(AccountMeta {
+                pubkey: *pubkey,
+                is_signer: index < usize::from(header.num_required_signatures),
+                is_writable: is_writable(index, account_keys, header),
+            })
+        })
+        .collect::<Result<_, _>>()?;
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data: instruction.data.clone(),
+    })
+}
+
+/// Reconstruct all the instructions of `message`.
+///
+/// `loaded` must hold the addresses loaded from the address lookup tables of
+/// v0 messages, writable addresses first, in lookup order. See
+/// [`decompile_instruction`] for how account permissions are resolved.
+pub fn decompile_all(
+    message: &VersionedMessage,
+    loaded: Option<&LoadedAddresses>,
+) -> Result<Vec<Instruction>, DecompileError> {
+    let lookups = message.address_table_lookups().unwrap_or_default();
+    let loaded = match loaded {
+        None if !lookups.is_empty() => return Err(DecompileError::MissingLoadedAddresses),
+        None => None,
+        Some(loaded) => {
+            let num_writable: usize = lookups.iter().map(|l| l.writable_indexes.len()).sum();
+            let num_readonly: usize = lookups.iter().map(|l| l.readonly_indexes.len()).sum();
+            if loaded.writable.len() != num_writable || loaded.readonly.len() != num_readonly {
+                return Err(DecompileError::LoadedAddressesMismatch);
+            }
+            Some(loaded)
+        }
+    };
+    let account_keys = AccountKeys::new(message.static_account_keys(), loaded);
+    message
+        .instructions()
+        .iter()
+        .map(|instruction| decompile_instruction(instruction, &account_keys, message.header()))
+        .collect()
+}
+
+fn is_writable(index: usize, account_keys: &AccountKeys, header: &MessageHeader) -> bool {
+    let num_static_keys = account_keys.static_keys().len();
+    if index >= num_static_keys {
+        let num_loaded_writable = account_keys
+            .dynamic_keys()
+            .map(|loaded| loaded.writable.len())
+            .unwrap_or_default();
+        return index - num_static_keys < num_loaded_writable;
+    }
+    let num_required_signatures = usize::from(header.num_required_signatures);
+    if index < num_required_signatures {
+        index
+            < num_required_signatures
+                .saturating_sub(usize::from(header.num_readonly_signed_accounts))
+    } else {
+        index < num_static_keys.saturating_sub(usize::from(header.num_readonly_unsigned_accounts))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{v0, AddressLookupTableAccount, Message},
+        solana_address::Address,
+        solana_hash::Hash,
+    };
+
+    fn legacy_instructions(payer: &Address) -> Vec<Instruction> {
+        let program_a = Address::new_unique();
+        let program_b = Address::new_unique();
+        let writable = Address::new_unique();
+        let readonly = Address::new_unique();
+        let readonly_signer = Address::new_unique();
+        vec![
+            Instruction::new_with_bytes(
+                program_a,
+                &[1, 2, 3],
+                vec![
+                    AccountMeta::new(*payer, true),
+                    AccountMeta::new(writable, false),
+                    AccountMeta::new_readonly(readonly, false),
+                ],
+            ),
+            Instruction::new_with_bytes(
+                program_b,
+                &[],
+                vec![
+                    AccountMeta::new_readonly(readonly, false),
+                    AccountMeta::new_readonly(readonly_signer, true),
+                ],
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_decompile_legacy_roundtrip() {
+        let payer = Address::new_unique();
+        let instructions = legacy_instructions(&payer);
+        let message = VersionedMessage::Legacy(Message::new(&instructions, Some(&payer)));
+        assert_eq!(decompile_all(&message, None), Ok(instructions));
+    }
+
+    #[test]
+    fn test_decompile_v0_roundtrip() {
+        let payer = Address::new_unique();
+        let program = Address::new_unique();
+        let static_readonly = Address::new_unique();
+        let table_writable = Address::new_unique();
+        let table_readonly = Address::new_unique();
+        let lookup_table = AddressLookupTableAccount {
+            key: Address::new_unique(),
+            addresses: vec![table_readonly, Address::new_unique(), table_writable],
+        };
+
+        let mut instructions = legacy_instructions(&payer);
+        instructions.push(Instruction::new_with_bytes(
+            program,
+            &[4],
+            vec![
+                AccountMeta::new(payer, true),
+                AccountMeta::new(table_writable, false),
+                AccountMeta::new_readonly(table_readonly, false),
+                AccountMeta::new_readonly(static_readonly, false),
+            ],
+        ));
+        let message =
+            v0::Message::try_compile(&payer, &instructions, &[lookup_table], Hash::new_unique())
+                .unwrap();
+        let lookup = &message.address_table_lookups[0];
+        assert_eq!(lookup.writable_indexes, vec![2]);
+        assert_eq!(lookup.readonly_indexes, vec![0]);
+
+        let loaded = LoadedAddresses {
+            writable: vec![table_writable],
+            readonly: vec![table_readonly],
+        };
+        let message = VersionedMessage::V0(message);
+        let decompiled = decompile_all(&message, Some(&loaded)).unwrap();
+        assert_eq!(decompiled, instructions);
+
+        // `table_writable` is only writable through the lookup table
+        assert!(!message.static_account_keys().contains(&table_writable));
+        let meta = &decompiled[2].accounts[1];
+        assert_eq!(meta.pubkey, table_writable);
+        assert!(meta.is_writable);
+        assert!(!meta.is_signer);
+        let meta = &decompiled[2].accounts[2];
+        assert_eq!(meta.pubkey, table_readonly);
+        assert!(!meta.is_writable);
+
+        assert_eq!(
+            decompile_all(&message, None),
+            Err(DecompileError::MissingLoadedAddresses)
+        );
+        let swapped = LoadedAddresses {
+            writable: vec![],
+            readonly: vec![table_writable, table_readonly],
+        };
+        assert_eq!(
+            decompile_all(&message, Some(&swapped)),
+            Err(DecompileError::LoadedAddressesMismatch)
+        );
+    }
+
+    #[test]
+    fn test_decompile_index_out_of_bounds() {
+        let keys = [Address::new_unique(), Address::new_unique()];
+        let header = MessageHeader {
+            num_required_signatures: 1,
+            num_readonly_signed_accounts: 0,
+            num_readonly_unsigned_accounts: 1,
+        };
+        let account_keys = AccountKeys::new(&keys, None);
+
+        let instruction = CompiledInstruction::new_from_raw_parts(2, vec![], vec![0]);
+        assert_eq!(
+            decompile_instruction(&instruction, &account_keys, &header),
+            Err(DecompileError::ProgramIdIndexOutOfBounds(2))
+        );
+        let instruction = CompiledInstruction::new_from_raw_parts(1, vec![], vec![0, 5]);
+        assert_eq!(
+            decompile_instruction(&instruction, &account_keys, &header),
+            Err(DecompileError::AccountIndexOutOfBounds(5))
+        );
+        let instruction = CompiledInstruction::new_from_raw_parts(1, vec![9], vec![0]);
+        assert_eq!(
+            decompile_instruction(&instruction, &account_keys, &header),
+            Ok(Instruction::new_with_bytes(
+                keys[1],
+                &[9],
+                vec![AccountMeta::new(keys[0], true)]
+            ))
+        );
+    }
+}
@@ -57,10 +57,11 @@ use {solana_sdk_ids::bpf_loader_upgradeable, std::collections::HashSet};
 mod non_bpf_modules {
     mod account_keys;
     mod address_loader;
+    mod decompile;
     mod sanitized;
     mod versions;
 
-    pub use {account_keys::*, address_loader::*, sanitized::*, versions::*};
+    pub use {account_keys::*, address_loader::*, decompile::*, sanitized::*, versions::*};
 }
 
 use crate::compiled_instruction::CompiledInstruction;
@@ -30,10 +30,14 @@ use {
 };
 
 mod sanitized;
+#[cfg(feature = "strict-sanitize")]
+mod strict_sanitize;
 pub mod v0;
 pub mod v1;
 
 pub use sanitized::*;
+#[cfg(feature = "strict-sanitize")]
+pub use strict_sanitize::*;
 
 /// Bit mask that indicates whether a serialized message is versioned.
 pub const MESSAGE_VERSION_PREFIX: u8 = 0x80;
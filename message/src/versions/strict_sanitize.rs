@@ -0,0 +1,449 @@
+//! Standalone static checks for versioned messages.
+
+use {
+    super::{v1, VersionedMessage},
+    crate::compiled_instruction::CompiledInstruction,
+    core::fmt,
+    solana_address::Address,
+    solana_hash::Hash,
+    solana_packet::PACKET_DATA_SIZE,
+    solana_sanitize::SanitizeError,
+    solana_short_vec::{encode_short_u16, MAX_ENCODING_LENGTH},
+    std::collections::HashSet,
+};
+
+/// Size of an ed25519 signature in a serialized transaction.
+const SIGNATURE_SIZE: usize = 64;
+
+/// Maximum number of account keys that can be addressed with a `u8` index.
+const MAX_ACCOUNT_KEYS: usize = 256;
+
+/// Errors returned by [`sanitize_message_strict_detailed`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StrictSanitizeError {
+    /// The header describes more signed and read-only unsigned accounts than
+    /// there are static account keys.
+    NotEnoughAccountKeys,
+    /// The header doesn't describe a writable signer to pay fees.
+    NoWritableFeePayer,
+    /// The static account keys contain duplicates.
+    DuplicateAccountKeys,
+    /// An address table lookup doesn't load any accounts.
+    EmptyAddressTableLookup,
+    /// The message references more accounts than can be indexed by a `u8`.
+    TooManyAccountKeys,
+    /// An instruction's program id index is out of the static account keys
+    /// or points at the fee payer.
+    InvalidProgramIdIndex,
+    /// An instruction's program id is a writable signer.
+    WritableSignerProgramId,
+    /// An instruction account index is out of range.
+    InvalidInstructionAccountIndex,
+    /// The signed transaction carrying the message doesn't fit in a packet.
+    TransactionTooLarge,
+}
+
+impl fmt::Display for StrictSanitizeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotEnoughAccountKeys => f.write_str("not enough account keys for the header"),
+            Self::NoWritableFeePayer => f.write_str("message has no writable fee payer"),
+            Self::DuplicateAccountKeys => f.write_str("duplicate account keys found in message"),
+            Self::EmptyAddressTableLookup => {
+                f.write_str("address table lookup doesn't load any accounts")
+            }
+            Self::TooManyAccountKeys => f.write_str("too many account keys (max 256)"),
+            Self::InvalidProgramIdIndex => {
+                f.write_str("program ID index out of bounds or is fee payer")
+            }
+            Self::WritableSignerProgramId => f.write_str("program ID is a writable signer"),
+            Self::InvalidInstructionAccountIndex => {
+                f.write_str("instruction account index out of bounds")
+            }
+            Self::TransactionTooLarge => f.write_str("transaction exceeds max size"),
+        }
+    }
+}
+
+impl core::error::Error for StrictSanitizeError {}
+
+impl From<StrictSanitizeError> for SanitizeError {
+    fn from(err: StrictSanitizeError) -> Self {
+        match err {
+            StrictSanitizeError::NoWritableFeePayer
+            | StrictSanitizeError::DuplicateAccountKeys
+            | StrictSanitizeError::EmptyAddressTableLookup
+            | StrictSanitizeError::WritableSignerProgramId => SanitizeError::InvalidValue,
+            StrictSanitizeError::NotEnoughAccountKeys
+            | StrictSanitizeError::TooManyAccountKeys
+            | StrictSanitizeError::InvalidProgramIdIndex
+            | StrictSanitizeError::InvalidInstructionAccountIndex => {
+                SanitizeError::IndexOutOfBounds
+            }
+            StrictSanitizeError::TransactionTooLarge => SanitizeError::ValueOutOfBounds,
+        }
+    }
+}
+
+/// Runs the static checks the runtime performs when sanitizing a message,
+/// without loading any accounts.
+///
+/// In addition to the checks done by [`VersionedMessage::sanitize`], this
+/// rejects duplicate static account keys, program ids that are writable
+/// signers, and messages whose signed transaction would exceed
+/// [`PACKET_DATA_SIZE`] (or [`v1::MAX_TRANSACTION_SIZE`] for v1 messages).
+/// Use [`sanitize_message_strict_detailed`] to tell the failed check.
+pub fn sanitize_message_strict(message: &VersionedMessage) -> Result<(), SanitizeError> {
+    sanitize_message_strict_detailed(message).map_err(SanitizeError::from)
+}
+
+/// Runs the checks of [`sanitize_message_strict`], reporting each failure
+/// with its own [`StrictSanitizeError`] variant.
+pub fn sanitize_message_strict_detailed(
+    message: &VersionedMessage,
+) -> Result<(), StrictSanitizeError> {
+    let header = message.header();
+    let account_keys = message.static_account_keys();
+    let num_static_account_keys = account_keys.len();
+
+    if usize::from(header.num_required_signatures)
+        .saturating_add(usize::from(header.num_readonly_unsigned_accounts))
+        > num_static_account_keys
+    {
+        return Err(StrictSanitizeError::NotEnoughAccountKeys);
+    }
+
+    // there should be at least 1 RW fee-payer account.
+    if header.num_readonly_signed_accounts >= header.num_required_signatures {
+        return Err(StrictSanitizeError::NoWritableFeePayer);
+    }
+
+    let mut unique_keys = HashSet::with_capacity(num_static_account_keys);
+    if !account_keys.iter().all(|key| unique_keys.insert(key)) {
+        return Err(StrictSanitizeError::DuplicateAccountKeys);
+    }
+
+    let mut num_dynamic_account_keys: usize = 0;
+    for lookup in message.address_table_lookups().unwrap_or_default() {
+        let num_lookup_indexes = lookup
+            .writable_indexes
+            .len()
+            .saturating_add(lookup.readonly_indexes.len());
+        if num_lookup_indexes == 0 {
+            return Err(StrictSanitizeError::EmptyAddressTableLookup);
+        }
+        num_dynamic_account_keys = num_dynamic_account_keys.saturating_add(num_lookup_indexes);
+    }
+
+    let total_account_keys = num_static_account_keys.saturating_add(num_dynamic_account_keys);
+    if total_account_keys > MAX_ACCOUNT_KEYS {
+        return Err(StrictSanitizeError::TooManyAccountKeys);
+    }
+
+    let num_writable_signers = usize::from(header.num_required_signatures)
+        .saturating_sub(usize::from(header.num_readonly_signed_accounts));
+    for ci in message.instructions() {
+        let program_id_index = usize::from(ci.program_id_index);
+        // program ids can't be loaded from lookup tables, and a program
+        // cannot be a payer.
+        if program_id_index >= num_static_account_keys || program_id_index == 0 {
+            return Err(StrictSanitizeError::InvalidProgramIdIndex);
+        }
+        if program_id_index < num_writable_signers {
+            return Err(StrictSanitizeError::WritableSignerProgramId);
+        }
+        if ci
+            .accounts
+            .iter()
+            .any(|ai| usize::from(*ai) >= total_account_keys)
+        {
+            return Err(StrictSanitizeError::InvalidInstructionAccountIndex);
+        }
+    }
+
+    let max_transaction_size = match message {
+        VersionedMessage::V1(_) => v1::MAX_TRANSACTION_SIZE,
+        _ => PACKET_DATA_SIZE,
+    };
+    if serialized_transaction_size(message) > max_transaction_size {
+        return Err(StrictSanitizeError::TransactionTooLarge);
+    }
+
+    Ok(())
+}
+
+/// Number of bytes needed to encode `len` as a compact-u16.
+///
+/// Lengths past `u16::MAX` cannot be encoded and are counted as the longest
+/// encoding.
+fn short_vec_len(len: usize) -> usize {
+    u16::try_from(len).map_or(MAX_ENCODING_LENGTH, |len| encode_short_u16(len).1)
+}
+
+fn serialized_instructions_size(instructions: &[CompiledInstruction]) -> usize {
+    instructions
+        .iter()
+        .fold(short_vec_len(instructions.len()), |size, ci| {
+            size.saturating_add(1) // program id index
+                .saturating_add(short_vec_len(ci.accounts.len()))
+                .saturating_add(ci.accounts.len())
+                .saturating_add(short_vec_len(ci.data.len()))
+                .saturating_add(ci.data.len())
+        })
+}
+
+/// Size of a signed transaction carrying `message`, computed without
+/// serializing it.
+fn serialized_transaction_size(message: &VersionedMessage) -> usize {
+    let num_signatures = usize::from(message.header().num_required_signatures);
+    let signatures_size =
+        short_vec_len(num_signatures).saturating_add(num_signatures.saturating_mul(SIGNATURE_SIZE));
+    let static_keys_size = |account_keys: &[Address]| {
+        short_vec_len(account_keys.len())
+            .saturating_add(account_keys.len().saturating_mul(size_of::<Address>()))
+    };
+
+    let message_size = match message {
+        VersionedMessage::Legacy(message) => crate::MESSAGE_HEADER_LENGTH
+            .saturating_add(static_keys_size(&message.account_keys))
+            .saturating_add(size_of::<Hash>())
+            .saturating_add(serialized_instructions_size(&message.instructions)),
+        VersionedMessage::V0(message) => {
+            let lookups_size = message.address_table_lookups.iter().fold(
+                short_vec_len(message.address_table_lookups.len()),
+                |size, lookup| {
+                    size.saturating_add(size_of::<Address>())
+                        .saturating_add(short_vec_len(lookup.writable_indexes.len()))
+                        .saturating_add(lookup.writable_indexes.len())
+                        .saturating_add(short_vec_len(lookup.readonly_indexes.len()))
+                        .saturating_add(lookup.readonly_indexes.len())
+                },
+            );
+            1usize // version prefix
+                .saturating_add(crate::MESSAGE_HEADER_LENGTH)
+                .saturating_add(static_keys_size(&message.account_keys))
+                .saturating_add(size_of::<Hash>())
+                .saturating_add(serialized_instructions_size(&message.instructions))
+                .saturating_add(lookups_size)
+        }
+        // version prefix
+        VersionedMessage::V1(message) => message.size().saturating_add(1),
+    };
+
+    signatures_size.saturating_add(message_size)
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{legacy::Message as LegacyMessage, v0, MessageHeader},
+    };
+
+    fn header(
+        num_required_signatures: u8,
+        num_readonly_signed_accounts: u8,
+        num_readonly_unsigned_accounts: u8,
+    ) -> MessageHeader {
+        MessageHeader {
+            num_required_signatures,
+            num_readonly_signed_accounts,
+            num_readonly_unsigned_accounts,
+        }
+    }
+
+    fn valid_v0_message() -> v0::Message {
+        v0::Message {
+            header: header(2, 1, 1),
+            account_keys: vec![
+                Address::new_unique(),
+                Address::new_unique(),
+                Address::new_unique(),
+                Address::new_unique(),
+            ],
+            recent_blockhash: Hash::new_unique(),
+            instructions: vec![CompiledInstruction {
+                program_id_index: 3,
+                accounts: vec![0, 1, 2, 4, 5, 6],
+                data: vec![1, 2, 3],
+            }],
+            address_table_lookups: vec![v0::MessageAddressTableLookup {
+                account_key: Address::new_unique(),
+                writable_indexes: vec![0, 7],
+                readonly_indexes: vec![3],
+            }],
+        }
+    }
+
+    #[test]
+    fn test_valid_v0_message_with_lookups() {
+        let message = VersionedMessage::V0(valid_v0_message());
+        assert_eq!(sanitize_message_strict(&message), Ok(()));
+        assert_eq!(sanitize_message_strict_detailed(&message), Ok(()));
+        assert_eq!(message.sanitize(), Ok(()));
+    }
+
+    #[test]
+    fn test_not_enough_account_keys() {
+        let mut message = valid_v0_message();
+        message.header = header(3, 1, 2);
+        assert_eq!(
+            sanitize_message_strict_detailed(&VersionedMessage::V0(message)),
+            Err(StrictSanitizeError::NotEnoughAccountKeys)
+        );
+    }
+
+    #[test]
+    fn test_no_writable_fee_payer() {
+        let mut message = valid_v0_message();
+        message.header = header(2, 2, 1);
+        assert_eq!(
+            sanitize_message_strict_detailed(&VersionedMessage::V0(message)),
+            Err(StrictSanitizeError::NoWritableFeePayer)
+        );
+    }
+
+    #[test]
+    fn test_duplicate_account_keys() {
+        let mut message = valid_v0_message();
+        message.account_keys[2] = message.account_keys[1];
+        let message = VersionedMessage::V0(message);
+        // not caught by the regular sanitization
+        assert_eq!(message.sanitize(), Ok(()));
+        assert_eq!(
+            sanitize_message_strict_detailed(&message),
+            Err(StrictSanitizeError::DuplicateAccountKeys)
+        );
+    }
+
+    #[test]
+    fn test_empty_address_table_lookup() {
+        let mut message = valid_v0_message();
+        message.address_table_lookups[0].writable_indexes.clear();
+        message.address_table_lookups[0].readonly_indexes.clear();
+        assert_eq!(
+            sanitize_message_strict_detailed(&VersionedMessage::V0(message)),
+            Err(StrictSanitizeError::EmptyAddressTableLookup)
+        );
+    }
+
+    #[test]
+    fn test_too_many_account_keys() {
+        let mut message = valid_v0_message();
+        message.address_table_lookups[0].writable_indexes = (0..=252).collect();
+        assert_eq!(
+            sanitize_message_strict_detailed(&VersionedMessage::V0(message)),
+            Err(StrictSanitizeError::TooManyAccountKeys)
+        );
+    }
+
+    #[test]
+    fn test_invalid_program_id_index() {
+        // fee payer
+        let mut message = valid_v0_message();
+        message.instructions[0].program_id_index = 0;
+        assert_eq!(
+            sanitize_message_strict_detailed(&VersionedMessage::V0(message)),
+            Err(StrictSanitizeError::InvalidProgramIdIndex)
+        );
+
+        // loaded from a lookup table
+        let mut message = valid_v0_message();
+        message.instructions[0].program_id_index = 4;
+        assert_eq!(
+            sanitize_message_strict_detailed(&VersionedMessage::V0(message)),
+            Err(StrictSanitizeError::InvalidProgramIdIndex)
+        );
+    }
+
+    #[test]
+    fn test_writable_signer_program_id() {
+        let mut message = valid_v0_message();
+        message.header = header(3, 1, 1);
+        message.instructions[0].program_id_index = 1;
+        let message = VersionedMessage::V0(message);
+        // not caught by the regular sanitization
+        assert_eq!(message.sanitize(), Ok(()));
+        assert_eq!(
+            sanitize_message_strict_detailed(&message),
+            Err(StrictSanitizeError::WritableSignerProgramId)
+        );
+    }
+
+    #[test]
+    fn test_invalid_instruction_account_index() {
+        let mut message = valid_v0_message();
+        message.instructions[0].accounts.push(7);
+        assert_eq!(
+            sanitize_message_strict_detailed(&VersionedMessage::V0(message)),
+            Err(StrictSanitizeError::InvalidInstructionAccountIndex)
+        );
+    }
+
+    #[test]
+    fn test_transaction_too_large() {
+        let mut message = valid_v0_message();
+        let size = serialized_transaction_size(&VersionedMessage::V0(message.clone()));
+        let data_len = message.instructions[0].data.len();
+        // growing the data past 127 bytes adds a byte to its length prefix
+        message.instructions[0]
+            .data
+            .resize(data_len + PACKET_DATA_SIZE - size - 1, 0);
+        let message = VersionedMessage::V0(message);
+        assert_eq!(serialized_transaction_size(&message), PACKET_DATA_SIZE);
+        assert_eq!(sanitize_message_strict(&message), Ok(()));
+
+        let VersionedMessage::V0(mut message) = message else {
+            unreachable!()
+        };
+        message.instructions[0].data.push(0);
+        assert_eq!(
+            sanitize_message_strict_detailed(&VersionedMessage::V0(message)),
+            Err(StrictSanitizeError::TransactionTooLarge)
+        );
+    }
+
+    #[test]
+    fn test_serialized_transaction_size() {
+        let legacy = LegacyMessage {
+            header: header(1, 0, 1),
+            account_keys: vec![Address::new_unique(), Address::new_unique()],
+            recent_blockhash: Hash::new_unique(),
+            instructions: vec![CompiledInstruction {
+                program_id_index: 1,
+                accounts: vec![0; 200],
+                data: vec![7; 300],
+            }],
+        };
+        let v0 = valid_v0_message();
+        for message in [VersionedMessage::Legacy(legacy), VersionedMessage::V0(v0)] {
+            let expected = 1
+                + SIGNATURE_SIZE * message.header().num_required_signatures as usize
+                + bincode::serialize(&message).unwrap().len();
+            assert_eq!(serialized_transaction_size(&message), expected);
+        }
+    }
+
+    #[test]
+    fn test_into_sanitize_error() {
+        let mut message = valid_v0_message();
+        message.account_keys[2] = message.account_keys[1];
+        assert_eq!(
+            sanitize_message_strict(&VersionedMessage::V0(message)),
+            Err(SanitizeError::InvalidValue)
+        );
+
+        assert_eq!(
+            SanitizeError::from(StrictSanitizeError::TransactionTooLarge),
+            SanitizeError::ValueOutOfBounds
+        );
+        assert_eq!(
+            SanitizeError::from(StrictSanitizeError::InvalidProgramIdIndex),
+            SanitizeError::IndexOutOfBounds
+        );
+        assert_eq!(
+            SanitizeError::from(StrictSanitizeError::DuplicateAccountKeys),
+            SanitizeError::InvalidValue
+        );
+    }
+}
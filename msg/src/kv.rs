@@ -0,0 +1,270 @@
+//! Structured key/value logging.
+//!
+//! [`log_kv!`] logs a fixed set of `"key" => value` pairs as binary data with
+//! [`sol_log_data`], avoiding the formatting machinery used by [`msg!`]. Keys
+//! must be string literals and values are limited to integers, 32-byte
+//! addresses and short byte slices, so the cost of a log line is known at
+//! compile time.
+//!
+//! # Compute cost
+//!
+//! Unlike `msg!("amount={} slot={}", amount, slot)`, `log_kv!` does not go
+//! through `format!`, so it neither allocates nor converts integers to
+//! decimal inside the VM. The actual cost depends on the program and runtime
+//! version; use [`log_compute_units!`] around a call site to measure it.
+//!
+//! # Encoding
+//!
+//! The first field of the log is [`KV_LOG_TAG`]. Each pair then occupies two
+//! fields: the UTF-8 key, followed by a one-byte value tag and the value
+//! payload. Integers are encoded as little-endian 64-bit values. Off-chain,
+//! [`parse_kv_log`] decodes the fields back into pairs.
+//!
+//! [`sol_log_data`]: crate::sol_log_data
+//! [`msg!`]: crate::msg
+//! [`log_kv!`]: crate::log_kv
+//! [`log_compute_units!`]: crate::log_compute_units
+
+#[cfg(feature = "alloc")]
+use alloc::{string::String, vec::Vec};
+
+/// Marker emitted as the first field of every [`log_kv!`](crate::log_kv) log.
+pub const KV_LOG_TAG: &[u8] = b"kv";
+
+/// Maximum number of key/value pairs accepted by [`log_kv!`](crate::log_kv).
+pub const MAX_KV_PAIRS: usize = 8;
+
+/// Maximum length of a byte slice value; longer slices are truncated.
+pub const MAX_KV_BYTES_LEN: usize = 64;
+
+const TAG_U64: u8 = 0;
+const TAG_I64: u8 = 1;
+const TAG_ADDRESS: u8 = 2;
+const TAG_BYTES: u8 = 3;
+
+/// A value encoded for logging with [`log_kv!`](crate::log_kv).
+#[derive(Clone, Copy)]
+pub struct EncodedValue {
+    buf: [u8; 1 + MAX_KV_BYTES_LEN],
+    len: usize,
+}
+
+impl EncodedValue {
+    fn new(tag: u8, payload: &[u8]) -> Self {
+        let payload = &payload[..payload.len().min(MAX_KV_BYTES_LEN)];
+        let mut buf = [0; 1 + MAX_KV_BYTES_LEN];
+        buf[0] = tag;
+        buf[1..1 + payload.len()].copy_from_slice(payload);
+        Self {
+            buf,
+            len: 1 + payload.len(),
+        }
+    }
+
+    /// The tag byte followed by the value payload.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.buf[..self.len]
+    }
+}
+
+/// Types that can be logged as values with [`log_kv!`](crate::log_kv).
+pub trait KvValue {
+    fn encode_kv(&self) -> EncodedValue;
+}
+
+impl<T: KvValue + ?Sized> KvValue for &T {
+    fn encode_kv(&self) -> EncodedValue {
+        (**self).encode_kv()
+    }
+}
+
+macro_rules! impl_kv_value_unsigned {
+    ($($ty:ty),*) => {$(
+        impl KvValue for $ty {
+            fn encode_kv(&self) -> EncodedValue {
+                EncodedValue::new(TAG_U64, &(*self as u64).to_le_bytes())
+            }
+        }
+    )*};
+}
+
+macro_rules! impl_kv_value_signed {
+    ($($ty:ty),*) => {$(
+        impl KvValue for $ty {
+            fn encode_kv(&self) -> EncodedValue {
+                EncodedValue::new(TAG_I64, &(*self as i64).to_le_bytes())
+            }
+        }
+    )*};
+}
+
+impl_kv_value_unsigned!(u8, u16, u32, u64, usize);
+impl_kv_value_signed!(i8, i16, i32, i64, isize);
+
+/// 32-byte arrays are logged as addresses.
+impl KvValue for [u8; 32] {
+    fn encode_kv(&self) -> EncodedValue {
+        EncodedValue::new(TAG_ADDRESS, self)
+    }
+}
+
+/// Byte slices are logged as raw bytes, truncated to [`MAX_KV_BYTES_LEN`].
+impl KvValue for [u8] {
+    fn encode_kv(&self) -> EncodedValue {
+        EncodedValue::new(TAG_BYTES, self)
+    }
+}
+
+/// Log up to [`MAX_KV_PAIRS`] key/value pairs as binary data.
+///
+/// Keys must be string literals. Values may be any type implementing
+/// [`KvValue`]: integers, 32-byte addresses (`[u8; 32]`) and byte slices.
+///
+/// # Examples
+///
+/// ```
+/// use solana_msg::log_kv;
+///
+/// let owner = [7u8; 32];
+/// let amount: u64 = 42;
+/// log_kv!("owner" => owner, "amount" => amount, "memo" => &b"hello"[..]);
+/// ```
+///
+/// [`MAX_KV_PAIRS`]: crate::kv::MAX_KV_PAIRS
+/// [`KvValue`]: crate::kv::KvValue
+#[macro_export]
+macro_rules! log_kv {
+    ($($key:literal => $value:expr),+ $(,)?) => {{
+        const _: () = ::core::assert!(
+            [$($key),+].len() <= $crate::kv::MAX_KV_PAIRS,
+            "too many key/value pairs passed to log_kv!"
+        );
+        $crate::sol_log_data(&[
+            $crate::kv::KV_LOG_TAG,
+            $(
+                $key.as_bytes(),
+                $crate::kv::KvValue::encode_kv(&$value).as_bytes(),
+            )+
+        ]);
+    }};
+}
+
+/// A value decoded by [`parse_kv_log`].
+#[cfg(feature = "alloc")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LogValue {
+    U64(u64),
+    I64(i64),
+    Address([u8; 32]),
+    Bytes(Vec<u8>),
+}
+
+/// Decode the fields of a log emitted by [`log_kv!`](crate::log_kv).
+///
+/// Returns no pairs if the fields were not produced by `log_kv!`. Malformed
+/// pairs, such as a key without a value, are skipped.
+#[cfg(feature = "alloc")]
+pub fn parse_kv_log(data: &[&[u8]]) -> Vec<(String, LogValue)> {
+    let Some((&KV_LOG_TAG, fields)) = data.split_first() else {
+        return Vec::new();
+    };
+    fields
+        .chunks_exact(2)
+        .filter_map(|pair| {
+            let key = core::str::from_utf8(pair[0]).ok()?;
+            let (value_tag, payload) = pair[1].split_first()?;
+            let value = match *value_tag {
+                TAG_U64 => LogValue::U64(u64::from_le_bytes(payload.try_into().ok()?)),
+                TAG_I64 => LogValue::I64(i64::from_le_bytes(payload.try_into().ok()?)),
+                TAG_ADDRESS => LogValue::Address(payload.try_into().ok()?),
+                TAG_BYTES if payload.len() <= MAX_KV_BYTES_LEN => LogValue::Bytes(payload.to_vec()),
+                _ => return None,
+            };
+            Some((String::from(key), value))
+        })
+        .collect()
+}
+
+#[cfg(all(test, feature = "std", not(target_os = "solana")))]
+mod tests {
+    use {
+        super::*,
+        crate::tests::{capture_log_data, take_captured_log_data},
+        alloc::{string::ToString, vec},
+    };
+
+    fn parse_captured() -> Vec<Vec<(String, LogValue)>> {
+        take_captured_log_data()
+            .iter()
+            .map(|fields| {
+                let fields: Vec<&[u8]> = fields.iter().map(Vec::as_slice).collect();
+                parse_kv_log(&fields)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_log_kv_roundtrip() {
+        capture_log_data();
+        let owner = [7u8; 32];
+        let amount: u64 = u64::MAX;
+        let delta: i32 = -5;
+        let memo: &[u8] = b"hello";
+        log_kv!(
+            "owner" => owner,
+            "amount" => amount,
+            "delta" => delta,
+            "memo" => memo,
+            "index" => 3u8,
+        );
+
+        assert_eq!(
+            parse_captured(),
+            vec![vec![
+                ("owner".to_string(), LogValue::Address(owner)),
+                ("amount".to_string(), LogValue::U64(u64::MAX)),
+                ("delta".to_string(), LogValue::I64(-5)),
+                ("memo".to_string(), LogValue::Bytes(b"hello".to_vec())),
+                ("index".to_string(), LogValue::U64(3)),
+            ]]
+        );
+    }
+
+    #[test]
+    fn test_log_kv_truncates_long_bytes() {
+        capture_log_data();
+        let long = [1u8; MAX_KV_BYTES_LEN + 10];
+        log_kv!("long" => &long[..]);
+
+        assert_eq!(
+            parse_captured(),
+            vec![vec![(
+                "long".to_string(),
+                LogValue::Bytes(vec![1; MAX_KV_BYTES_LEN])
+            )]]
+        );
+    }
+
+    #[test]
+    fn test_parse_kv_log_skips_malformed() {
+        assert_eq!(parse_kv_log(&[]), vec![]);
+        assert_eq!(parse_kv_log(&[KV_LOG_TAG]), vec![]);
+        assert_eq!(parse_kv_log(&[b"other", b"k", &[TAG_U64; 9]]), vec![]);
+
+        let valid = ("k".to_string(), LogValue::I64(-1));
+        let i64_value = [&[TAG_I64][..], &(-1i64).to_le_bytes()].concat();
+        for malformed in [
+            // wrong integer width
+            &[b"k", &[TAG_U64, 1, 2][..]][..],
+            // unknown tag
+            &[b"k", &[0xff]],
+            // invalid utf-8 key
+            &[&[0xff], &[TAG_BYTES]],
+            // missing value
+            &[b"k"],
+        ] {
+            let fields = [&[KV_LOG_TAG, b"k", &i64_value][..], malformed].concat();
+            assert_eq!(parse_kv_log(&fields), vec![valid.clone()]);
+        }
+    }
+}
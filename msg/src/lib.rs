@@ -49,6 +49,19 @@ macro_rules! msg {
 #[cfg(target_os = "solana")]
 pub mod syscalls;
 
+pub mod kv;
+
+/// Print the remaining compute units available to the program.
+///
+/// Unlike [`msg!`], this macro does no formatting; it is a thin passthrough to
+/// [`sol_log_compute_units`].
+#[macro_export]
+macro_rules! log_compute_units {
+    () => {
+        $crate::sol_log_compute_units()
+    };
+}
+
 /// Print a string to the log.
 #[inline]
 pub fn sol_log(message: &str) {
@@ -63,3 +76,80 @@ pub fn sol_log(message: &str) {
     #[cfg(all(not(target_os = "solana"), not(feature = "std")))]
     core::hint::black_box(message);
 }
+
+/// Print some slices as base64-encoded binary data to the log.
+///
+/// On non-Solana targets the call is forwarded to the stub registered with
+/// [`set_sol_log_data_stub`], which `solana-sysvar`'s `program_stubs` does
+/// when its syscall stubs are first used. Without a registered stub the data
+/// is dropped.
+#[inline]
+pub fn sol_log_data(data: &[&[u8]]) {
+    #[cfg(target_os = "solana")]
+    unsafe {
+        syscalls::sol_log_data(data as *const _ as *const u8, data.len() as u64)
+    };
+
+    #[cfg(all(not(target_os = "solana"), feature = "std"))]
+    if let Some(stub) = *SOL_LOG_DATA_STUB.read().unwrap() {
+        stub(data);
+    }
+
+    #[cfg(all(not(target_os = "solana"), not(feature = "std")))]
+    core::hint::black_box(data);
+}
+
+#[cfg(all(not(target_os = "solana"), feature = "std"))]
+type SolLogDataStub = fn(&[&[u8]]);
+
+#[cfg(all(not(target_os = "solana"), feature = "std"))]
+static SOL_LOG_DATA_STUB: std::sync::RwLock<Option<SolLogDataStub>> = std::sync::RwLock::new(None);
+
+/// Set the function that [`sol_log_data`] forwards to on non-Solana targets,
+/// returning the previous one.
+#[cfg(all(not(target_os = "solana"), feature = "std"))]
+pub fn set_sol_log_data_stub(stub: SolLogDataStub) -> Option<SolLogDataStub> {
+    SOL_LOG_DATA_STUB.write().unwrap().replace(stub)
+}
+
+/// Print the remaining compute units available to the program.
+#[inline]
+pub fn sol_log_compute_units() {
+    #[cfg(target_os = "solana")]
+    unsafe {
+        syscalls::sol_log_compute_units_();
+    }
+
+    #[cfg(all(not(target_os = "solana"), feature = "std"))]
+    std::println!("sol_log_compute_units() not available");
+}
+
+#[cfg(all(test, feature = "std", not(target_os = "solana")))]
+mod tests {
+    use {
+        core::cell::RefCell,
+        std::{vec, vec::Vec},
+    };
+
+    std::thread_local! {
+        static CAPTURED_LOG_DATA: RefCell<Vec<Vec<Vec<u8>>>> = const { RefCell::new(vec![]) };
+    }
+
+    fn record_log_data(data: &[&[u8]]) {
+        CAPTURED_LOG_DATA.with(|captured| {
+            captured
+                .borrow_mut()
+                .push(data.iter().map(|f| f.to_vec()).collect())
+        });
+    }
+
+    /// Record the fields passed to [`crate::sol_log_data`] per thread.
+    pub(crate) fn capture_log_data() {
+        crate::set_sol_log_data_stub(record_log_data);
+    }
+
+    /// Drain the fields passed to [`crate::sol_log_data`] on this thread.
+    pub(crate) fn take_captured_log_data() -> Vec<Vec<Vec<u8>>> {
+        CAPTURED_LOG_DATA.with(|captured| captured.take())
+    }
+}
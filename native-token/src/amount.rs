@@ -0,0 +1,248 @@
+//! Conversion between raw token amounts and decimal amount strings.
+//!
+//! A token with `decimals` decimals counts its amounts in units of
+//! `10^-decimals` tokens, so that the raw amount `1_500` of a token with 3
+//! decimals is the amount `"1.5"`. The conversions only use integer
+//! arithmetic, so they are exact for every raw amount.
+
+/// The maximum number of decimals of an amount parsed into a `u64`, since
+/// `10^19` is the largest power of ten that fits in a `u64`.
+pub const MAX_DECIMALS: u8 = 19;
+
+/// What to do with an amount string with more fractional digits than the
+/// decimals of the token.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum TruncationPolicy {
+    /// Fail to parse the amount.
+    #[default]
+    Reject,
+    /// Ignore the excess digits, rounding the amount towards zero.
+    Truncate,
+}
+
+/// Convert a decimal amount string into a raw amount of a token with
+/// `decimals` decimals.
+///
+/// The amount is made of ASCII digits with an optional `.` separating the
+/// fractional digits, either side of which may be empty but not both.
+/// Returns `None` if the amount is malformed, has more fractional digits than
+/// `decimals`, does not fit in a `u64`, or if `decimals` is greater than
+/// [`MAX_DECIMALS`].
+///
+/// # Examples
+///
+/// ```
+/// use solana_native_token::amount_str_to_raw;
+///
+/// assert_eq!(amount_str_to_raw("1.5", 6), Some(1_500_000));
+/// assert_eq!(amount_str_to_raw(".25", 2), Some(25));
+/// assert_eq!(amount_str_to_raw("0.001", 2), None);
+/// ```
+pub fn amount_str_to_raw(amount: &str, decimals: u8) -> Option<u64> {
+    amount_str_to_raw_with_policy(amount, decimals, TruncationPolicy::Reject)
+}
+
+/// Convert a decimal amount string into a raw amount like
+/// [`amount_str_to_raw`], handling excess fractional digits according to
+/// `policy`.
+///
+/// Truncated digits must still be ASCII digits.
+pub fn amount_str_to_raw_with_policy(
+    amount: &str,
+    decimals: u8,
+    policy: TruncationPolicy,
+) -> Option<u64> {
+    if decimals > MAX_DECIMALS {
+        return None;
+    }
+    let (integer, fraction) = amount.split_once('.').unwrap_or((amount, ""));
+    if integer.is_empty() && fraction.is_empty() {
+        return None;
+    }
+    if !integer
+        .bytes()
+        .chain(fraction.bytes())
+        .all(|b| b.is_ascii_digit())
+    {
+        return None;
+    }
+
+    let decimals = usize::from(decimals);
+    let fraction = match fraction.get(decimals..) {
+        Some(excess) if !excess.is_empty() => match policy {
+            TruncationPolicy::Reject => return None,
+            TruncationPolicy::Truncate => &fraction[..decimals],
+        },
+        _ => fraction,
+    };
+
+    let mut raw = 0u64;
+    for digit in integer.bytes() {
+        raw = raw.checked_mul(10)?.checked_add(u64::from(digit - b'0'))?;
+    }
+    raw = raw.checked_mul(10u64.pow(decimals as u32))?;
+    let mut fraction_raw = 0u64;
+    for digit in fraction.bytes() {
+        fraction_raw = fraction_raw * 10 + u64::from(digit - b'0');
+    }
+    // at most `decimals` digits, cannot overflow
+    fraction_raw *= 10u64.pow((decimals - fraction.len()) as u32);
+    raw.checked_add(fraction_raw)
+}
+
+/// Convert a raw amount of a token with `decimals` decimals into a decimal
+/// amount string.
+///
+/// Trailing zeros of the fractional digits are left out, as is the `.` of
+/// whole amounts, so that the output parses back into `raw` with
+/// [`amount_str_to_raw`] for `decimals` up to [`MAX_DECIMALS`].
+///
+/// # Examples
+///
+/// ```
+/// use solana_native_token::raw_to_amount_string;
+///
+/// assert_eq!(raw_to_amount_string(1_500_000, 6), "1.5");
+/// assert_eq!(raw_to_amount_string(42, 0), "42");
+/// assert_eq!(raw_to_amount_string(7, 3), "0.007");
+/// ```
+pub fn raw_to_amount_string(raw: u64, decimals: u8) -> String {
+    let decimals = usize::from(decimals);
+    let digits = format!("{raw:0>width$}", width = decimals + 1);
+    let (integer, fraction) = digits.split_at(digits.len() - decimals);
+    let fraction = fraction.trim_end_matches('0');
+    if fraction.is_empty() {
+        integer.to_string()
+    } else {
+        format!("{integer}.{fraction}")
+    }
+}
+
+/// Add two decimal amount strings of a token with `decimals` decimals.
+///
+/// Returns `None` if either amount fails to parse with
+/// [`amount_str_to_raw`] or if the sum does not fit in a `u64`.
+pub fn checked_ui_add(a: &str, b: &str, decimals: u8) -> Option<String> {
+    let sum = amount_str_to_raw(a, decimals)?.checked_add(amount_str_to_raw(b, decimals)?)?;
+    Some(raw_to_amount_string(sum, decimals))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_boundaries() {
+        for decimals in 0..=MAX_DECIMALS {
+            let one = 10u64.pow(u32::from(decimals));
+            let smallest = if decimals == 0 {
+                "1".to_string()
+            } else {
+                format!("0.{}1", "0".repeat(usize::from(decimals) - 1))
+            };
+            assert_eq!(amount_str_to_raw(&smallest, decimals), Some(1));
+            assert_eq!(raw_to_amount_string(1, decimals), smallest);
+            assert_eq!(amount_str_to_raw("1", decimals), Some(one));
+            assert_eq!(raw_to_amount_string(one, decimals), "1");
+            assert_eq!(amount_str_to_raw("0", decimals), Some(0));
+            assert_eq!(raw_to_amount_string(0, decimals), "0");
+
+            let max = raw_to_amount_string(u64::MAX, decimals);
+            assert_eq!(amount_str_to_raw(&max, decimals), Some(u64::MAX));
+            assert_eq!(
+                max.replace('.', ""),
+                u64::MAX.to_string(),
+                "decimals {decimals}"
+            );
+            // one more than u64::MAX
+            let digits = (u128::from(u64::MAX) + 1).to_string();
+            let (integer, fraction) = digits.split_at(digits.len() - usize::from(decimals));
+            let above_max = format!("{integer}.{fraction}");
+            assert_eq!(amount_str_to_raw(&above_max, decimals), None);
+        }
+        assert_eq!(raw_to_amount_string(u64::MAX, 19), "1.8446744073709551615");
+        assert_eq!(amount_str_to_raw("1.8446744073709551616", 19), None);
+        assert_eq!(amount_str_to_raw("18446744073709551615", 0), Some(u64::MAX));
+        assert_eq!(amount_str_to_raw("18446744073709551616", 0), None);
+        assert_eq!(amount_str_to_raw("1", 20), None);
+        assert_eq!(amount_str_to_raw("0", MAX_DECIMALS + 1), None);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for decimals in 0..=MAX_DECIMALS {
+            for raw in [
+                0,
+                1,
+                9,
+                10,
+                11,
+                1_000,
+                123_456_789,
+                u64::MAX / 7,
+                u64::MAX - 1,
+            ] {
+                let amount = raw_to_amount_string(raw, decimals);
+                assert_eq!(amount_str_to_raw(&amount, decimals), Some(raw));
+            }
+        }
+    }
+
+    #[test]
+    fn test_formats() {
+        assert_eq!(amount_str_to_raw("1.", 2), Some(100));
+        assert_eq!(amount_str_to_raw(".5", 2), Some(50));
+        assert_eq!(amount_str_to_raw("007.50", 2), Some(750));
+        assert_eq!(amount_str_to_raw("", 2), None);
+        assert_eq!(amount_str_to_raw(".", 2), None);
+        assert_eq!(amount_str_to_raw("1.2.3", 2), None);
+        assert_eq!(amount_str_to_raw("-1", 2), None);
+        assert_eq!(amount_str_to_raw("+1", 2), None);
+        assert_eq!(amount_str_to_raw(" 1", 2), None);
+        assert_eq!(amount_str_to_raw("1e3", 2), None);
+        assert_eq!(amount_str_to_raw("1.", 0), Some(1));
+        assert_eq!(raw_to_amount_string(1_230, 3), "1.23");
+        assert_eq!(raw_to_amount_string(100, 2), "1");
+    }
+
+    #[test]
+    fn test_truncation_policy() {
+        assert_eq!(amount_str_to_raw("1.239", 2), None);
+        assert_eq!(amount_str_to_raw("0.5", 0), None);
+        assert_eq!(
+            amount_str_to_raw_with_policy("1.239", 2, TruncationPolicy::Truncate),
+            Some(123)
+        );
+        assert_eq!(
+            amount_str_to_raw_with_policy("0.5", 0, TruncationPolicy::Truncate),
+            Some(0)
+        );
+        assert_eq!(
+            amount_str_to_raw_with_policy("1.2300", 2, TruncationPolicy::Reject),
+            None
+        );
+        // excess digits must still be digits
+        assert_eq!(
+            amount_str_to_raw_with_policy("1.23x", 2, TruncationPolicy::Truncate),
+            None
+        );
+        assert_eq!(TruncationPolicy::default(), TruncationPolicy::Reject);
+    }
+
+    #[test]
+    fn test_checked_ui_add() {
+        assert_eq!(checked_ui_add("1.5", "2.25", 2).as_deref(), Some("3.75"));
+        assert_eq!(checked_ui_add("0.1", "0.2", 9).as_deref(), Some("0.3"));
+        assert_eq!(checked_ui_add("1", "2", 0).as_deref(), Some("3"));
+        assert_eq!(checked_ui_add("1.001", "1", 2), None);
+        assert_eq!(checked_ui_add("x", "1", 2), None);
+        assert_eq!(
+            checked_ui_add("18446744073.709551615", "0", 9).as_deref(),
+            Some("18446744073.709551615")
+        );
+        assert_eq!(
+            checked_ui_add("18446744073.709551615", "0.000000001", 9),
+            None
+        );
+    }
+}
@@ -0,0 +1,224 @@
+//! Compile-time SOL amounts.
+//!
+//! [`sol_to_lamports_const`] and the [`lamports!`](crate::lamports) macro
+//! turn amounts written in SOL into lamports in const contexts, so a fixed
+//! price can be written as `lamports!(2.5 SOL)` rather than as a lamport
+//! count with a comment. Overflow and amounts more precise than a lamport
+//! fail the const evaluation, and thus the build.
+
+use crate::LAMPORTS_PER_SOL;
+
+/// Convert `whole` SOL plus `fractional_nanos` billionths of a SOL into
+/// lamports.
+///
+/// # Panics
+///
+/// Panics if `fractional_nanos` is not less than [`LAMPORTS_PER_SOL`], or if
+/// the amount does not fit in a `u64`. In a const context, the panic is a
+/// compile error.
+///
+/// # Examples
+///
+/// ```
+/// use solana_native_token::sol_to_lamports_const;
+///
+/// const PRICE: u64 = sol_to_lamports_const(2, 500_000_000);
+/// assert_eq!(PRICE, 2_500_000_000);
+/// ```
+///
+/// ```compile_fail
+/// use solana_native_token::sol_to_lamports_const;
+///
+/// // more than `u64::MAX` lamports
+/// const PRICE: u64 = sol_to_lamports_const(18_446_744_074, 0);
+/// let _ = PRICE;
+/// ```
+pub const fn sol_to_lamports_const(whole: u64, fractional_nanos: u32) -> u64 {
+    if fractional_nanos as u64 >= LAMPORTS_PER_SOL {
+        panic!("fractional nanos must be less than a SOL");
+    }
+    match whole.checked_mul(LAMPORTS_PER_SOL) {
+        Some(lamports) => match lamports.checked_add(fractional_nanos as u64) {
+            Some(lamports) => lamports,
+            None => panic!("SOL amount overflows u64 lamports"),
+        },
+        None => panic!("SOL amount overflows u64 lamports"),
+    }
+}
+
+/// Whether `lamports` is a whole number of SOL.
+#[allow(clippy::manual_is_multiple_of)]
+pub const fn is_whole_sol(lamports: u64) -> bool {
+    lamports % LAMPORTS_PER_SOL == 0
+}
+
+/// Parse the decimal literal `amount` into units of `10^-decimals`, for
+/// [`lamports!`](crate::lamports).
+///
+/// Panics, failing the const evaluation, if the literal is not a plain
+/// decimal number, has nonzero digits beyond `decimals`, or overflows.
+pub const fn parse_amount(amount: &str, decimals: u32) -> u64 {
+    let bytes = amount.as_bytes();
+    let mut raw = 0u64;
+    let mut fraction_digits = None;
+    let mut digits = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        let byte = bytes[i];
+        i += 1;
+        match byte {
+            b'_' => continue,
+            b'.' if fraction_digits.is_none() => {
+                fraction_digits = Some(0);
+                continue;
+            }
+            b'0'..=b'9' => {}
+            _ => panic!("amount must be a decimal number literal"),
+        }
+        digits += 1;
+        let digit = (byte - b'0') as u64;
+        if let Some(count) = fraction_digits {
+            if count == decimals {
+                if digit != 0 {
+                    panic!("amount is more precise than a lamport");
+                }
+                continue;
+            }
+            fraction_digits = Some(count + 1);
+        }
+        raw = match raw.checked_mul(10) {
+            Some(raw) => match raw.checked_add(digit) {
+                Some(raw) => raw,
+                None => panic!("amount overflows u64 lamports"),
+            },
+            None => panic!("amount overflows u64 lamports"),
+        };
+    }
+    if digits == 0 {
+        panic!("amount must be a decimal number literal");
+    }
+    let mut scale = match fraction_digits {
+        Some(count) => decimals - count,
+        None => decimals,
+    };
+    while scale > 0 {
+        raw = match raw.checked_mul(10) {
+            Some(raw) => raw,
+            None => panic!("amount overflows u64 lamports"),
+        };
+        scale -= 1;
+    }
+    raw
+}
+
+/// Convert a literal amount of SOL or mSOL into lamports at compile time.
+///
+/// The amount is a decimal literal followed by its unit, `SOL` or `mSOL`
+/// (thousandths of a SOL). It is parsed exactly, so an amount more precise
+/// than a lamport, or that overflows a `u64`, fails to compile.
+///
+/// # Examples
+///
+/// ```
+/// use solana_native_token::lamports;
+///
+/// const PRICE: u64 = lamports!(2.5 SOL);
+/// assert_eq!(PRICE, 2_500_000_000);
+/// assert_eq!(lamports!(250 mSOL), 250_000_000);
+/// assert_eq!(lamports!(0.000_000_001 SOL), 1);
+/// ```
+///
+/// ```compile_fail
+/// use solana_native_token::lamports;
+///
+/// // a tenth of a lamport
+/// let _ = lamports!(0.0000000001 SOL);
+/// ```
+///
+/// ```compile_fail
+/// use solana_native_token::lamports;
+///
+/// // more than `u64::MAX` lamports
+/// let _ = lamports!(18446744073.709551616 SOL);
+/// ```
+///
+/// ```compile_fail
+/// use solana_native_token::lamports;
+///
+/// // not a plain decimal number
+/// let _ = lamports!(1e9 mSOL);
+/// ```
+#[macro_export]
+macro_rules! lamports {
+    ($amount:literal SOL) => {{
+        const LAMPORTS: u64 = $crate::__private::parse_amount(::core::stringify!($amount), 9);
+        LAMPORTS
+    }};
+    ($amount:literal mSOL) => {{
+        const LAMPORTS: u64 = $crate::__private::parse_amount(::core::stringify!($amount), 6);
+        LAMPORTS
+    }};
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sol_to_lamports_const() {
+        const PRICE: u64 = sol_to_lamports_const(2, 500_000_000);
+        assert_eq!(PRICE, 2_500_000_000);
+        assert_eq!(sol_to_lamports_const(0, 1), 1);
+        assert_eq!(sol_to_lamports_const(0, 999_999_999), 999_999_999);
+        assert_eq!(
+            sol_to_lamports_const(u64::MAX / LAMPORTS_PER_SOL, 709_551_615),
+            u64::MAX
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "SOL amount overflows u64 lamports")]
+    fn test_sol_to_lamports_const_overflow() {
+        sol_to_lamports_const(u64::MAX / LAMPORTS_PER_SOL, 709_551_616);
+    }
+
+    #[test]
+    #[should_panic(expected = "SOL amount overflows u64 lamports")]
+    fn test_sol_to_lamports_const_whole_overflow() {
+        sol_to_lamports_const(u64::MAX / LAMPORTS_PER_SOL + 1, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "fractional nanos must be less than a SOL")]
+    fn test_sol_to_lamports_const_fractional_nanos() {
+        sol_to_lamports_const(1, 1_000_000_000);
+    }
+
+    #[test]
+    fn test_is_whole_sol() {
+        assert!(is_whole_sol(0));
+        assert!(is_whole_sol(LAMPORTS_PER_SOL));
+        assert!(is_whole_sol(lamports!(18 SOL)));
+        assert!(!is_whole_sol(1));
+        assert!(!is_whole_sol(lamports!(1.5 SOL)));
+        assert!(!is_whole_sol(u64::MAX));
+    }
+
+    #[test]
+    fn test_lamports() {
+        assert_eq!(lamports!(0 SOL), 0);
+        assert_eq!(lamports!(1 SOL), LAMPORTS_PER_SOL);
+        assert_eq!(lamports!(2.5 SOL), 2_500_000_000);
+        assert_eq!(lamports!(0.1 SOL), 100_000_000);
+        assert_eq!(lamports!(0.000000001 SOL), 1);
+        assert_eq!(lamports!(0.0000000010 SOL), 1);
+        assert_eq!(lamports!(1_000 SOL), 1_000_000_000_000);
+        assert_eq!(lamports!(8.50228288 SOL), 8_502_282_880);
+        assert_eq!(lamports!(18446744073.709551615 SOL), u64::MAX);
+
+        assert_eq!(lamports!(250 mSOL), 250_000_000);
+        assert_eq!(lamports!(0.5 mSOL), 500_000);
+        assert_eq!(lamports!(0.000001 mSOL), 1);
+        assert_eq!(lamports!(1 mSOL), lamports!(0.001 SOL));
+    }
+}
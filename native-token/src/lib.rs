@@ -3,30 +3,33 @@
 #![cfg_attr(docsrs, feature(doc_cfg))]
 #![allow(clippy::arithmetic_side_effects)]
 
+mod amount;
+mod const_amount;
+
+pub use {
+    amount::{
+        amount_str_to_raw, amount_str_to_raw_with_policy, checked_ui_add, raw_to_amount_string,
+        TruncationPolicy, MAX_DECIMALS,
+    },
+    const_amount::{is_whole_sol, sol_to_lamports_const},
+};
+
+#[doc(hidden)]
+pub mod __private {
+    pub use crate::const_amount::parse_amount;
+}
+
 /// There are 10^9 lamports in one SOL
 pub const LAMPORTS_PER_SOL: u64 = 1_000_000_000;
-const SOL_DECIMALS: usize = 9;
+const SOL_DECIMALS: u8 = 9;
 
 /// Convert native tokens (SOL) into fractional native tokens (lamports)
+///
+/// This is [`amount_str_to_raw`] with the 9 decimals of SOL, so signs,
+/// non-digit characters and digits beyond the precision of a lamport are
+/// rejected.
 pub fn sol_str_to_lamports(sol_str: &str) -> Option<u64> {
-    if sol_str == "." {
-        None
-    } else {
-        let (sol, lamports) = sol_str.split_once('.').unwrap_or((sol_str, ""));
-        let sol = if sol.is_empty() {
-            0
-        } else {
-            sol.parse::<u64>().ok()?
-        };
-        let lamports = if lamports.is_empty() {
-            0
-        } else {
-            format!("{lamports:0<9}")[..SOL_DECIMALS].parse().ok()?
-        };
-        LAMPORTS_PER_SOL
-            .checked_mul(sol)
-            .and_then(|x| x.checked_add(lamports))
-    }
+    amount_str_to_raw(sol_str, SOL_DECIMALS)
 }
 
 use std::fmt::{Debug, Display, Formatter, Result};
@@ -86,5 +89,6 @@ mod tests {
         assert_eq!(None, sol_str_to_lamports("-0.000000001"));
         // i64::MIN as string, error
         assert_eq!(None, sol_str_to_lamports("-9223372036.854775808"));
+        assert_eq!(None, sol_str_to_lamports("."));
     }
 }
@@ -14,6 +14,12 @@ static_assertions::const_assert_eq!(OffchainMessage::HEADER_LEN, 17);
 static_assertions::const_assert_eq!(v0::OffchainMessage::MAX_LEN, 65515);
 #[cfg(test)]
 static_assertions::const_assert_eq!(v0::OffchainMessage::MAX_LEN_LEDGER, 1212);
+#[cfg(test)]
+static_assertions::const_assert_eq!(v1::OffchainMessage::HEADER_LEN, 43);
+#[cfg(test)]
+static_assertions::const_assert_eq!(v1::OffchainMessage::MAX_LEN, 65475);
+#[cfg(test)]
+static_assertions::const_assert_eq!(v1::OffchainMessage::MAX_LEN_LEDGER, 1172);
 
 /// Check if given bytes contain only printable ASCII characters
 pub fn is_printable_ascii(data: &[u8]) -> bool {
@@ -158,9 +164,172 @@ pub mod v0 {
     }
 }
 
+#[allow(clippy::arithmetic_side_effects)]
+pub mod v1 {
+    use {
+        super::{is_printable_ascii, is_utf8, MessageFormat, OffchainMessage as Base},
+        solana_hash::Hash,
+        solana_packet::PACKET_DATA_SIZE,
+        solana_sanitize::SanitizeError,
+        solana_sha256_hasher::Hasher,
+    };
+
+    /// Length of the application domain field
+    pub const APPLICATION_DOMAIN_LEN: usize = 32;
+
+    /// OffchainMessage Version 1.
+    /// Binds a non-empty valid message to an application domain and an
+    /// expiry timestamp, so a signature can't be replayed across applications
+    /// or after it expires.
+    #[derive(Debug, PartialEq, Eq, Clone)]
+    pub struct OffchainMessage {
+        application_domain: [u8; APPLICATION_DOMAIN_LEN],
+        expiry: u64,
+        format: MessageFormat,
+        message: Vec<u8>,
+    }
+
+    impl OffchainMessage {
+        // Header Length = Application Domain (32) + Expiry (8) + Message Format (1)
+        //                 + Message Length (2)
+        pub const HEADER_LEN: usize = APPLICATION_DOMAIN_LEN + 8 + 1 + 2;
+        // Max length of the OffchainMessage
+        pub const MAX_LEN: usize = u16::MAX as usize - Base::HEADER_LEN - Self::HEADER_LEN;
+        // Max Length of the OffchainMessage supported by the Ledger
+        pub const MAX_LEN_LEDGER: usize = PACKET_DATA_SIZE - Base::HEADER_LEN - Self::HEADER_LEN;
+
+        /// Construct a new OffchainMessage object from the given application
+        /// domain, expiry unix timestamp and message
+        pub fn new(
+            application_domain: [u8; APPLICATION_DOMAIN_LEN],
+            expiry: u64,
+            message: &[u8],
+        ) -> Result<Self, SanitizeError> {
+            let format = if message.is_empty() {
+                return Err(SanitizeError::InvalidValue);
+            } else if message.len() <= OffchainMessage::MAX_LEN_LEDGER {
+                if is_printable_ascii(message) {
+                    MessageFormat::RestrictedAscii
+                } else if is_utf8(message) {
+                    MessageFormat::LimitedUtf8
+                } else {
+                    return Err(SanitizeError::InvalidValue);
+                }
+            } else if message.len() <= OffchainMessage::MAX_LEN {
+                if is_utf8(message) {
+                    MessageFormat::ExtendedUtf8
+                } else {
+                    return Err(SanitizeError::InvalidValue);
+                }
+            } else {
+                return Err(SanitizeError::ValueOutOfBounds);
+            };
+            Ok(Self {
+                application_domain,
+                expiry,
+                format,
+                message: message.to_vec(),
+            })
+        }
+
+        /// Serialize the message to bytes, including the full header
+        pub fn serialize(&self, data: &mut Vec<u8>) -> Result<(), SanitizeError> {
+            // invalid messages shouldn't be possible, but a quick sanity check never hurts
+            assert!(!self.message.is_empty() && self.message.len() <= Self::MAX_LEN);
+            data.reserve(Self::HEADER_LEN.saturating_add(self.message.len()));
+            // application domain
+            data.extend_from_slice(&self.application_domain);
+            // expiry
+            data.extend_from_slice(&self.expiry.to_le_bytes());
+            // format
+            data.push(self.format.into());
+            // message length
+            data.extend_from_slice(&(self.message.len() as u16).to_le_bytes());
+            // message
+            data.extend_from_slice(&self.message);
+            Ok(())
+        }
+
+        /// Deserialize the message from bytes that include a full header
+        pub fn deserialize(data: &[u8]) -> Result<Self, SanitizeError> {
+            // validate data length
+            if data.len() <= Self::HEADER_LEN || data.len() > Self::HEADER_LEN + Self::MAX_LEN {
+                return Err(SanitizeError::ValueOutOfBounds);
+            }
+            // decode header
+            let (application_domain, rest) = data.split_at(APPLICATION_DOMAIN_LEN);
+            let application_domain = <[u8; APPLICATION_DOMAIN_LEN]>::try_from(application_domain)
+                .map_err(|_| SanitizeError::InvalidValue)?;
+            let (expiry, rest) = rest.split_at(8);
+            let expiry = u64::from_le_bytes(
+                <[u8; 8]>::try_from(expiry).map_err(|_| SanitizeError::InvalidValue)?,
+            );
+            let format =
+                MessageFormat::try_from(rest[0]).map_err(|_| SanitizeError::InvalidValue)?;
+            let message_len = u16::from_le_bytes([rest[1], rest[2]]) as usize;
+            // check header
+            if Self::HEADER_LEN.saturating_add(message_len) != data.len() {
+                return Err(SanitizeError::InvalidValue);
+            }
+            let message = &data[Self::HEADER_LEN..];
+            // check format
+            let is_valid = match format {
+                MessageFormat::RestrictedAscii => {
+                    (message.len() <= Self::MAX_LEN_LEDGER) && is_printable_ascii(message)
+                }
+                MessageFormat::LimitedUtf8 => {
+                    (message.len() <= Self::MAX_LEN_LEDGER) && is_utf8(message)
+                }
+                MessageFormat::ExtendedUtf8 => (message.len() <= Self::MAX_LEN) && is_utf8(message),
+            };
+
+            if is_valid {
+                Ok(Self {
+                    application_domain,
+                    expiry,
+                    format,
+                    message: message.to_vec(),
+                })
+            } else {
+                Err(SanitizeError::InvalidValue)
+            }
+        }
+
+        /// Compute the SHA256 hash of the serialized off-chain message
+        pub fn hash(serialized_message: &[u8]) -> Result<Hash, SanitizeError> {
+            let mut hasher = Hasher::default();
+            hasher.hash(serialized_message);
+            Ok(hasher.result())
+        }
+
+        /// Check whether the message has expired at the given unix timestamp.
+        /// The message is still valid at exactly its expiry timestamp.
+        pub fn is_expired(&self, unix_timestamp: u64) -> bool {
+            unix_timestamp > self.expiry
+        }
+
+        pub fn get_application_domain(&self) -> &[u8; APPLICATION_DOMAIN_LEN] {
+            &self.application_domain
+        }
+
+        pub fn get_expiry(&self) -> u64 {
+            self.expiry
+        }
+
+        pub fn get_format(&self) -> MessageFormat {
+            self.format
+        }
+
+        pub fn get_message(&self) -> &Vec<u8> {
+            &self.message
+        }
+    }
+}
+
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub enum OffchainMessage {
     V0(v0::OffchainMessage),
+    V1(v1::OffchainMessage),
 }
 
 impl OffchainMessage {
@@ -176,6 +345,20 @@ impl OffchainMessage {
         }
     }
 
+    /// Construct a new version 1 OffchainMessage object bound to the given
+    /// application domain and expiry unix timestamp
+    pub fn new_v1(
+        application_domain: [u8; v1::APPLICATION_DOMAIN_LEN],
+        expiry: u64,
+        message: &[u8],
+    ) -> Result<Self, SanitizeError> {
+        Ok(Self::V1(v1::OffchainMessage::new(
+            application_domain,
+            expiry,
+            message,
+        )?))
+    }
+
     /// Serialize the off-chain message to bytes including full header
     pub fn serialize(&self) -> Result<Vec<u8>, SanitizeError> {
         // serialize signing domain
@@ -187,6 +370,10 @@ impl OffchainMessage {
                 data.push(0);
                 msg.serialize(&mut data)?;
             }
+            Self::V1(msg) => {
+                data.push(1);
+                msg.serialize(&mut data)?;
+            }
         }
         Ok(data)
     }
@@ -200,6 +387,7 @@ impl OffchainMessage {
         let data = &data[Self::SIGNING_DOMAIN.len().saturating_add(1)..];
         match version {
             0 => Ok(Self::V0(v0::OffchainMessage::deserialize(data)?)),
+            1 => Ok(Self::V1(v1::OffchainMessage::deserialize(data)?)),
             _ => Err(SanitizeError::ValueOutOfBounds),
         }
     }
@@ -208,24 +396,37 @@ impl OffchainMessage {
     pub fn hash(&self) -> Result<Hash, SanitizeError> {
         match self {
             Self::V0(_) => v0::OffchainMessage::hash(&self.serialize()?),
+            Self::V1(_) => v1::OffchainMessage::hash(&self.serialize()?),
         }
     }
 
     pub fn get_version(&self) -> u8 {
         match self {
             Self::V0(_) => 0,
+            Self::V1(_) => 1,
         }
     }
 
     pub fn get_format(&self) -> MessageFormat {
         match self {
             Self::V0(msg) => msg.get_format(),
+            Self::V1(msg) => msg.get_format(),
         }
     }
 
     pub fn get_message(&self) -> &Vec<u8> {
         match self {
             Self::V0(msg) => msg.get_message(),
+            Self::V1(msg) => msg.get_message(),
+        }
+    }
+
+    /// Check whether the message has expired at the given unix timestamp.
+    /// Version 0 messages carry no expiry and never expire.
+    pub fn is_expired(&self, unix_timestamp: u64) -> bool {
+        match self {
+            Self::V0(_) => false,
+            Self::V1(msg) => msg.is_expired(unix_timestamp),
         }
     }
 
@@ -236,6 +437,9 @@ impl OffchainMessage {
 
     #[cfg(feature = "verify")]
     /// Verify that the message signature is valid for the given public key
+    ///
+    /// The expiry of version 1 messages is not checked, use
+    /// [`verify_at`](Self::verify_at) for that.
     pub fn verify(
         &self,
         signer: &solana_pubkey::Pubkey,
@@ -243,6 +447,21 @@ impl OffchainMessage {
     ) -> Result<bool, SanitizeError> {
         Ok(signature.verify(signer.as_ref(), &self.serialize()?))
     }
+
+    #[cfg(feature = "verify")]
+    /// Verify that the message signature is valid for the given public key
+    /// and that the message has not expired at the given unix timestamp
+    pub fn verify_at(
+        &self,
+        signer: &solana_pubkey::Pubkey,
+        signature: &Signature,
+        unix_timestamp: u64,
+    ) -> Result<bool, SanitizeError> {
+        if self.is_expired(unix_timestamp) {
+            return Ok(false);
+        }
+        self.verify(signer, signature)
+    }
 }
 
 #[cfg(test)]
@@ -299,4 +518,177 @@ mod tests {
         let signature = message.sign(&keypair).unwrap();
         assert!(message.verify(&keypair.pubkey(), &signature).unwrap());
     }
+
+    #[test]
+    fn test_offchain_message_v1_round_trip() {
+        let message = OffchainMessage::new_v1([7; 32], 1_700_000_000, b"Test Message").unwrap();
+        assert_eq!(message.get_version(), 1);
+        assert_eq!(message.get_format(), MessageFormat::RestrictedAscii);
+        assert_eq!(message.get_message().as_slice(), b"Test Message");
+        let OffchainMessage::V1(ref msg) = message else {
+            panic!("expected a v1 message");
+        };
+        assert_eq!(msg.get_application_domain(), &[7; 32]);
+        assert_eq!(msg.get_expiry(), 1_700_000_000);
+
+        let serialized = message.serialize().unwrap();
+        assert_eq!(&serialized[..16], OffchainMessage::SIGNING_DOMAIN);
+        assert_eq!(serialized[16], 1);
+        assert_eq!(&serialized[17..49], &[7; 32]);
+        assert_eq!(&serialized[49..57], &1_700_000_000u64.to_le_bytes());
+        assert_eq!(serialized[57], 0);
+        assert_eq!(&serialized[58..60], &12u16.to_le_bytes());
+        assert_eq!(&serialized[60..], b"Test Message");
+        assert_eq!(message, OffchainMessage::deserialize(&serialized).unwrap());
+
+        let message = OffchainMessage::new_v1([1; 32], 0, "Тестовое сообщение".as_bytes()).unwrap();
+        assert_eq!(message.get_format(), MessageFormat::LimitedUtf8);
+        let serialized = message.serialize().unwrap();
+        assert_eq!(message, OffchainMessage::deserialize(&serialized).unwrap());
+        assert_ne!(
+            message.hash().unwrap(),
+            OffchainMessage::new(0, "Тестовое сообщение".as_bytes())
+                .unwrap()
+                .hash()
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_offchain_message_cross_version_rejection() {
+        let v0 = OffchainMessage::new(0, b"Test Message").unwrap();
+        let mut serialized = v0.serialize().unwrap();
+        serialized[16] = 1;
+        assert!(OffchainMessage::deserialize(&serialized).is_err());
+
+        let v1 = OffchainMessage::new_v1([7; 32], u64::MAX, b"Test Message").unwrap();
+        let mut serialized = v1.serialize().unwrap();
+        serialized[16] = 0;
+        assert!(OffchainMessage::deserialize(&serialized).is_err());
+        serialized[16] = 2;
+        assert_eq!(
+            OffchainMessage::deserialize(&serialized),
+            Err(SanitizeError::ValueOutOfBounds)
+        );
+    }
+
+    #[test]
+    fn test_offchain_message_v1_strict_deserialization() {
+        let message = OffchainMessage::new_v1([7; 32], 10, b"Test Message").unwrap();
+        let serialized = message.serialize().unwrap();
+        // trailing byte
+        let mut data = serialized.clone();
+        data.push(b'!');
+        assert_eq!(
+            OffchainMessage::deserialize(&data),
+            Err(SanitizeError::InvalidValue)
+        );
+        // truncated payload
+        assert_eq!(
+            OffchainMessage::deserialize(&serialized[..serialized.len() - 1]),
+            Err(SanitizeError::InvalidValue)
+        );
+        // empty payload
+        assert_eq!(
+            OffchainMessage::deserialize(&serialized[..60]),
+            Err(SanitizeError::ValueOutOfBounds)
+        );
+        // unknown format
+        let mut data = serialized.clone();
+        data[57] = 3;
+        assert_eq!(
+            OffchainMessage::deserialize(&data),
+            Err(SanitizeError::InvalidValue)
+        );
+        // non-ascii payload declared as ascii
+        let mut data = serialized;
+        data[60] = 0x7f;
+        assert_eq!(
+            OffchainMessage::deserialize(&data),
+            Err(SanitizeError::InvalidValue)
+        );
+    }
+
+    #[test]
+    fn test_offchain_message_v1_max_len() {
+        let max_ledger = v1::OffchainMessage::MAX_LEN_LEDGER;
+        let max = v1::OffchainMessage::MAX_LEN;
+
+        let message = OffchainMessage::new_v1([0; 32], 0, &vec![b'a'; max_ledger]).unwrap();
+        assert_eq!(message.get_format(), MessageFormat::RestrictedAscii);
+        let message = OffchainMessage::new_v1([0; 32], 0, &vec![b'a'; max_ledger + 1]).unwrap();
+        assert_eq!(message.get_format(), MessageFormat::ExtendedUtf8);
+        let serialized = message.serialize().unwrap();
+        assert_eq!(message, OffchainMessage::deserialize(&serialized).unwrap());
+
+        let message = OffchainMessage::new_v1([0; 32], 0, &vec![b'a'; max]).unwrap();
+        let serialized = message.serialize().unwrap();
+        assert_eq!(serialized.len(), u16::MAX as usize);
+        assert_eq!(message, OffchainMessage::deserialize(&serialized).unwrap());
+        assert_eq!(
+            OffchainMessage::new_v1([0; 32], 0, &vec![b'a'; max + 1]),
+            Err(SanitizeError::ValueOutOfBounds)
+        );
+
+        // restricted formats are capped at the ledger limit
+        let mut serialized = OffchainMessage::new_v1([0; 32], 0, &vec![b'a'; max_ledger + 1])
+            .unwrap()
+            .serialize()
+            .unwrap();
+        serialized[57] = MessageFormat::RestrictedAscii.into();
+        assert_eq!(
+            OffchainMessage::deserialize(&serialized),
+            Err(SanitizeError::InvalidValue)
+        );
+        serialized[57] = MessageFormat::LimitedUtf8.into();
+        assert_eq!(
+            OffchainMessage::deserialize(&serialized),
+            Err(SanitizeError::InvalidValue)
+        );
+
+        assert_eq!(
+            OffchainMessage::new_v1([0; 32], 0, b""),
+            Err(SanitizeError::InvalidValue)
+        );
+        assert_eq!(
+            OffchainMessage::new_v1([0; 32], 0, &[0xff, 0xfe]),
+            Err(SanitizeError::InvalidValue)
+        );
+    }
+
+    #[test]
+    fn test_offchain_message_v1_expiry() {
+        let expiry = 1_700_000_000;
+        let message = OffchainMessage::new_v1([7; 32], expiry, b"Test Message").unwrap();
+        let keypair = Keypair::new();
+        let signature = message.sign(&keypair).unwrap();
+
+        assert!(!message.is_expired(expiry - 1));
+        assert!(!message.is_expired(expiry));
+        assert!(message.is_expired(expiry + 1));
+        assert!(message
+            .verify_at(&keypair.pubkey(), &signature, expiry)
+            .unwrap());
+        assert!(!message
+            .verify_at(&keypair.pubkey(), &signature, expiry + 1)
+            .unwrap());
+        assert!(!message
+            .verify_at(&Keypair::new().pubkey(), &signature, expiry)
+            .unwrap());
+
+        // signatures don't carry over to another application domain
+        let other = OffchainMessage::new_v1([8; 32], expiry, b"Test Message").unwrap();
+        assert!(!other.verify_at(&keypair.pubkey(), &signature, 0).unwrap());
+
+        // verify only checks the signature
+        assert!(message.verify(&keypair.pubkey(), &signature).unwrap());
+
+        // v0 messages never expire
+        let v0 = OffchainMessage::new(0, b"Test Message").unwrap();
+        let signature = v0.sign(&keypair).unwrap();
+        assert!(v0
+            .verify_at(&keypair.pubkey(), &signature, u64::MAX)
+            .unwrap());
+        assert!(v0.verify(&keypair.pubkey(), &signature).unwrap());
+    }
 }
@@ -0,0 +1,46 @@
+//! Feeds arbitrary packet batches through classification and stats.
+//!
+//! Run with `cargo fuzz run packet_batch` from the `packet` directory.
+
+#![cfg_attr(fuzzing, no_main)]
+
+#[cfg(fuzzing)]
+libfuzzer_sys::fuzz_target!(|batch: Vec<solana_packet::Packet>| {
+    use solana_packet::{
+        batch::{
+            compact_discarded, partition_discarded, AtomicPacketStats, PacketSizeHistogram,
+            PacketStats,
+        },
+        classify::mark_simple_votes,
+        filter_discard_short,
+    };
+
+    let mut batch = batch;
+    for packet in &batch {
+        let _ = packet.meta().is_well_formed();
+        let _ = packet.summary().to_string();
+    }
+    mark_simple_votes(&mut batch);
+    filter_discard_short(&mut batch, 1);
+
+    let stats = PacketStats::from_batch(&batch);
+    assert_eq!(stats.total, batch.len());
+    let atomic_stats = AtomicPacketStats::default();
+    atomic_stats.observe_batch(&batch);
+    assert_eq!(atomic_stats.snapshot(), stats);
+
+    let histogram = PacketSizeHistogram::default();
+    batch
+        .iter()
+        .for_each(|packet| histogram.observe(packet.meta().size));
+    assert_eq!(histogram.snapshot().iter().sum::<u64>(), batch.len() as u64);
+
+    let valid = partition_discarded(&mut batch);
+    assert_eq!(valid, batch.len() - stats.discarded);
+    assert!(batch[..valid].iter().all(|packet| !packet.meta().discard()));
+    assert_eq!(compact_discarded(&mut batch), stats.discarded);
+    assert_eq!(batch.len(), valid);
+});
+
+#[cfg(not(fuzzing))]
+fn main() {}
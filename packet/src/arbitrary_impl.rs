@@ -0,0 +1,112 @@
+//! [`Arbitrary`] implementations, to feed malformed packets to ingest code.
+//!
+//! Every field of [`Meta`] takes any value, including port 0, unspecified
+//! addresses and combinations of flags no stage sets, except that the size
+//! is at most [`PACKET_DATA_SIZE`], which the buffer of a packet relies on.
+//! Check [`Meta::is_well_formed`] to tell the malformed values apart.
+
+use {
+    crate::{Meta, Packet, PacketFlags, PACKET_DATA_SIZE},
+    arbitrary::{Arbitrary, Result, Unstructured},
+    solana_pubkey::Pubkey,
+    std::net::IpAddr,
+};
+
+impl<'a> Arbitrary<'a> for PacketFlags {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(Self::from_bits_retain(u8::arbitrary(u)?))
+    }
+}
+
+impl<'a> Arbitrary<'a> for Meta {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let size = u.int_in_range(0..=PACKET_DATA_SIZE)?;
+        let addr = IpAddr::arbitrary(u)?;
+        // the flow info and scope id only exist for IPv6 sources
+        let (flowinfo, scope_id) = match addr {
+            IpAddr::V4(_) => (0, 0),
+            IpAddr::V6(_) => (u32::arbitrary(u)?, u32::arbitrary(u)?),
+        };
+        Ok(Self {
+            size,
+            addr,
+            port: u16::arbitrary(u)?,
+            flags: PacketFlags::arbitrary(u)?,
+            remote_pubkey: Pubkey::new_from_array(<[u8; 32]>::arbitrary(u)?),
+            drop_reason: Arbitrary::arbitrary(u)?,
+            flowinfo,
+            scope_id,
+            received_at: Arbitrary::arbitrary(u)?,
+            connection_id: Arbitrary::arbitrary(u)?,
+        })
+    }
+}
+
+/// The payload takes the next [`Meta::size`] bytes of the input, padded with
+/// zeros if the input runs out.
+impl<'a> Arbitrary<'a> for Packet {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let meta = Meta::arbitrary(u)?;
+        let mut buffer = [0u8; PACKET_DATA_SIZE];
+        u.fill_buffer(&mut buffer[..meta.size])?;
+        Ok(Self::new(buffer, meta))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input(len: usize, seed: u64) -> Vec<u8> {
+        // xorshift, to get varied bytes without a rand dependency
+        let mut state = seed | 1;
+        (0..len)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                state as u8
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_arbitrary_packet_bounds() {
+        let mut malformed = 0;
+        for seed in 0..256 {
+            let data = input(2 * PACKET_DATA_SIZE, seed);
+            let mut u = Unstructured::new(&data);
+            let packet = Packet::arbitrary(&mut u).unwrap();
+            let meta = packet.meta();
+            assert!(meta.size <= PACKET_DATA_SIZE);
+            assert!(packet.buffer[meta.size..].iter().all(|&byte| byte == 0));
+            if meta.addr.is_ipv4() {
+                assert_eq!(meta.flowinfo, 0);
+                assert_eq!(meta.scope_id, 0);
+            }
+            malformed += usize::from(meta.is_well_formed().is_err());
+        }
+        // the generator produces both well formed and malformed metas
+        assert!(malformed > 0);
+        assert!(malformed < 256);
+    }
+
+    #[test]
+    fn test_arbitrary_packet_short_input() {
+        for len in 0..64 {
+            let data = input(len, len as u64);
+            let mut u = Unstructured::new(&data);
+            let packet = Packet::arbitrary(&mut u).unwrap();
+            assert!(packet.meta().size <= PACKET_DATA_SIZE);
+        }
+    }
+
+    #[test]
+    fn test_arbitrary_flags() {
+        for bits in [0, 0b0011_0000, u8::MAX] {
+            let data = [bits];
+            let flags = PacketFlags::arbitrary(&mut Unstructured::new(&data)).unwrap();
+            assert_eq!(flags.bits(), bits);
+        }
+    }
+}
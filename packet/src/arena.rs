@@ -0,0 +1,405 @@
+//! Allocation of received packets from shared chunks.
+//!
+//! Receiving into a fresh buffer per packet puts the allocator on the hot
+//! path. A [`PacketArena`] allocates large chunks instead and hands out
+//! regions of [`PACKET_DATA_SIZE`] bytes of the current chunk with
+//! [`PacketArena::alloc_packet`], so many packets share one allocation.
+//!
+//! A frozen region is an [`ArenaPacket`], whose payload is a [`Bytes`]
+//! referencing the chunk. The buffer of a [`Packet`] is inline, so it cannot
+//! reference the chunk, and [`ArenaPacket::to_packet`] copies the payload.
+//!
+//! Each chunk is reference counted: it is freed once the arena and all the
+//! packets allocated from it are dropped, so packets may outlive the arena.
+//! The arena keeps the chunks it has moved past, and reuses one for the next
+//! chunk once all the packets allocated from it are dropped.
+
+use {
+    crate::{Meta, Packet, PACKET_DATA_SIZE},
+    bytes::{Bytes, BytesMut},
+    std::{fmt, mem, slice::SliceIndex, sync::Mutex},
+};
+
+/// The default size of the chunks of a [`PacketArena`], 256 KiB.
+pub const DEFAULT_CHUNK_SIZE: usize = 256 * 1024;
+
+/// An allocator of packets from shared chunks, see the [module
+/// documentation](self).
+pub struct PacketArena {
+    chunk_size: usize,
+    state: Mutex<ArenaState>,
+}
+
+struct ArenaState {
+    /// The unallocated tail of the current chunk.
+    current: BytesMut,
+    /// The unallocated tails of the chunks moved past, to reuse once all the
+    /// packets allocated from them are dropped.
+    retired: Vec<BytesMut>,
+    /// The number of chunks allocated by the arena.
+    chunks: usize,
+}
+
+impl PacketArena {
+    /// Creates an arena allocating chunks of `chunk_size` bytes, rounded up
+    /// to [`PACKET_DATA_SIZE`]. Chunks are allocated on demand.
+    pub fn new(chunk_size: usize) -> Self {
+        Self {
+            chunk_size: chunk_size.max(PACKET_DATA_SIZE),
+            state: Mutex::new(ArenaState {
+                current: BytesMut::new(),
+                retired: Vec::new(),
+                chunks: 0,
+            }),
+        }
+    }
+
+    /// Returns the size of the chunks of the arena.
+    pub fn chunk_size(&self) -> usize {
+        self.chunk_size
+    }
+
+    /// Returns the number of chunks held by the arena, either current or
+    /// waiting to be reused.
+    pub fn chunk_count(&self) -> usize {
+        self.state.lock().unwrap().chunks
+    }
+
+    /// Allocates a zeroed region of [`PACKET_DATA_SIZE`] bytes to receive a
+    /// packet into.
+    ///
+    /// The region comes from the current chunk. Once it is exhausted, a
+    /// chunk whose packets have all been dropped is reused, or a new one is
+    /// allocated if there is none.
+    pub fn alloc_packet(&self) -> ArenaPacketMut {
+        let mut state = self.state.lock().unwrap();
+        if state.current.len() < PACKET_DATA_SIZE {
+            self.next_chunk(&mut state);
+        }
+        ArenaPacketMut {
+            buffer: state.current.split_to(PACKET_DATA_SIZE),
+            meta: Meta::default(),
+        }
+    }
+
+    fn next_chunk(&self, state: &mut ArenaState) {
+        let exhausted = mem::take(&mut state.current);
+        if state.chunks > 0 {
+            state.retired.push(exhausted);
+        }
+        for index in 0..state.retired.len() {
+            let chunk = &mut state.retired[index];
+            chunk.clear();
+            // Only succeeds if no packet references the chunk anymore.
+            if chunk.try_reclaim(self.chunk_size) {
+                let mut chunk = state.retired.swap_remove(index);
+                chunk.resize(self.chunk_size, 0);
+                state.current = chunk;
+                return;
+            }
+        }
+        state.current = BytesMut::zeroed(self.chunk_size);
+        state.chunks = state.chunks.saturating_add(1);
+    }
+}
+
+impl Default for PacketArena {
+    fn default() -> Self {
+        Self::new(DEFAULT_CHUNK_SIZE)
+    }
+}
+
+impl fmt::Debug for PacketArena {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PacketArena")
+            .field("chunk_size", &self.chunk_size)
+            .field("chunks", &self.chunk_count())
+            .finish()
+    }
+}
+
+/// A writable region of an arena chunk to receive a packet into, see
+/// [`PacketArena::alloc_packet`].
+///
+/// Mirrors the writable accessors of [`Packet`]: write the payload into
+/// [`ArenaPacketMut::buffer_mut`], set its size in the meta, and
+/// [`ArenaPacketMut::freeze`] the region.
+pub struct ArenaPacketMut {
+    buffer: BytesMut,
+    meta: Meta,
+}
+
+impl ArenaPacketMut {
+    /// Returns a mutable reference to the entirety of the region to write
+    /// into. The caller is responsible for updating the size in the meta
+    /// after writing to the buffer.
+    #[inline]
+    pub fn buffer_mut(&mut self) -> &mut [u8] {
+        debug_assert!(!self.meta.discard());
+        &mut self.buffer[..]
+    }
+
+    #[inline]
+    pub fn meta(&self) -> &Meta {
+        &self.meta
+    }
+
+    #[inline]
+    pub fn meta_mut(&mut self) -> &mut Meta {
+        &mut self.meta
+    }
+
+    /// Freezes the region into a packet whose payload is the first
+    /// `meta.size` bytes of the region.
+    pub fn freeze(mut self) -> ArenaPacket {
+        debug_assert!(self.meta.size <= PACKET_DATA_SIZE);
+        self.buffer.truncate(self.meta.size);
+        ArenaPacket {
+            payload: self.buffer.freeze(),
+            meta: self.meta,
+        }
+    }
+}
+
+impl fmt::Debug for ArenaPacketMut {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "ArenaPacketMut {{ size: {:?}, addr: {:?} }}",
+            self.meta.size,
+            self.meta.socket_addr()
+        )
+    }
+}
+
+/// A received packet whose payload references an arena chunk, see
+/// [`ArenaPacketMut::freeze`].
+///
+/// Cloning the packet does not copy the payload.
+#[derive(Clone)]
+pub struct ArenaPacket {
+    payload: Bytes,
+    meta: Meta,
+}
+
+impl ArenaPacket {
+    /// Returns an immutable reference to the payload, as [`Packet::data`].
+    /// Returns None if the index is invalid or if the packet is marked as
+    /// discard.
+    #[inline]
+    pub fn data<I>(&self, index: I) -> Option<&<I as SliceIndex<[u8]>>::Output>
+    where
+        I: SliceIndex<[u8]>,
+    {
+        if self.meta.discard() {
+            None
+        } else {
+            self.payload.get(index)
+        }
+    }
+
+    /// Returns the payload, even if the packet is marked as discard.
+    #[inline]
+    pub fn payload(&self) -> &Bytes {
+        &self.payload
+    }
+
+    #[inline]
+    pub fn meta(&self) -> &Meta {
+        &self.meta
+    }
+
+    #[inline]
+    pub fn meta_mut(&mut self) -> &mut Meta {
+        &mut self.meta
+    }
+
+    /// Copies the payload and the meta into a [`Packet`].
+    pub fn to_packet(&self) -> Packet {
+        let mut buffer = [0u8; PACKET_DATA_SIZE];
+        buffer[..self.payload.len()].copy_from_slice(&self.payload);
+        let meta = Meta {
+            size: self.payload.len(),
+            ..self.meta.clone()
+        };
+        Packet::new(buffer, meta)
+    }
+}
+
+impl fmt::Debug for ArenaPacket {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "ArenaPacket {{ size: {:?}, addr: {:?} }}",
+            self.payload.len(),
+            self.meta.socket_addr()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::PacketFlags,
+        std::{
+            net::{IpAddr, Ipv4Addr, SocketAddr},
+            sync::Arc,
+            thread,
+        },
+    };
+
+    const PACKETS_PER_CHUNK: usize = 8;
+
+    fn arena() -> PacketArena {
+        PacketArena::new(PACKETS_PER_CHUNK * PACKET_DATA_SIZE)
+    }
+
+    fn receive(arena: &PacketArena, fill: u8, size: usize) -> ArenaPacket {
+        let mut packet = arena.alloc_packet();
+        assert!(packet.buffer_mut().iter().all(|byte| *byte == 0));
+        packet.buffer_mut().fill(fill);
+        packet.meta_mut().size = size;
+        packet.freeze()
+    }
+
+    #[test]
+    fn test_chunk_size() {
+        assert_eq!(PacketArena::default().chunk_size(), DEFAULT_CHUNK_SIZE);
+        assert_eq!(PacketArena::new(1).chunk_size(), PACKET_DATA_SIZE);
+        assert_eq!(
+            PacketArena::new(0).alloc_packet().buffer_mut().len(),
+            PACKET_DATA_SIZE
+        );
+        assert_eq!(PacketArena::default().chunk_count(), 0);
+    }
+
+    #[test]
+    fn test_payload_isolation() {
+        let arena = arena();
+        let packets: Vec<_> = (0..PACKETS_PER_CHUNK)
+            .map(|i| receive(&arena, i as u8 + 1, PACKET_DATA_SIZE - i))
+            .collect();
+        assert_eq!(arena.chunk_count(), 1);
+
+        for (i, packet) in packets.iter().enumerate() {
+            assert_eq!(packet.data(..).unwrap().len(), PACKET_DATA_SIZE - i);
+            assert!(packet.payload().iter().all(|byte| *byte == i as u8 + 1));
+        }
+        // The packets share the chunk.
+        for pair in packets.windows(2) {
+            assert_eq!(
+                pair[1].payload().as_ptr() as usize - pair[0].payload().as_ptr() as usize,
+                PACKET_DATA_SIZE
+            );
+        }
+
+        let packet = receive(&arena, 0xff, 1);
+        assert_eq!(arena.chunk_count(), 2);
+        assert_eq!(packet.data(..), Some(&[0xff][..]));
+        assert!(packets[PACKETS_PER_CHUNK - 1]
+            .payload()
+            .iter()
+            .all(|byte| *byte == PACKETS_PER_CHUNK as u8));
+    }
+
+    #[test]
+    fn test_recycle_stable_memory() {
+        let arena = arena();
+        let batch = 2 * PACKETS_PER_CHUNK + PACKETS_PER_CHUNK / 2;
+        let receive_batch = |fill: u8| {
+            let packets: Vec<_> = (0..batch)
+                .map(|_| receive(&arena, fill, PACKET_DATA_SIZE))
+                .collect();
+            assert!(packets
+                .iter()
+                .all(|packet| packet.payload().iter().all(|byte| *byte == fill)));
+        };
+        receive_batch(0);
+        let chunks = arena.chunk_count();
+        assert_eq!(chunks, 3);
+        for cycle in 1..1000 {
+            receive_batch(cycle as u8);
+            assert_eq!(arena.chunk_count(), chunks);
+        }
+
+        // A live packet pins its chunk, which is not reused until it drops.
+        let pinned = receive(&arena, 0xaa, 1);
+        for cycle in 0..10 {
+            receive_batch(cycle);
+        }
+        let pinned_chunks = arena.chunk_count();
+        assert!(pinned_chunks > chunks && pinned_chunks <= chunks + 2);
+        for cycle in 0..100 {
+            receive_batch(cycle);
+            assert_eq!(arena.chunk_count(), pinned_chunks);
+            assert_eq!(pinned.data(..), Some(&[0xaa][..]));
+        }
+        drop(pinned);
+        for cycle in 0..100 {
+            receive_batch(cycle);
+            assert_eq!(arena.chunk_count(), pinned_chunks);
+        }
+    }
+
+    #[test]
+    fn test_packet_outlives_arena() {
+        let arena = arena();
+        let frozen = receive(&arena, 1, 10);
+        let mut writable = arena.alloc_packet();
+        let clone = frozen.clone();
+        drop(arena);
+
+        writable.buffer_mut()[..3].copy_from_slice(&[2, 3, 4]);
+        writable.meta_mut().size = 3;
+        let writable = writable.freeze();
+        assert_eq!(writable.data(..), Some(&[2, 3, 4][..]));
+        assert_eq!(frozen.data(..), Some(&[1; 10][..]));
+        drop(frozen);
+        assert_eq!(clone.data(..), Some(&[1; 10][..]));
+    }
+
+    #[test]
+    fn test_to_packet() {
+        let arena = arena();
+        let mut packet = arena.alloc_packet();
+        packet.buffer_mut()[..4].copy_from_slice(&[1, 2, 3, 4]);
+        let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 8001);
+        packet.meta_mut().size = 4;
+        packet.meta_mut().set_socket_addr(&addr);
+        let mut packet = packet.freeze();
+
+        let copy = packet.to_packet();
+        assert_eq!(copy.data(..), Some(&[1, 2, 3, 4][..]));
+        assert_eq!(copy.meta(), packet.meta());
+
+        packet.meta_mut().flags |= PacketFlags::DISCARD;
+        assert_eq!(packet.data(..), None);
+        assert_eq!(&packet.payload()[..], &[1, 2, 3, 4]);
+        assert_eq!(packet.to_packet().data(..), None);
+    }
+
+    #[test]
+    fn test_concurrent_alloc() {
+        let arena = Arc::new(arena());
+        let threads: Vec<_> = (0..4u8)
+            .map(|id| {
+                let arena = Arc::clone(&arena);
+                thread::spawn(move || {
+                    for round in 0..1000usize {
+                        let fill = id.wrapping_mul(64).wrapping_add(round as u8);
+                        let size = round % PACKET_DATA_SIZE + 1;
+                        let packet = receive(&arena, fill, size);
+                        assert_eq!(packet.payload().len(), size);
+                        assert!(packet.payload().iter().all(|byte| *byte == fill));
+                    }
+                })
+            })
+            .collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+        // Each thread pins at most one chunk besides the current one.
+        assert!(arena.chunk_count() <= 5);
+    }
+}
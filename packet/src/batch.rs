@@ -0,0 +1,478 @@
+//! Removal of discarded packets from batches.
+//!
+//! Stages after signature verification only process the packets that were
+//! not marked as discard. Skipping discards on every pass wastes cache, so
+//! the helpers in this module move them out of the way once, without
+//! allocating a new batch. [`PacketStats`] counts the packets of batches by
+//! drop reason beforehand, for metrics.
+//!
+//! Multi-threaded stages share an [`AtomicPacketStats`] instead, and can
+//! count packet sizes in a [`PacketSizeHistogram`].
+
+use {
+    crate::{DropReason, Meta, Packet},
+    core::ops::RangeInclusive,
+    std::sync::atomic::{AtomicU64, Ordering},
+};
+
+/// Removes the discarded packets of `packets` in place, returning how many
+/// were removed.
+///
+/// The order of the remaining packets is preserved, and the capacity of
+/// `packets` is left unchanged.
+pub fn compact_discarded(packets: &mut Vec<Packet>) -> usize {
+    let len = packets.len();
+    packets.retain(|packet| !packet.meta().discard());
+    len - packets.len()
+}
+
+/// Moves the discarded packets of `packets` after the valid ones, returning
+/// the number of valid packets.
+///
+/// After the call, `packets[..split]` holds the valid packets and
+/// `packets[split..]` the discarded ones, where `split` is the returned
+/// value. Packets are swapped rather than shifted, so the order of either
+/// part is not preserved.
+pub fn partition_discarded(packets: &mut [Packet]) -> usize {
+    let mut split = 0;
+    let mut end = packets.len();
+    while split < end {
+        if packets[split].meta().discard() {
+            end -= 1;
+            packets.swap(split, end);
+        } else {
+            split += 1;
+        }
+    }
+    split
+}
+
+/// Iterates over the packets of `packets` that are not marked as discard.
+pub fn iter_valid(packets: &[Packet]) -> impl Iterator<Item = &Packet> {
+    packets.iter().filter(|packet| !packet.meta().discard())
+}
+
+/// Counts of packets, of the discarded ones, and of the discarded ones by
+/// [`DropReason`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PacketStats {
+    /// Packets counted.
+    pub total: usize,
+    /// Discarded packets, with or without a drop reason.
+    pub discarded: usize,
+    /// Discarded packets by drop reason code, see [`DropReason::code`].
+    /// Index zero counts the discarded packets without a reason.
+    by_reason: [usize; 256],
+}
+
+impl Default for PacketStats {
+    fn default() -> Self {
+        Self {
+            total: 0,
+            discarded: 0,
+            by_reason: [0; 256],
+        }
+    }
+}
+
+impl PacketStats {
+    /// Counts the packets of `packets`.
+    pub fn from_batch(packets: &[Packet]) -> Self {
+        let mut stats = Self::default();
+        stats.record_batch(packets);
+        stats
+    }
+
+    /// Counts the packets of `packets`, in addition to those already
+    /// counted.
+    pub fn record_batch(&mut self, packets: &[Packet]) {
+        packets.iter().for_each(|packet| self.record(packet));
+    }
+
+    /// Counts `packet`, in addition to the packets already counted.
+    pub fn record(&mut self, packet: &Packet) {
+        let meta = packet.meta();
+        self.total = self.total.saturating_add(1);
+        if meta.discard() {
+            self.discarded = self.discarded.saturating_add(1);
+            let index = reason_index(meta);
+            self.by_reason[index] = self.by_reason[index].saturating_add(1);
+        }
+    }
+
+    /// Number of discarded packets dropped for `reason`.
+    pub fn dropped(&self, reason: DropReason) -> usize {
+        self.by_reason[usize::from(reason.code().get())]
+    }
+
+    /// Number of discarded packets without a drop reason.
+    pub fn dropped_without_reason(&self) -> usize {
+        self.by_reason[0]
+    }
+
+    /// Iterates over the drop reasons with a non-zero count, with their
+    /// counts, in order of code.
+    pub fn iter_dropped(&self) -> impl Iterator<Item = (DropReason, usize)> + '_ {
+        self.by_reason
+            .iter()
+            .enumerate()
+            .filter(|&(_, &count)| count > 0)
+            .filter_map(|(code, &count)| {
+                let code = core::num::NonZeroU8::new(u8::try_from(code).ok()?)?;
+                Some((DropReason::from_code(code), count))
+            })
+    }
+}
+
+/// Index of the drop reason of a discarded packet in the counts by reason.
+fn reason_index(meta: &Meta) -> usize {
+    meta.drop_reason()
+        .map_or(0, |reason| usize::from(reason.code().get()))
+}
+
+/// [`PacketStats`] shared between threads.
+///
+/// The counters are updated with relaxed atomic operations, so observing a
+/// packet is cheap and never blocks. Each counter only ever grows until
+/// [`reset`](Self::reset), but a [`snapshot`](Self::snapshot) taken while
+/// packets are observed may count some of them in a counter and not yet in
+/// another.
+#[derive(Debug)]
+pub struct AtomicPacketStats {
+    total: AtomicU64,
+    discarded: AtomicU64,
+    by_reason: [AtomicU64; 256],
+}
+
+impl Default for AtomicPacketStats {
+    fn default() -> Self {
+        Self {
+            total: AtomicU64::new(0),
+            discarded: AtomicU64::new(0),
+            by_reason: core::array::from_fn(|_| AtomicU64::new(0)),
+        }
+    }
+}
+
+impl AtomicPacketStats {
+    /// Counts the packet of `meta`.
+    pub fn observe(&self, meta: &Meta) {
+        self.total.fetch_add(1, Ordering::Relaxed);
+        if meta.discard() {
+            self.discarded.fetch_add(1, Ordering::Relaxed);
+            self.by_reason[reason_index(meta)].fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Counts the packets of `packets`.
+    pub fn observe_batch(&self, packets: &[Packet]) {
+        packets
+            .iter()
+            .for_each(|packet| self.observe(packet.meta()));
+    }
+
+    /// Returns the current counts.
+    pub fn snapshot(&self) -> PacketStats {
+        let load = |counter: &AtomicU64| {
+            usize::try_from(counter.load(Ordering::Relaxed)).unwrap_or(usize::MAX)
+        };
+        PacketStats {
+            total: load(&self.total),
+            discarded: load(&self.discarded),
+            by_reason: core::array::from_fn(|index| load(&self.by_reason[index])),
+        }
+    }
+
+    /// Sets all the counts back to zero.
+    pub fn reset(&self) {
+        self.total.store(0, Ordering::Relaxed);
+        self.discarded.store(0, Ordering::Relaxed);
+        for counter in &self.by_reason {
+            counter.store(0, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Histogram of packet sizes shared between threads, with power-of-two
+/// buckets.
+///
+/// Bucket zero counts the empty packets, and bucket `i` the sizes in
+/// `2^(i - 1)..2^i`, the last bucket also counting any larger size. The
+/// last bucket holds [`PACKET_DATA_SIZE`](crate::PACKET_DATA_SIZE).
+#[derive(Debug, Default)]
+pub struct PacketSizeHistogram {
+    buckets: [AtomicU64; Self::NUM_BUCKETS],
+}
+
+impl PacketSizeHistogram {
+    /// Number of buckets.
+    pub const NUM_BUCKETS: usize = 12;
+
+    /// Index of the bucket counting `size`.
+    pub const fn bucket_index(size: usize) -> usize {
+        let index = (usize::BITS - size.leading_zeros()) as usize;
+        if index < Self::NUM_BUCKETS {
+            index
+        } else {
+            Self::NUM_BUCKETS - 1
+        }
+    }
+
+    /// Sizes counted by the bucket `index`, the last one up to
+    /// `usize::MAX`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is not below [`NUM_BUCKETS`](Self::NUM_BUCKETS).
+    pub fn bucket_range(index: usize) -> RangeInclusive<usize> {
+        assert!(index < Self::NUM_BUCKETS, "no bucket {index}");
+        match index {
+            0 => 0..=0,
+            _ if index == Self::NUM_BUCKETS - 1 => 1 << (index - 1)..=usize::MAX,
+            _ => 1 << (index - 1)..=(1 << index) - 1,
+        }
+    }
+
+    /// Counts a packet of `size` bytes.
+    pub fn observe(&self, size: usize) {
+        self.buckets[Self::bucket_index(size)].fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Returns the current counts of the buckets.
+    pub fn snapshot(&self) -> [u64; Self::NUM_BUCKETS] {
+        core::array::from_fn(|index| self.buckets[index].load(Ordering::Relaxed))
+    }
+
+    /// Sets all the counts back to zero.
+    pub fn reset(&self) {
+        for bucket in &self.buckets {
+            bucket.store(0, Ordering::Relaxed);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A batch of `len` packets tagged with their index, with the packets
+    /// at the indices for which `discard` holds marked as discard.
+    fn batch(len: u8, discard: impl Fn(u8) -> bool) -> Vec<Packet> {
+        (0..len)
+            .map(|i| {
+                let mut packet = Packet::default();
+                packet.buffer_mut()[0] = i;
+                packet.meta_mut().size = 1;
+                packet.meta_mut().set_discard(discard(i));
+                packet
+            })
+            .collect()
+    }
+
+    fn tags<'a>(packets: impl IntoIterator<Item = &'a Packet>) -> Vec<u8> {
+        packets.into_iter().map(|packet| packet.buffer[0]).collect()
+    }
+
+    #[test]
+    fn test_compact_discarded() {
+        let mut packets = batch(10, |i| i % 3 == 0);
+        let capacity = packets.capacity();
+        assert_eq!(compact_discarded(&mut packets), 4);
+        assert_eq!(tags(&packets), [1, 2, 4, 5, 7, 8]);
+        assert_eq!(packets.capacity(), capacity);
+
+        assert_eq!(compact_discarded(&mut packets), 0);
+        assert_eq!(packets.len(), 6);
+
+        let mut packets = batch(4, |_| true);
+        assert_eq!(compact_discarded(&mut packets), 4);
+        assert!(packets.is_empty());
+        assert_eq!(compact_discarded(&mut packets), 0);
+    }
+
+    #[test]
+    fn test_partition_discarded() {
+        for discard in [
+            (|i| i % 3 == 0) as fn(u8) -> bool,
+            |i| i % 2 == 1,
+            |i| i < 5,
+            |i| i >= 5,
+            |_| true,
+            |_| false,
+        ] {
+            let mut packets = batch(10, discard);
+            let split = partition_discarded(&mut packets);
+            let valid = (0..10).filter(|&i| !discard(i)).count();
+            assert_eq!(split, valid);
+            assert!(packets[..split].iter().all(|p| !p.meta().discard()));
+            assert!(packets[split..].iter().all(|p| p.meta().discard()));
+
+            // no packet is lost or duplicated
+            let mut valid_tags = tags(&packets[..split]);
+            valid_tags.sort_unstable();
+            assert_eq!(valid_tags, tags(iter_valid(&batch(10, discard))));
+            let mut all_tags = tags(&packets);
+            all_tags.sort_unstable();
+            assert_eq!(all_tags, (0..10).collect::<Vec<_>>());
+        }
+        assert_eq!(partition_discarded(&mut []), 0);
+    }
+
+    #[test]
+    fn test_iter_valid() {
+        let packets = batch(10, |i| i % 3 == 0);
+        assert_eq!(tags(iter_valid(&packets)), [1, 2, 4, 5, 7, 8]);
+        assert_eq!(iter_valid(&batch(3, |_| true)).count(), 0);
+        assert_eq!(iter_valid(&[]).count(), 0);
+    }
+
+    #[test]
+    fn test_packet_stats() {
+        let mut packets = batch(10, |i| i < 7);
+        packets[0].meta_mut().discard_with_reason(DropReason::Dedup);
+        packets[1].meta_mut().discard_with_reason(DropReason::Dedup);
+        packets[2]
+            .meta_mut()
+            .discard_with_reason(DropReason::SigverifyFailed);
+        packets[3]
+            .meta_mut()
+            .discard_with_reason(DropReason::Other(42));
+        packets[4]
+            .meta_mut()
+            .discard_with_reason(DropReason::RateLimited);
+        // cleared discards are not counted
+        packets[4].meta_mut().set_discard(false);
+
+        let stats = PacketStats::from_batch(&packets);
+        assert_eq!(stats.total, 10);
+        assert_eq!(stats.discarded, 6);
+        assert_eq!(stats.dropped(DropReason::Dedup), 2);
+        assert_eq!(stats.dropped(DropReason::SigverifyFailed), 1);
+        assert_eq!(stats.dropped(DropReason::Other(42)), 1);
+        assert_eq!(stats.dropped(DropReason::RateLimited), 0);
+        assert_eq!(stats.dropped_without_reason(), 2);
+        assert_eq!(
+            stats.iter_dropped().collect::<Vec<_>>(),
+            [
+                (DropReason::Dedup, 2),
+                (DropReason::SigverifyFailed, 1),
+                (DropReason::Other(42), 1),
+            ]
+        );
+
+        let mut stats = stats;
+        stats.record_batch(&packets[..2]);
+        assert_eq!(stats.total, 12);
+        assert_eq!(stats.dropped(DropReason::Dedup), 4);
+        assert_eq!(PacketStats::from_batch(&[]), PacketStats::default());
+    }
+
+    #[test]
+    fn test_atomic_packet_stats_concurrent() {
+        let mut packets = batch(100, |i| i % 4 == 0);
+        for packet in packets.iter_mut().step_by(8) {
+            packet.meta_mut().discard_with_reason(DropReason::Dedup);
+        }
+        let expected = PacketStats::from_batch(&packets);
+
+        let stats = AtomicPacketStats::default();
+        let num_threads = 8;
+        std::thread::scope(|scope| {
+            for _ in 0..num_threads {
+                scope.spawn(|| stats.observe_batch(&packets));
+            }
+        });
+        let snapshot = stats.snapshot();
+        assert_eq!(snapshot.total, expected.total * num_threads);
+        assert_eq!(snapshot.discarded, expected.discarded * num_threads);
+        assert_eq!(
+            snapshot.dropped(DropReason::Dedup),
+            expected.dropped(DropReason::Dedup) * num_threads
+        );
+        assert_eq!(
+            snapshot.dropped_without_reason(),
+            expected.dropped_without_reason() * num_threads
+        );
+
+        stats.reset();
+        assert_eq!(stats.snapshot(), PacketStats::default());
+    }
+
+    #[test]
+    fn test_atomic_packet_stats_snapshot_monotonic() {
+        let packets = batch(50, |i| i % 2 == 0);
+        let stats = AtomicPacketStats::default();
+        std::thread::scope(|scope| {
+            for _ in 0..4 {
+                scope.spawn(|| {
+                    for _ in 0..100 {
+                        stats.observe_batch(&packets);
+                    }
+                });
+            }
+            let mut previous = stats.snapshot();
+            for _ in 0..1_000 {
+                let snapshot = stats.snapshot();
+                assert!(snapshot.total >= previous.total);
+                assert!(snapshot.discarded >= previous.discarded);
+                assert!(snapshot.dropped_without_reason() >= previous.dropped_without_reason());
+                previous = snapshot;
+            }
+        });
+        let snapshot = stats.snapshot();
+        assert_eq!(snapshot.total, 50 * 100 * 4);
+        assert_eq!(snapshot.discarded, 25 * 100 * 4);
+    }
+
+    #[test]
+    fn test_packet_size_histogram_buckets() {
+        for (size, index) in [
+            (0, 0),
+            (1, 1),
+            (2, 2),
+            (3, 2),
+            (4, 3),
+            (511, 9),
+            (512, 10),
+            (1023, 10),
+            (1024, 11),
+            (crate::PACKET_DATA_SIZE, 11),
+            (usize::MAX, 11),
+        ] {
+            assert_eq!(PacketSizeHistogram::bucket_index(size), index, "{size}");
+            assert!(PacketSizeHistogram::bucket_range(index).contains(&size));
+        }
+        // the ranges cover the sizes without overlapping
+        let mut next = 0;
+        for index in 0..PacketSizeHistogram::NUM_BUCKETS {
+            let range = PacketSizeHistogram::bucket_range(index);
+            assert_eq!(*range.start(), next);
+            assert_eq!(PacketSizeHistogram::bucket_index(*range.end()), index);
+            next = range.end().wrapping_add(1);
+        }
+        assert_eq!(next, 0);
+    }
+
+    #[test]
+    fn test_packet_size_histogram_observe() {
+        let histogram = PacketSizeHistogram::default();
+        std::thread::scope(|scope| {
+            for _ in 0..4 {
+                scope.spawn(|| {
+                    for size in [0, 1, 100, 1232, 1232] {
+                        histogram.observe(size);
+                    }
+                });
+            }
+        });
+        let mut expected = [0; PacketSizeHistogram::NUM_BUCKETS];
+        expected[0] = 4;
+        expected[1] = 4;
+        expected[7] = 4;
+        expected[11] = 8;
+        assert_eq!(histogram.snapshot(), expected);
+
+        histogram.reset();
+        assert_eq!(histogram.snapshot(), [0; PacketSizeHistogram::NUM_BUCKETS]);
+    }
+}
@@ -0,0 +1,198 @@
+//! Classification of packet payloads.
+//!
+//! Validators flag packets carrying simple vote transactions with
+//! [`PacketFlags::SIMPLE_VOTE_TX`](crate::PacketFlags::SIMPLE_VOTE_TX) so they
+//! can be scheduled separately. The helpers in this module re-derive that flag
+//! from the raw payload, for example when replaying packet captures.
+
+use {crate::Packet, solana_short_vec::decode_len, std::ops::Range};
+
+/// Maximum number of signatures of a simple vote transaction: the vote
+/// authority, and optionally a separate fee payer.
+pub const MAX_SIMPLE_VOTE_SIGNATURES: usize = 2;
+
+const SIGNATURE_BYTES: usize = 64;
+const PUBKEY_BYTES: usize = 32;
+const BLOCKHASH_BYTES: usize = 32;
+const MESSAGE_HEADER_BYTES: usize = 3;
+const MESSAGE_VERSION_PREFIX: u8 = 0x80;
+
+/// Returns `true` if `packet` holds a simple vote transaction.
+///
+/// A transaction is a simple vote if it has one or two signatures, a legacy
+/// message, and exactly one instruction whose program id is the vote program.
+/// Only the prefix of the payload needed to decide this is parsed, so the
+/// rest of the transaction is not validated. Malformed or truncated payloads
+/// are not simple votes.
+pub fn is_simple_vote(packet: &Packet) -> bool {
+    packet
+        .data(..)
+        .and_then(simple_vote_program_id_range)
+        .and_then(|range| packet.data(range))
+        .is_some_and(|program_id| program_id == solana_sdk_ids::vote::ID.as_ref())
+}
+
+/// Sets or clears the simple vote flag of every packet in `batch` according
+/// to [`is_simple_vote`], returning the number of simple votes.
+///
+/// Discarded packets are skipped.
+pub fn mark_simple_votes(batch: &mut [Packet]) -> usize {
+    batch
+        .iter_mut()
+        .filter(|packet| !packet.meta().discard())
+        .map(|packet| {
+            let is_simple_vote = is_simple_vote(packet);
+            packet.meta_mut().set_simple_vote(is_simple_vote);
+            is_simple_vote
+        })
+        .filter(|&is_simple_vote| is_simple_vote)
+        .count()
+}
+
+/// Locate the program id of the only instruction of a legacy transaction
+/// with at most [`MAX_SIMPLE_VOTE_SIGNATURES`] signatures.
+fn simple_vote_program_id_range(data: &[u8]) -> Option<Range<usize>> {
+    let (num_signatures, prefix_len) = decode_len(data).ok()?;
+    if num_signatures == 0 || num_signatures > MAX_SIMPLE_VOTE_SIGNATURES {
+        return None;
+    }
+    let message_start = prefix_len.checked_add(num_signatures.checked_mul(SIGNATURE_BYTES)?)?;
+    if data.get(message_start)? & MESSAGE_VERSION_PREFIX != 0 {
+        return None;
+    }
+
+    let keys_start = message_start.checked_add(MESSAGE_HEADER_BYTES)?;
+    let (num_keys, prefix_len) = decode_len(data.get(keys_start..)?).ok()?;
+    let keys_start = keys_start.checked_add(prefix_len)?;
+
+    let instructions_start = keys_start
+        .checked_add(num_keys.checked_mul(PUBKEY_BYTES)?)?
+        .checked_add(BLOCKHASH_BYTES)?;
+    let (num_instructions, prefix_len) = decode_len(data.get(instructions_start..)?).ok()?;
+    if num_instructions != 1 {
+        return None;
+    }
+
+    let program_id_index = usize::from(*data.get(instructions_start.checked_add(prefix_len)?)?);
+    if program_id_index >= num_keys {
+        return None;
+    }
+    let program_id_start = keys_start.checked_add(program_id_index.checked_mul(PUBKEY_BYTES)?)?;
+    Some(program_id_start..program_id_start.checked_add(PUBKEY_BYTES)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        solana_hash::Hash,
+        solana_pubkey::Pubkey,
+        solana_transaction::Transaction,
+        solana_vote_interface::{instruction as vote_instruction, state::Vote},
+    };
+
+    fn vote_transaction_bytes() -> Vec<u8> {
+        let vote_pubkey = Pubkey::new_unique();
+        let authorized_voter = Pubkey::new_unique();
+        let instruction = vote_instruction::vote(
+            &vote_pubkey,
+            &authorized_voter,
+            Vote::new(vec![1, 2, 3], Hash::default()),
+        );
+        let transaction = Transaction::new_with_payer(&[instruction], Some(&authorized_voter));
+        bincode::serialize(&transaction).unwrap()
+    }
+
+    fn transfer_transaction_bytes() -> Vec<u8> {
+        let from = Pubkey::new_unique();
+        let instruction =
+            solana_system_interface::instruction::transfer(&from, &Pubkey::new_unique(), 42);
+        let transaction = Transaction::new_with_payer(&[instruction], Some(&from));
+        bincode::serialize(&transaction).unwrap()
+    }
+
+    fn packet_from_bytes(bytes: &[u8]) -> Packet {
+        let mut packet = Packet::default();
+        packet.buffer_mut()[..bytes.len()].copy_from_slice(bytes);
+        packet.meta_mut().size = bytes.len();
+        packet
+    }
+
+    #[test]
+    fn test_is_simple_vote() {
+        assert!(is_simple_vote(
+            &packet_from_bytes(&vote_transaction_bytes())
+        ));
+        assert!(!is_simple_vote(&packet_from_bytes(
+            &transfer_transaction_bytes()
+        )));
+        assert!(!is_simple_vote(&Packet::default()));
+    }
+
+    #[test]
+    fn test_is_simple_vote_rejects_versioned_message() {
+        let mut bytes = vote_transaction_bytes();
+        // insert a v0 version prefix in front of the legacy message
+        let message_start = 1 + SIGNATURE_BYTES;
+        bytes.insert(message_start, MESSAGE_VERSION_PREFIX);
+        assert!(!is_simple_vote(&packet_from_bytes(&bytes)));
+    }
+
+    #[test]
+    fn test_is_simple_vote_rejects_extra_signatures() {
+        let bytes = vote_transaction_bytes();
+        // prepend two extra signatures, bumping the signature count to 3
+        let mut patched = vec![3];
+        patched.extend_from_slice(&[0; 2 * SIGNATURE_BYTES]);
+        patched.extend_from_slice(&bytes[1..]);
+        assert!(!is_simple_vote(&packet_from_bytes(&patched)));
+    }
+
+    #[test]
+    fn test_is_simple_vote_truncated() {
+        let bytes = vote_transaction_bytes();
+        // the program id lives before the instruction data, so a long enough
+        // prefix still classifies as a vote; shorter ones must not panic
+        let classified = (0..bytes.len())
+            .filter(|&len| is_simple_vote(&packet_from_bytes(&bytes[..len])))
+            .count();
+        assert!(classified < bytes.len());
+        assert!(!is_simple_vote(&packet_from_bytes(
+            &bytes[..1 + SIGNATURE_BYTES]
+        )));
+
+        // arbitrary garbage, including invalid compact-u16 prefixes
+        for byte in [0x00, 0x01, 0x7f, 0x80, 0xff] {
+            for len in [1, 2, 3, 100, crate::PACKET_DATA_SIZE] {
+                assert!(!is_simple_vote(&packet_from_bytes(&vec![byte; len])));
+            }
+        }
+    }
+
+    #[test]
+    fn test_is_simple_vote_respects_discard() {
+        let mut packet = packet_from_bytes(&vote_transaction_bytes());
+        packet.meta_mut().set_discard(true);
+        assert!(!is_simple_vote(&packet));
+    }
+
+    #[test]
+    fn test_mark_simple_votes() {
+        let mut batch = vec![
+            packet_from_bytes(&vote_transaction_bytes()),
+            packet_from_bytes(&transfer_transaction_bytes()),
+            packet_from_bytes(&vote_transaction_bytes()),
+            packet_from_bytes(&vote_transaction_bytes()),
+        ];
+        // stale flag is cleared
+        batch[1].meta_mut().set_simple_vote(true);
+        batch[3].meta_mut().set_discard(true);
+
+        assert_eq!(mark_simple_votes(&mut batch), 2);
+        let flags: Vec<bool> = batch
+            .iter()
+            .map(|packet| packet.meta().is_simple_vote_tx())
+            .collect();
+        assert_eq!(flags, vec![true, false, true, false]);
+    }
+}
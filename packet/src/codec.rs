@@ -0,0 +1,435 @@
+//! A stable binary encoding of packets, for capture files.
+//!
+//! The serde derive of [`Packet`] follows the in-memory layout, which changes
+//! whenever the struct is refactored. Data that outlives the process, such as
+//! packet captures, should use this codec instead: its layout is versioned
+//! and only changes along with [`CODEC_VERSION`].
+//!
+//! # Layout
+//!
+//! All integers are little-endian.
+//!
+//! | bytes | field                                                      |
+//! |-------|------------------------------------------------------------|
+//! | 1     | codec version, `1`                                         |
+//! | 1     | [`PacketFlags`] bits                                       |
+//! | 2     | port                                                       |
+//! | 1     | ip tag, `4` or `6`                                         |
+//! | 16    | ip address, IPv4 addresses padded with trailing zeros      |
+//! | 2     | payload length, at most [`PACKET_DATA_SIZE`]               |
+//! | n     | payload                                                    |
+//!
+//! The remote pubkey, the drop reason, the `received_at` timestamp, the
+//! `connection_id`, and the flow info and scope id of IPv6 addresses of
+//! [`Meta`] are not encoded.
+//!
+//! A capture file starts with [`CAPTURE_MAGIC`], followed by encoded packets
+//! back to back. See [`PacketFileWriter`] and [`PacketFileReader`].
+
+use {
+    crate::{Meta, Packet, PacketFlags, PACKET_DATA_SIZE},
+    std::{
+        fmt,
+        io::{self, Read, Write},
+        net::{IpAddr, Ipv4Addr, Ipv6Addr},
+    },
+};
+
+/// Version of the encoding written by [`Packet::encode_v1`].
+pub const CODEC_VERSION: u8 = 1;
+
+/// Magic bytes at the start of a capture file.
+pub const CAPTURE_MAGIC: [u8; 8] = *b"SOLPKTS\0";
+
+/// Size of an encoded packet before its payload.
+pub const HEADER_SIZE: usize = 23;
+
+const IPV4_TAG: u8 = 4;
+const IPV6_TAG: u8 = 6;
+
+/// Errors returned when decoding packets.
+#[derive(Debug)]
+pub enum PacketCodecError {
+    /// The input ends before the end of the packet.
+    Truncated,
+    /// The packet was encoded with an unknown codec version.
+    UnsupportedVersion(u8),
+    /// The ip tag is neither `4` nor `6`, or an IPv4 address has non-zero
+    /// padding.
+    InvalidAddress,
+    /// The payload is longer than [`PACKET_DATA_SIZE`].
+    PayloadTooLarge(usize),
+    /// The capture file does not start with [`CAPTURE_MAGIC`].
+    InvalidMagic,
+    /// Reading the capture file failed.
+    Io(io::Error),
+}
+
+impl core::error::Error for PacketCodecError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for PacketCodecError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Truncated => f.write_str("encoded packet is truncated"),
+            Self::UnsupportedVersion(version) => {
+                write!(f, "unsupported packet codec version {version}")
+            }
+            Self::InvalidAddress => f.write_str("invalid encoded ip address"),
+            Self::PayloadTooLarge(len) => {
+                write!(f, "payload of {len} bytes exceeds {PACKET_DATA_SIZE} bytes")
+            }
+            Self::InvalidMagic => f.write_str("not a packet capture file"),
+            Self::Io(e) => write!(f, "failed to read capture file: {e}"),
+        }
+    }
+}
+
+impl From<io::Error> for PacketCodecError {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl Packet {
+    /// Append the version 1 encoding of the packet to `out`.
+    ///
+    /// The payload of discarded packets is encoded too. See the
+    /// [module documentation](crate::codec) for the layout.
+    pub fn encode_v1(&self, out: &mut Vec<u8>) {
+        let payload = &self.buffer[..self.meta.size.min(PACKET_DATA_SIZE)];
+        let (ip_tag, ip) = match self.meta.addr {
+            IpAddr::V4(addr) => {
+                let mut ip = [0; 16];
+                ip[..4].copy_from_slice(&addr.octets());
+                (IPV4_TAG, ip)
+            }
+            IpAddr::V6(addr) => (IPV6_TAG, addr.octets()),
+        };
+        out.reserve(HEADER_SIZE + payload.len());
+        out.push(CODEC_VERSION);
+        out.push(self.meta.flags.bits());
+        out.extend_from_slice(&self.meta.port.to_le_bytes());
+        out.push(ip_tag);
+        out.extend_from_slice(&ip);
+        out.extend_from_slice(&(payload.len() as u16).to_le_bytes());
+        out.extend_from_slice(payload);
+    }
+
+    /// Decode a packet encoded by [`encode_v1`](Packet::encode_v1) from the
+    /// start of `bytes`, returning it along with the number of bytes read.
+    pub fn decode_v1(bytes: &[u8]) -> Result<(Packet, usize), PacketCodecError> {
+        let header = bytes
+            .get(..HEADER_SIZE)
+            .ok_or(PacketCodecError::Truncated)?;
+        let (meta, payload_len) = decode_header(header.try_into().unwrap())?;
+        let len = HEADER_SIZE + payload_len;
+        let payload = bytes
+            .get(HEADER_SIZE..len)
+            .ok_or(PacketCodecError::Truncated)?;
+        let mut packet = Packet {
+            buffer: [0; PACKET_DATA_SIZE],
+            meta,
+        };
+        packet.buffer[..payload_len].copy_from_slice(payload);
+        Ok((packet, len))
+    }
+}
+
+/// Decode the meta and the payload length of an encoded packet.
+fn decode_header(header: &[u8; HEADER_SIZE]) -> Result<(Meta, usize), PacketCodecError> {
+    if header[0] != CODEC_VERSION {
+        return Err(PacketCodecError::UnsupportedVersion(header[0]));
+    }
+    let flags = PacketFlags::from_bits_retain(header[1]);
+    let port = u16::from_le_bytes([header[2], header[3]]);
+    let ip: [u8; 16] = header[5..21].try_into().unwrap();
+    let addr = match header[4] {
+        IPV4_TAG if ip[4..].iter().all(|&byte| byte == 0) => {
+            IpAddr::V4(Ipv4Addr::new(ip[0], ip[1], ip[2], ip[3]))
+        }
+        IPV6_TAG => IpAddr::V6(Ipv6Addr::from(ip)),
+        _ => return Err(PacketCodecError::InvalidAddress),
+    };
+    let payload_len = usize::from(u16::from_le_bytes([header[21], header[22]]));
+    if payload_len > PACKET_DATA_SIZE {
+        return Err(PacketCodecError::PayloadTooLarge(payload_len));
+    }
+    Ok((Meta::new(payload_len, addr, port, flags, None), payload_len))
+}
+
+/// Writes packets to a capture file.
+#[derive(Debug)]
+pub struct PacketFileWriter<W> {
+    writer: W,
+    buffer: Vec<u8>,
+}
+
+impl<W: Write> PacketFileWriter<W> {
+    /// Start a capture file by writing [`CAPTURE_MAGIC`] to `writer`.
+    ///
+    /// Wrap files in a [`BufWriter`](std::io::BufWriter), as every packet is
+    /// written with a separate call.
+    pub fn new(mut writer: W) -> io::Result<Self> {
+        writer.write_all(&CAPTURE_MAGIC)?;
+        Ok(Self {
+            writer,
+            buffer: Vec::with_capacity(HEADER_SIZE + PACKET_DATA_SIZE),
+        })
+    }
+
+    /// Append `packet` to the capture file.
+    pub fn write_packet(&mut self, packet: &Packet) -> io::Result<()> {
+        self.buffer.clear();
+        packet.encode_v1(&mut self.buffer);
+        self.writer.write_all(&self.buffer)
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
+    /// Returns the underlying writer, without flushing it.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+/// Reads packets from a capture file written by [`PacketFileWriter`].
+///
+/// Iterating over the reader yields packets until the end of the file.
+#[derive(Debug)]
+pub struct PacketFileReader<R> {
+    reader: R,
+}
+
+impl<R: Read> PacketFileReader<R> {
+    /// Open a capture file, checking that it starts with [`CAPTURE_MAGIC`].
+    pub fn new(mut reader: R) -> Result<Self, PacketCodecError> {
+        let mut magic = [0; CAPTURE_MAGIC.len()];
+        read_exact(&mut reader, &mut magic)?;
+        if magic != CAPTURE_MAGIC {
+            return Err(PacketCodecError::InvalidMagic);
+        }
+        Ok(Self { reader })
+    }
+
+    /// Read the next packet, returning `None` at the end of the file.
+    pub fn read_packet(&mut self) -> Result<Option<Packet>, PacketCodecError> {
+        let mut header = [0; HEADER_SIZE];
+        // a clean end of file may only happen between packets
+        let mut filled = 0;
+        while filled < HEADER_SIZE {
+            match self.reader.read(&mut header[filled..]) {
+                Ok(0) if filled == 0 => return Ok(None),
+                Ok(0) => return Err(PacketCodecError::Truncated),
+                Ok(n) => filled += n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+        let (meta, payload_len) = decode_header(&header)?;
+        let mut packet = Packet {
+            buffer: [0; PACKET_DATA_SIZE],
+            meta,
+        };
+        read_exact(&mut self.reader, &mut packet.buffer[..payload_len])?;
+        Ok(Some(packet))
+    }
+}
+
+impl<R: Read> Iterator for PacketFileReader<R> {
+    type Item = Result<Packet, PacketCodecError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_packet().transpose()
+    }
+}
+
+fn read_exact<R: Read>(reader: &mut R, buf: &mut [u8]) -> Result<(), PacketCodecError> {
+    reader.read_exact(buf).map_err(|e| match e.kind() {
+        io::ErrorKind::UnexpectedEof => PacketCodecError::Truncated,
+        _ => PacketCodecError::Io(e),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, std::net::SocketAddr};
+
+    fn packet(addr: SocketAddr, flags: PacketFlags, payload: &[u8]) -> Packet {
+        let mut packet = Packet::default();
+        packet.buffer_mut()[..payload.len()].copy_from_slice(payload);
+        let meta = packet.meta_mut();
+        meta.size = payload.len();
+        meta.set_socket_addr(&addr);
+        meta.flags = flags;
+        packet
+    }
+
+    #[test]
+    fn test_golden_bytes_ipv4() {
+        let packet = packet(
+            "10.1.2.3:8001".parse().unwrap(),
+            PacketFlags::FORWARDED | PacketFlags::SIMPLE_VOTE_TX,
+            &[0xde, 0xad, 0xbe, 0xef],
+        );
+        let mut bytes = vec![];
+        packet.encode_v1(&mut bytes);
+        assert_eq!(
+            bytes,
+            [
+                1,      // version
+                0b1010, // flags
+                0x41, 0x1f, // port
+                4, 10, 1, 2, 3, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, // ip
+                4, 0, // payload length
+                0xde, 0xad, 0xbe, 0xef, // payload
+            ]
+        );
+        assert_eq!(Packet::decode_v1(&bytes).unwrap(), (packet, bytes.len()));
+    }
+
+    #[test]
+    fn test_golden_bytes_ipv6() {
+        let packet = packet(
+            "[2001:db8::1]:65535".parse().unwrap(),
+            PacketFlags::empty(),
+            &[],
+        );
+        let mut bytes = vec![];
+        packet.encode_v1(&mut bytes);
+        assert_eq!(
+            bytes,
+            [
+                1, // version
+                0, // flags
+                0xff, 0xff, // port
+                6, 0x20, 0x01, 0x0d, 0xb8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, // ip
+                0, 0, // payload length
+            ]
+        );
+        assert_eq!(Packet::decode_v1(&bytes).unwrap(), (packet, HEADER_SIZE));
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let payload: Vec<u8> = (0..PACKET_DATA_SIZE).map(|i| i as u8).collect();
+        let packets = [
+            packet("127.0.0.1:1".parse().unwrap(), PacketFlags::all(), &payload),
+            packet(
+                "[::1]:8000".parse().unwrap(),
+                PacketFlags::DISCARD | PacketFlags::REPAIR,
+                &payload[..100],
+            ),
+            packet("0.0.0.0:0".parse().unwrap(), PacketFlags::empty(), &[]),
+        ];
+        let mut bytes = vec![];
+        for packet in &packets {
+            packet.encode_v1(&mut bytes);
+        }
+        let mut offset = 0;
+        for packet in &packets {
+            let (decoded, len) = Packet::decode_v1(&bytes[offset..]).unwrap();
+            // compare the payload of discarded packets too
+            assert_eq!(decoded.meta(), packet.meta());
+            let size = packet.meta().size;
+            assert_eq!(decoded.buffer[..size], packet.buffer[..size]);
+            offset += len;
+        }
+        assert_eq!(offset, bytes.len());
+    }
+
+    #[test]
+    fn test_decode_errors() {
+        let mut bytes = vec![];
+        packet(
+            "10.0.0.1:80".parse().unwrap(),
+            PacketFlags::empty(),
+            &[1, 2],
+        )
+        .encode_v1(&mut bytes);
+
+        for len in 0..bytes.len() {
+            assert!(matches!(
+                Packet::decode_v1(&bytes[..len]),
+                Err(PacketCodecError::Truncated)
+            ));
+        }
+
+        let mut invalid = bytes.clone();
+        invalid[0] = 2;
+        assert!(matches!(
+            Packet::decode_v1(&invalid),
+            Err(PacketCodecError::UnsupportedVersion(2))
+        ));
+
+        let mut invalid = bytes.clone();
+        invalid[4] = 5;
+        assert!(matches!(
+            Packet::decode_v1(&invalid),
+            Err(PacketCodecError::InvalidAddress)
+        ));
+        let mut invalid = bytes.clone();
+        invalid[20] = 1;
+        assert!(matches!(
+            Packet::decode_v1(&invalid),
+            Err(PacketCodecError::InvalidAddress)
+        ));
+
+        let mut invalid = bytes;
+        invalid[21..23].copy_from_slice(&(PACKET_DATA_SIZE as u16 + 1).to_le_bytes());
+        assert!(matches!(
+            Packet::decode_v1(&invalid),
+            Err(PacketCodecError::PayloadTooLarge(len)) if len == PACKET_DATA_SIZE + 1
+        ));
+    }
+
+    #[test]
+    fn test_capture_file() {
+        let packets: Vec<_> = (0..10u8)
+            .map(|i| {
+                packet(
+                    SocketAddr::from(([10, 0, 0, i], 8000 + u16::from(i))),
+                    PacketFlags::from_bits_retain(i),
+                    &vec![i; usize::from(i) * 100],
+                )
+            })
+            .collect();
+        let mut writer = PacketFileWriter::new(vec![]).unwrap();
+        for packet in &packets {
+            writer.write_packet(packet).unwrap();
+        }
+        let file = writer.into_inner();
+        assert_eq!(file[..CAPTURE_MAGIC.len()], CAPTURE_MAGIC);
+
+        let reader = PacketFileReader::new(file.as_slice()).unwrap();
+        let read: Vec<_> = reader.collect::<Result<_, _>>().unwrap();
+        assert_eq!(read, packets);
+
+        // truncated in the middle of a packet
+        let mut reader = PacketFileReader::new(&file[..file.len() - 1]).unwrap();
+        for _ in 0..packets.len() - 1 {
+            reader.read_packet().unwrap().unwrap();
+        }
+        assert!(matches!(
+            reader.read_packet(),
+            Err(PacketCodecError::Truncated)
+        ));
+
+        assert!(matches!(
+            PacketFileReader::new(&b"not a capture"[..]),
+            Err(PacketCodecError::InvalidMagic)
+        ));
+        assert!(matches!(
+            PacketFileReader::new(&CAPTURE_MAGIC[..4]),
+            Err(PacketCodecError::Truncated)
+        ));
+    }
+}
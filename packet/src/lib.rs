@@ -2,6 +2,32 @@
 #![cfg_attr(feature = "frozen-abi", feature(min_specialization))]
 #![cfg_attr(docsrs, feature(doc_cfg))]
 
+#[cfg(feature = "dev-context-only-utils")]
+mod arbitrary_impl;
+#[cfg(feature = "arena")]
+pub mod arena;
+pub mod batch;
+#[cfg(feature = "classify")]
+pub mod classify;
+pub mod codec;
+#[cfg(feature = "mmsg")]
+pub mod mmsg;
+#[cfg(feature = "quic")]
+pub mod quic;
+pub mod rate_limit;
+#[cfg(feature = "serde")]
+mod serde_impl;
+#[cfg(any(feature = "sign", feature = "verify"))]
+mod sign;
+#[cfg(feature = "ed25519-verify")]
+pub mod sigverify;
+mod well_formed;
+
+#[cfg(feature = "sign")]
+pub use crate::sign::SignRegionError;
+pub use crate::well_formed::MetaValidationError;
+#[cfg(feature = "serde")]
+use serde_derive::{Deserialize, Serialize};
 #[cfg(feature = "frozen-abi")]
 use solana_frozen_abi_macro::AbiExample;
 #[cfg(feature = "bincode")]
@@ -14,15 +40,11 @@ use {
     solana_pubkey::Pubkey,
     std::{
         fmt,
-        net::{IpAddr, Ipv4Addr, SocketAddr},
+        net::{IpAddr, Ipv4Addr, SocketAddr, SocketAddrV6},
+        num::NonZeroU8,
         slice::SliceIndex,
     },
 };
-#[cfg(feature = "serde")]
-use {
-    serde_derive::{Deserialize, Serialize},
-    serde_with::{serde_as, Bytes},
-};
 
 #[cfg(test)]
 static_assertions::const_assert_eq!(PACKET_DATA_SIZE, 1232);
@@ -64,9 +86,63 @@ bitflags! {
     }
 }
 
+/// Why a packet was marked as discard, see [`Meta::discard_with_reason`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DropReason {
+    /// The packet duplicates one seen before.
+    Dedup,
+    /// A signature of the packet failed to verify.
+    SigverifyFailed,
+    /// The packet is too short or does not parse.
+    Malformed,
+    /// The sender exceeded its rate limit.
+    RateLimited,
+    /// The packet was dropped while forwarding.
+    Forwarding,
+    /// A reason not covered above, identified by a custom code.
+    ///
+    /// Codes of the named reasons read back as those reasons, and a code of
+    /// zero reads back as `Other(u8::MAX)`; custom codes should be above
+    /// [`DropReason::MAX_NAMED_CODE`].
+    Other(u8),
+}
+
+impl DropReason {
+    /// The highest code used by a named reason.
+    pub const MAX_NAMED_CODE: u8 = 5;
+
+    /// The code stored in [`Meta`] for this reason.
+    pub const fn code(self) -> NonZeroU8 {
+        let code = match self {
+            Self::Dedup => 1,
+            Self::SigverifyFailed => 2,
+            Self::Malformed => 3,
+            Self::RateLimited => 4,
+            Self::Forwarding => 5,
+            Self::Other(code) => code,
+        };
+        match NonZeroU8::new(code) {
+            Some(code) => code,
+            None => NonZeroU8::MAX,
+        }
+    }
+
+    /// The reason stored in [`Meta`] as `code`.
+    pub const fn from_code(code: NonZeroU8) -> Self {
+        match code.get() {
+            1 => Self::Dedup,
+            2 => Self::SigverifyFailed,
+            3 => Self::Malformed,
+            4 => Self::RateLimited,
+            5 => Self::Forwarding,
+            code => Self::Other(code),
+        }
+    }
+}
+
 #[cfg_attr(feature = "frozen-abi", derive(AbiExample))]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug)]
 #[repr(C)]
 pub struct Meta {
     pub size: usize,
@@ -74,8 +150,58 @@ pub struct Meta {
     pub port: u16,
     pub flags: PacketFlags,
     remote_pubkey: Pubkey,
+    // Not serialized: the serde layout is positional, so the field could
+    // not be skipped only when empty without breaking bincode decoding.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    drop_reason: Option<NonZeroU8>,
+    // The flow info and scope id of an IPv6 source, zero otherwise. Only
+    // the scope id is serialized, as link-local addresses can't be reached
+    // without it; the human readable form keeps it in the address string.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    flowinfo: u32,
+    scope_id: u32,
+    // Kernel receive timestamp in nanoseconds since the Unix epoch. Not
+    // serialized either, as it only makes sense on the receiving node.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    received_at: Option<u64>,
+    // The QUIC connection the packet arrived on, if any. Not serialized for
+    // the same reason.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    connection_id: Option<u64>,
+}
+
+// Fields that are not serialized are left out of equality, so that a meta
+// compares equal to itself after a serialization round trip. A new field
+// must either be serialized or be ignored here as well; the size check
+// below is a reminder to revisit this impl.
+impl PartialEq for Meta {
+    fn eq(&self, other: &Self) -> bool {
+        let Self {
+            size,
+            addr,
+            port,
+            flags,
+            remote_pubkey,
+            drop_reason: _,
+            flowinfo: _,
+            scope_id,
+            received_at: _,
+            connection_id: _,
+        } = self;
+        *size == other.size
+            && *addr == other.addr
+            && *port == other.port
+            && *flags == other.flags
+            && *remote_pubkey == other.remote_pubkey
+            && *scope_id == other.scope_id
+    }
 }
 
+#[cfg(target_pointer_width = "64")]
+const _: () = assert!(core::mem::size_of::<Meta>() == 104);
+
+impl Eq for Meta {}
+
 #[cfg(feature = "frozen-abi")]
 impl ::solana_frozen_abi::abi_example::AbiExample for PacketFlags {
     fn example() -> Self {
@@ -91,43 +217,15 @@ impl ::solana_frozen_abi::abi_example::EvenAsOpaque for PacketFlags {
     const TYPE_NAME_MATCHER: &'static str = "::_::InternalBitFlags";
 }
 
-// serde_as is used as a work around because array isn't supported by serde
-// (and serde_bytes).
-//
-// the root cause is of a historical special handling for [T; 0] in rust's
-// `Default` and supposedly mirrored serde's `Serialize` (macro) impls,
-// pre-dating stabilized const generics, meaning it'll take long time...:
-//   https://github.com/rust-lang/rust/issues/61415
-//   https://github.com/rust-lang/rust/issues/88744#issuecomment-1138678928
-//
-// Due to the nature of the root cause, the current situation is complicated.
-// All in all, the serde_as solution is chosen for good perf and low maintenance
-// need at the cost of another crate dependency..
-//
-// For details, please refer to the below various links...
-//
-// relevant merged/published pr for this serde_as functionality used here:
-//   https://github.com/jonasbb/serde_with/pull/277
-// open pr at serde_bytes:
-//   https://github.com/serde-rs/bytes/pull/28
-// open issue at serde:
-//   https://github.com/serde-rs/serde/issues/1937
-// closed pr at serde (due to the above mentioned [N; 0] issue):
-//   https://github.com/serde-rs/serde/pull/1860
-// ryoqun's dirty experiments:
-//   https://github.com/ryoqun/serde-array-comparisons
-//
-// We use the cfg_eval crate as advised by the serde_with guide:
-// https://docs.rs/serde_with/latest/serde_with/guide/serde_as/index.html#gating-serde_as-on-features
-#[cfg_attr(feature = "serde", cfg_eval::cfg_eval, serde_as)]
+/// With the `serde` feature, a packet serializes as its in-memory layout. With
+/// the `json` feature, human readable formats use a readable form instead,
+/// see `Packet::to_json_debug`.
 #[cfg_attr(feature = "frozen-abi", derive(AbiExample))]
-#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 #[derive(Clone, Eq)]
 #[repr(C)]
 pub struct Packet {
     // Bytes past Packet.meta.size are not valid to read from.
     // Use Packet.data(index) to read from the buffer.
-    #[cfg_attr(feature = "serde", serde_as(as = "Bytes"))]
     buffer: [u8; PACKET_DATA_SIZE],
     meta: Meta,
 }
@@ -157,6 +255,77 @@ impl Packet {
         }
     }
 
+    /// Returns true if the packet is not marked as discard and holds at least
+    /// `n` bytes of payload.
+    ///
+    /// This is a cheap check for rejecting packets that are too short to
+    /// contain a valid payload before doing any expensive parsing.
+    #[inline]
+    pub fn has_min_len(&self, n: usize) -> bool {
+        !self.meta.discard() && self.meta.size >= n
+    }
+
+    /// Returns the byte at `offset`, or None if it is past the end of the
+    /// payload or the packet is marked as discard.
+    #[inline]
+    pub fn peek_u8(&self, offset: usize) -> Option<u8> {
+        self.data(offset).copied()
+    }
+
+    /// Returns the little-endian `u16` starting at `offset`, or None if it
+    /// does not fit in the payload or the packet is marked as discard.
+    #[inline]
+    pub fn peek_u16_le(&self, offset: usize) -> Option<u16> {
+        self.peek_array(offset).map(u16::from_le_bytes)
+    }
+
+    /// Returns the `N` bytes starting at `offset`, or None if they do not fit
+    /// in the payload or the packet is marked as discard.
+    #[inline]
+    pub fn peek_array<const N: usize>(&self, offset: usize) -> Option<[u8; N]> {
+        let bytes = self.data(offset..offset.checked_add(N)?)?;
+        bytes.try_into().ok()
+    }
+
+    /// Returns the alignment of the start of the payload, the largest power of
+    /// two dividing its address.
+    ///
+    /// The payload is at the start of the packet, so it is aligned to at least
+    /// `align_of::<Packet>()` wherever the packet is moved, cloned or batched.
+    #[inline]
+    pub fn payload_alignment(&self) -> usize {
+        1 << (self.buffer.as_ptr() as usize).trailing_zeros()
+    }
+
+    /// Returns a reference to the `T` starting at `offset`, or None if it does
+    /// not fit in the payload, is not aligned for `T` or the packet is marked
+    /// as discard.
+    ///
+    /// Values at offsets multiple of their alignment are aligned if the
+    /// alignment of `T` is at most [`Packet::payload_alignment`]. Use
+    /// [`Packet::data_read_pod`] to read other values.
+    #[cfg(feature = "bytemuck")]
+    #[inline]
+    pub fn data_as_pod<T: bytemuck::Pod>(&self, offset: usize) -> Option<&T> {
+        let bytes = self.data(offset..offset.checked_add(std::mem::size_of::<T>())?)?;
+        bytemuck::try_from_bytes(bytes).ok()
+    }
+
+    /// Returns a copy of the `T` starting at `offset`, or None if it does not
+    /// fit in the payload or the packet is marked as discard.
+    ///
+    /// Aligned values are read in place, and unaligned ones copied with an
+    /// unaligned read.
+    #[cfg(feature = "bytemuck")]
+    #[inline]
+    pub fn data_read_pod<T: bytemuck::Pod>(&self, offset: usize) -> Option<T> {
+        let bytes = self.data(offset..offset.checked_add(std::mem::size_of::<T>())?)?;
+        Some(match bytemuck::try_from_bytes(bytes) {
+            Ok(value) => *value,
+            Err(_) => bytemuck::pod_read_unaligned(bytes),
+        })
+    }
+
     /// Returns a mutable reference to the entirety of the underlying buffer to
     /// write into. The caller is responsible for updating Packet.meta.size
     /// after writing to the buffer.
@@ -176,6 +345,24 @@ impl Packet {
         &mut self.meta
     }
 
+    /// Summarize the packet's address, size, flags and first payload bytes.
+    ///
+    /// The payload of discarded packets is included, since it is what
+    /// operators need when diagnosing drops.
+    pub fn summary(&self) -> PacketSummary {
+        let size = self.meta.size.min(PACKET_DATA_SIZE);
+        let payload_len = size.min(SUMMARY_PAYLOAD_BYTES);
+        let mut payload = [0; SUMMARY_PAYLOAD_BYTES];
+        payload[..payload_len].copy_from_slice(&self.buffer[..payload_len]);
+        PacketSummary {
+            addr: self.meta.socket_addr(),
+            size: self.meta.size,
+            flags: self.meta.flags,
+            payload,
+            payload_len,
+        }
+    }
+
     #[cfg(feature = "bincode")]
     pub fn from_data<T: Encode>(dest: Option<&SocketAddr>, data: T) -> Result<Self> {
         let mut packet = Self::default();
@@ -214,14 +401,60 @@ impl Packet {
     }
 }
 
+/// `{:?}` includes the packet flags; the alternate form `{:#?}` prints only the
+/// size and address.
 impl fmt::Debug for Packet {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(
-            f,
-            "Packet {{ size: {:?}, addr: {:?} }}",
-            self.meta.size,
-            self.meta.socket_addr()
-        )
+        if f.alternate() {
+            write!(
+                f,
+                "Packet {{ size: {:?}, addr: {:?} }}",
+                self.meta.size,
+                self.meta.socket_addr()
+            )
+        } else {
+            write!(
+                f,
+                "Packet {{ size: {:?}, addr: {:?}, flags: {} }}",
+                self.meta.size,
+                self.meta.socket_addr(),
+                FlagNames(self.meta.flags)
+            )
+        }
+    }
+}
+
+/// Number of payload bytes included in a [`PacketSummary`].
+pub const SUMMARY_PAYLOAD_BYTES: usize = 8;
+
+/// A one-line description of a packet for log lines, see [`Packet::summary`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PacketSummary {
+    pub addr: SocketAddr,
+    pub size: usize,
+    pub flags: PacketFlags,
+    payload: [u8; SUMMARY_PAYLOAD_BYTES],
+    payload_len: usize,
+}
+
+impl PacketSummary {
+    /// The first payload bytes of the packet, at most
+    /// [`SUMMARY_PAYLOAD_BYTES`].
+    pub fn payload(&self) -> &[u8] {
+        &self.payload[..self.payload_len]
+    }
+}
+
+/// Formats as `addr [FLAGS] sizeB payload`, with the payload prefix in hex.
+impl fmt::Display for PacketSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} {}B", self.addr, FlagNames(self.flags), self.size)?;
+        if !self.payload().is_empty() {
+            f.write_str(" ")?;
+        }
+        self.payload()
+            .iter()
+            .try_for_each(|byte| write!(f, "{byte:02x}"))
     }
 }
 
@@ -256,16 +489,66 @@ impl Meta {
             port,
             flags,
             remote_pubkey: remote_pubkey.unwrap_or_default(),
+            drop_reason: None,
+            flowinfo: 0,
+            scope_id: 0,
+            received_at: None,
+            connection_id: None,
         }
     }
 
+    /// The source or destination address of the packet, with the flow info
+    /// and scope id of IPv6 addresses set by [`Meta::set_socket_addr`].
     pub fn socket_addr(&self) -> SocketAddr {
-        SocketAddr::new(self.addr, self.port)
+        match self.addr {
+            IpAddr::V4(addr) => SocketAddr::new(addr.into(), self.port),
+            IpAddr::V6(addr) => {
+                SocketAddrV6::new(addr, self.port, self.flowinfo, self.scope_id).into()
+            }
+        }
     }
 
+    /// Sets the address of the packet, including the flow info and scope id
+    /// of IPv6 addresses.
     pub fn set_socket_addr(&mut self, socket_addr: &SocketAddr) {
-        self.addr = socket_addr.ip();
+        match socket_addr {
+            SocketAddr::V4(socket_addr) => {
+                self.addr = IpAddr::V4(*socket_addr.ip());
+                self.port = socket_addr.port();
+                self.flowinfo = 0;
+                self.scope_id = 0;
+            }
+            SocketAddr::V6(socket_addr) => self.set_socket_addr_v6(socket_addr),
+        }
+    }
+
+    /// Sets the IPv6 address of the packet, including its flow info and scope
+    /// id, which link-local addresses need.
+    pub fn set_socket_addr_v6(&mut self, socket_addr: &SocketAddrV6) {
+        self.addr = IpAddr::V6(*socket_addr.ip());
         self.port = socket_addr.port();
+        self.flowinfo = socket_addr.flowinfo();
+        self.scope_id = socket_addr.scope_id();
+    }
+
+    /// When the kernel received the packet, in nanoseconds since the Unix
+    /// epoch, if it was timestamped. Set by `mmsg::recv_mmsg`.
+    pub fn received_at(&self) -> Option<u64> {
+        self.received_at
+    }
+
+    pub fn set_received_at(&mut self, received_at: Option<u64>) {
+        self.received_at = received_at;
+    }
+
+    /// The id of the QUIC connection the packet was received on, if any.
+    /// Set by `Packet::from_quic_datagram`.
+    pub fn connection_id(&self) -> Option<u64> {
+        self.connection_id
+    }
+
+    pub fn set_connection_id(&mut self, connection_id: Option<u64>) {
+        self.connection_id = connection_id;
     }
 
     pub fn set_from_staked_node(&mut self, from_staked_node: bool) {
@@ -278,9 +561,58 @@ impl Meta {
         self.flags.contains(PacketFlags::DISCARD)
     }
 
+    /// Sets or clears the discard flag. Clearing it also clears the drop
+    /// reason.
     #[inline]
     pub fn set_discard(&mut self, discard: bool) {
         self.flags.set(PacketFlags::DISCARD, discard);
+        if !discard {
+            self.drop_reason = None;
+        }
+    }
+
+    /// Marks the packet as discard, recording why.
+    #[inline]
+    pub fn discard_with_reason(&mut self, reason: DropReason) {
+        self.set_discard(true);
+        self.drop_reason = Some(reason.code());
+    }
+
+    /// The reason recorded by [`Meta::discard_with_reason`], if the packet
+    /// is discarded.
+    #[inline]
+    pub fn drop_reason(&self) -> Option<DropReason> {
+        self.drop_reason
+            .filter(|_| self.discard())
+            .map(DropReason::from_code)
+    }
+
+    /// Marks the packet as discard if `cond` holds, returning true if this
+    /// call newly marked it.
+    ///
+    /// Meant for filtering batches, where the return value can be summed to
+    /// count how many packets a filter rejected:
+    ///
+    /// ```
+    /// # use solana_packet::Packet;
+    /// # let mut batch = vec![Packet::default(); 4];
+    /// let discarded = batch
+    ///     .iter_mut()
+    ///     .map(|packet| {
+    ///         let cond = packet.peek_u8(0) != Some(1);
+    ///         packet.meta_mut().mark_discard_if(cond)
+    ///     })
+    ///     .filter(|&marked| marked)
+    ///     .count();
+    /// # assert_eq!(discarded, 4);
+    /// ```
+    #[inline]
+    pub fn mark_discard_if(&mut self, cond: bool) -> bool {
+        let marked = cond && !self.discard();
+        if marked {
+            self.set_discard(true);
+        }
+        marked
     }
 
     #[deprecated(since = "4.2.0", note = "Not in use anymore")]
@@ -337,6 +669,48 @@ impl Meta {
     }
 }
 
+/// Marks every packet in `batch` holding fewer than `min_len` bytes as
+/// discard, returning how many packets were newly marked.
+pub fn filter_discard_short(batch: &mut [Packet], min_len: usize) -> usize {
+    batch
+        .iter_mut()
+        .map(|packet| {
+            let cond = !packet.has_min_len(min_len);
+            packet.meta_mut().mark_discard_if(cond)
+        })
+        .filter(|&marked| marked)
+        .count()
+}
+
+/// Formats as `addr [FLAGS] sizeB`, e.g. `10.0.0.1:8001 [FORWARDED|FROM_STAKED_NODE] 968B`.
+impl fmt::Display for Meta {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {}B",
+            self.socket_addr(),
+            FlagNames(self.flags),
+            self.size
+        )
+    }
+}
+
+/// Formats packet flags as `[NAME|NAME]`.
+struct FlagNames(PacketFlags);
+
+impl fmt::Display for FlagNames {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("[")?;
+        for (i, (name, _)) in self.0.iter_names().enumerate() {
+            if i > 0 {
+                f.write_str("|")?;
+            }
+            f.write_str(name)?;
+        }
+        f.write_str("]")
+    }
+}
+
 impl Default for Meta {
     fn default() -> Self {
         Self {
@@ -345,6 +719,11 @@ impl Default for Meta {
             port: 0,
             flags: PacketFlags::empty(),
             remote_pubkey: Pubkey::default(),
+            drop_reason: None,
+            flowinfo: 0,
+            scope_id: 0,
+            received_at: None,
+            connection_id: None,
         }
     }
 }
@@ -392,6 +771,75 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_payload_alignment() {
+        let packets = vec![Packet::default(); 3];
+        for packet in &packets {
+            assert!(packet.payload_alignment() >= std::mem::align_of::<Packet>());
+            assert!(packet.payload_alignment().is_power_of_two());
+            assert_eq!(
+                packet.buffer.as_ptr() as usize % packet.payload_alignment(),
+                0
+            );
+        }
+        assert_eq!(
+            std::mem::align_of::<Packet>(),
+            std::mem::align_of::<usize>()
+        );
+    }
+
+    #[test]
+    fn test_data_as_pod() {
+        let mut packet = Packet::default();
+        for (i, byte) in packet.buffer_mut()[..24].iter_mut().enumerate() {
+            *byte = i as u8;
+        }
+        packet.meta_mut().size = 20;
+
+        assert_eq!(
+            packet.data_as_pod::<u64>(8),
+            Some(&u64::from_le_bytes([8, 9, 10, 11, 12, 13, 14, 15]))
+        );
+        assert_eq!(
+            packet.data_as_pod::<[u32; 2]>(0),
+            Some(&[
+                u32::from_le_bytes([0, 1, 2, 3]),
+                u32::from_le_bytes([4, 5, 6, 7])
+            ])
+        );
+        // past the payload
+        assert_eq!(packet.data_as_pod::<u64>(16), None);
+        assert_eq!(packet.data_read_pod::<u64>(16), None);
+        assert_eq!(packet.data_read_pod::<u64>(usize::MAX), None);
+
+        packet.meta_mut().set_discard(true);
+        assert_eq!(packet.data_as_pod::<u64>(0), None);
+        assert_eq!(packet.data_read_pod::<u64>(0), None);
+    }
+
+    #[test]
+    fn test_data_read_pod_unaligned() {
+        let mut packet = Packet::default();
+        for (i, byte) in packet.buffer_mut()[..24].iter_mut().enumerate() {
+            *byte = i as u8;
+        }
+        packet.meta_mut().size = 24;
+
+        for offset in 0..=16 {
+            let expected = u64::from_le_bytes(packet.peek_array(offset).unwrap());
+            assert_eq!(packet.data_read_pod::<u64>(offset), Some(expected));
+            #[allow(clippy::manual_is_multiple_of)]
+            let aligned =
+                (packet.buffer.as_ptr() as usize + offset) % std::mem::align_of::<u64>() == 0;
+            assert_eq!(
+                packet.data_as_pod::<u64>(offset).copied(),
+                aligned.then_some(expected)
+            );
+        }
+        // types with an alignment of 1 are never misaligned
+        assert_eq!(packet.data_as_pod::<[u8; 3]>(5), Some(&[5, 6, 7]));
+    }
+
     #[test]
     fn test_remote_pubkey() {
         let mut meta = Meta::default();
@@ -424,4 +872,242 @@ mod tests {
         assert_eq!(meta.remote_pubkey, Pubkey::default());
         assert_eq!(meta.remote_pubkey(), None);
     }
+
+    #[test]
+    fn test_socket_addr_scope_id() {
+        let link_local = SocketAddrV6::new("fe80::1".parse().unwrap(), 8001, 7, 3);
+        let mut meta = Meta::default();
+        meta.set_socket_addr_v6(&link_local);
+        assert_eq!(meta.socket_addr(), SocketAddr::V6(link_local));
+        assert_eq!(meta.to_string(), "[fe80::1%3]:8001 [] 0B");
+
+        // setting an address replaces the flow info and scope id
+        let addr: SocketAddr = "[fe80::2]:8002".parse().unwrap();
+        meta.set_socket_addr(&addr);
+        assert_eq!(meta.socket_addr(), addr);
+        meta.set_socket_addr(&link_local.into());
+        assert_eq!(meta.socket_addr(), SocketAddr::V6(link_local));
+        let addr: SocketAddr = "10.0.0.1:8001".parse().unwrap();
+        meta.set_socket_addr(&addr);
+        assert_eq!(meta.socket_addr(), addr);
+        assert_eq!(
+            meta,
+            Meta::new(0, addr.ip(), addr.port(), PacketFlags::empty(), None)
+        );
+
+        let packet = Packet::from_data(Some(&link_local.into()), 1u8).unwrap();
+        assert_eq!(packet.meta().socket_addr(), SocketAddr::V6(link_local));
+        assert_eq!(packet.summary().addr, SocketAddr::V6(link_local));
+        assert_eq!(
+            format!("{packet:#?}"),
+            "Packet { size: 1, addr: [fe80::1%3]:8001 }"
+        );
+    }
+
+    fn packet_with_data(data: &[u8]) -> Packet {
+        let mut packet = Packet::default();
+        packet.buffer_mut()[..data.len()].copy_from_slice(data);
+        packet.meta_mut().size = data.len();
+        packet
+    }
+
+    #[test]
+    fn test_peek_boundaries() {
+        let packet = packet_with_data(&[1, 2, 3, 4]);
+        assert!(packet.has_min_len(0));
+        assert!(packet.has_min_len(4));
+        assert!(!packet.has_min_len(5));
+
+        assert_eq!(packet.peek_u8(0), Some(1));
+        assert_eq!(packet.peek_u8(3), Some(4));
+        assert_eq!(packet.peek_u8(4), None);
+
+        assert_eq!(packet.peek_u16_le(0), Some(0x0201));
+        assert_eq!(packet.peek_u16_le(2), Some(0x0403));
+        assert_eq!(packet.peek_u16_le(3), None);
+
+        assert_eq!(packet.peek_array::<4>(0), Some([1, 2, 3, 4]));
+        assert_eq!(packet.peek_array::<0>(4), Some([]));
+        assert_eq!(packet.peek_array::<1>(4), None);
+        assert_eq!(packet.peek_array::<2>(usize::MAX), None);
+
+        // bytes past meta.size are not readable
+        let mut packet = packet;
+        packet.meta_mut().size = 2;
+        assert_eq!(packet.peek_u8(2), None);
+        assert_eq!(packet.peek_u16_le(1), None);
+    }
+
+    #[test]
+    fn test_peek_discard() {
+        let mut packet = packet_with_data(&[1, 2, 3, 4]);
+        packet.meta_mut().set_discard(true);
+        assert!(!packet.has_min_len(0));
+        assert_eq!(packet.peek_u8(0), None);
+        assert_eq!(packet.peek_u16_le(0), None);
+        assert_eq!(packet.peek_array::<4>(0), None);
+    }
+
+    #[test]
+    fn test_mark_discard_if() {
+        let mut meta = Meta::default();
+        assert!(!meta.mark_discard_if(false));
+        assert!(!meta.discard());
+        assert!(meta.mark_discard_if(true));
+        assert!(meta.discard());
+        // already discarded packets are not counted twice
+        assert!(!meta.mark_discard_if(true));
+        assert!(meta.discard());
+    }
+
+    #[test]
+    fn test_drop_reason() {
+        let mut meta = Meta::default();
+        assert_eq!(meta.drop_reason(), None);
+        meta.discard_with_reason(DropReason::SigverifyFailed);
+        assert!(meta.discard());
+        assert_eq!(meta.drop_reason(), Some(DropReason::SigverifyFailed));
+
+        // a later reason replaces the first one
+        meta.discard_with_reason(DropReason::RateLimited);
+        assert_eq!(meta.drop_reason(), Some(DropReason::RateLimited));
+        meta.set_discard(true);
+        assert_eq!(meta.drop_reason(), Some(DropReason::RateLimited));
+
+        // clearing the flag clears the reason
+        meta.set_discard(false);
+        assert_eq!(meta.drop_reason(), None);
+        meta.set_discard(true);
+        assert_eq!(meta.drop_reason(), None);
+        // flags set directly do not revive a stale reason
+        meta.discard_with_reason(DropReason::Dedup);
+        meta.flags.remove(PacketFlags::DISCARD);
+        assert_eq!(meta.drop_reason(), None);
+
+        for reason in [
+            DropReason::Dedup,
+            DropReason::SigverifyFailed,
+            DropReason::Malformed,
+            DropReason::RateLimited,
+            DropReason::Forwarding,
+            DropReason::Other(DropReason::MAX_NAMED_CODE + 1),
+            DropReason::Other(u8::MAX),
+        ] {
+            assert_eq!(DropReason::from_code(reason.code()), reason);
+        }
+        assert_eq!(DropReason::Dedup.code().get(), 1);
+        assert_eq!(DropReason::Other(0).code(), NonZeroU8::MAX);
+        assert_eq!(DropReason::Other(2).code().get(), 2);
+    }
+
+    #[test]
+    fn test_drop_reason_preserved() {
+        let mut packet = packet_with_data(&[1, 2, 3]);
+        packet.meta_mut().discard_with_reason(DropReason::Malformed);
+        let meta = packet.meta().clone();
+        assert_eq!(meta.drop_reason(), Some(DropReason::Malformed));
+        let packet = Packet::new(packet.buffer, meta);
+        assert_eq!(
+            packet.clone().meta().drop_reason(),
+            Some(DropReason::Malformed)
+        );
+        assert_ne!(packet, packet_with_data(&[1, 2, 3]));
+
+        // serialized metas are unchanged and read back without a reason
+        let mut meta = Meta::default();
+        let without_reason = bincode::serialize(&meta).unwrap();
+        meta.discard_with_reason(DropReason::Dedup);
+        meta.set_discard(false);
+        assert_eq!(bincode::serialize(&meta).unwrap(), without_reason);
+        meta.discard_with_reason(DropReason::Dedup);
+        let packet = Packet::new([7; PACKET_DATA_SIZE], meta.clone());
+        let decoded: Packet = bincode::deserialize(&bincode::serialize(&packet).unwrap()).unwrap();
+        assert!(decoded.meta().discard());
+        assert_eq!(decoded.meta().drop_reason(), None);
+        // the reason doesn't survive the round trip, so it doesn't take
+        // part in equality either
+        assert_eq!(decoded, packet);
+
+        // neither do the other fields that are not serialized, while the
+        // scope id survives
+        let mut meta = Meta::default();
+        meta.set_socket_addr_v6(&SocketAddrV6::new("fe80::1".parse().unwrap(), 8001, 7, 3));
+        meta.set_received_at(Some(1_700_000_000));
+        meta.set_connection_id(Some(9));
+        let decoded: Meta = bincode::deserialize(&bincode::serialize(&meta).unwrap()).unwrap();
+        assert_eq!(decoded, meta);
+        assert_eq!(decoded.socket_addr().to_string(), "[fe80::1%3]:8001");
+    }
+
+    #[test]
+    fn test_filter_discard_short() {
+        let mut batch = vec![
+            packet_with_data(&[0; 10]),
+            packet_with_data(&[0; 64]),
+            packet_with_data(&[0; 65]),
+            packet_with_data(&[]),
+            packet_with_data(&[0; 100]),
+        ];
+        batch[4].meta_mut().set_discard(true);
+
+        assert_eq!(filter_discard_short(&mut batch, 65), 3);
+        let discarded: Vec<_> = batch.iter().map(|p| p.meta().discard()).collect();
+        assert_eq!(discarded, vec![true, true, false, true, true]);
+
+        assert_eq!(filter_discard_short(&mut batch, 65), 0);
+        assert_eq!(filter_discard_short(&mut batch, 0), 0);
+    }
+
+    fn operator_packet() -> Packet {
+        let mut packet = packet_with_data(&[0xde, 0xad, 0xbe, 0xef, 1, 2, 3, 4, 5, 6]);
+        let meta = packet.meta_mut();
+        meta.set_socket_addr(&"10.0.0.1:8001".parse().unwrap());
+        meta.flags = PacketFlags::FORWARDED | PacketFlags::FROM_STAKED_NODE;
+        packet
+    }
+
+    #[test]
+    fn test_meta_display() {
+        let mut packet = operator_packet();
+        packet.meta_mut().size = 968;
+        assert_eq!(
+            packet.meta().to_string(),
+            "10.0.0.1:8001 [FORWARDED|FROM_STAKED_NODE] 968B"
+        );
+        assert_eq!(Meta::default().to_string(), "0.0.0.0:0 [] 0B");
+    }
+
+    #[test]
+    fn test_packet_debug() {
+        let packet = operator_packet();
+        assert_eq!(
+            format!("{packet:?}"),
+            "Packet { size: 10, addr: 10.0.0.1:8001, flags: [FORWARDED|FROM_STAKED_NODE] }"
+        );
+        // the alternate form keeps the legacy output
+        assert_eq!(
+            format!("{packet:#?}"),
+            "Packet { size: 10, addr: 10.0.0.1:8001 }"
+        );
+    }
+
+    #[test]
+    fn test_packet_summary() {
+        let mut packet = operator_packet();
+        let summary = packet.summary();
+        assert_eq!(summary.payload(), &[0xde, 0xad, 0xbe, 0xef, 1, 2, 3, 4]);
+        assert_eq!(
+            summary.to_string(),
+            "10.0.0.1:8001 [FORWARDED|FROM_STAKED_NODE] 10B deadbeef01020304"
+        );
+
+        // short and discarded packets
+        packet.meta_mut().size = 2;
+        packet.meta_mut().set_discard(true);
+        assert_eq!(
+            packet.summary().to_string(),
+            "10.0.0.1:8001 [DISCARD|FORWARDED|FROM_STAKED_NODE] 2B dead"
+        );
+        assert_eq!(Packet::default().summary().to_string(), "0.0.0.0:0 [] 0B");
+    }
 }
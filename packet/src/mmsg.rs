@@ -0,0 +1,649 @@
+//! Sending and receiving of many packets with few system calls.
+//!
+//! On Linux, [`send_mmsg`] hands a whole slice of packets to a single
+//! `sendmmsg` call, with the I/O vectors pointing directly at the packet
+//! buffers. On other platforms it falls back to one `send_to` call per
+//! packet.
+//!
+//! Packets are sent to the address of their [`Meta`](crate::Meta), and the
+//! packets marked as discard are skipped. The functions return how many
+//! packets of the slice were handled, sent or skipped, which is less than
+//! its length when the socket did not accept all of them: the caller then
+//! retries with the remaining packets.
+//!
+//! [`recv_mmsg`] receives into a slice of packets with a single `recvmmsg`
+//! call on Linux, optionally with the kernel receive timestamps of the
+//! packets, see [`RecvConfig`].
+
+use {
+    crate::Packet,
+    std::{io, net::UdpSocket},
+};
+
+/// The maximum number of messages of a single `sendmmsg` call, the kernel
+/// sends at most this many.
+pub const UIO_MAXIOV: usize = 1024;
+
+/// Sends `packets` to the addresses in their meta, returning how many
+/// packets were handled.
+///
+/// Discarded packets are skipped, and counted as handled. If sending fails
+/// after some packets were sent, the count of handled packets is returned
+/// and the rest of `packets` can be retried. At most [`UIO_MAXIOV`] packets
+/// are sent on Linux, see [`send_mmsg_batched`] for larger slices.
+///
+/// # Errors
+///
+/// Returns the error of the socket if not even the first packet could be
+/// sent.
+pub fn send_mmsg(socket: &UdpSocket, packets: &[Packet]) -> io::Result<usize> {
+    #[cfg(target_os = "linux")]
+    {
+        linux::send_mmsg(socket, packets)
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        send_each(socket, packets)
+    }
+}
+
+/// Sends `packets` with [`send_mmsg`], at most `batch_size` packets per call,
+/// returning how many packets were handled.
+///
+/// `batch_size` is clamped to `1..=UIO_MAXIOV`. Sending stops at the first
+/// batch the socket did not fully accept, so a partial count can be
+/// retried as with [`send_mmsg`].
+///
+/// # Errors
+///
+/// Returns the error of the socket if not even the first packet could be
+/// sent.
+pub fn send_mmsg_batched(
+    socket: &UdpSocket,
+    packets: &[Packet],
+    batch_size: usize,
+) -> io::Result<usize> {
+    let batch_size = batch_size.clamp(1, UIO_MAXIOV);
+    let mut handled = 0;
+    for batch in packets.chunks(batch_size) {
+        match send_mmsg(socket, batch) {
+            Ok(count) => {
+                handled += count;
+                if count < batch.len() {
+                    break;
+                }
+            }
+            Err(err) if handled == 0 => return Err(err),
+            Err(_) => break,
+        }
+    }
+    Ok(handled)
+}
+
+/// Configuration of [`recv_mmsg`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RecvConfig {
+    /// Set [`Meta::received_at`](crate::Meta::received_at) from the kernel
+    /// receive timestamps of the packets, preferring hardware timestamps.
+    /// Timestamping must be enabled on the socket with
+    /// [`enable_kernel_timestamps`].
+    pub kernel_timestamps: bool,
+}
+
+/// Receives packets into `packets`, returning how many were received.
+///
+/// Blocks until a packet is received, then receives the packets already
+/// queued on the socket, up to the length of `packets` and at most
+/// [`UIO_MAXIOV`]. Only a single packet is received per call on other
+/// platforms than Linux, where kernel timestamps are not supported either.
+/// The meta of each packet is reset, and then set to the size and source
+/// address of the received data.
+pub fn recv_mmsg(
+    socket: &UdpSocket,
+    packets: &mut [Packet],
+    config: &RecvConfig,
+) -> io::Result<usize> {
+    for packet in packets.iter_mut() {
+        *packet.meta_mut() = crate::Meta::default();
+    }
+    #[cfg(target_os = "linux")]
+    {
+        linux::recv_mmsg(socket, packets, config)
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = config;
+        recv_one(socket, packets)
+    }
+}
+
+/// Receives a single packet into the first of `packets`.
+#[cfg(any(not(target_os = "linux"), test))]
+fn recv_one(socket: &UdpSocket, packets: &mut [Packet]) -> io::Result<usize> {
+    let Some(packet) = packets.first_mut() else {
+        return Ok(0);
+    };
+    let (size, addr) = socket.recv_from(packet.buffer_mut())?;
+    packet.meta_mut().size = size;
+    packet.meta_mut().set_socket_addr(&addr);
+    Ok(1)
+}
+
+/// Returns whether the kernel can timestamp the packets received by
+/// `socket`, see [`enable_kernel_timestamps`].
+///
+/// This probes the kernel support only: whether packets get hardware or
+/// software timestamps depends on the network interface. Always false on
+/// other platforms than Linux.
+pub fn kernel_timestamping_supported(socket: &UdpSocket) -> bool {
+    #[cfg(target_os = "linux")]
+    {
+        linux::kernel_timestamping_supported(socket)
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = socket;
+        false
+    }
+}
+
+/// Enables hardware and software receive timestamps on `socket`, to be read
+/// by [`recv_mmsg`] with [`RecvConfig::kernel_timestamps`].
+///
+/// # Errors
+///
+/// Returns the error of the socket, and an [`io::ErrorKind::Unsupported`]
+/// error on other platforms than Linux.
+pub fn enable_kernel_timestamps(socket: &UdpSocket) -> io::Result<()> {
+    #[cfg(target_os = "linux")]
+    {
+        linux::enable_kernel_timestamps(socket)
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = socket;
+        Err(io::ErrorKind::Unsupported.into())
+    }
+}
+
+/// Sends `packets` with one `send_to` call each.
+#[cfg(any(not(target_os = "linux"), test))]
+fn send_each(socket: &UdpSocket, packets: &[Packet]) -> io::Result<usize> {
+    for (index, packet) in packets.iter().enumerate() {
+        let Some(data) = packet.data(..) else {
+            continue;
+        };
+        if let Err(err) = socket.send_to(data, packet.meta().socket_addr()) {
+            return if index == 0 { Err(err) } else { Ok(index) };
+        }
+    }
+    Ok(packets.len())
+}
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use {
+        super::{RecvConfig, UIO_MAXIOV},
+        crate::{Packet, PACKET_DATA_SIZE},
+        libc::{
+            c_int, c_uint, c_void, iovec, mmsghdr, sa_family_t, sockaddr_in, sockaddr_in6,
+            sockaddr_storage, socklen_t, timespec, AF_INET, AF_INET6, MSG_WAITFORONE,
+            SCM_TIMESTAMPING, SOF_TIMESTAMPING_RAW_HARDWARE, SOF_TIMESTAMPING_RX_HARDWARE,
+            SOF_TIMESTAMPING_RX_SOFTWARE, SOF_TIMESTAMPING_SOFTWARE, SOL_SOCKET, SO_TIMESTAMPING,
+        },
+        std::{
+            io,
+            mem::{self, MaybeUninit},
+            net::{Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV6, UdpSocket},
+            os::fd::AsRawFd,
+            ptr,
+        },
+    };
+
+    /// The software, deprecated and hardware timestamps of a
+    /// `SCM_TIMESTAMPING` control message.
+    type ScmTimestamping = [timespec; 3];
+
+    /// Length of a control buffer holding a `SCM_TIMESTAMPING` message, in
+    /// `u64`s for alignment.
+    // SAFETY: `CMSG_SPACE` only computes a length.
+    const CONTROL_WORDS: usize =
+        unsafe { libc::CMSG_SPACE(mem::size_of::<ScmTimestamping>() as c_uint) } as usize / 8 + 1;
+
+    pub(super) fn kernel_timestamping_supported(socket: &UdpSocket) -> bool {
+        let mut flags: c_uint = 0;
+        let mut len = mem::size_of::<c_uint>() as socklen_t;
+        // SAFETY: `flags` and `len` are valid for the call.
+        let result = unsafe {
+            libc::getsockopt(
+                socket.as_raw_fd(),
+                SOL_SOCKET,
+                SO_TIMESTAMPING,
+                &mut flags as *mut c_uint as *mut c_void,
+                &mut len,
+            )
+        };
+        result == 0
+    }
+
+    pub(super) fn enable_kernel_timestamps(socket: &UdpSocket) -> io::Result<()> {
+        let flags: c_uint = SOF_TIMESTAMPING_RX_HARDWARE
+            | SOF_TIMESTAMPING_RAW_HARDWARE
+            | SOF_TIMESTAMPING_RX_SOFTWARE
+            | SOF_TIMESTAMPING_SOFTWARE;
+        // SAFETY: `flags` is valid for the call.
+        let result = unsafe {
+            libc::setsockopt(
+                socket.as_raw_fd(),
+                SOL_SOCKET,
+                SO_TIMESTAMPING,
+                &flags as *const c_uint as *const c_void,
+                mem::size_of::<c_uint>() as socklen_t,
+            )
+        };
+        if result < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+
+    pub(super) fn recv_mmsg(
+        socket: &UdpSocket,
+        packets: &mut [Packet],
+        config: &RecvConfig,
+    ) -> io::Result<usize> {
+        let len = packets.len().min(UIO_MAXIOV);
+        let packets = &mut packets[..len];
+        if packets.is_empty() {
+            return Ok(0);
+        }
+        let mut iovs: Vec<iovec> = packets
+            .iter_mut()
+            .map(|packet| {
+                let buffer = packet.buffer_mut();
+                iovec {
+                    iov_base: buffer.as_mut_ptr() as *mut c_void,
+                    iov_len: PACKET_DATA_SIZE,
+                }
+            })
+            .collect();
+        // SAFETY: all-zero bytes are a valid `sockaddr_storage`.
+        let mut addrs: Vec<sockaddr_storage> = vec![unsafe { mem::zeroed() }; len];
+        let mut controls =
+            vec![[0u64; CONTROL_WORDS]; if config.kernel_timestamps { len } else { 0 }];
+
+        // The headers point into `iovs`, `addrs` and `controls`, which are
+        // not resized past this point.
+        let mut hdrs: Vec<mmsghdr> = (0..len)
+            .map(|i| {
+                // SAFETY: all-zero bytes are a valid `mmsghdr`.
+                let mut hdr: mmsghdr = unsafe { mem::zeroed() };
+                hdr.msg_hdr.msg_name = &mut addrs[i] as *mut sockaddr_storage as *mut c_void;
+                hdr.msg_hdr.msg_namelen = mem::size_of::<sockaddr_storage>() as socklen_t;
+                hdr.msg_hdr.msg_iov = &mut iovs[i];
+                hdr.msg_hdr.msg_iovlen = 1;
+                if let Some(control) = controls.get_mut(i) {
+                    hdr.msg_hdr.msg_control = control.as_mut_ptr() as *mut c_void;
+                    hdr.msg_hdr.msg_controllen = mem::size_of_val(control) as _;
+                }
+                hdr
+            })
+            .collect();
+
+        // SAFETY: the headers, and the buffers they point to, outlive the
+        // call, and `hdrs.len()` is at most `UIO_MAXIOV`.
+        let received = unsafe {
+            libc::recvmmsg(
+                socket.as_raw_fd(),
+                hdrs.as_mut_ptr(),
+                hdrs.len() as c_uint,
+                MSG_WAITFORONE,
+                ptr::null_mut(),
+            )
+        };
+        if received < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let received = received as usize;
+
+        for (packet, (hdr, addr)) in packets
+            .iter_mut()
+            .zip(hdrs.iter().zip(&addrs))
+            .take(received)
+        {
+            let meta = packet.meta_mut();
+            meta.size = hdr.msg_len as usize;
+            if let Some(addr) = socket_addr_from(addr) {
+                meta.set_socket_addr(&addr);
+            }
+            if config.kernel_timestamps {
+                meta.set_received_at(received_at(&hdr.msg_hdr));
+            }
+        }
+        Ok(received)
+    }
+
+    fn socket_addr_from(storage: &sockaddr_storage) -> Option<SocketAddr> {
+        match c_int::from(storage.ss_family) {
+            AF_INET => {
+                // SAFETY: the family says the storage holds a `sockaddr_in`.
+                let addr = unsafe { &*(storage as *const _ as *const sockaddr_in) };
+                Some(SocketAddr::new(
+                    Ipv4Addr::from(addr.sin_addr.s_addr.to_ne_bytes()).into(),
+                    u16::from_be(addr.sin_port),
+                ))
+            }
+            AF_INET6 => {
+                // SAFETY: the family says the storage holds a `sockaddr_in6`.
+                let addr = unsafe { &*(storage as *const _ as *const sockaddr_in6) };
+                Some(
+                    SocketAddrV6::new(
+                        Ipv6Addr::from(addr.sin6_addr.s6_addr),
+                        u16::from_be(addr.sin6_port),
+                        addr.sin6_flowinfo,
+                        addr.sin6_scope_id,
+                    )
+                    .into(),
+                )
+            }
+            _ => None,
+        }
+    }
+
+    /// Reads the `SCM_TIMESTAMPING` control message of `hdr`, in nanoseconds
+    /// since the Unix epoch, preferring the hardware timestamp.
+    fn received_at(hdr: &libc::msghdr) -> Option<u64> {
+        // SAFETY: `hdr` was filled by `recvmmsg`, so its control messages
+        // are valid and within its control buffer.
+        let mut cmsg = unsafe { libc::CMSG_FIRSTHDR(hdr) };
+        while !cmsg.is_null() {
+            // SAFETY: as above.
+            let (level, kind) = unsafe { ((*cmsg).cmsg_level, (*cmsg).cmsg_type) };
+            if level == SOL_SOCKET && kind == SCM_TIMESTAMPING {
+                // SAFETY: the data of a `SCM_TIMESTAMPING` message is a
+                // `scm_timestamping`, which may not be aligned.
+                let timestamps =
+                    unsafe { ptr::read_unaligned(libc::CMSG_DATA(cmsg) as *const ScmTimestamping) };
+                let [software, _, hardware] = timestamps;
+                return [hardware, software].into_iter().find_map(|timestamp| {
+                    let nanos = u64::try_from(timestamp.tv_sec)
+                        .ok()?
+                        .checked_mul(1_000_000_000)?
+                        .checked_add(u64::try_from(timestamp.tv_nsec).ok()?)?;
+                    (nanos != 0).then_some(nanos)
+                });
+            }
+            // SAFETY: as above.
+            cmsg = unsafe { libc::CMSG_NXTHDR(hdr, cmsg) };
+        }
+        None
+    }
+
+    pub(super) fn send_mmsg(socket: &UdpSocket, packets: &[Packet]) -> io::Result<usize> {
+        let packets = &packets[..packets.len().min(UIO_MAXIOV)];
+        // Indexes in `packets` of the packets to send, the discarded ones are
+        // skipped.
+        let mut indexes = Vec::with_capacity(packets.len());
+        let mut iovs = Vec::with_capacity(packets.len());
+        let mut addrs = Vec::with_capacity(packets.len());
+        for (index, packet) in packets.iter().enumerate() {
+            let Some(data) = packet.data(..) else {
+                continue;
+            };
+            indexes.push(index);
+            iovs.push(iovec {
+                iov_base: data.as_ptr() as *mut _,
+                iov_len: data.len(),
+            });
+            addrs.push(sockaddr_from(&packet.meta().socket_addr()));
+        }
+        if indexes.is_empty() {
+            return Ok(packets.len());
+        }
+
+        // The headers point into `iovs` and `addrs`, which are not resized
+        // past this point.
+        let mut hdrs: Vec<mmsghdr> = iovs
+            .iter_mut()
+            .zip(addrs.iter_mut())
+            .map(|(iov, (addr, addr_len))| {
+                // SAFETY: all-zero bytes are a valid `mmsghdr`.
+                let mut hdr: mmsghdr = unsafe { mem::zeroed() };
+                hdr.msg_hdr.msg_name = addr as *mut sockaddr_storage as *mut _;
+                hdr.msg_hdr.msg_namelen = *addr_len;
+                hdr.msg_hdr.msg_iov = iov;
+                hdr.msg_hdr.msg_iovlen = 1;
+                hdr
+            })
+            .collect();
+
+        // SAFETY: the headers, and the buffers and addresses they point to,
+        // outlive the call, and `hdrs.len()` is at most `UIO_MAXIOV`.
+        let sent =
+            unsafe { libc::sendmmsg(socket.as_raw_fd(), hdrs.as_mut_ptr(), hdrs.len() as u32, 0) };
+        if sent < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(indexes.get(sent as usize).copied().unwrap_or(packets.len()))
+    }
+
+    fn sockaddr_from(addr: &SocketAddr) -> (sockaddr_storage, socklen_t) {
+        let mut storage = MaybeUninit::<sockaddr_storage>::zeroed();
+        let len = match addr {
+            SocketAddr::V4(addr) => {
+                let raw = sockaddr_in {
+                    sin_family: AF_INET as sa_family_t,
+                    sin_port: addr.port().to_be(),
+                    sin_addr: libc::in_addr {
+                        s_addr: u32::from_ne_bytes(addr.ip().octets()),
+                    },
+                    sin_zero: [0; 8],
+                };
+                // SAFETY: `sockaddr_storage` is large and aligned enough for
+                // any socket address.
+                unsafe { ptr::write(storage.as_mut_ptr() as *mut sockaddr_in, raw) };
+                mem::size_of::<sockaddr_in>()
+            }
+            SocketAddr::V6(addr) => {
+                let raw = sockaddr_in6 {
+                    sin6_family: AF_INET6 as sa_family_t,
+                    sin6_port: addr.port().to_be(),
+                    sin6_flowinfo: addr.flowinfo(),
+                    sin6_addr: libc::in6_addr {
+                        s6_addr: addr.ip().octets(),
+                    },
+                    sin6_scope_id: addr.scope_id(),
+                };
+                // SAFETY: as above.
+                unsafe { ptr::write(storage.as_mut_ptr() as *mut sockaddr_in6, raw) };
+                mem::size_of::<sockaddr_in6>()
+            }
+        };
+        // SAFETY: zeroed, then partly overwritten with a socket address.
+        (unsafe { storage.assume_init() }, len as socklen_t)
+    }
+}
+
+#[cfg(test)]
+// solana-net-utils is not a dependency of the SDK, the tests only need
+// plain loopback sockets.
+#[allow(clippy::disallowed_methods)]
+mod tests {
+    use {
+        super::*,
+        std::{net::SocketAddr, time::Duration},
+    };
+
+    fn receiver() -> UdpSocket {
+        let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+        socket
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        socket
+    }
+
+    fn packets(count: usize, dest: SocketAddr) -> Vec<Packet> {
+        (0..count)
+            .map(|i| {
+                let mut packet = Packet::default();
+                let payload = vec![i as u8; i + 1];
+                packet.buffer_mut()[..payload.len()].copy_from_slice(&payload);
+                packet.meta_mut().size = payload.len();
+                packet.meta_mut().set_socket_addr(&dest);
+                packet
+            })
+            .collect()
+    }
+
+    fn receive(socket: &UdpSocket, count: usize) -> Vec<Vec<u8>> {
+        let mut buffer = [0; 2048];
+        (0..count)
+            .map(|_| {
+                let len = socket.recv(&mut buffer).unwrap();
+                buffer[..len].to_vec()
+            })
+            .collect()
+    }
+
+    fn expected(indexes: impl IntoIterator<Item = usize>) -> Vec<Vec<u8>> {
+        indexes.into_iter().map(|i| vec![i as u8; i + 1]).collect()
+    }
+
+    #[test]
+    fn test_send_mmsg() {
+        let receiver = receiver();
+        let sender = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let mut packets = packets(8, receiver.local_addr().unwrap());
+        packets[3].meta_mut().set_discard(true);
+
+        assert_eq!(send_mmsg(&sender, &packets).unwrap(), 8);
+        assert_eq!(receive(&receiver, 7), expected([0, 1, 2, 4, 5, 6, 7]));
+        // only discarded packets
+        assert_eq!(send_mmsg(&sender, &packets[3..4]).unwrap(), 1);
+        assert_eq!(send_mmsg(&sender, &[]).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_send_mmsg_batched() {
+        let receiver = receiver();
+        let sender = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let packets = packets(5, receiver.local_addr().unwrap());
+
+        assert_eq!(send_mmsg_batched(&sender, &packets, 2).unwrap(), 5);
+        assert_eq!(receive(&receiver, 5), expected(0..5));
+        // a zero batch size sends one packet per call
+        assert_eq!(send_mmsg_batched(&sender, &packets, 0).unwrap(), 5);
+        assert_eq!(receive(&receiver, 5), expected(0..5));
+    }
+
+    #[test]
+    fn test_send_each() {
+        let receiver = receiver();
+        let sender = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let mut packets = packets(4, receiver.local_addr().unwrap());
+        packets[0].meta_mut().set_discard(true);
+
+        assert_eq!(send_each(&sender, &packets).unwrap(), 4);
+        assert_eq!(receive(&receiver, 3), expected(1..4));
+    }
+
+    #[test]
+    fn test_send_error() {
+        let sender = UdpSocket::bind("127.0.0.1:0").unwrap();
+        // an IPv4 socket cannot send to an IPv6 address
+        let packets = packets(2, "[::1]:8000".parse().unwrap());
+
+        assert!(send_mmsg(&sender, &packets).is_err());
+        assert!(send_mmsg_batched(&sender, &packets, 1).is_err());
+        assert!(send_each(&sender, &packets).is_err());
+    }
+
+    fn received(packets: &[Packet]) -> Vec<Vec<u8>> {
+        packets
+            .iter()
+            .map(|packet| packet.data(..).unwrap().to_vec())
+            .collect()
+    }
+
+    #[test]
+    fn test_recv_mmsg() {
+        let receiver = receiver();
+        let sender = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let packets = packets(3, receiver.local_addr().unwrap());
+        assert_eq!(send_mmsg(&sender, &packets).unwrap(), 3);
+
+        let mut buffers = vec![Packet::default(); 4];
+        buffers[0].meta_mut().set_discard(true);
+        let mut count = 0;
+        while count < 3 {
+            count += recv_mmsg(&receiver, &mut buffers[count..], &RecvConfig::default()).unwrap();
+        }
+        assert_eq!(received(&buffers[..3]), expected(0..3));
+        for packet in &buffers[..3] {
+            assert!(!packet.meta().discard());
+            assert_eq!(packet.meta().socket_addr(), sender.local_addr().unwrap());
+            assert_eq!(packet.meta().received_at(), None);
+        }
+        assert_eq!(
+            recv_mmsg(&receiver, &mut [], &RecvConfig::default()).unwrap(),
+            0
+        );
+    }
+
+    #[test]
+    fn test_recv_one() {
+        let receiver = receiver();
+        let sender = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let packets = packets(2, receiver.local_addr().unwrap());
+        assert_eq!(send_mmsg(&sender, &packets).unwrap(), 2);
+
+        let mut buffers = vec![Packet::default(); 2];
+        assert_eq!(recv_one(&receiver, &mut buffers).unwrap(), 1);
+        assert_eq!(recv_one(&receiver, &mut buffers[1..]).unwrap(), 1);
+        assert_eq!(received(&buffers), expected(0..2));
+        assert_eq!(
+            buffers[1].meta().socket_addr(),
+            sender.local_addr().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_kernel_timestamping_supported() {
+        let socket = receiver();
+        assert_eq!(
+            kernel_timestamping_supported(&socket),
+            cfg!(target_os = "linux")
+        );
+        assert_eq!(
+            enable_kernel_timestamps(&socket).is_ok(),
+            cfg!(target_os = "linux")
+        );
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_recv_mmsg_kernel_timestamps() {
+        let receiver = receiver();
+        enable_kernel_timestamps(&receiver).unwrap();
+        let sender = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let packets = packets(3, receiver.local_addr().unwrap());
+        for packet in &packets {
+            assert_eq!(send_mmsg(&sender, std::slice::from_ref(packet)).unwrap(), 1);
+        }
+
+        let config = RecvConfig {
+            kernel_timestamps: true,
+        };
+        let mut buffers = vec![Packet::default(); 3];
+        let mut count = 0;
+        while count < 3 {
+            count += recv_mmsg(&receiver, &mut buffers[count..], &config).unwrap();
+        }
+        assert_eq!(received(&buffers), expected(0..3));
+        let timestamps: Vec<u64> = buffers
+            .iter()
+            .map(|packet| packet.meta().received_at().unwrap())
+            .collect();
+        assert!(timestamps.windows(2).all(|pair| pair[0] <= pair[1]));
+    }
+}
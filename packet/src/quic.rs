@@ -0,0 +1,146 @@
+//! Conversion of QUIC datagrams to and from packets.
+//!
+//! QUIC ingest yields a payload, the address of the peer and the id of the
+//! connection it arrived on. [`Packet::from_quic_datagram`] turns those into
+//! a packet with the meta set consistently, so the stake of the peer is
+//! recorded when the packet is built rather than later by each caller, and
+//! [`Packet::to_datagram`] goes the other way.
+//!
+//! The packet buffer is inline, so both conversions copy the payload.
+
+use {
+    crate::{Meta, Packet, PacketFlags, PACKET_DATA_SIZE},
+    bytes::Bytes,
+    std::{fmt, net::SocketAddr},
+};
+
+/// The maximum size of a QUIC datagram payload that fits in a packet.
+pub const MAX_QUIC_DATAGRAM_SIZE: usize = PACKET_DATA_SIZE;
+
+/// A datagram payload too large to fit in a packet.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PacketSizeError {
+    /// The size of the payload.
+    pub size: usize,
+    /// The maximum size of a payload.
+    pub max: usize,
+}
+
+impl core::error::Error for PacketSizeError {}
+
+impl fmt::Display for PacketSizeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "datagram of {} bytes exceeds the maximum of {} bytes",
+            self.size, self.max
+        )
+    }
+}
+
+impl Packet {
+    /// Builds a packet from a QUIC datagram received from `peer`.
+    ///
+    /// The packet is flagged [`PacketFlags::FROM_STAKED_NODE`] if `staked`,
+    /// and records `connection_id`, see [`Meta::connection_id`]. Fails if the
+    /// payload is longer than [`MAX_QUIC_DATAGRAM_SIZE`].
+    pub fn from_quic_datagram(
+        payload: Bytes,
+        peer: SocketAddr,
+        staked: bool,
+        connection_id: Option<u64>,
+    ) -> Result<Self, PacketSizeError> {
+        let size = payload.len();
+        if size > MAX_QUIC_DATAGRAM_SIZE {
+            return Err(PacketSizeError {
+                size,
+                max: MAX_QUIC_DATAGRAM_SIZE,
+            });
+        }
+        let mut buffer = [0u8; PACKET_DATA_SIZE];
+        buffer[..size].copy_from_slice(&payload);
+        let mut meta = Meta {
+            size,
+            ..Meta::default()
+        };
+        meta.set_socket_addr(&peer);
+        meta.flags.set(PacketFlags::FROM_STAKED_NODE, staked);
+        meta.set_connection_id(connection_id);
+        Ok(Self::new(buffer, meta))
+    }
+
+    /// Returns the payload of the packet and the address in its meta, to
+    /// send as a QUIC datagram.
+    ///
+    /// Unlike [`Packet::data`], the payload is returned even if the packet
+    /// is marked as discard. A size in the meta beyond the buffer is clamped
+    /// to [`PACKET_DATA_SIZE`].
+    pub fn to_datagram(&self) -> (Bytes, SocketAddr) {
+        let size = self.meta.size.min(PACKET_DATA_SIZE);
+        let payload = Bytes::copy_from_slice(&self.buffer[..size]);
+        (payload, self.meta.socket_addr())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, std::net::SocketAddrV6};
+
+    #[test]
+    fn test_from_quic_datagram_flags() {
+        let peer: SocketAddr = "10.0.0.1:8009".parse().unwrap();
+        let payload = Bytes::from_static(&[1, 2, 3]);
+
+        let packet = Packet::from_quic_datagram(payload.clone(), peer, true, Some(7)).unwrap();
+        assert_eq!(packet.data(..), Some(&[1, 2, 3][..]));
+        assert_eq!(packet.meta().socket_addr(), peer);
+        assert_eq!(packet.meta().flags, PacketFlags::FROM_STAKED_NODE);
+        assert_eq!(packet.meta().connection_id(), Some(7));
+
+        let packet = Packet::from_quic_datagram(payload, peer, false, None).unwrap();
+        assert_eq!(packet.meta().flags, PacketFlags::empty());
+        assert_eq!(packet.meta().connection_id(), None);
+    }
+
+    #[test]
+    fn test_from_quic_datagram_size() {
+        let peer: SocketAddr = "10.0.0.1:8009".parse().unwrap();
+
+        let payload = Bytes::from(vec![5u8; MAX_QUIC_DATAGRAM_SIZE]);
+        let packet = Packet::from_quic_datagram(payload.clone(), peer, false, None).unwrap();
+        assert_eq!(packet.data(..), Some(&payload[..]));
+
+        let payload = Bytes::from(vec![5u8; MAX_QUIC_DATAGRAM_SIZE + 1]);
+        assert_eq!(
+            Packet::from_quic_datagram(payload, peer, false, None),
+            Err(PacketSizeError {
+                size: MAX_QUIC_DATAGRAM_SIZE + 1,
+                max: MAX_QUIC_DATAGRAM_SIZE,
+            })
+        );
+    }
+
+    #[test]
+    fn test_to_datagram() {
+        let peer: SocketAddr = SocketAddrV6::new("fe80::1".parse().unwrap(), 8009, 0, 3).into();
+        let payload = Bytes::from_static(b"datagram");
+        let mut packet = Packet::from_quic_datagram(payload.clone(), peer, true, Some(1)).unwrap();
+        assert_eq!(packet.to_datagram(), (payload.clone(), peer));
+
+        // the payload of a discarded packet is still returned
+        packet.meta_mut().set_discard(true);
+        assert_eq!(packet.to_datagram(), (payload, peer));
+    }
+
+    #[test]
+    fn test_to_datagram_oversized_meta() {
+        let peer: SocketAddr = "10.0.0.1:8009".parse().unwrap();
+        let mut packet =
+            Packet::from_quic_datagram(Bytes::from_static(b"datagram"), peer, false, None).unwrap();
+        packet.meta_mut().size = PACKET_DATA_SIZE + 1;
+        let (payload, addr) = packet.to_datagram();
+        assert_eq!(payload.len(), PACKET_DATA_SIZE);
+        assert_eq!(&payload[..8], b"datagram");
+        assert_eq!(addr, peer);
+    }
+}
@@ -0,0 +1,281 @@
+//! Rate limiting of packets by source address.
+//!
+//! [`SourceRateLimiter`] keeps a token bucket per source IP address. Each
+//! packet takes a token from the bucket of its source, and buckets refill at
+//! a constant rate up to their capacity, so a source can send a burst of
+//! `capacity` packets and then `refill_per_sec` packets per second. Packets
+//! from staked nodes are never limited.
+//!
+//! The current time is passed to every call, so that tests can control it.
+
+use {
+    crate::{DropReason, Meta, Packet},
+    solana_address::lru::Lru,
+    std::{net::IpAddr, time::Instant},
+};
+
+/// Tokens are counted in billionths, so that buckets refill by the
+/// nanosecond without rounding.
+const NANOS_PER_TOKEN: u64 = 1_000_000_000;
+
+/// Whether a packet is within the rate limit of its source.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Decision {
+    /// The packet is within the limit, or from a staked node.
+    Allow,
+    /// The source exceeded its limit, the packet should be dropped.
+    Throttle,
+}
+
+/// Counts of the packets of a batch, see [`SourceRateLimiter::apply`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RateLimitCounts {
+    /// Packets allowed from unstaked sources.
+    pub allowed: usize,
+    /// Packets marked as discard for exceeding the limit.
+    pub throttled: usize,
+    /// Packets allowed because they are from staked nodes.
+    pub staked: usize,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    /// Available tokens, in billionths of a token.
+    tokens: u64,
+    last_refill: Instant,
+}
+
+/// Token buckets of the most recently seen source addresses.
+///
+/// At most `max_sources` buckets are kept. When a packet arrives from a new
+/// source while the limiter is full, the bucket of the least recently seen
+/// source is evicted. A source seen again after its eviction starts with a
+/// full bucket.
+#[derive(Debug)]
+pub struct SourceRateLimiter {
+    /// Tokens in a full bucket, in billionths of a token.
+    capacity: u64,
+    refill_per_sec: u64,
+    buckets: Lru<IpAddr, Bucket>,
+}
+
+impl SourceRateLimiter {
+    /// Creates a limiter allowing bursts of `capacity` packets per source,
+    /// refilled at `refill_per_sec` packets per second, and tracking at most
+    /// `max_sources` sources.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_sources` is zero.
+    pub fn new(capacity: u32, refill_per_sec: u32, max_sources: usize) -> Self {
+        assert!(max_sources > 0, "max_sources must not be zero");
+        Self {
+            capacity: u64::from(capacity) * NANOS_PER_TOKEN,
+            refill_per_sec: u64::from(refill_per_sec),
+            buckets: Lru::new(max_sources),
+        }
+    }
+
+    /// Number of sources with a bucket.
+    pub fn num_sources(&self) -> usize {
+        self.buckets.len()
+    }
+
+    /// Takes a token from the bucket of the source of `meta`, returning
+    /// [`Decision::Throttle`] if the bucket is empty.
+    ///
+    /// Packets from staked nodes are allowed without taking a token.
+    pub fn check(&mut self, meta: &Meta, now: Instant) -> Decision {
+        if meta.is_from_staked_node() {
+            return Decision::Allow;
+        }
+        let bucket = self.buckets.get_or_insert_with(meta.addr, || Bucket {
+            tokens: self.capacity,
+            last_refill: now,
+        });
+        bucket.refill(now, self.capacity, self.refill_per_sec);
+
+        match bucket.tokens.checked_sub(NANOS_PER_TOKEN) {
+            Some(tokens) => {
+                bucket.tokens = tokens;
+                Decision::Allow
+            }
+            None => Decision::Throttle,
+        }
+    }
+
+    /// Checks every packet of `packets` not already marked as discard, and
+    /// marks the throttled ones as discard with [`DropReason::RateLimited`].
+    pub fn apply(&mut self, packets: &mut [Packet], now: Instant) -> RateLimitCounts {
+        let mut counts = RateLimitCounts::default();
+        for packet in packets.iter_mut().filter(|packet| !packet.meta().discard()) {
+            if packet.meta().is_from_staked_node() {
+                counts.staked += 1;
+                continue;
+            }
+            match self.check(packet.meta(), now) {
+                Decision::Allow => counts.allowed += 1,
+                Decision::Throttle => {
+                    packet
+                        .meta_mut()
+                        .discard_with_reason(DropReason::RateLimited);
+                    counts.throttled += 1;
+                }
+            }
+        }
+        counts
+    }
+}
+
+impl Bucket {
+    fn refill(&mut self, now: Instant, capacity: u64, refill_per_sec: u64) {
+        // `now` may be earlier than the last refill if the caller reads the
+        // clock on several threads
+        let elapsed = now.saturating_duration_since(self.last_refill);
+        let refill = elapsed
+            .as_nanos()
+            .saturating_mul(u128::from(refill_per_sec));
+        self.tokens = u128::from(self.tokens)
+            .saturating_add(refill)
+            .min(u128::from(capacity)) as u64;
+        self.last_refill = self.last_refill.max(now);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::PacketFlags,
+        std::{net::Ipv4Addr, time::Duration},
+    };
+
+    fn meta(ip: u8) -> Meta {
+        Meta::new(
+            0,
+            IpAddr::V4(Ipv4Addr::new(10, 0, 0, ip)),
+            8000,
+            PacketFlags::empty(),
+            None,
+        )
+    }
+
+    fn allowed(limiter: &mut SourceRateLimiter, meta: &Meta, now: Instant, n: usize) -> usize {
+        (0..n)
+            .filter(|_| limiter.check(meta, now) == Decision::Allow)
+            .count()
+    }
+
+    #[test]
+    fn test_refill() {
+        let start = Instant::now();
+        let mut limiter = SourceRateLimiter::new(4, 2, 8);
+        let meta = meta(1);
+        assert_eq!(allowed(&mut limiter, &meta, start, 10), 4);
+
+        // half a token after 250ms, one after 500ms
+        let now = start + Duration::from_millis(250);
+        assert_eq!(limiter.check(&meta, now), Decision::Throttle);
+        let now = start + Duration::from_millis(500);
+        assert_eq!(limiter.check(&meta, now), Decision::Allow);
+        assert_eq!(limiter.check(&meta, now), Decision::Throttle);
+
+        // partial tokens carry over
+        let now = start + Duration::from_millis(1250);
+        assert_eq!(allowed(&mut limiter, &meta, now, 10), 1);
+        let now = start + Duration::from_millis(1500);
+        assert_eq!(allowed(&mut limiter, &meta, now, 10), 1);
+
+        // the bucket holds at most `capacity` tokens
+        let now = start + Duration::from_secs(3600);
+        assert_eq!(allowed(&mut limiter, &meta, now, 10), 4);
+
+        // time going backwards does not refill the bucket
+        assert_eq!(limiter.check(&meta, start), Decision::Throttle);
+        let now = start + Duration::from_secs(3600) + Duration::from_millis(500);
+        assert_eq!(limiter.check(&meta, now), Decision::Allow);
+    }
+
+    #[test]
+    fn test_burst_then_sustained() {
+        let start = Instant::now();
+        let mut limiter = SourceRateLimiter::new(100, 10, 8);
+        let source = meta(1);
+        assert_eq!(allowed(&mut limiter, &source, start, 150), 100);
+
+        // 50 packets per second for 10 seconds, one in five is allowed
+        let mut total = 0;
+        for tick in 1..=500 {
+            let now = start + Duration::from_millis(tick * 20);
+            total += allowed(&mut limiter, &source, now, 1);
+        }
+        assert_eq!(total, 100);
+
+        // other sources are not affected
+        assert_eq!(allowed(&mut limiter, &meta(2), start, 150), 100);
+    }
+
+    #[test]
+    fn test_eviction() {
+        let now = Instant::now();
+        let mut limiter = SourceRateLimiter::new(1, 0, 2);
+        assert_eq!(limiter.check(&meta(1), now), Decision::Allow);
+        assert_eq!(limiter.check(&meta(2), now), Decision::Allow);
+        assert_eq!(limiter.num_sources(), 2);
+
+        // 1 is seen after 2, so 2 is evicted for 3
+        assert_eq!(limiter.check(&meta(1), now), Decision::Throttle);
+        assert_eq!(limiter.check(&meta(3), now), Decision::Allow);
+        assert_eq!(limiter.num_sources(), 2);
+        assert_eq!(limiter.check(&meta(1), now), Decision::Throttle);
+        assert_eq!(limiter.check(&meta(3), now), Decision::Throttle);
+
+        // 2 starts over with a full bucket, evicting 1
+        assert_eq!(limiter.check(&meta(2), now), Decision::Allow);
+        assert_eq!(limiter.check(&meta(3), now), Decision::Throttle);
+        assert_eq!(limiter.check(&meta(1), now), Decision::Allow);
+        assert_eq!(limiter.num_sources(), 2);
+    }
+
+    #[test]
+    fn test_staked_bypass() {
+        let now = Instant::now();
+        let mut limiter = SourceRateLimiter::new(1, 0, 1);
+        let mut staked = meta(1);
+        staked.set_from_staked_node(true);
+        assert_eq!(allowed(&mut limiter, &staked, now, 10), 10);
+        assert_eq!(limiter.num_sources(), 0);
+
+        // the unstaked packets of the same address are limited
+        assert_eq!(allowed(&mut limiter, &meta(1), now, 10), 1);
+        assert_eq!(allowed(&mut limiter, &staked, now, 10), 10);
+    }
+
+    #[test]
+    fn test_apply() {
+        let now = Instant::now();
+        let mut limiter = SourceRateLimiter::new(2, 0, 8);
+        let mut packets: Vec<_> = (0..6)
+            .map(|_| Packet::new([0; crate::PACKET_DATA_SIZE], meta(1)))
+            .collect();
+        packets[0].meta_mut().set_from_staked_node(true);
+        packets[1].meta_mut().set_discard(true);
+
+        let counts = limiter.apply(&mut packets, now);
+        assert_eq!(
+            counts,
+            RateLimitCounts {
+                allowed: 2,
+                throttled: 2,
+                staked: 1,
+            }
+        );
+        let discards: Vec<_> = packets.iter().map(|p| p.meta().discard()).collect();
+        assert_eq!(discards, [false, true, false, false, true, true]);
+        assert_eq!(packets[1].meta().drop_reason(), None);
+        assert_eq!(
+            packets[5].meta().drop_reason(),
+            Some(DropReason::RateLimited)
+        );
+    }
+}
@@ -0,0 +1,342 @@
+//! Serde support for [`Packet`].
+//!
+//! Binary formats use the derived representation of [`PacketDef`], which
+//! follows the struct definition. With the `json` feature, human readable
+//! formats, such as JSON, use [`ReadablePacket`] instead, for debugging: the
+//! payload is a base64 string, the source is a socket address string and the
+//! flags are a list of names.
+
+#[cfg(feature = "json")]
+use {
+    crate::PacketFlags,
+    base64::{prelude::BASE64_STANDARD, Engine},
+    serde::de::Error,
+    solana_pubkey::Pubkey,
+    std::net::SocketAddr,
+};
+use {
+    crate::{Meta, Packet, PACKET_DATA_SIZE},
+    serde::{Deserialize, Deserializer, Serialize, Serializer},
+    serde_with::{serde_as, Bytes},
+};
+
+// serde_as is used as a work around because array isn't supported by serde
+// (and serde_bytes).
+//
+// the root cause is of a historical special handling for [T; 0] in rust's
+// `Default` and supposedly mirrored serde's `Serialize` (macro) impls,
+// pre-dating stabilized const generics, meaning it'll take long time...:
+//   https://github.com/rust-lang/rust/issues/61415
+//   https://github.com/rust-lang/rust/issues/88744#issuecomment-1138678928
+//
+// Due to the nature of the root cause, the current situation is complicated.
+// All in all, the serde_as solution is chosen for good perf and low maintenance
+// need at the cost of another crate dependency..
+//
+// For details, please refer to the below various links...
+//
+// relevant merged/published pr for this serde_as functionality used here:
+//   https://github.com/jonasbb/serde_with/pull/277
+// open pr at serde_bytes:
+//   https://github.com/serde-rs/bytes/pull/28
+// open issue at serde:
+//   https://github.com/serde-rs/serde/issues/1937
+// closed pr at serde (due to the above mentioned [N; 0] issue):
+//   https://github.com/serde-rs/serde/pull/1860
+// ryoqun's dirty experiments:
+//   https://github.com/ryoqun/serde-array-comparisons
+//
+// The serde layout follows the struct definition and is only meant for
+// in-memory snapshots. Packets persisted to disk, such as capture files,
+// should use the versioned encoding in the `codec` module.
+#[serde_as]
+#[derive(serde_derive::Serialize, serde_derive::Deserialize)]
+#[serde(remote = "Packet", rename = "Packet")]
+struct PacketDef {
+    #[serde_as(as = "Bytes")]
+    buffer: [u8; PACKET_DATA_SIZE],
+    meta: Meta,
+}
+
+/// The human readable form of a [`Packet`].
+///
+/// The drop reason is not included, as in the binary form.
+#[cfg(feature = "json")]
+#[derive(serde_derive::Serialize, serde_derive::Deserialize)]
+#[serde(rename = "Packet", deny_unknown_fields)]
+struct ReadablePacket {
+    /// The payload, in standard base64.
+    payload: String,
+    addr: SocketAddr,
+    /// Names of the [`PacketFlags`] set.
+    flags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    remote_pubkey: Option<String>,
+}
+
+impl Serialize for Packet {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        #[cfg(feature = "json")]
+        if serializer.is_human_readable() {
+            return ReadablePacket::from(self).serialize(serializer);
+        }
+        PacketDef::serialize(self, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Packet {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[cfg(feature = "json")]
+        if deserializer.is_human_readable() {
+            return ReadablePacket::deserialize(deserializer)?.into_packet();
+        }
+        PacketDef::deserialize(deserializer)
+    }
+}
+
+#[cfg(feature = "json")]
+impl From<&Packet> for ReadablePacket {
+    fn from(packet: &Packet) -> Self {
+        // discarded packets are included, so the payload is not read with
+        // `Packet::data`
+        let payload = &packet.buffer[..packet.meta.size.min(PACKET_DATA_SIZE)];
+        Self {
+            payload: BASE64_STANDARD.encode(payload),
+            addr: packet.meta.socket_addr(),
+            flags: packet
+                .meta
+                .flags
+                .iter_names()
+                .map(|(name, _)| name.into())
+                .collect(),
+            remote_pubkey: packet.meta.remote_pubkey().map(|pubkey| pubkey.to_string()),
+        }
+    }
+}
+
+#[cfg(feature = "json")]
+impl ReadablePacket {
+    fn into_packet<E: Error>(self) -> Result<Packet, E> {
+        let payload = BASE64_STANDARD
+            .decode(&self.payload)
+            .map_err(|err| E::custom(format_args!("invalid base64 payload: {err}")))?;
+        if payload.len() > PACKET_DATA_SIZE {
+            return Err(E::invalid_length(
+                payload.len(),
+                &"a payload of at most PACKET_DATA_SIZE bytes",
+            ));
+        }
+        let flags = self
+            .flags
+            .iter()
+            .try_fold(PacketFlags::empty(), |flags, name| {
+                PacketFlags::from_name(name)
+                    .map(|flag| flags | flag)
+                    .ok_or_else(|| E::custom(format_args!("unknown packet flag {name:?}")))
+            })?;
+        let remote_pubkey = self
+            .remote_pubkey
+            .map(|pubkey| {
+                pubkey.parse::<Pubkey>().map_err(|err| {
+                    E::custom(format_args!("invalid remote pubkey {pubkey:?}: {err}"))
+                })
+            })
+            .transpose()?;
+
+        let mut buffer = [0; PACKET_DATA_SIZE];
+        buffer[..payload.len()].copy_from_slice(&payload);
+        let mut meta = Meta::new(
+            payload.len(),
+            self.addr.ip(),
+            self.addr.port(),
+            flags,
+            remote_pubkey,
+        );
+        meta.set_socket_addr(&self.addr);
+        Ok(Packet::new(buffer, meta))
+    }
+}
+
+#[cfg(feature = "json")]
+impl Packet {
+    /// Serializes the packet as JSON, in its human readable form.
+    ///
+    /// The payload is a base64 string, the source is a socket address string
+    /// and the flags are a list of names, e.g.
+    /// `{"payload":"AQID","addr":"10.0.0.1:8001","flags":["DISCARD"]}`.
+    pub fn to_json_debug(&self) -> String {
+        serde_json::to_string(self).expect("packets always serialize to JSON")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::PacketFlags,
+        solana_pubkey::Pubkey,
+        std::net::{Ipv4Addr, SocketAddr, SocketAddrV6},
+    };
+
+    fn packet() -> Packet {
+        let mut buffer = [0xff; PACKET_DATA_SIZE];
+        buffer[..3].copy_from_slice(&[1, 2, 3]);
+        let meta = Meta::new(
+            3,
+            Ipv4Addr::new(10, 0, 0, 1).into(),
+            8001,
+            PacketFlags::DISCARD | PacketFlags::FROM_STAKED_NODE,
+            Some(Pubkey::new_from_array([7; 32])),
+        );
+        Packet::new(buffer, meta)
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_json_round_trip() {
+        let packet = packet();
+        let json = packet.to_json_debug();
+        assert_eq!(
+            json,
+            format!(
+                r#"{{"payload":"AQID","addr":"10.0.0.1:8001","flags":["DISCARD","FROM_STAKED_NODE"],"remote_pubkey":"{}"}}"#,
+                Pubkey::new_from_array([7; 32])
+            )
+        );
+
+        let decoded: Packet = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded.meta(), packet.meta());
+        assert_eq!(decoded.buffer[..3], [1, 2, 3]);
+        // bytes past the payload are not kept
+        assert!(decoded.buffer[3..].iter().all(|&byte| byte == 0));
+
+        let packet = Packet::default();
+        let json = packet.to_json_debug();
+        assert_eq!(json, r#"{"payload":"","addr":"0.0.0.0:0","flags":[]}"#);
+        let decoded: Packet = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded, packet);
+    }
+
+    #[cfg(feature = "bincode")]
+    #[test]
+    fn test_bincode_layout() {
+        let packet = packet();
+        let bytes = bincode::serialize(&packet).unwrap();
+        // the derived layout: the buffer with its length, then the meta
+        let expected = [
+            &(PACKET_DATA_SIZE as u64).to_le_bytes()[..],
+            &packet.buffer[..],
+            &bincode::serialize(packet.meta()).unwrap(),
+        ]
+        .concat();
+        assert_eq!(bytes, expected);
+
+        let decoded: Packet = bincode::deserialize(&bytes).unwrap();
+        assert_eq!(decoded.buffer, packet.buffer);
+        assert_eq!(decoded.meta(), packet.meta());
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_json_scope_id() {
+        let link_local = SocketAddrV6::new("fe80::1".parse().unwrap(), 8001, 0, 3);
+        let mut packet = Packet::default();
+        packet.meta_mut().set_socket_addr_v6(&link_local);
+        let json = packet.to_json_debug();
+        assert_eq!(
+            json,
+            r#"{"payload":"","addr":"[fe80::1%3]:8001","flags":[]}"#
+        );
+        let decoded: Packet = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded.meta().socket_addr(), SocketAddr::V6(link_local));
+
+        let mut packet = Packet::default();
+        packet
+            .meta_mut()
+            .set_socket_addr(&"[fe80::1]:8001".parse().unwrap());
+        assert_eq!(
+            packet.to_json_debug(),
+            r#"{"payload":"","addr":"[fe80::1]:8001","flags":[]}"#
+        );
+    }
+
+    #[cfg(feature = "bincode")]
+    #[test]
+    fn test_bincode_meta_layout() {
+        // the serialized fields of `Meta`, in order
+        #[derive(serde_derive::Serialize)]
+        struct MetaLayout {
+            size: usize,
+            addr: std::net::IpAddr,
+            port: u16,
+            flags: PacketFlags,
+            remote_pubkey: Pubkey,
+            scope_id: u32,
+        }
+
+        for (addr, scope_id) in [
+            ("10.0.0.1:8001", 0),
+            ("[fe80::1]:8001", 0),
+            ("[fe80::1%3]:8001", 3),
+        ] {
+            let addr: SocketAddr = addr.parse().unwrap();
+            let mut meta = Meta::new(3, addr.ip(), addr.port(), PacketFlags::DISCARD, None);
+            meta.set_socket_addr(&addr);
+            let expected = MetaLayout {
+                size: 3,
+                addr: addr.ip(),
+                port: addr.port(),
+                flags: PacketFlags::DISCARD,
+                remote_pubkey: Pubkey::default(),
+                scope_id,
+            };
+            assert_eq!(
+                bincode::serialize(&meta).unwrap(),
+                bincode::serialize(&expected).unwrap()
+            );
+        }
+    }
+
+    #[cfg(feature = "bincode")]
+    #[test]
+    fn test_bincode_scope_id_round_trip() {
+        let link_local = SocketAddrV6::new("fe80::1".parse().unwrap(), 8001, 0, 3);
+        let mut packet = packet();
+        packet.meta_mut().set_socket_addr_v6(&link_local);
+        let decoded: Packet = bincode::deserialize(&bincode::serialize(&packet).unwrap()).unwrap();
+        assert_eq!(decoded.meta().socket_addr(), SocketAddr::V6(link_local));
+        assert_eq!(decoded, packet);
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_json_errors() {
+        let error = |json: &str| {
+            serde_json::from_str::<Packet>(json)
+                .unwrap_err()
+                .to_string()
+        };
+
+        assert!(
+            error(r#"{"payload":"","addr":"0.0.0.0:0","flags":["DISCARD","DROPPED"]}"#)
+                .starts_with(r#"unknown packet flag "DROPPED""#)
+        );
+        assert!(error(r#"{"payload":"!","addr":"0.0.0.0:0","flags":[]}"#)
+            .starts_with("invalid base64 payload"));
+        let payload = BASE64_STANDARD.encode([0; PACKET_DATA_SIZE + 1]);
+        assert!(error(&format!(
+            r#"{{"payload":"{payload}","addr":"0.0.0.0:0","flags":[]}}"#
+        ))
+        .starts_with("invalid length 1233"));
+        assert!(
+            error(r#"{"payload":"","addr":"0.0.0.0:0","flags":[],"remote_pubkey":"0"}"#)
+                .starts_with(r#"invalid remote pubkey "0""#)
+        );
+        assert!(error(r#"{"payload":"","addr":"0.0.0.0","flags":[]}"#)
+            .starts_with("invalid socket address"));
+        assert!(
+            error(r#"{"payload":"","addr":"0.0.0.0:0","flags":[],"size":3}"#)
+                .starts_with("unknown field `size`")
+        );
+    }
+}
@@ -0,0 +1,251 @@
+//! Signing and verification of packet payload regions.
+//!
+//! Shreds carry a signature over a suffix of their own payload. These helpers
+//! sign and verify such a region in place, without juggling separate borrows
+//! of the signed bytes and of the signature.
+
+#[cfg(feature = "verify")]
+use solana_pubkey::Pubkey;
+use {crate::Packet, solana_signature::SIGNATURE_BYTES, std::ops::Range};
+#[cfg(feature = "sign")]
+use {
+    solana_signer::{Signer, SignerError},
+    std::fmt,
+};
+
+/// Errors returned by [`Packet::sign_region`].
+#[cfg(feature = "sign")]
+#[derive(Debug, PartialEq, Eq)]
+pub enum SignRegionError {
+    /// The packet is marked as discard.
+    Discarded,
+    /// A range is inverted or extends past the end of the payload.
+    OutOfBounds,
+    /// The signed range and the signature range overlap.
+    OverlappingRanges,
+    /// The signature range is not [`SIGNATURE_BYTES`] long.
+    InvalidSignatureRange,
+    /// The signer failed to sign the region.
+    Signer(SignerError),
+}
+
+#[cfg(feature = "sign")]
+impl core::error::Error for SignRegionError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            Self::Signer(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "sign")]
+impl fmt::Display for SignRegionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Discarded => f.write_str("packet is marked as discard"),
+            Self::OutOfBounds => f.write_str("range is out of the payload bounds"),
+            Self::OverlappingRanges => f.write_str("signed and signature ranges overlap"),
+            Self::InvalidSignatureRange => {
+                write!(f, "signature range is not {SIGNATURE_BYTES} bytes long")
+            }
+            Self::Signer(e) => write!(f, "failed to sign region: {e}"),
+        }
+    }
+}
+
+#[cfg(feature = "sign")]
+impl From<SignerError> for SignRegionError {
+    fn from(e: SignerError) -> Self {
+        Self::Signer(e)
+    }
+}
+
+impl Packet {
+    /// Sign the payload bytes in `signed_range` with `signer`, and write the
+    /// signature into the payload bytes in `signature_range`.
+    ///
+    /// Both ranges are relative to the start of the payload and must lie
+    /// within it. They must not overlap, and `signature_range` must be
+    /// exactly [`SIGNATURE_BYTES`] long. The packet is left untouched if
+    /// signing fails.
+    #[cfg(feature = "sign")]
+    pub fn sign_region(
+        &mut self,
+        signer: &dyn Signer,
+        signed_range: Range<usize>,
+        signature_range: Range<usize>,
+    ) -> Result<(), SignRegionError> {
+        if self.meta.discard() {
+            return Err(SignRegionError::Discarded);
+        }
+        if !is_in_bounds(&signed_range, self.meta.size)
+            || !is_in_bounds(&signature_range, self.meta.size)
+        {
+            return Err(SignRegionError::OutOfBounds);
+        }
+        if signature_range.len() != SIGNATURE_BYTES {
+            return Err(SignRegionError::InvalidSignatureRange);
+        }
+        if signed_range.start < signature_range.end && signature_range.start < signed_range.end {
+            return Err(SignRegionError::OverlappingRanges);
+        }
+        let signature = signer.try_sign_message(&self.buffer[signed_range])?;
+        self.buffer[signature_range].copy_from_slice(signature.as_ref());
+        Ok(())
+    }
+
+    /// Returns true if the payload bytes in `signature_range` are a valid
+    /// signature by `pubkey` of the payload bytes in `signed_range`.
+    ///
+    /// Returns false if either range does not fit in the payload, or if the
+    /// packet is marked as discard.
+    #[cfg(feature = "verify")]
+    pub fn verify_region(
+        &self,
+        pubkey: &Pubkey,
+        signed_range: Range<usize>,
+        signature_range: Range<usize>,
+    ) -> bool {
+        let Some(signature) = self
+            .data(signature_range)
+            .and_then(|bytes| <[u8; SIGNATURE_BYTES]>::try_from(bytes).ok())
+        else {
+            return false;
+        };
+        self.data(signed_range).is_some_and(|message| {
+            solana_signature::Signature::from(signature).verify(pubkey.as_ref(), message)
+        })
+    }
+}
+
+#[cfg(feature = "sign")]
+fn is_in_bounds(range: &Range<usize>, size: usize) -> bool {
+    range.start <= range.end && range.end <= size.min(crate::PACKET_DATA_SIZE)
+}
+
+#[cfg(all(test, feature = "sign", feature = "verify"))]
+mod tests {
+    use {
+        super::*, solana_keypair::Keypair, solana_signature::Signature,
+        solana_signer::programmable_signer::ProgrammableSigner,
+    };
+
+    const SHRED_SIZE: usize = 1203;
+
+    fn shred_packet() -> Packet {
+        let mut packet = Packet::default();
+        for (i, byte) in packet.buffer_mut()[..SHRED_SIZE].iter_mut().enumerate() {
+            *byte = i as u8;
+        }
+        packet.meta_mut().size = SHRED_SIZE;
+        packet
+    }
+
+    #[test]
+    fn test_sign_verify_region() {
+        let keypair = Keypair::new();
+        let mut packet = shred_packet();
+        let signed_range = SIGNATURE_BYTES..SHRED_SIZE;
+        packet
+            .sign_region(&keypair, signed_range.clone(), 0..SIGNATURE_BYTES)
+            .unwrap();
+        assert_eq!(
+            packet.data(..SIGNATURE_BYTES).unwrap(),
+            keypair
+                .sign_message(packet.data(signed_range.clone()).unwrap())
+                .as_ref()
+        );
+        assert!(packet.verify_region(&keypair.pubkey(), signed_range.clone(), 0..SIGNATURE_BYTES));
+
+        // wrong key, tampered payload, misplaced signature
+        assert!(!packet.verify_region(
+            &Keypair::new().pubkey(),
+            signed_range.clone(),
+            0..SIGNATURE_BYTES
+        ));
+        assert!(!packet.verify_region(&keypair.pubkey(), 1..SHRED_SIZE, 0..SIGNATURE_BYTES));
+        assert!(!packet.verify_region(&keypair.pubkey(), signed_range.clone(), 1..65));
+        packet.buffer_mut()[100] ^= 1;
+        assert!(!packet.verify_region(&keypair.pubkey(), signed_range, 0..SIGNATURE_BYTES));
+    }
+
+    #[test]
+    fn test_sign_region_signer_output() {
+        let signature = Signature::from([7; SIGNATURE_BYTES]);
+        let signer = ProgrammableSigner::new_fixed(Pubkey::new_unique(), signature);
+        let mut packet = shred_packet();
+        packet
+            .sign_region(&signer, 0..100, SHRED_SIZE - SIGNATURE_BYTES..SHRED_SIZE)
+            .unwrap();
+        assert_eq!(
+            packet.data(SHRED_SIZE - SIGNATURE_BYTES..).unwrap(),
+            signature.as_ref()
+        );
+
+        let signer = ProgrammableSigner::new_failing(Pubkey::new_unique(), || {
+            SignerError::Custom("offline".to_string())
+        });
+        let expected = packet.clone();
+        assert_eq!(
+            packet.sign_region(&signer, 0..100, 100..164),
+            Err(SignRegionError::Signer(SignerError::Custom(
+                "offline".to_string()
+            )))
+        );
+        assert_eq!(packet, expected);
+    }
+
+    #[test]
+    fn test_sign_region_overlapping() {
+        let keypair = Keypair::new();
+        let mut packet = shred_packet();
+        for (signed_range, signature_range) in [
+            (0..SHRED_SIZE, 0..SIGNATURE_BYTES),
+            (63..SHRED_SIZE, 0..SIGNATURE_BYTES),
+            (100..200, 150..214),
+            (100..200, 37..101),
+        ] {
+            assert_eq!(
+                packet.sign_region(&keypair, signed_range, signature_range),
+                Err(SignRegionError::OverlappingRanges)
+            );
+        }
+        // adjacent ranges do not overlap
+        packet
+            .sign_region(&keypair, 64..SHRED_SIZE, 0..SIGNATURE_BYTES)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_sign_region_out_of_bounds() {
+        let keypair = Keypair::new();
+        let mut packet = shred_packet();
+        #[allow(clippy::reversed_empty_ranges)]
+        for (signed_range, signature_range) in [
+            (SIGNATURE_BYTES..SHRED_SIZE + 1, 0..SIGNATURE_BYTES),
+            (0..100, SHRED_SIZE..SHRED_SIZE + SIGNATURE_BYTES),
+            (200..100, 0..SIGNATURE_BYTES),
+        ] {
+            assert_eq!(
+                packet.sign_region(&keypair, signed_range, signature_range),
+                Err(SignRegionError::OutOfBounds)
+            );
+        }
+        assert_eq!(
+            packet.sign_region(&keypair, 100..200, 0..32),
+            Err(SignRegionError::InvalidSignatureRange)
+        );
+        assert!(!packet.verify_region(
+            &keypair.pubkey(),
+            SIGNATURE_BYTES..SHRED_SIZE + 1,
+            0..SIGNATURE_BYTES
+        ));
+
+        packet.meta_mut().set_discard(true);
+        assert_eq!(
+            packet.sign_region(&keypair, SIGNATURE_BYTES..SHRED_SIZE, 0..SIGNATURE_BYTES),
+            Err(SignRegionError::Discarded)
+        );
+    }
+}
@@ -0,0 +1,296 @@
+//! Host-side signature verification of transaction packets.
+//!
+//! Validators verify packet signatures in a dedicated pipeline stage, on the
+//! GPU when available. The helpers in this module verify packets on the CPU,
+//! for services replaying packet captures. They only query packets: no flag
+//! is set on the packets verified.
+
+use {
+    crate::Packet,
+    rayon::prelude::*,
+    solana_pubkey::PUBKEY_BYTES,
+    solana_short_vec::decode_len,
+    solana_signature::{Signature, SIGNATURE_BYTES},
+    std::ops::Range,
+};
+
+const MESSAGE_HEADER_BYTES: usize = 3;
+const MESSAGE_VERSION_PREFIX: u8 = 0x80;
+
+/// Location of the signatures, signer public keys and message of a
+/// transaction serialized in a packet.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TxOffsets {
+    /// Number of signatures, which is also the number of signers.
+    pub num_signatures: usize,
+    /// Offset of the first signature.
+    pub signatures_start: usize,
+    /// Offset of the public key of the first signer.
+    pub pubkeys_start: usize,
+    /// The signed message.
+    pub message: Range<usize>,
+}
+
+impl TxOffsets {
+    /// Locate the signature regions of the transaction in `packet`.
+    ///
+    /// Returns `None` if the packet is marked as discard, or if its payload
+    /// is not a legacy or v0 transaction with at least one signature and as
+    /// many signatures as required signers. Only the prefix of the payload
+    /// up to the signer public keys is parsed.
+    pub fn extract(packet: &Packet) -> Option<Self> {
+        let data = packet.data(..)?;
+        let (num_signatures, prefix_len) = decode_len(data).ok()?;
+        if num_signatures == 0 {
+            return None;
+        }
+        let signatures_start = prefix_len;
+        let message_start =
+            signatures_start.checked_add(num_signatures.checked_mul(SIGNATURE_BYTES)?)?;
+
+        let mut header_start = message_start;
+        let prefix = *data.get(message_start)?;
+        if prefix & MESSAGE_VERSION_PREFIX != 0 {
+            if prefix & !MESSAGE_VERSION_PREFIX != 0 {
+                // only v0 messages are supported
+                return None;
+            }
+            header_start = header_start.checked_add(1)?;
+        }
+        let num_required_signatures = usize::from(*data.get(header_start)?);
+        if num_required_signatures != num_signatures {
+            return None;
+        }
+
+        let keys_start = header_start.checked_add(MESSAGE_HEADER_BYTES)?;
+        let (num_keys, prefix_len) = decode_len(data.get(keys_start..)?).ok()?;
+        if num_keys < num_signatures {
+            return None;
+        }
+        let pubkeys_start = keys_start.checked_add(prefix_len)?;
+        let pubkeys_end = pubkeys_start.checked_add(num_signatures.checked_mul(PUBKEY_BYTES)?)?;
+        if pubkeys_end > data.len() {
+            return None;
+        }
+
+        Some(Self {
+            num_signatures,
+            signatures_start,
+            pubkeys_start,
+            message: message_start..data.len(),
+        })
+    }
+}
+
+/// Returns `true` if every signature of the transaction in `packet`, located
+/// by `offsets`, is valid.
+///
+/// The signatures are checked with [`solana_signature::verify_batch`], which
+/// rejects public keys and `R` components of small order, and non-canonical
+/// `R` and `s` components, like the runtime does. See its documentation for
+/// the signatures the batch equation cannot tell apart from strictly valid
+/// ones.
+///
+/// Returns `false` if the packet is marked as discard or if `offsets` does
+/// not fit in its payload.
+pub fn verify_packet_signatures(packet: &Packet, offsets: &TxOffsets) -> bool {
+    if offsets.num_signatures == 0 {
+        return false;
+    }
+    let Some(message) = packet.data(offsets.message.clone()) else {
+        return false;
+    };
+    let Some(signatures) = offsets
+        .num_signatures
+        .checked_mul(SIGNATURE_BYTES)
+        .and_then(|len| {
+            packet.data(offsets.signatures_start..offsets.signatures_start.checked_add(len)?)
+        })
+    else {
+        return false;
+    };
+    let Some(pubkeys) = offsets
+        .num_signatures
+        .checked_mul(PUBKEY_BYTES)
+        .and_then(|len| {
+            packet.data(offsets.pubkeys_start..offsets.pubkeys_start.checked_add(len)?)
+        })
+    else {
+        return false;
+    };
+
+    let signatures: Vec<Signature> = signatures
+        .chunks_exact(SIGNATURE_BYTES)
+        .map(|bytes| Signature::from(<[u8; SIGNATURE_BYTES]>::try_from(bytes).unwrap()))
+        .collect();
+    let items: Vec<(&[u8; PUBKEY_BYTES], &[u8], &Signature)> = pubkeys
+        .chunks_exact(PUBKEY_BYTES)
+        .zip(&signatures)
+        .map(|(pubkey, signature)| (pubkey.try_into().unwrap(), message, signature))
+        .collect();
+    solana_signature::verify_batch(&items)
+}
+
+/// Verify the transaction in each of `packets`, in parallel.
+///
+/// The result for a packet is `false` if it is marked as discard, if its
+/// payload is not a transaction, or if any signature is invalid. See
+/// [`verify_packet_signatures`].
+pub fn verify_batch(packets: &[Packet]) -> Vec<bool> {
+    packets
+        .par_iter()
+        .map(|packet| {
+            TxOffsets::extract(packet)
+                .is_some_and(|offsets| verify_packet_signatures(packet, &offsets))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*, solana_keypair::Keypair, solana_pubkey::Pubkey, solana_signer::Signer,
+        solana_system_interface::instruction as system_instruction,
+        solana_transaction::Transaction,
+    };
+
+    fn packet_from_bytes(bytes: &[u8]) -> Packet {
+        let mut packet = Packet::default();
+        packet.buffer_mut()[..bytes.len()].copy_from_slice(bytes);
+        packet.meta_mut().size = bytes.len();
+        packet
+    }
+
+    fn signed_packet(mut transaction: Transaction, signers: &[&Keypair]) -> Packet {
+        let message = bincode::serialize(&transaction.message).unwrap();
+        transaction.signatures = signers
+            .iter()
+            .map(|signer| signer.sign_message(&message))
+            .collect();
+        packet_from_bytes(&bincode::serialize(&transaction).unwrap())
+    }
+
+    fn transfer_packet() -> Packet {
+        let from = Keypair::new();
+        let instruction = system_instruction::transfer(&from.pubkey(), &Pubkey::new_unique(), 42);
+        let transaction = Transaction::new_with_payer(&[instruction], Some(&from.pubkey()));
+        signed_packet(transaction, &[&from])
+    }
+
+    fn two_signer_packet() -> Packet {
+        let payer = Keypair::new();
+        let from = Keypair::new();
+        let instruction = system_instruction::transfer(&from.pubkey(), &Pubkey::new_unique(), 42);
+        let transaction = Transaction::new_with_payer(&[instruction], Some(&payer.pubkey()));
+        signed_packet(transaction, &[&payer, &from])
+    }
+
+    fn verify(packet: &Packet) -> bool {
+        TxOffsets::extract(packet).is_some_and(|offsets| verify_packet_signatures(packet, &offsets))
+    }
+
+    #[test]
+    fn test_extract_offsets() {
+        let packet = transfer_packet();
+        let offsets = TxOffsets::extract(&packet).unwrap();
+        assert_eq!(offsets.num_signatures, 1);
+        assert_eq!(offsets.signatures_start, 1);
+        assert_eq!(offsets.message, 65..packet.meta().size);
+        // header and the compact-u16 length of the account keys
+        assert_eq!(offsets.pubkeys_start, 65 + 3 + 1);
+
+        let offsets = TxOffsets::extract(&two_signer_packet()).unwrap();
+        assert_eq!(offsets.num_signatures, 2);
+        assert_eq!(offsets.pubkeys_start, 1 + 128 + 3 + 1);
+    }
+
+    #[test]
+    fn test_verify_signed_transactions() {
+        assert!(verify(&transfer_packet()));
+        assert!(verify(&two_signer_packet()));
+    }
+
+    #[test]
+    fn test_verify_corrupted_signature() {
+        let mut packet = two_signer_packet();
+        packet.buffer_mut()[1 + 64 + 10] ^= 1;
+        assert!(!verify(&packet));
+    }
+
+    #[test]
+    fn test_verify_corrupted_message() {
+        let mut packet = transfer_packet();
+        let last = packet.meta().size - 1;
+        packet.buffer_mut()[last] ^= 1;
+        assert!(!verify(&packet));
+    }
+
+    #[test]
+    fn test_verify_small_order_forgery() {
+        // the identity point as public key and `R`, with `s = 0`, satisfies
+        // the verification equation for any message
+        let mut identity = [0u8; PUBKEY_BYTES];
+        identity[0] = 1;
+        let mut packet = transfer_packet();
+        let offsets = TxOffsets::extract(&packet).unwrap();
+        let signature = &mut packet.buffer_mut()[offsets.signatures_start..][..SIGNATURE_BYTES];
+        signature.fill(0);
+        signature[..PUBKEY_BYTES].copy_from_slice(&identity);
+        packet.buffer_mut()[offsets.pubkeys_start..][..PUBKEY_BYTES].copy_from_slice(&identity);
+        assert!(!verify(&packet));
+    }
+
+    #[test]
+    fn test_verify_malformed() {
+        let packet = transfer_packet();
+        let offsets = TxOffsets::extract(&packet).unwrap();
+        // signature regions out of the payload
+        let out_of_bounds = TxOffsets {
+            message: offsets.message.start..offsets.message.end + 1,
+            ..offsets.clone()
+        };
+        assert!(!verify_packet_signatures(&packet, &out_of_bounds));
+        let too_many = TxOffsets {
+            num_signatures: usize::MAX,
+            ..offsets.clone()
+        };
+        assert!(!verify_packet_signatures(&packet, &too_many));
+        let none = TxOffsets {
+            num_signatures: 0,
+            ..offsets
+        };
+        assert!(!verify_packet_signatures(&packet, &none));
+
+        // truncated payloads and garbage must not panic
+        let bytes = packet.data(..).unwrap().to_vec();
+        for len in 0..bytes.len() {
+            assert!(!verify(&packet_from_bytes(&bytes[..len])));
+        }
+        for byte in [0x00, 0x01, 0x7f, 0x80, 0xff] {
+            for len in [1, 2, 3, 100, crate::PACKET_DATA_SIZE] {
+                assert!(!verify(&packet_from_bytes(&vec![byte; len])));
+            }
+        }
+
+        let mut discarded = transfer_packet();
+        discarded.meta_mut().set_discard(true);
+        assert!(!verify(&discarded));
+    }
+
+    #[test]
+    fn test_verify_batch() {
+        let mut corrupted = transfer_packet();
+        corrupted.buffer_mut()[70] ^= 1;
+        let batch = vec![
+            transfer_packet(),
+            corrupted,
+            two_signer_packet(),
+            packet_from_bytes(&[0xff; 10]),
+        ];
+        let expected = batch.clone();
+        assert_eq!(verify_batch(&batch), vec![true, false, true, false]);
+        // pure query
+        assert_eq!(batch, expected);
+        assert!(verify_batch(&[]).is_empty());
+    }
+}
@@ -0,0 +1,191 @@
+//! Checks of the assumptions stages downstream of ingest make about [`Meta`].
+
+use {
+    crate::{Meta, PacketFlags, PACKET_DATA_SIZE},
+    std::fmt,
+};
+
+/// Flags no stage sets anymore.
+const UNUSED_FLAGS: PacketFlags = PacketFlags::UNUSED_0
+    .union(PacketFlags::UNUSED_1)
+    .union(PacketFlags::PERF_TRACK_PACKET);
+
+/// Flags of transactions, which repair responses never carry, see
+/// [`Meta::is_well_formed`].
+const TRANSACTION_FLAGS: PacketFlags = PacketFlags::FORWARDED.union(PacketFlags::SIMPLE_VOTE_TX);
+
+/// Why a [`Meta`] is not well formed, see [`Meta::is_well_formed`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MetaValidationError {
+    /// The size is larger than [`PACKET_DATA_SIZE`].
+    SizeTooLarge(usize),
+    /// The address is unspecified, `0.0.0.0` or `::`.
+    UnspecifiedAddr,
+    /// The port is zero, and the address is not a loopback address.
+    ZeroPort,
+    /// These flags are set, although no stage sets them anymore.
+    UnusedFlags(PacketFlags),
+    /// These flags are set together with [`PacketFlags::REPAIR`].
+    ConflictingFlags(PacketFlags),
+}
+
+impl core::error::Error for MetaValidationError {}
+
+impl fmt::Display for MetaValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::SizeTooLarge(size) => write!(
+                f,
+                "size {size} exceeds the packet data size of {PACKET_DATA_SIZE}"
+            ),
+            Self::UnspecifiedAddr => f.write_str("unspecified address"),
+            Self::ZeroPort => f.write_str("zero port on a non-loopback address"),
+            Self::UnusedFlags(flags) => write!(f, "unused flags set: {flags:?}"),
+            Self::ConflictingFlags(flags) => {
+                write!(f, "flags set on a repair packet: {flags:?}")
+            }
+        }
+    }
+}
+
+impl Meta {
+    /// Checks that the meta holds the invariants stages after ingest assume,
+    /// returning the first rule it breaks:
+    ///
+    /// 1. The size is at most [`PACKET_DATA_SIZE`], so that the payload is in
+    ///    the buffer.
+    /// 2. The address is specified, since packets are received from, or sent
+    ///    to, a peer.
+    /// 3. The port is not zero, which no peer can send from, unless the
+    ///    address is a loopback address, as in local tests.
+    /// 4. [`PacketFlags::UNUSED_0`], [`PacketFlags::UNUSED_1`] and
+    ///    [`PacketFlags::PERF_TRACK_PACKET`] are clear, since no stage sets
+    ///    them anymore.
+    /// 5. [`PacketFlags::REPAIR`] is not combined with
+    ///    [`PacketFlags::FORWARDED`] or [`PacketFlags::SIMPLE_VOTE_TX`], since
+    ///    repair responses carry shreds rather than transactions.
+    ///
+    /// The other fields, including the discard flag and drop reason, may
+    /// hold any value.
+    pub fn is_well_formed(&self) -> Result<(), MetaValidationError> {
+        if self.size > PACKET_DATA_SIZE {
+            return Err(MetaValidationError::SizeTooLarge(self.size));
+        }
+        if self.addr.is_unspecified() {
+            return Err(MetaValidationError::UnspecifiedAddr);
+        }
+        if self.port == 0 && !self.addr.is_loopback() {
+            return Err(MetaValidationError::ZeroPort);
+        }
+        let unused = self.flags.intersection(UNUSED_FLAGS);
+        if !unused.is_empty() {
+            return Err(MetaValidationError::UnusedFlags(unused));
+        }
+        if self.flags.contains(PacketFlags::REPAIR) {
+            let conflicting = self.flags.intersection(TRANSACTION_FLAGS);
+            if !conflicting.is_empty() {
+                return Err(MetaValidationError::ConflictingFlags(conflicting));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+    };
+
+    fn meta(addr: &str, flags: PacketFlags) -> Meta {
+        let mut meta = Meta {
+            size: 100,
+            flags,
+            ..Meta::default()
+        };
+        meta.set_socket_addr(&addr.parse::<SocketAddr>().unwrap());
+        meta
+    }
+
+    #[test]
+    fn test_is_well_formed() {
+        for addr in [
+            "10.0.0.1:8001",
+            "[2001:db8::1]:8001",
+            "127.0.0.1:0",
+            "[::1]:0",
+        ] {
+            assert_eq!(meta(addr, PacketFlags::empty()).is_well_formed(), Ok(()));
+        }
+        for flags in [
+            PacketFlags::DISCARD | PacketFlags::FORWARDED | PacketFlags::SIMPLE_VOTE_TX,
+            PacketFlags::DISCARD | PacketFlags::REPAIR | PacketFlags::FROM_STAKED_NODE,
+        ] {
+            assert_eq!(meta("10.0.0.1:8001", flags).is_well_formed(), Ok(()));
+        }
+
+        let mut full = meta("10.0.0.1:8001", PacketFlags::empty());
+        full.size = PACKET_DATA_SIZE;
+        assert_eq!(full.is_well_formed(), Ok(()));
+    }
+
+    #[test]
+    fn test_is_well_formed_violations() {
+        let mut large = meta("10.0.0.1:8001", PacketFlags::empty());
+        large.size = PACKET_DATA_SIZE + 1;
+        assert_eq!(
+            large.is_well_formed(),
+            Err(MetaValidationError::SizeTooLarge(PACKET_DATA_SIZE + 1))
+        );
+
+        for addr in [
+            Ipv4Addr::UNSPECIFIED.into(),
+            IpAddr::from(Ipv6Addr::UNSPECIFIED),
+        ] {
+            let mut unspecified = meta("10.0.0.1:8001", PacketFlags::empty());
+            unspecified.addr = addr;
+            assert_eq!(
+                unspecified.is_well_formed(),
+                Err(MetaValidationError::UnspecifiedAddr)
+            );
+        }
+        assert_eq!(
+            Meta::default().is_well_formed(),
+            Err(MetaValidationError::UnspecifiedAddr)
+        );
+
+        for addr in ["10.0.0.1:0", "[2001:db8::1]:0"] {
+            assert_eq!(
+                meta(addr, PacketFlags::empty()).is_well_formed(),
+                Err(MetaValidationError::ZeroPort)
+            );
+        }
+
+        for flags in [
+            PacketFlags::UNUSED_0,
+            PacketFlags::UNUSED_1,
+            PacketFlags::PERF_TRACK_PACKET,
+        ] {
+            assert_eq!(
+                meta("10.0.0.1:8001", flags | PacketFlags::DISCARD).is_well_formed(),
+                Err(MetaValidationError::UnusedFlags(flags))
+            );
+        }
+
+        assert_eq!(
+            meta("10.0.0.1:8001", PacketFlags::all()).is_well_formed(),
+            Err(MetaValidationError::UnusedFlags(UNUSED_FLAGS))
+        );
+        for flags in [
+            PacketFlags::FORWARDED,
+            PacketFlags::SIMPLE_VOTE_TX,
+            TRANSACTION_FLAGS,
+        ] {
+            assert_eq!(
+                meta("10.0.0.1:8001", flags | PacketFlags::REPAIR).is_well_formed(),
+                Err(MetaValidationError::ConflictingFlags(flags))
+            );
+        }
+    }
+}
@@ -0,0 +1,484 @@
+//! The instruction data layout of the signature verification precompiles.
+//!
+//! The ed25519 and secp256r1 programs take the same instruction data: a
+//! signature count in one byte, padding up to the signature offsets, one
+//! offsets structure per signature, and then any data the offsets point into.
+//! Only the sizes of the public keys and signatures, and the maximum number of
+//! signatures, differ. A precompile describes these with a
+//! [`SignatureOffsetsLayout`], and the functions of this crate build and parse
+//! its instruction data.
+#![no_std]
+#![cfg_attr(docsrs, feature(doc_cfg))]
+extern crate alloc;
+
+use {
+    alloc::{vec, vec::Vec},
+    bytemuck::{bytes_of, Pod},
+    core::{fmt, mem::size_of},
+};
+
+/// The sizes of the instruction data of a signature verification precompile.
+pub trait SignatureOffsetsLayout {
+    /// The offsets locating the public key, signature and message of one
+    /// signature.
+    type Offsets: Pod;
+
+    /// Size of a serialized public key.
+    const PUBKEY_SERIALIZED_SIZE: usize;
+    /// Size of a serialized signature.
+    const SIGNATURE_SERIALIZED_SIZE: usize;
+    /// Size of the serialized offsets of one signature. Must be the size of
+    /// [`Offsets`](Self::Offsets).
+    const SIGNATURE_OFFSETS_SERIALIZED_SIZE: usize = size_of::<Self::Offsets>();
+    /// Start of the first offsets, after the signature count and the padding
+    /// aligning the offsets.
+    const SIGNATURE_OFFSETS_START: usize = 2;
+    /// End of the offsets of an instruction verifying a single signature.
+    const DATA_START: usize =
+        Self::SIGNATURE_OFFSETS_SERIALIZED_SIZE + Self::SIGNATURE_OFFSETS_START;
+    /// Maximum number of signatures in a single instruction.
+    const MAX_SIGNATURES: usize;
+
+    /// The offsets of a signature whose data is in the instruction itself.
+    fn inline_offsets(
+        public_key_offset: u16,
+        signature_offset: u16,
+        message_data_offset: u16,
+        message_data_size: u16,
+    ) -> Self::Offsets;
+}
+
+/// A signature to verify, as passed to [`build_many`].
+#[derive(Debug, Clone)]
+pub enum Entry<'a, O> {
+    /// A signature whose data is located by the offsets, usually in other
+    /// instructions. The offsets are encoded as is.
+    External(O),
+    /// A signature whose data is appended to the instruction data.
+    ///
+    /// The public key and signature must be
+    /// [`PUBKEY_SERIALIZED_SIZE`](SignatureOffsetsLayout::PUBKEY_SERIALIZED_SIZE)
+    /// and
+    /// [`SIGNATURE_SERIALIZED_SIZE`](SignatureOffsetsLayout::SIGNATURE_SERIALIZED_SIZE)
+    /// bytes long.
+    Inline {
+        message: &'a [u8],
+        signature: &'a [u8],
+        pubkey: &'a [u8],
+    },
+}
+
+/// Reasons [`build_many`] can fail.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LayoutError {
+    /// More than [`MAX_SIGNATURES`](SignatureOffsetsLayout::MAX_SIGNATURES)
+    /// signatures were given.
+    TooManySignatures,
+    /// An offset or message length of an inline signature does not fit in
+    /// `u16`.
+    DataTooLarge,
+}
+
+impl core::error::Error for LayoutError {}
+
+impl fmt::Display for LayoutError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::TooManySignatures => f.write_str("too many signatures"),
+            Self::DataTooLarge => f.write_str("instruction data too large"),
+        }
+    }
+}
+
+/// Build the instruction data verifying a single signature.
+///
+/// # Panics
+///
+/// Panics if the public key or signature of an inline `entry` has the wrong
+/// length.
+pub fn build_single<L: SignatureOffsetsLayout>(
+    entry: Entry<'_, L::Offsets>,
+) -> Result<Vec<u8>, LayoutError> {
+    build_many::<L>(&[entry])
+}
+
+/// Build the instruction data verifying `entries`.
+///
+/// The offsets of all the entries come first, followed by the public key,
+/// signature and message of each inline entry, in order. The offsets of the
+/// inline entries point into the instruction data.
+///
+/// # Panics
+///
+/// Panics if the public key or signature of an inline entry has the wrong
+/// length.
+pub fn build_many<L: SignatureOffsetsLayout>(
+    entries: &[Entry<'_, L::Offsets>],
+) -> Result<Vec<u8>, LayoutError> {
+    if entries.len() > L::MAX_SIGNATURES {
+        return Err(LayoutError::TooManySignatures);
+    }
+    let num_signatures = u8::try_from(entries.len()).map_err(|_| LayoutError::TooManySignatures)?;
+    let (offsets, size) = compute_offsets::<L>(entries, |n| {
+        u16::try_from(n).map_err(|_| LayoutError::DataTooLarge)
+    })?;
+    let data = write::<L>(num_signatures, entries, &offsets, size);
+    debug_assert_eq!(
+        data.len(),
+        inline_instruction_size::<L>(entries).unwrap_or(size)
+    );
+    Ok(data)
+}
+
+/// Build the instruction data verifying `entries` as [`build_many`] does,
+/// but without checking the number of signatures, and truncating the count
+/// to a byte and the offsets and message lengths to `u16`.
+///
+/// This is how the instruction constructors of the precompiles have always
+/// encoded their data, and only makes a difference for data the precompiles
+/// reject anyway.
+///
+/// # Panics
+///
+/// Panics if the public key or signature of an inline entry has the wrong
+/// length.
+pub fn encode_many<L: SignatureOffsetsLayout>(entries: &[Entry<'_, L::Offsets>]) -> Vec<u8> {
+    let (offsets, size) = match compute_offsets::<L>(entries, |n| Ok(n as u16)) {
+        Ok(offsets) => offsets,
+        Err(_) => unreachable!("truncating offsets never fails"),
+    };
+    let data = write::<L>(entries.len() as u8, entries, &offsets, size);
+    debug_assert_eq!(
+        data.len(),
+        inline_instruction_size::<L>(entries).unwrap_or(size)
+    );
+    data
+}
+
+/// Parse the offsets of all the signatures in `data`.
+///
+/// Returns `None` if `data` is empty or too short to hold the offsets of the
+/// signatures it counts.
+pub fn parse<L: SignatureOffsetsLayout>(data: &[u8]) -> Option<Vec<L::Offsets>> {
+    let num_signatures = usize::from(*data.first()?);
+    let end = L::SIGNATURE_OFFSETS_START
+        .checked_add(L::SIGNATURE_OFFSETS_SERIALIZED_SIZE.checked_mul(num_signatures)?)?;
+    Some(
+        data.get(L::SIGNATURE_OFFSETS_START..end)?
+            .chunks_exact(L::SIGNATURE_OFFSETS_SERIALIZED_SIZE)
+            .map(bytemuck::pod_read_unaligned)
+            .collect(),
+    )
+}
+
+/// Returns the length of the instruction data built by [`build_many`] for
+/// `num_signatures` inline signatures over messages of `message_lens` bytes.
+///
+/// Returns `None` if `message_lens` does not hold exactly `num_signatures`
+/// lengths, if `num_signatures` exceeds
+/// [`MAX_SIGNATURES`](SignatureOffsetsLayout::MAX_SIGNATURES), or if an
+/// offset or message length does not fit in `u16`.
+pub fn instruction_size<L: SignatureOffsetsLayout>(
+    num_signatures: usize,
+    message_lens: &[usize],
+) -> Option<usize> {
+    if num_signatures > L::MAX_SIGNATURES || message_lens.len() != num_signatures {
+        return None;
+    }
+    let mut size = L::SIGNATURE_OFFSETS_START
+        .checked_add(L::SIGNATURE_OFFSETS_SERIALIZED_SIZE.checked_mul(num_signatures)?)?;
+    for &message_len in message_lens {
+        let message_data_offset = size
+            .checked_add(L::PUBKEY_SERIALIZED_SIZE)?
+            .checked_add(L::SIGNATURE_SERIALIZED_SIZE)?;
+        if message_data_offset > usize::from(u16::MAX) || message_len > usize::from(u16::MAX) {
+            return None;
+        }
+        size = message_data_offset.checked_add(message_len)?;
+    }
+    Some(size)
+}
+
+/// Returns the largest message length such that an instruction verifying
+/// `num_signatures` inline messages of that length fits in `budget` bytes of
+/// instruction data.
+///
+/// Returns `None` if `num_signatures` is zero or exceeds
+/// [`MAX_SIGNATURES`](SignatureOffsetsLayout::MAX_SIGNATURES), or if not even
+/// empty messages fit in `budget`.
+pub fn max_message_len_for<L: SignatureOffsetsLayout>(
+    num_signatures: usize,
+    budget: usize,
+) -> Option<usize> {
+    if num_signatures == 0 {
+        return None;
+    }
+    let fits = |message_len| {
+        instruction_size::<L>(num_signatures, &vec![message_len; num_signatures])
+            .is_some_and(|size| size <= budget)
+    };
+    if !fits(0) {
+        return None;
+    }
+    let (mut low, mut high) = (0, usize::from(u16::MAX));
+    while low < high {
+        let mid = low + (high - low).div_ceil(2);
+        if fits(mid) {
+            low = mid;
+        } else {
+            high = mid - 1;
+        }
+    }
+    Some(low)
+}
+
+/// Returns [`instruction_size`] for `entries` if they are all inline, the only
+/// case it covers, and within its limits.
+fn inline_instruction_size<L: SignatureOffsetsLayout>(
+    entries: &[Entry<'_, L::Offsets>],
+) -> Option<usize> {
+    let message_lens = entries
+        .iter()
+        .map(|entry| match entry {
+            Entry::External(_) => None,
+            Entry::Inline { message, .. } => Some(message.len()),
+        })
+        .collect::<Option<Vec<_>>>()?;
+    instruction_size::<L>(entries.len(), &message_lens)
+}
+
+/// Computes the offsets of `entries`, narrowing those of the inline entries
+/// with `to_u16`, and the length of the instruction data.
+fn compute_offsets<L: SignatureOffsetsLayout>(
+    entries: &[Entry<'_, L::Offsets>],
+    to_u16: fn(usize) -> Result<u16, LayoutError>,
+) -> Result<(Vec<L::Offsets>, usize), LayoutError> {
+    let mut data_offset = L::SIGNATURE_OFFSETS_START
+        .saturating_add(L::SIGNATURE_OFFSETS_SERIALIZED_SIZE.saturating_mul(entries.len()));
+    let offsets = entries
+        .iter()
+        .map(|entry| match entry {
+            Entry::External(offsets) => Ok(*offsets),
+            Entry::Inline {
+                message,
+                signature,
+                pubkey,
+            } => {
+                assert_eq!(pubkey.len(), L::PUBKEY_SERIALIZED_SIZE, "public key length");
+                assert_eq!(
+                    signature.len(),
+                    L::SIGNATURE_SERIALIZED_SIZE,
+                    "signature length"
+                );
+                let public_key_offset = data_offset;
+                let signature_offset = public_key_offset.saturating_add(L::PUBKEY_SERIALIZED_SIZE);
+                let message_data_offset =
+                    signature_offset.saturating_add(L::SIGNATURE_SERIALIZED_SIZE);
+                data_offset = message_data_offset.saturating_add(message.len());
+                Ok(L::inline_offsets(
+                    to_u16(public_key_offset)?,
+                    to_u16(signature_offset)?,
+                    to_u16(message_data_offset)?,
+                    to_u16(message.len())?,
+                ))
+            }
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok((offsets, data_offset))
+}
+
+fn write<L: SignatureOffsetsLayout>(
+    num_signatures: u8,
+    entries: &[Entry<'_, L::Offsets>],
+    offsets: &[L::Offsets],
+    size: usize,
+) -> Vec<u8> {
+    let mut data = Vec::with_capacity(size);
+
+    data.push(num_signatures);
+    // pad so that the offsets are aligned
+    data.resize(L::SIGNATURE_OFFSETS_START, 0);
+
+    for offsets in offsets {
+        data.extend_from_slice(bytes_of(offsets));
+    }
+
+    for entry in entries {
+        if let Entry::Inline {
+            message,
+            signature,
+            pubkey,
+        } = entry
+        {
+            data.extend_from_slice(pubkey);
+            data.extend_from_slice(signature);
+            data.extend_from_slice(message);
+        }
+    }
+    data
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A layout with short keys and signatures, whose offsets are the fields
+    /// of the precompile offsets structures in order.
+    struct TestLayout;
+
+    impl SignatureOffsetsLayout for TestLayout {
+        type Offsets = [u16; 7];
+
+        const PUBKEY_SERIALIZED_SIZE: usize = 3;
+        const SIGNATURE_SERIALIZED_SIZE: usize = 4;
+        const MAX_SIGNATURES: usize = 3;
+
+        fn inline_offsets(
+            public_key_offset: u16,
+            signature_offset: u16,
+            message_data_offset: u16,
+            message_data_size: u16,
+        ) -> Self::Offsets {
+            [
+                signature_offset,
+                u16::MAX,
+                public_key_offset,
+                u16::MAX,
+                message_data_offset,
+                message_data_size,
+                u16::MAX,
+            ]
+        }
+    }
+
+    const EXTERNAL: [u16; 7] = [100, 1, 200, 2, 300, 40, 3];
+
+    fn inline<'a>(message: &'a [u8], byte: &'a [u8; 4]) -> Entry<'a, [u16; 7]> {
+        Entry::Inline {
+            message,
+            signature: byte,
+            pubkey: &byte[..3],
+        }
+    }
+
+    #[test]
+    fn test_build_and_parse() {
+        assert_eq!(TestLayout::SIGNATURE_OFFSETS_SERIALIZED_SIZE, 14);
+        assert_eq!(TestLayout::DATA_START, 16);
+
+        let entries = [
+            inline(b"hello", &[1; 4]),
+            Entry::External(EXTERNAL),
+            inline(b"", &[2; 4]),
+        ];
+        let data = build_many::<TestLayout>(&entries).unwrap();
+        assert_eq!(data[..2], [3, 0]);
+        assert_eq!(
+            parse::<TestLayout>(&data).unwrap(),
+            [
+                [47, u16::MAX, 44, u16::MAX, 51, 5, u16::MAX],
+                EXTERNAL,
+                [59, u16::MAX, 56, u16::MAX, 63, 0, u16::MAX],
+            ]
+        );
+        assert_eq!(data[44..], [&[1; 7][..], b"hello", &[2; 7]].concat()[..]);
+        assert_eq!(encode_many::<TestLayout>(&entries), data);
+
+        let data = build_single::<TestLayout>(inline(b"ab", &[1; 4])).unwrap();
+        assert_eq!(data.len(), TestLayout::DATA_START + 3 + 4 + 2);
+        assert_eq!(instruction_size::<TestLayout>(1, &[2]), Some(data.len()));
+    }
+
+    #[test]
+    fn test_build_many_errors() {
+        let entries = vec![Entry::External(EXTERNAL); TestLayout::MAX_SIGNATURES + 1];
+        assert_eq!(
+            build_many::<TestLayout>(&entries),
+            Err(LayoutError::TooManySignatures)
+        );
+        // encoding does not check the count
+        assert_eq!(
+            parse::<TestLayout>(&encode_many::<TestLayout>(&entries))
+                .unwrap()
+                .len(),
+            TestLayout::MAX_SIGNATURES + 1
+        );
+
+        let message = vec![0; usize::from(u16::MAX) + 1];
+        assert_eq!(
+            build_single::<TestLayout>(inline(&message, &[1; 4])),
+            Err(LayoutError::DataTooLarge)
+        );
+        let message = vec![0; usize::from(u16::MAX)];
+        assert_eq!(
+            build_many::<TestLayout>(&[inline(&message, &[1; 4]), inline(b"", &[2; 4])]),
+            Err(LayoutError::DataTooLarge)
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "public key length")]
+    fn test_build_wrong_pubkey_length() {
+        let _ = build_single::<TestLayout>(Entry::Inline {
+            message: b"",
+            signature: &[1; 4],
+            pubkey: &[1; 4],
+        });
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        assert_eq!(parse::<TestLayout>(&[]), None);
+        assert_eq!(parse::<TestLayout>(&[0]), None);
+        assert_eq!(parse::<TestLayout>(&[0, 0]), Some(vec![]));
+        let data = build_single::<TestLayout>(Entry::External(EXTERNAL)).unwrap();
+        assert_eq!(parse::<TestLayout>(&data[..data.len() - 1]), None);
+        let mut data = data;
+        data[0] = 2;
+        assert_eq!(parse::<TestLayout>(&data), None);
+    }
+
+    #[test]
+    fn test_instruction_size() {
+        let messages: Vec<Vec<u8>> = [0, 1, 32, 255].iter().map(|&len| vec![3; len]).collect();
+        for num_signatures in 0..=TestLayout::MAX_SIGNATURES {
+            let entries: Vec<_> = messages
+                .iter()
+                .take(num_signatures)
+                .map(|message| inline(message, &[1; 4]))
+                .collect();
+            let message_lens: Vec<_> = messages[..num_signatures].iter().map(Vec::len).collect();
+            assert_eq!(
+                instruction_size::<TestLayout>(num_signatures, &message_lens),
+                Some(build_many::<TestLayout>(&entries).unwrap().len())
+            );
+        }
+        assert_eq!(instruction_size::<TestLayout>(2, &[10]), None);
+        assert_eq!(instruction_size::<TestLayout>(4, &[0; 4]), None);
+        assert_eq!(
+            instruction_size::<TestLayout>(1, &[usize::from(u16::MAX) + 1]),
+            None
+        );
+    }
+
+    #[test]
+    fn test_max_message_len_for() {
+        for num_signatures in 1..=TestLayout::MAX_SIGNATURES {
+            let max = max_message_len_for::<TestLayout>(num_signatures, 1232).unwrap();
+            let size =
+                |len| instruction_size::<TestLayout>(num_signatures, &vec![len; num_signatures]);
+            assert!(size(max).unwrap() <= 1232);
+            assert!(size(max + 1).unwrap() > 1232);
+        }
+        assert_eq!(
+            max_message_len_for::<TestLayout>(1, TestLayout::DATA_START + 7),
+            Some(0)
+        );
+        assert_eq!(
+            max_message_len_for::<TestLayout>(1, TestLayout::DATA_START + 6),
+            None
+        );
+        assert_eq!(max_message_len_for::<TestLayout>(0, 1232), None);
+        assert_eq!(max_message_len_for::<TestLayout>(4, usize::MAX), None);
+    }
+}
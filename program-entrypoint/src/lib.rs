@@ -37,6 +37,10 @@ pub const SUCCESS: u64 = 0;
 pub const HEAP_START_ADDRESS: u64 = 0x300000000;
 /// Length of the heap memory region used for program heap.
 pub const HEAP_LENGTH: usize = 32 * 1024;
+/// Maximum length of the heap memory region a program can request with the
+/// compute budget program, which must be a multiple of 1024 bytes.
+// Same as `solana_compute_budget_interface::MAX_HEAP_FRAME_BYTES`.
+pub const MAX_HEAP_LENGTH: usize = 256 * 1024;
 
 /// Value used to indicate that a serialized account is not a duplicate
 pub const NON_DUP_MARKER: u8 = u8::MAX;
@@ -124,6 +128,40 @@ pub const NON_DUP_MARKER: u8 = u8::MAX;
 #[macro_export]
 macro_rules! entrypoint {
     ($process_instruction:ident) => {
+        $crate::entrypoint_with_heap!($process_instruction, $crate::HEAP_LENGTH);
+    };
+}
+
+/// Declare the program entrypoint and set up global handlers, with a heap
+/// larger than the default [`HEAP_LENGTH`].
+///
+/// This is the same as the [`entrypoint!`] macro, except that the global
+/// allocator uses `heap_len` bytes of heap. The heap length must be a
+/// multiple of 1024 bytes, between [`HEAP_LENGTH`] and [`MAX_HEAP_LENGTH`],
+/// which is checked at compile time.
+///
+/// The runtime only maps the default heap unless the transaction requests a
+/// larger heap frame with the compute budget program, e.g. with
+/// `ComputeBudgetInstruction::request_heap_frame(heap_len)`. Every
+/// transaction invoking the program must request at least `heap_len` bytes,
+/// or allocations past the default heap fail with an access violation.
+///
+/// ```ignore
+/// const HEAP_LENGTH: usize = 64 * 1024;
+///
+/// entrypoint_with_heap!(process_instruction, HEAP_LENGTH);
+/// ```
+#[macro_export]
+macro_rules! entrypoint_with_heap {
+    ($process_instruction:ident, $heap_len:expr) => {
+        const _: () = assert!(
+            $heap_len % 1024 == 0
+                && $heap_len >= $crate::HEAP_LENGTH
+                && $heap_len <= $crate::MAX_HEAP_LENGTH,
+            "the heap length must be a multiple of 1024 bytes, between HEAP_LENGTH and \
+             MAX_HEAP_LENGTH",
+        );
+
         /// # Safety
         #[no_mangle]
         pub unsafe extern "C" fn entrypoint(input: *mut u8) -> u64 {
@@ -133,7 +171,7 @@ macro_rules! entrypoint {
                 Err(error) => error.into(),
             }
         }
-        $crate::custom_heap_default!();
+        $crate::custom_heap_default!($heap_len);
         $crate::custom_panic_default!();
     };
 }
@@ -215,15 +253,20 @@ macro_rules! entrypoint_no_alloc {
 ///
 /// [global allocator]: https://doc.rust-lang.org/stable/std/alloc/trait.GlobalAlloc.html
 ///
+/// The allocator uses [`HEAP_LENGTH`] bytes of heap, or the given number of
+/// bytes, see [`entrypoint_with_heap!`].
 #[macro_export]
 macro_rules! custom_heap_default {
     () => {
+        $crate::custom_heap_default!($crate::HEAP_LENGTH);
+    };
+    ($heap_len:expr) => {
         #[cfg(all(not(feature = "custom-heap"), target_os = "solana"))]
         #[global_allocator]
         static A: $crate::BumpAllocator = unsafe {
             $crate::BumpAllocator::with_fixed_address_range(
                 $crate::HEAP_START_ADDRESS as usize,
-                $crate::HEAP_LENGTH,
+                $heap_len,
             )
         };
     };
@@ -298,11 +341,25 @@ macro_rules! custom_panic_default {
 }
 
 /// The bump allocator used as the default rust heap when running programs.
+///
+/// Allocations are made from the end of the heap towards its start, where
+/// the allocator keeps its state. With the `heap-stats` feature, the state
+/// also holds the end of the heap and the lowest address allocated, for
+/// [`BumpAllocator::high_water_mark`] and the [`heap_used`] family of
+/// functions.
 pub struct BumpAllocator {
     start: usize,
     len: usize,
 }
 
+/// Bytes at the start of the heap holding the state of the allocator: the
+/// address of the last allocation and, with the `heap-stats` feature, the
+/// end of the heap and the lowest address allocated.
+#[cfg(not(feature = "heap-stats"))]
+const HEADER_LEN: usize = size_of::<usize>();
+#[cfg(feature = "heap-stats")]
+const HEADER_LEN: usize = 3 * size_of::<usize>();
+
 impl BumpAllocator {
     /// Creates the allocator tied to a provided slice.
     /// This will not initialize the provided memory, except for the first
@@ -319,8 +376,8 @@ impl BumpAllocator {
     #[allow(clippy::arithmetic_side_effects)]
     pub unsafe fn new(arena: &mut [u8]) -> Self {
         debug_assert!(
-            arena.len() > size_of::<usize>(),
-            "Arena should be larger than usize"
+            arena.len() > HEADER_LEN,
+            "Arena should be larger than the allocator header"
         );
 
         // create a pointer to the start of the arena
@@ -329,10 +386,13 @@ impl BumpAllocator {
         // initialize the data there
         *pos_ptr = pos_ptr as usize + arena.len();
 
-        Self {
+        let allocator = Self {
             start: pos_ptr as usize,
             len: arena.len(),
-        }
+        };
+        #[cfg(feature = "heap-stats")]
+        allocator.init_stats();
+        allocator
     }
 
     /// Creates the allocator tied to specific range of addresses.
@@ -347,6 +407,183 @@ impl BumpAllocator {
     pub const unsafe fn with_fixed_address_range(start: usize, len: usize) -> Self {
         Self { start, len }
     }
+
+    /// Frees all the allocations, keeping the high-water mark.
+    ///
+    /// # Safety
+    /// No allocation may be used after the reset, as the memory will be
+    /// handed out again.
+    #[cfg(feature = "heap-stats")]
+    #[allow(clippy::arithmetic_side_effects)]
+    pub unsafe fn reset(&self) {
+        let pos_ptr = self.start as *mut usize;
+        if *pos_ptr != 0 {
+            *pos_ptr = self.start + self.len;
+        }
+    }
+
+    /// Pointer to the end of the heap and to the lowest address allocated.
+    #[cfg(feature = "heap-stats")]
+    fn stats_ptrs(&self) -> (*mut usize, *mut usize) {
+        let pos_ptr = self.start as *mut usize;
+        (pos_ptr.wrapping_add(1), pos_ptr.wrapping_add(2))
+    }
+
+    #[cfg(feature = "heap-stats")]
+    #[allow(clippy::arithmetic_side_effects)]
+    unsafe fn init_stats(&self) {
+        let (end_ptr, lowest_ptr) = self.stats_ptrs();
+        *end_ptr = self.start + self.len;
+        *lowest_ptr = self.start + self.len;
+    }
+
+    /// Returns the address of the last allocation, or None before the first
+    /// allocation.
+    #[cfg(feature = "heap-stats")]
+    fn pos(&self) -> Option<usize> {
+        // SAFETY: The allocator owns the start of the heap.
+        let pos = unsafe { *(self.start as *const usize) };
+        (pos != 0).then_some(pos)
+    }
+
+    /// Bytes of heap allocated since the last reset, including the padding
+    /// of alignment.
+    #[cfg(feature = "heap-stats")]
+    #[allow(clippy::arithmetic_side_effects)]
+    pub fn used(&self) -> usize {
+        self.pos().map_or(0, |pos| self.start + self.len - pos)
+    }
+
+    /// Bytes of heap left to allocate.
+    ///
+    /// Allocations needing alignment may fail with fewer bytes.
+    #[cfg(feature = "heap-stats")]
+    #[allow(clippy::arithmetic_side_effects)]
+    pub fn remaining(&self) -> usize {
+        self.pos()
+            .unwrap_or(self.start + self.len)
+            .saturating_sub(self.start + HEADER_LEN)
+    }
+
+    /// The largest number of bytes of heap allocated at once, across resets.
+    #[cfg(feature = "heap-stats")]
+    #[allow(clippy::arithmetic_side_effects)]
+    pub fn high_water_mark(&self) -> usize {
+        if self.pos().is_none() {
+            return 0;
+        }
+        let (_, lowest_ptr) = self.stats_ptrs();
+        // SAFETY: The allocator owns the start of the heap, and wrote the
+        // lowest address with the first allocation.
+        self.start + self.len - unsafe { *lowest_ptr }
+    }
+}
+
+/// The allocator of the program heap, if it made an allocation.
+///
+/// The allocator records the end of the heap with its first allocation, so
+/// heaps larger than [`HEAP_LENGTH`] are supported. On other targets than
+/// Solana, there is no program heap.
+///
+/// The state is read from the start of the heap, assuming the default global
+/// allocator. A custom global allocator may have written anything there.
+#[cfg(feature = "heap-stats")]
+#[allow(clippy::arithmetic_side_effects)]
+fn program_heap() -> Option<BumpAllocator> {
+    #[cfg(target_os = "solana")]
+    {
+        // SAFETY: The program heap is mapped from `HEAP_START_ADDRESS`.
+        let end = unsafe { *(HEAP_START_ADDRESS as *const usize).add(1) };
+        (end != 0).then(|| unsafe {
+            BumpAllocator::with_fixed_address_range(
+                HEAP_START_ADDRESS as usize,
+                end - HEAP_START_ADDRESS as usize,
+            )
+        })
+    }
+    #[cfg(not(target_os = "solana"))]
+    None
+}
+
+/// Bytes of the program heap allocated by the default global allocator.
+///
+/// Returns 0 before the first allocation and on other targets than Solana.
+/// The result is meaningless with a custom global allocator, which does not
+/// keep the state of the default one at the start of the heap.
+#[cfg(feature = "heap-stats")]
+pub fn heap_used() -> usize {
+    program_heap().map_or(0, |heap| heap.used())
+}
+
+/// Bytes of the program heap left to the default global allocator.
+///
+/// Returns 0 before the first allocation and on other targets than Solana.
+/// The result is meaningless with a custom global allocator, which does not
+/// keep the state of the default one at the start of the heap.
+#[cfg(feature = "heap-stats")]
+pub fn heap_remaining() -> usize {
+    program_heap().map_or(0, |heap| heap.remaining())
+}
+
+/// The largest number of bytes of the program heap allocated at once by the
+/// default global allocator.
+///
+/// Returns 0 before the first allocation and on other targets than Solana.
+/// The result is meaningless with a custom global allocator, which does not
+/// keep the state of the default one at the start of the heap.
+#[cfg(feature = "heap-stats")]
+pub fn heap_high_water_mark() -> usize {
+    program_heap().map_or(0, |heap| heap.high_water_mark())
+}
+
+/// Logs the bytes of the program heap used and remaining, and the high-water
+/// mark, without allocating.
+///
+/// Nothing is logged on other targets than Solana.
+#[cfg(feature = "heap-stats")]
+pub fn log_heap_stats() {
+    #[cfg(target_os = "solana")]
+    log_heap_stats_syscall();
+}
+
+#[cfg(all(feature = "heap-stats", target_os = "solana"))]
+fn log_heap_stats_syscall() {
+    use std::fmt::Write;
+
+    struct Buffer {
+        bytes: [u8; 128],
+        len: usize,
+    }
+
+    impl Write for Buffer {
+        fn write_str(&mut self, s: &str) -> std::fmt::Result {
+            let end = self.len.checked_add(s.len()).ok_or(std::fmt::Error)?;
+            self.bytes
+                .get_mut(self.len..end)
+                .ok_or(std::fmt::Error)?
+                .copy_from_slice(s.as_bytes());
+            self.len = end;
+            Ok(())
+        }
+    }
+
+    let mut buffer = Buffer {
+        bytes: [0; 128],
+        len: 0,
+    };
+    // the message is at most 110 bytes long
+    let _ = write!(
+        buffer,
+        "Heap: {} bytes used, {} bytes remaining, {} bytes high-water mark",
+        heap_used(),
+        heap_remaining(),
+        heap_high_water_mark(),
+    );
+    let message = &buffer.bytes[..buffer.len];
+
+    unsafe {
+        __log(message.as_ptr(), message.len() as u64);
+    }
 }
 
 /// Integer arithmetic in this global allocator implementation is safe when
@@ -361,13 +598,22 @@ unsafe impl std::alloc::GlobalAlloc for BumpAllocator {
         if pos == 0 {
             // First time, set starting position
             pos = self.start + self.len;
+            #[cfg(feature = "heap-stats")]
+            self.init_stats();
         }
         pos = pos.saturating_sub(layout.size());
         pos &= !(layout.align().wrapping_sub(1));
-        if pos < self.start + size_of::<*mut u8>() {
+        if pos < self.start + HEADER_LEN {
             return null_mut();
         }
         *pos_ptr = pos;
+        #[cfg(feature = "heap-stats")]
+        {
+            let (_, lowest_ptr) = self.stats_ptrs();
+            if pos < *lowest_ptr {
+                *lowest_ptr = pos;
+            }
+        }
         pos as *mut u8
     }
     #[inline]
@@ -573,7 +819,7 @@ mod test {
         {
             let mut heap = [0u8; 128];
             let allocator = unsafe { BumpAllocator::new(&mut heap) };
-            for i in 0..128 - size_of::<*mut u8>() {
+            for i in 0..128 - HEADER_LEN {
                 let ptr = unsafe {
                     allocator.alloc(Layout::from_size_align(1, size_of::<u8>()).unwrap())
                 };
@@ -605,18 +851,82 @@ mod test {
             let ptr = unsafe { allocator.alloc(Layout::from_size_align(1, 64).unwrap()) };
             assert_eq!(0, ptr.align_offset(64));
         }
-        // alloc entire block (minus the pos ptr)
+        // alloc entire block (minus the header)
         {
             let mut heap = [0u8; 128];
             let allocator = unsafe { BumpAllocator::new(&mut heap) };
             let ptr = unsafe {
                 allocator.alloc(
-                    Layout::from_size_align(heap.len() - size_of::<usize>(), size_of::<u8>())
-                        .unwrap(),
+                    Layout::from_size_align(heap.len() - HEADER_LEN, size_of::<u8>()).unwrap(),
                 )
             };
             assert_ne!(ptr, null_mut());
             assert_eq!(0, ptr.align_offset(size_of::<u64>()));
         }
     }
+
+    #[cfg(feature = "heap-stats")]
+    #[test]
+    fn test_bump_allocator_reset() {
+        let mut heap = [0u8; 128];
+        let end = heap.as_ptr() as usize + heap.len();
+        let allocator = unsafe { BumpAllocator::new(&mut heap) };
+        let layout = Layout::from_size_align(16, 1).unwrap();
+        let ptr = unsafe { allocator.alloc(layout) };
+        assert_eq!(ptr as usize, end - 16);
+        unsafe { allocator.reset() };
+        let ptr = unsafe { allocator.alloc(layout) };
+        assert_eq!(ptr as usize, end - 16);
+    }
+
+    #[cfg(feature = "heap-stats")]
+    #[test]
+    fn test_heap_stats() {
+        let mut heap = [0u8; 256];
+        let len = heap.len();
+        let allocator = unsafe { BumpAllocator::new(&mut heap) };
+        assert_eq!(allocator.used(), 0);
+        assert_eq!(allocator.remaining(), len - HEADER_LEN);
+        assert_eq!(allocator.high_water_mark(), 0);
+
+        let ptr = unsafe { allocator.alloc(Layout::from_size_align(3, 1).unwrap()) };
+        assert_ne!(ptr, null_mut());
+        assert_eq!(allocator.used(), 3);
+        assert_eq!(allocator.remaining(), len - HEADER_LEN - 3);
+
+        // the padding of alignment is counted as used
+        let ptr = unsafe { allocator.alloc(Layout::from_size_align(8, 8).unwrap()) };
+        assert_eq!(0, ptr.align_offset(8));
+        let used = allocator.used();
+        assert!((11..19).contains(&used));
+        assert_eq!(allocator.remaining(), len - HEADER_LEN - used);
+        assert_eq!(allocator.high_water_mark(), used);
+
+        // the high-water mark is kept across resets
+        unsafe { allocator.reset() };
+        assert_eq!(allocator.used(), 0);
+        assert_eq!(allocator.remaining(), len - HEADER_LEN);
+        assert_eq!(allocator.high_water_mark(), used);
+        let ptr = unsafe { allocator.alloc(Layout::from_size_align(2, 1).unwrap()) };
+        assert_ne!(ptr, null_mut());
+        assert_eq!(allocator.high_water_mark(), used);
+
+        // exhaustion returns null, leaving the counters alone
+        let ptr = unsafe { allocator.alloc(Layout::from_size_align(len, 1).unwrap()) };
+        assert_eq!(ptr, null_mut());
+        assert_eq!(allocator.used(), 2);
+        let ptr =
+            unsafe { allocator.alloc(Layout::from_size_align(len - HEADER_LEN - 2, 1).unwrap()) };
+        assert_ne!(ptr, null_mut());
+        assert_eq!(allocator.remaining(), 0);
+        assert_eq!(allocator.high_water_mark(), len - HEADER_LEN);
+        let ptr = unsafe { allocator.alloc(Layout::from_size_align(1, 1).unwrap()) };
+        assert_eq!(ptr, null_mut());
+
+        // there is no program heap on the host
+        assert_eq!(heap_used(), 0);
+        assert_eq!(heap_remaining(), 0);
+        assert_eq!(heap_high_water_mark(), 0);
+        log_heap_stats();
+    }
 }
@@ -8,6 +8,44 @@ use borsh::io::Error as BorshIoError;
 use core::{convert::TryFrom, fmt};
 #[cfg(feature = "serde")]
 use serde_derive::{Deserialize, Serialize};
+pub use {
+    namespace::{
+        namespace, validate_namespaces, CustomErrorNamespace, NamespacedError,
+        OverlappingNamespaces,
+    },
+    parse::ParseProgramErrorError,
+};
+
+mod macros;
+mod namespace;
+mod parse;
+pub mod sdk_codes;
+mod stable_hash;
+
+// hidden re-exports to make macros work
+#[doc(hidden)]
+pub mod __private {
+    /// Same comparison as `solana_address::address_eq`, which can't be
+    /// re-exported since `solana-address` depends on this crate.
+    #[cfg(feature = "address-eq")]
+    #[inline(always)]
+    pub fn address_eq(a1: &[u8; 32], a2: &[u8; 32]) -> bool {
+        use core::ptr::read_unaligned;
+
+        let p1_ptr = a1.as_ptr().cast::<u64>();
+        let p2_ptr = a2.as_ptr().cast::<u64>();
+
+        unsafe {
+            read_unaligned(p1_ptr) == read_unaligned(p2_ptr)
+                && read_unaligned(p1_ptr.add(1)) == read_unaligned(p2_ptr.add(1))
+                && read_unaligned(p1_ptr.add(2)) == read_unaligned(p2_ptr.add(2))
+                && read_unaligned(p1_ptr.add(3)) == read_unaligned(p2_ptr.add(3))
+        }
+    }
+
+    #[cfg(feature = "debug-logs")]
+    pub use solana_msg::sol_log;
+}
 
 pub type ProgramResult = core::result::Result<(), ProgramError>;
 
@@ -154,7 +192,8 @@ impl fmt::Display for ProgramError {
 ///
 /// The `ProgramError::to_str::<E>()` function also requires implementing
 /// `TryFrom<u32>` on an error type, which can be done easily using
-/// `num_enum::TryFromPrimitive`.
+/// `num_enum::TryFromPrimitive`. The `ProgramErrorCode` derive of
+/// `solana-sdk-macro` implements both traits for fieldless error enums.
 pub trait ToStr {
     fn to_str(&self) -> &'static str;
 }
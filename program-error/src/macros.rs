@@ -0,0 +1,296 @@
+//! Macros to check conditions and return errors early.
+//!
+//! With the `debug-logs` feature, each macro logs the file and line it was
+//! invoked from before returning the error.
+
+/// Returns `Err(err.into())` from the enclosing function if `cond` is false.
+///
+/// # Examples
+///
+/// ```
+/// use solana_program_error::{require, ProgramError, ProgramResult};
+///
+/// fn check_amount(amount: u64) -> ProgramResult {
+///     require!(amount > 0, ProgramError::InvalidArgument);
+///     Ok(())
+/// }
+///
+/// assert_eq!(check_amount(1), Ok(()));
+/// assert_eq!(check_amount(0), Err(ProgramError::InvalidArgument));
+/// ```
+#[macro_export]
+macro_rules! require {
+    ($cond:expr, $err:expr $(,)?) => {
+        if !$cond {
+            $crate::__log_error_location!();
+            return ::core::result::Result::Err(::core::convert::Into::into($err));
+        }
+    };
+}
+
+/// Returns `Err(err.into())` from the enclosing function if `left` is not
+/// equal to `right`.
+///
+/// Both operands are evaluated once.
+///
+/// # Examples
+///
+/// ```
+/// use solana_program_error::{require_eq, ProgramError, ProgramResult};
+///
+/// fn check_len(data: &[u8]) -> ProgramResult {
+///     require_eq!(data.len(), 8, ProgramError::InvalidInstructionData);
+///     Ok(())
+/// }
+///
+/// assert_eq!(check_len(&[0; 8]), Ok(()));
+/// assert_eq!(check_len(&[0; 7]), Err(ProgramError::InvalidInstructionData));
+/// ```
+#[macro_export]
+macro_rules! require_eq {
+    ($left:expr, $right:expr, $err:expr $(,)?) => {
+        match (&$left, &$right) {
+            (left, right) => {
+                if *left != *right {
+                    $crate::__log_error_location!();
+                    return ::core::result::Result::Err(::core::convert::Into::into($err));
+                }
+            }
+        }
+    };
+}
+
+/// Returns `Err(err.into())` from the enclosing function if `left` is equal
+/// to `right`.
+///
+/// Both operands are evaluated once.
+///
+/// # Examples
+///
+/// ```
+/// use solana_program_error::{require_neq, ProgramError, ProgramResult};
+///
+/// fn check_distinct(source: u8, destination: u8) -> ProgramResult {
+///     require_neq!(source, destination, ProgramError::InvalidArgument);
+///     Ok(())
+/// }
+///
+/// assert_eq!(check_distinct(1, 2), Ok(()));
+/// assert_eq!(check_distinct(1, 1), Err(ProgramError::InvalidArgument));
+/// ```
+#[macro_export]
+macro_rules! require_neq {
+    ($left:expr, $right:expr, $err:expr $(,)?) => {
+        match (&$left, &$right) {
+            (left, right) => {
+                if *left == *right {
+                    $crate::__log_error_location!();
+                    return ::core::result::Result::Err(::core::convert::Into::into($err));
+                }
+            }
+        }
+    };
+}
+
+/// Returns `Err(err.into())` from the enclosing function if the keys behind
+/// the references `left` and `right` differ.
+///
+/// With the `address-eq` feature, the keys must be `&Address` and are
+/// compared 8 bytes at a time like `solana_address::address_eq`, which is
+/// cheaper than `==` on chain. Without the feature, the keys are compared
+/// with `==`.
+///
+/// # Examples
+///
+/// ```
+/// use solana_program_error::{require_keys_eq, ProgramError, ProgramResult};
+/// # use solana_address::Address;
+///
+/// fn check_owner(owner: &Address, expected: &Address) -> ProgramResult {
+///     require_keys_eq!(owner, expected, ProgramError::IllegalOwner);
+///     Ok(())
+/// }
+///
+/// let owner = Address::new_from_array([1; 32]);
+/// assert_eq!(check_owner(&owner, &owner), Ok(()));
+/// assert_eq!(
+///     check_owner(&owner, &Address::default()),
+///     Err(ProgramError::IllegalOwner)
+/// );
+/// ```
+#[macro_export]
+macro_rules! require_keys_eq {
+    ($left:expr, $right:expr, $err:expr $(,)?) => {
+        match ($left, $right) {
+            (left, right) => {
+                if !$crate::__keys_eq!(left, right) {
+                    $crate::__log_error_location!();
+                    return ::core::result::Result::Err(::core::convert::Into::into($err));
+                }
+            }
+        }
+    };
+}
+
+/// Evaluates to `Err(ProgramError::from(err))`.
+///
+/// # Examples
+///
+/// ```
+/// use solana_program_error::{err, ProgramError, ProgramResult};
+///
+/// #[derive(Debug)]
+/// enum MyError {
+///     Frozen = 1,
+/// }
+///
+/// impl From<MyError> for ProgramError {
+///     fn from(e: MyError) -> Self {
+///         ProgramError::Custom(e as u32)
+///     }
+/// }
+///
+/// fn transfer(frozen: bool) -> ProgramResult {
+///     if frozen {
+///         return err!(MyError::Frozen);
+///     }
+///     Ok(())
+/// }
+///
+/// assert_eq!(transfer(false), Ok(()));
+/// assert_eq!(transfer(true), Err(ProgramError::Custom(1)));
+/// ```
+#[macro_export]
+macro_rules! err {
+    ($err:expr $(,)?) => {{
+        $crate::__log_error_location!();
+        ::core::result::Result::Err($crate::ProgramError::from($err))
+    }};
+}
+
+#[cfg(feature = "debug-logs")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __log_error_location {
+    () => {
+        $crate::__private::sol_log(::core::concat!(
+            "Error thrown at ",
+            ::core::file!(),
+            ":",
+            ::core::line!()
+        ))
+    };
+}
+
+#[cfg(not(feature = "debug-logs"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __log_error_location {
+    () => {};
+}
+
+#[cfg(feature = "address-eq")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __keys_eq {
+    ($left:expr, $right:expr) => {
+        $crate::__private::address_eq($left.as_array(), $right.as_array())
+    };
+}
+
+#[cfg(not(feature = "address-eq"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __keys_eq {
+    ($left:expr, $right:expr) => {
+        *$left == *$right
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use {crate::ProgramError, solana_address::Address};
+
+    #[derive(Debug)]
+    enum TestError {
+        Unlucky = 7,
+    }
+
+    impl From<TestError> for ProgramError {
+        fn from(e: TestError) -> Self {
+            ProgramError::Custom(e as u32)
+        }
+    }
+
+    #[test]
+    fn test_require() {
+        fn check(cond: bool) -> Result<(), ProgramError> {
+            require!(cond, TestError::Unlucky);
+            Ok(())
+        }
+        assert_eq!(check(true), Ok(()));
+        assert_eq!(check(false), Err(ProgramError::Custom(7)));
+    }
+
+    #[test]
+    fn test_require_eq() {
+        fn check(left: u64, right: u64) -> Result<(), ProgramError> {
+            require_eq!(left, right, ProgramError::InvalidArgument);
+            Ok(())
+        }
+        assert_eq!(check(3, 3), Ok(()));
+        assert_eq!(check(3, 4), Err(ProgramError::InvalidArgument));
+    }
+
+    #[test]
+    fn test_require_eq_evaluates_once() {
+        fn check(values: &mut impl Iterator<Item = u8>) -> Result<(), ProgramError> {
+            require_eq!(values.next(), Some(1), ProgramError::InvalidArgument);
+            Ok(())
+        }
+        let mut values = [1, 2].into_iter();
+        assert_eq!(check(&mut values), Ok(()));
+        assert_eq!(check(&mut values), Err(ProgramError::InvalidArgument));
+        assert_eq!(values.next(), None);
+    }
+
+    #[test]
+    fn test_require_neq() {
+        fn check(left: &str, right: &str) -> Result<(), ProgramError> {
+            require_neq!(left, right, TestError::Unlucky);
+            Ok(())
+        }
+        assert_eq!(check("a", "b"), Ok(()));
+        assert_eq!(check("a", "a"), Err(ProgramError::Custom(7)));
+    }
+
+    #[test]
+    fn test_require_keys_eq() {
+        fn check(left: &Address, right: &Address) -> Result<(), ProgramError> {
+            require_keys_eq!(left, right, ProgramError::IncorrectProgramId);
+            Ok(())
+        }
+        let key = Address::new_from_array([3; 32]);
+        assert_eq!(check(&key, &Address::new_from_array([3; 32])), Ok(()));
+        // a difference in any of the words the chunked comparison reads
+        for i in 0..32 {
+            let mut bytes = [3; 32];
+            bytes[i] = 0;
+            assert_eq!(
+                check(&key, &Address::new_from_array(bytes)),
+                Err(ProgramError::IncorrectProgramId)
+            );
+        }
+    }
+
+    #[test]
+    fn test_err() {
+        fn fail() -> Result<(), ProgramError> {
+            err!(TestError::Unlucky)
+        }
+        assert_eq!(fail(), Err(ProgramError::Custom(7)));
+
+        let result: Result<(), ProgramError> = err!(ProgramError::Immutable);
+        assert_eq!(result, Err(ProgramError::Immutable));
+    }
+}
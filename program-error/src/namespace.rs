@@ -0,0 +1,278 @@
+//! Disjoint ranges of custom error codes.
+
+use {crate::ProgramError, core::fmt};
+
+/// A range of [`ProgramError::Custom`] codes reserved for the errors of one
+/// module of a program.
+///
+/// A namespace starting at `base` holds the codes `base` to
+/// `base + u16::MAX`, so that modules can number their errors from zero
+/// without colliding, as long as their namespaces are disjoint. See
+/// [`validate_namespaces`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct CustomErrorNamespace {
+    base: u32,
+}
+
+/// Create the namespace of the custom error codes starting at `base`.
+///
+/// # Panics
+///
+/// Panics if the namespace does not fit in a `u32`, i.e. if `base` is
+/// greater than `u32::MAX - u16::MAX`. In a `const` item, this is a compile
+/// error.
+pub const fn namespace(base: u32) -> CustomErrorNamespace {
+    assert!(
+        base.checked_add(u16::MAX as u32).is_some(),
+        "custom error namespace overflows u32"
+    );
+    CustomErrorNamespace { base }
+}
+
+impl CustomErrorNamespace {
+    /// The first code of the namespace.
+    pub const fn base(&self) -> u32 {
+        self.base
+    }
+
+    /// The custom error code at `offset` in the namespace.
+    pub const fn code(&self, offset: u16) -> u32 {
+        // cannot overflow, checked by `namespace`
+        self.base + offset as u32
+    }
+
+    /// The offset of `code` in the namespace, if the namespace holds it.
+    pub const fn offset_of(&self, code: u32) -> Option<u16> {
+        match code.checked_sub(self.base) {
+            Some(offset) if offset <= u16::MAX as u32 => Some(offset as u16),
+            _ => None,
+        }
+    }
+
+    /// Returns true if the two namespaces share a code.
+    pub const fn overlaps(&self, other: &CustomErrorNamespace) -> bool {
+        self.base.abs_diff(other.base) <= u16::MAX as u32
+    }
+}
+
+/// An error type with its codes in a [`CustomErrorNamespace`].
+///
+/// # Examples
+///
+/// A program combining a vault module and an oracle module, each numbering
+/// its errors from zero:
+///
+/// ```
+/// use solana_program_error::{
+///     namespace, validate_namespaces, CustomErrorNamespace, NamespacedError, ProgramError,
+/// };
+///
+/// mod vault {
+///     use solana_program_error::{namespace, CustomErrorNamespace, NamespacedError};
+///
+///     pub enum VaultError {
+///         Locked,
+///         Empty,
+///     }
+///
+///     impl NamespacedError for VaultError {
+///         const NAMESPACE: CustomErrorNamespace = namespace(0);
+///         fn offset(&self) -> u16 {
+///             match self {
+///                 Self::Locked => 0,
+///                 Self::Empty => 1,
+///             }
+///         }
+///     }
+/// }
+///
+/// mod oracle {
+///     use solana_program_error::{namespace, CustomErrorNamespace, NamespacedError};
+///
+///     pub enum OracleError {
+///         Stale,
+///     }
+///
+///     impl NamespacedError for OracleError {
+///         const NAMESPACE: CustomErrorNamespace = namespace(0x1_0000);
+///         fn offset(&self) -> u16 {
+///             0
+///         }
+///     }
+/// }
+///
+/// const NAMESPACES: &[(CustomErrorNamespace, &str)] = &[
+///     (vault::VaultError::NAMESPACE, "vault"),
+///     (oracle::OracleError::NAMESPACE, "oracle"),
+/// ];
+/// const _: () = assert!(validate_namespaces(NAMESPACES).is_ok());
+///
+/// let error = vault::VaultError::Empty.to_program_error();
+/// assert_eq!(error, ProgramError::Custom(1));
+/// assert_eq!(error.decompose_custom(NAMESPACES), Some((0, 1)));
+///
+/// let error = oracle::OracleError::Stale.to_program_error();
+/// assert_eq!(error, ProgramError::Custom(0x1_0000));
+/// assert_eq!(error.decompose_custom(NAMESPACES), Some((1, 0)));
+/// ```
+pub trait NamespacedError {
+    /// The namespace holding the codes of this error type.
+    const NAMESPACE: CustomErrorNamespace;
+
+    /// The offset of this error in [`Self::NAMESPACE`].
+    fn offset(&self) -> u16;
+
+    /// Convert this error into a [`ProgramError::Custom`] error.
+    fn to_program_error(&self) -> ProgramError {
+        ProgramError::Custom(Self::NAMESPACE.code(self.offset()))
+    }
+}
+
+/// Error returned by [`validate_namespaces`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OverlappingNamespaces {
+    /// Index of the first overlapping namespace.
+    pub first: usize,
+    /// Index of the second overlapping namespace.
+    pub second: usize,
+}
+
+impl core::error::Error for OverlappingNamespaces {}
+
+impl fmt::Display for OverlappingNamespaces {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "custom error namespaces {} and {} overlap",
+            self.first, self.second
+        )
+    }
+}
+
+/// Check that no two of `namespaces` share a code.
+///
+/// Returns the indexes of the first pair of overlapping namespaces found.
+/// This is a `const fn`, so that a program can check its namespaces at
+/// compile time.
+pub const fn validate_namespaces(
+    namespaces: &[(CustomErrorNamespace, &str)],
+) -> Result<(), OverlappingNamespaces> {
+    let mut first = 0;
+    while first < namespaces.len() {
+        let mut second = first + 1;
+        while second < namespaces.len() {
+            if namespaces[first].0.overlaps(&namespaces[second].0) {
+                return Err(OverlappingNamespaces { first, second });
+            }
+            second += 1;
+        }
+        first += 1;
+    }
+    Ok(())
+}
+
+impl ProgramError {
+    /// Split a [`ProgramError::Custom`] code into the index of the first of
+    /// `namespaces` holding it and its offset in that namespace.
+    ///
+    /// Returns `None` for other errors and for codes outside of all the
+    /// namespaces.
+    pub fn decompose_custom(
+        &self,
+        namespaces: &[(CustomErrorNamespace, &str)],
+    ) -> Option<(usize, u16)> {
+        let Self::Custom(code) = self else {
+            return None;
+        };
+        let code = *code;
+        namespaces
+            .iter()
+            .enumerate()
+            .find_map(|(index, (namespace, _))| Some((index, namespace.offset_of(code)?)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_boundary_offsets() {
+        let ns = namespace(100);
+        assert_eq!(ns.code(0), 100);
+        assert_eq!(ns.code(u16::MAX), 100 + u16::MAX as u32);
+        assert_eq!(ns.offset_of(99), None);
+        assert_eq!(ns.offset_of(100), Some(0));
+        assert_eq!(ns.offset_of(100 + u16::MAX as u32), Some(u16::MAX));
+        assert_eq!(ns.offset_of(101 + u16::MAX as u32), None);
+
+        let last = namespace(u32::MAX - u16::MAX as u32);
+        assert_eq!(last.code(u16::MAX), u32::MAX);
+        assert_eq!(last.offset_of(u32::MAX), Some(u16::MAX));
+        assert_eq!(namespace(0).offset_of(0), Some(0));
+    }
+
+    #[test]
+    #[should_panic(expected = "custom error namespace overflows u32")]
+    fn test_namespace_overflow() {
+        namespace(u32::MAX - u16::MAX as u32 + 1);
+    }
+
+    #[test]
+    fn test_validate_namespaces() {
+        assert_eq!(validate_namespaces(&[]), Ok(()));
+        assert_eq!(
+            validate_namespaces(&[
+                (namespace(0), "a"),
+                (namespace(0x1_0000), "b"),
+                (namespace(0x2_0000), "c"),
+            ]),
+            Ok(())
+        );
+        // namespaces sharing a single code
+        assert_eq!(
+            validate_namespaces(&[
+                (namespace(0x2_0000), "a"),
+                (namespace(0), "b"),
+                (namespace(0xffff), "c"),
+            ]),
+            Err(OverlappingNamespaces {
+                first: 1,
+                second: 2
+            })
+        );
+        assert_eq!(
+            validate_namespaces(&[(namespace(7), "a"), (namespace(7), "b")]),
+            Err(OverlappingNamespaces {
+                first: 0,
+                second: 1
+            })
+        );
+    }
+
+    #[test]
+    fn test_decompose_custom() {
+        let namespaces = [(namespace(6000), "anchor"), (namespace(0x10_0000), "vault")];
+        assert_eq!(
+            ProgramError::Custom(6000).decompose_custom(&namespaces),
+            Some((0, 0))
+        );
+        assert_eq!(
+            ProgramError::Custom(6000 + u16::MAX as u32).decompose_custom(&namespaces),
+            Some((0, u16::MAX))
+        );
+        assert_eq!(
+            ProgramError::Custom(0x10_0002).decompose_custom(&namespaces),
+            Some((1, 2))
+        );
+        assert_eq!(
+            ProgramError::Custom(5999).decompose_custom(&namespaces),
+            None
+        );
+        assert_eq!(
+            ProgramError::InvalidArgument.decompose_custom(&namespaces),
+            None
+        );
+        assert_eq!(ProgramError::Custom(0).decompose_custom(&[]), None);
+    }
+}
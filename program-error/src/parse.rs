@@ -0,0 +1,207 @@
+//! Parsing of [`ProgramError`] from its `Display` output.
+
+use {
+    crate::ProgramError,
+    core::{
+        fmt::{self, Write},
+        str::FromStr,
+    },
+};
+
+/// The `Display` prefix of [`ProgramError::Custom`], followed by the code in
+/// hex.
+const CUSTOM_PREFIX: &str = "Custom program error: ";
+
+/// The variants of [`ProgramError`] other than [`ProgramError::Custom`].
+const VARIANTS: [ProgramError; 25] = [
+    ProgramError::InvalidArgument,
+    ProgramError::InvalidInstructionData,
+    ProgramError::InvalidAccountData,
+    ProgramError::AccountDataTooSmall,
+    ProgramError::InsufficientFunds,
+    ProgramError::IncorrectProgramId,
+    ProgramError::MissingRequiredSignature,
+    ProgramError::AccountAlreadyInitialized,
+    ProgramError::UninitializedAccount,
+    ProgramError::NotEnoughAccountKeys,
+    ProgramError::AccountBorrowFailed,
+    ProgramError::MaxSeedLengthExceeded,
+    ProgramError::InvalidSeeds,
+    ProgramError::BorshIoError,
+    ProgramError::AccountNotRentExempt,
+    ProgramError::UnsupportedSysvar,
+    ProgramError::IllegalOwner,
+    ProgramError::MaxAccountsDataAllocationsExceeded,
+    ProgramError::InvalidRealloc,
+    ProgramError::MaxInstructionTraceLengthExceeded,
+    ProgramError::BuiltinProgramsMustConsumeComputeUnits,
+    ProgramError::InvalidAccountOwner,
+    ProgramError::ArithmeticOverflow,
+    ProgramError::Immutable,
+    ProgramError::IncorrectAuthority,
+];
+
+/// The string is not the `Display` output of a [`ProgramError`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ParseProgramErrorError;
+
+impl core::error::Error for ParseProgramErrorError {}
+
+impl fmt::Display for ParseProgramErrorError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("unrecognized program error")
+    }
+}
+
+/// Parses the `Display` output of a [`ProgramError`], e.g. from program logs.
+///
+/// The hex code of [`ProgramError::Custom`] may use either case, as may its
+/// `0x` prefix.
+///
+/// # Examples
+///
+/// ```
+/// use solana_program_error::ProgramError;
+///
+/// assert_eq!(
+///     "Custom program error: 0x1F".parse(),
+///     Ok(ProgramError::Custom(0x1f))
+/// );
+/// let error = ProgramError::InvalidSeeds;
+/// assert_eq!(error.to_string().parse(), Ok(error));
+/// ```
+impl FromStr for ProgramError {
+    type Err = ParseProgramErrorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(code) = s.strip_prefix(CUSTOM_PREFIX) {
+            return parse_hex_code(code)
+                .map(ProgramError::Custom)
+                .ok_or(ParseProgramErrorError);
+        }
+        VARIANTS
+            .into_iter()
+            .find(|error| displays_as(error, s))
+            .ok_or(ParseProgramErrorError)
+    }
+}
+
+impl TryFrom<&str> for ProgramError {
+    type Error = ParseProgramErrorError;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+/// Parses the `{:#x}` formatting of a `u32`, with a `0x` or `0X` prefix.
+fn parse_hex_code(s: &str) -> Option<u32> {
+    let digits = s.strip_prefix("0x").or_else(|| s.strip_prefix("0X"))?;
+    // `from_str_radix` accepts a leading `+`
+    if !digits.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+        return None;
+    }
+    u32::from_str_radix(digits, 16).ok()
+}
+
+/// Returns true if `value` is displayed as `s`, without allocating.
+fn displays_as(value: &impl fmt::Display, s: &str) -> bool {
+    /// Consumes the expected string as the output is written, failing on
+    /// the first mismatch.
+    struct Matcher<'a>(&'a str);
+
+    impl Write for Matcher<'_> {
+        fn write_str(&mut self, s: &str) -> fmt::Result {
+            self.0 = self.0.strip_prefix(s).ok_or(fmt::Error)?;
+            Ok(())
+        }
+    }
+
+    let mut matcher = Matcher(s);
+    write!(matcher, "{value}").is_ok() && matcher.0.is_empty()
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use {super::*, std::string::ToString};
+
+    #[test]
+    fn test_round_trip() {
+        for error in VARIANTS {
+            // fails to compile when a variant is added, as a reminder to add
+            // it to `VARIANTS`
+            match error {
+                ProgramError::Custom(_)
+                | ProgramError::InvalidArgument
+                | ProgramError::InvalidInstructionData
+                | ProgramError::InvalidAccountData
+                | ProgramError::AccountDataTooSmall
+                | ProgramError::InsufficientFunds
+                | ProgramError::IncorrectProgramId
+                | ProgramError::MissingRequiredSignature
+                | ProgramError::AccountAlreadyInitialized
+                | ProgramError::UninitializedAccount
+                | ProgramError::NotEnoughAccountKeys
+                | ProgramError::AccountBorrowFailed
+                | ProgramError::MaxSeedLengthExceeded
+                | ProgramError::InvalidSeeds
+                | ProgramError::BorshIoError
+                | ProgramError::AccountNotRentExempt
+                | ProgramError::UnsupportedSysvar
+                | ProgramError::IllegalOwner
+                | ProgramError::MaxAccountsDataAllocationsExceeded
+                | ProgramError::InvalidRealloc
+                | ProgramError::MaxInstructionTraceLengthExceeded
+                | ProgramError::BuiltinProgramsMustConsumeComputeUnits
+                | ProgramError::InvalidAccountOwner
+                | ProgramError::ArithmeticOverflow
+                | ProgramError::Immutable
+                | ProgramError::IncorrectAuthority => {}
+            }
+            assert_eq!(error.to_string().parse(), Ok(error.clone()));
+            assert_eq!(
+                ProgramError::try_from(error.to_string().as_str()),
+                Ok(error)
+            );
+        }
+        for code in [0, 1, 0xab, 0x1000_0000, u32::MAX] {
+            let error = ProgramError::Custom(code);
+            assert_eq!(error.to_string().parse(), Ok(error));
+        }
+    }
+
+    #[test]
+    fn test_custom_case() {
+        for s in [
+            "Custom program error: 0xabcdef",
+            "Custom program error: 0xABCDEF",
+            "Custom program error: 0XAbCdEf",
+            "Custom program error: 0x00abcdef",
+        ] {
+            assert_eq!(s.parse(), Ok(ProgramError::Custom(0xabcdef)));
+        }
+    }
+
+    #[test]
+    fn test_unrecognized() {
+        for s in [
+            "",
+            "Custom program error: ",
+            "Custom program error: 0x",
+            "Custom program error: 0x+1",
+            "Custom program error: 0x-1",
+            "Custom program error: 1",
+            "Custom program error: 0x100000000",
+            "Custom program error: 0x1 ",
+            "custom program error: 0x1",
+            "Unsupported sysvar ",
+            "Unsupported",
+            "unsupported sysvar",
+            "UnsupportedSysvar",
+        ] {
+            assert_eq!(s.parse::<ProgramError>(), Err(ParseProgramErrorError));
+        }
+    }
+}
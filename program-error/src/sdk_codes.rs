@@ -0,0 +1,81 @@
+//! Custom error codes of the errors of SDK crates.
+//!
+//! Errors of SDK crates that have no exact [`ProgramError`] builtin are
+//! converted into [`ProgramError::Custom`] codes in [`NAMESPACE`], the last
+//! namespace of the custom range. Programs numbering their own errors from
+//! zero never reach it. Each error type has a block of `0x100` offsets, and
+//! the codes are stable: a code is never reused for another error.
+//!
+//! | Offsets         | Error type              |
+//! |-----------------|-------------------------|
+//! | `0x000..0x100`  | `Secp256k1RecoverError` |
+//! | `0x100..0x200`  | `ParseAddressError`     |
+//!
+//! [`CODES`] names every code, so that clients can decode them without
+//! depending on the crates defining the errors.
+
+use crate::{namespace, CustomErrorNamespace, ProgramError};
+
+/// The namespace of the custom codes of SDK errors, from `0xffff0000` to
+/// `u32::MAX`.
+pub const NAMESPACE: CustomErrorNamespace = namespace(u32::MAX - u16::MAX as u32);
+
+/// `Secp256k1RecoverError::InvalidHash`.
+pub const SECP256K1_RECOVER_INVALID_HASH: u32 = NAMESPACE.code(0x000);
+/// `Secp256k1RecoverError::InvalidRecoveryId`.
+pub const SECP256K1_RECOVER_INVALID_RECOVERY_ID: u32 = NAMESPACE.code(0x001);
+/// `Secp256k1RecoverError::InvalidSignature`.
+pub const SECP256K1_RECOVER_INVALID_SIGNATURE: u32 = NAMESPACE.code(0x002);
+
+/// `ParseAddressError::WrongSize`.
+pub const PARSE_ADDRESS_WRONG_SIZE: u32 = NAMESPACE.code(0x100);
+/// `ParseAddressError::Invalid`.
+pub const PARSE_ADDRESS_INVALID: u32 = NAMESPACE.code(0x101);
+
+/// Every code of [`NAMESPACE`] in use, with the error it stands for.
+pub const CODES: &[(u32, &str)] = &[
+    (
+        SECP256K1_RECOVER_INVALID_HASH,
+        "Secp256k1RecoverError::InvalidHash",
+    ),
+    (
+        SECP256K1_RECOVER_INVALID_RECOVERY_ID,
+        "Secp256k1RecoverError::InvalidRecoveryId",
+    ),
+    (
+        SECP256K1_RECOVER_INVALID_SIGNATURE,
+        "Secp256k1RecoverError::InvalidSignature",
+    ),
+    (PARSE_ADDRESS_WRONG_SIZE, "ParseAddressError::WrongSize"),
+    (PARSE_ADDRESS_INVALID, "ParseAddressError::Invalid"),
+];
+
+/// Returns the name of the SDK error converted into `error`, if any.
+pub fn decode(error: &ProgramError) -> Option<&'static str> {
+    let ProgramError::Custom(code) = error else {
+        return None;
+    };
+    CODES
+        .iter()
+        .find_map(|(known, name)| (known == code).then_some(*name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_codes() {
+        assert_eq!(NAMESPACE.base(), 0xffff_0000);
+        assert_eq!(SECP256K1_RECOVER_INVALID_HASH, 0xffff_0000);
+        assert_eq!(PARSE_ADDRESS_INVALID, 0xffff_0101);
+        for (index, (code, name)) in CODES.iter().enumerate() {
+            assert!(NAMESPACE.offset_of(*code).is_some());
+            assert!(CODES[..index].iter().all(|(other, _)| other != code));
+            assert_eq!(decode(&ProgramError::Custom(*code)), Some(*name));
+        }
+        assert_eq!(decode(&ProgramError::Custom(0xffff_0003)), None);
+        assert_eq!(decode(&ProgramError::Custom(0)), None);
+        assert_eq!(decode(&ProgramError::InvalidArgument), None);
+    }
+}
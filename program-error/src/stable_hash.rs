@@ -0,0 +1,99 @@
+//! Stable hashing of [`ProgramError`], to bucket errors in analytics.
+
+use crate::ProgramError;
+
+/// Mixed into the hashed code, so that program error hashes differ from the
+/// hashes of other error types with the same code.
+const DOMAIN: u64 = 0x7072_6f67_6572_7221; // "progerr!"
+
+impl ProgramError {
+    /// A 64-bit fingerprint of the error, stable across versions.
+    ///
+    /// The hash is computed from the numeric code of the error, the
+    /// conversion to `u64`, which includes the code of
+    /// [`ProgramError::Custom`] errors. It does not depend on the `Debug` or
+    /// serde representations, and never changes for existing variants.
+    /// Distinct errors have distinct hashes.
+    pub fn stable_hash(&self) -> u64 {
+        mix(u64::from(self.clone()) ^ DOMAIN)
+    }
+}
+
+/// The splitmix64 finalizer, a bijection on `u64`.
+const fn mix(mut x: u64) -> u64 {
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    x ^ (x >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use {super::*, std::vec::Vec};
+
+    #[test]
+    fn test_stable_hash_golden() {
+        for (error, hash) in [
+            (ProgramError::Custom(0), 0x61c9_2956_42f1_6032),
+            (ProgramError::Custom(1), 0x218e_08c9_7a0e_d164),
+            (ProgramError::Custom(u32::MAX), 0x3753_52ce_b800_09a2),
+            (ProgramError::InvalidArgument, 0xc5ae_aaed_9225_29b9),
+            (ProgramError::InvalidInstructionData, 0xf4f7_0ee9_7587_6639),
+            (ProgramError::InvalidAccountData, 0xe273_a9b3_aa27_4516),
+            (ProgramError::AccountDataTooSmall, 0x193f_f6b7_4431_11ef),
+            (ProgramError::InsufficientFunds, 0xd0c4_3b4e_b6d9_4b4d),
+            (ProgramError::IncorrectProgramId, 0xf156_0dde_6f1d_3b45),
+            (
+                ProgramError::MissingRequiredSignature,
+                0x1802_4f51_1a8b_39d6,
+            ),
+            (
+                ProgramError::AccountAlreadyInitialized,
+                0xe381_c155_ed6d_ef8b,
+            ),
+            (ProgramError::UninitializedAccount, 0x83b5_c979_6659_eccd),
+            (ProgramError::NotEnoughAccountKeys, 0x8894_2d19_c9c7_d0a6),
+            (ProgramError::AccountBorrowFailed, 0xf0af_5ba2_1b1a_ec69),
+            (ProgramError::MaxSeedLengthExceeded, 0xbfef_680f_b9e3_074d),
+            (ProgramError::InvalidSeeds, 0x7d48_ef10_cd40_4bef),
+            (ProgramError::BorshIoError, 0xcbf8_98d7_efd0_37a5),
+            (ProgramError::AccountNotRentExempt, 0x20f9_5c17_4cf2_0ee3),
+            (ProgramError::UnsupportedSysvar, 0x707d_4301_778c_7eff),
+            (ProgramError::IllegalOwner, 0x2e0c_622b_8ef2_e2f9),
+            (
+                ProgramError::MaxAccountsDataAllocationsExceeded,
+                0x0022_f4b5_0859_c41f,
+            ),
+            (ProgramError::InvalidRealloc, 0xe865_58f8_4ca8_f01c),
+            (
+                ProgramError::MaxInstructionTraceLengthExceeded,
+                0x2cf0_f084_90c3_beb7,
+            ),
+            (
+                ProgramError::BuiltinProgramsMustConsumeComputeUnits,
+                0x4a34_500b_8046_29b6,
+            ),
+            (ProgramError::InvalidAccountOwner, 0x565c_7b90_59f1_d2d3),
+            (ProgramError::ArithmeticOverflow, 0x6900_8ffd_1b76_8ed2),
+            (ProgramError::Immutable, 0x67e0_664f_b4aa_1335),
+            (ProgramError::IncorrectAuthority, 0x98fc_3114_fb49_2861),
+        ] {
+            assert_eq!(error.stable_hash(), hash, "{error:?}");
+        }
+    }
+
+    #[test]
+    fn test_stable_hash_collisions() {
+        let mut hashes = (1..=26)
+            .map(|code| ProgramError::from(code << crate::BUILTIN_BIT_SHIFT))
+            .chain((1..4096).map(ProgramError::Custom))
+            .chain((12..32).map(|shift| ProgramError::Custom(1 << shift)))
+            .map(|error| error.stable_hash())
+            .collect::<Vec<_>>();
+        let len = hashes.len();
+        hashes.sort_unstable();
+        hashes.dedup();
+        assert_eq!(hashes.len(), len);
+    }
+}
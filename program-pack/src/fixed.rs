@@ -0,0 +1,312 @@
+//! The [`FixedPack`] serialization trait, for account state with a fixed
+//! layout.
+//!
+//! Unlike [`Pack`](crate::Pack), implementations (de)serialize from and to
+//! arrays of the packed length, so they do not check lengths or slice the
+//! data themselves. The `*_at` helpers read and write fields at offsets of
+//! the packed representation:
+//!
+//! ```
+//! use {
+//!     solana_address::Address,
+//!     solana_program_error::ProgramError,
+//!     solana_program_pack::{
+//!         read_address_at, read_u64_le_at, write_address_at, write_u64_le_at, FixedPack, Pack,
+//!     },
+//! };
+//!
+//! #[derive(Debug, PartialEq)]
+//! struct Vault {
+//!     owner: Address,
+//!     amount: u64,
+//! }
+//!
+//! impl FixedPack for Vault {
+//!     type Packed = [u8; 40];
+//!
+//!     fn pack_into(&self, dst: &mut [u8; 40]) {
+//!         write_address_at(dst, 0, &self.owner);
+//!         write_u64_le_at(dst, 32, self.amount);
+//!     }
+//!
+//!     fn unpack_from(src: &[u8; 40]) -> Result<Self, ProgramError> {
+//!         Ok(Self {
+//!             owner: read_address_at(src, 0),
+//!             amount: read_u64_le_at(src, 32),
+//!         })
+//!     }
+//! }
+//!
+//! let vault = Vault { owner: Address::new_from_array([1; 32]), amount: 42 };
+//! let mut data = [0; 40];
+//! vault.pack_to_slice(&mut data)?;
+//! assert_eq!(<Vault as FixedPack>::unpack_from_slice(&data)?, vault);
+//! // the `Pack` API is implemented too
+//! assert_eq!(Vault::unpack_unchecked(&data)?, vault);
+//! # Ok::<(), ProgramError>(())
+//! ```
+
+use {
+    crate::{Pack, Sealed},
+    solana_address::Address,
+    solana_program_error::ProgramError,
+};
+
+/// A byte array, `[u8; N]`, holding a packed representation.
+pub trait PackedArray: private::Sealed {
+    /// The length of the array.
+    const LEN: usize;
+
+    #[doc(hidden)]
+    fn from_slice(src: &[u8]) -> Option<&Self>;
+    #[doc(hidden)]
+    fn from_slice_mut(dst: &mut [u8]) -> Option<&mut Self>;
+}
+
+impl<const N: usize> PackedArray for [u8; N] {
+    const LEN: usize = N;
+
+    fn from_slice(src: &[u8]) -> Option<&Self> {
+        src.try_into().ok()
+    }
+
+    fn from_slice_mut(dst: &mut [u8]) -> Option<&mut Self> {
+        dst.try_into().ok()
+    }
+}
+
+mod private {
+    pub trait Sealed {}
+
+    impl<const N: usize> Sealed for [u8; N] {}
+}
+
+/// Safely and efficiently (de)serialize account state with a fixed layout.
+///
+/// Every `FixedPack` type also implements [`Pack`], so it can be used where
+/// the older trait is expected.
+pub trait FixedPack: Sized {
+    /// The packed representation, `[u8; LEN]`.
+    type Packed: PackedArray;
+
+    /// The length, in bytes, of the packed representation.
+    ///
+    /// This is the length of [`Self::Packed`], and is not meant to be
+    /// overridden.
+    const LEN: usize = <Self::Packed as PackedArray>::LEN;
+
+    /// Packs `self` into `dst`.
+    fn pack_into(&self, dst: &mut Self::Packed);
+
+    /// Unpacks a value from `src`.
+    fn unpack_from(src: &Self::Packed) -> Result<Self, ProgramError>;
+
+    /// Packs `self` into `dst`, which must be exactly [`Self::LEN`] bytes
+    /// long.
+    fn pack_to_slice(&self, dst: &mut [u8]) -> Result<(), ProgramError> {
+        let dst = Self::Packed::from_slice_mut(dst).ok_or(ProgramError::InvalidAccountData)?;
+        self.pack_into(dst);
+        Ok(())
+    }
+
+    /// Unpacks a value from `src`, which must be exactly [`Self::LEN`] bytes
+    /// long.
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = Self::Packed::from_slice(src).ok_or(ProgramError::InvalidAccountData)?;
+        Self::unpack_from(src)
+    }
+}
+
+impl<T: FixedPack> Sealed for T {}
+
+impl<T: FixedPack> Pack for T {
+    const LEN: usize = <T as FixedPack>::LEN;
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        FixedPack::pack_to_slice(self, dst).expect("destination should be LEN bytes long");
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        <T as FixedPack>::unpack_from_slice(src)
+    }
+}
+
+/// Reads the little-endian `u64` at `offset` of `src`.
+///
+/// # Panics
+///
+/// Panics if `src` is shorter than `offset + 8` bytes.
+#[inline]
+pub fn read_u64_le_at(src: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(read_array_at(src, offset))
+}
+
+/// Writes `value` at `offset` of `dst`, in little endian.
+///
+/// # Panics
+///
+/// Panics if `dst` is shorter than `offset + 8` bytes.
+#[inline]
+pub fn write_u64_le_at(dst: &mut [u8], offset: usize, value: u64) {
+    write_array_at(dst, offset, &value.to_le_bytes());
+}
+
+/// Reads the address at `offset` of `src`.
+///
+/// # Panics
+///
+/// Panics if `src` is shorter than `offset + 32` bytes.
+#[inline]
+pub fn read_address_at(src: &[u8], offset: usize) -> Address {
+    Address::new_from_array(read_array_at(src, offset))
+}
+
+/// Writes `address` at `offset` of `dst`.
+///
+/// # Panics
+///
+/// Panics if `dst` is shorter than `offset + 32` bytes.
+#[inline]
+pub fn write_address_at(dst: &mut [u8], offset: usize, address: &Address) {
+    write_array_at(dst, offset, address.as_array());
+}
+
+#[inline]
+fn read_array_at<const N: usize>(src: &[u8], offset: usize) -> [u8; N] {
+    let mut array = [0; N];
+    array.copy_from_slice(&src[offset..offset.saturating_add(N)]);
+    array
+}
+
+#[inline]
+fn write_array_at<const N: usize>(dst: &mut [u8], offset: usize, array: &[u8; N]) {
+    dst[offset..offset.saturating_add(N)].copy_from_slice(array);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    struct Vault {
+        owner: Address,
+        amount: u64,
+    }
+
+    impl FixedPack for Vault {
+        type Packed = [u8; 40];
+
+        fn pack_into(&self, dst: &mut [u8; 40]) {
+            write_address_at(dst, 0, &self.owner);
+            write_u64_le_at(dst, 32, self.amount);
+        }
+
+        fn unpack_from(src: &[u8; 40]) -> Result<Self, ProgramError> {
+            let amount = read_u64_le_at(src, 32);
+            if amount == u64::MAX {
+                return Err(ProgramError::InvalidAccountData);
+            }
+            Ok(Self {
+                owner: read_address_at(src, 0),
+                amount,
+            })
+        }
+    }
+
+    fn vault() -> Vault {
+        Vault {
+            owner: Address::new_from_array([7; 32]),
+            amount: 0x0102_0304_0506_0708,
+        }
+    }
+
+    fn packed() -> [u8; 40] {
+        let mut packed = [7; 40];
+        packed[32..].copy_from_slice(&[8, 7, 6, 5, 4, 3, 2, 1]);
+        packed
+    }
+
+    #[test]
+    fn test_fixed_pack() {
+        assert_eq!(<Vault as FixedPack>::LEN, 40);
+
+        let mut data = [0; 40];
+        vault().pack_into(&mut data);
+        assert_eq!(data, packed());
+        assert_eq!(Vault::unpack_from(&data), Ok(vault()));
+
+        let mut data = [0; 40];
+        assert_eq!(vault().pack_to_slice(&mut data), Ok(()));
+        assert_eq!(data, packed());
+        assert_eq!(<Vault as FixedPack>::unpack_from_slice(&data), Ok(vault()));
+
+        // errors of the implementation are returned
+        let mut data = packed();
+        data[32..].fill(0xff);
+        assert_eq!(
+            Vault::unpack_from(&data),
+            Err(ProgramError::InvalidAccountData)
+        );
+    }
+
+    #[test]
+    fn test_exact_length() {
+        for len in [0, 39, 41] {
+            let mut data = [0; 41];
+            assert_eq!(
+                vault().pack_to_slice(&mut data[..len]),
+                Err(ProgramError::InvalidAccountData)
+            );
+            assert!(data.iter().all(|&byte| byte == 0));
+            assert_eq!(
+                <Vault as FixedPack>::unpack_from_slice(&[7; 41][..len]),
+                Err(ProgramError::InvalidAccountData)
+            );
+        }
+    }
+
+    #[test]
+    fn test_pack_adapter() {
+        assert_eq!(<Vault as Pack>::LEN, 40);
+        assert_eq!(Vault::get_packed_len(), 40);
+
+        let mut data = [0; 40];
+        assert_eq!(Vault::pack(vault(), &mut data), Ok(()));
+        assert_eq!(data, packed());
+        assert_eq!(Vault::unpack_unchecked(&data), Ok(vault()));
+        assert_eq!(<Vault as Pack>::unpack_from_slice(&data), Ok(vault()));
+
+        let mut data = [0; 40];
+        vault().pack_into_slice(&mut data);
+        assert_eq!(data, packed());
+
+        assert_eq!(
+            Vault::pack(vault(), &mut [0; 39]),
+            Err(ProgramError::InvalidAccountData)
+        );
+        assert_eq!(
+            Vault::unpack_unchecked(&packed()[..39]),
+            Err(ProgramError::InvalidAccountData)
+        );
+    }
+
+    #[test]
+    fn test_helpers() {
+        let mut data = [0; 48];
+        write_u64_le_at(&mut data, 1, u64::MAX - 1);
+        assert_eq!(read_u64_le_at(&data, 1), u64::MAX - 1);
+        assert_eq!(data[0], 0);
+        assert_eq!(data[9], 0);
+
+        let address = Address::new_from_array([9; 32]);
+        write_address_at(&mut data, 16, &address);
+        assert_eq!(read_address_at(&data, 16), address);
+        assert_eq!(data[15], 0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_helpers_out_of_bounds() {
+        read_u64_le_at(&[0; 8], 1);
+    }
+}
@@ -4,10 +4,18 @@
 //! recommended for new code since it does not define a language-independent
 //! serialization format.
 //!
+//! New code with a fixed layout can implement the [`FixedPack`] trait
+//! instead, which implements [`Pack`] as well.
+//!
 //! [spl]: https://github.com/solana-labs/solana-program-library
 #![no_std]
 #![cfg_attr(docsrs, feature(doc_cfg))]
 
+mod fixed;
+
+pub use crate::fixed::{
+    read_address_at, read_u64_le_at, write_address_at, write_u64_le_at, FixedPack, PackedArray,
+};
 use solana_program_error::ProgramError;
 
 /// Check if a program account state is initialized
@@ -37,7 +37,9 @@ pub struct Rent {
 }
 
 /// Maximum permitted size of account data (10 MiB).
-const MAX_PERMITTED_DATA_LENGTH: u64 = 10 * 1024 * 1024;
+///
+/// Same as `solana_system_interface::MAX_PERMITTED_DATA_LENGTH`.
+pub const MAX_PERMITTED_DATA_LENGTH: u64 = 10 * 1024 * 1024;
 
 /// Default rental rate in lamports/byte.
 ///
@@ -78,16 +80,73 @@ pub const ACCOUNT_STORAGE_OVERHEAD: u64 = 128;
 
 impl Default for Rent {
     fn default() -> Self {
-        #[allow(deprecated)]
-        Self {
-            lamports_per_byte: DEFAULT_LAMPORTS_PER_BYTE,
-            exemption_threshold: SIMD0194_EXEMPTION_THRESHOLD,
-            burn_percent: DEFAULT_BURN_PERCENT,
-        }
+        Self::DEFAULT
     }
 }
 
+/// Compile-time evaluation of [`Rent::minimum_balance_default`].
+struct MinimumBalance<const LEN: usize>;
+
+impl<const LEN: usize> MinimumBalance<LEN> {
+    const VALUE: u64 = Rent::minimum_balance_default(LEN);
+}
+
 impl Rent {
+    /// The default rent parameters, equal to [`Rent::default()`].
+    #[allow(deprecated)]
+    pub const DEFAULT: Rent = Rent {
+        lamports_per_byte: DEFAULT_LAMPORTS_PER_BYTE,
+        exemption_threshold: SIMD0194_EXEMPTION_THRESHOLD,
+        burn_percent: DEFAULT_BURN_PERCENT,
+    };
+
+    /// Calculates the minimum balance for rent exemption with the
+    /// [default](Rent::DEFAULT) rent parameters.
+    ///
+    /// Returns the same value as `Rent::default().minimum_balance(data_len)`.
+    /// The default exemption threshold is `1.0`, so the floating-point
+    /// multiplication of the general formula is the identity and the
+    /// computation reduces to integer arithmetic:
+    ///
+    /// `(ACCOUNT_STORAGE_OVERHEAD + data_len) * DEFAULT_LAMPORTS_PER_BYTE`
+    ///
+    /// which is exact since the result stays well below 2^53 for every
+    /// permitted data length.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `data_len` exceeds the maximum permitted data length. When
+    /// used in a const context this is a compile-time error.
+    pub const fn minimum_balance_default(data_len: usize) -> u64 {
+        if data_len as u64 > MAX_PERMITTED_DATA_LENGTH {
+            panic!("Maximum permitted data length exceeded");
+        }
+        (ACCOUNT_STORAGE_OVERHEAD + data_len as u64) * DEFAULT_LAMPORTS_PER_BYTE
+    }
+
+    /// Calculates the minimum balance for rent exemption of an account with
+    /// `LEN` bytes of data, with the [default](Rent::DEFAULT) rent parameters.
+    ///
+    /// The value is always computed at compile time, and a `LEN` above the
+    /// maximum permitted data length fails to compile.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use solana_rent::Rent;
+    ///
+    /// const TOKEN_ACCOUNT_MINIMUM_BALANCE: u64 = Rent::minimum_balance_const::<165>();
+    ///
+    /// assert_eq!(
+    ///     TOKEN_ACCOUNT_MINIMUM_BALANCE,
+    ///     Rent::default().minimum_balance(165)
+    /// );
+    /// ```
+    #[inline(always)]
+    pub const fn minimum_balance_const<const LEN: usize>() -> u64 {
+        MinimumBalance::<LEN>::VALUE
+    }
+
     /// Calculates the minimum balance for rent exemption.
     ///
     /// This method avoids floating-point operations when the `exemption_threshold`
@@ -242,6 +301,50 @@ mod tests {
         assert_eq!(2f64.to_le_bytes(), CURRENT_EXEMPTION_THRESHOLD);
     }
 
+    #[test]
+    fn test_default_const() {
+        assert_eq!(Rent::DEFAULT, Rent::default());
+    }
+
+    #[test]
+    fn test_minimum_balance_const() {
+        const MINT: u64 = Rent::minimum_balance_const::<82>();
+        const TOKEN_ACCOUNT: u64 = Rent::minimum_balance_const::<165>();
+        const MAX: u64 = Rent::minimum_balance_const::<{ MAX_PERMITTED_DATA_LENGTH as usize }>();
+        let rent = Rent::default();
+        assert_eq!(MINT, rent.minimum_balance(82));
+        assert_eq!(TOKEN_ACCOUNT, rent.minimum_balance(165));
+        assert_eq!(
+            MAX,
+            rent.minimum_balance(MAX_PERMITTED_DATA_LENGTH as usize)
+        );
+        assert_eq!(Rent::minimum_balance_const::<0>(), rent.minimum_balance(0));
+    }
+
+    #[test]
+    fn test_minimum_balance_default_matches_float() {
+        // the full floating-point formula with the default threshold
+        #[allow(deprecated)]
+        let float_calc = |data_len: usize| {
+            (((ACCOUNT_STORAGE_OVERHEAD + data_len as u64) * Rent::DEFAULT.lamports_per_byte)
+                as f64
+                * f64::from_le_bytes(Rent::DEFAULT.exemption_threshold)) as u64
+        };
+        let max = MAX_PERMITTED_DATA_LENGTH as usize;
+        for data_len in (0..=max).step_by(4093).chain([1, 82, 165, max - 1, max]) {
+            assert_eq!(
+                Rent::minimum_balance_default(data_len),
+                float_calc(data_len)
+            );
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Maximum permitted data length exceeded")]
+    fn test_minimum_balance_default_too_large() {
+        Rent::minimum_balance_default(MAX_PERMITTED_DATA_LENGTH as usize + 1);
+    }
+
     proptest! {
         #[test]
         fn test_minimum_balance(bytes in 0usize..=MAX_PERMITTED_DATA_LENGTH as usize) {
@@ -254,6 +357,7 @@ mod tests {
             };
             let default_calc = default_rent.minimum_balance(bytes);
             assert_eq!(default_calc, previous_rent.minimum_balance(bytes));
+            assert_eq!(default_calc, Rent::minimum_balance_default(bytes));
 
             // check that the calculation gives the same result using floats
             #[allow(deprecated)]
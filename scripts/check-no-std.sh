@@ -21,6 +21,7 @@ no_std_crates=(
   -p solana-epoch-stake
   -p solana-fee-calculator
   -p solana-hash
+  -p solana-hex
   -p solana-instruction-view
   -p solana-keccak-hasher
   -p solana-msg
@@ -34,8 +35,10 @@ no_std_crates=(
   -p solana-rent
   -p solana-sanitize
   -p solana-sdk-ids
+  -p solana-secp256k1-recover
   -p solana-sha256-hasher
   -p solana-signature
+  -p solana-signer-store
   -p solana-sysvar-id
 )
 
@@ -44,6 +47,7 @@ no_std_crates=(
 no_std_alloc_crates=(
   -p solana-account-info
   -p solana-instruction
+  -p solana-short-vec
 )
 
 # Use the upstream BPF target, which doesn't support std, to make sure that our
@@ -53,7 +57,7 @@ target="bpfel-unknown-none"
 # These features require alloc
 exclude_features_no_alloc="alloc,borsh,curve25519,serde,slice-cpi"
 # These features never work on upstream BPF
-exclude_features="atomic,bincode,default,dev-context-only-utils,frozen-abi,rand,std,verify"
+exclude_features="atomic,bincode,default,dev-context-only-utils,frozen-abi,profiling,rand,std,verify"
 
 ./cargo nightly hack check \
   -Zbuild-std=core \
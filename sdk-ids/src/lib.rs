@@ -1,5 +1,14 @@
 #![no_std]
 #![cfg_attr(docsrs, feature(doc_cfg))]
+#[cfg(feature = "std")]
+extern crate std;
+
+pub mod table;
+
+#[doc(hidden)]
+pub mod __private {
+    pub use solana_address::Address;
+}
 
 pub mod address_lookup_table {
     solana_address::declare_id!("AddressLookupTab1e1111111111111111111111111");
@@ -120,3 +129,93 @@ pub mod zk_token_proof_program {
 pub mod zk_elgamal_proof_program {
     solana_address::declare_id!("ZkE1Gama1Proof11111111111111111111111111111");
 }
+
+/// Names of the programs and sysvars whose ids are defined in this crate.
+pub const KNOWN_PROGRAM_IDS: table::ProgramIdTable = {
+    const ENTRIES: &[(solana_address::Address, &str)] = &table::sort_entries([
+        (address_lookup_table::ID, "Address Lookup Table Program"),
+        (bpf_loader::ID, "BPF Loader"),
+        (bpf_loader_deprecated::ID, "BPF Loader (Deprecated)"),
+        (bpf_loader_upgradeable::ID, "BPF Upgradeable Loader"),
+        (compute_budget::ID, "Compute Budget Program"),
+        (config::ID, "Config Program"),
+        (ed25519_program::ID, "Ed25519 SigVerify Precompile"),
+        (feature::ID, "Feature Program"),
+        (incinerator::ID, "Incinerator"),
+        (loader_v4::ID, "Loader v4"),
+        (native_loader::ID, "Native Loader"),
+        (secp256k1_program::ID, "Secp256k1 SigVerify Precompile"),
+        (secp256r1_program::ID, "Secp256r1 SigVerify Precompile"),
+        (stake::config::ID, "Stake Config"),
+        (stake::ID, "Stake Program"),
+        (system_program::ID, "System Program"),
+        (vote::ID, "Vote Program"),
+        (sysvar::ID, "Sysvar Owner"),
+        (sysvar::clock::ID, "Sysvar: Clock"),
+        (sysvar::epoch_rewards::ID, "Sysvar: Epoch Rewards"),
+        (sysvar::epoch_schedule::ID, "Sysvar: Epoch Schedule"),
+        (sysvar::fees::ID, "Sysvar: Fees"),
+        (sysvar::instructions::ID, "Sysvar: Instructions"),
+        (sysvar::last_restart_slot::ID, "Sysvar: Last Restart Slot"),
+        (sysvar::recent_blockhashes::ID, "Sysvar: Recent Blockhashes"),
+        (sysvar::rent::ID, "Sysvar: Rent"),
+        (sysvar::rewards::ID, "Sysvar: Rewards"),
+        (sysvar::slot_hashes::ID, "Sysvar: Slot Hashes"),
+        (sysvar::slot_history::ID, "Sysvar: Slot History"),
+        (sysvar::stake_history::ID, "Sysvar: Stake History"),
+        (zk_token_proof_program::ID, "ZK Token Proof Program"),
+        (zk_elgamal_proof_program::ID, "ZK ElGamal Proof Program"),
+    ]);
+    table::ProgramIdTable::new(ENTRIES)
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_known_program_ids() {
+        let ids = [
+            address_lookup_table::ID,
+            bpf_loader::ID,
+            bpf_loader_deprecated::ID,
+            bpf_loader_upgradeable::ID,
+            compute_budget::ID,
+            config::ID,
+            ed25519_program::ID,
+            feature::ID,
+            incinerator::ID,
+            loader_v4::ID,
+            native_loader::ID,
+            secp256k1_program::ID,
+            secp256r1_program::ID,
+            stake::config::ID,
+            stake::ID,
+            system_program::ID,
+            vote::ID,
+            sysvar::ID,
+            sysvar::clock::ID,
+            sysvar::epoch_rewards::ID,
+            sysvar::epoch_schedule::ID,
+            sysvar::fees::ID,
+            sysvar::instructions::ID,
+            sysvar::last_restart_slot::ID,
+            sysvar::recent_blockhashes::ID,
+            sysvar::rent::ID,
+            sysvar::rewards::ID,
+            sysvar::slot_hashes::ID,
+            sysvar::slot_history::ID,
+            sysvar::stake_history::ID,
+            zk_token_proof_program::ID,
+            zk_elgamal_proof_program::ID,
+        ];
+        assert_eq!(KNOWN_PROGRAM_IDS.len(), ids.len());
+        for id in ids {
+            assert!(KNOWN_PROGRAM_IDS.get(&id).is_some());
+        }
+        assert_eq!(
+            KNOWN_PROGRAM_IDS.get(&sysvar::clock::ID),
+            Some("Sysvar: Clock")
+        );
+    }
+}
@@ -0,0 +1,339 @@
+//! Tables mapping program ids to human-readable names.
+
+#[cfg(feature = "std")]
+use std::{fmt, vec::Vec};
+use {core::cmp::Ordering, solana_address::Address};
+
+/// A static table of program ids and their names, sorted by address.
+///
+/// Tables are usually built with [`program_id_table!`](crate::program_id_table),
+/// which sorts the entries and rejects duplicates at compile time.
+#[derive(Debug, Clone, Copy)]
+pub struct ProgramIdTable {
+    entries: &'static [(Address, &'static str)],
+}
+
+impl ProgramIdTable {
+    /// Create a table from entries sorted by address.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the entries are not sorted or contain the same address
+    /// twice. When used in a const context this is a compile-time error.
+    pub const fn new(entries: &'static [(Address, &'static str)]) -> Self {
+        let mut i = 1;
+        while i < entries.len() {
+            match cmp_address(&entries[i - 1].0, &entries[i].0) {
+                Ordering::Less => {}
+                Ordering::Equal => panic!("duplicate address in program id table"),
+                Ordering::Greater => panic!("program id table is not sorted by address"),
+            }
+            i += 1;
+        }
+        Self { entries }
+    }
+
+    pub const fn entries(&self) -> &'static [(Address, &'static str)] {
+        self.entries
+    }
+
+    pub const fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Look up the name registered for `address`.
+    pub const fn get(&self, address: &Address) -> Option<&'static str> {
+        let mut low = 0;
+        let mut high = self.entries.len();
+        while low < high {
+            let mid = low + (high - low) / 2;
+            match cmp_address(&self.entries[mid].0, address) {
+                Ordering::Less => low = mid + 1,
+                Ordering::Greater => high = mid,
+                Ordering::Equal => return Some(self.entries[mid].1),
+            }
+        }
+        None
+    }
+
+    /// Merge two tables, keeping entries sorted by address.
+    ///
+    /// Addresses present in both tables with the same name are kept once;
+    /// with different names they are reported as a [`CollisionError`].
+    #[cfg(feature = "std")]
+    pub fn merge(&self, other: &ProgramIdTable) -> Result<MergedTable, CollisionError> {
+        let mut entries = Vec::with_capacity(self.len().saturating_add(other.len()));
+        let mut left = self.entries.iter().peekable();
+        let mut right = other.entries.iter().peekable();
+        loop {
+            let next = match (left.peek().copied(), right.peek().copied()) {
+                (None, None) => break,
+                (Some(_), None) => left.next(),
+                (None, Some(_)) => right.next(),
+                (Some((l, l_name)), Some((r, r_name))) => match l.cmp(r) {
+                    Ordering::Less => left.next(),
+                    Ordering::Greater => right.next(),
+                    Ordering::Equal if l_name == r_name => {
+                        right.next();
+                        left.next()
+                    }
+                    Ordering::Equal => {
+                        return Err(CollisionError {
+                            address: l.clone(),
+                            name: l_name,
+                            other_name: r_name,
+                        })
+                    }
+                },
+            };
+            entries.extend(next.cloned());
+        }
+        Ok(MergedTable { entries })
+    }
+}
+
+/// Sort program id table entries by address at compile time.
+///
+/// # Panics
+///
+/// Panics if the same address appears twice.
+#[doc(hidden)]
+pub const fn sort_entries<const N: usize>(
+    mut entries: [(Address, &'static str); N],
+) -> [(Address, &'static str); N] {
+    // insertion sort, since `sort` isn't available in const fns
+    let mut i = 1;
+    while i < N {
+        let mut j = i;
+        while j > 0 {
+            match cmp_address(&entries[j - 1].0, &entries[j].0) {
+                Ordering::Less => break,
+                Ordering::Equal => panic!("duplicate address in program_id_table!"),
+                Ordering::Greater => {
+                    let tmp = (copy_address(&entries[j].0), entries[j].1);
+                    entries[j] = (copy_address(&entries[j - 1].0), entries[j - 1].1);
+                    entries[j - 1] = tmp;
+                }
+            }
+            j -= 1;
+        }
+        i += 1;
+    }
+    entries
+}
+
+const fn copy_address(address: &Address) -> Address {
+    Address::new_from_array(*address.as_array())
+}
+
+const fn cmp_address(a: &Address, b: &Address) -> Ordering {
+    let (a, b) = (a.as_array(), b.as_array());
+    let mut i = 0;
+    while i < a.len() {
+        if a[i] < b[i] {
+            return Ordering::Less;
+        }
+        if a[i] > b[i] {
+            return Ordering::Greater;
+        }
+        i += 1;
+    }
+    Ordering::Equal
+}
+
+/// Build a [`ProgramIdTable`] from `"name" => "base58 address"` pairs.
+///
+/// The entries are sorted at compile time, and an address that appears twice
+/// is a compile-time error:
+///
+/// ```compile_fail
+/// const TABLE: solana_sdk_ids::table::ProgramIdTable = solana_sdk_ids::program_id_table! {
+///     "System Program" => "11111111111111111111111111111111",
+///     "Also System Program" => "11111111111111111111111111111111",
+/// };
+/// ```
+///
+/// # Examples
+///
+/// ```
+/// use solana_sdk_ids::{program_id_table, system_program, table::ProgramIdTable, vote};
+///
+/// const TABLE: ProgramIdTable = program_id_table! {
+///     "Vote Program" => "Vote111111111111111111111111111111111111111",
+///     "System Program" => "11111111111111111111111111111111",
+/// };
+///
+/// assert_eq!(TABLE.get(&system_program::ID), Some("System Program"));
+/// assert_eq!(TABLE.get(&vote::ID), Some("Vote Program"));
+/// ```
+#[macro_export]
+macro_rules! program_id_table {
+    ($($name:literal => $address:literal),* $(,)?) => {{
+        const ENTRIES: &[($crate::__private::Address, &str)] = &$crate::table::sort_entries([
+            $(($crate::__private::Address::from_str_const($address), $name)),*
+        ]);
+        $crate::table::ProgramIdTable::new(ENTRIES)
+    }};
+}
+
+/// The result of [`ProgramIdTable::merge`].
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MergedTable {
+    entries: Vec<(Address, &'static str)>,
+}
+
+#[cfg(feature = "std")]
+impl MergedTable {
+    pub fn entries(&self) -> &[(Address, &'static str)] {
+        &self.entries
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Look up the name registered for `address`.
+    pub fn get(&self, address: &Address) -> Option<&'static str> {
+        self.entries
+            .binary_search_by(|(entry, _)| entry.cmp(address))
+            .ok()
+            .map(|index| self.entries[index].1)
+    }
+}
+
+/// An address registered with different names in two merged tables.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CollisionError {
+    pub address: Address,
+    pub name: &'static str,
+    pub other_name: &'static str,
+}
+
+#[cfg(feature = "std")]
+impl core::error::Error for CollisionError {}
+
+#[cfg(feature = "std")]
+impl fmt::Display for CollisionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "program id {} is registered as both \"{}\" and \"{}\"",
+            self.address, self.name, self.other_name
+        )
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use {super::*, crate::KNOWN_PROGRAM_IDS, std::string::ToString};
+
+    const APP_TABLE: ProgramIdTable = program_id_table! {
+        "Token Program" => "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA",
+        "Memo Program" => "MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr",
+        "Associated Token Program" => "ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL",
+    };
+
+    #[test]
+    fn test_const_table_sorted_lookup() {
+        let entries = APP_TABLE.entries();
+        assert_eq!(entries.len(), 3);
+        assert!(entries.windows(2).all(|w| w[0].0 < w[1].0));
+
+        for (address, name) in [
+            (
+                "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA",
+                "Token Program",
+            ),
+            (
+                "MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr",
+                "Memo Program",
+            ),
+            (
+                "ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL",
+                "Associated Token Program",
+            ),
+        ] {
+            assert_eq!(APP_TABLE.get(&Address::from_str_const(address)), Some(name));
+        }
+        assert_eq!(APP_TABLE.get(&Address::default()), None);
+        assert_eq!(APP_TABLE.get(&Address::new_from_array([0xff; 32])), None);
+
+        const EMPTY: ProgramIdTable = program_id_table! {};
+        assert!(EMPTY.is_empty());
+        assert_eq!(EMPTY.get(&Address::default()), None);
+    }
+
+    #[test]
+    fn test_const_lookup() {
+        const SYSTEM_NAME: Option<&str> = KNOWN_PROGRAM_IDS.get(&crate::system_program::ID);
+        assert_eq!(SYSTEM_NAME, Some("System Program"));
+    }
+
+    #[test]
+    #[should_panic(expected = "not sorted")]
+    fn test_new_unsorted() {
+        static ENTRIES: [(Address, &str); 2] = [
+            (Address::new_from_array([2; 32]), "b"),
+            (Address::new_from_array([1; 32]), "a"),
+        ];
+        ProgramIdTable::new(&ENTRIES);
+    }
+
+    #[test]
+    #[should_panic(expected = "duplicate address")]
+    fn test_sort_entries_duplicate() {
+        sort_entries([
+            (Address::new_from_array([1; 32]), "a"),
+            (Address::new_from_array([1; 32]), "b"),
+        ]);
+    }
+
+    #[test]
+    fn test_merge_ordering() {
+        let merged = KNOWN_PROGRAM_IDS.merge(&APP_TABLE).unwrap();
+        assert_eq!(merged.len(), KNOWN_PROGRAM_IDS.len() + APP_TABLE.len());
+        assert!(merged.entries().windows(2).all(|w| w[0].0 < w[1].0));
+        for (address, name) in KNOWN_PROGRAM_IDS
+            .entries()
+            .iter()
+            .chain(APP_TABLE.entries())
+        {
+            assert_eq!(merged.get(address), Some(*name));
+        }
+        assert_eq!(merged, APP_TABLE.merge(&KNOWN_PROGRAM_IDS).unwrap());
+
+        // identical entries are deduplicated
+        let merged = KNOWN_PROGRAM_IDS.merge(&KNOWN_PROGRAM_IDS).unwrap();
+        assert_eq!(merged.entries(), KNOWN_PROGRAM_IDS.entries());
+    }
+
+    #[test]
+    fn test_merge_collision() {
+        const COLLIDING: ProgramIdTable = program_id_table! {
+            "My Program" => "11111111111111111111111111111111",
+        };
+        assert_eq!(
+            KNOWN_PROGRAM_IDS.merge(&COLLIDING),
+            Err(CollisionError {
+                address: crate::system_program::ID,
+                name: "System Program",
+                other_name: "My Program",
+            })
+        );
+        assert_eq!(
+            COLLIDING.merge(&KNOWN_PROGRAM_IDS).unwrap_err().to_string(),
+            "program id 11111111111111111111111111111111 is registered as both \"My Program\" \
+             and \"System Program\""
+        );
+    }
+}
@@ -268,3 +268,164 @@ pub fn derive_clone_zeroed(input: proc_macro::TokenStream) -> proc_macro::TokenS
     }
     .into()
 }
+
+/// Derives the conversions of a program error enum to and from
+/// [`ProgramError::Custom`] codes.
+///
+/// The enum must be fieldless and `#[repr(u32)]`. The derive implements:
+///
+/// - `From<E> for ProgramError`, as `ProgramError::Custom` with the
+///   discriminant of the variant;
+/// - `TryFrom<u32> for E`, returning the code back as the error if no variant
+///   has it as discriminant;
+/// - `ToStr for E`, with the message of each variant;
+/// - `E::COUNT`, the number of variants.
+///
+/// The message of a variant is given by an `#[error_msg("...")]` attribute,
+/// or else by its doc comment, with the lines joined by spaces. Variants with
+/// neither use their name.
+///
+/// The generated code refers to `::solana_program_error`, which must be a
+/// dependency of the crate using the derive.
+///
+/// [`ProgramError::Custom`]: https://docs.rs/solana-program-error/latest/solana_program_error/enum.ProgramError.html#variant.Custom
+///
+/// # Example
+///
+/// ```
+/// use {solana_program_error::ProgramError, solana_sdk_macro::ProgramErrorCode};
+///
+/// #[derive(Clone, Copy, Debug, PartialEq, Eq, ProgramErrorCode)]
+/// #[repr(u32)]
+/// enum MyError {
+///     /// Message for A
+///     A,
+///     #[error_msg("Some other message for B")]
+///     B = 10,
+/// }
+///
+/// let program_error = ProgramError::from(MyError::B);
+/// assert_eq!(program_error, ProgramError::Custom(10));
+/// assert_eq!("Some other message for B", program_error.to_str::<MyError>());
+/// assert_eq!(MyError::try_from(0), Ok(MyError::A));
+/// assert_eq!(MyError::try_from(1), Err(1));
+/// assert_eq!(MyError::COUNT, 2);
+/// ```
+#[proc_macro_derive(ProgramErrorCode, attributes(error_msg))]
+pub fn derive_program_error_code(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as syn::DeriveInput);
+    program_error_code(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn program_error_code(input: &syn::DeriveInput) -> Result<proc_macro2::TokenStream> {
+    let syn::Data::Enum(data) = &input.data else {
+        return Err(syn::Error::new_spanned(
+            &input.ident,
+            "ProgramErrorCode can only be derived for enums",
+        ));
+    };
+    if !has_repr_u32(&input.attrs)? {
+        return Err(syn::Error::new_spanned(
+            &input.ident,
+            "ProgramErrorCode requires the enum to be `#[repr(u32)]`",
+        ));
+    }
+    if !input.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &input.generics,
+            "ProgramErrorCode cannot be derived for generic enums",
+        ));
+    }
+
+    let mut variants = Vec::with_capacity(data.variants.len());
+    let mut messages = Vec::with_capacity(data.variants.len());
+    for variant in &data.variants {
+        if !matches!(variant.fields, syn::Fields::Unit) {
+            return Err(syn::Error::new_spanned(
+                variant,
+                "ProgramErrorCode variants cannot have fields",
+            ));
+        }
+        variants.push(&variant.ident);
+        messages.push(error_message(variant)?);
+    }
+
+    let name = &input.ident;
+    let count = variants.len();
+    Ok(quote! {
+        impl ::core::convert::From<#name> for ::solana_program_error::ProgramError {
+            fn from(error: #name) -> Self {
+                ::solana_program_error::ProgramError::Custom(error as u32)
+            }
+        }
+
+        impl ::core::convert::TryFrom<u32> for #name {
+            type Error = u32;
+
+            fn try_from(code: u32) -> ::core::result::Result<Self, u32> {
+                #(
+                    if code == #name::#variants as u32 {
+                        return ::core::result::Result::Ok(#name::#variants);
+                    }
+                )*
+                ::core::result::Result::Err(code)
+            }
+        }
+
+        impl ::solana_program_error::ToStr for #name {
+            fn to_str(&self) -> &'static str {
+                match *self {
+                    #(#name::#variants => #messages,)*
+                }
+            }
+        }
+
+        impl #name {
+            /// The number of variants.
+            pub const COUNT: usize = #count;
+        }
+    })
+}
+
+fn has_repr_u32(attrs: &[syn::Attribute]) -> Result<bool> {
+    let mut found = false;
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("repr")) {
+        attr.parse_nested_meta(|meta| {
+            found |= meta.path.is_ident("u32");
+            Ok(())
+        })?;
+    }
+    Ok(found)
+}
+
+fn error_message(variant: &syn::Variant) -> Result<LitStr> {
+    let mut docs = Vec::new();
+    for attr in &variant.attrs {
+        if attr.path().is_ident("error_msg") {
+            return attr.parse_args::<LitStr>();
+        }
+        if let syn::Meta::NameValue(syn::MetaNameValue {
+            path,
+            value:
+                Expr::Lit(syn::ExprLit {
+                    lit: syn::Lit::Str(doc),
+                    ..
+                }),
+            ..
+        }) = &attr.meta
+        {
+            let line = doc.value();
+            if path.is_ident("doc") && !line.trim().is_empty() {
+                docs.push(line.trim().to_string());
+            }
+        }
+    }
+    let message = if docs.is_empty() {
+        variant.ident.to_string()
+    } else {
+        docs.join(" ")
+    };
+    Ok(LitStr::new(&message, variant.ident.span()))
+}
@@ -0,0 +1,52 @@
+use {solana_program_error::ProgramError, solana_sdk_macro::ProgramErrorCode};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ProgramErrorCode)]
+#[repr(u32)]
+enum MyError {
+    /// Message for A
+    A,
+    /// Message for B,
+    /// on two lines
+    B,
+    #[error_msg("Message for C")]
+    /// Ignored doc comment
+    C = 7,
+    D,
+    E = 42,
+}
+
+#[test]
+fn test_program_error_code() {
+    assert_eq!(MyError::COUNT, 5);
+    for (error, code, message) in [
+        (MyError::A, 0, "Message for A"),
+        (MyError::B, 1, "Message for B, on two lines"),
+        (MyError::C, 7, "Message for C"),
+        (MyError::D, 8, "D"),
+        (MyError::E, 42, "E"),
+    ] {
+        let program_error = ProgramError::from(error);
+        assert_eq!(program_error, ProgramError::Custom(code));
+        assert_eq!(MyError::try_from(code), Ok(error));
+        assert_eq!(program_error.to_str::<MyError>(), message);
+    }
+
+    for code in [2, 6, 9, 41, 43, u32::MAX] {
+        assert_eq!(MyError::try_from(code), Err(code));
+        assert_eq!(
+            ProgramError::Custom(code).to_str::<MyError>(),
+            "Error: Unknown"
+        );
+    }
+    assert_eq!(
+        ProgramError::InvalidArgument.to_str::<MyError>(),
+        "Error: InvalidArgument"
+    );
+}
+
+#[test]
+fn test_ui() {
+    let t = trybuild::TestCases::new();
+    t.pass("tests/ui/pass_*.rs");
+    t.compile_fail("tests/ui/fail_*.rs");
+}
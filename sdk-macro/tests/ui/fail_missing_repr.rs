@@ -0,0 +1,8 @@
+use solana_sdk_macro::ProgramErrorCode;
+
+#[derive(ProgramErrorCode)]
+enum MyError {
+    A,
+}
+
+fn main() {}
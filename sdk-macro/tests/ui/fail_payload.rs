@@ -0,0 +1,10 @@
+use solana_sdk_macro::ProgramErrorCode;
+
+#[derive(ProgramErrorCode)]
+#[repr(u32)]
+enum MyError {
+    A,
+    B(u32),
+}
+
+fn main() {}
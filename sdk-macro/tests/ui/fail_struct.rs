@@ -0,0 +1,9 @@
+use solana_sdk_macro::ProgramErrorCode;
+
+#[derive(ProgramErrorCode)]
+#[repr(C)]
+struct MyError {
+    code: u32,
+}
+
+fn main() {}
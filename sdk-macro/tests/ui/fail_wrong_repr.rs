@@ -0,0 +1,9 @@
+use solana_sdk_macro::ProgramErrorCode;
+
+#[derive(ProgramErrorCode)]
+#[repr(u8)]
+enum MyError {
+    A,
+}
+
+fn main() {}
@@ -0,0 +1,21 @@
+use {
+    solana_program_error::{ProgramError, ToStr},
+    solana_sdk_macro::ProgramErrorCode,
+};
+
+#[derive(Debug, ProgramErrorCode)]
+#[repr(u32)]
+#[allow(dead_code)]
+pub enum MyError {
+    /// Message for A
+    A = 1,
+    #[error_msg("Message for B")]
+    B,
+}
+
+fn main() {
+    const _: usize = MyError::COUNT;
+    assert!(MyError::try_from(0).is_err());
+    assert_eq!(MyError::B.to_str(), "Message for B");
+    assert_eq!(ProgramError::from(MyError::A), ProgramError::Custom(1));
+}
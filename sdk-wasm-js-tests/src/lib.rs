@@ -3,7 +3,11 @@
 #![cfg(target_arch = "wasm32")]
 #![allow(non_snake_case)]
 pub use solana_sdk_wasm_js::{
-    address::Address, hash::Hash, instruction::Instruction, keypair::Keypair, solana_program_init,
+    address::Address,
+    hash::Hash,
+    instruction::{AccountMeta, Instruction},
+    keypair::Keypair,
+    solana_program_init,
     transaction::Transaction,
 };
 use wasm_bindgen::prelude::*;
@@ -0,0 +1,64 @@
+import { expect } from "chai";
+import { solana_program_init, AccountMeta, Address, Instruction } from "crate";
+solana_program_init();
+
+describe("Instruction", function () {
+  const systemProgramId = new Address("11111111111111111111111111111111");
+  const from = new Address(new Uint8Array(32).fill(1));
+  const to = new Address(new Uint8Array(32).fill(2));
+
+  // SystemInstruction::Transfer { lamports: 42 }
+  function transfer() {
+    const instruction = new Instruction(systemProgramId);
+    instruction.addAccount(AccountMeta.newWritable(from, true));
+    instruction.addAccount(AccountMeta.newWritable(to, false));
+    instruction.setData(new Uint8Array([2, 0, 0, 0, 42, 0, 0, 0, 0, 0, 0, 0]));
+    return instruction;
+  }
+
+  it("getters", () => {
+    const instruction = transfer();
+    expect(instruction.programId().equals(systemProgramId)).to.be.true;
+    expect(Array.from(instruction.data())).to.deep.equal([
+      2, 0, 0, 0, 42, 0, 0, 0, 0, 0, 0, 0,
+    ]);
+    expect(instruction.accountsLength()).to.equal(2);
+
+    const fromMeta = instruction.accountAt(0);
+    expect(fromMeta.address().equals(from)).to.be.true;
+    expect(fromMeta.isSigner()).to.be.true;
+    expect(fromMeta.isWritable()).to.be.true;
+
+    const toMeta = instruction.accountAt(1);
+    expect(toMeta.address().equals(to)).to.be.true;
+    expect(toMeta.isSigner()).to.be.false;
+    expect(toMeta.isWritable()).to.be.true;
+
+    expect(() => instruction.accountAt(2)).to.throw(
+      "Account index out of bounds: 2 >= 2"
+    );
+  });
+
+  it("toJSON", () => {
+    const instruction = transfer();
+    expect(instruction.toJSON()).to.deep.equal({
+      programId: "11111111111111111111111111111111",
+      keys: [
+        {
+          pubkey: from.toString(),
+          isSigner: true,
+          isWritable: true,
+        },
+        {
+          pubkey: to.toString(),
+          isSigner: false,
+          isWritable: true,
+        },
+      ],
+      data: "AgAAACoAAAAAAAAA",
+    });
+    expect(JSON.parse(JSON.stringify(instruction))).to.deep.equal(
+      instruction.toJSON()
+    );
+  });
+});
@@ -1,9 +1,10 @@
 //! Address wrapper
 
 use {
-    crate::display_to_jsvalue,
+    crate::error::ErrorJs,
     js_sys::{Array, Uint8Array},
     solana_address::{ADDRESS_BYTES, MAX_SEEDS, MAX_SEED_LEN},
+    solana_program_error::ProgramError,
     wasm_bindgen::{prelude::wasm_bindgen, JsCast, JsValue},
 };
 
@@ -15,29 +16,38 @@ pub struct Address {
 
 crate::conversion::impl_inner_conversion!(Address, solana_address::Address);
 
-fn js_value_to_seeds_vec(array_of_uint8_arrays: &[JsValue]) -> Result<Vec<Vec<u8>>, JsValue> {
+fn js_value_to_seeds_vec(array_of_uint8_arrays: &[JsValue]) -> Result<Vec<Vec<u8>>, ErrorJs> {
     if array_of_uint8_arrays.len() > MAX_SEEDS {
-        return Err(JsValue::from(std::format!(
-            "Too many seeds: {} > {}",
-            array_of_uint8_arrays.len(),
-            MAX_SEEDS
-        )));
+        return Err(ErrorJs::new(
+            ProgramError::MaxSeedLengthExceeded,
+            std::format!(
+                "Too many seeds: {} > {}",
+                array_of_uint8_arrays.len(),
+                MAX_SEEDS
+            ),
+        ));
     }
 
     array_of_uint8_arrays
         .iter()
         .enumerate()
         .map(|(i, u8_array_js)| {
-            let u8_array = u8_array_js
-                .dyn_ref::<Uint8Array>()
-                .ok_or_else(|| JsValue::from(std::format!("Invalid seed type at index {}", i)))?;
+            let u8_array = u8_array_js.dyn_ref::<Uint8Array>().ok_or_else(|| {
+                ErrorJs::new(
+                    ProgramError::InvalidArgument,
+                    std::format!("Invalid seed type at index {}", i),
+                )
+            })?;
             if u8_array.length() as usize > MAX_SEED_LEN {
-                return Err(JsValue::from(std::format!(
-                    "Seed {} too long: {} > {}",
-                    i,
-                    u8_array.length(),
-                    MAX_SEED_LEN
-                )));
+                return Err(ErrorJs::new(
+                    ProgramError::MaxSeedLengthExceeded,
+                    std::format!(
+                        "Seed {} too long: {} > {}",
+                        i,
+                        u8_array.length(),
+                        MAX_SEED_LEN
+                    ),
+                ));
             }
 
             Ok(u8_array.to_vec())
@@ -52,37 +62,45 @@ impl Address {
     ///
     /// * `value` - optional public key as a base58 encoded string, `Uint8Array`, `[number]`
     #[wasm_bindgen(constructor)]
-    pub fn constructor(value: JsValue) -> Result<Self, JsValue> {
+    pub fn constructor(value: JsValue) -> Result<Self, ErrorJs> {
         if let Some(base58_str) = value.as_string() {
             base58_str
                 .parse::<solana_address::Address>()
                 .map(Into::into)
-                .map_err(display_to_jsvalue)
+                .map_err(Into::into)
         } else if let Some(uint8_array) = value.dyn_ref::<Uint8Array>() {
             if uint8_array.length() as usize != ADDRESS_BYTES {
-                return Err(std::format!(
-                    "Invalid Uint8Array length: expected {}, got {}",
-                    ADDRESS_BYTES,
-                    uint8_array.length()
-                )
-                .into());
+                return Err(ErrorJs::new(
+                    ProgramError::InvalidArgument,
+                    std::format!(
+                        "Invalid Uint8Array length: expected {}, got {}",
+                        ADDRESS_BYTES,
+                        uint8_array.length()
+                    ),
+                ));
             }
             let mut bytes = [0u8; ADDRESS_BYTES];
             uint8_array.copy_to(&mut bytes);
             Ok(solana_address::Address::new_from_array(bytes).into())
         } else if let Some(array) = value.dyn_ref::<Array>() {
             if array.length() as usize != ADDRESS_BYTES {
-                return Err(std::format!(
-                    "Invalid Array length: expected {}, got {}",
-                    ADDRESS_BYTES,
-                    array.length()
-                )
-                .into());
+                return Err(ErrorJs::new(
+                    ProgramError::InvalidArgument,
+                    std::format!(
+                        "Invalid Array length: expected {}, got {}",
+                        ADDRESS_BYTES,
+                        array.length()
+                    ),
+                ));
             }
+            let invalid_argument =
+                |x: JsValue| ErrorJs::new(ProgramError::InvalidArgument, std::format!("{:?}", x));
             let mut bytes = [0u8; ADDRESS_BYTES];
-            let iterator = js_sys::try_iter(&array.values())?.expect("array to be iterable");
+            let iterator = js_sys::try_iter(&array.values())
+                .map_err(invalid_argument)?
+                .expect("array to be iterable");
             for (i, x) in iterator.enumerate() {
-                let x = x?;
+                let x = x.map_err(invalid_argument)?;
 
                 if let Some(n) = x.as_f64() {
                     if n >= 0. && n <= 255. {
@@ -90,13 +108,19 @@ impl Address {
                         continue;
                     }
                 }
-                return Err(std::format!("Invalid array argument: {:?}", x).into());
+                return Err(ErrorJs::new(
+                    ProgramError::InvalidArgument,
+                    std::format!("Invalid array argument: {:?}", x),
+                ));
             }
             Ok(solana_address::Address::new_from_array(bytes).into())
         } else if value.is_undefined() {
             Ok(solana_address::Address::default().into())
         } else {
-            Err("Unsupported argument".into())
+            Err(ErrorJs::new(
+                ProgramError::InvalidArgument,
+                "Unsupported argument",
+            ))
         }
     }
 
@@ -121,17 +145,17 @@ impl Address {
     }
 
     /// Derive an Address from anothern Address, string seed, and a program id
-    pub fn createWithSeed(base: &Self, seed: &str, owner: &Self) -> Result<Self, JsValue> {
+    pub fn createWithSeed(base: &Self, seed: &str, owner: &Self) -> Result<Self, ErrorJs> {
         solana_address::Address::create_with_seed(&base.inner, seed, &owner.inner)
             .map(Into::into)
-            .map_err(display_to_jsvalue)
+            .map_err(Into::into)
     }
 
     /// Derive a program address from seeds and a program id
     pub fn createProgramAddress(
         seeds: std::boxed::Box<[JsValue]>,
         program_id: &Self,
-    ) -> Result<Self, JsValue> {
+    ) -> Result<Self, ErrorJs> {
         let seeds_vec = js_value_to_seeds_vec(&seeds)?;
         let seeds_slice = seeds_vec
             .iter()
@@ -140,7 +164,7 @@ impl Address {
 
         solana_address::Address::create_program_address(seeds_slice.as_slice(), &program_id.inner)
             .map(Into::into)
-            .map_err(display_to_jsvalue)
+            .map_err(Into::into)
     }
 
     /// Find a valid program address
@@ -150,7 +174,7 @@ impl Address {
     pub fn findProgramAddress(
         seeds: std::boxed::Box<[JsValue]>,
         program_id: &Self,
-    ) -> Result<JsValue, JsValue> {
+    ) -> Result<JsValue, ErrorJs> {
         let seeds_vec = js_value_to_seeds_vec(&seeds)?;
         let seeds_slice = seeds_vec
             .iter()
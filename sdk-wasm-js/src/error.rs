@@ -0,0 +1,103 @@
+//! Errors thrown to Javascript
+
+use {
+    solana_address::error::{AddressError, ParseAddressError},
+    solana_instruction::error::InstructionError,
+    solana_program_error::ProgramError,
+    std::fmt,
+    wasm_bindgen::prelude::wasm_bindgen,
+};
+
+/// Error thrown by the wasm bindings
+///
+/// The `name` is the name of the [`ProgramError`] or [`InstructionError`]
+/// variant, and the `code` the numeric value of the program error, which
+/// are stable and can be matched on. The `message` describes the failure.
+#[wasm_bindgen]
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ErrorJs {
+    code: u64,
+    name: std::string::String,
+    message: std::string::String,
+}
+
+impl ErrorJs {
+    /// Create an error for `error`, with a more specific message
+    pub fn new(error: ProgramError, message: impl fmt::Display) -> Self {
+        Self {
+            name: variant_name(&error),
+            code: error.into(),
+            message: message.to_string(),
+        }
+    }
+}
+
+impl From<ProgramError> for ErrorJs {
+    fn from(error: ProgramError) -> Self {
+        let message = error.to_string();
+        Self::new(error, message)
+    }
+}
+
+/// The code is the one of the matching [`ProgramError`], or zero for the
+/// instruction errors without one.
+impl From<InstructionError> for ErrorJs {
+    fn from(error: InstructionError) -> Self {
+        Self {
+            name: variant_name(&error),
+            message: error.to_string(),
+            code: ProgramError::try_from(error).map_or(0, Into::into),
+        }
+    }
+}
+
+impl From<AddressError> for ErrorJs {
+    fn from(error: AddressError) -> Self {
+        ProgramError::from(error).into()
+    }
+}
+
+impl From<ParseAddressError> for ErrorJs {
+    fn from(error: ParseAddressError) -> Self {
+        let message = error.to_string();
+        Self::new(error.into(), message)
+    }
+}
+
+/// Name of the enum variant of `error`, as its `Debug` representation
+/// without the fields
+fn variant_name(error: &impl fmt::Debug) -> std::string::String {
+    let debug = std::format!("{error:?}");
+    match debug.find(['(', ' ', '{']) {
+        Some(end) => debug[..end].into(),
+        None => debug,
+    }
+}
+
+#[allow(non_snake_case)]
+#[wasm_bindgen]
+impl ErrorJs {
+    /// Numeric code of the error
+    #[wasm_bindgen(getter)]
+    pub fn code(&self) -> f64 {
+        // program error codes are below 2^53, and so exact
+        self.code as f64
+    }
+
+    /// Name of the error, such as `"MaxSeedLengthExceeded"`
+    #[wasm_bindgen(getter)]
+    pub fn name(&self) -> std::string::String {
+        self.name.clone()
+    }
+
+    /// Description of the error
+    #[wasm_bindgen(getter)]
+    pub fn message(&self) -> std::string::String {
+        self.message.clone()
+    }
+
+    /// Return the error as `"<name>: <message>"`
+    pub fn toString(&self) -> std::string::String {
+        std::format!("{}: {}", self.name, self.message)
+    }
+}
@@ -4,7 +4,11 @@
 #![allow(non_snake_case)]
 
 use {
-    crate::address::Address, js_sys::Uint8Array, solana_packet::PACKET_DATA_SIZE,
+    crate::{address::Address, error::ErrorJs},
+    base64::{prelude::BASE64_STANDARD, Engine},
+    js_sys::{Array, Object, Reflect, Uint8Array},
+    solana_packet::PACKET_DATA_SIZE,
+    solana_program_error::ProgramError,
     wasm_bindgen::prelude::*,
 };
 
@@ -30,14 +34,16 @@ impl Instruction {
             .into()
     }
 
-    pub fn setData(&mut self, data: Uint8Array) -> Result<(), JsValue> {
+    pub fn setData(&mut self, data: Uint8Array) -> Result<(), ErrorJs> {
         if data.length() as usize > MAX_INSTRUCTION_DATA_LEN {
-            return Err(std::format!(
-                "Instruction data too large: {} > {}",
-                data.length(),
-                MAX_INSTRUCTION_DATA_LEN
-            )
-            .into());
+            return Err(ErrorJs::new(
+                ProgramError::InvalidInstructionData,
+                std::format!(
+                    "Instruction data too large: {} > {}",
+                    data.length(),
+                    MAX_INSTRUCTION_DATA_LEN
+                ),
+            ));
         }
 
         self.inner.data = data.to_vec();
@@ -47,6 +53,64 @@ impl Instruction {
     pub fn addAccount(&mut self, account_meta: AccountMeta) {
         self.inner.accounts.push(account_meta.inner);
     }
+
+    /// Return the program id of the instruction
+    pub fn programId(&self) -> Address {
+        self.inner.program_id.into()
+    }
+
+    /// Return a copy of the instruction data
+    pub fn data(&self) -> Uint8Array {
+        self.inner.data.as_slice().into()
+    }
+
+    /// Return the number of accounts in the instruction
+    pub fn accountsLength(&self) -> usize {
+        self.inner.accounts.len()
+    }
+
+    /// Return the account at the given index
+    pub fn accountAt(&self, index: usize) -> Result<AccountMeta, ErrorJs> {
+        self.inner
+            .accounts
+            .get(index)
+            .cloned()
+            .map(Into::into)
+            .ok_or_else(|| {
+                ErrorJs::new(
+                    ProgramError::NotEnoughAccountKeys,
+                    std::format!(
+                        "Account index out of bounds: {} >= {}",
+                        index,
+                        self.inner.accounts.len()
+                    ),
+                )
+            })
+    }
+
+    /// Return the instruction in the shape web3.js expects:
+    /// `{ programId, keys: [{ pubkey, isSigner, isWritable }], data }`,
+    /// with addresses as base58 strings and data as a base64 string
+    pub fn toJSON(&self) -> Result<JsValue, JsValue> {
+        let keys = Array::new_with_length(self.inner.accounts.len() as u32);
+        for (i, account) in self.inner.accounts.iter().enumerate() {
+            keys.set(i as u32, AccountMeta::from(account.clone()).toJSON()?);
+        }
+
+        let json = Object::new();
+        Reflect::set(
+            &json,
+            &"programId".into(),
+            &self.inner.program_id.to_string().into(),
+        )?;
+        Reflect::set(&json, &"keys".into(), &keys)?;
+        Reflect::set(
+            &json,
+            &"data".into(),
+            &BASE64_STANDARD.encode(&self.inner.data).into(),
+        )?;
+        Ok(json.into())
+    }
 }
 
 #[wasm_bindgen]
@@ -68,4 +132,33 @@ impl AccountMeta {
     pub fn newReadonly(address: Address, is_signer: bool) -> Self {
         solana_instruction::AccountMeta::new_readonly(address.inner, is_signer).into()
     }
+
+    /// Return the address of the account
+    pub fn address(&self) -> Address {
+        self.inner.pubkey.into()
+    }
+
+    /// Return whether the account must sign the transaction
+    pub fn isSigner(&self) -> bool {
+        self.inner.is_signer
+    }
+
+    /// Return whether the account is writable
+    pub fn isWritable(&self) -> bool {
+        self.inner.is_writable
+    }
+
+    /// Return the account meta in the shape web3.js expects:
+    /// `{ pubkey, isSigner, isWritable }`, with the address as a base58 string
+    pub fn toJSON(&self) -> Result<JsValue, JsValue> {
+        let json = Object::new();
+        Reflect::set(
+            &json,
+            &"pubkey".into(),
+            &self.inner.pubkey.to_string().into(),
+        )?;
+        Reflect::set(&json, &"isSigner".into(), &self.inner.is_signer.into())?;
+        Reflect::set(&json, &"isWritable".into(), &self.inner.is_writable.into())?;
+        Ok(json.into())
+    }
 }
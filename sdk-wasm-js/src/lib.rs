@@ -8,6 +8,7 @@ use {
 };
 
 pub mod address;
+pub mod error;
 pub mod hash;
 pub mod instruction;
 pub mod keypair;
@@ -0,0 +1,62 @@
+import { expect } from "chai";
+import {
+  solana_program_init,
+  Address,
+  ErrorJs,
+  Instruction,
+} from "crate";
+solana_program_init();
+
+// TODO: wasm_bindgen doesn't currently support exporting constants
+const MAX_SEED_LEN = 32;
+// `ProgramError` codes are shifted left by 32 bits
+const MAX_SEED_LENGTH_EXCEEDED = 13 * 2 ** 32;
+const INVALID_ARGUMENT = 2 * 2 ** 32;
+const NOT_ENOUGH_ACCOUNT_KEYS = 11 * 2 ** 32;
+
+function catchError(f) {
+  try {
+    f();
+  } catch (error) {
+    return error;
+  }
+  expect.fail("expected an error to be thrown");
+}
+
+describe("ErrorJs", function () {
+  it("seed too long", () => {
+    const programId = new Address("BPFLoader1111111111111111111111111111111111");
+    for (const derive of [
+      Address.createProgramAddress,
+      Address.findProgramAddress,
+    ]) {
+      const error = catchError(() =>
+        derive([Buffer.alloc(MAX_SEED_LEN + 1)], programId)
+      );
+      expect(error).to.be.instanceof(ErrorJs);
+      expect(error.name).to.eq("MaxSeedLengthExceeded");
+      expect(error.code).to.eq(MAX_SEED_LENGTH_EXCEEDED);
+      expect(error.message).to.eq("Seed 0 too long: 33 > 32");
+      expect(error.toString()).to.eq(
+        "MaxSeedLengthExceeded: Seed 0 too long: 33 > 32"
+      );
+    }
+  });
+
+  it("invalid address", () => {
+    const error = catchError(() => new Address(null));
+    expect(error.name).to.eq("InvalidArgument");
+    expect(error.code).to.eq(INVALID_ARGUMENT);
+    expect(error.message).to.eq("Unsupported argument");
+  });
+
+  it("account index out of bounds", () => {
+    const instruction = new Instruction(
+      new Address("11111111111111111111111111111111")
+    );
+    const error = catchError(() => instruction.accountAt(0));
+    expect(error.name).to.eq("NotEnoughAccountKeys");
+    expect(error.code).to.eq(NOT_ENOUGH_ACCOUNT_KEYS);
+    expect(error.message).to.eq("Account index out of bounds: 0 >= 0");
+  });
+});
@@ -1,5 +1,6 @@
 #![cfg_attr(feature = "frozen-abi", feature(min_specialization))]
 #![cfg_attr(docsrs, feature(doc_cfg))]
+#![no_std]
 //! Public key recovery from [secp256k1] ECDSA signatures.
 //!
 //! [secp256k1]: https://en.bitcoin.it/wiki/Secp256k1
@@ -27,7 +28,12 @@
 //! [`ecrecover`]: https://docs.soliditylang.org/en/v0.8.14/units-and-global-variables.html?highlight=ecrecover#mathematical-and-cryptographic-functions
 
 #[cfg(feature = "borsh")]
-use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
+extern crate alloc;
+#[cfg(feature = "borsh")]
+use {
+    alloc::string::ToString,
+    borsh::{BorshDeserialize, BorshSchema, BorshSerialize},
+};
 use {core::convert::TryFrom, thiserror::Error};
 
 #[derive(Debug, Clone, PartialEq, Eq, Error)]
@@ -61,6 +67,27 @@ impl From<Secp256k1RecoverError> for u64 {
     }
 }
 
+/// Each error is converted into its code in
+/// [`solana_program_error::sdk_codes`].
+#[cfg(feature = "error")]
+impl From<Secp256k1RecoverError> for solana_program_error::ProgramError {
+    fn from(error: Secp256k1RecoverError) -> Self {
+        use solana_program_error::sdk_codes::{
+            SECP256K1_RECOVER_INVALID_HASH, SECP256K1_RECOVER_INVALID_RECOVERY_ID,
+            SECP256K1_RECOVER_INVALID_SIGNATURE,
+        };
+        match error {
+            Secp256k1RecoverError::InvalidHash => Self::Custom(SECP256K1_RECOVER_INVALID_HASH),
+            Secp256k1RecoverError::InvalidRecoveryId => {
+                Self::Custom(SECP256K1_RECOVER_INVALID_RECOVERY_ID)
+            }
+            Secp256k1RecoverError::InvalidSignature => {
+                Self::Custom(SECP256K1_RECOVER_INVALID_SIGNATURE)
+            }
+        }
+    }
+}
+
 pub const SECP256K1_SIGNATURE_LENGTH: usize = 64;
 pub const SECP256K1_PUBLIC_KEY_LENGTH: usize = 64;
 
@@ -235,6 +262,9 @@ pub use solana_define_syscall::definitions::sol_secp256k1_recover;
 /// outside of the program's memory space. If the provided slices are too long
 /// then they may be used to "smuggle" uninterpreted data.
 ///
+/// Prefer [`secp256k1_recover_checked`], which validates the lengths of `hash`
+/// and `signature` on all targets before recovering the public key.
+///
 /// # Examples
 ///
 /// This example demonstrates recovering a public key and using it to verify a
@@ -444,3 +474,134 @@ pub fn secp256k1_recover(
         ))
     }
 }
+
+/// Recover the public key from a [secp256k1] ECDSA signature and
+/// cryptographically-hashed message, validating the input lengths first.
+///
+/// [secp256k1]: https://en.bitcoin.it/wiki/Secp256k1
+///
+/// This behaves like [`secp256k1_recover`], except that `hash` must be
+/// exactly 32 bytes, `signature` exactly 64 bytes and `recovery_id` in the
+/// range [0, 3] on all targets, including on-chain where the syscall does not
+/// check the lengths itself. The extra cost is three comparisons.
+///
+/// # Errors
+///
+/// Returns [`Secp256k1RecoverError::InvalidHash`] if `hash` is not 32 bytes,
+/// [`Secp256k1RecoverError::InvalidRecoveryId`] if `recovery_id` is greater
+/// than 3, and [`Secp256k1RecoverError::InvalidSignature`] if `signature` is
+/// not 64 bytes. Otherwise the errors of [`secp256k1_recover`] apply.
+#[inline(always)]
+pub fn secp256k1_recover_checked(
+    hash: &[u8],
+    recovery_id: u8,
+    signature: &[u8],
+) -> Result<Secp256k1Pubkey, Secp256k1RecoverError> {
+    const HASH_SIZE: usize = 32;
+    if hash.len() != HASH_SIZE {
+        return Err(Secp256k1RecoverError::InvalidHash);
+    }
+    if recovery_id > 3 {
+        return Err(Secp256k1RecoverError::InvalidRecoveryId);
+    }
+    if signature.len() != SECP256K1_SIGNATURE_LENGTH {
+        return Err(Secp256k1RecoverError::InvalidSignature);
+    }
+    secp256k1_recover(hash, recovery_id, signature)
+}
+
+#[cfg(all(test, not(any(target_os = "solana", target_arch = "bpf"))))]
+mod tests {
+    extern crate std;
+
+    use super::*;
+
+    fn signed_message() -> ([u8; 32], u8, [u8; 64], Secp256k1Pubkey) {
+        let secret_key = k256::ecdsa::SigningKey::from_slice(&[1; 32]).unwrap();
+        let hash = [7; 32];
+        let (signature, recovery_id) = secret_key.sign_prehash_recoverable(&hash).unwrap();
+        let pubkey = Secp256k1Pubkey::new(
+            &secret_key
+                .verifying_key()
+                .to_encoded_point(false)
+                .as_bytes()[1..65],
+        );
+        (
+            hash,
+            recovery_id.to_byte(),
+            signature.to_bytes().as_slice().try_into().unwrap(),
+            pubkey,
+        )
+    }
+
+    #[test]
+    fn test_secp256k1_recover_checked() {
+        let (hash, recovery_id, signature, pubkey) = signed_message();
+        let recovered = secp256k1_recover_checked(&hash, recovery_id, &signature).unwrap();
+        assert!(recovered == pubkey);
+        assert!(recovered == secp256k1_recover(&hash, recovery_id, &signature).unwrap());
+    }
+
+    #[test]
+    fn test_secp256k1_recover_checked_hash_length() {
+        let (hash, recovery_id, signature, _) = signed_message();
+        let long_hash = [hash.as_slice(), &[0]].concat();
+        for bad_hash in [&[][..], &hash[..31], &long_hash[..]] {
+            assert_eq!(
+                secp256k1_recover_checked(bad_hash, recovery_id, &signature).err(),
+                Some(Secp256k1RecoverError::InvalidHash)
+            );
+        }
+    }
+
+    #[test]
+    fn test_secp256k1_recover_checked_recovery_id() {
+        let (hash, _, signature, _) = signed_message();
+        for recovery_id in [4, 27, u8::MAX] {
+            assert_eq!(
+                secp256k1_recover_checked(&hash, recovery_id, &signature).err(),
+                Some(Secp256k1RecoverError::InvalidRecoveryId)
+            );
+        }
+    }
+
+    #[test]
+    fn test_secp256k1_recover_checked_signature_length() {
+        let (hash, recovery_id, signature, _) = signed_message();
+        // extra trailing bytes must not be smuggled into a valid recovery
+        let long_signature = [signature.as_slice(), &[0]].concat();
+        for bad_signature in [&[][..], &signature[..63], &long_signature[..]] {
+            assert_eq!(
+                secp256k1_recover_checked(&hash, recovery_id, bad_signature).err(),
+                Some(Secp256k1RecoverError::InvalidSignature)
+            );
+        }
+    }
+
+    #[test]
+    fn test_program_error_codes() {
+        use solana_program_error::{sdk_codes, ProgramError};
+
+        for (error, code, name) in [
+            (
+                Secp256k1RecoverError::InvalidHash,
+                0xffff_0000,
+                "Secp256k1RecoverError::InvalidHash",
+            ),
+            (
+                Secp256k1RecoverError::InvalidRecoveryId,
+                0xffff_0001,
+                "Secp256k1RecoverError::InvalidRecoveryId",
+            ),
+            (
+                Secp256k1RecoverError::InvalidSignature,
+                0xffff_0002,
+                "Secp256k1RecoverError::InvalidSignature",
+            ),
+        ] {
+            let program_error = ProgramError::from(error);
+            assert_eq!(program_error, ProgramError::Custom(code));
+            assert_eq!(sdk_codes::decode(&program_error), Some(name));
+        }
+    }
+}
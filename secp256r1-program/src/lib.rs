@@ -10,8 +10,18 @@
 //! low-S values, the same message and key can produce two different valid signatures, potentially breaking
 //! replay protection schemes that rely on signature uniqueness.
 #![cfg_attr(docsrs, feature(doc_cfg))]
-use bytemuck::{Pod, Zeroable};
 pub use solana_sdk_ids::secp256r1_program::{check_id, id, ID};
+use {
+    bytemuck::{Pod, Zeroable},
+    core::fmt,
+    solana_precompile_layout::{LayoutError, SignatureOffsetsLayout},
+};
+
+pub const COMPRESSED_PUBKEY_SERIALIZED_SIZE: usize = 33;
+pub const SIGNATURE_SERIALIZED_SIZE: usize = 64;
+pub const SIGNATURE_OFFSETS_SERIALIZED_SIZE: usize = 14;
+pub const SIGNATURE_OFFSETS_START: usize = 2;
+pub const DATA_START: usize = SIGNATURE_OFFSETS_SERIALIZED_SIZE + SIGNATURE_OFFSETS_START;
 
 #[derive(Default, Debug, Copy, Clone, Zeroable, Pod, Eq, PartialEq)]
 #[repr(C)]
@@ -38,21 +48,84 @@ pub struct Secp256r1SignatureOffsets {
     pub message_instruction_index: u16,
 }
 
+/// Maximum number of signatures the secp256r1 program accepts in a single
+/// instruction.
+pub const MAX_SIGNATURES: usize = 8;
+
+/// The [`SignatureOffsetsLayout`] of secp256r1 instructions, to build and
+/// parse them with the functions of [`solana_precompile_layout`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Secp256r1Layout;
+
+impl SignatureOffsetsLayout for Secp256r1Layout {
+    type Offsets = Secp256r1SignatureOffsets;
+
+    const PUBKEY_SERIALIZED_SIZE: usize = COMPRESSED_PUBKEY_SERIALIZED_SIZE;
+    const SIGNATURE_SERIALIZED_SIZE: usize = SIGNATURE_SERIALIZED_SIZE;
+    const SIGNATURE_OFFSETS_SERIALIZED_SIZE: usize = SIGNATURE_OFFSETS_SERIALIZED_SIZE;
+    const SIGNATURE_OFFSETS_START: usize = SIGNATURE_OFFSETS_START;
+    const MAX_SIGNATURES: usize = MAX_SIGNATURES;
+
+    fn inline_offsets(
+        public_key_offset: u16,
+        signature_offset: u16,
+        message_data_offset: u16,
+        message_data_size: u16,
+    ) -> Secp256r1SignatureOffsets {
+        Secp256r1SignatureOffsets {
+            signature_offset,
+            signature_instruction_index: u16::MAX,
+            public_key_offset,
+            public_key_instruction_index: u16::MAX,
+            message_data_offset,
+            message_data_size,
+            message_instruction_index: u16::MAX,
+        }
+    }
+}
+
+/// Reasons [`new_secp256r1_instruction_with_signatures`] can fail.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Secp256r1InstructionError {
+    /// More than [`MAX_SIGNATURES`] signatures were given.
+    TooManySignatures,
+    /// An offset or message length does not fit in the `u16` fields of
+    /// [`Secp256r1SignatureOffsets`].
+    DataTooLarge,
+}
+
+impl core::error::Error for Secp256r1InstructionError {}
+
+impl fmt::Display for Secp256r1InstructionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::TooManySignatures => f.write_str("too many signatures"),
+            Self::DataTooLarge => f.write_str("instruction data too large"),
+        }
+    }
+}
+
+impl From<LayoutError> for Secp256r1InstructionError {
+    fn from(error: LayoutError) -> Self {
+        match error {
+            LayoutError::TooManySignatures => Self::TooManySignatures,
+            LayoutError::DataTooLarge => Self::DataTooLarge,
+        }
+    }
+}
+
 #[cfg(all(not(target_arch = "wasm32"), not(target_os = "solana")))]
 mod target_arch {
     use {
-        crate::Secp256r1SignatureOffsets,
-        bytemuck::bytes_of,
+        crate::{
+            Secp256r1InstructionError, Secp256r1Layout, COMPRESSED_PUBKEY_SERIALIZED_SIZE,
+            SIGNATURE_SERIALIZED_SIZE,
+        },
         openssl::{bn::BigNum, ec::EcKey, ecdsa::EcdsaSig, nid::Nid, pkey::PKey, sign::Signer},
         solana_instruction::Instruction,
+        solana_precompile_layout::Entry,
     };
 
-    pub const COMPRESSED_PUBKEY_SERIALIZED_SIZE: usize = 33;
-    pub const SIGNATURE_SERIALIZED_SIZE: usize = 64;
-    pub const SIGNATURE_OFFSETS_SERIALIZED_SIZE: usize = 14;
-    pub const SIGNATURE_OFFSETS_START: usize = 2;
-    pub const DATA_START: usize = SIGNATURE_OFFSETS_SERIALIZED_SIZE + SIGNATURE_OFFSETS_START;
-
     // Order as defined in SEC2: 2.7.2 Recommended Parameters secp256r1
     pub const SECP256R1_ORDER: [u8; FIELD_SIZE] = [
         0xFF, 0xFF, 0xFF, 0xFF, 0x00, 0x00, 0x00, 0x00, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF,
@@ -124,46 +197,212 @@ mod target_arch {
         Ok(signature)
     }
 
+    /// Returns the length of the instruction data built by
+    /// [`new_secp256r1_instruction_with_signatures`] for `num_signatures`
+    /// signatures over messages of `message_lens` bytes.
+    ///
+    /// Returns `None` if `message_lens` does not hold exactly `num_signatures`
+    /// lengths, if `num_signatures` exceeds
+    /// [`MAX_SIGNATURES`](crate::MAX_SIGNATURES), or if an offset or message
+    /// length does not fit in the `u16` fields of
+    /// [`Secp256r1SignatureOffsets`](crate::Secp256r1SignatureOffsets).
+    pub fn instruction_size(num_signatures: usize, message_lens: &[usize]) -> Option<usize> {
+        solana_precompile_layout::instruction_size::<Secp256r1Layout>(num_signatures, message_lens)
+    }
+
+    /// Returns the largest message length such that an instruction verifying
+    /// `num_signatures` messages of that length fits in `budget` bytes of
+    /// instruction data.
+    ///
+    /// Returns `None` if `num_signatures` is zero or exceeds
+    /// [`MAX_SIGNATURES`](crate::MAX_SIGNATURES), or if not even empty messages
+    /// fit in `budget`.
+    pub fn max_message_len_for(num_signatures: usize, budget: usize) -> Option<usize> {
+        solana_precompile_layout::max_message_len_for::<Secp256r1Layout>(num_signatures, budget)
+    }
+
     pub fn new_secp256r1_instruction_with_signature(
         message: &[u8],
         signature: &[u8; SIGNATURE_SERIALIZED_SIZE],
         pubkey: &[u8; COMPRESSED_PUBKEY_SERIALIZED_SIZE],
     ) -> Instruction {
-        let mut instruction_data = Vec::with_capacity(
-            DATA_START
-                .saturating_add(SIGNATURE_SERIALIZED_SIZE)
-                .saturating_add(COMPRESSED_PUBKEY_SERIALIZED_SIZE)
-                .saturating_add(message.len()),
-        );
-
-        let num_signatures: u8 = 1;
-        let public_key_offset = DATA_START;
-        let signature_offset = public_key_offset.saturating_add(COMPRESSED_PUBKEY_SERIALIZED_SIZE);
-        let message_data_offset = signature_offset.saturating_add(SIGNATURE_SERIALIZED_SIZE);
-
-        instruction_data.extend_from_slice(bytes_of(&[num_signatures, 0]));
-
-        let offsets = Secp256r1SignatureOffsets {
-            signature_offset: signature_offset as u16,
-            signature_instruction_index: u16::MAX,
-            public_key_offset: public_key_offset as u16,
-            public_key_instruction_index: u16::MAX,
-            message_data_offset: message_data_offset as u16,
-            message_data_size: message.len() as u16,
-            message_instruction_index: u16::MAX,
-        };
-
-        instruction_data.extend_from_slice(bytes_of(&offsets));
-        instruction_data.extend_from_slice(pubkey);
-        instruction_data.extend_from_slice(signature);
-        instruction_data.extend_from_slice(message);
-
         Instruction {
             program_id: crate::id(),
             accounts: vec![],
-            data: instruction_data,
+            data: solana_precompile_layout::encode_many::<Secp256r1Layout>(&[Entry::Inline {
+                message,
+                signature,
+                pubkey,
+            }]),
         }
     }
+
+    /// Build a secp256r1 instruction verifying several `(message, signature,
+    /// pubkey)` triples, all stored in the instruction data.
+    ///
+    /// The data starts with the signature offsets, followed by the public key,
+    /// signature and message of each triple in order. Its length is given by
+    /// [`instruction_size`].
+    ///
+    /// Fails with [`Secp256r1InstructionError::TooManySignatures`] if there are
+    /// more than [`MAX_SIGNATURES`](crate::MAX_SIGNATURES) triples, or with
+    /// [`Secp256r1InstructionError::DataTooLarge`] if an offset or message
+    /// length does not fit in the `u16` fields of
+    /// [`Secp256r1SignatureOffsets`](crate::Secp256r1SignatureOffsets).
+    pub fn new_secp256r1_instruction_with_signatures(
+        signatures: &[(
+            &[u8],
+            &[u8; SIGNATURE_SERIALIZED_SIZE],
+            &[u8; COMPRESSED_PUBKEY_SERIALIZED_SIZE],
+        )],
+    ) -> Result<Instruction, Secp256r1InstructionError> {
+        let entries: Vec<_> = signatures
+            .iter()
+            .map(|&(message, signature, pubkey)| Entry::Inline {
+                message,
+                signature,
+                pubkey,
+            })
+            .collect();
+
+        Ok(Instruction {
+            program_id: crate::id(),
+            accounts: vec![],
+            data: solana_precompile_layout::build_many::<Secp256r1Layout>(&entries)?,
+        })
+    }
 }
 
 pub use self::target_arch::*;
+
+#[cfg(all(test, not(target_arch = "wasm32"), not(target_os = "solana")))]
+mod tests {
+    use super::*;
+
+    const MESSAGE_LENS: [usize; 5] = [0, 1, 32, 255, 1000];
+
+    #[test]
+    fn test_instruction_size_matches_builders() {
+        let signature = [1; SIGNATURE_SERIALIZED_SIZE];
+        let pubkey = [2; COMPRESSED_PUBKEY_SERIALIZED_SIZE];
+        let messages: Vec<Vec<u8>> = MESSAGE_LENS.iter().map(|&len| vec![3; len]).collect();
+
+        for message in &messages {
+            let instruction =
+                new_secp256r1_instruction_with_signature(message, &signature, &pubkey);
+            assert_eq!(
+                instruction_size(1, &[message.len()]),
+                Some(instruction.data.len())
+            );
+        }
+
+        for num_signatures in 1..=MAX_SIGNATURES {
+            let signatures: Vec<_> = messages
+                .iter()
+                .cycle()
+                .skip(num_signatures)
+                .take(num_signatures)
+                .map(|message| (message.as_slice(), &signature, &pubkey))
+                .collect();
+            let message_lens: Vec<_> = signatures
+                .iter()
+                .map(|(message, _, _)| message.len())
+                .collect();
+            let instruction = new_secp256r1_instruction_with_signatures(&signatures).unwrap();
+            assert_eq!(instruction.data[0] as usize, num_signatures);
+            assert_eq!(
+                instruction_size(num_signatures, &message_lens),
+                Some(instruction.data.len())
+            );
+        }
+    }
+
+    #[test]
+    fn test_instruction_size_invalid() {
+        assert_eq!(instruction_size(0, &[]), Some(SIGNATURE_OFFSETS_START));
+        assert_eq!(instruction_size(2, &[10]), None);
+        assert_eq!(
+            instruction_size(MAX_SIGNATURES + 1, &[0; MAX_SIGNATURES + 1]),
+            None
+        );
+        assert_eq!(instruction_size(1, &[usize::from(u16::MAX) + 1]), None);
+        // the last message starts past the u16 offset range
+        assert_eq!(instruction_size(2, &[usize::from(u16::MAX), 0]), None);
+        assert_eq!(instruction_size(1, &[usize::MAX]), None);
+    }
+
+    #[test]
+    fn test_builder_limits() {
+        let signature = [0; SIGNATURE_SERIALIZED_SIZE];
+        let pubkey = [0; COMPRESSED_PUBKEY_SERIALIZED_SIZE];
+
+        let signatures = vec![(&b""[..], &signature, &pubkey); MAX_SIGNATURES + 1];
+        assert!(new_secp256r1_instruction_with_signatures(&signatures[1..]).is_ok());
+        assert_eq!(
+            new_secp256r1_instruction_with_signatures(&signatures),
+            Err(Secp256r1InstructionError::TooManySignatures)
+        );
+
+        let too_long = vec![0; usize::from(u16::MAX) + 1];
+        assert_eq!(
+            new_secp256r1_instruction_with_signatures(&[(&too_long, &signature, &pubkey)]),
+            Err(Secp256r1InstructionError::DataTooLarge)
+        );
+    }
+
+    #[test]
+    fn test_max_message_len_for() {
+        const PACKET_BUDGET: usize = 1232;
+        for num_signatures in 1..=MAX_SIGNATURES {
+            let max = max_message_len_for(num_signatures, PACKET_BUDGET).unwrap();
+            let size = |len| instruction_size(num_signatures, &vec![len; num_signatures]);
+            assert!(size(max).unwrap() <= PACKET_BUDGET);
+            assert!(size(max + 1).unwrap() > PACKET_BUDGET);
+        }
+        assert_eq!(max_message_len_for(1, DATA_START + 97), Some(0));
+        assert_eq!(max_message_len_for(1, DATA_START + 96), None);
+        assert_eq!(max_message_len_for(0, PACKET_BUDGET), None);
+        assert_eq!(max_message_len_for(MAX_SIGNATURES + 1, usize::MAX), None);
+        assert_eq!(
+            max_message_len_for(1, usize::MAX),
+            Some(usize::from(u16::MAX))
+        );
+    }
+
+    /// Checks `data` byte for byte, against `head` in hex for the count and
+    /// offsets, and against the concatenation of `tail` for the rest.
+    fn assert_golden(data: &[u8], head: &str, tail: &[&[u8]]) {
+        let head: Vec<u8> = (0..head.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&head[i..i + 2], 16).unwrap())
+            .collect();
+        assert_eq!(data[..head.len()], head[..]);
+        assert_eq!(data[head.len()..], tail.concat()[..]);
+    }
+
+    #[test]
+    fn test_golden_instruction_data() {
+        let signature = [1; SIGNATURE_SERIALIZED_SIZE];
+        let pubkey = [2; COMPRESSED_PUBKEY_SERIALIZED_SIZE];
+        let signature_2 = [3; SIGNATURE_SERIALIZED_SIZE];
+        let pubkey_2 = [4; COMPRESSED_PUBKEY_SERIALIZED_SIZE];
+
+        let instruction = new_secp256r1_instruction_with_signature(b"hello", &signature, &pubkey);
+        assert_golden(
+            &instruction.data,
+            "01003100ffff1000ffff71000500ffff",
+            &[&pubkey, &signature, b"hello"],
+        );
+
+        let instruction = new_secp256r1_instruction_with_signatures(&[
+            (&b""[..], &signature, &pubkey),
+            (&b"ab"[..], &signature_2, &pubkey_2),
+        ])
+        .unwrap();
+        assert_golden(
+            &instruction.data,
+            "02003f00ffff1e00ffff7f000000ffffa000ffff7f00ffffe0000200ffff",
+            &[&pubkey, &signature, &pubkey_2, &signature_2, b"ab"],
+        );
+    }
+}
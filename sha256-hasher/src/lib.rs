@@ -1,5 +1,9 @@
 #![no_std]
 #![cfg_attr(docsrs, feature(doc_cfg))]
+#[cfg(test)]
+extern crate std;
+
+pub mod tick;
 
 #[cfg(any(target_os = "solana", target_arch = "bpf"))]
 pub use solana_define_syscall::definitions::sol_sha256;
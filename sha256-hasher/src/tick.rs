@@ -0,0 +1,177 @@
+//! Proof-of-history style hash chains.
+//!
+//! A chain advances either by hashing the current hash with itself
+//! ([`ChainStep::Tick`]) or by mixing in another hash
+//! ([`ChainStep::Mixin`]), matching the way entries are hashed by the
+//! validator.
+
+use {crate::hashv, solana_hash::Hash};
+
+/// A single step of a hash chain.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ChainStep {
+    /// Advance the chain by this many sequential hashes.
+    Tick(u64),
+    /// Hash the current hash together with the given hash.
+    Mixin(Hash),
+}
+
+/// Incrementally computes a hash chain.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TickHasher {
+    hash: Hash,
+}
+
+impl TickHasher {
+    pub fn new(start: Hash) -> Self {
+        Self { hash: start }
+    }
+
+    /// Advance the chain by `hashes` sequential hashes.
+    pub fn tick(&mut self, hashes: u64) {
+        for _ in 0..hashes {
+            self.hash = hashv(&[self.hash.as_ref()]);
+        }
+    }
+
+    /// Mix `mixin` into the chain.
+    pub fn record(&mut self, mixin: &Hash) {
+        self.hash = hashv(&[self.hash.as_ref(), mixin.as_ref()]);
+    }
+
+    /// Apply a single chain step.
+    pub fn step(&mut self, step: &ChainStep) {
+        match step {
+            ChainStep::Tick(hashes) => self.tick(*hashes),
+            ChainStep::Mixin(mixin) => self.record(mixin),
+        }
+    }
+
+    // `Hash` is only `Copy` with its `copy` feature
+    #[allow(clippy::clone_on_copy)]
+    pub fn current(&self) -> Hash {
+        self.hash.clone()
+    }
+}
+
+/// Compute the end of the chain starting at `start` after applying `steps`.
+#[allow(clippy::clone_on_copy)]
+pub fn verify_chain(start: &Hash, steps: &[ChainStep]) -> Hash {
+    let mut hasher = TickHasher::new(start.clone());
+    steps.iter().for_each(|step| hasher.step(step));
+    hasher.current()
+}
+
+/// A part of a hash chain ending at a recorded checkpoint.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChainSegment<'a> {
+    pub steps: &'a [ChainStep],
+    /// The recorded hash at the end of the segment.
+    pub end: Hash,
+}
+
+/// Verify consecutive chain segments in parallel.
+///
+/// The first segment starts at `start` and each following segment starts at
+/// the recorded end of the previous one. Returns true if every segment ends
+/// at its recorded hash.
+#[cfg(all(feature = "rayon", not(any(target_os = "solana", target_arch = "bpf"))))]
+pub fn verify_segments(start: &Hash, segments: &[ChainSegment]) -> bool {
+    use rayon::prelude::*;
+
+    segments.par_iter().enumerate().all(|(index, segment)| {
+        let segment_start = match index.checked_sub(1) {
+            Some(previous) => &segments[previous].end,
+            None => start,
+        };
+        verify_chain(segment_start, segment.steps) == segment.end
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, std::vec::Vec};
+
+    const ONE_TICK: [u8; 32] = [
+        102, 104, 122, 173, 248, 98, 189, 119, 108, 143, 193, 139, 142, 159, 142, 32, 8, 151, 20,
+        133, 110, 226, 51, 179, 144, 42, 89, 29, 13, 95, 41, 37,
+    ];
+    const TWO_TICKS_AND_MIXIN: [u8; 32] = [
+        145, 57, 216, 34, 133, 119, 255, 116, 9, 42, 153, 135, 250, 137, 133, 59, 191, 187, 57,
+        189, 236, 129, 74, 162, 145, 15, 189, 238, 93, 241, 31, 32,
+    ];
+
+    #[test]
+    fn test_known_vector() {
+        let mut hasher = TickHasher::new(Hash::default());
+        hasher.tick(0);
+        assert_eq!(hasher.current(), Hash::default());
+        hasher.tick(1);
+        assert_eq!(hasher.current(), Hash::new_from_array(ONE_TICK));
+        hasher.tick(1);
+        hasher.record(&Hash::new_from_array([1; 32]));
+        assert_eq!(hasher.current(), Hash::new_from_array(TWO_TICKS_AND_MIXIN));
+    }
+
+    fn test_steps() -> Vec<ChainStep> {
+        (0..20u8)
+            .map(|i| {
+                if i % 3 == 0 {
+                    ChainStep::Mixin(Hash::new_from_array([i; 32]))
+                } else {
+                    ChainStep::Tick(u64::from(i))
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    #[allow(clippy::clone_on_copy)]
+    fn test_incremental_matches_batch() {
+        let start = Hash::new_from_array([7; 32]);
+        let steps = test_steps();
+
+        let mut hasher = TickHasher::new(start.clone());
+        for step in &steps {
+            match step {
+                ChainStep::Tick(hashes) => hasher.tick(*hashes),
+                ChainStep::Mixin(mixin) => hasher.record(mixin),
+            }
+        }
+        assert_eq!(hasher.current(), verify_chain(&start, &steps));
+
+        // ticks split across steps are equivalent to a single tick
+        assert_eq!(
+            verify_chain(&start, &[ChainStep::Tick(2), ChainStep::Tick(3)]),
+            verify_chain(&start, &[ChainStep::Tick(5)])
+        );
+        assert_eq!(verify_chain(&start, &[]), start);
+    }
+
+    #[cfg(all(feature = "rayon", not(any(target_os = "solana", target_arch = "bpf"))))]
+    #[test]
+    #[allow(clippy::clone_on_copy)]
+    fn test_parallel_matches_sequential() {
+        let start = Hash::new_from_array([7; 32]);
+        let steps = test_steps();
+
+        let mut segments = Vec::new();
+        let mut segment_start = start.clone();
+        for chunk in steps.chunks(3) {
+            let end = verify_chain(&segment_start, chunk);
+            segments.push(ChainSegment {
+                steps: chunk,
+                end: end.clone(),
+            });
+            segment_start = end;
+        }
+        assert_eq!(segment_start, verify_chain(&start, &steps));
+        assert!(verify_segments(&start, &segments));
+        assert!(verify_segments(&start, &[]));
+
+        assert!(!verify_segments(&Hash::default(), &segments));
+        let mut corrupted = segments.clone();
+        corrupted[3].end = Hash::default();
+        assert!(!verify_segments(&start, &corrupted));
+    }
+}
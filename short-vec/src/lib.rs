@@ -2,17 +2,26 @@
 #![cfg_attr(feature = "frozen-abi", feature(min_specialization))]
 #![cfg_attr(docsrs, feature(doc_cfg))]
 #![allow(clippy::arithmetic_side_effects)]
+#![no_std]
+
+extern crate alloc;
+#[cfg(feature = "frozen-abi")]
+extern crate std;
+
 #[cfg(feature = "frozen-abi")]
 use solana_frozen_abi_macro::AbiExample;
-use std::convert::TryFrom;
+use {
+    alloc::vec::Vec,
+    core::{convert::TryFrom, fmt},
+};
 #[cfg(feature = "serde")]
 use {
+    core::marker::PhantomData,
     serde_core::{
         de::{self, Deserializer, SeqAccess, Visitor},
         ser::{self, SerializeTuple, Serializer},
         Deserialize, Serialize,
     },
-    std::{fmt, marker::PhantomData},
 };
 
 /// Same as u16, but serialized with 1 to 3 bytes. If the value is above
@@ -49,6 +58,7 @@ impl Serialize for ShortU16 {
     }
 }
 
+#[cfg(feature = "serde")]
 enum VisitStatus {
     Done(u16),
     More(u16),
@@ -89,9 +99,12 @@ impl VisitError {
     }
 }
 
+#[cfg(feature = "serde")]
 type VisitResult = Result<VisitStatus, VisitError>;
 
-const MAX_ENCODING_LENGTH: usize = 3;
+/// Maximum number of bytes a [`ShortU16`] occupies once encoded.
+pub const MAX_ENCODING_LENGTH: usize = 3;
+#[cfg(feature = "serde")]
 fn visit_byte(elem: u8, val: u16, nth_byte: usize) -> VisitResult {
     if elem == 0 && nth_byte != 0 {
         return Err(VisitError::Alias);
@@ -270,33 +283,169 @@ impl<'de, T: Deserialize<'de>> Deserialize<'de> for ShortVec<T> {
 /// Return the decoded value and how many bytes it consumed.
 #[allow(clippy::result_unit_err)]
 pub fn decode_shortu16_len(bytes: &[u8]) -> Result<(usize, usize), ()> {
-    let mut val = 0;
-    for (nth_byte, byte) in bytes.iter().take(MAX_ENCODING_LENGTH).enumerate() {
-        match visit_byte(*byte, val, nth_byte).map_err(|_| ())? {
-            VisitStatus::More(new_val) => val = new_val,
-            VisitStatus::Done(new_val) => {
-                return Ok((usize::from(new_val), nth_byte.saturating_add(1)));
+    decode_len(bytes).map_err(|_| ())
+}
+
+/// Errors returned by the standalone compact-u16 encoding functions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShortVecError {
+    /// The input ended before a terminal byte was found.
+    TooShort,
+    /// The value is encoded with more bytes than necessary.
+    Alias,
+    /// The third byte has its continuation bit set.
+    ByteThreeContinues,
+    /// The encoded or requested value does not fit in a u16.
+    Overflow,
+    /// The input ended before all the length-prefixed elements were read.
+    ElementsTooShort,
+}
+
+impl core::error::Error for ShortVecError {}
+
+impl fmt::Display for ShortVecError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ShortVecError::TooShort => f.write_str("compact-u16 encoding is truncated"),
+            ShortVecError::Alias => f.write_str("compact-u16 encoding is not in strict form"),
+            ShortVecError::ByteThreeContinues => {
+                f.write_str("compact-u16 encoding continues past byte three")
+            }
+            ShortVecError::Overflow => f.write_str("value is out of the range [0, 65535]"),
+            ShortVecError::ElementsTooShort => {
+                f.write_str("input is too short for the length-prefixed elements")
             }
         }
     }
-    Err(())
+}
+
+/// Encodes `value` as a compact-u16.
+///
+/// Returns a buffer holding the encoding and the number of bytes of the
+/// buffer that are used.
+pub const fn encode_short_u16(value: u16) -> ([u8; MAX_ENCODING_LENGTH], usize) {
+    let mut buf = [0u8; MAX_ENCODING_LENGTH];
+    let mut rem_val = value;
+    let mut len = 0;
+    loop {
+        let elem = (rem_val & 0x7f) as u8;
+        rem_val >>= 7;
+        if rem_val == 0 {
+            buf[len] = elem;
+            return (buf, len + 1);
+        }
+        buf[len] = elem | 0x80;
+        len += 1;
+    }
+}
+
+/// Appends the compact-u16 encoding of `len` to `buf`.
+///
+/// Fails with [`ShortVecError::Overflow`] if `len` is larger than
+/// `u16::MAX`, in which case `buf` is left untouched.
+pub fn encode_len(buf: &mut Vec<u8>, len: usize) -> Result<(), ShortVecError> {
+    let value = u16::try_from(len).map_err(|_| ShortVecError::Overflow)?;
+    let (bytes, used) = encode_short_u16(value);
+    buf.extend_from_slice(&bytes[..used]);
+    Ok(())
+}
+
+/// Decodes a compact-u16 from the start of `bytes`.
+///
+/// Returns the decoded value and how many bytes it consumed. Only the strict
+/// (shortest) encoding of each value is accepted.
+pub const fn decode_len(bytes: &[u8]) -> Result<(usize, usize), ShortVecError> {
+    // 1 byte  : 0xxxxxxx                   => 00000000 0xxxxxxx :      0 -    127
+    // 2 bytes : 1xxxxxxx 0yyyyyyy          => 00yyyyyy yxxxxxxx :    128 - 16,383
+    // 3 bytes : 1xxxxxxx 1yyyyyyy 000000zz => zzyyyyyy yxxxxxxx : 16,384 - 65,535
+    let mut val: u32 = 0;
+    let mut nth_byte = 0;
+    while nth_byte < MAX_ENCODING_LENGTH {
+        if nth_byte >= bytes.len() {
+            return Err(ShortVecError::TooShort);
+        }
+        let elem = bytes[nth_byte];
+        if elem == 0 && nth_byte != 0 {
+            return Err(ShortVecError::Alias);
+        }
+        val |= ((elem & 0x7f) as u32) << (nth_byte * 7);
+        if elem & 0x80 == 0 {
+            if val > u16::MAX as u32 {
+                return Err(ShortVecError::Overflow);
+            }
+            return Ok((val as usize, nth_byte + 1));
+        }
+        nth_byte += 1;
+    }
+    Err(ShortVecError::ByteThreeContinues)
+}
+
+/// Decodes a compact-u16 length prefix followed by that many elements of
+/// `element_size` bytes each.
+///
+/// Returns the raw bytes of the elements and the total number of bytes
+/// consumed, including the prefix.
+pub const fn decode_len_prefixed_bytes(
+    bytes: &[u8],
+    element_size: usize,
+) -> Result<(&[u8], usize), ShortVecError> {
+    let (len, prefix_len) = match decode_len(bytes) {
+        Ok(decoded) => decoded,
+        Err(err) => return Err(err),
+    };
+    // `len` is at most u16::MAX, so this only overflows for absurd element sizes.
+    let Some(elements_len) = len.checked_mul(element_size) else {
+        return Err(ShortVecError::ElementsTooShort);
+    };
+    let (_, rest) = bytes.split_at(prefix_len);
+    if rest.len() < elements_len {
+        return Err(ShortVecError::ElementsTooShort);
+    }
+    let (elements, _) = rest.split_at(elements_len);
+    Ok((elements, prefix_len + elements_len))
+}
+
+/// Decodes a compact-u16 length prefix followed by that many elements of
+/// `element_size` bytes each, converting every element with `decode_element`.
+///
+/// Returns the decoded elements and the total number of bytes consumed,
+/// including the prefix.
+pub fn decode_slice_len_prefixed<'a, T>(
+    bytes: &'a [u8],
+    element_size: usize,
+    mut decode_element: impl FnMut(&'a [u8]) -> T,
+) -> Result<(Vec<T>, usize), ShortVecError> {
+    let (elements, consumed) = decode_len_prefixed_bytes(bytes, element_size)?;
+    let elements = if element_size == 0 {
+        // Zero-sized elements carry no bytes, but the length still counts.
+        let len = decode_len(bytes)?.0;
+        (0..len).map(|_| decode_element(&[])).collect()
+    } else {
+        elements
+            .chunks_exact(element_size)
+            .map(decode_element)
+            .collect()
+    };
+    Ok((elements, consumed))
 }
 
 #[cfg(all(test, feature = "serde"))]
 mod tests {
     use {
         super::*,
+        alloc::vec,
         assert_matches::assert_matches,
         bincode::{deserialize, serialize},
+        proptest::prelude::*,
     };
 
     /// Return the serialized length.
-    fn encode_len(len: u16) -> Vec<u8> {
+    fn encoded_len_bytes(len: u16) -> Vec<u8> {
         bincode::serialize(&ShortU16(len)).unwrap()
     }
 
     fn assert_len_encoding(len: u16, bytes: &[u8]) {
-        assert_eq!(encode_len(len), bytes, "unexpected usize encoding");
+        assert_eq!(encoded_len_bytes(len), bytes, "unexpected usize encoding");
         assert_eq!(
             decode_shortu16_len(bytes).unwrap(),
             (usize::from(len), bytes.len()),
@@ -367,6 +516,146 @@ mod tests {
         assert_bad_deserialized_value(&[0x80, 0x80, 0x06]);
     }
 
+    #[test]
+    fn test_encode_decode_len_canonical() {
+        for (value, bytes) in [
+            (0x0, &[0x00][..]),
+            (0x7f, &[0x7f]),
+            (0x80, &[0x80, 0x01]),
+            (0x3fff, &[0xff, 0x7f]),
+            (0x4000, &[0x80, 0x80, 0x01]),
+            (0xffff, &[0xff, 0xff, 0x03]),
+        ] {
+            let mut buf = vec![];
+            encode_len(&mut buf, value).unwrap();
+            assert_eq!(buf, bytes);
+            assert_eq!(decode_len(bytes), Ok((value, bytes.len())));
+            // Trailing bytes are not consumed.
+            let mut extended = bytes.to_vec();
+            extended.push(0xff);
+            assert_eq!(decode_len(&extended), Ok((value, bytes.len())));
+        }
+    }
+
+    #[test]
+    fn test_encode_len_overflow() {
+        let mut buf = vec![0xaa];
+        assert_eq!(
+            encode_len(&mut buf, u16::MAX as usize + 1),
+            Err(ShortVecError::Overflow)
+        );
+        assert_eq!(
+            encode_len(&mut buf, usize::MAX),
+            Err(ShortVecError::Overflow)
+        );
+        assert_eq!(buf, [0xaa]);
+    }
+
+    #[test]
+    fn test_decode_len_rejects_aliases() {
+        // Every value that fits in fewer bytes, encoded with a redundant
+        // trailing zero byte.
+        for value in 0..=0x3fffu16 {
+            let (bytes, used) = encode_short_u16(value);
+            let mut alias = bytes[..used].to_vec();
+            *alias.last_mut().unwrap() |= 0x80;
+            alias.push(0x00);
+            assert_eq!(decode_len(&alias), Err(ShortVecError::Alias));
+            assert!(deserialize::<ShortU16>(&alias).is_err());
+            if used == 1 {
+                let alias = [alias[0], 0x80, 0x00];
+                assert_eq!(decode_len(&alias), Err(ShortVecError::Alias));
+                assert!(deserialize::<ShortU16>(&alias).is_err());
+            }
+        }
+    }
+
+    #[test]
+    fn test_decode_len_malformed() {
+        assert_eq!(decode_len(&[]), Err(ShortVecError::TooShort));
+        assert_eq!(decode_len(&[0x80]), Err(ShortVecError::TooShort));
+        assert_eq!(decode_len(&[0xff, 0xff]), Err(ShortVecError::TooShort));
+        assert_eq!(
+            decode_len(&[0x80, 0x80, 0x80, 0x00]),
+            Err(ShortVecError::ByteThreeContinues)
+        );
+        assert_eq!(
+            decode_len(&[0xff, 0xff, 0x83]),
+            Err(ShortVecError::ByteThreeContinues)
+        );
+        for third in 0x04..=0x7f {
+            assert_eq!(
+                decode_len(&[0x80, 0x80, third]),
+                Err(ShortVecError::Overflow)
+            );
+        }
+        assert_eq!(decode_shortu16_len(&[0x80, 0x80, 0x04]), Err(()));
+    }
+
+    #[test]
+    fn test_encode_decode_len_exhaustive() {
+        for value in 0..=u16::MAX {
+            let (bytes, used) = encode_short_u16(value);
+            let bytes = &bytes[..used];
+            assert_eq!(bytes, serialize(&ShortU16(value)).unwrap());
+            assert_eq!(decode_len(bytes), Ok((usize::from(value), used)));
+            assert_eq!(deserialize::<ShortU16>(bytes).unwrap().0, value);
+        }
+    }
+
+    #[test]
+    fn test_decode_slice_len_prefixed() {
+        let vec = ShortVec(vec![[1u8, 2], [3, 4], [5, 6]]);
+        let mut bytes = serialize(&vec).unwrap();
+        bytes.push(0xff);
+
+        let (elements, consumed) = decode_len_prefixed_bytes(&bytes, 2).unwrap();
+        assert_eq!(elements, [1, 2, 3, 4, 5, 6]);
+        assert_eq!(consumed, 7);
+
+        let (decoded, consumed) =
+            decode_slice_len_prefixed(&bytes, 2, |e| <[u8; 2]>::try_from(e).unwrap()).unwrap();
+        assert_eq!(decoded, vec.0);
+        assert_eq!(consumed, 7);
+
+        let (decoded, consumed) = decode_slice_len_prefixed(&[0x03], 0, |e| e.len()).unwrap();
+        assert_eq!(decoded, [0, 0, 0]);
+        assert_eq!(consumed, 1);
+
+        assert_eq!(
+            decode_len_prefixed_bytes(&bytes[..6], 2),
+            Err(ShortVecError::ElementsTooShort)
+        );
+        assert_eq!(
+            decode_len_prefixed_bytes(&[0x01], usize::MAX),
+            Err(ShortVecError::ElementsTooShort)
+        );
+        assert_eq!(
+            decode_len_prefixed_bytes(&[0x80], 1),
+            Err(ShortVecError::TooShort)
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn test_decode_len_matches_serde(bytes in prop::collection::vec(any::<u8>(), 0..5)) {
+            let standalone = decode_len(&bytes).map(|(value, _)| value).ok();
+            let serde = deserialize::<ShortU16>(&bytes).map(|v| usize::from(v.0)).ok();
+            prop_assert_eq!(standalone, serde);
+        }
+
+        #[test]
+        fn test_short_vec_round_trip(vec in prop::collection::vec(any::<u32>(), 0..300)) {
+            let bytes = serialize(&ShortVec(vec.clone())).unwrap();
+            let (decoded, consumed) = decode_slice_len_prefixed(&bytes, 4, |e| {
+                u32::from_le_bytes(e.try_into().unwrap())
+            })
+            .unwrap();
+            prop_assert_eq!(decoded, vec);
+            prop_assert_eq!(consumed, bytes.len());
+        }
+    }
+
     #[test]
     fn test_short_vec_u8() {
         let vec = ShortVec(vec![4u8; 32]);
@@ -29,6 +29,11 @@ pub mod error;
 pub const SIGNATURE_BYTES: usize = 64;
 /// Maximum string length of a base58 encoded signature
 const MAX_BASE58_SIGNATURE_LEN: usize = 88;
+/// Order `L` of the ed25519 base point, little-endian
+const ED25519_ORDER: [u8; 32] = [
+    0xed, 0xd3, 0xf5, 0x5c, 0x1a, 0x63, 0x12, 0x58, 0xd6, 0x9c, 0xf7, 0xa2, 0xde, 0xf9, 0xde, 0x14,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x10,
+];
 
 #[repr(transparent)]
 #[cfg_attr(feature = "frozen-abi", derive(solana_frozen_abi_macro::AbiExample))]
@@ -57,10 +62,55 @@ impl Signature {
     pub const fn as_array(&self) -> &[u8; SIGNATURE_BYTES] {
         &self.0
     }
+
+    /// Return the `R` component of the ed25519 signature, its first 32
+    /// bytes: an encoded curve point.
+    #[inline(always)]
+    pub const fn r_bytes(&self) -> &[u8; 32] {
+        let Some((r, _)) = self.0.split_first_chunk::<32>() else {
+            unreachable!()
+        };
+        r
+    }
+
+    /// Return the `s` component of the ed25519 signature, its last 32
+    /// bytes: a little-endian scalar.
+    #[inline(always)]
+    pub const fn s_bytes(&self) -> &[u8; 32] {
+        let Some((_, s)) = self.0.split_last_chunk::<32>() else {
+            unreachable!()
+        };
+        s
+    }
+
+    /// Returns whether the `s` component of the signature is canonical,
+    /// that is below the order `L` of the ed25519 group.
+    ///
+    /// Adding `L` to the `s` component of a valid signature gives another
+    /// signature of the same message, which lenient verifiers accept, so
+    /// that the signature is malleable. Only the canonical one is valid for
+    /// the strict verification of [`Signature::verify`].
+    ///
+    /// This only compares bytes: the `R` component is not checked.
+    pub const fn is_canonical(&self) -> bool {
+        let s = self.s_bytes();
+        // compare from the most significant byte
+        let mut i = s.len();
+        while i > 0 {
+            i -= 1;
+            if s[i] != ED25519_ORDER[i] {
+                return s[i] < ED25519_ORDER[i];
+            }
+        }
+        false
+    }
 }
 
 #[cfg(feature = "rand")]
 impl Signature {
+    /// Returns a random signature, for tests.
+    ///
+    /// The signature is not valid for any public key.
     pub fn new_unique() -> Self {
         Self::from(core::array::from_fn(|_| rand::random()))
     }
@@ -78,9 +128,72 @@ impl Signature {
         publickey.verify_strict(message_bytes, &signature)
     }
 
+    /// Verifies the signature of `message_bytes` by the ed25519 public key
+    /// `pubkey_bytes`.
+    ///
+    /// The signature is checked with `verify_strict` semantics, as the
+    /// runtime does: besides non-canonical `s` components, it rejects
+    /// signatures whose public key or `R` component is of small order,
+    /// which a plain ed25519 verification accepts for any message.
     pub fn verify(&self, pubkey_bytes: &[u8], message_bytes: &[u8]) -> bool {
         self.verify_verbose(pubkey_bytes, message_bytes).is_ok()
     }
+
+    /// Verifies the signature like [`Signature::verify`], after checking
+    /// that it is [canonical](Signature::is_canonical).
+    ///
+    /// `verify` already rejects non-canonical `s` components, but through
+    /// the scalar decoding of `ed25519-dalek`, whose leniency depends on its
+    /// features. This checks the bytes first, so that a signature is never
+    /// accepted in one encoding and rejected in another, whatever the build.
+    pub fn verify_canonical(&self, pubkey_bytes: &[u8], message_bytes: &[u8]) -> bool {
+        self.is_canonical() && self.verify(pubkey_bytes, message_bytes)
+    }
+}
+
+/// Verifies a batch of `(pubkey, message, signature)` items at once, which is
+/// faster than verifying them one by one.
+///
+/// Returns `true` if every signature is valid, including for an empty batch.
+/// Like [`Signature::verify`], signatures with a public key or `R` component
+/// of small order, or a non-canonical `R` or `s` component, are rejected.
+///
+/// # Important
+///
+/// The batch equation cannot tell components with a torsion part that are
+/// not of small order: such signatures, which [`Signature::verify`]
+/// rejects, may pass. They cannot be produced by honest signers, but when
+/// the result must match the runtime exactly, verify the signatures one by
+/// one.
+#[cfg(feature = "batch")]
+pub fn verify_batch(items: &[(&[u8; 32], &[u8], &Signature)]) -> bool {
+    use {
+        alloc::vec::Vec,
+        curve25519_dalek::edwards::CompressedEdwardsY,
+        ed25519_dalek::{Signature as DalekSignature, VerifyingKey},
+    };
+
+    let mut messages = Vec::with_capacity(items.len());
+    let mut signatures = Vec::with_capacity(items.len());
+    let mut verifying_keys = Vec::with_capacity(items.len());
+    for (pubkey, message, signature) in items {
+        let Ok(verifying_key) = VerifyingKey::from_bytes(pubkey) else {
+            return false;
+        };
+        // the small order checks of `verify_strict`, which the batch
+        // equation does not do
+        let r = CompressedEdwardsY(signature.0[..32].try_into().unwrap());
+        let canonical_r = r
+            .decompress()
+            .is_some_and(|point| !point.is_small_order() && point.compress() == r);
+        if verifying_key.is_weak() || !canonical_r {
+            return false;
+        }
+        messages.push(*message);
+        signatures.push(DalekSignature::from_bytes(signature.as_array()));
+        verifying_keys.push(verifying_key);
+    }
+    ed25519_dalek::verify_batch(&messages, &signatures, &verifying_keys).is_ok()
 }
 
 impl AsRef<[u8]> for Signature {
@@ -186,6 +299,7 @@ mod tests {
         super::*,
         serde_derive::{Deserialize, Serialize},
         solana_pubkey::Pubkey,
+        std::{format, string::ToString},
     };
 
     #[test]
@@ -304,4 +418,212 @@ mod tests {
         // Sanity check: ensure the pointer is the same.
         assert_eq!(signature.as_array().as_ptr(), signature.0.as_ptr());
     }
+
+    /// Test vectors 1 to 3 of RFC 8032, section 7.1: public key, message and
+    /// signature.
+    const RFC8032_VECTORS: [(&str, &str, &str); 3] = [
+        (
+            "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a",
+            "",
+            "e5564300c360ac729086e2cc806e828a84877f1eb8e5d974d873e065224901555fb8821590a33bacc61e39701cf9b46bd25bf5f0595bbe24655141438e7a100b",
+        ),
+        (
+            "3d4017c3e843895a92b70aa74d1b7ebc9c982ccf2ec4968cc0cd55f12af4660c",
+            "72",
+            "92a009a9f0d4cab8720e820b5f642540a2b27b5416503f8fb3762223ebdb69da085ac1e43e15996e458f3613d0f11d8c387b2eaeb4302aeeb00d291612bb0c00",
+        ),
+        (
+            "fc51cd8e6218a1a38da47ed00230f0580816ed13ba3303ac5deb911548908025",
+            "af82",
+            "6291d657deec24024827e69c3abe01a30ce548a284743a445e3680d7db5ac3ac18ff9b538d16f290ae67f760984dc6594a7c15e9716ed28dc027beceea1ec40a",
+        ),
+    ];
+
+    fn rfc8032_vectors() -> impl Iterator<Item = ([u8; 32], Vec<u8>, Signature)> {
+        RFC8032_VECTORS.iter().map(|(pubkey, message, signature)| {
+            (
+                hex::decode(pubkey).unwrap().try_into().unwrap(),
+                hex::decode(message).unwrap(),
+                Signature::try_from(hex::decode(signature).unwrap()).unwrap(),
+            )
+        })
+    }
+
+    /// A public key and signature of the identity point, a point of small
+    /// order, which plain ed25519 verification accepts for any message.
+    fn small_order_signature() -> ([u8; 32], Signature) {
+        let mut identity = [0; 32];
+        identity[0] = 1;
+        let mut signature = [0; SIGNATURE_BYTES];
+        signature[..32].copy_from_slice(&identity);
+        (identity, Signature::from(signature))
+    }
+
+    #[test]
+    fn test_verify_rfc8032() {
+        for (pubkey, message, signature) in rfc8032_vectors() {
+            assert!(signature.verify(&pubkey, &message));
+            assert!(!signature.verify(&pubkey, b"other message"));
+
+            let mut tampered = <[u8; 64]>::from(signature);
+            tampered[63] ^= 1;
+            assert!(!Signature::from(tampered).verify(&pubkey, &message));
+        }
+    }
+
+    #[test]
+    fn test_verify_strict() {
+        use ed25519_dalek::Verifier;
+
+        let (pubkey, signature) = small_order_signature();
+        let verifying_key = ed25519_dalek::VerifyingKey::from_bytes(&pubkey).unwrap();
+        let dalek_signature = ed25519_dalek::Signature::from_bytes(signature.as_array());
+        assert!(verifying_key
+            .verify(b"any message", &dalek_signature)
+            .is_ok());
+        assert!(!signature.verify(&pubkey, b"any message"));
+    }
+
+    #[test]
+    fn test_verify_batch() {
+        let vectors: Vec<_> = rfc8032_vectors().collect();
+        let mut items: Vec<_> = vectors
+            .iter()
+            .map(|(pubkey, message, signature)| (pubkey, message.as_slice(), signature))
+            .collect();
+        assert!(verify_batch(&items));
+        assert!(verify_batch(&[]));
+
+        items[1].1 = b"other message";
+        assert!(!verify_batch(&items));
+        items[1].1 = &vectors[1].1;
+
+        // dalek's batch verification alone accepts the small order signature
+        let (pubkey, signature) = small_order_signature();
+        assert!(ed25519_dalek::verify_batch(
+            &[b"any message"],
+            &[ed25519_dalek::Signature::from_bytes(signature.as_array())],
+            &[ed25519_dalek::VerifyingKey::from_bytes(&pubkey).unwrap()],
+        )
+        .is_ok());
+        items.push((&pubkey, b"any message", &signature));
+        assert!(!verify_batch(&items));
+    }
+
+    #[test]
+    fn test_base58_round_trip() {
+        let signatures = [
+            Signature::default(),
+            Signature::from([0xff; SIGNATURE_BYTES]),
+            Signature::new_unique(),
+        ]
+        .into_iter()
+        .chain(rfc8032_vectors().map(|(_, _, signature)| signature));
+        for signature in signatures {
+            let string = signature.to_string();
+            assert_eq!(string, bs58::encode(signature).into_string());
+            assert_eq!(string.parse::<Signature>(), Ok(signature));
+            assert_eq!(format!("{signature:?}"), string);
+        }
+        assert_ne!(Signature::new_unique(), Signature::new_unique());
+        assert_eq!(
+            Signature::default().to_string(),
+            "1111111111111111111111111111111111111111111111111111111111111111"
+        );
+    }
+
+    /// Adds the group order `L` to the `s` component of `signature`, which
+    /// keeps it valid for lenient verifiers.
+    fn add_order_to_s(signature: &Signature) -> Signature {
+        let mut bytes = <[u8; 64]>::from(*signature);
+        let mut carry = 0;
+        for (byte, order_byte) in bytes[32..].iter_mut().zip(ED25519_ORDER) {
+            let sum = u16::from(*byte) + u16::from(order_byte) + carry;
+            *byte = sum as u8;
+            carry = sum >> 8;
+        }
+        assert_eq!(carry, 0);
+        Signature::from(bytes)
+    }
+
+    #[test]
+    fn test_r_s_bytes() {
+        let bytes: [u8; 64] = core::array::from_fn(|i| i as u8);
+        let signature = Signature::from(bytes);
+        assert_eq!(signature.r_bytes()[..], bytes[..32]);
+        assert_eq!(signature.s_bytes()[..], bytes[32..]);
+    }
+
+    #[test]
+    fn test_is_canonical_boundaries() {
+        let with_s = |s: [u8; 32]| {
+            let mut bytes = [0; 64];
+            bytes[32..].copy_from_slice(&s);
+            Signature::from(bytes)
+        };
+        let mut below_order = ED25519_ORDER;
+        below_order[0] -= 1;
+        let mut above_order = ED25519_ORDER;
+        above_order[0] += 1;
+
+        assert!(with_s([0; 32]).is_canonical());
+        assert!(with_s(below_order).is_canonical());
+        assert!(!with_s(ED25519_ORDER).is_canonical());
+        assert!(!with_s(above_order).is_canonical());
+        assert!(!with_s([0xff; 32]).is_canonical());
+        // only the `s` component is checked
+        assert!(Signature::from([0; 64]).is_canonical());
+        assert!(!Signature::from([0xff; 64]).is_canonical());
+    }
+
+    #[test]
+    fn test_is_canonical_signed() {
+        use ed25519_dalek::Signer;
+
+        for seed in 0..32 {
+            let signing_key = ed25519_dalek::SigningKey::from_bytes(&[seed; 32]);
+            let pubkey = signing_key.verifying_key().to_bytes();
+            let message = [seed; 8];
+            let signature = Signature::from(signing_key.sign(&message).to_bytes());
+            assert!(signature.is_canonical());
+            assert!(signature.verify_canonical(&pubkey, &message));
+            assert!(!signature.verify_canonical(&pubkey, b"other message"));
+
+            let malleated = add_order_to_s(&signature);
+            assert!(!malleated.is_canonical());
+            assert!(!malleated.verify(&pubkey, &message));
+            assert!(!malleated.verify_canonical(&pubkey, &message));
+        }
+    }
+
+    #[test]
+    fn test_is_canonical_matches_dalek() {
+        let signatures = rfc8032_vectors()
+            .flat_map(|(_, _, signature)| [signature, add_order_to_s(&signature)])
+            .chain((0..1_000).map(|_| Signature::new_unique()))
+            .chain([
+                Signature::default(),
+                Signature::from([0xff; SIGNATURE_BYTES]),
+            ]);
+        for signature in signatures {
+            let dalek_canonical =
+                curve25519_dalek::Scalar::from_canonical_bytes(*signature.s_bytes())
+                    .is_some()
+                    .into();
+            assert_eq!(signature.is_canonical(), dalek_canonical);
+        }
+
+        // `verify_strict` rejects the malleated RFC 8032 signatures
+        for (pubkey, message, signature) in rfc8032_vectors() {
+            let malleated = add_order_to_s(&signature);
+            let verifying_key = ed25519_dalek::VerifyingKey::from_bytes(&pubkey).unwrap();
+            assert!(verifying_key
+                .verify_strict(
+                    &message,
+                    &ed25519_dalek::Signature::from_bytes(malleated.as_array())
+                )
+                .is_err());
+            assert!(!malleated.verify_canonical(&pubkey, &message));
+        }
+    }
 }
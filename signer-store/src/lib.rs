@@ -35,18 +35,46 @@
 //!     original number of bits (i.e., the length of the input vectors; not the
 //!     length of the final vector).
 //! 3.  **Data Payload**: A sequence of bytes containing the packed base-3 digits.
+//!
+//! ## Wide Variants
+//! Both schemes limit the input to `u16::MAX` bits. For longer inputs, the
+//! `Version::Base2Wide` and `Version::Base3Wide` formats are identical to
+//! their narrow counterparts, except that the length prefix is a `u32` in
+//! little-endian format (4 bytes). [`encode_base2_auto`] and
+//! [`encode_base3_auto`] use the narrow format whenever the input fits, so
+//! that their output is unchanged for small inputs, and [`decode`] accepts
+//! all four versions.
+//!
+//! # `no_std`
+//!
+//! The crate is `no_std`. The encoders and [`decode`] work with [`BitVec`]s
+//! and require the `alloc` feature, enabled by default. Without it,
+//! [`decode_to_bytes_into`] decodes into caller-provided buffers, so that
+//! programs can decode signer sets on-chain without allocating.
+#![no_std]
 #![cfg_attr(docsrs, feature(doc_cfg))]
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+#[cfg(test)]
+extern crate std;
+
+#[cfg(feature = "alloc")]
 use {
+    alloc::{vec, vec::Vec},
     bitvec::prelude::*,
+};
+use {
+    core::fmt,
     num_derive::{FromPrimitive, ToPrimitive},
     num_traits::FromPrimitive,
-    thiserror::Error,
 };
 
 const VERSION_BYTE_LEN: usize = 1;
 const LENGTH_PREFIX_LEN: usize = 2;
 const HEADER_LEN: usize = VERSION_BYTE_LEN + LENGTH_PREFIX_LEN;
+const WIDE_LENGTH_PREFIX_LEN: usize = 4;
+const WIDE_HEADER_LEN: usize = VERSION_BYTE_LEN + WIDE_LENGTH_PREFIX_LEN;
 
 /// Represents the encoding version, used as the first byte in the output.
 #[derive(Debug, PartialEq, Eq, FromPrimitive, ToPrimitive)]
@@ -54,40 +82,113 @@ const HEADER_LEN: usize = VERSION_BYTE_LEN + LENGTH_PREFIX_LEN;
 pub enum Version {
     Base2 = 0,
     Base3 = 1,
+    /// `Base2` with a `u32` length prefix.
+    Base2Wide = 2,
+    /// `Base3` with a `u32` length prefix.
+    Base3Wide = 3,
+}
+
+impl Version {
+    // Returns the length of the header, version byte and length prefix
+    fn header_len(&self) -> usize {
+        match self {
+            Self::Base2 | Self::Base3 => HEADER_LEN,
+            Self::Base2Wide | Self::Base3Wide => WIDE_HEADER_LEN,
+        }
+    }
 }
 
 /// An error that can occur during the encoding process.
-#[derive(Error, Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq, Eq)]
 pub enum EncodeError {
-    #[error("in Base3 encoding, the provided bit-vectors have unmatching lengths")]
     MismatchedLengths,
-    #[error("in Base3 encoding, the invalid combination `(true, true)` was found")]
     InvalidBitCombination,
-    #[error("the length of the input vectors exceeds u16::MAX (65,535)")]
     LengthExceedsLimit,
-    #[error("an arithmetic operation resulted in an overflow")]
     ArithmeticOverflow,
 }
 
+impl core::error::Error for EncodeError {}
+
+impl fmt::Display for EncodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::MismatchedLengths => {
+                f.write_str("in Base3 encoding, the provided bit-vectors have unmatching lengths")
+            }
+            Self::InvalidBitCombination => {
+                f.write_str("in Base3 encoding, the invalid combination `(true, true)` was found")
+            }
+            Self::LengthExceedsLimit => {
+                f.write_str("the length of the input vectors exceeds the limit of the encoding")
+            }
+            Self::ArithmeticOverflow => {
+                f.write_str("an arithmetic operation resulted in an overflow")
+            }
+        }
+    }
+}
+
 // Each u8 can hold 5 base-3 symbols (3^5 = 243).
 const BASE3_SYMBOLS_PER_BYTE: usize = 5;
 
 /// Encodes a single boolean vector using Base2 encoding.
 ///
 /// The output `Vec<u8>` is prefixed with the `Version::Base2` byte.
+/// Returns [`EncodeError::LengthExceedsLimit`] if the vector is longer than
+/// `u16::MAX` bits, see [`encode_base2_auto`].
+#[cfg(feature = "alloc")]
 pub fn encode_base2(bit_vec: &BitVec<u8, Lsb0>) -> Result<Vec<u8>, EncodeError> {
-    let num_bits = bit_vec.len();
-    if num_bits > u16::MAX as usize {
-        return Err(EncodeError::LengthExceedsLimit);
-    }
+    encode_impl_base2(bit_vec, Version::Base2)
+}
 
-    let raw_slice = bit_vec.as_raw_slice();
-    let capacity = HEADER_LEN
-        .checked_add(raw_slice.len())
+/// Encodes a single boolean vector using Base2 encoding, with the wide
+/// format if the vector is longer than `u16::MAX` bits.
+///
+/// The output is the same as the one of [`encode_base2`] for vectors of up
+/// to `u16::MAX` bits, and is prefixed with the `Version::Base2Wide` byte
+/// otherwise.
+#[cfg(feature = "alloc")]
+pub fn encode_base2_auto(bit_vec: &BitVec<u8, Lsb0>) -> Result<Vec<u8>, EncodeError> {
+    let version = if bit_vec.len() > u16::MAX as usize {
+        Version::Base2Wide
+    } else {
+        Version::Base2
+    };
+    encode_impl_base2(bit_vec, version)
+}
+
+// Internal function to encode the header of `version` for `num_bits`
+#[cfg(feature = "alloc")]
+fn encode_header(
+    version: Version,
+    num_bits: usize,
+    payload_len: usize,
+) -> Result<Vec<u8>, EncodeError> {
+    let capacity = version
+        .header_len()
+        .checked_add(payload_len)
         .ok_or(EncodeError::ArithmeticOverflow)?;
     let mut result = Vec::with_capacity(capacity);
-    result.push(Version::Base2 as u8);
-    result.extend_from_slice(&(num_bits as u16).to_le_bytes());
+    match version {
+        Version::Base2 | Version::Base3 => {
+            let num_bits = u16::try_from(num_bits).map_err(|_| EncodeError::LengthExceedsLimit)?;
+            result.push(version as u8);
+            result.extend_from_slice(&num_bits.to_le_bytes());
+        }
+        Version::Base2Wide | Version::Base3Wide => {
+            let num_bits = u32::try_from(num_bits).map_err(|_| EncodeError::LengthExceedsLimit)?;
+            result.push(version as u8);
+            result.extend_from_slice(&num_bits.to_le_bytes());
+        }
+    }
+    Ok(result)
+}
+
+// Internal function to handle Base2 encoding logic
+#[cfg(feature = "alloc")]
+fn encode_impl_base2(bit_vec: &BitVec<u8, Lsb0>, version: Version) -> Result<Vec<u8>, EncodeError> {
+    let raw_slice = bit_vec.as_raw_slice();
+    let mut result = encode_header(version, bit_vec.len(), raw_slice.len())?;
     result.extend_from_slice(raw_slice);
 
     Ok(result)
@@ -98,29 +199,53 @@ pub fn encode_base2(bit_vec: &BitVec<u8, Lsb0>) -> Result<Vec<u8>, EncodeError>
 /// This function assumes that for any given index, `bit_vec_base` and
 /// `bit_vec_fallback` will not both have a bit set to `1`.
 /// The output `Vec<u8>` is prefixed with the `Version::Base3` byte.
+///
+/// Returns [`EncodeError::LengthExceedsLimit`] if the vectors are longer than
+/// `u16::MAX` bits, see [`encode_base3_auto`].
+#[cfg(feature = "alloc")]
 pub fn encode_base3(
     bit_vec_base: &BitVec<u8, Lsb0>,
     bit_vec_fallback: &BitVec<u8, Lsb0>,
+) -> Result<Vec<u8>, EncodeError> {
+    encode_impl_base3(bit_vec_base, bit_vec_fallback, Version::Base3)
+}
+
+/// Encodes two boolean vectors using Base3 encoding, with the wide format
+/// if the vectors are longer than `u16::MAX` bits.
+///
+/// The output is the same as the one of [`encode_base3`] for vectors of up
+/// to `u16::MAX` bits, and is prefixed with the `Version::Base3Wide` byte
+/// otherwise.
+#[cfg(feature = "alloc")]
+pub fn encode_base3_auto(
+    bit_vec_base: &BitVec<u8, Lsb0>,
+    bit_vec_fallback: &BitVec<u8, Lsb0>,
+) -> Result<Vec<u8>, EncodeError> {
+    let version = if bit_vec_base.len() > u16::MAX as usize {
+        Version::Base3Wide
+    } else {
+        Version::Base3
+    };
+    encode_impl_base3(bit_vec_base, bit_vec_fallback, version)
+}
+
+// Internal function to handle Base3 encoding logic
+#[cfg(feature = "alloc")]
+fn encode_impl_base3(
+    bit_vec_base: &BitVec<u8, Lsb0>,
+    bit_vec_fallback: &BitVec<u8, Lsb0>,
+    version: Version,
 ) -> Result<Vec<u8>, EncodeError> {
     if bit_vec_base.len() != bit_vec_fallback.len() {
         return Err(EncodeError::MismatchedLengths);
     }
     let num_bits = bit_vec_base.len();
-    if num_bits > u16::MAX as usize {
-        return Err(EncodeError::LengthExceedsLimit);
-    }
 
     let base_bytes = bit_vec_base.as_raw_slice();
     let fallback_bytes = bit_vec_fallback.as_raw_slice();
 
     let num_chunks = num_bits.div_ceil(BASE3_SYMBOLS_PER_BYTE);
-    let capacity = HEADER_LEN
-        .checked_add(num_chunks)
-        .ok_or(EncodeError::ArithmeticOverflow)?;
-    let mut result = Vec::with_capacity(capacity);
-
-    result.push(Version::Base3 as u8);
-    result.extend_from_slice(&(num_bits as u16).to_le_bytes());
+    let mut result = encode_header(version, num_bits, num_chunks)?;
 
     for chunk_index in 0..num_chunks {
         let mut block_num: u8 = 0;
@@ -158,6 +283,7 @@ pub fn encode_base3(
 }
 
 /// Represents the result of a decoding operation.
+#[cfg(feature = "alloc")]
 #[derive(Debug, PartialEq, Eq)]
 pub enum Decoded {
     /// A single vector from Base2 decoding.
@@ -167,24 +293,100 @@ pub enum Decoded {
 }
 
 /// An error that can occur during the decoding process.
-#[derive(Error, Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq, Eq)]
 pub enum DecodeError {
-    #[error("the input slice is too short to be valid")]
     InputTooShort,
-    #[error("the encoding version byte is unsupported")]
     UnsupportedEncoding,
-    #[error("the data payload is not of the expected length")]
     CorruptDataPayload,
-    #[error("an arithmetic operation resulted in an overflow")]
     ArithmeticOverflow,
+    OutputTooSmall,
+}
+
+impl core::error::Error for DecodeError {}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::InputTooShort => f.write_str("the input slice is too short to be valid"),
+            Self::UnsupportedEncoding => f.write_str("the encoding version byte is unsupported"),
+            Self::CorruptDataPayload => {
+                f.write_str("the data payload is not of the expected length")
+            }
+            Self::ArithmeticOverflow => {
+                f.write_str("an arithmetic operation resulted in an overflow")
+            }
+            Self::OutputTooSmall => f.write_str("the output buffer is too small"),
+        }
+    }
 }
 
 /// Decodes an encoded byte slice into one or two boolean vectors.
 ///
 /// It reads the first byte to determine the encoding scheme and then decodes
-/// the rest of the data accordingly.
+/// the rest of the data accordingly. The wide versions decode like their
+/// narrow counterparts. Encodings of more than `max_len` bits are rejected
+/// with [`DecodeError::CorruptDataPayload`].
+#[cfg(feature = "alloc")]
 pub fn decode(bytes: &[u8], max_len: usize) -> Result<Decoded, DecodeError> {
-    if bytes.len() < 3 {
+    let (version, data_bytes, total_bits) = decode_header(bytes, max_len)?;
+
+    match version {
+        Version::Base2 | Version::Base2Wide => decode_impl_base2(data_bytes, total_bits),
+        Version::Base3 | Version::Base3Wide => decode_impl_base3(data_bytes, total_bits),
+    }
+}
+
+/// Decodes an encoded byte slice into caller-provided buffers, without
+/// allocating.
+///
+/// On success, returns the number of decoded bits `num_bits`. The bits are
+/// written in `Lsb0` order into the first `num_bits.div_ceil(8)` bytes of
+/// `base_out` and `fallback_out`, with the unused bits of the last byte
+/// cleared; the rest of the buffers is left untouched. A Base2 encoding
+/// decodes into `base_out`, and clears the bytes of `fallback_out`.
+///
+/// Returns [`DecodeError::OutputTooSmall`] if either buffer is shorter than
+/// `num_bits.div_ceil(8)` bytes.
+pub fn decode_to_bytes_into(
+    bytes: &[u8],
+    base_out: &mut [u8],
+    fallback_out: &mut [u8],
+    max_len: usize,
+) -> Result<usize, DecodeError> {
+    let (version, data_bytes, total_bits) = decode_header(bytes, max_len)?;
+
+    let decoded_byte_len = total_bits.div_ceil(8);
+    let (Some(base_out), Some(fallback_out)) = (
+        base_out.get_mut(..decoded_byte_len),
+        fallback_out.get_mut(..decoded_byte_len),
+    ) else {
+        return Err(DecodeError::OutputTooSmall);
+    };
+
+    match version {
+        Version::Base2 | Version::Base2Wide => {
+            if data_bytes.len() != decoded_byte_len {
+                return Err(DecodeError::CorruptDataPayload);
+            }
+            base_out.copy_from_slice(data_bytes);
+            fallback_out.fill(0);
+            if total_bits % 8 != 0 {
+                // Clear the padding bits of the last byte
+                base_out[decoded_byte_len - 1] &= (1 << (total_bits % 8)) - 1;
+            }
+        }
+        Version::Base3 | Version::Base3Wide => {
+            decode_base3_into(data_bytes, total_bits, base_out, fallback_out)?
+        }
+    }
+
+    Ok(total_bits)
+}
+
+// Internal function to parse the header, returning the version, the data
+// payload and the number of encoded bits
+fn decode_header(bytes: &[u8], max_len: usize) -> Result<(Version, &[u8], usize), DecodeError> {
+    if bytes.len() < HEADER_LEN {
         // Must have at least version (1) + length (2)
         return Err(DecodeError::InputTooShort);
     }
@@ -192,23 +394,26 @@ pub fn decode(bytes: &[u8], max_len: usize) -> Result<Decoded, DecodeError> {
     let version_byte = bytes[0];
     let version = Version::from_u8(version_byte).ok_or(DecodeError::UnsupportedEncoding)?;
 
-    let mut len_arr = [0u8; 2];
-    len_arr.copy_from_slice(&bytes[1..3]);
-    let total_bits = u16::from_le_bytes(len_arr) as usize;
+    let header_len = version.header_len();
+    let len_bytes = bytes
+        .get(VERSION_BYTE_LEN..header_len)
+        .ok_or(DecodeError::InputTooShort)?;
+    let total_bits = match *len_bytes {
+        [b0, b1] => u16::from_le_bytes([b0, b1]) as usize,
+        [b0, b1, b2, b3] => usize::try_from(u32::from_le_bytes([b0, b1, b2, b3]))
+            .map_err(|_| DecodeError::CorruptDataPayload)?,
+        _ => unreachable!(), // The length prefix is 2 or 4 bytes
+    };
 
     if total_bits > max_len {
         return Err(DecodeError::CorruptDataPayload);
     }
 
-    let data_bytes = &bytes[3..];
-
-    match version {
-        Version::Base2 => decode_impl_base2(data_bytes, total_bits),
-        Version::Base3 => decode_impl_base3(data_bytes, total_bits),
-    }
+    Ok((version, &bytes[header_len..], total_bits))
 }
 
 // Internal function to handle Base2 decoding logic
+#[cfg(feature = "alloc")]
 fn decode_impl_base2(data_bytes: &[u8], total_bits: usize) -> Result<Decoded, DecodeError> {
     let expected_byte_len = total_bits.div_ceil(8);
     if data_bytes.len() != expected_byte_len {
@@ -222,16 +427,37 @@ fn decode_impl_base2(data_bytes: &[u8], total_bits: usize) -> Result<Decoded, De
 }
 
 // Internal function to handle Base3 decoding logic
+#[cfg(feature = "alloc")]
 fn decode_impl_base3(data_bytes: &[u8], total_bits: usize) -> Result<Decoded, DecodeError> {
+    let decoded_byte_len = total_bits.div_ceil(8);
+    let mut base_bytes = vec![0u8; decoded_byte_len];
+    let mut fallback_bytes = vec![0u8; decoded_byte_len];
+    decode_base3_into(data_bytes, total_bits, &mut base_bytes, &mut fallback_bytes)?;
+
+    let mut base_vec = BitVec::from_vec(base_bytes);
+    base_vec.truncate(total_bits);
+    let mut fallback_vec = BitVec::from_vec(fallback_bytes);
+    fallback_vec.truncate(total_bits);
+
+    Ok(Decoded::Base3(base_vec, fallback_vec))
+}
+
+// Internal function to unpack the base-3 digits into output buffers of
+// `total_bits.div_ceil(8)` bytes
+fn decode_base3_into(
+    data_bytes: &[u8],
+    total_bits: usize,
+    base_bytes: &mut [u8],
+    fallback_bytes: &mut [u8],
+) -> Result<(), DecodeError> {
     let expected_num_chunks = total_bits.div_ceil(BASE3_SYMBOLS_PER_BYTE);
 
     if data_bytes.len() != expected_num_chunks {
         return Err(DecodeError::CorruptDataPayload);
     }
 
-    let decoded_byte_len = total_bits.div_ceil(8);
-    let mut base_bytes = vec![0u8; decoded_byte_len];
-    let mut fallback_bytes = vec![0u8; decoded_byte_len];
+    base_bytes.fill(0);
+    fallback_bytes.fill(0);
 
     for (chunk_index, &block_byte) in data_bytes.iter().enumerate() {
         let mut block_num = block_byte;
@@ -266,12 +492,7 @@ fn decode_impl_base3(data_bytes: &[u8], total_bits: usize) -> Result<Decoded, De
         }
     }
 
-    let mut base_vec = BitVec::from_vec(base_bytes);
-    base_vec.truncate(total_bits);
-    let mut fallback_vec = BitVec::from_vec(fallback_bytes);
-    fallback_vec.truncate(total_bits);
-
-    Ok(Decoded::Base3(base_vec, fallback_vec))
+    Ok(())
 }
 
 #[cfg(test)]
@@ -389,7 +610,7 @@ mod tests {
 
     #[test]
     fn test_decode_unsupported_encoding() {
-        let bytes = vec![2, 0, 0, 1, 2, 3]; // Invalid version byte '2'
+        let bytes = vec![4, 0, 0, 1, 2, 3]; // Invalid version byte '4'
         let result = decode(&bytes, 10);
         assert_eq!(result, Err(DecodeError::UnsupportedEncoding));
     }
@@ -418,4 +639,231 @@ mod tests {
         let result = decode(&encoded, 10);
         assert_eq!(result, Err(DecodeError::CorruptDataPayload));
     }
+
+    #[test]
+    fn test_decode_to_bytes_into_base2() {
+        let original = bitvec![u8, Lsb0; 0, 1, 1, 0, 1, 0, 0, 1, 1, 1, 0, 0, 1];
+        let mut encoded = encode_base2(&original).unwrap();
+        // Set a padding bit, which must not be decoded
+        *encoded.last_mut().unwrap() |= 0x80;
+
+        let mut base = [0xff; 4];
+        let mut fallback = [0xff; 4];
+        let num_bits = decode_to_bytes_into(&encoded, &mut base, &mut fallback, 13).unwrap();
+        assert_eq!(num_bits, 13);
+        let mut expected = original.clone().into_vec();
+        expected.extend_from_slice(&[0xff; 2]);
+        assert_eq!(base.as_slice(), expected);
+        assert_eq!(fallback, [0, 0, 0xff, 0xff]);
+    }
+
+    #[test]
+    fn test_decode_to_bytes_into_base3() {
+        for len in [0, 1, 8, 10, 23, 40] {
+            let (base, fallback) = create_base3_test_data(len);
+            let encoded = encode_base3(&base, &fallback).unwrap();
+
+            let mut base_out = [0xff; 8];
+            let mut fallback_out = [0xff; 8];
+            let num_bits =
+                decode_to_bytes_into(&encoded, &mut base_out, &mut fallback_out, len).unwrap();
+            assert_eq!(num_bits, len);
+
+            let byte_len = len.div_ceil(8);
+            assert_eq!(&base_out[..byte_len], base.as_raw_slice());
+            assert_eq!(&fallback_out[..byte_len], fallback.as_raw_slice());
+            assert!(base_out[byte_len..].iter().all(|&b| b == 0xff));
+            assert!(fallback_out[byte_len..].iter().all(|&b| b == 0xff));
+            assert_eq!(
+                decode(&encoded, len).unwrap(),
+                Decoded::Base3(
+                    BitVec::from_slice(&base_out[..byte_len])[..len].to_bitvec(),
+                    BitVec::from_slice(&fallback_out[..byte_len])[..len].to_bitvec(),
+                )
+            );
+        }
+    }
+
+    #[test]
+    fn test_decode_to_bytes_into_output_too_small() {
+        let (base, fallback) = create_base3_test_data(17);
+        let encoded = encode_base3(&base, &fallback).unwrap();
+        let mut short = [0; 2];
+        let mut exact = [0; 3];
+        assert_eq!(
+            decode_to_bytes_into(&encoded, &mut short, &mut exact, 17),
+            Err(DecodeError::OutputTooSmall)
+        );
+        assert_eq!(
+            decode_to_bytes_into(&encoded, &mut exact, &mut short, 17),
+            Err(DecodeError::OutputTooSmall)
+        );
+        assert_eq!(
+            decode_to_bytes_into(&encode_base2(&base).unwrap(), &mut exact, &mut [], 17),
+            Err(DecodeError::OutputTooSmall)
+        );
+        let mut other = [0; 3];
+        assert_eq!(
+            decode_to_bytes_into(&encoded, &mut exact, &mut other, 17),
+            Ok(17)
+        );
+    }
+
+    #[test]
+    fn test_decode_to_bytes_into_invalid_input() {
+        let mut base = [0; 4];
+        let mut fallback = [0; 4];
+        assert_eq!(
+            decode_to_bytes_into(&[1, 0], &mut base, &mut fallback, 10),
+            Err(DecodeError::InputTooShort)
+        );
+        assert_eq!(
+            decode_to_bytes_into(&[4, 0, 0], &mut base, &mut fallback, 10),
+            Err(DecodeError::UnsupportedEncoding)
+        );
+        let (bits, _) = create_base3_test_data(20);
+        let mut encoded = encode_base2(&bits).unwrap();
+        assert_eq!(
+            decode_to_bytes_into(&encoded, &mut base, &mut fallback, 19),
+            Err(DecodeError::CorruptDataPayload)
+        );
+        encoded.pop();
+        assert_eq!(
+            decode_to_bytes_into(&encoded, &mut base, &mut fallback, 20),
+            Err(DecodeError::CorruptDataPayload)
+        );
+    }
+
+    #[test]
+    fn test_auto_narrow_boundary() {
+        let max_narrow = u16::MAX as usize;
+        let bits = BitVec::repeat(true, max_narrow);
+        let encoded = encode_base2_auto(&bits).unwrap();
+        assert_eq!(encoded, encode_base2(&bits).unwrap());
+        assert_eq!(decode(&encoded, max_narrow).unwrap(), Decoded::Base2(bits));
+
+        let (base, fallback) = create_base3_test_data(max_narrow);
+        let encoded = encode_base3_auto(&base, &fallback).unwrap();
+        assert_eq!(encoded, encode_base3(&base, &fallback).unwrap());
+        assert_eq!(
+            decode(&encoded, max_narrow).unwrap(),
+            Decoded::Base3(base, fallback)
+        );
+    }
+
+    #[test]
+    fn test_auto_wide_boundary() {
+        let min_wide = u16::MAX as usize + 1;
+        let bits = BitVec::repeat(true, min_wide);
+        assert_eq!(encode_base2(&bits), Err(EncodeError::LengthExceedsLimit));
+        let encoded = encode_base2_auto(&bits).unwrap();
+        assert_eq!(encoded[0], Version::Base2Wide as u8);
+        assert_eq!(
+            u32::from_le_bytes(encoded[1..5].try_into().unwrap()),
+            min_wide as u32
+        );
+        assert_eq!(encoded.len(), WIDE_HEADER_LEN + min_wide / 8);
+        assert_eq!(decode(&encoded, min_wide).unwrap(), Decoded::Base2(bits));
+
+        let (base, fallback) = create_base3_test_data(min_wide);
+        assert_eq!(
+            encode_base3(&base, &fallback),
+            Err(EncodeError::LengthExceedsLimit)
+        );
+        let encoded = encode_base3_auto(&base, &fallback).unwrap();
+        assert_eq!(encoded[0], Version::Base3Wide as u8);
+        assert_eq!(
+            u32::from_le_bytes(encoded[1..5].try_into().unwrap()),
+            min_wide as u32
+        );
+        assert_eq!(
+            decode(&encoded, min_wide).unwrap(),
+            Decoded::Base3(base, fallback)
+        );
+    }
+
+    #[test]
+    fn test_auto_small_inputs_unchanged() {
+        let bits = bitvec![u8, Lsb0; 0, 1, 1, 0, 1];
+        assert_eq!(
+            encode_base2_auto(&bits).unwrap(),
+            vec![Version::Base2 as u8, 5, 0, 0b10110]
+        );
+        let (base, fallback) = create_base3_test_data(0);
+        assert_eq!(
+            encode_base3_auto(&base, &fallback).unwrap(),
+            vec![Version::Base3 as u8, 0, 0]
+        );
+        assert_eq!(
+            encode_base3_auto(&bitvec![u8, Lsb0; 1], &bitvec![u8, Lsb0; 1]),
+            Err(EncodeError::InvalidBitCombination)
+        );
+    }
+
+    #[test]
+    fn test_wide_round_trip_100k() {
+        let len = 100_000;
+        let bits: BitVec<u8, Lsb0> = (0..len).map(|i| i % 7 == 0).collect();
+        let encoded = encode_base2_auto(&bits).unwrap();
+        assert_eq!(decode(&encoded, len).unwrap(), Decoded::Base2(bits));
+
+        let (base, fallback) = create_base3_test_data(len);
+        let encoded = encode_base3_auto(&base, &fallback).unwrap();
+        assert_eq!(
+            encoded.len(),
+            WIDE_HEADER_LEN + len / BASE3_SYMBOLS_PER_BYTE
+        );
+        assert_eq!(
+            decode(&encoded, len).unwrap(),
+            Decoded::Base3(base.clone(), fallback.clone())
+        );
+
+        let mut base_out = vec![0; len / 8];
+        let mut fallback_out = vec![0; len / 8];
+        assert_eq!(
+            decode_to_bytes_into(&encoded, &mut base_out, &mut fallback_out, len),
+            Ok(len)
+        );
+        assert_eq!(base_out, base.as_raw_slice());
+        assert_eq!(fallback_out, fallback.as_raw_slice());
+    }
+
+    #[test]
+    fn test_decode_wide_max_len_exceeded() {
+        let len = 100_000;
+        let bits = BitVec::repeat(false, len);
+        let encoded = encode_base2_auto(&bits).unwrap();
+        assert_eq!(
+            decode(&encoded, len - 1),
+            Err(DecodeError::CorruptDataPayload)
+        );
+        assert_eq!(
+            decode(&encoded, u16::MAX as usize),
+            Err(DecodeError::CorruptDataPayload)
+        );
+
+        // A wide header claiming more bits than allowed, without a payload
+        let mut header = vec![Version::Base3Wide as u8];
+        header.extend_from_slice(&u32::MAX.to_le_bytes());
+        assert_eq!(decode(&header, len), Err(DecodeError::CorruptDataPayload));
+        assert_eq!(
+            decode_to_bytes_into(&header, &mut [], &mut [], len),
+            Err(DecodeError::CorruptDataPayload)
+        );
+    }
+
+    #[test]
+    fn test_decode_wide_input_too_short() {
+        // A narrow header is too short for the wide versions
+        for version in [Version::Base2Wide, Version::Base3Wide] {
+            assert_eq!(
+                decode(&[version as u8, 0, 0, 0], 10),
+                Err(DecodeError::InputTooShort)
+            );
+        }
+        assert_eq!(
+            decode(&[Version::Base2Wide as u8, 0, 0, 0, 0], 10),
+            Ok(Decoded::Base2(BitVec::new()))
+        );
+    }
 }
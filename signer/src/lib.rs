@@ -15,6 +15,8 @@ use {
 };
 
 pub mod null_signer;
+#[cfg(feature = "dev-context-only-utils")]
+pub mod programmable_signer;
 pub mod signers;
 
 #[derive(Debug, PartialEq, Eq)]
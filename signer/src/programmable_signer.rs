@@ -0,0 +1,192 @@
+//! A keypair-less `Signer` test double with scripted behavior.
+
+use {
+    crate::{Signer, SignerError},
+    solana_pubkey::Pubkey,
+    solana_signature::Signature,
+    std::{fmt, sync::Arc},
+};
+
+type SignFn = Arc<dyn Fn(&[u8]) -> Signature + Send + Sync>;
+type ErrorFn = Arc<dyn Fn() -> SignerError + Send + Sync>;
+
+#[derive(Clone)]
+enum Behavior {
+    Fixed(Signature),
+    Closure(SignFn),
+    Fail(ErrorFn),
+}
+
+/// ProgrammableSigner - A `Signer` implementation for tests that produces
+/// canned signatures without holding any key material.
+///
+/// Depending on how it is constructed, it always returns the same signature,
+/// computes the signature from the message with a closure, or fails every
+/// signing attempt with a `SignerError`.
+#[derive(Clone)]
+pub struct ProgrammableSigner {
+    pubkey: Pubkey,
+    behavior: Behavior,
+    is_interactive: bool,
+}
+
+impl ProgrammableSigner {
+    /// Creates a signer that signs every message with `signature`.
+    pub fn new_fixed(pubkey: Pubkey, signature: Signature) -> Self {
+        Self::new(pubkey, Behavior::Fixed(signature))
+    }
+
+    /// Creates a signer that signs messages with `sign`.
+    pub fn new_with_fn<F>(pubkey: Pubkey, sign: F) -> Self
+    where
+        F: Fn(&[u8]) -> Signature + Send + Sync + 'static,
+    {
+        Self::new(pubkey, Behavior::Closure(Arc::new(sign)))
+    }
+
+    /// Creates a signer whose signing attempts fail with the error returned
+    /// by `error`.
+    pub fn new_failing<F>(pubkey: Pubkey, error: F) -> Self
+    where
+        F: Fn() -> SignerError + Send + Sync + 'static,
+    {
+        Self::new(pubkey, Behavior::Fail(Arc::new(error)))
+    }
+
+    fn new(pubkey: Pubkey, behavior: Behavior) -> Self {
+        Self {
+            pubkey,
+            behavior,
+            is_interactive: false,
+        }
+    }
+
+    /// Sets the value reported by [`Signer::is_interactive`].
+    pub fn with_interactive(mut self, is_interactive: bool) -> Self {
+        self.is_interactive = is_interactive;
+        self
+    }
+}
+
+impl fmt::Debug for ProgrammableSigner {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let behavior = match &self.behavior {
+            Behavior::Fixed(signature) => format!("Fixed({signature:?})"),
+            Behavior::Closure(_) => "Closure".to_string(),
+            Behavior::Fail(_) => "Fail".to_string(),
+        };
+        f.debug_struct("ProgrammableSigner")
+            .field("pubkey", &self.pubkey)
+            .field("behavior", &behavior)
+            .field("is_interactive", &self.is_interactive)
+            .finish()
+    }
+}
+
+impl Signer for ProgrammableSigner {
+    fn try_pubkey(&self) -> Result<Pubkey, SignerError> {
+        Ok(self.pubkey)
+    }
+
+    fn try_sign_message(&self, message: &[u8]) -> Result<Signature, SignerError> {
+        match &self.behavior {
+            Behavior::Fixed(signature) => Ok(*signature),
+            Behavior::Closure(sign) => Ok(sign(message)),
+            Behavior::Fail(error) => Err(error()),
+        }
+    }
+
+    fn is_interactive(&self) -> bool {
+        self.is_interactive
+    }
+}
+
+impl<T> PartialEq<T> for ProgrammableSigner
+where
+    T: Signer,
+{
+    fn eq(&self, other: &T) -> bool {
+        self.pubkey == other.pubkey()
+    }
+}
+
+/// Produces batches of [`ProgrammableSigner`]s with distinct addresses.
+pub struct SignerFactory;
+
+impl SignerFactory {
+    /// Creates `count` signers with `Pubkey::new_unique` addresses.
+    ///
+    /// Each signer signs every message with a fixed signature made of its
+    /// own address repeated twice, so signatures are distinct per signer and
+    /// stable across calls.
+    pub fn new_unique(count: usize) -> Vec<ProgrammableSigner> {
+        Self::new_unique_with(count, |pubkey| {
+            let mut signature = [0u8; 64];
+            signature[..32].copy_from_slice(pubkey.as_ref());
+            signature[32..].copy_from_slice(pubkey.as_ref());
+            ProgrammableSigner::new_fixed(*pubkey, Signature::from(signature))
+        })
+    }
+
+    /// Creates `count` signers with `Pubkey::new_unique` addresses, building
+    /// each one with `make_signer`.
+    pub fn new_unique_with<F>(count: usize, make_signer: F) -> Vec<ProgrammableSigner>
+    where
+        F: Fn(&Pubkey) -> ProgrammableSigner,
+    {
+        (0..count)
+            .map(|_| make_signer(&Pubkey::new_unique()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fixed() {
+        let pubkey = Pubkey::new_unique();
+        let signer = ProgrammableSigner::new_fixed(pubkey, Signature::from([7; 64]));
+        assert_eq!(signer.pubkey(), pubkey);
+        assert_eq!(signer.try_pubkey(), Ok(pubkey));
+        assert_eq!(signer.try_sign_message(b"a"), Ok(Signature::from([7; 64])));
+        assert_eq!(signer.sign_message(b"b"), Signature::from([7; 64]));
+        assert!(!signer.is_interactive());
+        assert!(signer.with_interactive(true).is_interactive());
+    }
+
+    #[test]
+    fn test_closure() {
+        let signer = ProgrammableSigner::new_with_fn(Pubkey::new_unique(), |message| {
+            Signature::from([message.len() as u8; 64])
+        });
+        assert_eq!(
+            signer.try_sign_message(b"abc"),
+            Ok(Signature::from([3; 64]))
+        );
+        assert_eq!(signer.sign_message(b""), Signature::from([0; 64]));
+    }
+
+    #[test]
+    fn test_failing() {
+        let signer = ProgrammableSigner::new_failing(Pubkey::new_unique(), || {
+            SignerError::UserCancel("rejected".to_string())
+        });
+        assert_eq!(
+            signer.try_sign_message(b"abc"),
+            Err(SignerError::UserCancel("rejected".to_string()))
+        );
+        assert_eq!(signer.sign_message(b"abc"), Signature::default());
+    }
+
+    #[test]
+    fn test_factory() {
+        let signers = SignerFactory::new_unique(3);
+        assert_eq!(signers.len(), 3);
+        assert_ne!(signers[0].pubkey(), signers[1].pubkey());
+        assert_ne!(signers[1].pubkey(), signers[2].pubkey());
+        assert_ne!(signers[0].sign_message(b""), signers[1].sign_message(b""));
+        assert_eq!(signers[0].sign_message(b"a"), signers[0].sign_message(b"b"));
+    }
+}
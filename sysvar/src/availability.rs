@@ -0,0 +1,251 @@
+//! Runtime detection of sysvars that are not available.
+//!
+//! Deprecated sysvars such as [`Fees`] are being removed from clusters.
+//! Programs can check whether a sysvar is present with [`is_sysvar_present`]
+//! before depending on it, and [`Sysvar::get`] of the deprecated sysvars logs
+//! why it failed before returning [`ProgramError::UnsupportedSysvar`].
+//!
+//! The recent blockhashes sysvar is too large to be loaded with a syscall, so
+//! its presence cannot be detected: programs still using it must receive its
+//! account.
+//!
+//! On non-SBF targets, the presence of sysvars is decided by the installed
+//! [`SyscallStubs`]: returning [`SYSVAR_NOT_FOUND`] from
+//! [`SyscallStubs::sol_get_sysvar`] simulates an absent sysvar.
+//!
+//! [`Fees`]: crate::fees::Fees
+//! [`Sysvar::get`]: crate::Sysvar::get
+//! [`SyscallStubs`]: crate::program_stubs::SyscallStubs
+//! [`SyscallStubs::sol_get_sysvar`]: crate::program_stubs::SyscallStubs::sol_get_sysvar
+
+#[allow(deprecated)]
+use crate::fees::{self, Fees};
+use {
+    crate::{OFFSET_LENGTH_EXCEEDS_SYSVAR, SYSVAR_NOT_FOUND},
+    solana_program_entrypoint::SUCCESS,
+    solana_program_error::ProgramError,
+    solana_pubkey::Pubkey,
+};
+
+/// Returns whether the sysvar `sysvar_id` can be loaded from the runtime.
+///
+/// The sysvar is probed with a zero-length `sol_get_sysvar` syscall, except
+/// for the fees sysvar, which is probed with its dedicated syscall. Sysvars
+/// that the runtime only exposes as accounts, such as the recent blockhashes
+/// sysvar, are reported as absent.
+///
+/// # Errors
+///
+/// Returns the error matching the result of the syscall if it is neither a
+/// success nor a missing sysvar.
+pub fn is_sysvar_present(sysvar_id: &Pubkey) -> Result<bool, ProgramError> {
+    if fees::check_id(sysvar_id) {
+        return Ok(get_fees().is_ok());
+    }
+
+    let sysvar_id = sysvar_id as *const _ as *const u8;
+    let mut probe = 0u8;
+    let var_addr = &mut probe as *mut u8;
+
+    #[cfg(target_os = "solana")]
+    let result =
+        unsafe { solana_define_syscall::definitions::sol_get_sysvar(sysvar_id, var_addr, 0, 0) };
+
+    #[cfg(not(target_os = "solana"))]
+    let result = crate::program_stubs::sol_get_sysvar(sysvar_id, var_addr, 0, 0);
+
+    match result {
+        // the sysvar was found, even if it has no data
+        SUCCESS | OFFSET_LENGTH_EXCEEDS_SYSVAR => Ok(true),
+        SYSVAR_NOT_FOUND => Ok(false),
+        error => Err(ProgramError::from(error)),
+    }
+}
+
+/// Load the fees sysvar with its dedicated syscall.
+#[allow(deprecated)]
+pub(crate) fn get_fees() -> Result<Fees, ProgramError> {
+    let mut var = Fees::default();
+    let var_addr = &mut var as *mut _ as *mut u8;
+
+    #[cfg(target_os = "solana")]
+    let result = unsafe { solana_define_syscall::definitions::sol_get_fees_sysvar(var_addr) };
+
+    #[cfg(not(target_os = "solana"))]
+    let result = crate::program_stubs::sol_get_fees_sysvar(var_addr);
+
+    match result {
+        SUCCESS => Ok(var),
+        _ => Err(ProgramError::UnsupportedSysvar),
+    }
+}
+
+/// Log `message` from the program.
+pub(crate) fn log(message: &str) {
+    #[cfg(target_os = "solana")]
+    unsafe {
+        solana_define_syscall::definitions::sol_log_(message.as_ptr(), message.len() as u64)
+    };
+
+    #[cfg(not(target_os = "solana"))]
+    crate::program_stubs::sol_log(message);
+}
+
+#[cfg(test)]
+#[allow(deprecated)]
+mod tests {
+    use {
+        super::*,
+        crate::{
+            program_stubs::{copy_sysvar_data, set_syscall_stubs, SyscallStubs},
+            recent_blockhashes::RecentBlockhashes,
+            tests::to_bytes,
+            Sysvar,
+        },
+        serial_test::serial,
+        solana_clock::Clock,
+        solana_fee_calculator::FeeCalculator,
+        std::sync::{Arc, Mutex},
+    };
+
+    /// Serves the sysvars of `sysvars`, reporting the others as missing.
+    struct ClusterStubs {
+        sysvars: Vec<(Pubkey, Vec<u8>)>,
+        logs: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl ClusterStubs {
+        fn install(sysvars: Vec<(Pubkey, Vec<u8>)>) -> Arc<Mutex<Vec<String>>> {
+            let logs = Arc::default();
+            set_syscall_stubs(Box::new(Self {
+                sysvars,
+                logs: Arc::clone(&logs),
+            }));
+            logs
+        }
+
+        fn data(&self, sysvar_id: &Pubkey) -> Option<&[u8]> {
+            self.sysvars
+                .iter()
+                .find(|(id, _)| id == sysvar_id)
+                .map(|(_, data)| data.as_slice())
+        }
+    }
+
+    impl SyscallStubs for ClusterStubs {
+        fn sol_log(&self, message: &str) {
+            self.logs.lock().unwrap().push(message.to_string());
+        }
+
+        fn sol_get_sysvar(
+            &self,
+            sysvar_id_addr: *const u8,
+            var_addr: *mut u8,
+            offset: u64,
+            length: u64,
+        ) -> u64 {
+            let sysvar_id = unsafe { &*(sysvar_id_addr as *const Pubkey) };
+            let Some(data) = self.data(sysvar_id) else {
+                return SYSVAR_NOT_FOUND;
+            };
+            copy_sysvar_data(data, var_addr, offset, length)
+        }
+
+        fn sol_get_fees_sysvar(&self, var_addr: *mut u8) -> u64 {
+            let Some(data) = self.data(&fees::ID) else {
+                return SYSVAR_NOT_FOUND;
+            };
+            unsafe { std::ptr::copy_nonoverlapping(data.as_ptr(), var_addr, data.len()) };
+            SUCCESS
+        }
+    }
+
+    fn fees() -> Fees {
+        Fees::new(&FeeCalculator {
+            lamports_per_signature: 5_000,
+        })
+    }
+
+    fn clock() -> Clock {
+        Clock {
+            slot: 1,
+            epoch_start_timestamp: 2,
+            epoch: 3,
+            leader_schedule_epoch: 4,
+            unix_timestamp: 5,
+        }
+    }
+
+    #[test]
+    #[serial]
+    fn test_present_sysvars() {
+        let logs = ClusterStubs::install(vec![
+            (fees::ID, to_bytes(&fees())),
+            (crate::clock::ID, to_bytes(&clock())),
+        ]);
+
+        assert_eq!(is_sysvar_present(&fees::ID), Ok(true));
+        assert_eq!(is_sysvar_present(&crate::clock::ID), Ok(true));
+        assert_eq!(Fees::get(), Ok(fees()));
+        assert_eq!(Clock::get(), Ok(clock()));
+        assert!(logs.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    #[serial]
+    fn test_absent_sysvars() {
+        let logs = ClusterStubs::install(vec![]);
+
+        assert_eq!(is_sysvar_present(&fees::ID), Ok(false));
+        assert_eq!(is_sysvar_present(&crate::clock::ID), Ok(false));
+        assert_eq!(Fees::get(), Err(ProgramError::UnsupportedSysvar));
+        assert_eq!(Clock::get(), Err(ProgramError::UnsupportedSysvar));
+        assert_eq!(
+            *logs.lock().unwrap(),
+            ["Fees sysvar is not available on this cluster"]
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn test_recent_blockhashes() {
+        let logs = ClusterStubs::install(vec![]);
+        assert_eq!(
+            RecentBlockhashes::get(),
+            Err(ProgramError::UnsupportedSysvar)
+        );
+        assert_eq!(
+            *logs.lock().unwrap(),
+            ["RecentBlockhashes sysvar cannot be loaded with `Sysvar::get`, pass its account instead"]
+        );
+        assert_eq!(is_sysvar_present(&crate::recent_blockhashes::ID), Ok(false));
+    }
+
+    #[test]
+    #[serial]
+    fn test_probe_results() {
+        // a sysvar without data still exists
+        ClusterStubs::install(vec![(crate::clock::ID, vec![])]);
+        assert_eq!(is_sysvar_present(&crate::clock::ID), Ok(true));
+        assert_eq!(Clock::get(), Err(ProgramError::UnsupportedSysvar));
+
+        struct FailingStubs;
+        impl SyscallStubs for FailingStubs {
+            fn sol_get_sysvar(&self, _: *const u8, _: *mut u8, _: u64, _: u64) -> u64 {
+                solana_instruction::error::ACCOUNT_BORROW_FAILED
+            }
+        }
+        set_syscall_stubs(Box::new(FailingStubs));
+        assert_eq!(
+            is_sysvar_present(&crate::clock::ID),
+            Err(ProgramError::AccountBorrowFailed)
+        );
+
+        // the default stubs serve no sysvar
+        set_syscall_stubs(Box::new(crate::program_stubs::DefaultSyscallStubs {}));
+        assert_eq!(
+            is_sysvar_present(&crate::clock::ID),
+            Err(ProgramError::UnsupportedSysvar)
+        );
+    }
+}
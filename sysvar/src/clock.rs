@@ -70,7 +70,7 @@
 //!
 //!     assert!(clock::check_id(clock_account_info.key));
 //!
-//!     let clock = Clock::from_account_info(clock_account_info)?;
+//!     let clock = Clock::from_account_info_checked(clock_account_info)?;
 //!     msg!("clock: {:#?}", clock);
 //!
 //!     Ok(())
@@ -80,7 +80,8 @@
 //! # let p = Clock::id();
 //! # let l = &mut 1169280;
 //! # let d = &mut vec![240, 153, 233, 7, 0, 0, 0, 0, 11, 115, 118, 98, 0, 0, 0, 0, 51, 1, 0, 0, 0, 0, 0, 0, 52, 1, 0, 0, 0, 0, 0, 0, 121, 50, 119, 98, 0, 0, 0, 0];
-//! # let a = AccountInfo::new(&p, false, false, l, d, &p, false);
+//! # let o = solana_sdk_ids::sysvar::ID;
+//! # let a = AccountInfo::new(&p, false, false, l, d, &o, false);
 //! # let accounts = &[a.clone(), a];
 //! # process_instruction(
 //! #     &Pubkey::new_unique(),
@@ -18,6 +18,13 @@
 //! [`EpochRewards`] implements [`Sysvar::get`] and can be loaded efficiently without
 //! passing the sysvar account ID to the program.
 //!
+//! The rewards period is only active during the first blocks of an epoch:
+//! the rewards are calculated in the first block, then distributed over
+//! `num_partitions` blocks, after which `active` is cleared until the next
+//! epoch. Stake accounts are read-only while the period is active, and
+//! stake programs reject the instructions modifying them with
+//! [`check_via_get`].
+//!
 //! See also the Solana [documentation on the epoch rewards sysvar][sdoc].
 //!
 //! [sdoc]: https://docs.solanalabs.com/runtime/sysvars#epochrewards
@@ -88,7 +95,7 @@
 //!
 //!     assert!(epoch_rewards::check_id(epoch_rewards_account_info.key));
 //!
-//!     let epoch_rewards = EpochRewards::from_account_info(epoch_rewards_account_info)?;
+//!     let epoch_rewards = EpochRewards::from_account_info_checked(epoch_rewards_account_info)?;
 //!     msg!("epoch_rewards: {:#?}", epoch_rewards);
 //!
 //!     Ok(())
@@ -105,7 +112,8 @@
 //! #     ..EpochRewards::default()
 //! # };
 //! # let mut d: Vec<u8> = bincode::serialize(&epoch_rewards).unwrap();
-//! # let a = AccountInfo::new(&p, false, false, l, &mut d, &p, false);
+//! # let o = solana_sdk_ids::sysvar::ID;
+//! # let a = AccountInfo::new(&p, false, false, l, &mut d, &o, false);
 //! # let accounts = &[a.clone(), a];
 //! # process_instruction(
 //! #     &Pubkey::new_unique(),
@@ -156,7 +164,10 @@
 
 #[cfg(feature = "bincode")]
 use crate::SysvarSerialize;
-use crate::{impl_sysvar_get, Sysvar};
+use {
+    crate::{impl_sysvar_get, Sysvar},
+    solana_program_error::ProgramResult,
+};
 pub use {
     solana_epoch_rewards::EpochRewards,
     solana_sdk_ids::sysvar::epoch_rewards::{check_id, id, ID},
@@ -173,6 +184,19 @@ impl Sysvar for EpochRewards {
 #[cfg(feature = "bincode")]
 impl SysvarSerialize for EpochRewards {}
 
+/// Loads the sysvar with [`Sysvar::get`], and returns an error if the
+/// rewards distribution is active.
+///
+/// # Errors
+///
+/// Returns the error of `get`, and [`ProgramError::Immutable`] during the
+/// distribution, see [`EpochRewards::assert_distribution_inactive`].
+///
+/// [`ProgramError::Immutable`]: solana_program_error::ProgramError::Immutable
+pub fn check_via_get() -> ProgramResult {
+    EpochRewards::get()?.assert_distribution_inactive()
+}
+
 #[cfg(test)]
 mod tests {
     use {super::*, crate::Sysvar, serial_test::serial};
@@ -199,4 +223,37 @@ mod tests {
         let got = EpochRewards::get().unwrap();
         assert_eq!(got, expected);
     }
+
+    #[test]
+    #[serial]
+    #[cfg(feature = "bincode")]
+    fn test_check_via_get() {
+        use solana_program_error::ProgramError;
+
+        let mut epoch_rewards = EpochRewards {
+            distribution_starting_block_height: 42,
+            num_partitions: 7,
+            total_rewards: 100,
+            distributed_rewards: 10,
+            active: true,
+            ..EpochRewards::default()
+        };
+        let data = bincode::serialize(&epoch_rewards).unwrap();
+        crate::tests::mock_get_sysvar_syscall(&data);
+        assert_eq!(check_via_get(), Err(ProgramError::Immutable));
+        assert_eq!(
+            u64::from(check_via_get().unwrap_err()),
+            u64::from(ProgramError::Immutable)
+        );
+        assert_eq!(
+            EpochRewards::get().unwrap().distribution_progress(),
+            Some((10, 100))
+        );
+
+        epoch_rewards.active = false;
+        let data = bincode::serialize(&epoch_rewards).unwrap();
+        crate::tests::mock_get_sysvar_syscall(&data);
+        assert_eq!(check_via_get(), Ok(()));
+        assert_eq!(EpochRewards::get().unwrap().distribution_progress(), None);
+    }
 }
@@ -69,7 +69,7 @@
 //!
 //!     assert!(epoch_schedule::check_id(epoch_schedule_account_info.key));
 //!
-//!     let epoch_schedule = EpochSchedule::from_account_info(epoch_schedule_account_info)?;
+//!     let epoch_schedule = EpochSchedule::from_account_info_checked(epoch_schedule_account_info)?;
 //!     msg!("epoch_schedule: {:#?}", epoch_schedule);
 //!
 //!     Ok(())
@@ -79,7 +79,8 @@
 //! # let p = EpochSchedule::id();
 //! # let l = &mut 1120560;
 //! # let d = &mut vec![0, 32, 0, 0, 0, 0, 0, 0, 0, 32, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
-//! # let a = AccountInfo::new(&p, false, false, l, d, &p, false);
+//! # let o = solana_sdk_ids::sysvar::ID;
+//! # let a = AccountInfo::new(&p, false, false, l, d, &o, false);
 //! # let accounts = &[a.clone(), a];
 //! # process_instruction(
 //! #     &Pubkey::new_unique(),
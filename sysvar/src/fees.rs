@@ -26,7 +26,10 @@ use crate::SysvarSerialize;
 use serde_derive::{Deserialize, Serialize};
 pub use solana_sdk_ids::sysvar::fees::{check_id, id, ID};
 use {
-    crate::{impl_sysvar_get, Sysvar},
+    crate::{
+        availability::{get_fees, log},
+        Sysvar,
+    },
     solana_fee_calculator::FeeCalculator,
     solana_sdk_macro::CloneZeroed,
     solana_sysvar_id::impl_deprecated_sysvar_id,
@@ -56,7 +59,15 @@ impl Fees {
 }
 
 impl Sysvar for Fees {
-    impl_sysvar_get!(sol_get_fees_sysvar);
+    /// Load the sysvar directly from the runtime.
+    ///
+    /// Logs a message and returns [`ProgramError::UnsupportedSysvar`] if the
+    /// sysvar is not available on the cluster.
+    ///
+    /// [`ProgramError::UnsupportedSysvar`]: solana_program_error::ProgramError::UnsupportedSysvar
+    fn get() -> Result<Self, solana_program_error::ProgramError> {
+        get_fees().inspect_err(|_| log("Fees sysvar is not available on this cluster"))
+    }
 }
 
 #[cfg(feature = "bincode")]
@@ -0,0 +1,597 @@
+//! Mutually consistent sysvar values for program unit tests.
+//!
+//! [`SysvarFixture`] derives [`Clock`], [`EpochSchedule`], [`EpochRewards`]
+//! and [`Rent`] values that agree with each other for a given slot, and can
+//! serve them to [`Sysvar::get`] through the host syscall stubs.
+//!
+//! [`MockClock`] simulates the passage of slots and epochs between processor
+//! invocations, keeping the timestamps of the [`Clock`] consistent.
+//!
+//! [`Sysvar::get`]: crate::Sysvar::get
+
+use {
+    crate::{
+        program_stubs::{copy_sysvar_data, set_syscall_stubs, SyscallStubs},
+        SYSVAR_NOT_FOUND,
+    },
+    solana_clock::{Clock, Slot, UnixTimestamp, DEFAULT_MS_PER_SLOT},
+    solana_epoch_rewards::EpochRewards,
+    solana_epoch_schedule::EpochSchedule,
+    solana_pubkey::Pubkey,
+    solana_rent::Rent,
+    solana_sdk_ids::sysvar,
+    std::time::Duration,
+};
+
+/// Builder for a consistent set of sysvar values at a given slot.
+///
+/// # Examples
+///
+/// ```
+/// use {
+///     solana_epoch_schedule::EpochSchedule,
+///     solana_sysvar::{clock::Clock, fixture::SysvarFixture, Sysvar},
+/// };
+///
+/// let fixture = SysvarFixture::at_slot(1_000_000)
+///     .with_epoch_schedule(EpochSchedule::without_warmup())
+///     .activate_rewards(1_000_000, 250_000);
+///
+/// let clock = fixture.with_sysvar_data(|| Clock::get().unwrap());
+/// assert_eq!(clock, fixture.clock());
+/// ```
+#[derive(Clone, Debug)]
+pub struct SysvarFixture {
+    slot: Slot,
+    unix_timestamp: UnixTimestamp,
+    epoch_schedule: EpochSchedule,
+    rent: Rent,
+    rewards: Option<(u64, u64)>,
+}
+
+impl SysvarFixture {
+    /// Create a fixture at `slot` with the default epoch schedule and rent,
+    /// and no active rewards distribution.
+    pub fn at_slot(slot: Slot) -> Self {
+        Self {
+            slot,
+            unix_timestamp: 0,
+            epoch_schedule: EpochSchedule::default(),
+            rent: Rent::default(),
+            rewards: None,
+        }
+    }
+
+    pub fn with_epoch_schedule(mut self, epoch_schedule: EpochSchedule) -> Self {
+        self.epoch_schedule = epoch_schedule;
+        self.validate();
+        self
+    }
+
+    pub fn with_rent(mut self, rent: Rent) -> Self {
+        self.rent = rent;
+        self
+    }
+
+    pub fn with_unix_timestamp(mut self, unix_timestamp: UnixTimestamp) -> Self {
+        self.unix_timestamp = unix_timestamp;
+        self
+    }
+
+    /// Mark the epoch rewards distribution as active, with `distributed` of
+    /// `total` lamports already paid out.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `distributed` is not less than `total`, or if no rewards
+    /// distribution can be in progress at the fixture's slot.
+    pub fn activate_rewards(mut self, total: u64, distributed: u64) -> Self {
+        self.rewards = Some((total, distributed));
+        self.validate();
+        self
+    }
+
+    fn validate(&self) {
+        let Some((total, distributed)) = self.rewards else {
+            return;
+        };
+        assert!(
+            distributed < total,
+            "SysvarFixture: an active rewards distribution must have distributed ({distributed}) \
+             < total ({total}) rewards"
+        );
+        let epoch = self.epoch_schedule.get_epoch(self.slot);
+        assert!(
+            epoch > 0,
+            "SysvarFixture: rewards cannot be active in epoch 0 (slot {})",
+            self.slot
+        );
+        let distribution_starting_block_height = self.distribution_starting_block_height();
+        assert!(
+            self.slot >= distribution_starting_block_height,
+            "SysvarFixture: rewards distribution for epoch {epoch} starts at slot \
+             {distribution_starting_block_height}, after the fixture slot {}",
+            self.slot
+        );
+    }
+
+    fn distribution_starting_block_height(&self) -> u64 {
+        let epoch = self.epoch_schedule.get_epoch(self.slot);
+        self.epoch_schedule
+            .get_first_slot_in_epoch(epoch)
+            .saturating_add(1)
+    }
+
+    pub fn slot(&self) -> Slot {
+        self.slot
+    }
+
+    pub fn clock(&self) -> Clock {
+        Clock {
+            slot: self.slot,
+            epoch_start_timestamp: self.unix_timestamp,
+            epoch: self.epoch_schedule.get_epoch(self.slot),
+            leader_schedule_epoch: self.epoch_schedule.get_leader_schedule_epoch(self.slot),
+            unix_timestamp: self.unix_timestamp,
+        }
+    }
+
+    pub fn epoch_schedule(&self) -> EpochSchedule {
+        self.epoch_schedule.clone()
+    }
+
+    pub fn rent(&self) -> Rent {
+        self.rent.clone()
+    }
+
+    pub fn epoch_rewards(&self) -> EpochRewards {
+        match self.rewards {
+            Some((total, distributed)) => EpochRewards {
+                distribution_starting_block_height: self.distribution_starting_block_height(),
+                num_partitions: 1,
+                total_points: u128::from(total),
+                total_rewards: total,
+                distributed_rewards: distributed,
+                active: true,
+                ..EpochRewards::default()
+            },
+            None => EpochRewards::default(),
+        }
+    }
+
+    /// The bincode-serialized account data of the sysvar with the given id,
+    /// or `None` if the fixture does not provide it.
+    pub fn sysvar_data(&self, id: &Pubkey) -> Option<Vec<u8>> {
+        let data = if *id == sysvar::clock::id() {
+            bincode::serialize(&self.clock())
+        } else if *id == sysvar::epoch_schedule::id() {
+            bincode::serialize(&self.epoch_schedule)
+        } else if *id == sysvar::epoch_rewards::id() {
+            bincode::serialize(&self.epoch_rewards())
+        } else if *id == sysvar::rent::id() {
+            bincode::serialize(&self.rent)
+        } else {
+            return None;
+        };
+        Some(data.unwrap())
+    }
+
+    /// Run `f` with the fixture's sysvars served to [`Sysvar::get`], restoring
+    /// the previous syscall stubs afterwards.
+    ///
+    /// This swaps the global syscall stubs, so tests using it must not run
+    /// concurrently with other tests that do.
+    ///
+    /// [`Sysvar::get`]: crate::Sysvar::get
+    pub fn with_sysvar_data<R>(&self, f: impl FnOnce() -> R) -> R {
+        let stubs = FixtureSyscallStubs {
+            sysvars: [
+                sysvar::clock::id(),
+                sysvar::epoch_schedule::id(),
+                sysvar::epoch_rewards::id(),
+                sysvar::rent::id(),
+            ]
+            .into_iter()
+            .filter_map(|id| self.sysvar_data(&id).map(|data| (id, data)))
+            .collect(),
+        };
+        let _restore = RestoreStubs(Some(set_syscall_stubs(Box::new(stubs))));
+        f()
+    }
+}
+
+/// A [`Clock`] advancing through the slots of an [`EpochSchedule`].
+///
+/// Timestamps assume every slot lasts the same duration, [`DEFAULT_MS_PER_SLOT`]
+/// milliseconds unless changed with [`MockClock::with_slot_duration`]: the
+/// `unix_timestamp` of the clock is the time of its slot, and its
+/// `epoch_start_timestamp` the time of the first slot of its epoch.
+///
+/// # Examples
+///
+/// ```
+/// use {
+///     solana_epoch_schedule::EpochSchedule,
+///     solana_sysvar::{clock::Clock, fixture::MockClock, Sysvar},
+/// };
+///
+/// let mut clock = MockClock::new(0, &EpochSchedule::without_warmup());
+/// clock.advance_slots(10);
+/// {
+///     let _guard = clock.install();
+///     assert_eq!(Clock::get().unwrap().unix_timestamp, 4);
+/// }
+/// clock.advance_to_next_epoch();
+/// let _guard = clock.install();
+/// assert_eq!(Clock::get().unwrap().epoch, 1);
+/// ```
+#[derive(Clone, Debug)]
+pub struct MockClock {
+    slot: Slot,
+    epoch_schedule: EpochSchedule,
+    slot_duration: Duration,
+    /// The slot and timestamp anchoring the timestamps of the other slots.
+    origin: (Slot, UnixTimestamp),
+}
+
+impl MockClock {
+    /// Create a clock at `start_slot` of `epoch_schedule`, with a unix
+    /// timestamp of zero.
+    pub fn new(start_slot: Slot, epoch_schedule: &EpochSchedule) -> Self {
+        Self {
+            slot: start_slot,
+            epoch_schedule: epoch_schedule.clone(),
+            slot_duration: Duration::from_millis(DEFAULT_MS_PER_SLOT),
+            origin: (start_slot, 0),
+        }
+    }
+
+    /// Set the unix timestamp of the current slot.
+    pub fn with_unix_timestamp(mut self, unix_timestamp: UnixTimestamp) -> Self {
+        self.origin = (self.slot, unix_timestamp);
+        self
+    }
+
+    /// Set the duration of the slots, keeping the unix timestamp of the
+    /// current slot.
+    pub fn with_slot_duration(mut self, slot_duration: Duration) -> Self {
+        self.origin = (self.slot, self.timestamp_at(self.slot));
+        self.slot_duration = slot_duration;
+        self
+    }
+
+    /// Advance the clock by `n` slots.
+    pub fn advance_slots(&mut self, n: u64) {
+        self.slot = self.slot.checked_add(n).expect("slot overflow");
+    }
+
+    /// Advance the clock to the first slot of the next epoch.
+    pub fn advance_to_next_epoch(&mut self) {
+        let epoch = self.epoch_schedule.get_epoch(self.slot);
+        self.slot = self.epoch_schedule.get_first_slot_in_epoch(epoch + 1);
+    }
+
+    pub fn slot(&self) -> Slot {
+        self.slot
+    }
+
+    pub fn clock(&self) -> Clock {
+        let epoch = self.epoch_schedule.get_epoch(self.slot);
+        Clock {
+            slot: self.slot,
+            epoch_start_timestamp: self
+                .timestamp_at(self.epoch_schedule.get_first_slot_in_epoch(epoch)),
+            epoch,
+            leader_schedule_epoch: self.epoch_schedule.get_leader_schedule_epoch(self.slot),
+            unix_timestamp: self.timestamp_at(self.slot),
+        }
+    }
+
+    /// Serve the clock and epoch schedule to [`Sysvar::get`] until the
+    /// returned guard is dropped, which restores the previous syscall stubs.
+    ///
+    /// Later changes to the clock are not served until it is installed again.
+    /// Other sysvars are not found while the guard is alive.
+    ///
+    /// This swaps the global syscall stubs, so tests using it must not run
+    /// concurrently with other tests that do.
+    ///
+    /// [`Sysvar::get`]: crate::Sysvar::get
+    pub fn install(&self) -> MockClockGuard {
+        let stubs = FixtureSyscallStubs {
+            sysvars: vec![
+                (
+                    sysvar::clock::id(),
+                    bincode::serialize(&self.clock()).unwrap(),
+                ),
+                (
+                    sysvar::epoch_schedule::id(),
+                    bincode::serialize(&self.epoch_schedule).unwrap(),
+                ),
+            ],
+        };
+        MockClockGuard {
+            _restore: RestoreStubs(Some(set_syscall_stubs(Box::new(stubs)))),
+        }
+    }
+
+    fn timestamp_at(&self, slot: Slot) -> UnixTimestamp {
+        let (origin_slot, origin_timestamp) = self.origin;
+        let elapsed_ms = (i128::from(slot) - i128::from(origin_slot))
+            * i128::try_from(self.slot_duration.as_millis()).unwrap();
+        UnixTimestamp::try_from(i128::from(origin_timestamp) + elapsed_ms.div_euclid(1_000))
+            .expect("unix timestamp overflow")
+    }
+}
+
+/// Restores the syscall stubs replaced by [`MockClock::install`] when
+/// dropped.
+#[must_use = "the mock clock is uninstalled when the guard is dropped"]
+pub struct MockClockGuard {
+    _restore: RestoreStubs,
+}
+
+/// Reinstalls the wrapped syscall stubs when dropped.
+struct RestoreStubs(Option<Box<dyn SyscallStubs>>);
+
+impl Drop for RestoreStubs {
+    fn drop(&mut self) {
+        if let Some(stubs) = self.0.take() {
+            set_syscall_stubs(stubs);
+        }
+    }
+}
+
+struct FixtureSyscallStubs {
+    sysvars: Vec<(Pubkey, Vec<u8>)>,
+}
+
+impl SyscallStubs for FixtureSyscallStubs {
+    fn sol_get_sysvar(
+        &self,
+        sysvar_id_addr: *const u8,
+        var_addr: *mut u8,
+        offset: u64,
+        length: u64,
+    ) -> u64 {
+        let sysvar_id = unsafe { *(sysvar_id_addr as *const Pubkey) };
+        let Some((_, data)) = self.sysvars.iter().find(|(id, _)| *id == sysvar_id) else {
+            return SYSVAR_NOT_FOUND;
+        };
+        copy_sysvar_data(data, var_addr, offset, length)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::Sysvar,
+        serial_test::serial,
+        solana_account_info::AccountInfo,
+        solana_program_error::{ProgramError, ProgramResult},
+    };
+
+    #[test]
+    fn test_clock_matches_epoch_schedule() {
+        for epoch_schedule in [
+            EpochSchedule::default(),
+            EpochSchedule::without_warmup(),
+            EpochSchedule::custom(64, 32, true),
+        ] {
+            for slot in [0, 31, 32, 1_000, 432_000, 10_000_000] {
+                let fixture =
+                    SysvarFixture::at_slot(slot).with_epoch_schedule(epoch_schedule.clone());
+                let clock = fixture.clock();
+                assert_eq!(clock.slot, slot);
+                assert_eq!(clock.epoch, epoch_schedule.get_epoch(slot));
+                assert_eq!(
+                    clock.leader_schedule_epoch,
+                    epoch_schedule.get_leader_schedule_epoch(slot)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_epoch_rewards_derived_from_slot() {
+        let epoch_schedule = EpochSchedule::without_warmup();
+        let slot = epoch_schedule.get_first_slot_in_epoch(3) + 10;
+        let fixture = SysvarFixture::at_slot(slot)
+            .with_epoch_schedule(epoch_schedule.clone())
+            .activate_rewards(100, 40);
+
+        let epoch_rewards = fixture.epoch_rewards();
+        assert!(epoch_rewards.active);
+        assert_eq!(epoch_rewards.total_rewards, 100);
+        assert_eq!(epoch_rewards.distributed_rewards, 40);
+        assert_eq!(
+            epoch_rewards.distribution_starting_block_height,
+            epoch_schedule.get_first_slot_in_epoch(3) + 1
+        );
+
+        assert_eq!(
+            SysvarFixture::at_slot(slot).epoch_rewards(),
+            EpochRewards::default()
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "distributed (100) < total (100)")]
+    fn test_activate_rewards_fully_distributed() {
+        SysvarFixture::at_slot(1_000_000).activate_rewards(100, 100);
+    }
+
+    #[test]
+    #[should_panic(expected = "rewards cannot be active in epoch 0")]
+    fn test_activate_rewards_epoch_zero() {
+        SysvarFixture::at_slot(1).activate_rewards(100, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "after the fixture slot")]
+    fn test_epoch_schedule_contradicts_rewards() {
+        let epoch_schedule = EpochSchedule::without_warmup();
+        let first_slot = epoch_schedule.get_first_slot_in_epoch(2);
+        SysvarFixture::at_slot(first_slot + 1)
+            .activate_rewards(100, 0)
+            .with_epoch_schedule(EpochSchedule::custom(first_slot + 1, first_slot + 1, false));
+    }
+
+    #[test]
+    fn test_sysvar_data() {
+        let fixture = SysvarFixture::at_slot(1_000_000).activate_rewards(100, 0);
+        let clock: Clock =
+            bincode::deserialize(&fixture.sysvar_data(&sysvar::clock::id()).unwrap()).unwrap();
+        assert_eq!(clock, fixture.clock());
+        let epoch_rewards: EpochRewards =
+            bincode::deserialize(&fixture.sysvar_data(&sysvar::epoch_rewards::id()).unwrap())
+                .unwrap();
+        assert_eq!(epoch_rewards, fixture.epoch_rewards());
+        assert_eq!(fixture.sysvar_data(&sysvar::slot_hashes::id()), None);
+    }
+
+    /// A processor that refuses to run while rewards are being distributed
+    /// and otherwise writes the current epoch and rent-exempt minimum of the
+    /// account into the account data.
+    fn process_instruction(
+        _program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        _instruction_data: &[u8],
+    ) -> ProgramResult {
+        let account = accounts.first().ok_or(ProgramError::NotEnoughAccountKeys)?;
+        if EpochRewards::get()?.active {
+            return Err(ProgramError::Custom(1));
+        }
+        let clock = Clock::get()?;
+        let epoch_schedule = EpochSchedule::get()?;
+        if clock.epoch != epoch_schedule.get_epoch(clock.slot) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let minimum_balance = Rent::get()?.minimum_balance(account.data_len());
+        let mut data = account.try_borrow_mut_data()?;
+        data[..8].copy_from_slice(&clock.epoch.to_le_bytes());
+        data[8..16].copy_from_slice(&minimum_balance.to_le_bytes());
+        Ok(())
+    }
+
+    #[test]
+    #[serial]
+    fn test_processor_with_fixture() {
+        let program_id = Pubkey::new_unique();
+        let key = Pubkey::new_unique();
+        let mut lamports = 0;
+        let mut data = vec![0; 16];
+        let account = AccountInfo::new(
+            &key,
+            false,
+            true,
+            &mut lamports,
+            &mut data,
+            &program_id,
+            false,
+        );
+
+        let fixture = SysvarFixture::at_slot(1_000_000).with_unix_timestamp(1_700_000_000);
+        fixture
+            .with_sysvar_data(|| {
+                process_instruction(&program_id, std::slice::from_ref(&account), &[])
+            })
+            .unwrap();
+        let data = account.try_borrow_data().unwrap();
+        assert_eq!(data[..8], fixture.clock().epoch.to_le_bytes());
+        assert_eq!(
+            data[8..16],
+            fixture.rent().minimum_balance(16).to_le_bytes()
+        );
+        drop(data);
+
+        let fixture = fixture.activate_rewards(1_000, 10);
+        assert_eq!(
+            fixture.with_sysvar_data(|| process_instruction(&program_id, &[account], &[])),
+            Err(ProgramError::Custom(1))
+        );
+    }
+
+    #[test]
+    fn test_mock_clock_epoch_rollover() {
+        for epoch_schedule in [
+            EpochSchedule::default(),
+            EpochSchedule::without_warmup(),
+            EpochSchedule::custom(64, 32, true),
+        ] {
+            let mut mock = MockClock::new(0, &epoch_schedule);
+            let mut previous = mock.clock();
+            for _ in 0..20 {
+                mock.advance_slots(7);
+                let clock = mock.clock();
+                assert_eq!(clock.epoch, epoch_schedule.get_epoch(clock.slot));
+                assert_eq!(
+                    clock.leader_schedule_epoch,
+                    epoch_schedule.get_leader_schedule_epoch(clock.slot)
+                );
+                assert_eq!(clock.unix_timestamp, (clock.slot * 400 / 1_000) as i64);
+                let first_slot = epoch_schedule.get_first_slot_in_epoch(clock.epoch);
+                assert_eq!(
+                    clock.epoch_start_timestamp,
+                    (first_slot * 400 / 1_000) as i64
+                );
+                if clock.epoch == previous.epoch {
+                    assert_eq!(clock.epoch_start_timestamp, previous.epoch_start_timestamp);
+                }
+                previous = clock;
+            }
+
+            for epoch in previous.epoch + 1..previous.epoch + 4 {
+                mock.advance_to_next_epoch();
+                let clock = mock.clock();
+                assert_eq!(clock.epoch, epoch);
+                assert_eq!(clock.slot, epoch_schedule.get_first_slot_in_epoch(epoch));
+                assert_eq!(clock.epoch_start_timestamp, clock.unix_timestamp);
+            }
+        }
+    }
+
+    #[test]
+    fn test_mock_clock_timestamps() {
+        let epoch_schedule = EpochSchedule::without_warmup();
+        let first_slot = epoch_schedule.get_first_slot_in_epoch(5);
+        let mut mock =
+            MockClock::new(first_slot + 10, &epoch_schedule).with_unix_timestamp(1_700_000_000);
+        assert_eq!(mock.clock().unix_timestamp, 1_700_000_000);
+        assert_eq!(mock.clock().epoch_start_timestamp, 1_700_000_000 - 4);
+
+        let mut mock_1s = mock.clone().with_slot_duration(Duration::from_secs(1));
+        mock.advance_slots(5);
+        mock_1s.advance_slots(5);
+        assert_eq!(mock.clock().unix_timestamp, 1_700_000_002);
+        assert_eq!(mock_1s.clock().unix_timestamp, 1_700_000_005);
+        assert_eq!(mock_1s.clock().epoch_start_timestamp, 1_700_000_000 - 10);
+    }
+
+    #[test]
+    #[serial]
+    fn test_mock_clock_install() {
+        let epoch_schedule = EpochSchedule::custom(64, 32, true);
+        let mut mock = MockClock::new(100, &epoch_schedule).with_unix_timestamp(1_000);
+        let outer = mock.clone();
+        let _outer_guard = outer.install();
+        assert_eq!(Clock::get(), Ok(outer.clock()));
+        assert_eq!(EpochSchedule::get(), Ok(epoch_schedule.clone()));
+        assert_eq!(Rent::get(), Err(ProgramError::UnsupportedSysvar));
+
+        mock.advance_to_next_epoch();
+        {
+            let _inner_guard = mock.install();
+            assert_eq!(Clock::get(), Ok(mock.clock()));
+            assert_ne!(Clock::get(), Ok(outer.clock()));
+        }
+        assert_eq!(Clock::get(), Ok(outer.clock()));
+
+        // installing takes a snapshot of the clock
+        let _guard = mock.install();
+        let installed = mock.clock();
+        mock.advance_slots(1);
+        assert_eq!(Clock::get(), Ok(installed));
+    }
+}
@@ -31,8 +31,8 @@
 //!
 //! Since Solana sysvars are accounts, if the `AccountInfo` is provided to the
 //! program, then the program can deserialize the sysvar with
-//! [`SysvarSerialize::from_account_info`] to access its data, as in this example that
-//! again logs the [`clock`] sysvar.
+//! [`SysvarSerialize::from_account_info_checked`] to access its data, as in this example
+//! that again logs the [`clock`] sysvar.
 //!
 //! ```
 //! use solana_account_info::{AccountInfo, next_account_info};
@@ -48,7 +48,7 @@
 //! ) -> ProgramResult {
 //!     let account_info_iter = &mut accounts.iter();
 //!     let clock_account = next_account_info(account_info_iter)?;
-//!     let clock = solana_clock::Clock::from_account_info(&clock_account)?;
+//!     let clock = solana_clock::Clock::from_account_info_checked(&clock_account)?;
 //!     msg!("clock: {:#?}", clock);
 //!     Ok(())
 //! }
@@ -81,16 +81,24 @@
 pub mod __private {
     #[cfg(target_os = "solana")]
     pub use solana_define_syscall::definitions;
-    pub use {solana_program_entrypoint::SUCCESS, solana_program_error::ProgramError};
+    pub use {
+        solana_program_entrypoint::SUCCESS, solana_program_error::ProgramError,
+        solana_sysvar_id::declare_sysvar_id,
+    };
 }
+#[cfg(feature = "account-view")]
+use solana_account_view::AccountView;
 #[cfg(feature = "bincode")]
 use {solana_account_info::AccountInfo, solana_sysvar_id::SysvarId};
 use {solana_program_error::ProgramError, solana_pubkey::Pubkey};
 
+pub mod availability;
 pub mod clock;
 pub mod epoch_rewards;
 pub mod epoch_schedule;
 pub mod fees;
+#[cfg(all(feature = "dev-context-only-utils", not(target_os = "solana")))]
+pub mod fixture;
 pub mod last_restart_slot;
 pub mod program_stubs;
 pub mod recent_blockhashes;
@@ -98,17 +106,20 @@ pub mod rent;
 pub mod rewards;
 pub mod slot_hashes;
 pub mod slot_history;
+pub mod stake_history;
+#[cfg(all(feature = "test-utils", not(target_os = "solana")))]
+pub mod test_utils;
 
 /// Return value indicating that the  `offset + length` is greater than the length of
 /// the sysvar data.
 //
 // Defined in the bpf loader as [`OFFSET_LENGTH_EXCEEDS_SYSVAR`](https://github.com/anza-xyz/agave/blob/master/programs/bpf_loader/src/syscalls/sysvar.rs#L172).
-const OFFSET_LENGTH_EXCEEDS_SYSVAR: u64 = 1;
+pub const OFFSET_LENGTH_EXCEEDS_SYSVAR: u64 = 1;
 
 /// Return value indicating that the sysvar was not found.
 //
 // Defined in the bpf loader as [`SYSVAR_NOT_FOUND`](https://github.com/anza-xyz/agave/blob/master/programs/bpf_loader/src/syscalls/sysvar.rs#L171).
-const SYSVAR_NOT_FOUND: u64 = 2;
+pub const SYSVAR_NOT_FOUND: u64 = 2;
 
 /// Interface for loading a sysvar.
 pub trait Sysvar: Default + Sized {
@@ -123,6 +134,12 @@ pub trait Sysvar: Default + Sized {
     fn get() -> Result<Self, ProgramError> {
         Err(ProgramError::UnsupportedSysvar)
     }
+
+    /// Load the sysvar like [`Sysvar::get`], returning `default` if it
+    /// cannot be loaded.
+    fn get_or(default: Self) -> Self {
+        Self::get().unwrap_or(default)
+    }
 }
 
 #[cfg(feature = "bincode")]
@@ -137,6 +154,9 @@ pub trait SysvarSerialize:
 
     /// Deserializes the sysvar from its `AccountInfo`.
     ///
+    /// The owner of the account is not checked, prefer
+    /// [`Self::from_account_info_checked`].
+    ///
     /// # Errors
     ///
     /// If `account_info` does not have the same ID as the sysvar this function
@@ -148,6 +168,44 @@ pub trait SysvarSerialize:
         bincode::deserialize(&account_info.data.borrow()).map_err(|_| ProgramError::InvalidArgument)
     }
 
+    /// Deserializes the sysvar from its `AccountInfo`, checking that the
+    /// account is owned by the sysvar program, [`solana_sdk_ids::sysvar::ID`].
+    ///
+    /// # Errors
+    ///
+    /// If `account_info` is not owned by the sysvar program this function
+    /// returns [`ProgramError::InvalidAccountOwner`], and otherwise the errors
+    /// of [`Self::from_account_info`].
+    fn from_account_info_checked(account_info: &AccountInfo) -> Result<Self, ProgramError> {
+        if !solana_sdk_ids::sysvar::check_id(account_info.owner) {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+        Self::from_account_info(account_info)
+    }
+
+    /// Deserializes the sysvar from its `AccountView`, checking that the
+    /// account is owned by the sysvar program, [`solana_sdk_ids::sysvar::ID`].
+    ///
+    /// # Errors
+    ///
+    /// If `account` is not owned by the sysvar program this function returns
+    /// [`ProgramError::InvalidAccountOwner`]. If `account` does not have the
+    /// same ID as the sysvar, or its data cannot be deserialized, it returns
+    /// [`ProgramError::InvalidArgument`]. If the data is mutably borrowed, it
+    /// returns [`ProgramError::AccountBorrowFailed`].
+    #[cfg(feature = "account-view")]
+    fn from_account_view(account: &AccountView) -> Result<Self, ProgramError> {
+        if !account.owned_by(&solana_sdk_ids::sysvar::ID) {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+        if !Self::check_id(account.address()) {
+            return Err(ProgramError::InvalidArgument);
+        }
+        account
+            .with_data_scoped(|data| bincode::deserialize(data))?
+            .map_err(|_| ProgramError::InvalidArgument)
+    }
+
     /// Serializes the sysvar to `AccountInfo`.
     ///
     /// # Errors
@@ -158,10 +216,69 @@ pub trait SysvarSerialize:
     }
 }
 
+/// The heap a sysvar deserialized by `from_account_info` may use on chain.
+///
+/// The large sysvars deserialize to about as many bytes as their account
+/// data, allocated on the program heap, which is 32 KiB by default and never
+/// freed. Half of it is left to the rest of the program.
+pub(crate) const DESERIALIZE_HEAP_BUDGET: usize = 16 * 1024;
+
+/// Deserializes the sysvar from `account_info` like
+/// [`SysvarSerialize::from_account_info`], unless the account data is longer
+/// than `max_len`, in which case [`ProgramError::UnsupportedSysvar`] is
+/// returned without deserializing.
+#[cfg(feature = "bincode")]
+pub(crate) fn from_account_info_bounded<T: SysvarSerialize>(
+    account_info: &AccountInfo,
+    max_len: usize,
+) -> Result<T, ProgramError> {
+    if !T::check_id(account_info.unsigned_key()) {
+        return Err(ProgramError::InvalidArgument);
+    }
+    if account_info.data_len() > max_len {
+        return Err(ProgramError::UnsupportedSysvar);
+    }
+    bincode::deserialize(&account_info.data.borrow()).map_err(|_| ProgramError::InvalidArgument)
+}
+
+/// Deserializes the sysvar from `account` like
+/// [`SysvarSerialize::from_account_view`], unless the account data is longer
+/// than `max_len`, in which case [`ProgramError::UnsupportedSysvar`] is
+/// returned without deserializing.
+#[cfg(feature = "account-view")]
+pub(crate) fn from_account_view_bounded<T: SysvarSerialize>(
+    account: &AccountView,
+    max_len: usize,
+) -> Result<T, ProgramError> {
+    if !account.owned_by(&solana_sdk_ids::sysvar::ID) {
+        return Err(ProgramError::InvalidAccountOwner);
+    }
+    if !T::check_id(account.address()) {
+        return Err(ProgramError::InvalidArgument);
+    }
+    if account.data_len() > max_len {
+        return Err(ProgramError::UnsupportedSysvar);
+    }
+    account
+        .with_data_scoped(|data| bincode::deserialize(data))?
+        .map_err(|_| ProgramError::InvalidArgument)
+}
+
+/// The data length limit `from_account_info` applies: `max_len` on chain,
+/// where the heap is bounded, and none off chain.
+#[cfg(feature = "bincode")]
+pub(crate) const fn on_chain_limit(max_len: usize) -> usize {
+    if cfg!(target_os = "solana") {
+        max_len
+    } else {
+        usize::MAX
+    }
+}
+
 /// Implements the [`Sysvar::get`] method for both SBF and host targets.
 #[macro_export]
 macro_rules! impl_sysvar_get {
-    // DEPRECATED: This variant is only for the deprecated Fees sysvar and should be
+    // DEPRECATED: This variant was only used for the deprecated Fees sysvar and should be
     // removed once Fees is no longer in use. It uses the old-style direct syscall
     // approach instead of the new sol_get_sysvar syscall.
     ($syscall_name:ident) => {
@@ -278,11 +395,10 @@ pub unsafe fn get_sysvar_unchecked(
 
 #[cfg(test)]
 mod tests {
+    pub use crate::test_utils::{mock_get_sysvar_syscall, mock_get_sysvar_syscall_with_id};
     use {
         super::*,
-        crate::program_stubs::{set_syscall_stubs, SyscallStubs},
         serde_derive::{Deserialize, Serialize},
-        solana_program_entrypoint::SUCCESS,
         solana_program_error::ProgramError,
         solana_pubkey::Pubkey,
         std::{cell::RefCell, rc::Rc},
@@ -293,82 +409,7 @@ mod tests {
     struct TestSysvar {
         something: Pubkey,
     }
-    solana_pubkey::declare_id!("TestSysvar111111111111111111111111111111111");
-    impl solana_sysvar_id::SysvarId for TestSysvar {
-        fn id() -> solana_pubkey::Pubkey {
-            id()
-        }
-
-        fn check_id(pubkey: &solana_pubkey::Pubkey) -> bool {
-            check_id(pubkey)
-        }
-    }
-    impl Sysvar for TestSysvar {}
-    impl SysvarSerialize for TestSysvar {}
-
-    // NOTE: Tests using these mocks MUST carry the #[serial] attribute
-    // because they modify global SYSCALL_STUBS state.
-
-    struct MockGetSysvarSyscall {
-        data: Vec<u8>,
-    }
-
-    impl SyscallStubs for MockGetSysvarSyscall {
-        #[allow(clippy::arithmetic_side_effects)]
-        fn sol_get_sysvar(
-            &self,
-            _sysvar_id_addr: *const u8,
-            var_addr: *mut u8,
-            offset: u64,
-            length: u64,
-        ) -> u64 {
-            let slice = unsafe { std::slice::from_raw_parts_mut(var_addr, length as usize) };
-            slice.copy_from_slice(&self.data[offset as usize..(offset + length) as usize]);
-            SUCCESS
-        }
-    }
-
-    /// Mock syscall stub for tests. Requires `#[serial]` attribute.
-    pub fn mock_get_sysvar_syscall(data: &[u8]) {
-        set_syscall_stubs(Box::new(MockGetSysvarSyscall {
-            data: data.to_vec(),
-        }));
-    }
-
-    struct ValidateIdSyscall {
-        data: Vec<u8>,
-        expected_id: Pubkey,
-    }
-
-    impl SyscallStubs for ValidateIdSyscall {
-        #[allow(clippy::arithmetic_side_effects)]
-        fn sol_get_sysvar(
-            &self,
-            sysvar_id_addr: *const u8,
-            var_addr: *mut u8,
-            offset: u64,
-            length: u64,
-        ) -> u64 {
-            // Validate that the correct sysvar id pointer was passed
-            let passed_id = unsafe { *(sysvar_id_addr as *const Pubkey) };
-            assert_eq!(passed_id, self.expected_id);
-
-            let slice = unsafe { std::slice::from_raw_parts_mut(var_addr, length as usize) };
-            slice.copy_from_slice(&self.data[offset as usize..(offset + length) as usize]);
-            SUCCESS
-        }
-    }
-
-    /// Mock syscall stub that validates sysvar ID. Requires `#[serial]` attribute.
-    pub fn mock_get_sysvar_syscall_with_id(
-        data: &[u8],
-        expected_id: &Pubkey,
-    ) -> Box<dyn SyscallStubs> {
-        set_syscall_stubs(Box::new(ValidateIdSyscall {
-            data: data.to_vec(),
-            expected_id: *expected_id,
-        }))
-    }
+    crate::impl_test_sysvar!(TestSysvar, "TestSysvar111111111111111111111111111111111");
 
     /// Convert a value to its in-memory byte representation.
     ///
@@ -411,4 +452,108 @@ mod tests {
         account_info.data = Rc::new(RefCell::new(&mut small_data));
         assert_eq!(test_sysvar.to_account_info(&mut account_info), None);
     }
+
+    #[test]
+    fn test_sysvar_account_info_checked() {
+        let test_sysvar = TestSysvar {
+            something: Pubkey::new_unique(),
+        };
+        let key = id();
+        let wrong_key = Pubkey::new_unique();
+        let owner = solana_sdk_ids::sysvar::id();
+        let wrong_owner = Pubkey::new_unique();
+        let mut lamports = 42;
+        let mut data = vec![0_u8; TestSysvar::size_of()];
+        let mut account_info =
+            AccountInfo::new(&key, false, true, &mut lamports, &mut data, &owner, false);
+
+        test_sysvar.to_account_info(&mut account_info).unwrap();
+        assert_eq!(
+            TestSysvar::from_account_info_checked(&account_info),
+            Ok(TestSysvar {
+                something: test_sysvar.something
+            })
+        );
+
+        account_info.key = &wrong_key;
+        assert_eq!(
+            TestSysvar::from_account_info_checked(&account_info),
+            Err(ProgramError::InvalidArgument)
+        );
+
+        account_info.key = &key;
+        account_info.owner = &wrong_owner;
+        assert_eq!(
+            TestSysvar::from_account_info_checked(&account_info),
+            Err(ProgramError::InvalidAccountOwner)
+        );
+        // the lenient variant does not check the owner
+        assert_eq!(
+            TestSysvar::from_account_info(&account_info),
+            Ok(test_sysvar)
+        );
+    }
+
+    #[test]
+    fn test_sysvar_account_view() {
+        use solana_account_view::{RuntimeAccount, NOT_BORROWED};
+
+        let test_sysvar = TestSysvar {
+            something: Pubkey::new_unique(),
+        };
+        let len = TestSysvar::size_of();
+        // 8-bytes aligned account header followed by the data
+        let mut memory = vec![0u64; (core::mem::size_of::<RuntimeAccount>() + len).div_ceil(8)];
+        let raw = memory.as_mut_ptr() as *mut RuntimeAccount;
+        unsafe {
+            (*raw).borrow_state = NOT_BORROWED;
+            (*raw).address = id();
+            (*raw).owner = solana_sdk_ids::sysvar::id();
+            (*raw).data_len = len as u64;
+        }
+        let mut account = unsafe { AccountView::new_unchecked(raw) };
+        account
+            .with_data_mut_scoped(|data| bincode::serialize_into(data, &test_sysvar).unwrap())
+            .unwrap();
+
+        assert_eq!(
+            TestSysvar::from_account_view(&account),
+            Ok(TestSysvar {
+                something: test_sysvar.something
+            })
+        );
+        assert_eq!(
+            from_account_view_bounded::<TestSysvar>(&account, len),
+            Ok(TestSysvar {
+                something: test_sysvar.something
+            })
+        );
+        assert_eq!(
+            from_account_view_bounded::<TestSysvar>(&account, len - 1),
+            Err(ProgramError::UnsupportedSysvar)
+        );
+
+        let borrowed = unsafe { AccountView::new_unchecked(raw) };
+        let data = account.try_borrow_mut().unwrap();
+        assert_eq!(
+            TestSysvar::from_account_view(&borrowed),
+            Err(ProgramError::AccountBorrowFailed)
+        );
+        drop(data);
+
+        unsafe { (*raw).address = Pubkey::new_unique() };
+        assert_eq!(
+            TestSysvar::from_account_view(&account),
+            Err(ProgramError::InvalidArgument)
+        );
+
+        unsafe {
+            (*raw).address = id();
+            (*raw).owner = Pubkey::new_unique();
+        }
+        assert_eq!(
+            TestSysvar::from_account_view(&account),
+            Err(ProgramError::InvalidAccountOwner)
+        );
+    }
 }
@@ -3,25 +3,120 @@
 #![cfg(not(target_os = "solana"))]
 
 use {
+    crate::{
+        clock::Clock, epoch_rewards::EpochRewards, epoch_schedule::EpochSchedule,
+        last_restart_slot::LastRestartSlot, rent::Rent, OFFSET_LENGTH_EXCEEDS_SYSVAR,
+    },
     base64::{prelude::BASE64_STANDARD, Engine},
     solana_account_info::AccountInfo,
     solana_instruction::{error::UNSUPPORTED_SYSVAR, Instruction},
+    solana_program_entrypoint::SUCCESS,
     solana_program_error::ProgramResult,
     solana_program_memory::stubs,
     solana_pubkey::Pubkey,
-    std::sync::{Arc, RwLock},
+    solana_sdk_ids::sysvar,
+    std::{
+        collections::HashMap,
+        mem::size_of,
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc, Mutex, RwLock,
+        },
+    },
 };
 
 lazy_static::lazy_static! {
-    static ref SYSCALL_STUBS: Arc<RwLock<Box<dyn SyscallStubs>>> = Arc::new(RwLock::new(Box::new(DefaultSyscallStubs {})));
+    static ref SYSCALL_STUBS: Arc<RwLock<Box<dyn SyscallStubs>>> = {
+        // Route `solana_msg::sol_log_data` through the stubs as well.
+        solana_msg::set_sol_log_data_stub(sol_log_data);
+        Arc::new(RwLock::new(Box::new(DefaultSyscallStubs {})))
+    };
 }
 
+static DEFAULT_SYSVARS_ENABLED: AtomicBool = AtomicBool::new(false);
+static DEFAULT_SYSVARS_WARNED: AtomicBool = AtomicBool::new(false);
+
 // The default syscall stubs may not do much, but `set_syscalls()` can be used
 // to swap in alternatives
 pub fn set_syscall_stubs(syscall_stubs: Box<dyn SyscallStubs>) -> Box<dyn SyscallStubs> {
     std::mem::replace(&mut SYSCALL_STUBS.write().unwrap(), syscall_stubs)
 }
 
+/// Makes the default [`SyscallStubs::sol_get_sysvar`] serve the default
+/// values of the [`Clock`], [`Rent`], [`EpochSchedule`], [`EpochRewards`]
+/// and [`LastRestartSlot`] sysvars, rather than failing.
+///
+/// Meant for host tools that link program crates and call `Sysvar::get` on
+/// paths unrelated to what they do, without setting up syscall stubs. The
+/// first sysvar served after enabling the mode logs a warning, since the
+/// values do not come from any cluster. Disabled by default.
+pub fn set_default_sysvars_enabled(enabled: bool) {
+    DEFAULT_SYSVARS_WARNED.store(false, Ordering::Relaxed);
+    DEFAULT_SYSVARS_ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+/// The bincode-serialized default value of the sysvar with the given id, for
+/// [`set_default_sysvars_enabled`].
+#[allow(deprecated)]
+fn default_sysvar_data(id: &Pubkey) -> Option<Vec<u8>> {
+    let mut data = Vec::new();
+    if *id == sysvar::clock::id() {
+        let clock = Clock::default();
+        data.extend_from_slice(&clock.slot.to_le_bytes());
+        data.extend_from_slice(&clock.epoch_start_timestamp.to_le_bytes());
+        data.extend_from_slice(&clock.epoch.to_le_bytes());
+        data.extend_from_slice(&clock.leader_schedule_epoch.to_le_bytes());
+        data.extend_from_slice(&clock.unix_timestamp.to_le_bytes());
+    } else if *id == sysvar::rent::id() {
+        let rent = Rent::default();
+        data.extend_from_slice(&rent.lamports_per_byte.to_le_bytes());
+        data.extend_from_slice(&rent.exemption_threshold);
+        data.push(rent.burn_percent);
+    } else if *id == sysvar::epoch_schedule::id() {
+        let epoch_schedule = EpochSchedule::default();
+        data.extend_from_slice(&epoch_schedule.slots_per_epoch.to_le_bytes());
+        data.extend_from_slice(&epoch_schedule.leader_schedule_slot_offset.to_le_bytes());
+        data.push(u8::from(epoch_schedule.warmup));
+        data.extend_from_slice(&epoch_schedule.first_normal_epoch.to_le_bytes());
+        data.extend_from_slice(&epoch_schedule.first_normal_slot.to_le_bytes());
+    } else if *id == sysvar::epoch_rewards::id() {
+        let epoch_rewards = EpochRewards::default();
+        data.extend_from_slice(
+            &epoch_rewards
+                .distribution_starting_block_height
+                .to_le_bytes(),
+        );
+        data.extend_from_slice(&epoch_rewards.num_partitions.to_le_bytes());
+        data.extend_from_slice(epoch_rewards.parent_blockhash.as_ref());
+        data.extend_from_slice(&epoch_rewards.total_points.to_le_bytes());
+        data.extend_from_slice(&epoch_rewards.total_rewards.to_le_bytes());
+        data.extend_from_slice(&epoch_rewards.distributed_rewards.to_le_bytes());
+        data.push(u8::from(epoch_rewards.active));
+    } else if *id == sysvar::last_restart_slot::id() {
+        let last_restart_slot = LastRestartSlot::default();
+        data.extend_from_slice(&last_restart_slot.last_restart_slot.to_le_bytes());
+    } else {
+        return None;
+    }
+    Some(data)
+}
+
+/// Copies `length` bytes of `data` starting at `offset` to `var_addr`, or
+/// returns [`OFFSET_LENGTH_EXCEEDS_SYSVAR`] like the runtime does when the
+/// range is out of bounds.
+pub(crate) fn copy_sysvar_data(data: &[u8], var_addr: *mut u8, offset: u64, length: u64) -> u64 {
+    let Some(src) = usize::try_from(offset)
+        .ok()
+        .zip(usize::try_from(length).ok())
+        .and_then(|(offset, length)| data.get(offset..offset.checked_add(length)?))
+    else {
+        return OFFSET_LENGTH_EXCEEDS_SYSVAR;
+    };
+    let dst = unsafe { std::slice::from_raw_parts_mut(var_addr, src.len()) };
+    dst.copy_from_slice(src);
+    SUCCESS
+}
+
 pub trait SyscallStubs: Sync + Send {
     fn sol_log(&self, message: &str) {
         println!("{message}");
@@ -42,14 +137,28 @@ pub trait SyscallStubs: Sync + Send {
         sol_log("SyscallStubs: sol_invoke_signed() not available");
         Ok(())
     }
+    /// Fails with `UNSUPPORTED_SYSVAR`, unless default sysvars are enabled,
+    /// see [`set_default_sysvars_enabled`].
+    // the pointers come from the syscall shims, like those of the other stubs
+    #[allow(clippy::not_unsafe_ptr_arg_deref)]
     fn sol_get_sysvar(
         &self,
-        _sysvar_id_addr: *const u8,
-        _var_addr: *mut u8,
-        _offset: u64,
-        _length: u64,
+        sysvar_id_addr: *const u8,
+        var_addr: *mut u8,
+        offset: u64,
+        length: u64,
     ) -> u64 {
-        UNSUPPORTED_SYSVAR
+        if !DEFAULT_SYSVARS_ENABLED.load(Ordering::Relaxed) {
+            return UNSUPPORTED_SYSVAR;
+        }
+        let sysvar_id = unsafe { *(sysvar_id_addr as *const Pubkey) };
+        let Some(data) = default_sysvar_data(&sysvar_id) else {
+            return UNSUPPORTED_SYSVAR;
+        };
+        if !DEFAULT_SYSVARS_WARNED.swap(true, Ordering::Relaxed) {
+            self.sol_log("SyscallStubs: sol_get_sysvar() serving default sysvar values");
+        }
+        copy_sysvar_data(&data, var_addr, offset, length)
     }
     fn sol_get_clock_sysvar(&self, _var_addr: *mut u8) -> u64 {
         UNSUPPORTED_SYSVAR
@@ -110,9 +219,272 @@ pub trait SyscallStubs: Sync + Send {
     }
 }
 
-struct DefaultSyscallStubs {}
+pub(crate) struct DefaultSyscallStubs {}
 impl SyscallStubs for DefaultSyscallStubs {}
 
+// Compute unit costs of the default compute budget of the runtime.
+const SYSCALL_BASE_COST: u64 = 100;
+const SYSVAR_BASE_COST: u64 = 100;
+const MEM_OP_BASE_COST: u64 = 10;
+const CPI_BYTES_PER_UNIT: u64 = 250;
+const INVOKE_UNITS: u64 = 1_000;
+
+#[derive(Default)]
+struct ComputeMeter {
+    consumed: u64,
+    invocations: HashMap<&'static str, u64>,
+}
+
+/// Syscall stubs metering the compute units the syscalls would consume on
+/// chain, so that host tests can compare helpers, or catch regressions.
+///
+/// Each call is charged what the runtime charges with the default compute
+/// budget, then forwarded to the inner stubs:
+///
+/// | Syscall | Compute units |
+/// |---|---|
+/// | `sol_log` | the message length, at least 100 |
+/// | `sol_log_data` | 100, plus 100 per field, plus the length of the fields |
+/// | `sol_log_compute_units`, `sol_remaining_compute_units`, `sol_get_processed_sibling_instruction`, `sol_get_stack_height` | 100 |
+/// | `sol_invoke_signed` | 1000 |
+/// | `sol_get_sysvar` | 100, plus the length / 250, at least 10 |
+/// | `sol_get_clock_sysvar` and the other getters of a whole sysvar | 100, plus the size of the sysvar |
+/// | `sol_get_epoch_stake` | 110 for a vote account, 100 for the total stake |
+/// | `sol_get_return_data` | 100, plus (the data length + 32) / 250 |
+/// | `sol_set_return_data` | 100, plus the data length / 250 |
+/// | `sol_memcpy`, `sol_memmove`, `sol_memcmp`, `sol_memset` | the length / 250, at least 10 |
+///
+/// Only the calls going through the stubs are metered. The compute units of
+/// the program's own instructions, which often dominate, are not, and
+/// neither are the memory helpers of `solana-program-memory`, which do not
+/// call the stubs on the host, nor the serialization of the accounts of
+/// `sol_invoke_signed`. So the meter bounds what the syscalls of a code path
+/// cost, for relative comparisons, rather than estimating its cost on chain.
+///
+/// A call exceeding the budget panics, as the runtime aborts the program,
+/// after the whole budget is recorded as consumed.
+///
+/// Clones share the meter, so a test keeps one to read the consumption of
+/// the one it installs with [`set_syscall_stubs`]:
+///
+/// ```
+/// use solana_sysvar::{
+///     clock::Clock,
+///     program_stubs::{self, ComputeMeterStub},
+///     Sysvar,
+/// };
+///
+/// let meter = ComputeMeterStub::new(1_000);
+/// let previous = program_stubs::set_syscall_stubs(Box::new(meter.clone()));
+/// program_stubs::set_default_sysvars_enabled(true);
+///
+/// program_stubs::sol_log("hello");
+/// let _clock = Clock::get();
+/// assert!(meter.consumed() <= 250);
+///
+/// program_stubs::set_default_sysvars_enabled(false);
+/// program_stubs::set_syscall_stubs(previous);
+/// ```
+#[derive(Clone)]
+pub struct ComputeMeterStub {
+    inner: Arc<dyn SyscallStubs>,
+    budget: u64,
+    meter: Arc<Mutex<ComputeMeter>>,
+}
+
+impl ComputeMeterStub {
+    /// Meter `budget` compute units, forwarding the calls to the default
+    /// stubs.
+    pub fn new(budget: u64) -> Self {
+        Self::with_stubs(Box::new(DefaultSyscallStubs {}), budget)
+    }
+
+    /// Meter `budget` compute units, forwarding the calls to `inner`.
+    pub fn with_stubs(inner: Box<dyn SyscallStubs>, budget: u64) -> Self {
+        Self {
+            inner: Arc::from(inner),
+            budget,
+            meter: Arc::default(),
+        }
+    }
+
+    pub fn budget(&self) -> u64 {
+        self.budget
+    }
+
+    /// The compute units consumed so far, at most the budget.
+    pub fn consumed(&self) -> u64 {
+        self.meter.lock().unwrap().consumed
+    }
+
+    pub fn remaining(&self) -> u64 {
+        self.budget.saturating_sub(self.consumed())
+    }
+
+    /// How many times `syscall`, named as the method of [`SyscallStubs`],
+    /// was called.
+    pub fn invocations(&self, syscall: &str) -> u64 {
+        let meter = self.meter.lock().unwrap();
+        meter.invocations.get(syscall).copied().unwrap_or(0)
+    }
+
+    /// Clear the consumption and the invocation counts.
+    pub fn reset(&self) {
+        *self.meter.lock().unwrap() = ComputeMeter::default();
+    }
+
+    fn consume(&self, syscall: &'static str, units: u64) {
+        let mut meter = self.meter.lock().unwrap();
+        *meter.invocations.entry(syscall).or_default() += 1;
+        let remaining = self.budget.saturating_sub(meter.consumed);
+        meter.consumed = meter.consumed.saturating_add(units).min(self.budget);
+        // release the meter before panicking, so that it can still be read
+        drop(meter);
+        if units > remaining {
+            panic!(
+                "Computational budget exceeded: {syscall} consumes {units} units, \
+                 {remaining} remaining"
+            );
+        }
+    }
+
+    fn consume_mem_op(&self, syscall: &'static str, n: usize) {
+        let units = (n as u64 / CPI_BYTES_PER_UNIT).max(MEM_OP_BASE_COST);
+        self.consume(syscall, units);
+    }
+}
+
+impl SyscallStubs for ComputeMeterStub {
+    fn sol_log(&self, message: &str) {
+        self.consume("sol_log", (message.len() as u64).max(SYSCALL_BASE_COST));
+        self.inner.sol_log(message);
+    }
+    fn sol_log_compute_units(&self) {
+        self.consume("sol_log_compute_units", SYSCALL_BASE_COST);
+        self.inner.sol_log(&format!(
+            "Program consumption: {} units remaining",
+            self.remaining()
+        ));
+    }
+    fn sol_remaining_compute_units(&self) -> u64 {
+        self.consume("sol_remaining_compute_units", SYSCALL_BASE_COST);
+        self.remaining()
+    }
+    fn sol_invoke_signed(
+        &self,
+        instruction: &Instruction,
+        account_infos: &[AccountInfo],
+        signers_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
+        self.consume("sol_invoke_signed", INVOKE_UNITS);
+        self.inner
+            .sol_invoke_signed(instruction, account_infos, signers_seeds)
+    }
+    fn sol_get_sysvar(
+        &self,
+        sysvar_id_addr: *const u8,
+        var_addr: *mut u8,
+        offset: u64,
+        length: u64,
+    ) -> u64 {
+        let units =
+            SYSVAR_BASE_COST.saturating_add((length / CPI_BYTES_PER_UNIT).max(MEM_OP_BASE_COST));
+        self.consume("sol_get_sysvar", units);
+        self.inner
+            .sol_get_sysvar(sysvar_id_addr, var_addr, offset, length)
+    }
+    fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+        let units = SYSVAR_BASE_COST + size_of::<Clock>() as u64;
+        self.consume("sol_get_clock_sysvar", units);
+        self.inner.sol_get_clock_sysvar(var_addr)
+    }
+    fn sol_get_epoch_schedule_sysvar(&self, var_addr: *mut u8) -> u64 {
+        let units = SYSVAR_BASE_COST + size_of::<EpochSchedule>() as u64;
+        self.consume("sol_get_epoch_schedule_sysvar", units);
+        self.inner.sol_get_epoch_schedule_sysvar(var_addr)
+    }
+    #[allow(deprecated)]
+    fn sol_get_fees_sysvar(&self, var_addr: *mut u8) -> u64 {
+        let units = SYSVAR_BASE_COST + size_of::<crate::fees::Fees>() as u64;
+        self.consume("sol_get_fees_sysvar", units);
+        self.inner.sol_get_fees_sysvar(var_addr)
+    }
+    fn sol_get_rent_sysvar(&self, var_addr: *mut u8) -> u64 {
+        let units = SYSVAR_BASE_COST + size_of::<Rent>() as u64;
+        self.consume("sol_get_rent_sysvar", units);
+        self.inner.sol_get_rent_sysvar(var_addr)
+    }
+    fn sol_get_epoch_rewards_sysvar(&self, var_addr: *mut u8) -> u64 {
+        let units = SYSVAR_BASE_COST + size_of::<EpochRewards>() as u64;
+        self.consume("sol_get_epoch_rewards_sysvar", units);
+        self.inner.sol_get_epoch_rewards_sysvar(var_addr)
+    }
+    fn sol_get_last_restart_slot(&self, var_addr: *mut u8) -> u64 {
+        let units = SYSVAR_BASE_COST + size_of::<LastRestartSlot>() as u64;
+        self.consume("sol_get_last_restart_slot", units);
+        self.inner.sol_get_last_restart_slot(var_addr)
+    }
+    fn sol_get_epoch_stake(&self, vote_address: *const u8) -> u64 {
+        // a null vote address asks for the total stake
+        let units = if vote_address.is_null() {
+            SYSCALL_BASE_COST
+        } else {
+            SYSCALL_BASE_COST + MEM_OP_BASE_COST
+        };
+        self.consume("sol_get_epoch_stake", units);
+        self.inner.sol_get_epoch_stake(vote_address)
+    }
+    /// # Safety
+    unsafe fn sol_memcpy(&self, dst: *mut u8, src: *const u8, n: usize) {
+        self.consume_mem_op("sol_memcpy", n);
+        self.inner.sol_memcpy(dst, src, n)
+    }
+    /// # Safety
+    unsafe fn sol_memmove(&self, dst: *mut u8, src: *const u8, n: usize) {
+        self.consume_mem_op("sol_memmove", n);
+        self.inner.sol_memmove(dst, src, n)
+    }
+    /// # Safety
+    unsafe fn sol_memcmp(&self, s1: *const u8, s2: *const u8, n: usize, result: *mut i32) {
+        self.consume_mem_op("sol_memcmp", n);
+        self.inner.sol_memcmp(s1, s2, n, result)
+    }
+    /// # Safety
+    unsafe fn sol_memset(&self, s: *mut u8, c: u8, n: usize) {
+        self.consume_mem_op("sol_memset", n);
+        self.inner.sol_memset(s, c, n)
+    }
+    fn sol_get_return_data(&self) -> Option<(Pubkey, Vec<u8>)> {
+        let return_data = self.inner.sol_get_return_data();
+        let length = return_data.as_ref().map_or(0, |(_, data)| data.len());
+        let units =
+            SYSCALL_BASE_COST + (length as u64 + size_of::<Pubkey>() as u64) / CPI_BYTES_PER_UNIT;
+        self.consume("sol_get_return_data", units);
+        return_data
+    }
+    fn sol_set_return_data(&self, data: &[u8]) {
+        let units = SYSCALL_BASE_COST + data.len() as u64 / CPI_BYTES_PER_UNIT;
+        self.consume("sol_set_return_data", units);
+        self.inner.sol_set_return_data(data)
+    }
+    fn sol_log_data(&self, fields: &[&[u8]]) {
+        let units = fields.iter().fold(
+            SYSCALL_BASE_COST + SYSCALL_BASE_COST * fields.len() as u64,
+            |units, field| units + field.len() as u64,
+        );
+        self.consume("sol_log_data", units);
+        self.inner.sol_log_data(fields)
+    }
+    fn sol_get_processed_sibling_instruction(&self, index: usize) -> Option<Instruction> {
+        self.consume("sol_get_processed_sibling_instruction", SYSCALL_BASE_COST);
+        self.inner.sol_get_processed_sibling_instruction(index)
+    }
+    fn sol_get_stack_height(&self) -> u64 {
+        self.consume("sol_get_stack_height", SYSCALL_BASE_COST);
+        self.inner.sol_get_stack_height()
+    }
+}
+
 pub fn sol_log(message: &str) {
     SYSCALL_STUBS.read().unwrap().sol_log(message);
 }
@@ -188,3 +560,153 @@ pub fn sol_get_stack_height() -> u64 {
 pub(crate) fn sol_get_fees_sysvar(var_addr: *mut u8) -> u64 {
     SYSCALL_STUBS.read().unwrap().sol_get_fees_sysvar(var_addr)
 }
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*, crate::Sysvar, serial_test::serial, solana_program_error::ProgramError,
+        std::sync::Mutex,
+    };
+
+    struct LoggingSyscallStubs {
+        logs: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl SyscallStubs for LoggingSyscallStubs {
+        fn sol_log(&self, message: &str) {
+            self.logs.lock().unwrap().push(message.to_string());
+        }
+    }
+
+    #[test]
+    #[serial]
+    fn test_default_sysvars_disabled() {
+        let previous = set_syscall_stubs(Box::new(DefaultSyscallStubs {}));
+        assert_eq!(Clock::get(), Err(ProgramError::UnsupportedSysvar));
+        assert_eq!(Rent::get(), Err(ProgramError::UnsupportedSysvar));
+
+        let clock = Clock {
+            slot: 7,
+            ..Clock::default()
+        };
+        assert_eq!(Clock::get_or(clock.clone()), clock);
+        set_syscall_stubs(previous);
+    }
+
+    #[test]
+    #[serial]
+    fn test_default_sysvars_enabled() {
+        let previous = set_syscall_stubs(Box::new(DefaultSyscallStubs {}));
+        set_default_sysvars_enabled(true);
+        assert_eq!(Clock::get(), Ok(Clock::default()));
+        assert_eq!(Rent::get(), Ok(Rent::default()));
+        assert_eq!(EpochSchedule::get(), Ok(EpochSchedule::default()));
+        assert_eq!(EpochRewards::get(), Ok(EpochRewards::default()));
+        assert_eq!(LastRestartSlot::get(), Ok(LastRestartSlot::default()));
+        assert_eq!(
+            Clock::get_or(Clock {
+                slot: 7,
+                ..Clock::default()
+            }),
+            Clock::default()
+        );
+
+        // other sysvars are still not served
+        let mut buffer = [0u8; 8];
+        assert_eq!(
+            crate::get_sysvar(&mut buffer, &sysvar::slot_hashes::id(), 0, 8),
+            Err(ProgramError::UnsupportedSysvar)
+        );
+        // reads past the end of the sysvar fail
+        assert_eq!(
+            crate::get_sysvar(&mut buffer, &sysvar::last_restart_slot::id(), 4, 8),
+            Err(ProgramError::InvalidArgument)
+        );
+
+        set_default_sysvars_enabled(false);
+        assert_eq!(Clock::get(), Err(ProgramError::UnsupportedSysvar));
+        set_syscall_stubs(previous);
+    }
+
+    #[test]
+    #[serial]
+    fn test_default_sysvars_warning() {
+        let logs = Arc::new(Mutex::new(Vec::new()));
+        let previous = set_syscall_stubs(Box::new(LoggingSyscallStubs { logs: logs.clone() }));
+
+        set_default_sysvars_enabled(true);
+        assert!(logs.lock().unwrap().is_empty());
+        assert_eq!(Clock::get(), Ok(Clock::default()));
+        assert_eq!(Rent::get(), Ok(Rent::default()));
+        assert_eq!(Clock::get(), Ok(Clock::default()));
+        assert_eq!(
+            *logs.lock().unwrap(),
+            ["SyscallStubs: sol_get_sysvar() serving default sysvar values"]
+        );
+
+        // enabling the mode again warns again
+        set_default_sysvars_enabled(true);
+        assert_eq!(Clock::get(), Ok(Clock::default()));
+        assert_eq!(logs.lock().unwrap().len(), 2);
+
+        set_default_sysvars_enabled(false);
+        set_syscall_stubs(previous);
+    }
+
+    #[test]
+    #[serial]
+    fn test_compute_meter_counts() {
+        let meter = ComputeMeterStub::new(10_000);
+        let previous = set_syscall_stubs(Box::new(meter.clone()));
+        set_default_sysvars_enabled(true);
+
+        sol_log("hello");
+        assert_eq!(Clock::get(), Ok(Clock::default()));
+        assert_eq!(Rent::get(), Ok(Rent::default()));
+        assert_eq!(meter.invocations("sol_log"), 1);
+        assert_eq!(meter.invocations("sol_get_sysvar"), 2);
+        assert_eq!(meter.invocations("sol_log_data"), 0);
+        assert_eq!(meter.consumed(), 100 + 2 * 110);
+        assert_eq!(meter.remaining(), 10_000 - 320);
+
+        // long messages cost their length, reading the meter costs too
+        sol_log(&"x".repeat(300));
+        assert_eq!(sol_remaining_compute_units(), 10_000 - 320 - 300 - 100);
+        sol_log_data(&[b"ab", b"cde"]);
+        assert_eq!(meter.consumed(), 720 + 305);
+
+        meter.reset();
+        assert_eq!(meter.consumed(), 0);
+        assert_eq!(meter.invocations("sol_log"), 0);
+
+        set_default_sysvars_enabled(false);
+        set_syscall_stubs(previous);
+    }
+
+    #[test]
+    #[serial]
+    fn test_compute_meter_budget_exhausted() {
+        let meter = ComputeMeterStub::new(150);
+        let previous = set_syscall_stubs(Box::new(meter.clone()));
+        set_default_sysvars_enabled(true);
+
+        sol_log("hello");
+        assert_eq!(meter.remaining(), 50);
+        let result = std::panic::catch_unwind(Clock::get);
+        let message = result.unwrap_err().downcast::<String>().unwrap();
+        assert_eq!(
+            *message,
+            "Computational budget exceeded: sol_get_sysvar consumes 110 units, 50 remaining"
+        );
+        assert_eq!(meter.consumed(), 150);
+        assert_eq!(meter.remaining(), 0);
+        assert_eq!(meter.invocations("sol_get_sysvar"), 1);
+
+        // the budget stays exhausted
+        assert!(std::panic::catch_unwind(|| sol_log("hello")).is_err());
+        assert_eq!(meter.consumed(), 150);
+
+        set_default_sysvars_enabled(false);
+        set_syscall_stubs(previous);
+    }
+}
@@ -37,6 +37,14 @@ use {
 )]
 pub const MAX_ENTRIES: usize = 150;
 
+/// The longest account data [`SysvarSerialize::from_account_info`] and
+/// `from_account_view` deserialize on chain, the length of the sysvar with [`MAX_ENTRIES`]
+/// entries. [`ProgramError::UnsupportedSysvar`] is returned for longer data.
+/// Off chain, data of any length is deserialized.
+///
+/// [`ProgramError::UnsupportedSysvar`]: solana_program_error::ProgramError::UnsupportedSysvar
+pub const MAX_SAFE_DESERIALIZE_LEN: usize = 6008;
+
 impl_sysvar_id!(RecentBlockhashes);
 
 #[deprecated(
@@ -151,7 +159,18 @@ impl<T: Ord> Iterator for IntoIterSorted<T> {
     }
 }
 
-impl Sysvar for RecentBlockhashes {}
+impl Sysvar for RecentBlockhashes {
+    /// Always returns [`ProgramError::UnsupportedSysvar`], after logging a
+    /// message: the sysvar is too large to be loaded with a syscall.
+    ///
+    /// [`ProgramError::UnsupportedSysvar`]: solana_program_error::ProgramError::UnsupportedSysvar
+    fn get() -> Result<Self, solana_program_error::ProgramError> {
+        crate::availability::log(
+            "RecentBlockhashes sysvar cannot be loaded with `Sysvar::get`, pass its account instead",
+        );
+        Err(solana_program_error::ProgramError::UnsupportedSysvar)
+    }
+}
 
 #[cfg(feature = "bincode")]
 impl SysvarSerialize for RecentBlockhashes {
@@ -159,6 +178,22 @@ impl SysvarSerialize for RecentBlockhashes {
         // hard-coded so that we don't have to construct an empty
         6008 // golden, update if MAX_ENTRIES changes
     }
+
+    fn from_account_info(
+        account_info: &solana_account_info::AccountInfo,
+    ) -> Result<Self, solana_program_error::ProgramError> {
+        crate::from_account_info_bounded(
+            account_info,
+            crate::on_chain_limit(MAX_SAFE_DESERIALIZE_LEN),
+        )
+    }
+
+    #[cfg(feature = "account-view")]
+    fn from_account_view(
+        account: &solana_account_view::AccountView,
+    ) -> Result<Self, solana_program_error::ProgramError> {
+        crate::from_account_view_bounded(account, crate::on_chain_limit(MAX_SAFE_DESERIALIZE_LEN))
+    }
 }
 
 impl Deref for RecentBlockhashes {
@@ -187,5 +222,53 @@ mod tests {
                 as usize,
             RecentBlockhashes::size_of()
         );
+        assert_eq!(RecentBlockhashes::size_of(), MAX_SAFE_DESERIALIZE_LEN);
+    }
+
+    #[test]
+    fn test_from_account_info_bounded() {
+        let recent_blockhashes = RecentBlockhashes(vec![Entry::new(&Hash::default(), 5); 2]);
+        let key = id();
+        let owner = solana_sdk_ids::sysvar::id();
+        let mut lamports = 0;
+        let mut data = vec![0; MAX_SAFE_DESERIALIZE_LEN];
+        bincode::serialize_into(&mut data[..], &recent_blockhashes).unwrap();
+        let account_info = solana_account_info::AccountInfo::new(
+            &key,
+            false,
+            false,
+            &mut lamports,
+            &mut data,
+            &owner,
+            false,
+        );
+        assert_eq!(
+            RecentBlockhashes::from_account_info(&account_info),
+            Ok(recent_blockhashes.clone())
+        );
+        assert_eq!(
+            crate::from_account_info_bounded(&account_info, MAX_SAFE_DESERIALIZE_LEN),
+            Ok(recent_blockhashes)
+        );
+
+        let mut data = vec![0; MAX_SAFE_DESERIALIZE_LEN + 1];
+        let account_info = solana_account_info::AccountInfo::new(
+            &key,
+            false,
+            false,
+            &mut lamports,
+            &mut data,
+            &owner,
+            false,
+        );
+        assert_eq!(
+            crate::from_account_info_bounded::<RecentBlockhashes>(
+                &account_info,
+                MAX_SAFE_DESERIALIZE_LEN
+            ),
+            Err(solana_program_error::ProgramError::UnsupportedSysvar)
+        );
+        // off chain, longer data is still deserialized
+        assert!(RecentBlockhashes::from_account_info(&account_info).is_ok());
     }
 }
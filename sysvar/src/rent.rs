@@ -71,7 +71,7 @@
 //!
 //!     assert!(rent::check_id(rent_account_info.key));
 //!
-//!     let rent = Rent::from_account_info(rent_account_info)?;
+//!     let rent = Rent::from_account_info_checked(rent_account_info)?;
 //!     msg!("rent: {:#?}", rent);
 //!
 //!     Ok(())
@@ -81,7 +81,8 @@
 //! # let p = Rent::id();
 //! # let l = &mut 1009200;
 //! # let d = &mut vec![152, 13, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 64, 100];
-//! # let a = AccountInfo::new(&p, false, false, l, d, &p, false);
+//! # let o = solana_sdk_ids::sysvar::ID;
+//! # let a = AccountInfo::new(&p, false, false, l, d, &o, false);
 //! # let accounts = &[a.clone(), a];
 //! # process_instruction(
 //! #     &Pubkey::new_unique(),
@@ -136,6 +137,73 @@ impl Sysvar for Rent {
 #[cfg(feature = "bincode")]
 impl SysvarSerialize for Rent {}
 
+/// [`solana_rent::MAX_PERMITTED_DATA_LENGTH`] as a `usize`.
+const MAX_PERMITTED_DATA_LENGTH: usize = solana_rent::MAX_PERMITTED_DATA_LENGTH as usize;
+
+/// Returns the lamports to add to an account holding `current_lamports`
+/// with `current_len` bytes of data, so that it is rent exempt after being
+/// resized to `new_len` bytes.
+///
+/// Returns `0` if the balance already covers the new length, for example
+/// when an exempt account shrinks. The result saturates to `u64::MAX` if
+/// `new_len` exceeds the maximum permitted data length, or if the rent
+/// parameters overflow, as no balance makes such an account exempt.
+pub fn lamports_needed_for_resize(
+    rent: &Rent,
+    current_lamports: u64,
+    current_len: usize,
+    new_len: usize,
+) -> u64 {
+    // the minimum balance grows with the data length
+    if new_len <= current_len
+        && rent
+            .try_minimum_balance(current_len)
+            .is_some_and(|minimum| current_lamports >= minimum)
+    {
+        return 0;
+    }
+    rent.try_minimum_balance(new_len)
+        .map_or(u64::MAX, |minimum| minimum.saturating_sub(current_lamports))
+}
+
+/// Returns the largest data length for which `lamports` is a rent exempt
+/// balance, the inverse of [`Rent::minimum_balance`].
+///
+/// The result saturates to the maximum permitted data length, and to `0`
+/// when `lamports` is below the minimum balance of an empty account, or if
+/// the rent parameters overflow. Callers should check the empty account
+/// with [`Rent::is_exempt`] to tell the latter cases apart.
+///
+/// The length is computed with integer math from the minimum balances of
+/// the smallest and largest accounts. It is exact for the default
+/// exemption thresholds; for other thresholds it is corrected by a few
+/// steps to account for the rounding of the floating-point computation.
+pub fn max_data_len_for_balance(rent: &Rent, lamports: u64) -> usize {
+    let (Some(base), Some(max)) = (
+        rent.try_minimum_balance(0),
+        rent.try_minimum_balance(MAX_PERMITTED_DATA_LENGTH),
+    ) else {
+        return 0;
+    };
+    if lamports < base {
+        return 0;
+    }
+    if lamports >= max {
+        return MAX_PERMITTED_DATA_LENGTH;
+    }
+
+    // `base <= lamports < max`, so the estimate is below the maximum length
+    let mut len = (u128::from(lamports - base) * MAX_PERMITTED_DATA_LENGTH as u128
+        / u128::from(max - base)) as usize;
+    while len > 0 && rent.minimum_balance(len) > lamports {
+        len -= 1;
+    }
+    while rent.minimum_balance(len + 1) <= lamports {
+        len += 1;
+    }
+    len
+}
+
 #[cfg(test)]
 mod tests {
     use {super::*, crate::Sysvar, serial_test::serial};
@@ -154,4 +222,93 @@ mod tests {
         let got = Rent::get().unwrap();
         assert_eq!(got, expected);
     }
+
+    #[allow(deprecated)]
+    fn rents() -> [Rent; 5] {
+        [
+            Rent::default(),
+            Rent::with_lamports_per_byte(1),
+            Rent::with_lamports_per_byte(3_480),
+            Rent {
+                exemption_threshold: 2.0f64.to_le_bytes(),
+                ..Rent::default()
+            },
+            Rent {
+                exemption_threshold: 1.2345f64.to_le_bytes(),
+                ..Rent::default()
+            },
+        ]
+    }
+
+    #[test]
+    fn test_max_data_len_for_balance() {
+        for rent in rents() {
+            let base = rent.minimum_balance(0);
+            let max = rent.minimum_balance(MAX_PERMITTED_DATA_LENGTH);
+            for lamports in (0..max + 1_000_000).step_by(max as usize / 997) {
+                let len = max_data_len_for_balance(&rent, lamports);
+                if lamports < base {
+                    assert_eq!(len, 0);
+                    continue;
+                }
+                assert!(rent.minimum_balance(len) <= lamports);
+                if len < MAX_PERMITTED_DATA_LENGTH {
+                    assert!(rent.minimum_balance(len + 1) > lamports);
+                }
+            }
+
+            // exact boundaries
+            for len in [0, 1, 165, 10_240, MAX_PERMITTED_DATA_LENGTH - 1] {
+                let minimum = rent.minimum_balance(len);
+                assert_eq!(max_data_len_for_balance(&rent, minimum), len);
+                assert_eq!(
+                    max_data_len_for_balance(&rent, minimum - 1),
+                    len.saturating_sub(1)
+                );
+            }
+            assert_eq!(
+                max_data_len_for_balance(&rent, max),
+                MAX_PERMITTED_DATA_LENGTH
+            );
+            assert_eq!(
+                max_data_len_for_balance(&rent, u64::MAX),
+                MAX_PERMITTED_DATA_LENGTH
+            );
+        }
+
+        assert_eq!(
+            max_data_len_for_balance(&Rent::free(), 0),
+            MAX_PERMITTED_DATA_LENGTH
+        );
+        let overflowing = Rent::with_lamports_per_byte(u64::MAX);
+        assert_eq!(max_data_len_for_balance(&overflowing, u64::MAX), 0);
+    }
+
+    #[test]
+    fn test_lamports_needed_for_resize() {
+        for rent in rents() {
+            let minimum = rent.minimum_balance(200);
+            assert_eq!(lamports_needed_for_resize(&rent, minimum, 100, 200), 0);
+            assert_eq!(lamports_needed_for_resize(&rent, minimum - 1, 100, 200), 1);
+            assert_eq!(lamports_needed_for_resize(&rent, 0, 100, 200), minimum);
+            assert_eq!(lamports_needed_for_resize(&rent, u64::MAX, 100, 200), 0);
+
+            // shrinking an exempt account, or one short of the new minimum
+            assert_eq!(lamports_needed_for_resize(&rent, minimum, 200, 100), 0);
+            let minimum = rent.minimum_balance(100);
+            assert_eq!(lamports_needed_for_resize(&rent, minimum - 1, 200, 100), 1);
+
+            // the top-up covers exactly the new length
+            let lamports = rent.minimum_balance(100);
+            let top_up = lamports_needed_for_resize(&rent, lamports, 100, 10_000);
+            assert_eq!(max_data_len_for_balance(&rent, lamports + top_up), 10_000);
+
+            assert_eq!(
+                lamports_needed_for_resize(&rent, u64::MAX, 0, MAX_PERMITTED_DATA_LENGTH + 1),
+                u64::MAX
+            );
+        }
+        let overflowing = Rent::with_lamports_per_byte(u64::MAX);
+        assert_eq!(lamports_needed_for_resize(&overflowing, 0, 0, 0), u64::MAX);
+    }
 }
@@ -2,12 +2,15 @@
 //!
 //! The _slot hashes sysvar_ provides access to the [`SlotHashes`] type.
 //!
-//! The [`SysvarSerialize::from_account_info`] and [`Sysvar::get`] methods always return
+//! The [`Sysvar::get`] method always returns
 //! [`solana_program_error::ProgramError::UnsupportedSysvar`] because this sysvar account is too large
-//! to process on-chain. Thus this sysvar cannot be accessed on chain, though
-//! one can still use the [`SysvarId::id`], [`SysvarId::check_id`] and
-//! [`SysvarSerialize::size_of`] methods in an on-chain program, and it can be accessed
-//! off-chain through RPC.
+//! to process on-chain. On chain, [`SysvarSerialize::from_account_info`] and
+//! `from_account_view` also return this error, without deserializing, when
+//! the account data is longer than [`MAX_SAFE_DESERIALIZE_LEN`], which the full sysvar is.
+//! Thus this sysvar cannot be accessed on chain, though one can still use the
+//! [`SysvarId::id`], [`SysvarId::check_id`] and [`SysvarSerialize::size_of`]
+//! methods in an on-chain program, and it can be accessed off-chain through
+//! RPC or deserialized from its account.
 //!
 //! [`SysvarId::id`]: https://docs.rs/solana-sysvar-id/latest/solana_sysvar_id/trait.SysvarId.html#tymethod.id
 //! [`SysvarId::check_id`]: https://docs.rs/solana-sysvar-id/latest/solana_sysvar_id/trait.SysvarId.html#tymethod.check_id
@@ -56,6 +59,13 @@ const U64_SIZE: usize = std::mem::size_of::<u64>();
 #[cfg(any(feature = "bytemuck", feature = "bincode"))]
 const SYSVAR_LEN: usize = 20_488; // golden, update if MAX_ENTRIES changes
 
+/// The longest account data [`SysvarSerialize::from_account_info`] and
+/// `from_account_view` deserialize on chain, [`ProgramError::UnsupportedSysvar`] is returned for
+/// longer data. Off chain, data of any length is deserialized.
+///
+/// [`ProgramError::UnsupportedSysvar`]: solana_program_error::ProgramError::UnsupportedSysvar
+pub const MAX_SAFE_DESERIALIZE_LEN: usize = crate::DESERIALIZE_HEAP_BUDGET;
+
 pub use {
     solana_sdk_ids::sysvar::slot_hashes::{check_id, id, ID},
     solana_slot_hashes::SlotHashes,
@@ -71,10 +81,20 @@ impl SysvarSerialize for SlotHashes {
         SYSVAR_LEN
     }
     fn from_account_info(
-        _account_info: &AccountInfo,
+        account_info: &AccountInfo,
     ) -> Result<Self, solana_program_error::ProgramError> {
-        // This sysvar is too large to bincode::deserialize in-program
-        Err(solana_program_error::ProgramError::UnsupportedSysvar)
+        // The full sysvar is too large to bincode::deserialize in-program
+        crate::from_account_info_bounded(
+            account_info,
+            crate::on_chain_limit(MAX_SAFE_DESERIALIZE_LEN),
+        )
+    }
+    #[cfg(feature = "account-view")]
+    fn from_account_view(
+        account: &solana_account_view::AccountView,
+    ) -> Result<Self, solana_program_error::ProgramError> {
+        // The full sysvar is too large to bincode::deserialize in-program
+        crate::from_account_view_bounded(account, crate::on_chain_limit(MAX_SAFE_DESERIALIZE_LEN))
     }
 }
 
@@ -197,6 +217,50 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_from_account_info() {
+        let slot_hashes = (0..MAX_ENTRIES)
+            .map(|slot| (slot as Slot, hash(&slot.to_le_bytes())))
+            .collect::<SlotHashes>();
+        let key = id();
+        let owner = solana_sdk_ids::sysvar::id();
+        let mut lamports = 0;
+        let mut data = bincode::serialize(&slot_hashes).unwrap();
+        assert!(data.len() > MAX_SAFE_DESERIALIZE_LEN);
+        let account_info =
+            AccountInfo::new(&key, false, false, &mut lamports, &mut data, &owner, false);
+
+        // off chain, the full sysvar is deserialized
+        assert_eq!(
+            SlotHashes::from_account_info(&account_info),
+            Ok(slot_hashes)
+        );
+    }
+
+    #[test]
+    fn test_from_account_info_bounded() {
+        let slot_hashes = SlotHashes::new(&[(1, hash(&[1])), (2, hash(&[2]))]);
+        let key = id();
+        let owner = solana_sdk_ids::sysvar::id();
+        let mut lamports = 0;
+        let mut data = vec![0; MAX_SAFE_DESERIALIZE_LEN];
+        bincode::serialize_into(&mut data[..], &slot_hashes).unwrap();
+        let account_info =
+            AccountInfo::new(&key, false, false, &mut lamports, &mut data, &owner, false);
+        assert_eq!(
+            crate::from_account_info_bounded(&account_info, MAX_SAFE_DESERIALIZE_LEN),
+            Ok(slot_hashes)
+        );
+
+        let mut data = vec![0; MAX_SAFE_DESERIALIZE_LEN + 1];
+        let account_info =
+            AccountInfo::new(&key, false, false, &mut lamports, &mut data, &owner, false);
+        assert_eq!(
+            crate::from_account_info_bounded::<SlotHashes>(&account_info, MAX_SAFE_DESERIALIZE_LEN),
+            Err(solana_program_error::ProgramError::UnsupportedSysvar)
+        );
+    }
+
     fn mock_slot_hashes(slot_hashes: &SlotHashes) {
         // The data is always `SlotHashes::size_of()`.
         let mut data = vec![0; SlotHashes::size_of()];
@@ -2,12 +2,15 @@
 //!
 //! The _slot history sysvar_ provides access to the [`SlotHistory`] type.
 //!
-//! The [`SysvarSerialize::from_account_info`] and [`Sysvar::get`] methods always return
+//! The [`Sysvar::get`] method always returns
 //! [`ProgramError::UnsupportedSysvar`] because this sysvar account is too large
-//! to process on-chain. Thus this sysvar cannot be accessed on chain, though
-//! one can still use the [`SysvarId::id`], [`SysvarId::check_id`] and
-//! [`SysvarSerialize::size_of`] methods in an on-chain program, and it can be accessed
-//! off-chain through RPC.
+//! to process on-chain. On chain, [`SysvarSerialize::from_account_info`] and
+//! `from_account_view` also return this error, without deserializing, when
+//! the account data is longer than [`MAX_SAFE_DESERIALIZE_LEN`], which the sysvar always is.
+//! Thus this sysvar cannot be accessed on chain, though one can still use the
+//! [`SysvarId::id`], [`SysvarId::check_id`] and [`SysvarSerialize::size_of`]
+//! methods in an on-chain program, and it can be accessed off-chain through
+//! RPC or deserialized from its account.
 //!
 //! [`SysvarId::id`]: https://docs.rs/solana-sysvar-id/latest/solana_sysvar_id/trait.SysvarId.html#tymethod.id
 //! [`SysvarId::check_id`]: https://docs.rs/solana-sysvar-id/latest/solana_sysvar_id/trait.SysvarId.html#tymethod.check_id
@@ -56,6 +59,12 @@ pub use {
     solana_sdk_ids::sysvar::slot_history::{check_id, id, ID},
     solana_slot_history::SlotHistory,
 };
+
+/// The longest account data [`SysvarSerialize::from_account_info`] and
+/// `from_account_view` deserialize on chain, [`ProgramError::UnsupportedSysvar`] is returned for
+/// longer data. Off chain, data of any length is deserialized.
+pub const MAX_SAFE_DESERIALIZE_LEN: usize = crate::DESERIALIZE_HEAP_BUDGET;
+
 impl Sysvar for SlotHistory {}
 #[cfg(feature = "bincode")]
 impl SysvarSerialize for SlotHistory {
@@ -64,9 +73,17 @@ impl SysvarSerialize for SlotHistory {
         // hard-coded so that we don't have to construct an empty
         131_097 // golden, update if MAX_ENTRIES changes
     }
-    fn from_account_info(_account_info: &AccountInfo) -> Result<Self, ProgramError> {
+    fn from_account_info(account_info: &AccountInfo) -> Result<Self, ProgramError> {
         // This sysvar is too large to bincode::deserialize in-program
-        Err(ProgramError::UnsupportedSysvar)
+        crate::from_account_info_bounded(
+            account_info,
+            crate::on_chain_limit(MAX_SAFE_DESERIALIZE_LEN),
+        )
+    }
+    #[cfg(feature = "account-view")]
+    fn from_account_view(account: &solana_account_view::AccountView) -> Result<Self, ProgramError> {
+        // The full sysvar is too large to bincode::deserialize in-program
+        crate::from_account_view_bounded(account, crate::on_chain_limit(MAX_SAFE_DESERIALIZE_LEN))
     }
 }
 
@@ -80,4 +97,54 @@ mod tests {
             bincode::serialized_size(&SlotHistory::default()).unwrap() as usize
         );
     }
+
+    #[test]
+    fn test_from_account_info() {
+        let mut slot_history = SlotHistory::default();
+        slot_history.add(42);
+        let key = id();
+        let owner = solana_sdk_ids::sysvar::id();
+        let mut lamports = 0;
+        let mut data = bincode::serialize(&slot_history).unwrap();
+        assert!(data.len() > MAX_SAFE_DESERIALIZE_LEN);
+        let account_info =
+            AccountInfo::new(&key, false, false, &mut lamports, &mut data, &owner, false);
+
+        // off chain, the full sysvar is deserialized
+        assert_eq!(
+            SlotHistory::from_account_info(&account_info),
+            Ok(slot_history)
+        );
+    }
+
+    #[test]
+    fn test_from_account_info_bounded() {
+        let key = id();
+        let owner = solana_sdk_ids::sysvar::id();
+        let mut lamports = 0;
+
+        // deserialization is attempted up to the limit, and fails on the
+        // invalid data
+        let mut data = vec![u8::MAX; MAX_SAFE_DESERIALIZE_LEN];
+        let account_info =
+            AccountInfo::new(&key, false, false, &mut lamports, &mut data, &owner, false);
+        assert_eq!(
+            crate::from_account_info_bounded::<SlotHistory>(
+                &account_info,
+                MAX_SAFE_DESERIALIZE_LEN
+            ),
+            Err(ProgramError::InvalidArgument)
+        );
+
+        let mut data = vec![0; MAX_SAFE_DESERIALIZE_LEN + 1];
+        let account_info =
+            AccountInfo::new(&key, false, false, &mut lamports, &mut data, &owner, false);
+        assert_eq!(
+            crate::from_account_info_bounded::<SlotHistory>(
+                &account_info,
+                MAX_SAFE_DESERIALIZE_LEN
+            ),
+            Err(ProgramError::UnsupportedSysvar)
+        );
+    }
 }
@@ -0,0 +1,209 @@
+//! History of stake activations and de-activations.
+//!
+//! The _stake history sysvar_ provides access to the stake history of the
+//! cluster, as a list of `(Epoch, StakeHistoryEntry)` pairs sorted from the
+//! newest to the oldest epoch, holding at most [`MAX_ENTRIES`] entries.
+//!
+//! The sysvar is too large to deserialize on-chain. [`StakeHistorySysvar`]
+//! instead reads individual entries with the `sol_get_sysvar` syscall, which
+//! is routed through [`program_stubs`](crate::program_stubs) on the host.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! # use solana_program_error::ProgramError;
+//! use solana_sysvar::stake_history::StakeHistorySysvar;
+//!
+//! # fn f(epoch: u64) -> Result<(), ProgramError> {
+//! if let Some(entry) = StakeHistorySysvar.get_entry(epoch)? {
+//!     let _ = entry.effective;
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+#[cfg(feature = "serde")]
+use serde_derive::{Deserialize, Serialize};
+use {solana_clock::Epoch, solana_program_error::ProgramError};
+pub use {
+    solana_sdk_ids::sysvar::stake_history::{check_id, id, ID},
+    solana_sysvar_id::SysvarId,
+};
+
+/// Maximum number of entries kept in the stake history.
+pub const MAX_ENTRIES: usize = 512;
+
+const U64_SIZE: usize = core::mem::size_of::<u64>();
+
+/// Size of a serialized `(Epoch, StakeHistoryEntry)` pair.
+const ENTRY_SIZE: usize = U64_SIZE + core::mem::size_of::<StakeHistoryEntry>();
+
+/// The stake activation state of the cluster at the end of an epoch.
+#[repr(C)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct StakeHistoryEntry {
+    /// Effective stake at this epoch.
+    pub effective: u64,
+    /// Sum of portion of activations not yet effective.
+    pub activating: u64,
+    /// Sum of portion of deactivations not yet fully deactivated.
+    pub deactivating: u64,
+}
+
+impl StakeHistoryEntry {
+    fn from_bytes(bytes: &[u8; ENTRY_SIZE - U64_SIZE]) -> Self {
+        let read = |i: usize| {
+            let mut word = [0; U64_SIZE];
+            word.copy_from_slice(&bytes[i * U64_SIZE..(i + 1) * U64_SIZE]);
+            u64::from_le_bytes(word)
+        };
+        Self {
+            effective: read(0),
+            activating: read(1),
+            deactivating: read(2),
+        }
+    }
+}
+
+/// On-chain access to the stake history sysvar.
+///
+/// Entries are looked up without loading the whole sysvar: the entry list is
+/// binary searched with small `sol_get_sysvar` reads, since every serialized
+/// entry has the same size.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct StakeHistorySysvar;
+
+impl StakeHistorySysvar {
+    /// The number of entries in the stake history.
+    pub fn len(&self) -> Result<usize, ProgramError> {
+        let len = read_u64(0)?;
+        usize::try_from(len)
+            .ok()
+            .filter(|&len| len <= MAX_ENTRIES)
+            .ok_or(ProgramError::InvalidAccountData)
+    }
+
+    pub fn is_empty(&self) -> Result<bool, ProgramError> {
+        self.len().map(|len| len == 0)
+    }
+
+    /// Look up the entry of `epoch`, returning `None` if the stake history has
+    /// no entry for it.
+    pub fn get_entry(&self, epoch: Epoch) -> Result<Option<StakeHistoryEntry>, ProgramError> {
+        // entries are sorted by descending epoch
+        let (mut low, mut high) = (0, self.len()?);
+        while low < high {
+            let mid = low + (high - low) / 2;
+            let offset = entry_offset(mid);
+            match read_u64(offset)?.cmp(&epoch) {
+                core::cmp::Ordering::Greater => low = mid + 1,
+                core::cmp::Ordering::Less => high = mid,
+                core::cmp::Ordering::Equal => {
+                    let mut entry = [0; ENTRY_SIZE - U64_SIZE];
+                    crate::get_sysvar(
+                        &mut entry,
+                        &ID,
+                        (offset + U64_SIZE) as u64,
+                        (ENTRY_SIZE - U64_SIZE) as u64,
+                    )?;
+                    return Ok(Some(StakeHistoryEntry::from_bytes(&entry)));
+                }
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// Offset of the `index`-th entry, after the `u64` length prefix.
+fn entry_offset(index: usize) -> usize {
+    U64_SIZE + index * ENTRY_SIZE
+}
+
+fn read_u64(offset: usize) -> Result<u64, ProgramError> {
+    let mut word = [0; U64_SIZE];
+    crate::get_sysvar(&mut word, &ID, offset as u64, U64_SIZE as u64)?;
+    Ok(u64::from_le_bytes(word))
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::tests::mock_get_sysvar_syscall, serial_test::serial};
+
+    fn entry(epoch: Epoch) -> StakeHistoryEntry {
+        StakeHistoryEntry {
+            effective: epoch * 3,
+            activating: epoch * 5,
+            deactivating: epoch * 7,
+        }
+    }
+
+    /// Serialize a stake history of `epochs`, newest first.
+    fn mock_stake_history(
+        epochs: impl DoubleEndedIterator<Item = Epoch>,
+    ) -> Vec<(Epoch, StakeHistoryEntry)> {
+        let entries: Vec<_> = epochs.rev().map(|epoch| (epoch, entry(epoch))).collect();
+        mock_get_sysvar_syscall(&bincode::serialize(&entries).unwrap());
+        entries
+    }
+
+    #[test]
+    fn test_entry_size() {
+        assert_eq!(
+            bincode::serialized_size(&(0 as Epoch, StakeHistoryEntry::default())).unwrap(),
+            ENTRY_SIZE as u64
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn test_get_entry() {
+        let entries = mock_stake_history(100..100 + MAX_ENTRIES as Epoch);
+        let sysvar = StakeHistorySysvar;
+        assert_eq!(sysvar.len(), Ok(MAX_ENTRIES));
+
+        // both ends
+        assert_eq!(sysvar.get_entry(100), Ok(Some(entry(100))));
+        assert_eq!(sysvar.get_entry(611), Ok(Some(entry(611))));
+        for (epoch, expected) in &entries {
+            assert_eq!(sysvar.get_entry(*epoch), Ok(Some(*expected)));
+        }
+
+        // outside of the history
+        assert_eq!(sysvar.get_entry(0), Ok(None));
+        assert_eq!(sysvar.get_entry(99), Ok(None));
+        assert_eq!(sysvar.get_entry(612), Ok(None));
+        assert_eq!(sysvar.get_entry(u64::MAX), Ok(None));
+    }
+
+    #[test]
+    #[serial]
+    fn test_get_entry_missing_epoch() {
+        // epochs with gaps
+        mock_stake_history((0..20).filter(|epoch| epoch % 3 != 0));
+        let sysvar = StakeHistorySysvar;
+        for epoch in 0..25 {
+            let expected = (epoch < 20 && epoch % 3 != 0).then(|| entry(epoch));
+            assert_eq!(sysvar.get_entry(epoch), Ok(expected));
+        }
+    }
+
+    #[test]
+    #[serial]
+    fn test_get_entry_empty() {
+        mock_stake_history(0..0);
+        let sysvar = StakeHistorySysvar;
+        assert_eq!(sysvar.is_empty(), Ok(true));
+        assert_eq!(sysvar.get_entry(0), Ok(None));
+    }
+
+    #[test]
+    #[serial]
+    fn test_invalid_length() {
+        mock_get_sysvar_syscall(&(MAX_ENTRIES as u64 + 1).to_le_bytes());
+        assert_eq!(
+            StakeHistorySysvar.get_entry(0),
+            Err(ProgramError::InvalidAccountData)
+        );
+    }
+}
@@ -0,0 +1,165 @@
+//! Helpers for testing programs that read custom sysvars on the host.
+//!
+//! [`impl_test_sysvar!`](crate::impl_test_sysvar) declares a fake sysvar in
+//! one line, and [`mock_get_sysvar_syscall`] serves its data to
+//! [`Sysvar::get`] through the host syscall stubs.
+//!
+//! The mocks replace the global syscall stubs, so tests using them must not
+//! run concurrently, e.g. by marking them `#[serial]`.
+//!
+//! [`Sysvar::get`]: crate::Sysvar::get
+
+use {
+    crate::{
+        get_sysvar,
+        program_stubs::{copy_sysvar_data, set_syscall_stubs, SyscallStubs},
+        SysvarSerialize,
+    },
+    solana_program_error::ProgramError,
+    solana_pubkey::Pubkey,
+};
+
+/// Implements [`SysvarId`], [`Sysvar`] and [`SysvarSerialize`] for a test
+/// sysvar type, declaring its address.
+///
+/// The type must implement `Default`, `serde::Serialize` and
+/// `serde::Deserialize`. [`Sysvar::get`] loads its bincode serialization
+/// through the `sol_get_sysvar` syscall, as mocked by
+/// [`mock_get_sysvar_syscall`](crate::test_utils::mock_get_sysvar_syscall).
+///
+/// Like `declare_id!`, the macro defines `ID`, `id()` and `check_id()` in
+/// the current module, so each test sysvar needs its own module.
+///
+/// [`SysvarId`]: solana_sysvar_id::SysvarId
+/// [`Sysvar`]: crate::Sysvar
+/// [`Sysvar::get`]: crate::Sysvar::get
+///
+/// # Examples
+///
+/// ```
+/// use {
+///     serde_derive::{Deserialize, Serialize},
+///     solana_sysvar::{test_utils::mock_get_sysvar_syscall, Sysvar, SysvarSerialize},
+///     solana_sysvar_id::SysvarId,
+/// };
+///
+/// #[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+/// struct Oracle {
+///     price: u64,
+///     slot: u64,
+/// }
+/// solana_sysvar::impl_test_sysvar!(Oracle, "TestSysvar111111111111111111111111111111111");
+///
+/// assert_eq!(Oracle::id(), ID);
+/// assert_eq!(Oracle::size_of(), 16);
+///
+/// let oracle = Oracle { price: 42, slot: 7 };
+/// mock_get_sysvar_syscall(&bincode::serialize(&oracle).unwrap());
+/// assert_eq!(Oracle::get().unwrap(), oracle);
+/// ```
+#[macro_export]
+macro_rules! impl_test_sysvar {
+    ($type:ty, $address:expr) => {
+        $crate::__private::declare_sysvar_id!($address, $type);
+
+        impl $crate::Sysvar for $type {
+            fn get() -> Result<Self, $crate::__private::ProgramError> {
+                $crate::test_utils::get_serialized_sysvar()
+            }
+        }
+
+        impl $crate::SysvarSerialize for $type {}
+    };
+}
+
+/// Loads the bincode serialization of `T` through the `sol_get_sysvar`
+/// syscall, as [`impl_test_sysvar!`](crate::impl_test_sysvar) does for
+/// [`Sysvar::get`](crate::Sysvar::get).
+///
+/// # Errors
+///
+/// Returns the errors of [`get_sysvar`], or
+/// [`ProgramError::InvalidArgument`] if the data does not deserialize.
+pub fn get_serialized_sysvar<T: SysvarSerialize>() -> Result<T, ProgramError> {
+    let len = T::size_of();
+    let mut data = vec![0; len];
+    get_sysvar(&mut data, &T::id(), 0, len as u64)?;
+    bincode::deserialize(&data).map_err(|_| ProgramError::InvalidArgument)
+}
+
+struct MockGetSysvarSyscall {
+    data: Vec<u8>,
+}
+
+impl SyscallStubs for MockGetSysvarSyscall {
+    fn sol_get_sysvar(
+        &self,
+        _sysvar_id_addr: *const u8,
+        var_addr: *mut u8,
+        offset: u64,
+        length: u64,
+    ) -> u64 {
+        copy_sysvar_data(&self.data, var_addr, offset, length)
+    }
+}
+
+/// Serve `data` to every `sol_get_sysvar` syscall, whatever the sysvar id.
+pub fn mock_get_sysvar_syscall(data: &[u8]) {
+    set_syscall_stubs(Box::new(MockGetSysvarSyscall {
+        data: data.to_vec(),
+    }));
+}
+
+struct ValidateIdSyscall {
+    data: Vec<u8>,
+    expected_id: Pubkey,
+}
+
+impl SyscallStubs for ValidateIdSyscall {
+    fn sol_get_sysvar(
+        &self,
+        sysvar_id_addr: *const u8,
+        var_addr: *mut u8,
+        offset: u64,
+        length: u64,
+    ) -> u64 {
+        // Validate that the correct sysvar id pointer was passed
+        let passed_id = unsafe { *(sysvar_id_addr as *const Pubkey) };
+        assert_eq!(passed_id, self.expected_id);
+
+        copy_sysvar_data(&self.data, var_addr, offset, length)
+    }
+}
+
+/// Serve `data` to `sol_get_sysvar` syscalls, panicking if one requests a
+/// sysvar other than `expected_id`. Returns the previous syscall stubs.
+pub fn mock_get_sysvar_syscall_with_id(data: &[u8], expected_id: &Pubkey) -> Box<dyn SyscallStubs> {
+    set_syscall_stubs(Box::new(ValidateIdSyscall {
+        data: data.to_vec(),
+        expected_id: *expected_id,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, serial_test::serial};
+
+    #[test]
+    #[serial]
+    fn test_mock_get_sysvar_syscall_out_of_range() {
+        mock_get_sysvar_syscall(&[1, 2, 3, 4]);
+        let id = Pubkey::new_unique();
+        let mut data = [0; 2];
+
+        assert_eq!(get_sysvar(&mut data, &id, 2, 2), Ok(()));
+        assert_eq!(data, [3, 4]);
+        assert_eq!(
+            get_sysvar(&mut data, &id, 3, 2),
+            Err(ProgramError::InvalidArgument)
+        );
+        assert_eq!(
+            get_sysvar(&mut data, &id, u64::MAX, 2),
+            Err(ProgramError::InvalidArgument)
+        );
+    }
+}
@@ -1115,7 +1115,10 @@ mod tests {
         solana_keypair::Keypair,
         solana_presigner::Presigner,
         solana_sha256_hasher::hash,
-        solana_signer::Signer,
+        solana_signer::{
+            programmable_signer::{ProgrammableSigner, SignerFactory},
+            Signer,
+        },
         solana_system_interface::instruction as system_instruction,
         std::mem::size_of,
     };
@@ -1484,6 +1487,55 @@ mod tests {
         assert!(tx.is_signed());
     }
 
+    #[test]
+    fn test_try_sign_programmable_signers() {
+        let program_id = Address::new_unique();
+        let signers = SignerFactory::new_unique(2);
+        let ix = Instruction::new_with_bincode(
+            program_id,
+            &0,
+            vec![
+                AccountMeta::new(signers[0].pubkey(), true),
+                AccountMeta::new(signers[1].pubkey(), true),
+            ],
+        );
+        let message = Message::new(&[ix], Some(&signers[0].pubkey()));
+
+        let mut tx = Transaction::new_unsigned(message.clone());
+        assert_eq!(tx.try_sign(&signers, Hash::default()), Ok(()));
+        assert!(tx.is_signed());
+        assert_eq!(tx.signatures[0], signers[0].sign_message(&[]));
+        assert_eq!(tx.signatures[1], signers[1].sign_message(&[]));
+
+        // Signing is deterministic
+        let mut other_tx = Transaction::new_unsigned(message.clone());
+        other_tx.sign(&signers, Hash::default());
+        assert_eq!(tx, other_tx);
+
+        // Closure signers observe the message being signed
+        let message_data = tx.message_data();
+        let first = ProgrammableSigner::new_with_fn(signers[0].pubkey(), |message| {
+            Signature::from([message.len() as u8; 64])
+        });
+        let mut tx = Transaction::new_unsigned(message.clone());
+        assert_eq!(tx.try_sign(&[&first, &signers[1]], Hash::default()), Ok(()));
+        assert_eq!(
+            tx.signatures[0],
+            Signature::from([message_data.len() as u8; 64])
+        );
+
+        // Signer failures propagate through `try_sign`
+        let failing = ProgrammableSigner::new_failing(signers[1].pubkey(), || {
+            SignerError::UserCancel("rejected".to_string())
+        });
+        let mut tx = Transaction::new_unsigned(message);
+        assert_eq!(
+            tx.try_sign(&[&signers[0], &failing], Hash::default()),
+            Err(SignerError::UserCancel("rejected".to_string()))
+        );
+        assert!(!tx.is_signed());
+    }
+
     #[test]
     fn test_try_sign_dyn_keypairs() {
         let program_id = Address::default();